@@ -36,6 +36,10 @@ pub enum DbType {
     Foxlake,
     #[strum(serialize = "tidb")]
     Tidb,
+    #[strum(serialize = "parquet")]
+    Parquet,
+    #[strum(serialize = "csv")]
+    Csv,
 }
 
 #[derive(Display, EnumString, IntoStaticStr, Debug, Clone, Hash, PartialEq, Eq)]
@@ -65,6 +69,8 @@ pub enum SinkType {
     #[default]
     #[strum(serialize = "dummy")]
     Dummy,
+    #[strum(serialize = "discard")]
+    Discard,
     #[strum(serialize = "write")]
     Write,
     #[strum(serialize = "struct")]
@@ -77,6 +83,8 @@ pub enum SinkType {
     Push,
     #[strum(serialize = "merge")]
     Merge,
+    #[strum(serialize = "dump")]
+    Dump,
 }
 
 #[derive(EnumString, IntoStaticStr, Clone, Display)]
@@ -99,6 +107,58 @@ pub enum ParallelType {
     Foxlake,
 }
 
+// Order of dml rows inside a batch before sinking, merge parallelizers always sink deletes first
+#[derive(Clone, Debug, Display, EnumString, IntoStaticStr, PartialEq, Eq, Default)]
+pub enum DmlOrder {
+    #[default]
+    #[strum(serialize = "origin")]
+    Origin,
+    #[strum(serialize = "delete_first")]
+    DeleteFirst,
+}
+
+// Cols to partition rows of tables without primary/unique keys by, in rdb_partition parallelizer
+#[derive(Clone, Debug, Display, EnumString, IntoStaticStr, PartialEq, Eq, Default)]
+pub enum NoKeyPartition {
+    #[default]
+    #[strum(serialize = "first_col")]
+    FirstCol,
+    #[strum(serialize = "all_cols")]
+    AllCols,
+}
+
+// How binary col values are written to string based targets, see SqlUtil::binary_to_encoded_str
+#[derive(Clone, Debug, Display, EnumString, IntoStaticStr, PartialEq, Eq, Default)]
+pub enum BinaryEncoding {
+    // valid utf8 as is, otherwise hex, the two can not be told apart
+    #[default]
+    #[strum(serialize = "utf8")]
+    Utf8,
+    // lowercase hex of all values, decoded by unhex()
+    #[strum(serialize = "hex")]
+    Hex,
+    // standard base64 with padding of all values, decoded by from_base64()
+    #[strum(serialize = "base64")]
+    Base64,
+}
+
+// Handling of col values a sinker can not write as is, see ColValueCapability
+#[derive(Clone, Debug, Display, EnumString, IntoStaticStr, PartialEq, Eq, Default)]
+pub enum UnsupportedColValuePolicy {
+    // the sinker's own conversion, e.g. binaries are sent to clickhouse as hex strings
+    #[default]
+    #[strum(serialize = "convert")]
+    Convert,
+    #[strum(serialize = "error")]
+    Error,
+    #[strum(serialize = "null")]
+    Null,
+    #[strum(serialize = "base64_string")]
+    Base64String,
+    #[strum(serialize = "skip_column")]
+    SkipColumn,
+}
+
 #[derive(EnumString, IntoStaticStr, Clone, Display)]
 pub enum PipelineType {
     #[strum(serialize = "basic")]
@@ -107,6 +167,15 @@ pub enum PipelineType {
     HttpServer,
 }
 
+#[derive(Clone, Debug, EnumString, IntoStaticStr, PartialEq, Default)]
+pub enum KafkaProducerType {
+    #[default]
+    #[strum(serialize = "kafka")]
+    Kafka,
+    #[strum(serialize = "rdkafka")]
+    Rdkafka,
+}
+
 #[derive(Clone, Debug, EnumString, IntoStaticStr, PartialEq, Default)]
 pub enum ConflictPolicyEnum {
     #[strum(serialize = "ignore")]
@@ -1,9 +1,16 @@
 use anyhow::bail;
+use regex::Regex;
 
 use crate::{error::Error, utils::sql_util::SqlUtil};
 
 use super::config_enums::DbType;
 
+// a raw regex token is enclosed by `r#` and `#`, eg: `r#.*#` indicates the regex `.*`
+pub const REGEX_ESCAPE_PAIR: (&str, &str) = ("r#", "#");
+
+// (schema, tb)
+pub type SchemaTb = (String, String);
+
 #[derive(Debug, Clone)]
 pub enum TokenEscapePair {
     Char((char, char)),
@@ -93,6 +100,83 @@ impl ConfigTokenParser {
         Ok(tokens)
     }
 
+    /// Parses router mappings like: src_db_1.src_tb_1:dst_db_1.dst_tb_1,src_db_2.src_tb_2:dst_db_2.dst_tb_2,
+    /// every pair must have exactly one source and one target, both in schema.tb form,
+    /// tokens are returned as they are in config, escapes are NOT removed.
+    pub fn parse_router_pairs(
+        config_str: &str,
+        db_type: &DbType,
+    ) -> anyhow::Result<Vec<(SchemaTb, SchemaTb)>> {
+        let mut results = Vec::new();
+        if config_str.is_empty() {
+            return Ok(results);
+        }
+
+        let escape_pairs = SqlUtil::get_escape_pairs(db_type);
+        let token_escape_pairs = TokenEscapePair::from_char_pairs(escape_pairs.clone());
+        let delimiters = [',', '.', ':'];
+        let chars: Vec<char> = config_str.chars().collect();
+
+        // tokens of the current pair, each with the delimiter following it
+        let mut pair: Vec<(String, Option<char>)> = Vec::new();
+        let mut start_index = 0;
+        loop {
+            let (token, next_index) =
+                Self::read_token(&chars, start_index, &delimiters, &token_escape_pairs);
+            let delimiter = chars.get(next_index).cloned();
+            pair.push((token.trim().to_string(), delimiter));
+
+            if matches!(delimiter, None | Some(',')) {
+                let pair_str: String = pair
+                    .iter()
+                    .map(|(token, delimiter)| match delimiter {
+                        Some(d) if *d != ',' => format!("{}{}", token, d),
+                        _ => token.clone(),
+                    })
+                    .collect();
+                let arity: Vec<Option<char>> = pair.iter().map(|(_, d)| *d).collect();
+                if arity[..arity.len() - 1] != [Some('.'), Some(':'), Some('.')] {
+                    bail! {Error::ConfigError(format!(
+                        "config error near: {}, router mapping should be in the form of src_db.src_tb:dst_db.dst_tb",
+                        pair_str
+                    ))}
+                }
+                for (token, _) in pair.iter() {
+                    if !SqlUtil::is_valid_token(token, db_type, &escape_pairs) {
+                        bail! {Error::ConfigError(format!(
+                            "config error near: {}, try enclose database/table/column with escapes if there are special characters other than letters and numbers",
+                            token
+                        ))}
+                    }
+                }
+
+                let tokens: Vec<String> = pair.drain(..).map(|(token, _)| token).collect();
+                results.push((
+                    (tokens[0].clone(), tokens[1].clone()),
+                    (tokens[2].clone(), tokens[3].clone()),
+                ));
+            }
+
+            if next_index >= chars.len() {
+                break;
+            }
+            start_index = next_index + 1;
+        }
+        Ok(results)
+    }
+
+    /// Compiles a `r#...#` token into a regex, returns None for literal tokens and invalid regexes.
+    pub fn compile_regex_token(token: &str) -> Option<Regex> {
+        let (left, right) = REGEX_ESCAPE_PAIR;
+        if token.len() < left.len() + right.len()
+            || !token.starts_with(left)
+            || !token.ends_with(right)
+        {
+            return None;
+        }
+        Regex::new(&token[left.len()..token.len() - right.len()]).ok()
+    }
+
     pub fn parse(
         config: &str,
         delimiters: &[char],
@@ -246,6 +330,57 @@ mod tests {
         assert_eq!(tokens[23], "`r#.?#`");
     }
 
+    #[test]
+    fn test_compile_regex_token() {
+        let re = ConfigTokenParser::compile_regex_token("r#^tmp_.*#").unwrap();
+        assert!(re.is_match("tmp_123"));
+        assert!(!re.is_match("archive"));
+
+        assert!(ConfigTokenParser::compile_regex_token("tmp_*").is_none());
+        assert!(ConfigTokenParser::compile_regex_token("`r#tmp#`").is_none());
+        assert!(ConfigTokenParser::compile_regex_token("r#").is_none());
+        assert!(ConfigTokenParser::compile_regex_token("r#(#").is_none());
+    }
+
+    #[test]
+    fn test_parse_router_pairs() {
+        let config = r#"db_1.tb_1:`db.2`.`tb:2`,db_3.tb_3:db_4.tb_4"#;
+        let pairs = ConfigTokenParser::parse_router_pairs(config, &DbType::Mysql).unwrap();
+        assert_eq!(
+            pairs,
+            vec![
+                (
+                    ("db_1".to_string(), "tb_1".to_string()),
+                    ("`db.2`".to_string(), "`tb:2`".to_string())
+                ),
+                (
+                    ("db_3".to_string(), "tb_3".to_string()),
+                    ("db_4".to_string(), "tb_4".to_string())
+                ),
+            ]
+        );
+        assert!(ConfigTokenParser::parse_router_pairs("", &DbType::Mysql)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_parse_router_pairs_missing_target() {
+        let config = "db_1.tb_1:db_2.tb_2,db_3.tb_3:db_4";
+        let err = ConfigTokenParser::parse_router_pairs(config, &DbType::Mysql).unwrap_err();
+        assert!(err.to_string().contains("db_3.tb_3:db_4"));
+
+        let config = "db_1.tb_1,db_2.tb_2";
+        assert!(ConfigTokenParser::parse_router_pairs(config, &DbType::Mysql).is_err());
+    }
+
+    #[test]
+    fn test_parse_router_pairs_three_part_target() {
+        let config = "db_1.tb_1:db_2.tb_2.col_2";
+        let err = ConfigTokenParser::parse_router_pairs(config, &DbType::Mysql).unwrap_err();
+        assert!(err.to_string().contains("db_1.tb_1:db_2.tb_2.col_2"));
+    }
+
     #[test]
     fn test_parse_mysql_router_config_tokens() {
         let config = r#"db_1.tb_1:`db.2`.`tb.2`,`db"3`.tb_3:db_4.`tb"4`"#;
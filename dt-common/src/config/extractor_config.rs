@@ -39,6 +39,7 @@ pub enum ExtractorConfig {
         parallel_size: usize,
         parallel_type: RdbParallelType,
         batch_size: usize,
+        chunk_size: usize,
         partition_cols: String,
     },
 
@@ -58,6 +59,8 @@ pub enum ExtractorConfig {
         end_time_utc: String,
         keepalive_idle_secs: u64,
         keepalive_interval_secs: u64,
+        binlog_local_path: String,
+        split_add_indexes: bool,
     },
 
     MysqlCheck {
@@ -77,6 +80,7 @@ pub enum ExtractorConfig {
         parallel_size: usize,
         parallel_type: RdbParallelType,
         batch_size: usize,
+        chunk_size: usize,
         partition_cols: String,
     },
 
@@ -179,6 +183,9 @@ pub enum ExtractorConfig {
     RedisReshard {
         url: String,
         connection_auth: ConnectionAuthConfig,
+        resume_file: String,
+        migrate_timeout_ms: u64,
+        migrate_batch_size: usize,
     },
 
     Kafka {
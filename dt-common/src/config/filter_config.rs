@@ -11,4 +11,6 @@ pub struct FilterConfig {
     pub do_dcls: String,
     pub ignore_cmds: String,
     pub where_conditions: String,
+    // also evaluate where_conditions against mysql/pg cdc rows
+    pub where_conditions_in_cdc: bool,
 }
@@ -7,6 +7,10 @@ pub enum JsonTemplateType {
     Standard,
     /// CloudCanal 格式，包含 action、before、data、db、schema、table 等字段
     CloudCanal,
+    /// Debezium 格式，{schema, payload: {before, after, source, op, ts_ms}} 信封
+    Debezium,
+    /// Canal 格式，包含 data、database、table、type、old、sqlType、mysqlType 等字段
+    Canal,
 }
 
 impl FromStr for JsonTemplateType {
@@ -16,7 +20,9 @@ impl FromStr for JsonTemplateType {
         match s.to_lowercase().as_str() {
             "standard" => Ok(JsonTemplateType::Standard),
             "cloudcanal" => Ok(JsonTemplateType::CloudCanal),
-            _ => Err(format!("不支持的 JSON 模板类型: {}", s)),
+            "debezium" => Ok(JsonTemplateType::Debezium),
+            "canal" => Ok(JsonTemplateType::Canal),
+            _ => Err(format!("Invalid json template: {}", s)),
         }
     }
 }
@@ -32,6 +38,28 @@ impl ToString for JsonTemplateType {
         match self {
             JsonTemplateType::Standard => "standard".to_string(),
             JsonTemplateType::CloudCanal => "cloudcanal".to_string(),
+            JsonTemplateType::Debezium => "debezium".to_string(),
+            JsonTemplateType::Canal => "canal".to_string(),
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        for s in ["standard", "cloudcanal", "debezium", "canal"] {
+            assert_eq!(JsonTemplateType::from_str(s).unwrap().to_string(), s);
+        }
+        assert_eq!(
+            JsonTemplateType::from_str("Debezium").unwrap(),
+            JsonTemplateType::Debezium
+        );
+        assert_eq!(
+            JsonTemplateType::from_str("maxwell").unwrap_err(),
+            "Invalid json template: maxwell"
+        );
+    }
+}
@@ -1,29 +1,45 @@
 use std::str::FromStr;
 use super::json_template_type::JsonTemplateType;
 
+const JSON_TEMPLATE_PREFIX: &str = "json_template:";
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum MessageFormat {
     Avro,
     Json,
+    Protobuf,
     /// JSON 模板格式，支持不同的模板类型
-    JsonTemplate(JsonTemplateType),
+    JsonTemplate {
+        template_type: JsonTemplateType,
+        /// 是否由简化格式（如 `cloudcanal`）解析而来，to_string 时保留原写法
+        shorthand: bool,
+    },
 }
 
 impl FromStr for MessageFormat {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_lowercase().as_str() {
+        let lower = s.to_lowercase();
+        // 支持 json_template:template_type 格式
+        if let Some(template_type) = lower.strip_prefix(JSON_TEMPLATE_PREFIX) {
+            let template_type = JsonTemplateType::from_str(template_type)
+                .map_err(|_| format!("Invalid json template in message format: {}", s))?;
+            return Ok(MessageFormat::JsonTemplate {
+                template_type,
+                shorthand: false,
+            });
+        }
+
+        match lower.as_str() {
             "avro" => Ok(MessageFormat::Avro),
             "json" => Ok(MessageFormat::Json),
-            // 支持 json_template:template_type 格式
-            s if s.starts_with("json_template:") => {
-                let template_type = s.strip_prefix("json_template:").unwrap();
-                let json_template_type = JsonTemplateType::from_str(template_type)?;
-                Ok(MessageFormat::JsonTemplate(json_template_type))
-            }
-            // 支持简化格式，直接使用模板类型名称
-            "cloudcanal" => Ok(MessageFormat::JsonTemplate(JsonTemplateType::CloudCanal)),
+            "protobuf" => Ok(MessageFormat::Protobuf),
+            // 支持简化格式，直接使用模板类型名称，standard 须写作 json 或 json_template:standard
+            "cloudcanal" | "debezium" | "canal" => Ok(MessageFormat::JsonTemplate {
+                template_type: JsonTemplateType::from_str(&lower)?,
+                shorthand: true,
+            }),
             _ => Err(format!("Invalid message format: {}", s)),
         }
     }
@@ -40,9 +56,70 @@ impl ToString for MessageFormat {
         match self {
             MessageFormat::Avro => "avro".to_string(),
             MessageFormat::Json => "json".to_string(),
-            MessageFormat::JsonTemplate(template_type) => {
-                format!("json_template:{}", template_type.to_string())
+            MessageFormat::Protobuf => "protobuf".to_string(),
+            MessageFormat::JsonTemplate {
+                template_type,
+                shorthand: true,
+            } => template_type.to_string(),
+            MessageFormat::JsonTemplate { template_type, .. } => {
+                format!("{}{}", JSON_TEMPLATE_PREFIX, template_type.to_string())
             }
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        for s in [
+            "avro",
+            "json",
+            "protobuf",
+            "cloudcanal",
+            "debezium",
+            "canal",
+            "json_template:standard",
+            "json_template:cloudcanal",
+            "json_template:debezium",
+            "json_template:canal",
+        ] {
+            let message_format = MessageFormat::from_str(s).unwrap();
+            assert_eq!(message_format.to_string(), s);
+            assert_eq!(
+                MessageFormat::from_str(&message_format.to_string()).unwrap(),
+                message_format
+            );
+        }
+
+        assert_eq!(
+            MessageFormat::from_str("CloudCanal").unwrap().to_string(),
+            "cloudcanal"
+        );
+        assert_eq!(
+            MessageFormat::from_str("cloudcanal").unwrap(),
+            MessageFormat::JsonTemplate {
+                template_type: JsonTemplateType::CloudCanal,
+                shorthand: true,
+            }
+        );
+    }
+
+    #[test]
+    fn test_invalid_message_format() {
+        assert_eq!(
+            MessageFormat::from_str("json_template:unknown").unwrap_err(),
+            "Invalid json template in message format: json_template:unknown"
+        );
+        assert_eq!(
+            MessageFormat::from_str("standard").unwrap_err(),
+            "Invalid message format: standard"
+        );
+        assert_eq!(
+            MessageFormat::from_str("XML").unwrap_err(),
+            "Invalid message format: XML"
+        );
+    }
+}
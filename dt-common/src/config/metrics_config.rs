@@ -9,4 +9,7 @@ pub struct MetricsConfig {
     pub workers: u64,
     // come from task config such like: k1=v1,k2=v2
     pub metrics_labels: HashMap<String, String>,
+    // tables labeled in per table metrics, such like: db_1.tb_1,db_2.*,
+    // others are summed up as schema="_other",table="_other", empty means all tables
+    pub table_allowlist: Vec<String>,
 }
@@ -21,7 +21,9 @@ pub mod runtime_config;
 pub mod s3_config;
 pub mod sinker_config;
 pub mod ssl_config;
+pub mod status_server_config;
 pub mod task_config;
+pub mod topic_strategy;
 
 #[cfg(feature = "metrics")]
 pub mod metrics_config;
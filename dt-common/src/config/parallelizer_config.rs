@@ -1,12 +1,18 @@
 use strum::{Display, EnumString, IntoStaticStr};
 
-use super::config_enums::ParallelType;
+use super::config_enums::{DmlOrder, NoKeyPartition, ParallelType};
 
 #[derive(Clone)]
 pub enum ParallelizerConfig {
     Basic {
         parallel_type: ParallelType,
         parallel_size: usize,
+        dml_order: DmlOrder,
+        no_key_partition: NoKeyPartition,
+        // json:[{"db":"db_1","tb":"tb_1","partition_cols":["c1","c2"]}]
+        no_key_partition_cols: String,
+        // 0 means no limit
+        max_drain_bytes: u64,
     },
     Snapshot {
         parallel_size: usize,
@@ -30,6 +36,41 @@ impl ParallelizerConfig {
         }
     }
 
+    pub fn dml_order(&self) -> DmlOrder {
+        match self {
+            Self::Basic { dml_order, .. } => dml_order.clone(),
+            Self::Snapshot { .. } => DmlOrder::Origin,
+        }
+    }
+
+    pub fn no_key_partition(&self) -> NoKeyPartition {
+        match self {
+            Self::Basic {
+                no_key_partition, ..
+            } => no_key_partition.clone(),
+            Self::Snapshot { .. } => NoKeyPartition::FirstCol,
+        }
+    }
+
+    pub fn no_key_partition_cols(&self) -> &str {
+        match self {
+            Self::Basic {
+                no_key_partition_cols,
+                ..
+            } => no_key_partition_cols,
+            Self::Snapshot { .. } => "",
+        }
+    }
+
+    pub fn max_drain_bytes(&self) -> u64 {
+        match self {
+            Self::Basic {
+                max_drain_bytes, ..
+            } => *max_drain_bytes,
+            Self::Snapshot { .. } => 0,
+        }
+    }
+
     pub fn chunk_partitioner_rebalance(&self) -> Option<&ChunkPartitionerRebalanceConfig> {
         match self {
             Self::Basic { .. } => None,
@@ -2,4 +2,6 @@
 pub struct ProcessorConfig {
     pub lua_code_file: String,
     pub lua_code: String,
+    // json:[{"db":"test_db","tb":"tb_1","col":"email","transform":"sha256"}]
+    pub col_transforms: String,
 }
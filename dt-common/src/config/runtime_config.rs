@@ -4,4 +4,9 @@ pub struct RuntimeConfig {
     pub log_dir: String,
     pub log4rs_file: String,
     pub check_result_stdout_only: bool,
+    // Max times a cdc task is rebuilt from its last checkpoint after a recoverable
+    // fatal error within task_restart_window_secs, 0 disables the supervision.
+    pub max_task_restarts: u32,
+    pub task_restart_window_secs: u64,
+    pub task_restart_interval_secs: u64,
 }
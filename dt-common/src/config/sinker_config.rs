@@ -1,11 +1,12 @@
 use super::{
-    config_enums::{ConflictPolicyEnum, DbType},
+    config_enums::{BinaryEncoding, ConflictPolicyEnum, DbType, KafkaProducerType},
     json_template_type::JsonTemplateType,
     message_format::MessageFormat,
     s3_config::S3Config,
+    topic_strategy::TopicStrategy,
 };
 use crate::config::{
-    config_enums::{RdbTransactionIsolation, SinkType},
+    config_enums::{RdbTransactionIsolation, SinkType, UnsupportedColValuePolicy},
     connection_auth_config::ConnectionAuthConfig,
     limiter_config::RateLimiterConfig,
 };
@@ -14,6 +15,11 @@ use crate::config::{
 pub enum SinkerConfig {
     Dummy,
 
+    Discard {
+        // sleep before discarding each batch, to simulate a slow target
+        batch_delay_ms: u64,
+    },
+
     Mysql {
         url: String,
         connection_auth: ConnectionAuthConfig,
@@ -23,6 +29,8 @@ pub enum SinkerConfig {
         // Specifies the transaction isolation level used for writes. The database default is used if not specified.
         // If ReadCommitted or ReadUncommitted is set, the target database must have BINLOG_FORMAT set to at least MIXED (ROW is recommended). Otherwise, write operations will fail.
         transaction_isolation: RdbTransactionIsolation,
+        // inserts update the existing rows on key conflicts instead of failing
+        upsert: bool,
     },
 
     Pg {
@@ -31,6 +39,10 @@ pub enum SinkerConfig {
         batch_size: usize,
         replace: bool,
         disable_foreign_key_checks: bool,
+        // inserts update the existing rows on key conflicts instead of failing
+        upsert: bool,
+        // write batched inserts with COPY ... FROM STDIN instead of INSERT
+        use_copy: bool,
     },
 
     Mongo {
@@ -58,8 +70,25 @@ pub enum SinkerConfig {
         ack_timeout_secs: u64,
         required_acks: String,
         with_field_defs: bool,
+        // send decimals, timestamps and dates of avro messages as logical types
+        avro_logical_types: bool,
         message_format: MessageFormat,
         json_template: JsonTemplateType,
+        topic_strategy: TopicStrategy,
+        key_cols: String,
+        batch_payload: bool,
+        diff_only: bool,
+        timezone: String,
+        // 0 means no heartbeat messages
+        heartbeat_interval_secs: u64,
+        heartbeat_topic: String,
+        producer_type: KafkaProducerType,
+        // max unresolved deliveries of the rdkafka producer, 0 means no limit
+        max_in_flight: usize,
+        with_headers: bool,
+        // exactly-once with kafka transactions, rdkafka producer only
+        transactional: bool,
+        transactional_id: String,
     },
 
     Redis {
@@ -77,12 +106,38 @@ pub enum SinkerConfig {
         statistic_log_dir: String,
     },
 
+    RedisDump {
+        dump_dir: String,
+        // 0 means the dump file never rotates
+        file_max_bytes: usize,
+        method: String,
+        version: f32,
+    },
+
     StarRocks {
         url: String,
         connection_auth: ConnectionAuthConfig,
         batch_size: usize,
         stream_load_url: String,
         hard_delete: bool,
+        dry_run: bool,
+        label_prefix: String,
+        compression: String,
+        // 0 means no limit, shared by all sub sinkers
+        stream_load_max_bps: u32,
+        stream_load_max_rps: u32,
+        // 0 means no timeout
+        stream_load_connect_timeout_secs: u64,
+        stream_load_timeout_secs: u64,
+        // for https stream_load_url with a self-signed certificate
+        stream_load_ssl_ca_path: String,
+        stream_load_accept_invalid_certs: bool,
+        binary_encoding: BinaryEncoding,
+        // batch_size is tuned within [min_batch_size, max_batch_size] by the load time,
+        // max_batch_size = 0 disables the tuning
+        min_batch_size: usize,
+        max_batch_size: usize,
+        target_load_time_ms: u64,
     },
 
     DorisStruct {
@@ -96,6 +151,15 @@ pub enum SinkerConfig {
         connection_auth: ConnectionAuthConfig,
         batch_size: usize,
         stream_load_url: String,
+        dry_run: bool,
+        label_prefix: String,
+        stream_load_connect_timeout_secs: u64,
+        stream_load_timeout_secs: u64,
+        stream_load_ssl_ca_path: String,
+        stream_load_accept_invalid_certs: bool,
+        binary_encoding: BinaryEncoding,
+        // sync_mode or async_mode, empty to load without group commit
+        group_commit: String,
     },
 
     StarRocksStruct {
@@ -109,6 +173,24 @@ pub enum SinkerConfig {
         batch_size: usize,
     },
 
+    Parquet {
+        batch_size: usize,
+        root_dir: String,
+        file_max_rows: usize,
+    },
+
+    Csv {
+        root_dir: String,
+        delimiter: String,
+        quote: String,
+        quote_all: bool,
+        header: bool,
+        file_max_rows: usize,
+        file_max_bytes: usize,
+        hard_delete: bool,
+        null_value: String,
+    },
+
     ClickhouseStruct {
         url: String,
         conflict_policy: ConflictPolicyEnum,
@@ -156,6 +238,10 @@ pub struct BasicSinkerConfig {
     pub batch_size: usize,
     pub max_connections: u32,
     pub rate_limiter: RateLimiterConfig,
+    pub unsupported_col_value_policy: UnsupportedColValuePolicy,
+    // db.tb.col patterns, cols not included or excluded are removed from sink payloads
+    pub include_cols: String,
+    pub exclude_cols: String,
 }
 
 impl Default for BasicSinkerConfig {
@@ -168,6 +254,9 @@ impl Default for BasicSinkerConfig {
             batch_size: 0,
             max_connections: 10,
             rate_limiter: RateLimiterConfig::default(),
+            unsupported_col_value_policy: UnsupportedColValuePolicy::default(),
+            include_cols: String::new(),
+            exclude_cols: String::new(),
         }
     }
 }
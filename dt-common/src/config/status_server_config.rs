@@ -0,0 +1,7 @@
+// serves the task status as JSON, configured in [metrics] but available without the metrics feature
+#[derive(Clone, Debug, Default)]
+pub struct StatusServerConfig {
+    pub http_host: String,
+    // 0 disables the status server
+    pub http_port: u64,
+}
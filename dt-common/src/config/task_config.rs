@@ -22,8 +22,9 @@ use crate::{
 use super::{
     checker_config::CheckerConfig,
     config_enums::{
-        CheckMode, ConflictPolicyEnum, DbType, ExtractType, MetaCenterType, ParallelType,
-        PipelineType, SinkType, TaskKind, TaskType,
+        CheckMode, ConflictPolicyEnum, DbType, DmlOrder, ExtractType, MetaCenterType,
+        NoKeyPartition, ParallelType, PipelineType, SinkType, TaskKind, TaskType,
+        UnsupportedColValuePolicy,
     },
     data_marker_config::DataMarkerConfig,
     extractor_config::{BasicExtractorConfig, ExtractorConfig},
@@ -41,6 +42,7 @@ use super::{
     runtime_config::RuntimeConfig,
     s3_config::S3Config,
     sinker_config::{BasicSinkerConfig, SinkerConfig},
+    status_server_config::StatusServerConfig,
 };
 
 #[derive(Clone)]
@@ -60,6 +62,7 @@ pub struct TaskConfig {
     pub meta_center: Option<MetaCenterConfig>,
     pub data_marker: Option<DataMarkerConfig>,
     pub processor: Option<ProcessorConfig>,
+    pub status_server: StatusServerConfig,
     #[cfg(feature = "metrics")]
     pub metrics: MetricsConfig,
 }
@@ -67,6 +70,11 @@ pub struct TaskConfig {
 pub const DEFAULT_DB_BATCH_SIZE: usize = 100;
 pub const DEFAULT_MAX_CONNECTIONS: u32 = 10;
 pub const DEFAULT_CHECK_LOG_FILE_SIZE: &str = "100mb";
+// large keys may take seconds to be serialized and restored by MIGRATE
+pub const DEFAULT_MIGRATE_TIMEOUT_MS: u64 = 60000;
+pub const DEFAULT_MIGRATE_BATCH_SIZE: usize = 100;
+// stream loads faster than half of it grow the batch, slower ones shrink it
+pub const DEFAULT_TARGET_LOAD_TIME_MS: u64 = 3000;
 
 // sections
 const GLOBAL: &str = "global";
@@ -82,6 +90,7 @@ const DATA_MARKER: &str = "data_marker";
 const PROCESSOR: &str = "processor";
 const CHECKER: &str = "checker";
 const META_CENTER: &str = "metacenter";
+const METRICS: &str = "metrics";
 // keys
 const CHECK_LOG_DIR: &str = "check_log_dir";
 const CHECK_LOG_FILE_SIZE: &str = "check_log_file_size";
@@ -97,6 +106,7 @@ const URL: &str = "url";
 const USERNAME: &str = "username";
 const PASSWORD: &str = "password";
 const BATCH_SIZE: &str = "batch_size";
+const CHUNK_SIZE: &str = "chunk_size";
 const MAX_CONNECTIONS: &str = "max_connections";
 const PARTITION_COLS: &str = "partition_cols";
 const HEARTBEAT_INTERVAL_SECS: &str = "heartbeat_interval_secs";
@@ -260,13 +270,18 @@ impl TaskConfig {
             data_marker: Self::load_data_marker_config(&loader)?,
             processor: Self::load_processor_config(&loader)?,
             meta_center: Self::load_meta_center_config(&loader)?,
+            status_server: Self::load_status_server_config(&loader),
             #[cfg(feature = "metrics")]
             metrics: Self::load_metrics_config(&loader)?,
         })
     }
 
     pub fn sink_target(&self) -> Option<BasicSinkerConfig> {
-        (!matches!(self.sinker_basic.sink_type, SinkType::Dummy)).then(|| self.sinker_basic.clone())
+        (!matches!(
+            self.sinker_basic.sink_type,
+            SinkType::Dummy | SinkType::Discard | SinkType::Dump
+        ))
+        .then(|| self.sinker_basic.clone())
     }
 
     pub fn checker_target(&self) -> Option<BasicSinkerConfig> {
@@ -382,8 +397,8 @@ impl TaskConfig {
         let check = if !checker_enabled {
             match (kind, sink_type) {
                 (TaskKind::Struct, SinkType::Struct)
-                | (TaskKind::Snapshot, SinkType::Write)
-                | (TaskKind::Cdc, SinkType::Write) => None,
+                | (TaskKind::Snapshot, SinkType::Write | SinkType::Discard)
+                | (TaskKind::Cdc, SinkType::Write | SinkType::Discard) => None,
                 _ => return None,
             }
         } else {
@@ -452,6 +467,13 @@ impl TaskConfig {
             BATCH_SIZE,
             pipeline.capacity_limiter.buffer_size / Self::load_snapshot_parallel_size(loader),
         );
+        // rows per chunk when splitting a table by its partition col, defaults to batch_size
+        let chunk_size: usize = loader.get_with_default(EXTRACTOR, CHUNK_SIZE, batch_size);
+        if chunk_size == 0 {
+            bail!(Error::ConfigError(
+                "`extractor.chunk_size` must be greater than 0".into()
+            ));
+        }
         let max_connections =
             loader.get_with_default(EXTRACTOR, MAX_CONNECTIONS, DEFAULT_MAX_CONNECTIONS);
 
@@ -489,42 +511,54 @@ impl TaskConfig {
                         RdbParallelType::Table,
                     ),
                     batch_size,
+                    chunk_size,
                     partition_cols: loader.get_optional(EXTRACTOR, PARTITION_COLS),
                 },
 
-                ExtractType::Cdc => ExtractorConfig::MysqlCdc {
-                    url,
-                    connection_auth,
-                    binlog_filename: loader.get_optional(EXTRACTOR, "binlog_filename"),
-                    binlog_position: loader.get_optional(EXTRACTOR, "binlog_position"),
-                    server_id: loader.get_required(EXTRACTOR, "server_id"),
-                    gtid_enabled: loader.get_optional(EXTRACTOR, "gtid_enabled"),
-                    gtid_set: loader.get_optional(EXTRACTOR, "gtid_set"),
-                    binlog_heartbeat_interval_secs: loader.get_with_default(
-                        EXTRACTOR,
-                        "binlog_heartbeat_interval_secs",
-                        10,
-                    ),
-                    binlog_timeout_secs: loader.get_with_default(
-                        EXTRACTOR,
-                        "binlog_timeout_secs",
-                        60,
-                    ),
-                    heartbeat_interval_secs,
-                    heartbeat_tb,
-                    keepalive_idle_secs: loader.get_with_default(
-                        EXTRACTOR,
-                        "keepalive_idle_secs",
-                        60,
-                    ),
-                    keepalive_interval_secs: loader.get_with_default(
-                        EXTRACTOR,
-                        "keepalive_interval_secs",
-                        10,
-                    ),
-                    start_time_utc: loader.get_optional(EXTRACTOR, "start_time_utc"),
-                    end_time_utc: loader.get_optional(EXTRACTOR, "end_time_utc"),
-                },
+                ExtractType::Cdc => {
+                    let binlog_local_path: String =
+                        loader.get_optional(EXTRACTOR, "binlog_local_path");
+                    ExtractorConfig::MysqlCdc {
+                        url,
+                        connection_auth,
+                        binlog_filename: loader.get_optional(EXTRACTOR, "binlog_filename"),
+                        binlog_position: loader.get_optional(EXTRACTOR, "binlog_position"),
+                        // server_id is only used when reading binlog from the server
+                        server_id: if binlog_local_path.is_empty() {
+                            loader.get_required(EXTRACTOR, "server_id")
+                        } else {
+                            loader.get_optional(EXTRACTOR, "server_id")
+                        },
+                        gtid_enabled: loader.get_optional(EXTRACTOR, "gtid_enabled"),
+                        gtid_set: loader.get_optional(EXTRACTOR, "gtid_set"),
+                        binlog_heartbeat_interval_secs: loader.get_with_default(
+                            EXTRACTOR,
+                            "binlog_heartbeat_interval_secs",
+                            10,
+                        ),
+                        binlog_timeout_secs: loader.get_with_default(
+                            EXTRACTOR,
+                            "binlog_timeout_secs",
+                            60,
+                        ),
+                        heartbeat_interval_secs,
+                        heartbeat_tb,
+                        keepalive_idle_secs: loader.get_with_default(
+                            EXTRACTOR,
+                            "keepalive_idle_secs",
+                            60,
+                        ),
+                        keepalive_interval_secs: loader.get_with_default(
+                            EXTRACTOR,
+                            "keepalive_interval_secs",
+                            10,
+                        ),
+                        start_time_utc: loader.get_optional(EXTRACTOR, "start_time_utc"),
+                        end_time_utc: loader.get_optional(EXTRACTOR, "end_time_utc"),
+                        binlog_local_path,
+                        split_add_indexes: loader.get_optional(EXTRACTOR, "split_add_indexes"),
+                    }
+                }
 
                 ExtractType::CheckLog => ExtractorConfig::MysqlCheck {
                     url,
@@ -589,6 +623,7 @@ impl TaskConfig {
                         RdbParallelType::Table,
                     ),
                     batch_size,
+                    chunk_size,
                     partition_cols: loader.get_optional(EXTRACTOR, PARTITION_COLS),
                 },
 
@@ -739,6 +774,13 @@ impl TaskConfig {
                 ExtractType::Reshard => ExtractorConfig::RedisReshard {
                     url,
                     connection_auth,
+                    resume_file: loader.get_optional(EXTRACTOR, "resume_file"),
+                    migrate_timeout_ms: loader.get_with_default(
+                        EXTRACTOR,
+                        "migrate_timeout_ms",
+                        DEFAULT_MIGRATE_TIMEOUT_MS,
+                    ),
+                    migrate_batch_size: Self::load_migrate_batch_size(loader)?,
                 },
 
                 _ => bail! { not_supported_err },
@@ -802,6 +844,24 @@ impl TaskConfig {
             return Ok((BasicSinkerConfig::default(), SinkerConfig::Dummy));
         }
 
+        if let SinkType::Discard = sink_type {
+            let batch_size: usize = loader.get_with_default(SINKER, BATCH_SIZE, 200);
+            if batch_size == 0 {
+                bail!(Error::ConfigError(
+                    "config [sinker].batch_size must be greater than 0".into()
+                ));
+            }
+            let basic = BasicSinkerConfig {
+                sink_type,
+                batch_size,
+                ..Default::default()
+            };
+            let sinker = SinkerConfig::Discard {
+                batch_delay_ms: loader.get_optional(SINKER, "batch_delay_ms"),
+            };
+            return Ok((basic, sinker));
+        }
+
         let db_type: DbType = loader.get_required(SINKER, DB_TYPE);
         let url: String = loader.get_optional(SINKER, URL);
         let batch_size: usize = loader.get_with_default(SINKER, BATCH_SIZE, 200);
@@ -826,7 +886,35 @@ impl TaskConfig {
             batch_size,
             max_connections,
             rate_limiter,
+            unsupported_col_value_policy: loader.get_with_default(
+                SINKER,
+                "unsupported_col_value_policy",
+                UnsupportedColValuePolicy::Convert,
+            ),
+            include_cols: loader.get_optional(SINKER, "include_cols"),
+            exclude_cols: loader.get_optional(SINKER, "exclude_cols"),
         };
+        // other sinkers write every col value type as is
+        if basic.unsupported_col_value_policy != UnsupportedColValuePolicy::Convert
+            && !matches!(
+                db_type,
+                DbType::Kafka | DbType::StarRocks | DbType::Doris | DbType::ClickHouse
+            )
+        {
+            bail!(Error::ConfigError(format!(
+                "config [sinker].unsupported_col_value_policy is not supported for sinker db type: {}",
+                db_type
+            )));
+        }
+        // only sinkers serializing col maps themselves apply the col projection
+        if !(basic.include_cols.is_empty() && basic.exclude_cols.is_empty())
+            && !matches!(db_type, DbType::Kafka | DbType::StarRocks | DbType::Doris)
+        {
+            bail!(Error::ConfigError(format!(
+                "config [sinker].include_cols / exclude_cols are not supported for sinker db type: {}",
+                db_type
+            )));
+        }
 
         let conflict_policy: ConflictPolicyEnum =
             loader.get_with_default(SINKER, "conflict_policy", ConflictPolicyEnum::Interrupt);
@@ -847,6 +935,7 @@ impl TaskConfig {
                         true,
                     ),
                     transaction_isolation: loader.get_optional(SINKER, "transaction_isolation"),
+                    upsert: loader.get_optional(SINKER, "upsert"),
                 },
 
                 SinkType::Struct => SinkerConfig::MysqlStruct {
@@ -873,6 +962,8 @@ impl TaskConfig {
                         DISABLE_FOREIGN_KEY_CHECKS,
                         true,
                     ),
+                    upsert: loader.get_optional(SINKER, "upsert"),
+                    use_copy: loader.get_optional(SINKER, "use_copy"),
                 },
 
                 SinkType::Struct => SinkerConfig::PgStruct {
@@ -909,8 +1000,25 @@ impl TaskConfig {
                 ack_timeout_secs: loader.get_with_default(SINKER, "ack_timeout_secs", 5),
                 required_acks: loader.get_with_default(SINKER, "required_acks", "one".to_string()),
                 with_field_defs: loader.get_with_default(SINKER, "with_field_defs", true),
+                avro_logical_types: loader.get_optional(SINKER, "avro_logical_types"),
                 message_format: loader.get_optional(SINKER, "message_format"),
                 json_template: loader.get_optional(SINKER, "json_template"),
+                topic_strategy: loader.get_optional(SINKER, "topic_strategy"),
+                key_cols: loader.get_optional(SINKER, "key_cols"),
+                batch_payload: loader.get_optional(SINKER, "batch_payload"),
+                diff_only: loader.get_optional(SINKER, "diff_only"),
+                timezone: loader.get_optional(SINKER, "timezone"),
+                heartbeat_interval_secs: loader.get_optional(SINKER, "heartbeat_interval_secs"),
+                heartbeat_topic: loader.get_with_default(
+                    SINKER,
+                    "heartbeat_topic",
+                    "ape_dts_heartbeat".to_string(),
+                ),
+                producer_type: loader.get_optional(SINKER, "producer_type"),
+                max_in_flight: loader.get_optional(SINKER, "max_in_flight"),
+                with_headers: loader.get_optional(SINKER, "with_headers"),
+                transactional: loader.get_optional(SINKER, "transactional"),
+                transactional_id: loader.get_optional(SINKER, "transactional_id"),
             },
 
             DbType::Redis => match sink_type {
@@ -929,17 +1037,48 @@ impl TaskConfig {
                     statistic_log_dir: loader.get_optional(SINKER, "statistic_log_dir"),
                 },
 
+                SinkType::Dump => SinkerConfig::RedisDump {
+                    dump_dir: loader.get_required(SINKER, "dump_dir"),
+                    file_max_bytes: loader.get_optional(SINKER, "file_max_bytes"),
+                    method: loader.get_optional(SINKER, "method"),
+                    version: loader.get_with_default(SINKER, "version", 7.0),
+                },
+
                 _ => bail! { not_supported_err },
             },
 
             DbType::StarRocks => match sink_type {
-                SinkType::Write => SinkerConfig::StarRocks {
-                    url,
-                    connection_auth,
-                    batch_size,
-                    stream_load_url: loader.get_optional(SINKER, "stream_load_url"),
-                    hard_delete: loader.get_optional(SINKER, "hard_delete"),
-                },
+                SinkType::Write => {
+                    let (min_batch_size, max_batch_size) = Self::load_batch_size_range(loader)?;
+                    SinkerConfig::StarRocks {
+                        url,
+                        connection_auth,
+                        batch_size,
+                        stream_load_url: loader.get_optional(SINKER, "stream_load_url"),
+                        hard_delete: loader.get_optional(SINKER, "hard_delete"),
+                        dry_run: loader.get_optional(SINKER, "dry_run"),
+                        label_prefix: loader.get_optional(SINKER, "label_prefix"),
+                        compression: loader.get_optional(SINKER, "compression"),
+                        stream_load_max_bps: loader.get_optional(SINKER, "stream_load_max_bps"),
+                        stream_load_max_rps: loader.get_optional(SINKER, "stream_load_max_rps"),
+                        stream_load_connect_timeout_secs: loader
+                            .get_optional(SINKER, "stream_load_connect_timeout_secs"),
+                        stream_load_timeout_secs: loader
+                            .get_optional(SINKER, "stream_load_timeout_secs"),
+                        stream_load_ssl_ca_path: loader
+                            .get_optional(SINKER, "stream_load_ssl_ca_path"),
+                        stream_load_accept_invalid_certs: loader
+                            .get_optional(SINKER, "stream_load_accept_invalid_certs"),
+                        binary_encoding: loader.get_optional(SINKER, "binary_encoding"),
+                        min_batch_size,
+                        max_batch_size,
+                        target_load_time_ms: loader.get_with_default(
+                            SINKER,
+                            "target_load_time_ms",
+                            DEFAULT_TARGET_LOAD_TIME_MS,
+                        ),
+                    }
+                }
 
                 SinkType::Struct => SinkerConfig::StarRocksStruct {
                     url,
@@ -956,6 +1095,17 @@ impl TaskConfig {
                     connection_auth,
                     batch_size,
                     stream_load_url: loader.get_optional(SINKER, "stream_load_url"),
+                    dry_run: loader.get_optional(SINKER, "dry_run"),
+                    label_prefix: loader.get_optional(SINKER, "label_prefix"),
+                    stream_load_connect_timeout_secs: loader
+                        .get_optional(SINKER, "stream_load_connect_timeout_secs"),
+                    stream_load_timeout_secs: loader
+                        .get_optional(SINKER, "stream_load_timeout_secs"),
+                    stream_load_ssl_ca_path: loader.get_optional(SINKER, "stream_load_ssl_ca_path"),
+                    stream_load_accept_invalid_certs: loader
+                        .get_optional(SINKER, "stream_load_accept_invalid_certs"),
+                    binary_encoding: loader.get_optional(SINKER, "binary_encoding"),
+                    group_commit: loader.get_optional(SINKER, "group_commit"),
                 },
 
                 SinkType::Struct => SinkerConfig::DorisStruct {
@@ -983,6 +1133,32 @@ impl TaskConfig {
                 _ => bail! { not_supported_err },
             },
 
+            DbType::Parquet => match sink_type {
+                SinkType::Write => SinkerConfig::Parquet {
+                    batch_size,
+                    root_dir: loader.get_required(SINKER, "root_dir"),
+                    file_max_rows: loader.get_with_default(SINKER, "file_max_rows", 1_000_000),
+                },
+
+                _ => bail! { not_supported_err },
+            },
+
+            DbType::Csv => match sink_type {
+                SinkType::Write => SinkerConfig::Csv {
+                    root_dir: loader.get_required(SINKER, "root_dir"),
+                    delimiter: loader.get_with_default(SINKER, "delimiter", ",".to_string()),
+                    quote: loader.get_with_default(SINKER, "quote", "\"".to_string()),
+                    quote_all: loader.get_optional(SINKER, "quote_all"),
+                    header: loader.get_with_default(SINKER, "header", true),
+                    file_max_rows: loader.get_with_default(SINKER, "file_max_rows", 1_000_000),
+                    file_max_bytes: loader.get_optional(SINKER, "file_max_bytes"),
+                    hard_delete: loader.get_optional(SINKER, "hard_delete"),
+                    null_value: loader.get_optional(SINKER, "null_value"),
+                },
+
+                _ => bail! { not_supported_err },
+            },
+
             DbType::Foxlake => {
                 let s3_config = S3Config {
                     bucket: loader.get_optional(SINKER, "s3_bucket"),
@@ -1038,9 +1214,29 @@ impl TaskConfig {
         let parallel_type =
             loader.get_with_default(PARALLELIZER, "parallel_type", ParallelType::Serial);
         if !matches!(parallel_type, ParallelType::Snapshot) {
+            let dml_order = loader.get_with_default(PARALLELIZER, "dml_order", DmlOrder::Origin);
+            if dml_order == DmlOrder::DeleteFirst
+                && !matches!(
+                    parallel_type,
+                    ParallelType::Serial | ParallelType::Table | ParallelType::RdbPartition
+                )
+            {
+                bail!(Error::ConfigError(format!(
+                    "config [parallelizer].dml_order={} is not supported by parallel_type={}",
+                    dml_order, parallel_type
+                )));
+            }
             return Ok(ParallelizerConfig::Basic {
                 parallel_size,
                 parallel_type,
+                dml_order,
+                no_key_partition: loader.get_with_default(
+                    PARALLELIZER,
+                    "no_key_partition",
+                    NoKeyPartition::FirstCol,
+                ),
+                no_key_partition_cols: loader.get_optional(PARALLELIZER, "no_key_partition_cols"),
+                max_drain_bytes: loader.get_optional(PARALLELIZER, "max_drain_bytes"),
             });
         }
 
@@ -1237,6 +1433,9 @@ impl TaskConfig {
             batch_size: checker.batch_size,
             max_connections: checker.max_connections,
             rate_limiter: RateLimiterConfig::default(),
+            unsupported_col_value_policy: UnsupportedColValuePolicy::default(),
+            include_cols: String::new(),
+            exclude_cols: String::new(),
         }
     }
 
@@ -1254,6 +1453,17 @@ impl TaskConfig {
                 "check_result_stdout_only",
                 false,
             ),
+            max_task_restarts: loader.get_optional(RUNTIME, "max_task_restarts"),
+            task_restart_window_secs: loader.get_with_default(
+                RUNTIME,
+                "task_restart_window_secs",
+                600,
+            ),
+            task_restart_interval_secs: loader.get_with_default(
+                RUNTIME,
+                "task_restart_interval_secs",
+                5,
+            ),
         })
     }
 
@@ -1265,6 +1475,32 @@ impl TaskConfig {
         }
     }
 
+    fn load_batch_size_range(loader: &IniLoader) -> anyhow::Result<(usize, usize)> {
+        let max_batch_size: usize = loader.get_optional(SINKER, "max_batch_size");
+        if max_batch_size == 0 {
+            return Ok((0, 0));
+        }
+        let min_batch_size = loader.get_with_default(SINKER, "min_batch_size", 1);
+        if min_batch_size == 0 || min_batch_size > max_batch_size {
+            bail!(Error::ConfigError(format!(
+                "config [sinker].min_batch_size must be in [1, max_batch_size], min_batch_size: {}, max_batch_size: {}",
+                min_batch_size, max_batch_size
+            )));
+        }
+        Ok((min_batch_size, max_batch_size))
+    }
+
+    fn load_migrate_batch_size(loader: &IniLoader) -> anyhow::Result<usize> {
+        let migrate_batch_size =
+            loader.get_with_default(EXTRACTOR, "migrate_batch_size", DEFAULT_MIGRATE_BATCH_SIZE);
+        if migrate_batch_size == 0 {
+            bail!(Error::ConfigError(
+                "config [extractor].migrate_batch_size must be greater than 0".into()
+            ));
+        }
+        Ok(migrate_batch_size)
+    }
+
     fn load_filter_config(loader: &IniLoader) -> anyhow::Result<FilterConfig> {
         Ok(FilterConfig {
             do_schemas: loader.get_optional(FILTER, "do_dbs"),
@@ -1278,6 +1514,7 @@ impl TaskConfig {
             do_structures: loader.get_with_default(FILTER, "do_structures", ASTRISK.to_string()),
             ignore_cmds: loader.get_optional(FILTER, "ignore_cmds"),
             where_conditions: loader.get_optional(FILTER, "where_conditions"),
+            where_conditions_in_cdc: loader.get_optional(FILTER, "where_conditions_in_cdc"),
         })
     }
 
@@ -1287,6 +1524,7 @@ impl TaskConfig {
             tb_map: loader.get_optional(ROUTER, "tb_map"),
             col_map: loader.get_optional(ROUTER, "col_map"),
             topic_map: loader.get_optional(ROUTER, "topic_map"),
+            doc_projection: loader.get_optional(ROUTER, "doc_projection"),
         })
     }
 
@@ -1384,6 +1622,7 @@ impl TaskConfig {
         Ok(Some(ProcessorConfig {
             lua_code_file,
             lua_code,
+            col_transforms: loader.get_optional(PROCESSOR, "col_transforms"),
         }))
     }
 
@@ -1424,6 +1663,18 @@ impl TaskConfig {
         Ok(Some(config))
     }
 
+    fn load_status_server_config(loader: &IniLoader) -> StatusServerConfig {
+        StatusServerConfig {
+            // opt-in and only reachable locally unless configured otherwise
+            http_host: loader.get_with_default(
+                METRICS,
+                "status_http_host",
+                "127.0.0.1".to_string(),
+            ),
+            http_port: loader.get_optional(METRICS, "status_http_port"),
+        }
+    }
+
     #[cfg(feature = "metrics")]
     fn load_metrics_config(loader: &IniLoader) -> anyhow::Result<MetricsConfig> {
         let metrics_section = "metrics";
@@ -1441,6 +1692,12 @@ impl TaskConfig {
             http_port: loader.get_with_default(metrics_section, "http_port", 9090),
             workers: loader.get_with_default(metrics_section, "workers", 2),
             metrics_labels,
+            table_allowlist: loader
+                .get_optional::<String>(metrics_section, "table_allowlist")
+                .split(',')
+                .map(|table| table.trim().to_string())
+                .filter(|table| !table.is_empty())
+                .collect(),
         })
     }
 }
@@ -1457,7 +1714,7 @@ mod tests {
         ChunkPartitionerRebalanceCost, ChunkPartitionerRebalanceStrategy,
     };
 
-    use super::{CheckMode, ParallelType, TaskConfig, TaskKind, TaskType};
+    use super::{CheckMode, ExtractorConfig, ParallelType, TaskConfig, TaskKind, TaskType};
 
     static NEXT_CONFIG_ID: AtomicU64 = AtomicU64::new(0);
 
@@ -1815,6 +2072,41 @@ rebalance_split_skew_ratio=1.5
         assert_eq!(rebalance.split_skew_ratio, 1.5);
     }
 
+    #[test]
+    fn extractor_chunk_size_defaults_to_batch_size() {
+        let load = |extra: &str| {
+            let config_path = write_temp_task_config(&format!(
+                r#"[extractor]
+db_type=mysql
+extract_type=snapshot
+url=mysql://127.0.0.1:3306
+batch_size=100
+{extra}
+
+[sinker]
+db_type=mysql
+sink_type=write
+url=mysql://127.0.0.1:3307
+"#
+            ));
+            let config = TaskConfig::new(config_path.to_str().unwrap());
+            fs::remove_file(config_path).unwrap();
+            config
+        };
+
+        let ExtractorConfig::MysqlSnapshot { chunk_size, .. } = load("").unwrap().extractor else {
+            panic!("expect mysql snapshot extractor config");
+        };
+        assert_eq!(chunk_size, 100);
+        let ExtractorConfig::MysqlSnapshot { chunk_size, .. } =
+            load("chunk_size=1000").unwrap().extractor
+        else {
+            panic!("expect mysql snapshot extractor config");
+        };
+        assert_eq!(chunk_size, 1000);
+        assert!(load("chunk_size=0").is_err());
+    }
+
     #[test]
     fn parallelizer_basic_config_has_no_rebalance_config() {
         let config_path = write_temp_task_config(
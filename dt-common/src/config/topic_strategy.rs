@@ -0,0 +1,79 @@
+use std::str::FromStr;
+
+/// How the kafka sinker derives the topic of a message
+#[derive(Clone, Debug, PartialEq)]
+pub enum TopicStrategy {
+    /// resolve topics by [router].topic_map
+    TopicMap,
+    /// one topic per table, named {schema}.{tb}
+    PerTable,
+    /// one topic per schema, named {schema}
+    PerSchema,
+    /// all messages go to the same topic
+    Single(String),
+}
+
+impl FromStr for TopicStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "topic_map" => Ok(TopicStrategy::TopicMap),
+            "per_table" => Ok(TopicStrategy::PerTable),
+            "per_schema" => Ok(TopicStrategy::PerSchema),
+            lower if lower.starts_with("single:") => {
+                // keep the case of the topic name
+                let name = s["single:".len()..].trim();
+                if name.is_empty() {
+                    return Err(format!("topic name is missing in topic strategy: {}", s));
+                }
+                Ok(TopicStrategy::Single(name.to_string()))
+            }
+            _ => Err(format!("Invalid topic strategy: {}", s)),
+        }
+    }
+}
+
+impl Default for TopicStrategy {
+    fn default() -> Self {
+        TopicStrategy::TopicMap
+    }
+}
+
+impl ToString for TopicStrategy {
+    fn to_string(&self) -> String {
+        match self {
+            TopicStrategy::TopicMap => "topic_map".to_string(),
+            TopicStrategy::PerTable => "per_table".to_string(),
+            TopicStrategy::PerSchema => "per_schema".to_string(),
+            TopicStrategy::Single(name) => format!("single:{}", name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_topic_strategy() {
+        assert_eq!(
+            TopicStrategy::from_str("topic_map").unwrap(),
+            TopicStrategy::TopicMap
+        );
+        assert_eq!(
+            TopicStrategy::from_str("PER_TABLE").unwrap(),
+            TopicStrategy::PerTable
+        );
+        assert_eq!(
+            TopicStrategy::from_str("per_schema").unwrap(),
+            TopicStrategy::PerSchema
+        );
+        assert_eq!(
+            TopicStrategy::from_str("single:All_Changes").unwrap(),
+            TopicStrategy::Single("All_Changes".into())
+        );
+        assert!(TopicStrategy::from_str("single:").is_err());
+        assert!(TopicStrategy::from_str("per_db").is_err());
+    }
+}
@@ -35,6 +35,9 @@ pub enum Error {
     #[error("parse redis result error: {0}")]
     RedisResultError(String),
 
+    #[error("redis cluster sink error, failed nodes: {nodes:?}, {message}")]
+    RedisClusterSinkError { nodes: Vec<String>, message: String },
+
     #[error("metadata error: {0}")]
     MetadataError(String),
 
@@ -67,4 +70,87 @@ pub enum Error {
 
     #[error("mlua error: {0}")]
     MluaError(#[from] mlua::Error),
+
+    #[error("precheck error, check: {check}, target: {target}, {message}")]
+    PrecheckError {
+        check: String,
+        // source or sink
+        target: String,
+        message: String,
+    },
+}
+
+impl Error {
+    /// Whether a task failed with this error may succeed once rebuilt from its last
+    /// checkpoint, e.g. a broken source connection, unlike config or auth problems.
+    pub fn is_recoverable(&self) -> bool {
+        match self {
+            Error::HeartbeatError(_)
+            | Error::BinlogError(_)
+            | Error::IoError(_)
+            | Error::HttpError(_)
+            | Error::RedisResultError(_)
+            | Error::RedisClusterSinkError { .. } => true,
+            Error::ExtractorError(msg) => Self::is_recoverable_extractor_error(msg),
+            Error::SqlxError(err) => Self::is_recoverable_sqlx_error(err),
+            Error::MongodbError(err) => Self::is_recoverable_mongodb_error(err),
+            _ => false,
+        }
+    }
+
+    /// Classifies a task failure by the first known error found in its chain.
+    pub fn is_recoverable_task_error(err: &anyhow::Error) -> bool {
+        for cause in err.chain() {
+            if let Some(err) = cause.downcast_ref::<Error>() {
+                return err.is_recoverable();
+            }
+            if cause.is::<mysql_binlog_connector_rust::binlog_error::BinlogError>()
+                || cause.is::<std::io::Error>()
+            {
+                return true;
+            }
+            if let Some(err) = cause.downcast_ref::<sqlx::Error>() {
+                return Self::is_recoverable_sqlx_error(err);
+            }
+            if let Some(err) = cause.downcast_ref::<mongodb::error::Error>() {
+                return Self::is_recoverable_mongodb_error(err);
+            }
+        }
+        false
+    }
+
+    /// ExtractorError only carries a message, a lost connection or a timeout is transient,
+    /// while invalid data, missing binlog files or protocol mismatches are not.
+    fn is_recoverable_extractor_error(msg: &str) -> bool {
+        let msg = msg.to_lowercase();
+        [
+            "connection",
+            "timed out",
+            "timeout",
+            "broken pipe",
+            "reset by peer",
+        ]
+        .iter()
+        .any(|cause| msg.contains(cause))
+    }
+
+    fn is_recoverable_sqlx_error(err: &sqlx::Error) -> bool {
+        matches!(
+            err,
+            sqlx::Error::Io(_)
+                | sqlx::Error::Tls(_)
+                | sqlx::Error::Protocol(_)
+                | sqlx::Error::PoolTimedOut
+                | sqlx::Error::PoolClosed
+                | sqlx::Error::WorkerCrashed
+        )
+    }
+
+    fn is_recoverable_mongodb_error(err: &mongodb::error::Error) -> bool {
+        !matches!(
+            *err.kind,
+            mongodb::error::ErrorKind::Authentication { .. }
+                | mongodb::error::ErrorKind::InvalidArgument { .. }
+        )
+    }
 }
@@ -2,3 +2,4 @@ pub mod base_limiter;
 pub mod buffer_limiter;
 pub mod capacity_limiter;
 pub mod rate_limiter;
+pub mod sink_rate_limiter;
@@ -0,0 +1,92 @@
+use std::{cmp, time::Duration};
+
+use tokio::time::Instant;
+
+use crate::limiter::{
+    base_limiter::{Limiter, UnitType},
+    rate_limiter::RateLimiter,
+};
+
+/// Limits the bytes and requests sent by sinkers per second, shared by all sub sinkers of a task.
+/// Unlike BufferLimiter, which limits the items taken from the pipeline, it limits the requests
+/// actually sent to the target, e.g. the compressed bodies of stream loads.
+pub struct SinkRateLimiter {
+    max_bps: u32,
+    bytes_limiter: Option<RateLimiter>,
+    requests_limiter: Option<RateLimiter>,
+}
+
+impl SinkRateLimiter {
+    pub fn new(max_bps: u32, max_rps: u32) -> Option<Self> {
+        if max_bps == 0 && max_rps == 0 {
+            return None;
+        }
+
+        Some(Self {
+            max_bps,
+            bytes_limiter: (max_bps > 0).then(|| RateLimiter::new(max_bps, UnitType::Bytes)),
+            requests_limiter: (max_rps > 0).then(|| RateLimiter::new(max_rps, UnitType::Records)),
+        })
+    }
+
+    /// Waits until a request of `bytes` can be sent, returns the time waited.
+    pub async fn acquire(&self, bytes: usize) -> anyhow::Result<Duration> {
+        let start_time = Instant::now();
+        if let Some(limiter) = &self.requests_limiter {
+            limiter.acquire(1).await?;
+        }
+
+        if let Some(limiter) = &self.bytes_limiter {
+            // the rate limiter rejects acquiring more than max_bps at once,
+            // so a larger request waits for several seconds of quota
+            let mut remaining = bytes;
+            while remaining > 0 {
+                let n = cmp::min(remaining, self.max_bps as usize);
+                limiter.acquire(n as u32).await?;
+                remaining -= n;
+            }
+        }
+        Ok(start_time.elapsed())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_limit() {
+        assert!(SinkRateLimiter::new(0, 0).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_max_bps_delays_next_request() {
+        let limiter = SinkRateLimiter::new(10_000, 0).unwrap();
+
+        // the first request drains the bucket without waiting
+        let wait = limiter.acquire(10_000).await.unwrap();
+        assert!(wait < Duration::from_millis(100), "waited {:?}", wait);
+
+        // the second one waits for the refill of 5_000 bytes, about 500ms
+        let wait = limiter.acquire(5_000).await.unwrap();
+        assert!(wait >= Duration::from_millis(400), "waited {:?}", wait);
+    }
+
+    #[tokio::test]
+    async fn test_request_larger_than_max_bps() {
+        let limiter = SinkRateLimiter::new(10_000, 0).unwrap();
+        // not rejected, waits for 1.5s of quota after the initial bucket
+        let wait = limiter.acquire(25_000).await.unwrap();
+        assert!(wait >= Duration::from_millis(1400), "waited {:?}", wait);
+    }
+
+    #[tokio::test]
+    async fn test_max_rps_delays_next_request() {
+        let limiter = SinkRateLimiter::new(0, 2).unwrap();
+        limiter.acquire(100).await.unwrap();
+        limiter.acquire(100).await.unwrap();
+        // 2 requests per second, the third waits about 500ms
+        let wait = limiter.acquire(100).await.unwrap();
+        assert!(wait >= Duration::from_millis(400), "waited {:?}", wait);
+    }
+}
@@ -189,9 +189,11 @@ impl MysqlColValueConvertor {
                 }
             }
 
-            // tinyblob, mediumblob, longblob, blob, tinytext, mediumtext, longtext, text
+            // tinyblob, mediumblob, longblob, blob, tinytext, mediumtext, longtext, text, geometry
             ColumnValue::Blob(v) => {
-                if col_type.is_string() {
+                if *col_type == MysqlColType::Geometry {
+                    ColValue::Geometry(v)
+                } else if col_type.is_string() {
                     // tinytext, mediumtext, longtext, text
                     ColValue::RawString(v)
                 } else {
@@ -332,6 +334,7 @@ impl MysqlColValueConvertor {
                 | MysqlColType::MediumBlob
                 | MysqlColType::Blob
                 | MysqlColType::LongBlob => ColValue::Blob(hex::decode(value_str)?),
+                MysqlColType::Geometry => ColValue::Geometry(hex::decode(value_str)?),
                 MysqlColType::Unknown => {
                     bail! {Error::Unexpected(format!(
                         "unsupported column type: {:?}",
@@ -503,6 +506,11 @@ impl MysqlColValueConvertor {
                 // |  1 | 212765.7                  |
                 Ok(ColValue::Json2(value.to_string()))
             }
+            MysqlColType::Geometry => {
+                // sqlx does not decode geometry into Vec<u8>
+                let value: Vec<u8> = row.get_unchecked(col);
+                Ok(ColValue::Geometry(value))
+            }
             MysqlColType::Unknown => Ok(ColValue::None),
         }
     }
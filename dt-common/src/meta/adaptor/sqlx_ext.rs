@@ -233,6 +233,8 @@ impl<'q> SqlxMysqlExt<'q> for Query<'q, MySql, MySqlArguments> {
                 ColValue::String(v) => self.bind(v),
                 ColValue::RawString(v) => self.bind(v),
                 ColValue::Blob(v) => self.bind(v),
+                // mysql accepts geometry values in its internal format
+                ColValue::Geometry(v) => self.bind(v),
                 ColValue::Bit(v) => self.bind(v),
                 ColValue::Set(v) => self.bind(v),
                 ColValue::Set2(v) => self.bind(v),
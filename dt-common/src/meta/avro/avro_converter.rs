@@ -1,9 +1,14 @@
-use std::{collections::HashMap, str::FromStr};
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+};
 
-use apache_avro::{from_avro_datum, to_avro_datum, types::Value, Schema};
+use apache_avro::{from_avro_datum, to_avro_datum, types::Value, Decimal, Schema};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeDelta};
 
 use crate::{
     config::config_enums::DbType,
+    log_warn,
     meta::{
         col_value::ColValue,
         ddl_meta::{ddl_data::DdlData, ddl_type::DdlType},
@@ -13,15 +18,23 @@ use crate::{
         row_data::RowData,
         row_type::RowType,
     },
+    utils::geometry_util::GeometryUtil,
 };
 
-use super::avro_converter_schema::{AvroConverterSchema, AvroFieldDef};
+use super::avro_converter_schema::{AvroConverterSchema, AvroFieldDef, DECIMAL_BRANCH_START};
 
 #[derive(Clone)]
 pub struct AvroConverter {
-    schema: Schema,
+    // keyed by the (precision, scale) of the decimal branches
+    schemas: HashMap<Vec<(usize, usize)>, Schema>,
+    head_schema: Schema,
     pub with_field_defs: bool,
+    // send decimals, timestamps and dates as avro logical types, which extends the value union,
+    // otherwise they are sent as strings in the original format
+    pub logical_types: bool,
     pub meta_manager: Option<RdbMetaManager>,
+    // schema.tb.col of logical type values sent as strings, to warn once per col
+    fallback_cols: HashSet<String>,
 }
 
 const BEFORE: &str = "before";
@@ -35,13 +48,21 @@ const QUERY: &str = "query";
 const SCHEMA: &str = "schema";
 const TB: &str = "tb";
 const FIELDS: &str = "fields";
+const TIMESTAMP_VALUE: &str = "value";
 
 impl AvroConverter {
-    pub fn new(meta_manager: Option<RdbMetaManager>, with_field_defs: bool) -> Self {
+    pub fn new(
+        meta_manager: Option<RdbMetaManager>,
+        with_field_defs: bool,
+        logical_types: bool,
+    ) -> Self {
         AvroConverter {
-            schema: AvroConverterSchema::get_avro_schema(),
+            schemas: HashMap::from([(Vec::new(), AvroConverterSchema::get_avro_schema())]),
+            head_schema: AvroConverterSchema::get_avro_head_schema(),
             meta_manager,
             with_field_defs,
+            logical_types,
+            fallback_cols: HashSet::new(),
         }
     }
 
@@ -55,25 +76,48 @@ impl AvroConverter {
 
     pub async fn row_data_to_avro_key(&mut self, row_data: &RowData) -> anyhow::Result<String> {
         if let Some(tb_meta) = self.get_tb_meta(row_data).await? {
-            let convert = |col_values: &HashMap<String, ColValue>| {
-                if let Some(col) = tb_meta.order_cols.first() {
-                    if let Some(value) = col_values.get(col) {
-                        return value.to_option_string();
-                    }
-                }
-                None
-            };
-
-            if let Some(key) = match row_data.row_type {
-                RowType::Insert => convert(row_data.require_after()?),
-                RowType::Update | RowType::Delete => convert(row_data.require_before()?),
-            } {
+            if let Some(key) = Self::get_avro_key(tb_meta, row_data)? {
                 return Ok(key);
             }
         }
         Ok(String::new())
     }
 
+    /// Key built from the first order col value of the row.
+    pub fn get_avro_key(tb_meta: &RdbTbMeta, row_data: &RowData) -> anyhow::Result<Option<String>> {
+        let convert = |col_values: &HashMap<String, ColValue>| {
+            if let Some(col) = tb_meta.order_cols.first() {
+                if let Some(value) = col_values.get(col) {
+                    return value.to_option_string();
+                }
+            }
+            None
+        };
+
+        Ok(match row_data.row_type {
+            RowType::Insert => convert(row_data.require_after()?),
+            RowType::Update | RowType::Delete => convert(row_data.require_before()?),
+        })
+    }
+
+    /// Key built from the given col values joined by ",", deletes take values from the before image.
+    pub fn get_avro_key_by_cols(key_cols: &[String], row_data: &RowData) -> anyhow::Result<String> {
+        let col_values = match row_data.row_type {
+            RowType::Delete => row_data.require_before()?,
+            RowType::Insert | RowType::Update => row_data.require_after()?,
+        };
+        let key_values: Vec<String> = key_cols
+            .iter()
+            .map(|col| {
+                col_values
+                    .get(col)
+                    .and_then(|v| v.to_option_string())
+                    .unwrap_or_default()
+            })
+            .collect();
+        Ok(key_values.join(","))
+    }
+
     pub async fn row_data_to_avro_value(&mut self, row_data: &RowData) -> anyhow::Result<Vec<u8>> {
         let mut cols = vec![];
         let mut merge_cols = |col_values: &Option<HashMap<String, ColValue>>| {
@@ -89,8 +133,69 @@ impl AvroConverter {
         merge_cols(&row_data.after);
         cols.sort();
 
+        let tb_meta = match self.meta_manager.as_mut() {
+            Some(meta_manager) => Some(
+                meta_manager
+                    .get_tb_meta(&row_data.schema, &row_data.tb)
+                    .await?,
+            ),
+            None => None,
+        };
+        let logical_types = self.logical_types;
+        let fallback_cols = &mut self.fallback_cols;
+        let mut to_avro = |col: &str, value: &ColValue| {
+            if !logical_types {
+                return Self::col_value_to_avro(value);
+            }
+            if let Some(avro_value) = Self::col_value_to_logical_avro(col, value, tb_meta) {
+                return avro_value;
+            }
+            if matches!(
+                value,
+                ColValue::Decimal(_)
+                    | ColValue::Timestamp(_)
+                    | ColValue::DateTime(_)
+                    | ColValue::Date(_)
+            ) && fallback_cols.insert(format!("{}.{}.{}", row_data.schema, row_data.tb, col))
+            {
+                log_warn!(
+                    "col: {}.{}.{} can not be sent as avro logical type, fall back to string, value: {}",
+                    row_data.schema,
+                    row_data.tb,
+                    col,
+                    value
+                );
+            }
+            Self::col_value_to_avro(value)
+        };
+
+        // decimal cols carrying their own (precision, scale), which are told by the fields
+        let mut decimal_cols = HashMap::new();
+        if let (true, Some(tb_meta)) = (logical_types, tb_meta) {
+            for col in cols.iter() {
+                if let Some(decimal_type) = tb_meta
+                    .col_origin_type_map
+                    .get(col)
+                    .and_then(|origin_type| Self::parse_decimal_type(origin_type))
+                {
+                    decimal_cols.insert(col.clone(), decimal_type);
+                }
+            }
+        }
+        let mut decimal_types: Vec<(usize, usize)> = decimal_cols.values().copied().collect();
+        decimal_types.sort();
+        decimal_types.dedup();
+        let decimal_branches: HashMap<String, usize> = decimal_cols
+            .iter()
+            .map(|(col, decimal_type)| {
+                let i = decimal_types.binary_search(decimal_type).unwrap();
+                (col.clone(), DECIMAL_BRANCH_START + i)
+            })
+            .collect();
+
         // before
-        let (before_avro_values, before_avro_types) = Self::col_values_to_avro(&row_data.before);
+        let (before_avro_values, before_avro_types) =
+            Self::col_values_to_avro(&row_data.before, &decimal_branches, &mut to_avro);
         let before = if let Value::Map(_) = &before_avro_values {
             Value::Union(1, Box::new(before_avro_values))
         } else {
@@ -98,20 +203,24 @@ impl AvroConverter {
         };
 
         // after
-        let (after_avro_values, after_avro_types) = Self::col_values_to_avro(&row_data.after);
+        let (after_avro_values, after_avro_types) =
+            Self::col_values_to_avro(&row_data.after, &decimal_branches, &mut to_avro);
         let after = if let Value::Map(_) = &after_avro_values {
             Value::Union(1, Box::new(after_avro_values))
         } else {
             Value::Union(0, Box::new(Value::Null))
         };
 
-        // fields
-        let fields = if !self.with_field_defs || cols.is_empty() {
+        // fields, decimal cols are always sent so the decimal branches can be rebuilt by readers
+        let field_cols: Vec<&String> = cols
+            .iter()
+            .filter(|col| self.with_field_defs || decimal_cols.contains_key(*col))
+            .collect();
+        let fields = if field_cols.is_empty() {
             Value::Union(0, Box::new(Value::Null))
         } else {
             let mut fields = vec![];
-            let tb_meta = self.get_tb_meta(row_data).await?;
-            for col in cols.iter() {
+            for col in field_cols {
                 let mut column_type = String::new();
                 if let Some(tb_meta) = tb_meta {
                     if let Some(col_origin_type) = tb_meta.col_origin_type_map.get(col) {
@@ -150,7 +259,7 @@ impl AvroConverter {
             (AFTER.into(), after),
             (EXTRA.into(), Value::Union(0, Box::new(Value::Null))),
         ]);
-        Ok(to_avro_datum(&self.schema, value)?)
+        Ok(to_avro_datum(self.get_schema(&decimal_types)?, value)?)
     }
 
     pub async fn ddl_data_to_avro_value(&mut self, ddl_data: DdlData) -> anyhow::Result<Vec<u8>> {
@@ -165,7 +274,10 @@ impl AvroConverter {
         );
         col_values.insert(QUERY.into(), ColValue::String(ddl_data.query));
 
-        let (avro_values, _) = Self::col_values_to_avro(&Some(col_values));
+        let (avro_values, _) =
+            Self::col_values_to_avro(&Some(col_values), &HashMap::new(), &mut |_, value| {
+                Self::col_value_to_avro(value)
+            });
         let extra = Value::Union(1, Box::new(avro_values));
 
         let value = Value::Record(vec![
@@ -177,12 +289,18 @@ impl AvroConverter {
             (AFTER.into(), Value::Union(0, Box::new(Value::Null))),
             (EXTRA.into(), extra),
         ]);
-        Ok(to_avro_datum(&self.schema, value)?)
+        Ok(to_avro_datum(self.get_schema(&[])?, value)?)
     }
 
-    pub fn avro_value_to_dt_data(&self, payload: Vec<u8>) -> anyhow::Result<DtData> {
+    /// Reads messages of both formats, since the logical type branches only extend the value union.
+    pub fn avro_value_to_dt_data(&mut self, payload: Vec<u8>) -> anyhow::Result<DtData> {
+        // the decimal branches of the message are told by its fields
+        let head = from_avro_datum(&self.head_schema, &mut payload.as_slice(), None)?;
+        let fields = Self::avro_to_fields(Self::avro_to_map(head).remove(FIELDS));
+        let decimal_types = Self::decimal_types_of_fields(&fields);
+
         let mut reader = payload.as_slice();
-        let value = from_avro_datum(&self.schema, &mut reader, None)?;
+        let value = from_avro_datum(self.get_schema(&decimal_types)?, &mut reader, None)?;
         let mut avro_map = Self::avro_to_map(value);
 
         let avro_to_string = |value: Option<Value>| {
@@ -205,7 +323,7 @@ impl AvroConverter {
                 }
                 String::new()
             };
-            let extra = self.avro_to_col_values(avro_map.remove(EXTRA));
+            let extra = Self::avro_to_col_values(avro_map.remove(EXTRA), &[]);
             let db_type = get_extra_string(&extra, DB_TYPE);
             let ddl_type = get_extra_string(&extra, DDL_TYPE);
             let query = get_extra_string(&extra, QUERY);
@@ -219,9 +337,8 @@ impl AvroConverter {
                 },
             })
         } else {
-            let _fields = self.avro_to_fields(avro_map.remove(FIELDS));
-            let before = self.avro_to_col_values(avro_map.remove(BEFORE));
-            let after = self.avro_to_col_values(avro_map.remove(AFTER));
+            let before = Self::avro_to_col_values(avro_map.remove(BEFORE), &decimal_types);
+            let after = Self::avro_to_col_values(avro_map.remove(AFTER), &decimal_types);
             Ok(DtData::Dml {
                 row_data: RowData::new(
                     schema,
@@ -235,14 +352,39 @@ impl AvroConverter {
         }
     }
 
-    fn avro_to_fields(&self, value: Option<Value>) -> Vec<AvroFieldDef> {
-        if let Some(v) = value {
-            return apache_avro::from_value(&v).unwrap();
+    fn get_schema(&mut self, decimal_types: &[(usize, usize)]) -> anyhow::Result<&Schema> {
+        if !self.schemas.contains_key(decimal_types) {
+            let schema = AvroConverterSchema::build_avro_schema(decimal_types)?;
+            self.schemas.insert(decimal_types.to_vec(), schema);
         }
-        vec![]
+        Ok(&self.schemas[decimal_types])
+    }
+
+    // sorted distinct (precision, scale) of the decimal fields
+    fn decimal_types_of_fields(fields: &[AvroFieldDef]) -> Vec<(usize, usize)> {
+        let mut decimal_types: Vec<(usize, usize)> = fields
+            .iter()
+            .filter_map(|field| Self::parse_decimal_type(&field.column_type))
+            .collect();
+        decimal_types.sort();
+        decimal_types.dedup();
+        decimal_types
     }
 
-    fn avro_to_col_values(&self, value: Option<Value>) -> Option<HashMap<String, ColValue>> {
+    fn avro_to_fields(value: Option<Value>) -> Vec<AvroFieldDef> {
+        match value {
+            Some(Value::Union(_, v)) => match *v {
+                Value::Array(_) => apache_avro::from_value(&v).unwrap_or_default(),
+                _ => vec![],
+            },
+            _ => vec![],
+        }
+    }
+
+    fn avro_to_col_values(
+        value: Option<Value>,
+        decimal_types: &[(usize, usize)],
+    ) -> Option<HashMap<String, ColValue>> {
         value.as_ref()?;
 
         // Some(Union(1, Map({
@@ -258,7 +400,18 @@ impl AvroConverter {
             if let Value::Map(map_v) = *v {
                 let mut col_values = HashMap::new();
                 for (col, value) in map_v {
-                    col_values.insert(col, Self::avro_to_col_value(value));
+                    let col_value = match value {
+                        Value::Union(i, v) if i as usize >= DECIMAL_BRANCH_START => {
+                            match (*v, decimal_types.get(i as usize - DECIMAL_BRANCH_START)) {
+                                (Value::Decimal(v), Some((_, scale))) => {
+                                    Self::avro_decimal_to_col_value(&v, *scale)
+                                }
+                                _ => ColValue::None,
+                            }
+                        }
+                        _ => Self::avro_to_col_value(value),
+                    };
+                    col_values.insert(col, col_value);
                 }
                 return Some(col_values);
             }
@@ -266,8 +419,11 @@ impl AvroConverter {
         None
     }
 
+    // decimal_branches: union index of the decimal branch of each decimal col
     fn col_values_to_avro(
         col_values: &Option<HashMap<String, ColValue>>,
+        decimal_branches: &HashMap<String, usize>,
+        to_avro: &mut impl FnMut(&str, &ColValue) -> Value,
     ) -> (Value, HashMap<String, String>) {
         let mut avro_types = HashMap::new();
         if col_values.is_none() {
@@ -276,7 +432,7 @@ impl AvroConverter {
 
         let mut avro_values = HashMap::new();
         for (col, value) in col_values.as_ref().unwrap() {
-            let avro_value = Self::col_value_to_avro(value);
+            let mut avro_value = to_avro(col.as_str(), value);
             let (union_position, avro_type) = match avro_value {
                 Value::Null => (0, "Null".to_string()),
                 Value::String(_) => (1, "String".to_string()),
@@ -284,6 +440,18 @@ impl AvroConverter {
                 Value::Double(_) => (3, "Double".to_string()),
                 Value::Bytes(_) => (4, "Bytes".to_string()),
                 Value::Boolean(_) => (5, "Boolean".to_string()),
+                Value::Date(_) => (6, "Date".to_string()),
+                Value::TimestampMillis(_) => {
+                    avro_value = Value::Record(vec![(TIMESTAMP_VALUE.into(), avro_value)]);
+                    (7, "TimestampMillis".to_string())
+                }
+                Value::Decimal(_) => match decimal_branches.get(col) {
+                    Some(i) => (*i as u32, "Decimal".to_string()),
+                    None => {
+                        avro_value = Value::Null;
+                        (0, String::new())
+                    }
+                },
                 // Not supported
                 _ => (0, String::new()),
             };
@@ -316,6 +484,12 @@ impl AvroConverter {
             ColValue::Float(v) => Value::Double(*v as f64),
             ColValue::Double(v) => Value::Double(*v),
             ColValue::Blob(v) | ColValue::Json(v) => Value::Bytes(v.clone()),
+            // WKB without the SRID
+            ColValue::Geometry(v) => Value::Bytes(
+                GeometryUtil::to_wkb(v)
+                    .map(|wkb| wkb.to_vec())
+                    .unwrap_or_else(|_| v.clone()),
+            ),
             ColValue::RawString(v) => ColValue::RawString(v.clone())
                 .to_utf8_string()
                 .map(Value::String)
@@ -340,6 +514,159 @@ impl AvroConverter {
         }
     }
 
+    /// Decimals with a known scale, timestamps and dates as avro logical types,
+    /// None if the value should be sent as is.
+    fn col_value_to_logical_avro(
+        col: &str,
+        value: &ColValue,
+        tb_meta: Option<&RdbTbMeta>,
+    ) -> Option<Value> {
+        match value {
+            ColValue::Decimal(v) => {
+                let origin_type = tb_meta?.col_origin_type_map.get(col)?;
+                let (precision, scale) = Self::parse_decimal_type(origin_type)?;
+                // sign extended to the size of the fixed decimal of the col
+                let size = AvroConverterSchema::decimal_fixed_size(precision);
+                let bytes = Self::decimal_to_unscaled_bytes(v, scale)?;
+                if bytes.len() > size {
+                    return None;
+                }
+                let sign = if bytes[0] & 0x80 != 0 { 0xff } else { 0 };
+                let mut fixed = vec![sign; size - bytes.len()];
+                fixed.extend(bytes);
+                Some(Value::Decimal(Decimal::from(fixed)))
+            }
+
+            ColValue::Timestamp(v) | ColValue::DateTime(v) => {
+                if let Ok(dt) = DateTime::parse_from_str(v, "%Y-%m-%d %H:%M:%S%.f%#z") {
+                    return Some(Value::TimestampMillis(dt.timestamp_millis()));
+                }
+                NaiveDateTime::parse_from_str(v, "%Y-%m-%d %H:%M:%S%.f")
+                    .ok()
+                    .map(|dt| Value::TimestampMillis(dt.and_utc().timestamp_millis()))
+            }
+
+            ColValue::Date(v) => {
+                let date = NaiveDate::parse_from_str(v, "%Y-%m-%d").ok()?;
+                // days since 1970-01-01
+                let days = date.signed_duration_since(NaiveDate::default()).num_days();
+                Some(Value::Date(days as i32))
+            }
+
+            _ => None,
+        }
+    }
+
+    // decimal(10,2), numeric(10,2), decimal(10) unsigned,
+    // None if precision and scale are unknown or invalid
+    fn parse_decimal_type(origin_type: &str) -> Option<(usize, usize)> {
+        let origin_type = origin_type.to_lowercase();
+        let args = origin_type
+            .strip_prefix("decimal(")
+            .or_else(|| origin_type.strip_prefix("numeric("))?;
+        let args = &args[..args.find(')')?];
+        let mut args = args.split(',').map(|v| v.trim().parse::<usize>());
+        let precision = args.next()?.ok()?;
+        let scale = match args.next() {
+            Some(v) => v.ok()?,
+            None => 0,
+        };
+        if precision == 0 || scale > precision {
+            return None;
+        }
+        Some((precision, scale))
+    }
+
+    // big-endian two's complement of the unscaled value,
+    // None if v is not a plain decimal or has more fraction digits than scale
+    fn decimal_to_unscaled_bytes(v: &str, scale: usize) -> Option<Vec<u8>> {
+        let (negative, v) = match v.strip_prefix('-') {
+            Some(v) => (true, v),
+            None => (false, v.strip_prefix('+').unwrap_or(v)),
+        };
+        let (int_part, frac_part) = v.split_once('.').unwrap_or((v, ""));
+        if (int_part.is_empty() && frac_part.is_empty())
+            || frac_part.len() > scale
+            || !int_part
+                .bytes()
+                .chain(frac_part.bytes())
+                .all(|c| c.is_ascii_digit())
+        {
+            return None;
+        }
+
+        // little-endian magnitude
+        let mut bytes: Vec<u8> = Vec::new();
+        let digits = int_part
+            .bytes()
+            .chain(frac_part.bytes())
+            .map(|c| c - b'0')
+            .chain(std::iter::repeat_n(0, scale - frac_part.len()));
+        for digit in digits {
+            let mut carry = digit as u32;
+            for byte in bytes.iter_mut() {
+                let v = *byte as u32 * 10 + carry;
+                *byte = v as u8;
+                carry = v >> 8;
+            }
+            while carry > 0 {
+                bytes.push(carry as u8);
+                carry >>= 8;
+            }
+        }
+        // sign byte
+        bytes.push(0);
+        if negative {
+            Self::negate(&mut bytes);
+        }
+        bytes.reverse();
+        Some(bytes)
+    }
+
+    fn unscaled_bytes_to_decimal(bytes: &[u8], scale: usize) -> String {
+        let negative = bytes.first().is_some_and(|b| b & 0x80 != 0);
+        // little-endian magnitude
+        let mut bytes: Vec<u8> = bytes.iter().rev().copied().collect();
+        if negative {
+            Self::negate(&mut bytes);
+        }
+
+        // decimal digits, least significant first
+        let mut digits = Vec::new();
+        while bytes.iter().any(|b| *b != 0) {
+            let mut remainder = 0u32;
+            for byte in bytes.iter_mut().rev() {
+                let v = (remainder << 8) | *byte as u32;
+                *byte = (v / 10) as u8;
+                remainder = v % 10;
+            }
+            digits.push((b'0' + remainder as u8) as char);
+        }
+        while digits.len() <= scale {
+            digits.push('0');
+        }
+
+        let mut int_part: String = digits.into_iter().rev().collect();
+        let frac_part = int_part.split_off(int_part.len() - scale);
+        let frac_part = frac_part.trim_end_matches('0');
+        let sign = if negative { "-" } else { "" };
+        if frac_part.is_empty() {
+            format!("{}{}", sign, int_part)
+        } else {
+            format!("{}{}.{}", sign, int_part, frac_part)
+        }
+    }
+
+    // two's complement negation of a little-endian integer
+    fn negate(bytes: &mut [u8]) {
+        let mut carry = 1u16;
+        for byte in bytes.iter_mut() {
+            let v = (!*byte) as u16 + carry;
+            *byte = v as u8;
+            carry = v >> 8;
+        }
+    }
+
     fn avro_to_col_value(value: Value) -> ColValue {
         match value {
             Value::Long(v) => ColValue::LongLong(v),
@@ -348,12 +675,31 @@ impl AvroConverter {
             Value::String(v) => ColValue::String(v),
             Value::Boolean(v) => ColValue::Bool(v),
             Value::Null => ColValue::None,
+            Value::TimestampMillis(v) => match DateTime::from_timestamp_millis(v) {
+                Some(dt) => ColValue::Timestamp(dt.format("%Y-%m-%d %H:%M:%S%.3f").to_string()),
+                None => ColValue::None,
+            },
+            Value::Date(v) => {
+                let date = NaiveDate::default() + TimeDelta::days(v as i64);
+                ColValue::Date(date.format("%Y-%m-%d").to_string())
+            }
             Value::Union(_, v) => Self::avro_to_col_value(*v),
+            // the record wrapping timestamp-millis
+            Value::Record(mut fields) if fields.len() == 1 && fields[0].0 == TIMESTAMP_VALUE => {
+                Self::avro_to_col_value(fields.remove(0).1)
+            }
             // NOT supported
             _ => ColValue::None,
         }
     }
 
+    fn avro_decimal_to_col_value(v: &Decimal, scale: usize) -> ColValue {
+        match Vec::<u8>::try_from(v) {
+            Ok(bytes) => ColValue::Decimal(Self::unscaled_bytes_to_decimal(&bytes, scale)),
+            Err(_) => ColValue::None,
+        }
+    }
+
     fn avro_to_map(value: Value) -> HashMap<String, Value> {
         let mut avro_map = HashMap::new();
         if let Value::Record(record) = value {
@@ -411,7 +757,7 @@ mod tests {
         after.insert(BOOLEAN_COL.into(), ColValue::Bool(true));
         after.insert(NULL_COL.into(), ColValue::None);
 
-        let mut avro_converter = AvroConverter::new(None, false);
+        let mut avro_converter = AvroConverter::new(None, false, false);
         let mut row_data = RowData::new(
             schema.into(),
             tb.into(),
@@ -437,7 +783,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_ddl_data_to_avro() {
-        let mut avro_converter = AvroConverter::new(None, false);
+        let mut avro_converter = AvroConverter::new(None, false, false);
 
         let ddl_data = DdlData {
             default_schema: "db1".to_string(),
@@ -464,6 +810,213 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_avro_logical_types_round_trip() {
+        let tb_meta = RdbTbMeta {
+            col_origin_type_map: HashMap::from([
+                ("dec_col".to_string(), "decimal(10,2)".to_string()),
+                ("neg_dec_col".to_string(), "decimal(10,2)".to_string()),
+                ("big_dec_col".to_string(), "decimal(65,30)".to_string()),
+            ]),
+            ..Default::default()
+        };
+        let col_values = HashMap::from([
+            ("dec_col".to_string(), ColValue::Decimal("123.45".into())),
+            ("neg_dec_col".to_string(), ColValue::Decimal("-0.50".into())),
+            (
+                "big_dec_col".to_string(),
+                ColValue::Decimal("12345678901234567890123456789012345.12".into()),
+            ),
+            (
+                "datetime_col".to_string(),
+                ColValue::DateTime("2023-01-02 03:04:05.123456".into()),
+            ),
+            (
+                "timestamp_col".to_string(),
+                ColValue::Timestamp("2023-01-02 11:04:05+08".into()),
+            ),
+            ("date_col".to_string(), ColValue::Date("1969-12-31".into())),
+        ]);
+
+        // decimal(10,2) is the first decimal branch, decimal(65,30) the second
+        let decimal_types = vec![(10, 2), (65, 30)];
+        let decimal_branches = HashMap::from([
+            ("dec_col".to_string(), DECIMAL_BRANCH_START),
+            ("neg_dec_col".to_string(), DECIMAL_BRANCH_START),
+            ("big_dec_col".to_string(), DECIMAL_BRANCH_START + 1),
+        ]);
+        let fields: Vec<AvroFieldDef> = ["big_dec_col", "dec_col", "neg_dec_col"]
+            .iter()
+            .map(|col| AvroFieldDef {
+                name: col.to_string(),
+                column_type: tb_meta.col_origin_type_map[*col].clone(),
+                avro_type: "Decimal".into(),
+            })
+            .collect();
+        assert_eq!(
+            AvroConverter::decimal_types_of_fields(&fields),
+            decimal_types
+        );
+
+        let mut avro_converter = AvroConverter::new(None, false, false);
+        let (after, _) = AvroConverter::col_values_to_avro(
+            &Some(col_values),
+            &decimal_branches,
+            &mut |col, value| {
+                AvroConverter::col_value_to_logical_avro(col, value, Some(&tb_meta)).unwrap()
+            },
+        );
+        let value = Value::Record(vec![
+            (SCHEMA.into(), Value::String("db1".into())),
+            (TB.into(), Value::String("tb1".into())),
+            (OPERATION.into(), Value::String(RowType::Insert.to_string())),
+            (
+                FIELDS.into(),
+                Value::Union(1, Box::new(apache_avro::to_value(fields).unwrap())),
+            ),
+            (BEFORE.into(), Value::Union(0, Box::new(Value::Null))),
+            (AFTER.into(), Value::Union(1, Box::new(after))),
+            (EXTRA.into(), Value::Union(0, Box::new(Value::Null))),
+        ]);
+        let payload =
+            to_avro_datum(avro_converter.get_schema(&decimal_types).unwrap(), value).unwrap();
+        let DtData::Dml { row_data } = avro_converter.avro_value_to_dt_data(payload).unwrap()
+        else {
+            panic!()
+        };
+
+        let after = row_data.after.unwrap();
+        assert_eq!(after["dec_col"], ColValue::Decimal("123.45".into()));
+        assert_eq!(after["neg_dec_col"], ColValue::Decimal("-0.5".into()));
+        assert_eq!(
+            after["big_dec_col"],
+            ColValue::Decimal("12345678901234567890123456789012345.12".into())
+        );
+        assert_eq!(
+            after["datetime_col"],
+            ColValue::Timestamp("2023-01-02 03:04:05.123".into())
+        );
+        assert_eq!(
+            after["timestamp_col"],
+            ColValue::Timestamp("2023-01-02 03:04:05.000".into())
+        );
+        assert_eq!(after["date_col"], ColValue::Date("1969-12-31".into()));
+    }
+
+    #[tokio::test]
+    async fn test_avro_logical_types_opt_in() {
+        let after = HashMap::from([
+            (
+                "datetime_col".to_string(),
+                ColValue::DateTime("2023-01-02 03:04:05.123".into()),
+            ),
+            ("date_col".to_string(), ColValue::Date("1969-12-31".into())),
+        ]);
+        let row_data = RowData::new(
+            "db1".into(),
+            "tb1".into(),
+            0,
+            RowType::Insert,
+            None,
+            Some(after),
+        );
+
+        // strings in the original format by default
+        let mut avro_converter = AvroConverter::new(None, false, false);
+        let payload = avro_converter
+            .row_data_to_avro_value(&row_data)
+            .await
+            .unwrap();
+        let DtData::Dml { row_data: decoded } =
+            avro_converter.avro_value_to_dt_data(payload).unwrap()
+        else {
+            panic!()
+        };
+        let decoded_after = decoded.after.unwrap();
+        assert_eq!(
+            decoded_after["datetime_col"],
+            ColValue::String("2023-01-02 03:04:05.123".into())
+        );
+        assert_eq!(
+            decoded_after["date_col"],
+            ColValue::String("1969-12-31".into())
+        );
+
+        let mut avro_converter = AvroConverter::new(None, false, true);
+        let payload = avro_converter
+            .row_data_to_avro_value(&row_data)
+            .await
+            .unwrap();
+        let DtData::Dml { row_data: decoded } =
+            avro_converter.avro_value_to_dt_data(payload).unwrap()
+        else {
+            panic!()
+        };
+        let decoded_after = decoded.after.unwrap();
+        assert_eq!(
+            decoded_after["datetime_col"],
+            ColValue::Timestamp("2023-01-02 03:04:05.123".into())
+        );
+        assert_eq!(
+            decoded_after["date_col"],
+            ColValue::Date("1969-12-31".into())
+        );
+    }
+
+    #[test]
+    fn test_avro_logical_types_fallback() {
+        let tb_meta = RdbTbMeta {
+            col_origin_type_map: HashMap::from([
+                ("pg_dec_col".to_string(), "numeric".to_string()),
+                ("narrow_dec_col".to_string(), "decimal(1,1)".to_string()),
+            ]),
+            ..Default::default()
+        };
+        let decimal = ColValue::Decimal("1.5".into());
+        // scale unknown
+        assert!(AvroConverter::col_value_to_logical_avro("dec_col", &decimal, None).is_none());
+        // scale unknown, or the value does not fit the precision
+        for col in ["pg_dec_col", "narrow_dec_col"] {
+            assert!(
+                AvroConverter::col_value_to_logical_avro(col, &decimal, Some(&tb_meta)).is_none()
+            );
+        }
+        let zero_date = ColValue::DateTime("0000-00-00 00:00:00".into());
+        assert!(AvroConverter::col_value_to_logical_avro("col", &zero_date, None).is_none());
+
+        assert_eq!(
+            AvroConverter::decimal_to_unscaled_bytes("-128", 0).unwrap(),
+            vec![0xff, 0x80]
+        );
+        assert_eq!(
+            AvroConverter::decimal_to_unscaled_bytes("1.5", 1).unwrap(),
+            vec![0x00, 0x0f]
+        );
+        assert!(AvroConverter::decimal_to_unscaled_bytes("1.55", 1).is_none());
+        assert!(AvroConverter::decimal_to_unscaled_bytes("NaN", 1).is_none());
+    }
+
+    #[test]
+    fn test_avro_schema_of_decimals() {
+        // unions with duplicate unnamed types are rejected by parse
+        let schema = AvroConverterSchema::build_avro_schema(&[(10, 2), (65, 30)]).unwrap();
+        let canonical = schema.canonical_form();
+        assert!(canonical.contains(r#""name":"Decimal_10_2","type":"fixed","size":5"#));
+        assert!(canonical.contains(r#""name":"Decimal_65_30","type":"fixed","size":28"#));
+
+        assert_eq!(AvroConverterSchema::decimal_fixed_size(2), 1);
+        assert_eq!(AvroConverterSchema::decimal_fixed_size(3), 2);
+        assert_eq!(AvroConverterSchema::decimal_fixed_size(10), 5);
+        assert_eq!(AvroConverterSchema::decimal_fixed_size(38), 16);
+
+        assert_eq!(
+            AvroConverter::parse_decimal_type("decimal(10,2) unsigned"),
+            Some((10, 2))
+        );
+        assert_eq!(AvroConverter::parse_decimal_type("numeric"), None);
+        assert_eq!(AvroConverter::parse_decimal_type("decimal(2,5)"), None);
+    }
+
     async fn validate_row_data(avro_converter: &mut AvroConverter, row_data: &RowData) {
         let payload = avro_converter
             .row_data_to_avro_value(row_data)
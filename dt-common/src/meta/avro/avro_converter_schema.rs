@@ -2,11 +2,8 @@ use apache_avro::Schema;
 
 pub struct AvroConverterSchema {}
 
-const SCHEMA_STR: &str = r#"
-{
-    "type": "record",
-    "name": "AvroData",
-    "fields": [
+// schema, tb, operation and fields, which are read first to learn the decimal branches
+const HEAD_FIELDS: &str = r#"
         {
             "name": "schema",
             "type": "string",
@@ -52,7 +49,9 @@ const SCHEMA_STR: &str = r#"
                     }
                 }
             ]
-        },
+        }"#;
+
+const ROW_FIELDS: &str = r#"
         {
             "name": "before",
             "default": null,
@@ -63,15 +62,7 @@ const SCHEMA_STR: &str = r#"
                     "null",
                     {
                         "type": "map",
-                        "values": 
-                        [
-                            "null",
-                            "string",
-                            "long",
-                            "double",
-                            "bytes",
-                            "boolean"
-                        ]
+                        "values": BEFORE_VALUES
                     }
                 ]
             }
@@ -86,15 +77,7 @@ const SCHEMA_STR: &str = r#"
                     "null",
                     {
                         "type": "map",
-                        "values": 
-                        [
-                            "null",
-                            "string",
-                            "long",
-                            "double",
-                            "bytes",
-                            "boolean"
-                        ]
+                        "values": AFTER_VALUES
                     }
                 ]
             }
@@ -121,13 +104,117 @@ const SCHEMA_STR: &str = r#"
                     }
                 ]
             }
-        }
-    ]
-}"#;
+        }"#;
+
+// a union holds at most one branch of each unnamed type,
+// so timestamp-millis is wrapped in a named record to not clash with long
+const VALUE_TYPES: &str = r#"
+                        [
+                            "null",
+                            "string",
+                            "long",
+                            "double",
+                            "bytes",
+                            "boolean",
+                            {
+                                "type": "int",
+                                "logicalType": "date"
+                            },
+                            {
+                                "type": "record",
+                                "name": "TimestampMillis",
+                                "fields": [
+                                    {
+                                        "name": "value",
+                                        "type": {
+                                            "type": "long",
+                                            "logicalType": "timestamp-millis"
+                                        }
+                                    }
+                                ]
+                            }DECIMAL_TYPES
+                        ]"#;
+
+// named types are defined once in before, after refers to them by name
+const VALUE_TYPE_REFS: &str = r#"
+                        [
+                            "null",
+                            "string",
+                            "long",
+                            "double",
+                            "bytes",
+                            "boolean",
+                            {
+                                "type": "int",
+                                "logicalType": "date"
+                            },
+                            "TimestampMillis"DECIMAL_TYPES
+                        ]"#;
+
+// union index of the first decimal branch
+pub const DECIMAL_BRANCH_START: usize = 8;
 
 impl AvroConverterSchema {
     pub fn get_avro_schema() -> Schema {
-        Schema::parse_str(SCHEMA_STR).unwrap()
+        Self::build_avro_schema(&[]).unwrap()
+    }
+
+    /// Each (precision, scale) of the decimals carried by a message is a named fixed decimal
+    /// branch appended to the value union in order, from DECIMAL_BRANCH_START.
+    pub fn build_avro_schema(decimal_types: &[(usize, usize)]) -> anyhow::Result<Schema> {
+        let mut decimal_defs = String::new();
+        let mut decimal_refs = String::new();
+        for (precision, scale) in decimal_types {
+            let name = Self::decimal_type_name(*precision, *scale);
+            decimal_defs.push_str(&format!(
+                r#", {{"type": "fixed", "name": "{}", "size": {}, "logicalType": "decimal", "precision": {}, "scale": {}}}"#,
+                name,
+                Self::decimal_fixed_size(*precision),
+                precision,
+                scale
+            ));
+            decimal_refs.push_str(&format!(r#", "{}""#, name));
+        }
+        let row_fields = ROW_FIELDS
+            .replace(
+                "BEFORE_VALUES",
+                &VALUE_TYPES.replace("DECIMAL_TYPES", &decimal_defs),
+            )
+            .replace(
+                "AFTER_VALUES",
+                &VALUE_TYPE_REFS.replace("DECIMAL_TYPES", &decimal_refs),
+            );
+        Ok(Schema::parse_str(&Self::record_str(&format!(
+            "{},{}",
+            HEAD_FIELDS, row_fields
+        )))?)
+    }
+
+    /// Reads schema, tb, operation and fields of a message and skips the rest.
+    pub fn get_avro_head_schema() -> Schema {
+        Schema::parse_str(&Self::record_str(HEAD_FIELDS)).unwrap()
+    }
+
+    pub fn decimal_type_name(precision: usize, scale: usize) -> String {
+        format!("Decimal_{}_{}", precision, scale)
+    }
+
+    // the fewest bytes holding a signed unscaled value of the precision
+    pub fn decimal_fixed_size(precision: usize) -> usize {
+        ((precision as f64 * 10f64.log2() + 1.0) / 8.0).ceil() as usize
+    }
+
+    fn record_str(fields: &str) -> String {
+        format!(
+            r#"
+{{
+    "type": "record",
+    "name": "AvroData",
+    "fields": [{}
+    ]
+}}"#,
+            fields
+        )
     }
 }
 
@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+
+use anyhow::bail;
+
+use crate::{
+    config::{
+        config_enums::DbType,
+        config_token_parser::{ConfigTokenParser, TokenEscapePair, REGEX_ESCAPE_PAIR},
+    },
+    error::Error,
+    meta::{col_value::ColValue, row_data::RowData},
+    rdb_filter::RdbFilter,
+    utils::sql_util::SqlUtil,
+};
+
+// (db, tb, col), each may contain wildcards or be a r#...# regex, same as [filter]
+type ColPattern = (String, String, String);
+
+/// Removes cols from sink payloads by [sinker] include_cols / exclude_cols,
+/// patterns match the routed db / tb / col names, key cols of the target are always kept.
+#[derive(Debug, Clone, Default)]
+pub struct ColProjection {
+    pub escape_pairs: Vec<(char, char)>,
+    pub include_cols: Vec<ColPattern>,
+    pub exclude_cols: Vec<ColPattern>,
+    // HashMap<(db, tb), HashMap<col, projected>>
+    cache: HashMap<(String, String), HashMap<String, bool>>,
+}
+
+impl ColProjection {
+    pub fn from_config(
+        include_cols: &str,
+        exclude_cols: &str,
+        db_type: &DbType,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            escape_pairs: SqlUtil::get_escape_pairs(db_type),
+            include_cols: Self::parse_col_patterns(include_cols, db_type)?,
+            exclude_cols: Self::parse_col_patterns(exclude_cols, db_type)?,
+            cache: HashMap::new(),
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.include_cols.is_empty() && self.exclude_cols.is_empty()
+    }
+
+    pub fn project_row(&mut self, row_data: &mut RowData, key_cols: &[String]) {
+        if self.is_empty() {
+            return;
+        }
+        let (schema, tb) = (&row_data.schema, &row_data.tb);
+        if let Some(before) = &mut row_data.before {
+            self.project_col_values(schema, tb, before, key_cols);
+        }
+        if let Some(after) = &mut row_data.after {
+            self.project_col_values(schema, tb, after, key_cols);
+        }
+    }
+
+    fn project_col_values(
+        &mut self,
+        schema: &str,
+        tb: &str,
+        col_values: &mut HashMap<String, ColValue>,
+        key_cols: &[String],
+    ) {
+        let removed_cols: Vec<String> = col_values
+            .keys()
+            .filter(|col| !key_cols.contains(col) && !self.is_projected(schema, tb, col))
+            .cloned()
+            .collect();
+        for col in removed_cols {
+            col_values.remove(&col);
+        }
+    }
+
+    fn is_projected(&mut self, schema: &str, tb: &str, col: &str) -> bool {
+        let tb_cache = self
+            .cache
+            .entry((schema.to_string(), tb.to_string()))
+            .or_default();
+        if let Some(projected) = tb_cache.get(col) {
+            return *projected;
+        }
+
+        let escape_pairs = &self.escape_pairs;
+        let match_tb = |pattern: &&ColPattern| {
+            RdbFilter::match_token(&pattern.0, schema, escape_pairs)
+                && RdbFilter::match_token(&pattern.1, tb, escape_pairs)
+        };
+        let match_col =
+            |pattern: &ColPattern| RdbFilter::match_token(&pattern.2, col, escape_pairs);
+
+        // if the table has include_cols, only they are kept
+        let mut tb_include_cols = self.include_cols.iter().filter(match_tb).peekable();
+        let included = tb_include_cols.peek().is_none() || tb_include_cols.any(match_col);
+        let projected = included && !self.exclude_cols.iter().filter(match_tb).any(match_col);
+        tb_cache.insert(col.to_string(), projected);
+        projected
+    }
+
+    fn parse_col_patterns(config_str: &str, db_type: &DbType) -> anyhow::Result<Vec<ColPattern>> {
+        let delimiters = vec![',', '.'];
+        let custom_escape_pairs = vec![TokenEscapePair::from((
+            REGEX_ESCAPE_PAIR.0.to_string(),
+            REGEX_ESCAPE_PAIR.1.to_string(),
+        ))];
+        let tokens = ConfigTokenParser::parse_config(
+            config_str,
+            db_type,
+            &delimiters,
+            Some(&custom_escape_pairs),
+        )?;
+        if tokens.len() % 3 != 0 {
+            bail! {Error::ConfigError(format!(
+                "config error near: {}, cols should be in the form of db.tb.col",
+                config_str
+            ))}
+        }
+        Ok(tokens
+            .chunks(3)
+            .map(|i| (i[0].clone(), i[1].clone(), i[2].clone()))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::meta::row_type::RowType;
+
+    fn build_row(schema: &str, tb: &str) -> RowData {
+        let after = HashMap::from([
+            ("id".to_string(), ColValue::Long(1)),
+            ("name".to_string(), ColValue::String("a".into())),
+            ("password".to_string(), ColValue::String("secret".into())),
+            ("password_salt".to_string(), ColValue::String("salt".into())),
+        ]);
+        RowData::new(
+            schema.into(),
+            tb.into(),
+            0,
+            RowType::Insert,
+            None,
+            Some(after),
+        )
+    }
+
+    fn project(projection: &mut ColProjection, schema: &str, tb: &str) -> Vec<String> {
+        let mut row_data = build_row(schema, tb);
+        projection.project_row(&mut row_data, &["id".to_string()]);
+        let mut cols: Vec<String> = row_data.after.unwrap().into_keys().collect();
+        cols.sort();
+        cols
+    }
+
+    #[test]
+    fn test_project_row() {
+        let mut projection =
+            ColProjection::from_config("db_1.tb_1.name", "*.*.password*", &DbType::Mysql).unwrap();
+        // the key col id is kept though not included
+        assert_eq!(project(&mut projection, "db_1", "tb_1"), vec!["id", "name"]);
+        assert_eq!(project(&mut projection, "db_2", "tb_1"), vec!["id", "name"]);
+        // cached
+        assert_eq!(project(&mut projection, "db_1", "tb_1"), vec!["id", "name"]);
+
+        let mut projection =
+            ColProjection::from_config("", "db_1.tb_1.`password`", &DbType::Mysql).unwrap();
+        assert_eq!(
+            project(&mut projection, "db_1", "tb_1"),
+            vec!["id", "name", "password_salt"]
+        );
+
+        // the key col is never excluded
+        let mut projection =
+            ColProjection::from_config("", "db_1.tb_1.id", &DbType::Mysql).unwrap();
+        assert_eq!(project(&mut projection, "db_1", "tb_1").len(), 4);
+    }
+
+    #[test]
+    fn test_parse_col_patterns() {
+        assert!(ColProjection::from_config("", "", &DbType::Mysql)
+            .unwrap()
+            .is_empty());
+        assert!(ColProjection::from_config("db_1.tb_1", "", &DbType::Mysql).is_err());
+    }
+}
@@ -1,13 +1,17 @@
 use std::{
+    borrow::Cow,
     cmp,
     collections::hash_map::DefaultHasher,
     hash::{Hash, Hasher},
 };
 
 use anyhow::bail;
+use base64::{engine::general_purpose, Engine as _};
 use mongodb::bson::{Bson, Document};
 use serde::{Deserialize, Serialize, Serializer};
 
+use crate::utils::geometry_util::GeometryUtil;
+
 #[derive(Debug, Clone, PartialEq, Deserialize)]
 #[allow(dead_code)]
 pub enum ColValue {
@@ -42,6 +46,8 @@ pub enum ColValue {
     Json2(String),
     Json3(serde_json::Value),
     MongoDoc(Document),
+    // mysql geometry: 4 bytes little-endian SRID followed by the WKB
+    Geometry(Vec<u8>),
 }
 
 impl std::fmt::Display for ColValue {
@@ -168,6 +174,102 @@ impl ColValue {
         }
     }
 
+    /// Whether two values represent the same data, used to diff the before and after images
+    /// of a row. Unlike PartialEq, which also compares the representation:
+    /// - integers of any width (Tiny..UnsignedLongLong) are equal if their values are equal,
+    ///   e.g. Tiny(1) equals Long(1)
+    /// - Float and Double are compared as f64, NaN equals NaN
+    /// - decimals are compared by numeric value, trailing zeros of the fraction are ignored,
+    ///   e.g. Decimal("1.0") equals Decimal("1.00") and Decimal("1")
+    /// - Json, Json2 and Json3 are compared as parsed json, so key order and whitespace are
+    ///   ignored, e.g. Json2("{\"a\":1}") equals Json3({"a":1})
+    /// - String, RawString, Set2 and Enum2 are compared as utf8 strings
+    /// - others fall back to is_same_value
+    pub fn semantic_eq(&self, other: &ColValue) -> bool {
+        if self.is_integer() && other.is_integer() {
+            return match (
+                self.convert_into_integer_128(),
+                other.convert_into_integer_128(),
+            ) {
+                (Ok(v1), Ok(v2)) => v1 == v2,
+                _ => false,
+            };
+        }
+
+        if self.is_float() && other.is_float() {
+            return match (self.convert_into_float_64(), other.convert_into_float_64()) {
+                (Ok(v1), Ok(v2)) => v1 == v2 || (v1.is_nan() && v2.is_nan()),
+                _ => false,
+            };
+        }
+
+        if let (ColValue::Decimal(v1), ColValue::Decimal(v2)) = (self, other) {
+            return Self::normalize_decimal(v1) == Self::normalize_decimal(v2);
+        }
+
+        if let (Some(v1), Some(v2)) = (self.parse_json(), other.parse_json()) {
+            return v1 == v2;
+        }
+
+        if let (Some(v1), Some(v2)) = (self.as_text(), other.as_text()) {
+            return v1 == v2;
+        }
+
+        self.is_same_value(other)
+    }
+
+    fn parse_json(&self) -> Option<Cow<'_, serde_json::Value>> {
+        match self {
+            ColValue::Json(v) => serde_json::from_slice(v).ok().map(Cow::Owned),
+            ColValue::Json2(v) => serde_json::from_str(v).ok().map(Cow::Owned),
+            ColValue::Json3(v) => Some(Cow::Borrowed(v)),
+            _ => None,
+        }
+    }
+
+    fn as_text(&self) -> Option<&str> {
+        match self {
+            ColValue::String(v) | ColValue::Set2(v) | ColValue::Enum2(v) => Some(v),
+            ColValue::RawString(v) => std::str::from_utf8(v).ok(),
+            _ => None,
+        }
+    }
+
+    // strip the sign of zero, leading zeros of the integer part and trailing zeros of the
+    // fraction, e.g. "-00.100" -> "-0.1", "1.00" -> "1"; non-numeric values such as "NaN"
+    // are kept as they are
+    fn normalize_decimal(v: &str) -> String {
+        let v = v.trim();
+        let (negative, digits) = match v.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, v.strip_prefix('+').unwrap_or(v)),
+        };
+        let (int_part, frac_part) = digits.split_once('.').unwrap_or((digits, ""));
+        if (int_part.is_empty() && frac_part.is_empty())
+            || !int_part.chars().all(|c| c.is_ascii_digit())
+            || !frac_part.chars().all(|c| c.is_ascii_digit())
+        {
+            return v.to_string();
+        }
+
+        let int_part = int_part.trim_start_matches('0');
+        let frac_part = frac_part.trim_end_matches('0');
+        if int_part.is_empty() && frac_part.is_empty() {
+            return "0".to_string();
+        }
+
+        let mut normalized = String::new();
+        if negative {
+            normalized.push('-');
+        }
+        normalized.push_str(if int_part.is_empty() { "0" } else { int_part });
+        if !frac_part.is_empty() {
+            normalized.push('.');
+            normalized.push_str(frac_part);
+        }
+        normalized
+    }
+
     pub fn hash_code(&self) -> anyhow::Result<u64> {
         if matches!(self, ColValue::None | ColValue::UnchangedToast) {
             return Ok(0);
@@ -222,6 +324,7 @@ impl ColValue {
             ColValue::Json2(_) => "Json2",
             ColValue::Json3(_) => "Json3",
             ColValue::MongoDoc(_) => "MongoDoc",
+            ColValue::Geometry(_) => "Geometry",
             ColValue::UnchangedToast => "UnchangedToast",
         }
     }
@@ -256,6 +359,7 @@ impl ColValue {
             ColValue::Json3(v) => Some(v.to_string()),
             ColValue::Blob(v) => Some(hex::encode(v)),
             ColValue::MongoDoc(v) => Some(Self::mongo_doc_to_string(v)),
+            ColValue::Geometry(v) => Some(Self::geometry_to_string(v)),
             ColValue::Bool(v) => Some(v.to_string()),
             ColValue::None | ColValue::UnchangedToast => Option::None,
         }
@@ -278,6 +382,31 @@ impl ColValue {
         }
     }
 
+    /// Canonical rendering for logs and text outputs:
+    /// - None and UnchangedToast: empty string
+    /// - Blob: "base64:" followed by the standard base64 (with padding) of the bytes
+    /// - Json, Json2, Json3: compacted json, or the text as is if it is not valid json
+    /// - RawString: utf8 with invalid bytes replaced by U+FFFD
+    /// - MongoDoc: relaxed extended json, Geometry: WKT
+    /// - others: same as to_option_string
+    pub fn to_display_string(&self) -> String {
+        match self {
+            ColValue::None | ColValue::UnchangedToast => String::new(),
+            ColValue::Blob(v) => format!("base64:{}", general_purpose::STANDARD.encode(v)),
+            ColValue::Json(v) => Self::compact_json(&String::from_utf8_lossy(v)),
+            ColValue::Json2(v) => Self::compact_json(v),
+            ColValue::Json3(v) => v.to_string(),
+            ColValue::RawString(v) => String::from_utf8_lossy(v).into_owned(),
+            _ => self.to_option_string().unwrap_or_default(),
+        }
+    }
+
+    fn compact_json(text: &str) -> String {
+        serde_json::from_str::<serde_json::Value>(text)
+            .map(|v| v.to_string())
+            .unwrap_or_else(|_| text.to_string())
+    }
+
     pub fn is_unchanged_toast(&self) -> bool {
         matches!(self, ColValue::UnchangedToast)
     }
@@ -312,7 +441,10 @@ impl ColValue {
             | ColValue::Set2(v)
             | ColValue::Enum2(v)
             | ColValue::Json2(v) => v.len(),
-            ColValue::Json(v) | ColValue::Blob(v) | ColValue::RawString(v) => v.len(),
+            ColValue::Json(v)
+            | ColValue::Blob(v)
+            | ColValue::RawString(v)
+            | ColValue::Geometry(v) => v.len(),
             ColValue::Json3(v) => v.to_string().len(),
             ColValue::MongoDoc(v) => Self::get_bson_size_doc(v),
             ColValue::None | ColValue::UnchangedToast => 0,
@@ -342,6 +474,11 @@ impl ColValue {
         }
     }
 
+    // WKT, or hex if the value can not be parsed
+    fn geometry_to_string(value: &[u8]) -> String {
+        GeometryUtil::to_wkt(value).unwrap_or_else(|_| hex::encode(value))
+    }
+
     fn mongo_doc_to_string(doc: &Document) -> String {
         // Use Canonical Extended JSON so BSON values with the same JSON value but different BSON
         // types, e.g. Int32(1) and Int64(1), remain distinguishable.
@@ -399,6 +536,7 @@ impl Serialize for ColValue {
             ColValue::MongoDoc(v) => Bson::Document(v.clone())
                 .into_relaxed_extjson()
                 .serialize(serializer),
+            ColValue::Geometry(v) => serializer.serialize_str(&Self::geometry_to_string(v)),
             ColValue::None | ColValue::UnchangedToast => serializer.serialize_none(),
         }
     }
@@ -440,6 +578,108 @@ mod tests {
     use crate::meta::tagged_col_value_map::TaggedColValueDef as MetaTaggedColValueDef;
     use std::collections::BTreeMap;
 
+    #[test]
+    fn test_to_display_string() {
+        let cases = vec![
+            (ColValue::None, ""),
+            (ColValue::UnchangedToast, ""),
+            (ColValue::Bool(true), "true"),
+            (ColValue::Tiny(-1), "-1"),
+            (ColValue::UnsignedTiny(255), "255"),
+            (ColValue::Short(-2), "-2"),
+            (ColValue::UnsignedShort(2), "2"),
+            (ColValue::Long(-3), "-3"),
+            (ColValue::UnsignedLong(3), "3"),
+            (ColValue::LongLong(-4), "-4"),
+            (ColValue::UnsignedLongLong(4), "4"),
+            (ColValue::Float(1.5), "1.5"),
+            (ColValue::Double(-2.25), "-2.25"),
+            (ColValue::Decimal("12.30".into()), "12.30"),
+            (ColValue::Time("12:00:00".into()), "12:00:00"),
+            (ColValue::Date("2024-01-01".into()), "2024-01-01"),
+            (
+                ColValue::DateTime("2024-01-01 12:00:00".into()),
+                "2024-01-01 12:00:00",
+            ),
+            (
+                ColValue::Timestamp("2024-01-01 12:00:00".into()),
+                "2024-01-01 12:00:00",
+            ),
+            (ColValue::Year(2024), "2024"),
+            (ColValue::String("abc".into()), "abc"),
+            (ColValue::RawString(vec![b'a', 0xff]), "a\u{fffd}"),
+            (ColValue::Blob(vec![1, 2, 3]), "base64:AQID"),
+            (ColValue::Bit(5), "5"),
+            (ColValue::Set(3), "3"),
+            (ColValue::Enum(1), "1"),
+            (ColValue::Set2("a,b".into()), "a,b"),
+            (ColValue::Enum2("a".into()), "a"),
+            (
+                ColValue::Json(br#"{"a": 1,  "b": [1, 2]}"#.to_vec()),
+                r#"{"a":1,"b":[1,2]}"#,
+            ),
+            (ColValue::Json2(r#"{"a": 1}"#.into()), r#"{"a":1}"#),
+            (ColValue::Json2("not json".into()), "not json"),
+            (ColValue::Json3(serde_json::json!({"a": 1})), r#"{"a":1}"#),
+            (
+                ColValue::MongoDoc(mongodb::bson::doc! {"a": 1}),
+                r#"{"a":1}"#,
+            ),
+        ];
+        for (col_value, expected) in cases {
+            assert_eq!(col_value.to_display_string(), expected, "{:?}", col_value);
+        }
+    }
+
+    #[test]
+    fn test_semantic_eq_integer_and_float() {
+        assert!(ColValue::Tiny(1).semantic_eq(&ColValue::Long(1)));
+        assert!(ColValue::UnsignedLongLong(1).semantic_eq(&ColValue::Short(1)));
+        assert!(!ColValue::Tiny(-1).semantic_eq(&ColValue::UnsignedTiny(255)));
+        assert!(ColValue::Float(1.5).semantic_eq(&ColValue::Double(1.5)));
+        assert!(ColValue::Float(f32::NAN).semantic_eq(&ColValue::Double(f64::NAN)));
+        // integers and floats are not mixed
+        assert!(!ColValue::Long(1).semantic_eq(&ColValue::Double(1.0)));
+        assert!(!ColValue::Long(1).semantic_eq(&ColValue::None));
+        assert!(ColValue::None.semantic_eq(&ColValue::None));
+    }
+
+    #[test]
+    fn test_semantic_eq_decimal() {
+        let eq = |v1: &str, v2: &str| {
+            ColValue::Decimal(v1.into()).semantic_eq(&ColValue::Decimal(v2.into()))
+        };
+        assert!(eq("1.0", "1.00"));
+        assert!(eq("1", "1.000"));
+        assert!(eq("-00.100", "-0.1"));
+        assert!(eq("0.00", "-0"));
+        assert!(eq("NaN", "NaN"));
+        assert!(!eq("1.0", "1.01"));
+        assert!(!eq("10", "1"));
+        assert!(!eq("-1", "1"));
+        assert!(!eq("NaN", "0"));
+    }
+
+    #[test]
+    fn test_semantic_eq_json() {
+        let json3 = ColValue::Json3(serde_json::json!({"a": 1, "b": [1, 2]}));
+        assert!(ColValue::Json2(r#"{"b": [1, 2], "a": 1}"#.into()).semantic_eq(&json3));
+        assert!(ColValue::Json(br#"{"a":1,"b":[1,2]}"#.to_vec()).semantic_eq(&json3));
+        assert!(json3.semantic_eq(&ColValue::Json2(r#"{"a":1,"b":[1,2]}"#.into())));
+        assert!(!ColValue::Json2(r#"{"a":2,"b":[1,2]}"#.into()).semantic_eq(&json3));
+        assert!(!ColValue::Json2(r#"{"a":1,"b":[2,1]}"#.into()).semantic_eq(&json3));
+        // invalid json is compared as it is
+        assert!(ColValue::Json2("{".into()).semantic_eq(&ColValue::Json2("{".into())));
+        assert!(!ColValue::Json2("{".into()).semantic_eq(&json3));
+    }
+
+    #[test]
+    fn test_semantic_eq_string() {
+        assert!(ColValue::RawString(b"a".to_vec()).semantic_eq(&ColValue::String("a".into())));
+        assert!(ColValue::Set2("a,b".into()).semantic_eq(&ColValue::String("a,b".into())));
+        assert!(!ColValue::RawString(vec![0xff]).semantic_eq(&ColValue::String("a".into())));
+    }
+
     #[test]
     fn test_is_same_value() {
         let v1 = ColValue::Float(f32::NAN);
@@ -3,7 +3,10 @@ use serde_json::json;
 
 use crate::config::config_enums::DbType;
 
-use super::{ddl_statement::DdlStatement, ddl_type::DdlType};
+use super::{
+    ddl_statement::{DdlStatement, DdlToSqlOptions},
+    ddl_type::DdlType,
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
 pub struct DdlData {
@@ -25,6 +28,10 @@ impl DdlData {
         self.statement.to_sql(&self.db_type)
     }
 
+    pub fn to_sql_with_options(&self, opts: &DdlToSqlOptions) -> String {
+        self.statement.to_sql_with_options(&self.db_type, opts)
+    }
+
     pub fn get_schema_tb(&self) -> (String, String) {
         let (mut schema, tb) = self.statement.get_schema_tb();
         if schema.is_empty() {
@@ -33,6 +40,19 @@ impl DdlData {
         (schema, tb)
     }
 
+    pub fn get_referenced_schema_tbs(&self) -> Vec<(String, String)> {
+        let mut res = Vec::new();
+        for (mut schema, tb) in self.statement.get_referenced_schema_tbs() {
+            if schema.is_empty() {
+                schema = self.default_schema.clone()
+            }
+            if !res.contains(&(schema.clone(), tb.clone())) {
+                res.push((schema, tb));
+            }
+        }
+        res
+    }
+
     pub fn get_rename_to_schema_tb(&self) -> (String, String) {
         let (mut schema, tb) = self.statement.get_rename_to_schema_tb();
         if schema.is_empty() {
@@ -55,6 +75,27 @@ impl DdlData {
         res
     }
 
+    // pieces keep ddl_type of the original ALTER TABLE so ddl filters still apply,
+    // while query is regenerated to contain only the piece
+    pub fn split_add_indexes(self) -> Vec<DdlData> {
+        let statements = self.statement.clone().split_add_indexes();
+        if statements.len() < 2 {
+            return vec![self];
+        }
+
+        let mut res = Vec::new();
+        for statement in statements {
+            res.push(Self {
+                default_schema: self.default_schema.clone(),
+                query: statement.to_sql(&self.db_type),
+                ddl_type: self.ddl_type.clone(),
+                db_type: self.db_type.clone(),
+                statement,
+            });
+        }
+        res
+    }
+
     pub fn get_data_size(&self) -> u64 {
         self.to_sql().len() as u64
     }
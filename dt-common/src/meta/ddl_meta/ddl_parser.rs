@@ -6,7 +6,7 @@ use std::{
 use anyhow::bail;
 use nom::{
     branch::alt,
-    bytes::complete::{tag, tag_no_case, take_while1},
+    bytes::complete::{tag, tag_no_case, take_while, take_while1},
     character::{
         complete::{multispace0, multispace1},
         is_alphanumeric,
@@ -21,13 +21,15 @@ use regex::Regex;
 use super::{
     ddl_data::DdlData,
     ddl_statement::{
-        AlterDatabaseStatement, AlterSchemaStatement, CreateDatabaseStatement,
-        CreateSchemaStatement, DdlStatement, DropDatabaseStatement, DropMultiTableStatement,
-        DropSchemaStatement, MysqlAlterTableRenameStatement, MysqlAlterTableStatement,
-        MysqlCreateIndexStatement, MysqlCreateTableStatement, MysqlDropIndexStatement,
-        MysqlTruncateTableStatement, PgAlterTableRenameStatement, PgAlterTableSetSchemaStatement,
-        PgAlterTableStatement, PgCreateIndexStatement, PgCreateTableStatement,
-        PgDropMultiIndexStatement, PgTruncateTableStatement, RenameMultiTableStatement,
+        AlterDatabaseRenameStatement, AlterDatabaseStatement, AlterSchemaStatement,
+        CreateDatabaseStatement, CreateSchemaStatement, DdlStatement, DropDatabaseStatement,
+        DropMultiTableStatement, DropSchemaStatement, MysqlAlterTableRenameStatement,
+        MysqlAlterTableStatement, MysqlCreateIndexStatement, MysqlCreateTableStatement,
+        MysqlDropIndexStatement, MysqlTruncateTableStatement, PgAlterTableRenameStatement,
+        PgAlterTableSetSchemaStatement, PgAlterTableStatement, PgAlterViewRenameStatement,
+        PgCreateIndexStatement, PgCreateMaterializedViewStatement, PgCreateTableStatement,
+        PgDropMultiIndexStatement, PgRefreshMaterializedViewStatement, PgTruncateTableStatement,
+        RenameMultiTableStatement,
     },
     ddl_type::DdlType,
     keywords::{
@@ -39,6 +41,11 @@ use crate::{config::config_enums::DbType, error::Error, utils::sql_util::SqlUtil
 
 type SchemaTable = (Option<Vec<u8>>, Vec<u8>);
 
+enum CreateDatabaseOption<'a> {
+    Charset(&'a [u8]),
+    Collate(&'a [u8]),
+}
+
 pub struct DdlParser {
     db_type: DbType,
 }
@@ -104,11 +111,22 @@ impl DdlParser {
             |i| self.rename_table(i),
             |i| self.create_index(i),
             |i| self.drop_index(i),
+            |i| self.pg_create_materialized_view(i),
+            |i| self.pg_refresh_materialized_view(i),
+            |i| self.pg_alter_view_rename(i),
         ))(i)
     }
 
     fn create_database<'a>(&'a self, i: &'a [u8]) -> IResult<&'a [u8], DdlData> {
-        let (remaining_input, (_, _, _, _, if_not_exists, database, _)) = tuple((
+        let option = |i: &'a [u8]| -> IResult<&'a [u8], CreateDatabaseOption<'a>> {
+            if self.db_type == DbType::Pg {
+                pg_create_database_option(i)
+            } else {
+                mysql_create_database_option(i)
+            }
+        };
+
+        let (remaining_input, (_, _, _, _, if_not_exists, database, _, options)) = tuple((
             tag_no_case("create"),
             multispace1,
             tag_no_case("database"),
@@ -116,13 +134,25 @@ impl DdlParser {
             opt(if_not_exists),
             |i| self.sql_identifier(i),
             multispace0,
+            opt(preceded(
+                opt(tuple((tag_no_case("with"), multispace1))),
+                many1(option),
+            )),
         ))(i)?;
 
-        let statement = CreateDatabaseStatement {
+        // only the leading charset / collate options are parsed, the rest is kept in unparsed
+        let mut statement = CreateDatabaseStatement {
             db: self.identifier_to_string(database),
             if_not_exists: if_not_exists.is_some(),
             unparsed: to_string(remaining_input),
+            ..Default::default()
         };
+        for option in options.unwrap_or_default() {
+            match option {
+                CreateDatabaseOption::Charset(v) => statement.charset = Some(to_string(v)),
+                CreateDatabaseOption::Collate(v) => statement.collate = Some(to_string(v)),
+            }
+        }
 
         let ddl = DdlData {
             ddl_type: DdlType::CreateDatabase,
@@ -158,23 +188,45 @@ impl DdlParser {
     }
 
     fn alter_database<'a>(&'a self, i: &'a [u8]) -> IResult<&'a [u8], DdlData> {
-        let (remaining_input, (_, _, _, _, database, _)) = tuple((
+        let rename_to = |i: &'a [u8]| -> IResult<&'a [u8], &[u8]> {
+            let (remaining_input, (_, _, _, _, new_name, _)) = tuple((
+                tag_no_case("rename"),
+                multispace1,
+                tag_no_case("to"),
+                multispace1,
+                |i| self.sql_identifier(i),
+                multispace0,
+            ))(i)?;
+            Ok((remaining_input, new_name))
+        };
+
+        let (remaining_input, (_, _, _, _, database, _, rename_to)) = tuple((
             tag_no_case("alter"),
             multispace1,
             tag_no_case("database"),
             multispace1,
             |i| self.sql_identifier(i),
             multispace1,
+            opt(rename_to),
         ))(i)?;
 
-        let statement = AlterDatabaseStatement {
-            db: self.identifier_to_string(database),
-            unparsed: to_string(remaining_input),
+        // pg: ALTER DATABASE name RENAME TO new_name
+        let statement = if let Some(new_db) = rename_to {
+            DdlStatement::AlterDatabaseRename(AlterDatabaseRenameStatement {
+                db: self.identifier_to_string(database),
+                new_db: self.identifier_to_string(new_db),
+                unparsed: to_string(remaining_input),
+            })
+        } else {
+            DdlStatement::AlterDatabase(AlterDatabaseStatement {
+                db: self.identifier_to_string(database),
+                unparsed: to_string(remaining_input),
+            })
         };
 
         let ddl = DdlData {
             ddl_type: DdlType::AlterDatabase,
-            statement: DdlStatement::AlterDatabase(statement),
+            statement,
             ..Default::default()
         };
         Ok((remaining_input, ddl))
@@ -296,11 +348,17 @@ impl DdlParser {
 
         // temporary tables won't be in binlog
         let (schema, tb) = self.parse_table(table);
+        let unparsed = to_string(remaining_input);
+        let (unparsed, comment, unparsed_after_comment) = split_mysql_table_comment(&unparsed)
+            .map(|(before, comment, after)| (before, Some(comment), after))
+            .unwrap_or((unparsed, None, String::new()));
         let statement = MysqlCreateTableStatement {
             db: schema,
             tb,
             if_not_exists: if_not_exists.is_some(),
-            unparsed: to_string(remaining_input),
+            unparsed,
+            comment,
+            unparsed_after_comment,
         };
 
         let ddl = DdlData {
@@ -410,9 +468,18 @@ impl DdlParser {
     fn mysql_alter_table<'a>(&'a self, i: &'a [u8]) -> IResult<&'a [u8], DdlData> {
         // https://dev.mysql.com/doc/refman/8.4/en/alter-table.html
         let rename_to = |i: &'a [u8]| -> IResult<&'a [u8], (String, String)> {
-            let (remaining_input, (_, _, _, new_table, _)) = tuple((
+            let (remaining_input, (_, _, _, _, new_table, _)) = tuple((
                 tag_no_case("rename"),
                 multispace1,
+                // RENAME COLUMN / INDEX / KEY are not table renames
+                not(peek(tuple((
+                    alt((
+                        tag_no_case("column"),
+                        tag_no_case("index"),
+                        tag_no_case("key"),
+                    )),
+                    multispace1,
+                )))),
                 opt(tuple((
                     alt((tag_no_case("as"), tag_no_case("to"))),
                     multispace1,
@@ -841,6 +908,130 @@ impl DdlParser {
         Ok((remaining_input, ddl))
     }
 
+    fn pg_create_materialized_view<'a>(&'a self, i: &'a [u8]) -> IResult<&'a [u8], DdlData> {
+        // https://www.postgresql.org/docs/16/sql-creatematerializedview.html
+        if self.db_type != DbType::Pg {
+            return Err(nom::Err::Error(nom::error::Error {
+                input: i,
+                code: nom::error::ErrorKind::Tag,
+            }));
+        }
+
+        let (remaining_input, (_, _, _, _, _, _, if_not_exists, view, _)) = tuple((
+            tag_no_case("create"),
+            multispace1,
+            tag_no_case("materialized"),
+            multispace1,
+            tag_no_case("view"),
+            multispace1,
+            opt(if_not_exists),
+            |i| self.schema_table(i),
+            multispace0,
+        ))(i)?;
+
+        let (schema, name) = self.parse_table(view);
+        let statement = PgCreateMaterializedViewStatement {
+            schema,
+            name,
+            if_not_exists: if_not_exists.is_some(),
+            unparsed: to_string(remaining_input),
+        };
+
+        let ddl = DdlData {
+            ddl_type: DdlType::CreateMaterializedView,
+            statement: DdlStatement::PgCreateMaterializedView(statement),
+            ..Default::default()
+        };
+        Ok((remaining_input, ddl))
+    }
+
+    fn pg_refresh_materialized_view<'a>(&'a self, i: &'a [u8]) -> IResult<&'a [u8], DdlData> {
+        // https://www.postgresql.org/docs/16/sql-refreshmaterializedview.html
+        if self.db_type != DbType::Pg {
+            return Err(nom::Err::Error(nom::error::Error {
+                input: i,
+                code: nom::error::ErrorKind::Tag,
+            }));
+        }
+
+        let (remaining_input, (_, _, _, _, _, _, concurrently, view, _)) = tuple((
+            tag_no_case("refresh"),
+            multispace1,
+            tag_no_case("materialized"),
+            multispace1,
+            tag_no_case("view"),
+            multispace1,
+            opt(tuple((tag_no_case("concurrently"), multispace1))),
+            |i| self.schema_table(i),
+            multispace0,
+        ))(i)?;
+
+        let (schema, name) = self.parse_table(view);
+        let statement = PgRefreshMaterializedViewStatement {
+            schema,
+            name,
+            is_concurrently: concurrently.is_some(),
+            unparsed: to_string(remaining_input),
+        };
+
+        let ddl = DdlData {
+            ddl_type: DdlType::RefreshMaterializedView,
+            statement: DdlStatement::PgRefreshMaterializedView(statement),
+            ..Default::default()
+        };
+        Ok((remaining_input, ddl))
+    }
+
+    fn pg_alter_view_rename<'a>(&'a self, i: &'a [u8]) -> IResult<&'a [u8], DdlData> {
+        // https://www.postgresql.org/docs/16/sql-alterview.html
+        // https://www.postgresql.org/docs/16/sql-altermaterializedview.html
+        if self.db_type != DbType::Pg {
+            return Err(nom::Err::Error(nom::error::Error {
+                input: i,
+                code: nom::error::ErrorKind::Tag,
+            }));
+        }
+
+        let (
+            remaining_input,
+            (_, _, materialized, _, _, if_exists, view, _, _, _, _, _, new_view, _),
+        ) = tuple((
+            tag_no_case("alter"),
+            multispace1,
+            opt(tuple((tag_no_case("materialized"), multispace1))),
+            tag_no_case("view"),
+            multispace1,
+            opt(if_exists),
+            |i| self.schema_table(i),
+            multispace1,
+            tag_no_case("rename"),
+            multispace1,
+            tag_no_case("to"),
+            multispace1,
+            |i| self.schema_table(i),
+            multispace0,
+        ))(i)?;
+
+        let (schema, tb) = self.parse_table(view);
+        let (new_schema, new_tb) = self.parse_table(new_view);
+        let statement = PgAlterViewRenameStatement {
+            schema,
+            tb,
+            new_schema,
+            new_tb,
+            if_exists: if_exists.is_some(),
+            is_materialized: materialized.is_some(),
+            unparsed: to_string(remaining_input),
+        };
+
+        let ddl = DdlData {
+            ddl_type: DdlType::AlterView,
+            statement: DdlStatement::PgAlterViewRename(statement),
+            ..Default::default()
+        };
+        Ok((remaining_input, ddl))
+    }
+
     // Parse a reference to a named schema.table, with an optional alias
     fn schema_table<'a>(&'a self, i: &'a [u8]) -> IResult<&'a [u8], SchemaTable> {
         map(
@@ -1028,6 +1219,156 @@ fn if_exists(i: &[u8]) -> IResult<&[u8], ()> {
     Ok((remaining_input, ()))
 }
 
+// [DEFAULT] {CHARACTER SET | CHARSET} [=] charset_name | [DEFAULT] COLLATE [=] collation_name
+fn mysql_create_database_option(i: &[u8]) -> IResult<&[u8], CreateDatabaseOption<'_>> {
+    let charset = alt((
+        recognize(tuple((
+            tag_no_case("character"),
+            multispace1,
+            tag_no_case("set"),
+        ))),
+        tag_no_case("charset"),
+    ));
+    preceded(
+        opt(tuple((tag_no_case("default"), multispace1))),
+        alt((
+            map(
+                preceded(charset, create_database_option_value),
+                CreateDatabaseOption::Charset,
+            ),
+            map(
+                preceded(tag_no_case("collate"), create_database_option_value),
+                CreateDatabaseOption::Collate,
+            ),
+        )),
+    )(i)
+}
+
+// ENCODING [=] encoding | LC_COLLATE [=] lc_collate
+fn pg_create_database_option(i: &[u8]) -> IResult<&[u8], CreateDatabaseOption<'_>> {
+    alt((
+        map(
+            preceded(tag_no_case("encoding"), create_database_option_value),
+            CreateDatabaseOption::Charset,
+        ),
+        map(
+            preceded(tag_no_case("lc_collate"), create_database_option_value),
+            CreateDatabaseOption::Collate,
+        ),
+    ))(i)
+}
+
+fn create_database_option_value(i: &[u8]) -> IResult<&[u8], &[u8]> {
+    let (remaining_input, (_, _, _, value, _)) = tuple((
+        multispace0,
+        opt(tag("=")),
+        multispace0,
+        alt((
+            delimited(tag("'"), take_while(|c| c != b'\''), tag("'")),
+            take_while1(|c| is_sql_identifier(c) || c == b'-'),
+        )),
+        multispace0,
+    ))(i)?;
+    Ok((remaining_input, value))
+}
+
+// cuts the table level COMMENT [=] 'string' clause out of the create table body,
+// returns: (body before the clause, comment, body after the clause)
+fn split_mysql_table_comment(unparsed: &str) -> Option<(String, String, String)> {
+    let bytes = unparsed.as_bytes();
+    let mut depth = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\'' | b'"' | b'`' => i = read_mysql_quoted(bytes, i).1,
+            b'(' => {
+                depth += 1;
+                i += 1;
+            }
+            b')' => {
+                depth -= 1;
+                i += 1;
+            }
+            c if depth == 0 && is_sql_identifier(c) => {
+                let start = i;
+                while i < bytes.len() && is_sql_identifier(bytes[i]) {
+                    i += 1;
+                }
+                let word = &bytes[start..i];
+                // table options end before partition options and the select of CREATE ... SELECT
+                if word.eq_ignore_ascii_case(b"partition")
+                    || word.eq_ignore_ascii_case(b"as")
+                    || word.eq_ignore_ascii_case(b"select")
+                {
+                    return None;
+                }
+                if !word.eq_ignore_ascii_case(b"comment") {
+                    continue;
+                }
+
+                let mut j = i;
+                while j < bytes.len() && bytes[j].is_ascii_whitespace() {
+                    j += 1;
+                }
+                if j < bytes.len() && bytes[j] == b'=' {
+                    j += 1;
+                }
+                while j < bytes.len() && bytes[j].is_ascii_whitespace() {
+                    j += 1;
+                }
+                if j < bytes.len() && (bytes[j] == b'\'' || bytes[j] == b'"') {
+                    let (comment, end) = read_mysql_quoted(bytes, j);
+                    return Some((
+                        unparsed[..start].trim_end().to_string(),
+                        to_string(&comment),
+                        unparsed[end..].to_string(),
+                    ));
+                }
+            }
+            _ => i += 1,
+        }
+    }
+    None
+}
+
+// reads the quoted string starting at bytes[start], returns: (unescaped content, end index),
+// the end index points into the original text, so the text around the string is kept as is
+// https://dev.mysql.com/doc/refman/8.0/en/string-literals.html#character-escape-sequences
+fn read_mysql_quoted(bytes: &[u8], start: usize) -> (Vec<u8>, usize) {
+    let quote = bytes[start];
+    let mut content = Vec::new();
+    let mut i = start + 1;
+    while i < bytes.len() {
+        let c = bytes[i];
+        if c == b'\\' && quote != b'`' && i + 1 < bytes.len() {
+            match bytes[i + 1] {
+                b'0' => content.push(0),
+                b'b' => content.push(0x08),
+                b'n' => content.push(b'\n'),
+                b'r' => content.push(b'\r'),
+                b't' => content.push(b'\t'),
+                b'Z' => content.push(0x1a),
+                // kept with the backslash, they are only escaped in pattern matching
+                b'%' | b'_' => content.extend_from_slice(&bytes[i..i + 2]),
+                escaped => content.push(escaped),
+            }
+            i += 2;
+        } else if c == quote {
+            // doubled quote
+            if i + 1 < bytes.len() && bytes[i + 1] == quote {
+                content.push(quote);
+                i += 2;
+            } else {
+                return (content, i + 1);
+            }
+        } else {
+            content.push(c);
+            i += 1;
+        }
+    }
+    (content, i)
+}
+
 fn ws_sep_comma(i: &[u8]) -> IResult<&[u8], &[u8]> {
     delimited(multispace0, tag(","), multispace0)(i)
 }
@@ -1039,7 +1380,10 @@ fn to_string(i: &[u8]) -> String {
 #[cfg(test)]
 mod test_mysql {
 
-    use crate::{config::config_enums::DbType, meta::ddl_meta::ddl_parser::DdlParser};
+    use crate::{
+        config::config_enums::DbType,
+        meta::ddl_meta::{ddl_parser::DdlParser, ddl_statement::DdlToSqlOptions},
+    };
 
     use super::*;
 
@@ -1085,6 +1429,82 @@ mod test_mysql {
         }
     }
 
+    #[test]
+    fn test_create_table_with_comment_mysql() {
+        let sqls = [
+            "create table aaa.bbb (id int) ENGINE=InnoDB COMMENT='user records'",
+            "create table aaa.bbb (id int comment 'id col') comment = 'it''s' DEFAULT CHARSET=utf8mb4",
+            "create table aaa.bbb (id int) COMMENT 'a\\\\b' PARTITION BY HASH(id) PARTITIONS 2",
+            "create table aaa.bbb (id int) COMMENT='a\\nb\\tc\\0\\Z\\\"d\\'e' ENGINE=InnoDB",
+            // partition comments are not table comments
+            "create table aaa.bbb (id int) PARTITION BY RANGE (id) (PARTITION p0 VALUES LESS THAN (10) COMMENT='p0')",
+        ];
+
+        let expect_sqls = [
+            "CREATE TABLE `aaa`.`bbb` (id int) ENGINE=InnoDB COMMENT='user records'",
+            "CREATE TABLE `aaa`.`bbb` (id int comment 'id col') COMMENT='it''s' DEFAULT CHARSET=utf8mb4",
+            "CREATE TABLE `aaa`.`bbb` (id int) COMMENT='a\\\\b' PARTITION BY HASH(id) PARTITIONS 2",
+            "CREATE TABLE `aaa`.`bbb` (id int) COMMENT='a\\nb\\tc\\0\\Z\"d''e' ENGINE=InnoDB",
+            "CREATE TABLE `aaa`.`bbb` (id int) PARTITION BY RANGE (id) (PARTITION p0 VALUES LESS THAN (10) COMMENT='p0')",
+        ];
+
+        let expect_comments = [
+            Some("user records"),
+            Some("it's"),
+            Some("a\\b"),
+            Some("a\nb\tc\0\x1a\"d'e"),
+            None,
+        ];
+
+        let parser = DdlParser::new(DbType::Mysql);
+        for i in 0..sqls.len() {
+            let r = parser.parse(sqls[i]).unwrap().unwrap();
+            assert_eq!(r.ddl_type, DdlType::CreateTable);
+            assert_eq!(r.to_sql(), expect_sqls[i]);
+            let DdlStatement::MysqlCreateTable(s) = &r.statement else {
+                panic!("unexpected statement");
+            };
+            assert_eq!(s.comment.as_deref(), expect_comments[i]);
+        }
+    }
+
+    #[test]
+    fn test_to_sql_with_options_mysql() {
+        let sqls = [
+            "create table aaa.bbb (id int)",
+            "create table if not exists aaa.bbb (id int)",
+            "drop table aaa.bbb",
+            "drop database if exists aaa",
+            // mysql has no IF EXISTS for alter table
+            "alter table aaa.bbb add column c int",
+        ];
+
+        let expect_sqls = [
+            "CREATE TABLE IF NOT EXISTS `aaa`.`bbb` (id int)",
+            "CREATE TABLE IF NOT EXISTS `aaa`.`bbb` (id int)",
+            "DROP TABLE IF EXISTS `aaa`.`bbb`",
+            "DROP DATABASE IF EXISTS `aaa`",
+            "ALTER TABLE `aaa`.`bbb` add column c int",
+        ];
+
+        let opts = DdlToSqlOptions {
+            force_if_not_exists: true,
+            force_if_exists: true,
+        };
+        let parser = DdlParser::new(DbType::Mysql);
+        for i in 0..sqls.len() {
+            let r = parser.parse(sqls[i]).unwrap().unwrap();
+            assert_eq!(r.to_sql_with_options(&opts), expect_sqls[i]);
+        }
+
+        // without forced options, the output is the same as to_sql
+        let r = parser.parse(sqls[0]).unwrap().unwrap();
+        assert_eq!(
+            r.to_sql_with_options(&DdlToSqlOptions::default()),
+            r.to_sql()
+        );
+    }
+
     #[test]
     fn test_create_table_with_schema_with_special_characters_mysql() {
         let sqls = [
@@ -1327,6 +1747,22 @@ mod test_mysql {
         }
     }
 
+    #[test]
+    fn test_alter_rename_column_mysql() {
+        let sqls = [
+            "alter table tb_2 rename column c_1 to c_2",
+            "alter table tb_2 rename index idx_1 to idx_2",
+            "alter table tb_2 rename key idx_1 to idx_2",
+        ];
+
+        let parser = DdlParser::new(DbType::Mysql);
+        for sql in sqls {
+            let r = parser.parse(sql).unwrap().unwrap();
+            assert!(matches!(r.statement, DdlStatement::MysqlAlterTable(_)));
+            assert_eq!(r.to_sql(), format!("ALTER TABLE `tb_2` {}", &sql[17..]));
+        }
+    }
+
     #[test]
     fn test_create_database_mysql() {
         let sqls = [
@@ -1389,6 +1825,42 @@ mod test_mysql {
         }
     }
 
+    #[test]
+    fn test_create_database_with_charset_mysql() {
+        let sqls = [
+            "CREATE DATABASE app CHARACTER SET utf8mb4 COLLATE utf8mb4_bin",
+            "create database if not exists `app` default charset=utf8mb4 default collate = 'utf8mb4_bin'",
+            "create database app COLLATE utf8mb4_bin",
+            // options after unknown ones stay unparsed
+            "create database app CHARACTER SET utf8mb4 ENCRYPTION 'N' COLLATE utf8mb4_bin;",
+        ];
+
+        let expect_sqls = [
+            "CREATE DATABASE `app` CHARACTER SET utf8mb4 COLLATE utf8mb4_bin",
+            "CREATE DATABASE IF NOT EXISTS `app` CHARACTER SET utf8mb4 COLLATE utf8mb4_bin",
+            "CREATE DATABASE `app` COLLATE utf8mb4_bin",
+            "CREATE DATABASE `app` CHARACTER SET utf8mb4 ENCRYPTION 'N' COLLATE utf8mb4_bin;",
+        ];
+
+        let parser = DdlParser::new(DbType::Mysql);
+        for i in 0..sqls.len() {
+            let r = parser.parse(sqls[i]).unwrap().unwrap();
+            assert_eq!(r.ddl_type, DdlType::CreateDatabase);
+            assert_eq!(r.to_sql(), expect_sqls[i]);
+            // re-serialized sql parses to the same statement
+            let r2 = parser.parse(&r.to_sql()).unwrap().unwrap();
+            assert_eq!(r2.statement, r.statement);
+        }
+
+        let r = parser.parse(sqls[0]).unwrap().unwrap();
+        let DdlStatement::CreateDatabase(s) = r.statement else {
+            panic!("unexpected statement")
+        };
+        assert_eq!(s.charset, Some("utf8mb4".into()));
+        assert_eq!(s.collate, Some("utf8mb4_bin".into()));
+        assert!(s.unparsed.is_empty());
+    }
+
     #[test]
     fn test_drop_database_mysql() {
         let sqls = [
@@ -1614,6 +2086,42 @@ mod test_mysql {
         }
     }
 
+    #[test]
+    fn test_split_alter_table_add_indexes_mysql() {
+        let parser = DdlParser::new(DbType::Mysql);
+
+        // multiple indexes
+        let sql = "alter table db_1.tb_1 add index idx_1(a), ADD UNIQUE KEY `idx_2` USING BTREE (b, c(10)) COMMENT 'x, y', add fulltext idx_3 (d)";
+        let expect_sqls = [
+            "CREATE INDEX `idx_1` ON `db_1`.`tb_1` (a)",
+            "CREATE UNIQUE INDEX `idx_2` USING BTREE ON `db_1`.`tb_1` (b, c(10)) COMMENT 'x, y'",
+            "CREATE FULLTEXT INDEX `idx_3` ON `db_1`.`tb_1` (d)",
+        ];
+        let r = parser.parse(sql).unwrap().unwrap();
+        // only split on demand
+        assert_eq!(r.clone().split_to_multi(), vec![r.clone()]);
+        let split = r.split_add_indexes();
+        assert_eq!(split.len(), expect_sqls.len());
+        for i in 0..split.len() {
+            assert_eq!(split[i].ddl_type, DdlType::AlterTable);
+            assert_eq!(split[i].to_sql(), expect_sqls[i]);
+            assert_eq!(split[i].query, expect_sqls[i]);
+        }
+
+        // kept as is: single index, other clauses, index without name
+        let sqls = [
+            "alter table db_1.tb_1 add index idx_1(a)",
+            "alter table db_1.tb_1 add index idx_1(a), add column c int",
+            "alter table db_1.tb_1 add index idx_1(a), add index (b)",
+            "alter table db_1.tb_1 add index idx_1(a), add index idx_2(b), algorithm=inplace",
+        ];
+        for sql in sqls {
+            let r = parser.parse(sql).unwrap().unwrap();
+            let split = r.clone().split_add_indexes();
+            assert_eq!(split, vec![r]);
+        }
+    }
+
     #[test]
     fn test_create_index_mysql() {
         let sqls = [
@@ -1662,7 +2170,9 @@ mod test_mysql {
 mod test_pg {
     use crate::{
         config::config_enums::DbType,
-        meta::ddl_meta::{ddl_parser::DdlParser, ddl_type::DdlType},
+        meta::ddl_meta::{
+            ddl_parser::DdlParser, ddl_statement::DdlToSqlOptions, ddl_type::DdlType,
+        },
     };
 
     #[test]
@@ -1852,6 +2362,33 @@ mod test_pg {
         }
     }
 
+    #[test]
+    fn test_alter_view_rename_pg() {
+        let sqls = [
+            "alter view v_1 rename to v_2",
+            r#"ALTER VIEW IF EXISTS "schema_1".v_1 RENAME TO v_2"#,
+            "alter materialized view schema_1.mv_1 rename to mv_2",
+            "alter materialized view if exists mv_1 rename to mv_2",
+        ];
+
+        let expect_sqls = [
+            r#"ALTER VIEW "v_1" RENAME TO "v_2""#,
+            r#"ALTER VIEW IF EXISTS "schema_1"."v_1" RENAME TO "v_2""#,
+            r#"ALTER MATERIALIZED VIEW "schema_1"."mv_1" RENAME TO "mv_2""#,
+            r#"ALTER MATERIALIZED VIEW IF EXISTS "mv_1" RENAME TO "mv_2""#,
+        ];
+
+        let parser = DdlParser::new(DbType::Pg);
+        for i in 0..sqls.len() {
+            let r = parser.parse(sqls[i]).unwrap().unwrap();
+            assert_eq!(r.ddl_type, DdlType::AlterView);
+            assert_eq!(r.to_sql(), expect_sqls[i]);
+            // not split like multi table renames
+            let statement = r.statement.clone();
+            assert_eq!(r.statement.split_to_multi(), vec![statement]);
+        }
+    }
+
     #[test]
     fn test_create_schema_pg() {
         let sqls = [
@@ -2000,6 +2537,73 @@ mod test_pg {
         }
     }
 
+    #[test]
+    fn test_create_database_with_encoding_pg() {
+        let sqls = [
+            "create database app",
+            "CREATE DATABASE app WITH ENCODING 'UTF8' LC_COLLATE = 'C'",
+            "create database app encoding=UTF8 owner postgres",
+            // options after unknown ones stay unparsed
+            "CREATE DATABASE app WITH OWNER postgres ENCODING 'UTF8'",
+        ];
+
+        let expect_sqls = [
+            r#"CREATE DATABASE "app""#,
+            r#"CREATE DATABASE "app" ENCODING 'UTF8' LC_COLLATE 'C'"#,
+            r#"CREATE DATABASE "app" ENCODING 'UTF8' owner postgres"#,
+            r#"CREATE DATABASE "app" WITH OWNER postgres ENCODING 'UTF8'"#,
+        ];
+
+        let parser = DdlParser::new(DbType::Pg);
+        for i in 0..sqls.len() {
+            let r = parser.parse(sqls[i]).unwrap().unwrap();
+            assert_eq!(r.ddl_type, DdlType::CreateDatabase);
+            assert_eq!(r.to_sql(), expect_sqls[i]);
+            let r2 = parser.parse(&r.to_sql()).unwrap().unwrap();
+            assert_eq!(r2.statement, r.statement);
+        }
+    }
+
+    #[test]
+    fn test_alter_database_rename_pg() {
+        let sqls = [
+            "alter database aaa rename to bbb",
+            // escapes
+            "alter database \"aaa\" rename to \"bbb\"",
+            // spaces + comments
+            "alter /*some comments,*/database/*some comments*/    aaa   rename   to bbb",
+            "alter database aaa owner to bbb",
+        ];
+
+        let expect_sqls = [
+            r#"ALTER DATABASE "aaa" RENAME TO "bbb""#,
+            // escapes
+            r#"ALTER DATABASE "aaa" RENAME TO "bbb""#,
+            // spaces + comments
+            r#"ALTER DATABASE "aaa" RENAME TO "bbb""#,
+            r#"ALTER DATABASE "aaa" owner to bbb"#,
+        ];
+
+        let parser = DdlParser::new(DbType::Pg);
+        for i in 0..sqls.len() {
+            let r = parser.parse(sqls[i]).unwrap().unwrap();
+            assert_eq!(r.ddl_type, DdlType::AlterDatabase);
+            assert_eq!(r.to_sql(), expect_sqls[i]);
+        }
+
+        let r = parser.parse(sqls[0]).unwrap().unwrap();
+        assert_eq!(r.get_schema_tb(), ("aaa".to_string(), String::new()));
+        assert_eq!(
+            r.get_rename_to_schema_tb(),
+            ("bbb".to_string(), String::new())
+        );
+
+        // not emitted for mysql
+        let mut r = r;
+        r.db_type = DbType::Mysql;
+        assert_eq!(r.to_sql(), "");
+    }
+
     #[test]
     fn test_truncate_table_pg() {
         let sqls = [
@@ -2084,6 +2688,85 @@ mod test_pg {
             assert_eq!(r.to_sql(), expect_sqls[i]);
         }
     }
+
+    #[test]
+    fn test_materialized_view_pg() {
+        let sqls = [
+            "create materialized view mv_1 as select * from tb_1",
+            r#"create materialized view if not exists "schema_1"."mv_1" (id, value) as select id, value from tb_1 with no data"#,
+            "refresh materialized view schema_1.mv_1",
+            "refresh materialized view concurrently mv_1 with data",
+        ];
+
+        let expect_sqls = [
+            r#"CREATE MATERIALIZED VIEW "mv_1" as select * from tb_1"#,
+            r#"CREATE MATERIALIZED VIEW IF NOT EXISTS "schema_1"."mv_1" (id, value) as select id, value from tb_1 with no data"#,
+            r#"REFRESH MATERIALIZED VIEW "schema_1"."mv_1""#,
+            r#"REFRESH MATERIALIZED VIEW CONCURRENTLY "mv_1" with data"#,
+        ];
+
+        let expect_ddl_types = [
+            DdlType::CreateMaterializedView,
+            DdlType::CreateMaterializedView,
+            DdlType::RefreshMaterializedView,
+            DdlType::RefreshMaterializedView,
+        ];
+
+        let parser = DdlParser::new(DbType::Pg);
+        for i in 0..sqls.len() {
+            let r = parser.parse(sqls[i]).unwrap().unwrap();
+            assert_eq!(r.ddl_type, expect_ddl_types[i]);
+            assert_eq!(r.to_sql(), expect_sqls[i]);
+            let r2 = parser.parse(&r.to_sql()).unwrap().unwrap();
+            assert_eq!(r2.statement, r.statement);
+        }
+
+        // route
+        let mut r = parser.parse(sqls[2]).unwrap().unwrap();
+        assert_eq!(
+            r.get_schema_tb(),
+            ("schema_1".to_string(), "mv_1".to_string())
+        );
+        r.statement.route("dst_schema".into(), "dst_mv".into());
+        assert_eq!(
+            r.to_sql(),
+            r#"REFRESH MATERIALIZED VIEW "dst_schema"."dst_mv""#
+        );
+    }
+
+    #[test]
+    fn test_to_sql_with_options_pg() {
+        let sqls = [
+            "create table tb_1 (id int)",
+            "create index if not exists idx_1 on tb_1 (id)",
+            // IF NOT EXISTS requires an index name
+            "create index on tb_1 (id)",
+            "drop index concurrently idx_1, idx_2",
+            "alter table only tb_1 add column c int",
+            "alter table tb_1 rename to tb_2",
+            "create database db_1",
+        ];
+
+        let expect_sqls = [
+            r#"CREATE TABLE IF NOT EXISTS "tb_1" (id int)"#,
+            r#"CREATE INDEX IF NOT EXISTS "idx_1" ON "tb_1" (id)"#,
+            r#"CREATE INDEX ON "tb_1" (id)"#,
+            r#"DROP INDEX CONCURRENTLY IF EXISTS "idx_1", "idx_2""#,
+            r#"ALTER TABLE IF EXISTS ONLY "tb_1" add column c int"#,
+            r#"ALTER TABLE IF EXISTS "tb_1" RENAME TO "tb_2""#,
+            r#"CREATE DATABASE "db_1""#,
+        ];
+
+        let opts = DdlToSqlOptions {
+            force_if_not_exists: true,
+            force_if_exists: true,
+        };
+        let parser = DdlParser::new(DbType::Pg);
+        for i in 0..sqls.len() {
+            let r = parser.parse(sqls[i]).unwrap().unwrap();
+            assert_eq!(r.to_sql_with_options(&opts), expect_sqls[i]);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -2105,4 +2788,66 @@ mod test_common {
             assert!(parser.parse(sql).unwrap().is_none());
         }
     }
+
+    #[test]
+    fn test_get_referenced_schema_tbs() {
+        let to_schema_tbs = |schema_tbs: &[(&str, &str)]| -> Vec<(String, String)> {
+            schema_tbs
+                .iter()
+                .map(|(schema, tb)| (schema.to_string(), tb.to_string()))
+                .collect()
+        };
+
+        let cases = [
+            (
+                DbType::Mysql,
+                "drop table if exists db_1.tb_1, tb_2, `db_2`.`tb_3`",
+                vec![("db_1", "tb_1"), ("db_0", "tb_2"), ("db_2", "tb_3")],
+            ),
+            (
+                DbType::Mysql,
+                "rename table db_1.tb_1 to db_2.tb_1, tb_2 to tb_3",
+                vec![
+                    ("db_1", "tb_1"),
+                    ("db_2", "tb_1"),
+                    ("db_0", "tb_2"),
+                    ("db_0", "tb_3"),
+                ],
+            ),
+            (
+                DbType::Mysql,
+                "alter table db_1.tb_1 rename to db_2.tb_2",
+                vec![("db_1", "tb_1"), ("db_2", "tb_2")],
+            ),
+            (
+                DbType::Mysql,
+                "alter table db_1.tb_1 add constraint fk_1 foreign key (pid) references `db_2`.`tb_2` (id)",
+                vec![("db_1", "tb_1"), ("db_2", "tb_2")],
+            ),
+            (
+                DbType::Pg,
+                "create table schema_1.tb_1 (id int, pid int references tb_2(id), cid int references tb_1(id))",
+                vec![("schema_1", "tb_1"), ("schema_1", "tb_2")],
+            ),
+            (
+                DbType::Pg,
+                "alter table schema_1.tb_1 set schema schema_2",
+                vec![("schema_1", "tb_1"), ("schema_2", "tb_1")],
+            ),
+            (
+                DbType::Pg,
+                "create index idx_1 on schema_1.tb_1 (id)",
+                vec![("schema_1", "tb_1")],
+            ),
+        ];
+
+        for (db_type, sql, expect_schema_tbs) in cases {
+            let mut ddl_data = DdlParser::new(db_type).parse(sql).unwrap().unwrap();
+            ddl_data.default_schema = "db_0".into();
+            assert_eq!(
+                ddl_data.get_referenced_schema_tbs(),
+                to_schema_tbs(&expect_schema_tbs)
+            );
+        }
+    }
 }
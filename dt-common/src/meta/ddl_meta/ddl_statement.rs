@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
 use crate::{config::config_enums::DbType, utils::sql_util::SqlUtil};
@@ -7,6 +9,7 @@ pub enum DdlStatement {
     CreateDatabase(CreateDatabaseStatement),
     DropDatabase(DropDatabaseStatement),
     AlterDatabase(AlterDatabaseStatement),
+    AlterDatabaseRename(AlterDatabaseRenameStatement),
 
     CreateSchema(CreateSchemaStatement),
     DropSchema(DropSchemaStatement),
@@ -23,8 +26,11 @@ pub enum DdlStatement {
     PgAlterTable(PgAlterTableStatement),
     PgAlterTableRename(PgAlterTableRenameStatement),
     PgAlterTableSetSchema(PgAlterTableSetSchemaStatement),
+    PgAlterViewRename(PgAlterViewRenameStatement),
     PgTruncateTable(PgTruncateTableStatement),
     PgCreateIndex(PgCreateIndexStatement),
+    PgCreateMaterializedView(PgCreateMaterializedViewStatement),
+    PgRefreshMaterializedView(PgRefreshMaterializedViewStatement),
 
     DropMultiTable(DropMultiTableStatement),
     RenameMultiTable(RenameMultiTableStatement),
@@ -85,11 +91,22 @@ impl DdlStatement {
         res
     }
 
+    // best effort: ALTER TABLE tb ADD INDEX a(...), ADD INDEX b(...)
+    pub fn split_add_indexes(self) -> Vec<DdlStatement> {
+        if let DdlStatement::MysqlAlterTable(s) = &self {
+            if let Some(statements) = split_mysql_add_indexes(s) {
+                return statements;
+            }
+        }
+        vec![self]
+    }
+
     pub fn get_schema_tb(&self) -> (String, String) {
         match self {
             DdlStatement::CreateDatabase(s) => (s.db.clone(), String::new()),
             DdlStatement::DropDatabase(s) => (s.db.clone(), String::new()),
             DdlStatement::AlterDatabase(s) => (s.db.clone(), String::new()),
+            DdlStatement::AlterDatabaseRename(s) => (s.db.clone(), String::new()),
 
             DdlStatement::CreateSchema(s) => (s.schema.clone(), String::new()),
             DdlStatement::DropSchema(s) => (s.schema.clone(), String::new()),
@@ -105,6 +122,8 @@ impl DdlStatement {
             DdlStatement::PgAlterTable(s) => (s.schema.clone(), s.tb.clone()),
             DdlStatement::PgTruncateTable(s) => (s.schema.clone(), s.tb.clone()),
             DdlStatement::PgCreateIndex(s) => (s.schema.clone(), s.tb.clone()),
+            DdlStatement::PgCreateMaterializedView(s) => (s.schema.clone(), s.name.clone()),
+            DdlStatement::PgRefreshMaterializedView(s) => (s.schema.clone(), s.name.clone()),
 
             DdlStatement::DropTable(s) => (s.schema.clone(), s.tb.clone()),
 
@@ -112,6 +131,7 @@ impl DdlStatement {
             DdlStatement::MysqlAlterTableRename(s) => (s.db.clone(), s.tb.clone()),
             DdlStatement::PgAlterTableRename(s) => (s.schema.clone(), s.tb.clone()),
             DdlStatement::PgAlterTableSetSchema(s) => (s.schema.clone(), s.tb.clone()),
+            DdlStatement::PgAlterViewRename(s) => (s.schema.clone(), s.tb.clone()),
 
             DdlStatement::PgDropIndex(_)
             | DdlStatement::PgDropMultiIndex(_)
@@ -123,13 +143,79 @@ impl DdlStatement {
 
     pub fn get_rename_to_schema_tb(&self) -> (String, String) {
         match self {
+            DdlStatement::AlterDatabaseRename(s) => (s.new_db.clone(), String::new()),
             DdlStatement::RenameTable(s) => (s.new_schema.clone(), s.new_tb.clone()),
             DdlStatement::MysqlAlterTableRename(s) => (s.new_db.clone(), s.new_tb.clone()),
             DdlStatement::PgAlterTableRename(s) => (s.new_schema.clone(), s.new_tb.clone()),
+            DdlStatement::PgAlterViewRename(s) => (s.new_schema.clone(), s.new_tb.clone()),
             _ => (String::new(), String::new()),
         }
     }
 
+    /// All schema.tables referenced by the statement: the target, both sides of renames,
+    /// each table of multi-table statements and the tables after FOREIGN KEY ... REFERENCES,
+    /// unqualified references take the schema of the statement, which may be empty.
+    pub fn get_referenced_schema_tbs(&self) -> Vec<(String, String)> {
+        fn push(res: &mut Vec<(String, String)>, schema_tb: (String, String)) {
+            if !res.contains(&schema_tb) {
+                res.push(schema_tb);
+            }
+        }
+
+        let mut res = Vec::new();
+
+        match self {
+            DdlStatement::DropMultiTable(s) => {
+                for schema_tb in s.schema_tbs.iter() {
+                    push(&mut res, schema_tb.clone());
+                }
+            }
+
+            DdlStatement::RenameMultiTable(s) => {
+                for (schema_tb, new_schema_tb) in s.schema_tbs.iter().zip(s.new_schema_tbs.iter()) {
+                    push(&mut res, schema_tb.clone());
+                    push(&mut res, new_schema_tb.clone());
+                }
+            }
+
+            DdlStatement::PgAlterTableSetSchema(s) => {
+                push(&mut res, (s.schema.clone(), s.tb.clone()));
+                push(&mut res, (s.new_schema.clone(), s.new_tb.clone()));
+            }
+
+            DdlStatement::PgDropIndex(_)
+            | DdlStatement::PgDropMultiIndex(_)
+            | DdlStatement::Unknown => {}
+
+            _ => {
+                push(&mut res, self.get_schema_tb());
+                let (new_schema, new_tb) = self.get_rename_to_schema_tb();
+                if !new_schema.is_empty() || !new_tb.is_empty() {
+                    push(&mut res, (new_schema, new_tb));
+                }
+            }
+        }
+
+        let (schema, unparsed, db_type) = match self {
+            DdlStatement::MysqlCreateTable(s) => (&s.db, &s.unparsed, DbType::Mysql),
+            DdlStatement::MysqlAlterTable(s) => (&s.db, &s.unparsed, DbType::Mysql),
+            DdlStatement::PgCreateTable(s) => (&s.schema, &s.unparsed, DbType::Pg),
+            DdlStatement::PgAlterTable(s) => (&s.schema, &s.unparsed, DbType::Pg),
+            _ => return res,
+        };
+        let chars: Vec<char> = unparsed.chars().collect();
+        for referenced in find_referenced_tables(&chars, &db_type) {
+            push(
+                &mut res,
+                (
+                    referenced.schema.unwrap_or_else(|| schema.clone()),
+                    referenced.tb,
+                ),
+            );
+        }
+        res
+    }
+
     pub fn route_rename_table(
         &mut self,
         dst_schema: String,
@@ -138,6 +224,11 @@ impl DdlStatement {
         dst_new_tb: String,
     ) {
         match self {
+            DdlStatement::AlterDatabaseRename(s) => {
+                s.db = dst_schema;
+                s.new_db = dst_new_schema;
+            }
+
             DdlStatement::MysqlAlterTableRename(s) => {
                 if !s.db.is_empty() {
                     s.db = dst_schema;
@@ -160,6 +251,17 @@ impl DdlStatement {
                 s.new_tb = dst_new_tb;
             }
 
+            DdlStatement::PgAlterViewRename(s) => {
+                if !s.schema.is_empty() {
+                    s.schema = dst_schema;
+                }
+                if !s.new_schema.is_empty() {
+                    s.new_schema = dst_new_schema;
+                }
+                s.tb = dst_tb;
+                s.new_tb = dst_new_tb;
+            }
+
             DdlStatement::RenameTable(s) => {
                 if !s.schema.is_empty() {
                     s.schema = dst_schema;
@@ -252,6 +354,18 @@ impl DdlStatement {
                 }
                 s.tb = dst_tb;
             }
+            DdlStatement::PgCreateMaterializedView(s) => {
+                if !s.schema.is_empty() {
+                    s.schema = dst_schema;
+                }
+                s.name = dst_tb;
+            }
+            DdlStatement::PgRefreshMaterializedView(s) => {
+                if !s.schema.is_empty() {
+                    s.schema = dst_schema;
+                }
+                s.name = dst_tb;
+            }
 
             DdlStatement::DropTable(s) => {
                 if !s.schema.is_empty() {
@@ -261,10 +375,12 @@ impl DdlStatement {
             }
 
             // not supported
-            DdlStatement::RenameTable(_)
+            DdlStatement::AlterDatabaseRename(_)
+            | DdlStatement::RenameTable(_)
             | DdlStatement::MysqlAlterTableRename(_)
             | DdlStatement::PgAlterTableRename(_)
             | DdlStatement::PgAlterTableSetSchema(_)
+            | DdlStatement::PgAlterViewRename(_)
             | DdlStatement::PgDropIndex(_)
             | DdlStatement::PgDropMultiIndex(_)
             | DdlStatement::DropMultiTable(_)
@@ -272,12 +388,59 @@ impl DdlStatement {
             | DdlStatement::Unknown => {}
         }
     }
+
+    /// Renames the cols referenced in the unparsed clauses of ALTER TABLE,
+    /// col_map: HashMap<src_col, dst_col>, both quoted and unquoted identifiers are renamed,
+    /// string literals are kept as is.
+    pub fn route_columns(&mut self, col_map: &HashMap<String, String>) {
+        if col_map.is_empty() {
+            return;
+        }
+        match self {
+            DdlStatement::MysqlAlterTable(s) => {
+                s.unparsed = route_cols_in_unparsed(&s.unparsed, col_map, &DbType::Mysql);
+            }
+            DdlStatement::PgAlterTable(s) => {
+                s.unparsed = route_cols_in_unparsed(&s.unparsed, col_map, &DbType::Pg);
+            }
+            _ => {}
+        }
+    }
+
+    /// Renames the tables referenced by FOREIGN KEY ... REFERENCES in the unparsed clauses of ALTER TABLE,
+    /// src_schema: schema of the altered table in source, used to resolve unqualified references,
+    /// dst_schema: schema of the altered table after routing, unqualified references which
+    /// are routed to another schema will be qualified,
+    /// tb_map: (src_schema, src_tb) -> (dst_schema, dst_tb).
+    pub fn route_referenced_tables<F>(&mut self, src_schema: &str, dst_schema: &str, tb_map: F)
+    where
+        F: Fn(&str, &str) -> (String, String),
+    {
+        let (unparsed, db_type) = match self {
+            DdlStatement::MysqlAlterTable(s) => (&mut s.unparsed, DbType::Mysql),
+            DdlStatement::PgAlterTable(s) => (&mut s.unparsed, DbType::Pg),
+            _ => return,
+        };
+        *unparsed = route_referenced_tables_in_unparsed(
+            unparsed, src_schema, dst_schema, &tb_map, &db_type,
+        );
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DdlToSqlOptions {
+    // add IF NOT EXISTS to CREATE statements which support it
+    pub force_if_not_exists: bool,
+    // add IF EXISTS to DROP / ALTER statements which support it
+    pub force_if_exists: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
 pub struct CreateDatabaseStatement {
     pub db: String,
     pub if_not_exists: bool,
+    pub charset: Option<String>,
+    pub collate: Option<String>,
     pub unparsed: String,
 }
 
@@ -294,6 +457,13 @@ pub struct AlterDatabaseStatement {
     pub unparsed: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct AlterDatabaseRenameStatement {
+    pub db: String,
+    pub new_db: String,
+    pub unparsed: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
 pub struct CreateSchemaStatement {
     pub schema: String,
@@ -322,6 +492,10 @@ pub struct MysqlCreateTableStatement {
     pub tb: String,
     pub if_not_exists: bool,
     pub unparsed: String,
+    // table level COMMENT, the clause is cut out of the sql,
+    // unparsed is the part before it and unparsed_after_comment the part after it
+    pub comment: Option<String>,
+    pub unparsed_after_comment: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
@@ -396,6 +570,17 @@ pub struct PgAlterTableSetSchemaStatement {
     pub unparsed: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct PgAlterViewRenameStatement {
+    pub schema: String,
+    pub tb: String,
+    pub new_schema: String,
+    pub new_tb: String,
+    pub if_exists: bool,
+    pub is_materialized: bool,
+    pub unparsed: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
 pub struct MysqlTruncateTableStatement {
     pub db: String,
@@ -449,6 +634,22 @@ pub struct PgCreateIndexStatement {
     pub unparsed: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct PgCreateMaterializedViewStatement {
+    pub schema: String,
+    pub name: String,
+    pub if_not_exists: bool,
+    pub unparsed: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct PgRefreshMaterializedViewStatement {
+    pub schema: String,
+    pub name: String,
+    pub is_concurrently: bool,
+    pub unparsed: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
 pub struct MysqlDropIndexStatement {
     pub db: String,
@@ -474,6 +675,47 @@ pub struct PgDropIndexStatement {
 }
 
 impl DdlStatement {
+    pub fn to_sql_with_options(&self, db_type: &DbType, opts: &DdlToSqlOptions) -> String {
+        if !opts.force_if_not_exists && !opts.force_if_exists {
+            return self.to_sql(db_type);
+        }
+
+        // only flip the flags, so statements already having the clause stay unchanged
+        let mut statement = self.clone();
+        match &mut statement {
+            // pg does not support CREATE DATABASE IF NOT EXISTS
+            DdlStatement::CreateDatabase(s) if *db_type != DbType::Pg => {
+                s.if_not_exists |= opts.force_if_not_exists
+            }
+            DdlStatement::CreateSchema(s) => s.if_not_exists |= opts.force_if_not_exists,
+            DdlStatement::MysqlCreateTable(s) => s.if_not_exists |= opts.force_if_not_exists,
+            DdlStatement::PgCreateTable(s) => s.if_not_exists |= opts.force_if_not_exists,
+            // IF NOT EXISTS requires an index name in pg
+            DdlStatement::PgCreateIndex(s) if s.index_name.is_some() => {
+                s.if_not_exists |= opts.force_if_not_exists
+            }
+            DdlStatement::PgCreateMaterializedView(s) => {
+                s.if_not_exists |= opts.force_if_not_exists
+            }
+
+            DdlStatement::DropDatabase(s) => s.if_exists |= opts.force_if_exists,
+            DdlStatement::DropSchema(s) => s.if_exists |= opts.force_if_exists,
+            DdlStatement::DropMultiTable(s) => s.if_exists |= opts.force_if_exists,
+            DdlStatement::DropTable(s) => s.if_exists |= opts.force_if_exists,
+            DdlStatement::PgDropMultiIndex(s) => s.if_exists |= opts.force_if_exists,
+            DdlStatement::PgDropIndex(s) => s.if_exists |= opts.force_if_exists,
+
+            DdlStatement::PgAlterTable(s) => s.if_exists |= opts.force_if_exists,
+            DdlStatement::PgAlterTableRename(s) => s.if_exists |= opts.force_if_exists,
+            DdlStatement::PgAlterTableSetSchema(s) => s.if_exists |= opts.force_if_exists,
+            DdlStatement::PgAlterViewRename(s) => s.if_exists |= opts.force_if_exists,
+
+            // mysql has no IF [NOT] EXISTS for ALTER TABLE / CREATE INDEX / DROP INDEX
+            _ => {}
+        }
+        statement.to_sql(db_type)
+    }
+
     pub fn to_sql(&self, db_type: &DbType) -> String {
         match self {
             DdlStatement::CreateDatabase(s) => {
@@ -482,6 +724,18 @@ impl DdlStatement {
                     sql = format!("{} IF NOT EXISTS", sql);
                 }
                 sql = append_identifier(&sql, &s.db, true, db_type);
+                if let Some(charset) = &s.charset {
+                    sql = match db_type {
+                        DbType::Pg => format!("{} ENCODING '{}'", sql, charset),
+                        _ => format!("{} CHARACTER SET {}", sql, charset),
+                    };
+                }
+                if let Some(collate) = &s.collate {
+                    sql = match db_type {
+                        DbType::Pg => format!("{} LC_COLLATE '{}'", sql, collate),
+                        _ => format!("{} COLLATE {}", sql, collate),
+                    };
+                }
                 append_unparsed(sql, &s.unparsed)
             }
 
@@ -500,6 +754,15 @@ impl DdlStatement {
                 append_unparsed(sql, &s.unparsed)
             }
 
+            // mysql has no ALTER DATABASE ... RENAME TO, it is skipped as an empty sql
+            DdlStatement::AlterDatabaseRename(s) if *db_type == DbType::Pg => {
+                let mut sql = "ALTER DATABASE".to_string();
+                sql = append_identifier(&sql, &s.db, true, db_type);
+                sql = format!("{} RENAME TO", sql);
+                sql = append_identifier(&sql, &s.new_db, true, db_type);
+                append_unparsed(sql, &s.unparsed)
+            }
+
             DdlStatement::CreateSchema(s) => {
                 let mut sql = "CREATE SCHEMA".to_string();
                 if s.if_not_exists {
@@ -538,7 +801,11 @@ impl DdlStatement {
                     sql = format!("{} IF NOT EXISTS", sql);
                 }
                 sql = append_tb(&sql, &s.db, &s.tb, db_type);
-                append_unparsed(sql, &s.unparsed)
+                sql = append_unparsed(sql, &s.unparsed);
+                if let Some(comment) = &s.comment {
+                    sql = format!("{} COMMENT='{}'", sql, escape_mysql_string(comment));
+                }
+                format!("{}{}", sql, s.unparsed_after_comment)
             }
 
             DdlStatement::PgCreateTable(s) => {
@@ -633,6 +900,21 @@ impl DdlStatement {
                 append_unparsed(sql, &s.unparsed)
             }
 
+            DdlStatement::PgAlterViewRename(s) => {
+                let mut sql = "ALTER".to_string();
+                if s.is_materialized {
+                    sql = format!("{} MATERIALIZED", sql);
+                }
+                sql = format!("{} VIEW", sql);
+                if s.if_exists {
+                    sql = format!("{} IF EXISTS", sql);
+                }
+                sql = append_tb(&sql, &s.schema, &s.tb, db_type);
+                sql = format!("{} RENAME TO", sql);
+                sql = append_tb(&sql, &s.new_schema, &s.new_tb, db_type);
+                append_unparsed(sql, &s.unparsed)
+            }
+
             DdlStatement::RenameMultiTable(s) => s.to_sql(db_type),
 
             DdlStatement::RenameTable(s) => {
@@ -682,6 +964,24 @@ impl DdlStatement {
                 append_unparsed(sql, &s.unparsed)
             }
 
+            DdlStatement::PgCreateMaterializedView(s) => {
+                let mut sql = "CREATE MATERIALIZED VIEW".to_string();
+                if s.if_not_exists {
+                    sql = format!("{} IF NOT EXISTS", sql);
+                }
+                sql = append_tb(&sql, &s.schema, &s.name, db_type);
+                append_unparsed(sql, &s.unparsed)
+            }
+
+            DdlStatement::PgRefreshMaterializedView(s) => {
+                let mut sql = "REFRESH MATERIALIZED VIEW".to_string();
+                if s.is_concurrently {
+                    sql = format!("{} CONCURRENTLY", sql);
+                }
+                sql = append_tb(&sql, &s.schema, &s.name, db_type);
+                append_unparsed(sql, &s.unparsed)
+            }
+
             DdlStatement::MysqlDropIndex(s) => {
                 let mut sql = "DROP INDEX".to_string();
                 sql = append_identifier(&sql, &s.index_name, true, db_type);
@@ -712,6 +1012,15 @@ impl DdlStatement {
             DdlStatement::CreateDatabase(create_database_statement) => {
                 size += create_database_statement.db.len() as u64;
                 size += create_database_statement.unparsed.len() as u64;
+                size += std::mem::size_of::<Option<String>>() as u64 * 2;
+                size += create_database_statement
+                    .charset
+                    .as_ref()
+                    .map_or(0, |s| s.len() as u64);
+                size += create_database_statement
+                    .collate
+                    .as_ref()
+                    .map_or(0, |s| s.len() as u64);
                 size += 1;
             }
             DdlStatement::DropDatabase(drop_database_statement) => {
@@ -723,6 +1032,11 @@ impl DdlStatement {
                 size += alter_database_statement.db.len() as u64;
                 size += alter_database_statement.unparsed.len() as u64;
             }
+            DdlStatement::AlterDatabaseRename(alter_database_rename_statement) => {
+                size += alter_database_rename_statement.db.len() as u64;
+                size += alter_database_rename_statement.new_db.len() as u64;
+                size += alter_database_rename_statement.unparsed.len() as u64;
+            }
             DdlStatement::CreateSchema(create_schema_statement) => {
                 size += create_schema_statement.schema.len() as u64;
                 size += create_schema_statement.unparsed.len() as u64;
@@ -747,6 +1061,12 @@ impl DdlStatement {
                 size += mysql_create_table_statement.tb.len() as u64;
                 size += mysql_create_table_statement.unparsed.len() as u64;
                 size += 1;
+                size += std::mem::size_of::<Option<String>>() as u64;
+                size += mysql_create_table_statement
+                    .comment
+                    .as_ref()
+                    .map_or(0, |s| s.len() as u64);
+                size += mysql_create_table_statement.unparsed_after_comment.len() as u64;
             }
             DdlStatement::MysqlAlterTable(mysql_alter_table_statement) => {
                 size += mysql_alter_table_statement.db.len() as u64;
@@ -802,6 +1122,14 @@ impl DdlStatement {
                 size += pg_alter_table_set_schema_statement.unparsed.len() as u64;
                 size += 2;
             }
+            DdlStatement::PgAlterViewRename(pg_alter_view_rename_statement) => {
+                size += pg_alter_view_rename_statement.schema.len() as u64;
+                size += pg_alter_view_rename_statement.tb.len() as u64;
+                size += pg_alter_view_rename_statement.new_schema.len() as u64;
+                size += pg_alter_view_rename_statement.new_tb.len() as u64;
+                size += pg_alter_view_rename_statement.unparsed.len() as u64;
+                size += 2;
+            }
             DdlStatement::PgTruncateTable(pg_truncate_table_statement) => {
                 size += pg_truncate_table_statement.schema.len() as u64;
                 size += pg_truncate_table_statement.tb.len() as u64;
@@ -819,6 +1147,18 @@ impl DdlStatement {
                     .map_or(0, |s| s.len() as u64);
                 size += 4;
             }
+            DdlStatement::PgCreateMaterializedView(pg_create_materialized_view_statement) => {
+                size += pg_create_materialized_view_statement.schema.len() as u64;
+                size += pg_create_materialized_view_statement.name.len() as u64;
+                size += pg_create_materialized_view_statement.unparsed.len() as u64;
+                size += 1;
+            }
+            DdlStatement::PgRefreshMaterializedView(pg_refresh_materialized_view_statement) => {
+                size += pg_refresh_materialized_view_statement.schema.len() as u64;
+                size += pg_refresh_materialized_view_statement.name.len() as u64;
+                size += pg_refresh_materialized_view_statement.unparsed.len() as u64;
+                size += 1;
+            }
             DdlStatement::PgDropIndex(pg_drop_index_statement) => {
                 size += pg_drop_index_statement.index_name.len() as u64;
                 size += pg_drop_index_statement.unparsed.len() as u64;
@@ -987,6 +1327,601 @@ fn append_unparsed(sql: String, unparsed: &str) -> String {
     sql
 }
 
+// route the cols of ALTER TABLE clauses, only identifiers in column positions are routed:
+// the targets of ADD / MODIFY / CHANGE / ALTER / DROP [COLUMN], RENAME COLUMN, AFTER
+// and the column lists of index / key definitions,
+// type names, index names, keywords and literals which equal a col are kept
+fn route_cols_in_unparsed(
+    unparsed: &str,
+    col_map: &HashMap<String, String>,
+    db_type: &DbType,
+) -> String {
+    let chars: Vec<char> = unparsed.chars().collect();
+    let tokens = tokenize_unparsed(&chars, db_type);
+
+    // start of the routed identifier -> escaped dst col
+    let mut routed = HashMap::new();
+    let mut route = |token: Option<&UnparsedToken>| {
+        let Some(UnparsedToken::Identifier { name, start, .. }) = token else {
+            return;
+        };
+        let dst_col = match db_type {
+            // unquoted identifiers were folded to lower case by the tokenizer
+            DbType::Pg => col_map.get(name),
+            // mysql column names are case-insensitive
+            _ => col_map
+                .iter()
+                .find(|(src_col, _)| src_col.eq_ignore_ascii_case(name))
+                .map(|(_, dst_col)| dst_col),
+        };
+        if let Some(dst_col) = dst_col {
+            routed.insert(*start, SqlUtil::escape_by_db_type(dst_col, db_type));
+        }
+    };
+
+    for clause in split_tokens_by_top_level_commas(&tokens) {
+        let keyword = |i: usize| match clause.get(i) {
+            Some(UnparsedToken::Identifier {
+                name,
+                quoted: false,
+                ..
+            }) => name.to_uppercase(),
+            _ => String::new(),
+        };
+        let skip_keywords = |mut i: usize, keywords: &[&str]| {
+            for k in keywords {
+                if keyword(i) != *k {
+                    return None;
+                }
+                i += 1;
+            }
+            Some(i)
+        };
+
+        let mut i = 1;
+        let with_column = keyword(i) == "COLUMN";
+        if with_column {
+            i += 1;
+        }
+        match keyword(0).as_str() {
+            "ADD" => {
+                i = skip_keywords(i, &["IF", "NOT", "EXISTS"]).unwrap_or(i);
+                let is_index = !with_column
+                    && matches!(
+                        keyword(i).as_str(),
+                        "INDEX"
+                            | "KEY"
+                            | "UNIQUE"
+                            | "PRIMARY"
+                            | "FULLTEXT"
+                            | "SPATIAL"
+                            | "FOREIGN"
+                            | "CONSTRAINT"
+                            | "CHECK"
+                    );
+                if is_index {
+                    for token in index_key_cols(&clause[i..]) {
+                        route(Some(token));
+                    }
+                } else {
+                    route(clause.get(i).copied());
+                    route_after_col(&clause[i..], &mut route);
+                }
+            }
+            "MODIFY" => {
+                route(clause.get(i).copied());
+                route_after_col(&clause[i..], &mut route);
+            }
+            "CHANGE" => {
+                route(clause.get(i).copied());
+                route(clause.get(i + 1).copied());
+                route_after_col(&clause[i..], &mut route);
+            }
+            "DROP" => {
+                i = skip_keywords(i, &["IF", "EXISTS"]).unwrap_or(i);
+                let is_col = with_column
+                    || !matches!(
+                        keyword(i).as_str(),
+                        "INDEX"
+                            | "KEY"
+                            | "PRIMARY"
+                            | "FOREIGN"
+                            | "CONSTRAINT"
+                            | "CHECK"
+                            | "PARTITION"
+                            | "DEFAULT"
+                    );
+                if is_col {
+                    route(clause.get(i).copied());
+                }
+            }
+            "ALTER" => {
+                let is_col = with_column
+                    || !matches!(
+                        keyword(i).as_str(),
+                        "INDEX" | "CONSTRAINT" | "CHECK" | "PARTITION"
+                    );
+                if is_col {
+                    route(clause.get(i).copied());
+                }
+            }
+            "RENAME" => {
+                // RENAME COLUMN old TO new, pg also supports RENAME old TO new
+                let is_col = with_column
+                    || (*db_type == DbType::Pg
+                        && !matches!(keyword(i).as_str(), "TO" | "AS" | "CONSTRAINT"));
+                if is_col && keyword(i + 1) == "TO" {
+                    route(clause.get(i).copied());
+                    route(clause.get(i + 2).copied());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut result = String::with_capacity(unparsed.len());
+    let mut i = 0;
+    for token in &tokens {
+        if let UnparsedToken::Identifier { start, end, .. } = token {
+            if let Some(dst_col) = routed.get(start) {
+                result.extend(&chars[i..*start]);
+                result.push_str(dst_col);
+                i = *end;
+            }
+        }
+    }
+    result.extend(&chars[i..]);
+    result
+}
+
+// the cols in the first parenthesized list of an index / key definition, e.g.
+// [CONSTRAINT [symbol]] FOREIGN KEY [index_name] (col, ...) REFERENCES tb (col, ...),
+// expressions such as ((col + 1)) and CHECK (expr) are not routed
+fn index_key_cols<'a>(tokens: &'a [&'a UnparsedToken]) -> Vec<&'a UnparsedToken> {
+    let mut res = Vec::new();
+    let Some(open) = tokens
+        .iter()
+        .position(|t| matches!(t, UnparsedToken::Punct('(')))
+    else {
+        return res;
+    };
+    if tokens[..open].iter().any(|t| is_keyword(t, "check")) {
+        return res;
+    }
+
+    // the first token of each key part: col_name [(length)] [ASC | DESC]
+    let mut depth = 0;
+    let mut part_start = true;
+    for token in &tokens[open + 1..] {
+        match token {
+            UnparsedToken::Punct('(') => depth += 1,
+            UnparsedToken::Punct(')') if depth == 0 => break,
+            UnparsedToken::Punct(')') => depth -= 1,
+            UnparsedToken::Punct(',') if depth == 0 => {
+                part_start = true;
+                continue;
+            }
+            UnparsedToken::Identifier { .. } if depth == 0 && part_start => res.push(*token),
+            _ => {}
+        }
+        part_start = false;
+    }
+    res
+}
+
+// [FIRST | AFTER col_name] in a column definition
+fn route_after_col<'a, F>(tokens: &[&'a UnparsedToken], route: &mut F)
+where
+    F: FnMut(Option<&'a UnparsedToken>),
+{
+    for (i, token) in tokens.iter().enumerate() {
+        if is_keyword(token, "after") {
+            route(tokens.get(i + 1).copied());
+        }
+    }
+}
+
+fn is_keyword(token: &UnparsedToken, keyword: &str) -> bool {
+    match token {
+        UnparsedToken::Identifier {
+            name,
+            quoted: false,
+            ..
+        } => name.eq_ignore_ascii_case(keyword),
+        _ => false,
+    }
+}
+
+enum UnparsedToken {
+    Literal,
+    Punct(char),
+    // chars[start..end] is the original text, unquoted pg identifiers are folded to lower case
+    Identifier {
+        name: String,
+        quoted: bool,
+        start: usize,
+        end: usize,
+    },
+}
+
+fn tokenize_unparsed(chars: &[char], db_type: &DbType) -> Vec<UnparsedToken> {
+    let (identifier_quote, literal_quotes): (char, &[char]) = match db_type {
+        DbType::Pg => ('"', &['\'']),
+        _ => ('`', &['\'', '"']),
+    };
+
+    let mut res = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if literal_quotes.contains(&c) {
+            // string literal, doubled quotes or backslash escapes inside
+            i += 1;
+            while i < chars.len() {
+                if chars[i] == '\\' && i + 1 < chars.len() {
+                    i += 2;
+                } else if chars[i] == c && i + 1 < chars.len() && chars[i + 1] == c {
+                    i += 2;
+                } else if chars[i] == c {
+                    i += 1;
+                    break;
+                } else {
+                    i += 1;
+                }
+            }
+            res.push(UnparsedToken::Literal);
+        } else if c == identifier_quote || is_unquoted_identifier_char(c) {
+            let start = i;
+            let Some((name, end)) = read_identifier(chars, i, db_type) else {
+                // an unclosed quote takes the rest
+                break;
+            };
+            res.push(UnparsedToken::Identifier {
+                name,
+                quoted: c == identifier_quote,
+                start,
+                end,
+            });
+            i = end;
+        } else {
+            if !c.is_whitespace() {
+                res.push(UnparsedToken::Punct(c));
+            }
+            i += 1;
+        }
+    }
+    res
+}
+
+fn split_tokens_by_top_level_commas(tokens: &[UnparsedToken]) -> Vec<Vec<&UnparsedToken>> {
+    let mut res = vec![Vec::new()];
+    let mut depth = 0;
+    for token in tokens {
+        match token {
+            UnparsedToken::Punct('(') => depth += 1,
+            UnparsedToken::Punct(')') => depth -= 1,
+            UnparsedToken::Punct(',') if depth == 0 => {
+                res.push(Vec::new());
+                continue;
+            }
+            _ => {}
+        }
+        res.last_mut().unwrap().push(token);
+    }
+    res
+}
+
+// split the unparsed of ALTER TABLE into CREATE INDEX statements,
+// only if it consists of multiple ADD INDEX clauses which can all be parsed, otherwise return None
+fn split_mysql_add_indexes(s: &MysqlAlterTableStatement) -> Option<Vec<DdlStatement>> {
+    let clauses = split_top_level_commas(&s.unparsed)?;
+    if clauses.len() < 2 {
+        return None;
+    }
+    clauses
+        .iter()
+        .map(|clause| {
+            parse_mysql_add_index(clause, &s.db, &s.tb).map(DdlStatement::MysqlCreateIndex)
+        })
+        .collect()
+}
+
+fn split_top_level_commas(sql: &str) -> Option<Vec<String>> {
+    let mut res = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0;
+    let mut quote: Option<char> = None;
+    let mut chars = sql.chars();
+    while let Some(c) = chars.next() {
+        current.push(c);
+        if let Some(q) = quote {
+            if c == '\\' && q != '`' {
+                current.extend(chars.next());
+            } else if c == q {
+                // doubled quotes close and reopen the quote
+                quote = None;
+            }
+            continue;
+        }
+
+        match c {
+            '\'' | '"' | '`' => quote = Some(c),
+            '(' => depth += 1,
+            ')' if depth == 0 => return None,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                current.pop();
+                res.push(std::mem::take(&mut current));
+            }
+            _ => {}
+        }
+    }
+
+    if quote.is_some() || depth != 0 {
+        return None;
+    }
+    res.push(current);
+    Some(res)
+}
+
+// ADD [UNIQUE | FULLTEXT | SPATIAL] {INDEX | KEY} index_name [USING {BTREE | HASH}] (key_part,...) [index_option]
+fn parse_mysql_add_index(clause: &str, db: &str, tb: &str) -> Option<MysqlCreateIndexStatement> {
+    let chars: Vec<char> = clause.trim().chars().collect();
+    let skip_whitespace = |mut j: usize| {
+        while j < chars.len() && chars[j].is_whitespace() {
+            j += 1;
+        }
+        j
+    };
+    let read_word = |j: usize| -> (String, usize) {
+        let start = skip_whitespace(j);
+        let mut end = start;
+        while end < chars.len() && is_unquoted_identifier_char(chars[end]) {
+            end += 1;
+        }
+        (chars[start..end].iter().collect(), end)
+    };
+    let is_index_keyword =
+        |word: &str| word.eq_ignore_ascii_case("index") || word.eq_ignore_ascii_case("key");
+
+    let (word, mut i) = read_word(0);
+    if !word.eq_ignore_ascii_case("add") {
+        return None;
+    }
+
+    let mut index_kind = None;
+    let (word, end) = read_word(i);
+    if ["unique", "fulltext", "spatial"]
+        .iter()
+        .any(|kind| word.eq_ignore_ascii_case(kind))
+    {
+        index_kind = Some(word);
+        i = end;
+        let (word, end) = read_word(i);
+        if is_index_keyword(&word) {
+            i = end;
+        }
+    } else if is_index_keyword(&word) {
+        i = end;
+    } else {
+        return None;
+    }
+
+    // CREATE INDEX requires the index name
+    let (word, _) = read_word(i);
+    if word.eq_ignore_ascii_case("using") {
+        return None;
+    }
+    let (index_name, end) = read_identifier(&chars, skip_whitespace(i), &DbType::Mysql)?;
+    i = end;
+
+    let mut index_type = None;
+    let (word, end) = read_word(i);
+    if word.eq_ignore_ascii_case("using") {
+        let (word, end) = read_word(end);
+        if !word.eq_ignore_ascii_case("btree") && !word.eq_ignore_ascii_case("hash") {
+            return None;
+        }
+        index_type = Some(word);
+        i = end;
+    }
+
+    let start = skip_whitespace(i);
+    if start >= chars.len() || chars[start] != '(' {
+        return None;
+    }
+
+    Some(MysqlCreateIndexStatement {
+        db: db.to_string(),
+        tb: tb.to_string(),
+        index_name,
+        index_kind,
+        index_type,
+        unparsed: chars[start..].iter().collect(),
+    })
+}
+
+fn route_referenced_tables_in_unparsed<F>(
+    unparsed: &str,
+    src_schema: &str,
+    dst_schema: &str,
+    tb_map: &F,
+    db_type: &DbType,
+) -> String
+where
+    F: Fn(&str, &str) -> (String, String),
+{
+    let chars: Vec<char> = unparsed.chars().collect();
+    let mut result = String::with_capacity(unparsed.len());
+    let mut i = 0;
+    for referenced in find_referenced_tables(&chars, db_type) {
+        result.extend(&chars[i..referenced.start]);
+        let (dst_ref_schema, dst_ref_tb) = tb_map(
+            referenced.schema.as_deref().unwrap_or(src_schema),
+            &referenced.tb,
+        );
+        if referenced.schema.is_some() || dst_ref_schema != dst_schema {
+            result.push_str(&SqlUtil::escape_by_db_type(&dst_ref_schema, db_type));
+            result.push('.');
+        }
+        result.push_str(&SqlUtil::escape_by_db_type(&dst_ref_tb, db_type));
+        i = referenced.end;
+    }
+    result.extend(&chars[i..]);
+    result
+}
+
+// [schema.]tb following a REFERENCES keyword, chars[start..end] is the original text
+struct ReferencedTable {
+    schema: Option<String>,
+    tb: String,
+    start: usize,
+    end: usize,
+}
+
+fn find_referenced_tables(chars: &[char], db_type: &DbType) -> Vec<ReferencedTable> {
+    let (identifier_quote, literal_quotes): (char, &[char]) = match db_type {
+        DbType::Pg => ('"', &['\'']),
+        _ => ('`', &['\'', '"']),
+    };
+
+    let mut res = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if literal_quotes.contains(&c) {
+            i += 1;
+            while i < chars.len() {
+                if chars[i] == '\\' && i + 1 < chars.len() {
+                    i += 2;
+                } else if chars[i] == c && i + 1 < chars.len() && chars[i + 1] == c {
+                    i += 2;
+                } else if chars[i] == c {
+                    i += 1;
+                    break;
+                } else {
+                    i += 1;
+                }
+            }
+        } else if c == identifier_quote {
+            // an unclosed quote takes the rest
+            i = read_identifier(chars, i, db_type).map_or(chars.len(), |r| r.1);
+        } else if is_unquoted_identifier_char(c) {
+            let start = i;
+            while i < chars.len() && is_unquoted_identifier_char(chars[i]) {
+                i += 1;
+            }
+            let word: String = chars[start..i].iter().collect();
+            if !word.eq_ignore_ascii_case("references") {
+                continue;
+            }
+
+            let skip_whitespace = |mut j: usize| {
+                while j < chars.len() && chars[j].is_whitespace() {
+                    j += 1;
+                }
+                j
+            };
+            let start = skip_whitespace(i);
+            let Some((first, first_end)) = read_identifier(chars, start, db_type) else {
+                continue;
+            };
+            let dot = skip_whitespace(first_end);
+            let referenced = if dot < chars.len() && chars[dot] == '.' {
+                match read_identifier(chars, skip_whitespace(dot + 1), db_type) {
+                    Some((second, end)) => ReferencedTable {
+                        schema: Some(first),
+                        tb: second,
+                        start,
+                        end,
+                    },
+                    None => continue,
+                }
+            } else {
+                ReferencedTable {
+                    schema: None,
+                    tb: first,
+                    start,
+                    end: first_end,
+                }
+            };
+            i = referenced.end;
+            res.push(referenced);
+        } else {
+            i += 1;
+        }
+    }
+    res
+}
+
+// read a quoted or unquoted identifier starting at chars[i], return: (identifier, end),
+// unquoted pg identifiers are folded to lower case like DdlParser does
+fn read_identifier(chars: &[char], i: usize, db_type: &DbType) -> Option<(String, usize)> {
+    if i >= chars.len() {
+        return None;
+    }
+
+    let identifier_quote = match db_type {
+        DbType::Pg => '"',
+        _ => '`',
+    };
+    let mut identifier = String::new();
+    let mut j = i;
+    if chars[j] == identifier_quote {
+        j += 1;
+        while j < chars.len() {
+            if chars[j] == identifier_quote
+                && j + 1 < chars.len()
+                && chars[j + 1] == identifier_quote
+            {
+                identifier.push(identifier_quote);
+                j += 2;
+            } else if chars[j] == identifier_quote {
+                return Some((identifier, j + 1));
+            } else {
+                identifier.push(chars[j]);
+                j += 1;
+            }
+        }
+        // unclosed quote
+        return None;
+    }
+
+    while j < chars.len() && is_unquoted_identifier_char(chars[j]) {
+        identifier.push(chars[j]);
+        j += 1;
+    }
+    if identifier.is_empty() {
+        None
+    } else if *db_type == DbType::Pg {
+        Some((identifier.to_lowercase(), j))
+    } else {
+        Some((identifier, j))
+    }
+}
+
+fn is_unquoted_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+fn escape_mysql_string(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\'' => escaped.push_str("''"),
+            '\0' => escaped.push_str("\\0"),
+            '\x08' => escaped.push_str("\\b"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            '\x1a' => escaped.push_str("\\Z"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
 fn escape_identifier(identifier: &str, db_type: &DbType) -> String {
     SqlUtil::escape_by_db_type(identifier, db_type)
 }
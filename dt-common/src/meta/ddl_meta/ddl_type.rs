@@ -31,6 +31,12 @@ pub enum DdlType {
     CreateIndex,
     #[strum(serialize = "drop_index")]
     DropIndex,
+    #[strum(serialize = "create_materialized_view")]
+    CreateMaterializedView,
+    #[strum(serialize = "refresh_materialized_view")]
+    RefreshMaterializedView,
+    #[strum(serialize = "alter_view")]
+    AlterView,
     #[strum(serialize = "unknown")]
     Unknown,
 }
@@ -0,0 +1,328 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use chrono::{FixedOffset, Offset, Utc};
+use serde_json::{json, Value};
+
+use super::{cloudcanal_converter::get_jdbc_type_code, json_converter::format_timestamp};
+use crate::meta::{
+    col_value::ColValue,
+    ddl_meta::{ddl_data::DdlData, ddl_type::DdlType},
+    rdb_meta_manager::RdbMetaManager,
+    row_data::RowData,
+    row_type::RowType,
+};
+
+/// Canal 格式的 JSON 转换器
+/// 生成与阿里巴巴 Canal 一致的消息格式，包含 data、database、table、type、es、ts、sql、sqlType、mysqlType、old 等字段
+#[derive(Clone)]
+pub struct CanalConverter {
+    pub meta_manager: Option<RdbMetaManager>,
+    /// 连接的数据库名，用于 PostgreSQL 区分 database 和 schema；MySQL 中 db 即 schema，不保存
+    pub database_name: Option<String>,
+    /// Timestamp 类型的值按该时区格式化，默认 UTC
+    pub timezone: FixedOffset,
+}
+
+impl CanalConverter {
+    pub fn new(meta_manager: Option<RdbMetaManager>, database_name: Option<String>) -> Self {
+        let is_mysql = meta_manager
+            .as_ref()
+            .is_some_and(|m| m.mysql_meta_manager.is_some());
+        let database_name = if is_mysql { None } else { database_name };
+        CanalConverter {
+            meta_manager,
+            database_name,
+            timezone: Utc.fix(),
+        }
+    }
+
+    pub fn refresh_meta(&mut self, data: &[DdlData]) {
+        if let Some(meta_manager) = &mut self.meta_manager {
+            for ddl_data in data {
+                meta_manager.invalidate_cache_by_ddl_data(ddl_data);
+            }
+        }
+    }
+
+    pub async fn row_data_to_json_key(&mut self, row_data: &RowData) -> Result<String> {
+        if let Some(meta_manager) = &mut self.meta_manager {
+            if let Ok(tb_meta) = meta_manager
+                .get_tb_meta(&row_data.schema, &row_data.tb)
+                .await
+            {
+                if let Some(primary_key) = tb_meta.key_map.get("primary") {
+                    // delete 没有 after，使用 before 中的主键值
+                    let col_values = match row_data.row_type {
+                        RowType::Delete => row_data.require_before()?,
+                        _ => row_data.require_after()?,
+                    };
+                    let key_values: Vec<Value> = primary_key
+                        .iter()
+                        .map(|pk_col| {
+                            col_value_to_json_value(col_values.get(pk_col), &self.timezone)
+                        })
+                        .collect();
+                    return Ok(serde_json::to_string(&key_values)?);
+                }
+            }
+        }
+        Ok(format!("{}_{}", row_data.schema, row_data.tb))
+    }
+
+    pub async fn row_data_to_json_value(&mut self, row_data: RowData) -> Result<String> {
+        // insert/update 的 data 为 after，delete 的 data 为被删除的行
+        let (canal_type, data, old) = match row_data.row_type {
+            RowType::Insert => ("INSERT", row_data.require_after()?, None),
+            RowType::Update => {
+                let before = row_data.require_before()?;
+                let after = row_data.require_after()?;
+                (
+                    "UPDATE",
+                    after,
+                    Some(Self::changed_col_values_to_json_value(
+                        before,
+                        after,
+                        &self.timezone,
+                    )),
+                )
+            }
+            RowType::Delete => ("DELETE", row_data.require_before()?, None),
+        };
+
+        // PostgreSQL 使用连接的数据库名，MySQL 中 db 即 schema
+        let database_name = self
+            .database_name
+            .clone()
+            .unwrap_or_else(|| row_data.schema.clone());
+
+        let ts = chrono::Utc::now().timestamp_millis();
+        let mut json_obj = json!({
+            "data": [col_values_to_json_value(data, &self.timezone)],
+            "database": database_name,
+            "es": ts,
+            "id": 0,
+            "isDdl": false,
+            "mysqlType": {},
+            // Canal 中 old 只包含 update 前被修改的列
+            "old": old.map(|v| json!([v])),
+            "pkNames": null,
+            "sql": "",
+            "sqlType": {},
+            "table": row_data.tb,
+            "ts": ts,
+            "type": canal_type,
+        });
+
+        // 获取表的元数据信息，添加主键和字段类型信息
+        if let Some(meta_manager) = &mut self.meta_manager {
+            let is_mysql = meta_manager.mysql_meta_manager.is_some();
+            let is_pg = meta_manager.pg_meta_manager.is_some();
+
+            if let Ok(tb_meta) = meta_manager
+                .get_tb_meta(&row_data.schema, &row_data.tb)
+                .await
+            {
+                if let Some(primary_key) = tb_meta.key_map.get("primary") {
+                    json_obj["pkNames"] = json!(primary_key);
+                }
+
+                let mut mysql_type = serde_json::Map::new();
+                let mut sql_type = serde_json::Map::new();
+                for col_name in &tb_meta.cols {
+                    if let Some(col_origin_type) = tb_meta.col_origin_type_map.get(col_name) {
+                        if is_mysql || is_pg {
+                            mysql_type
+                                .insert(col_name.clone(), Value::String(col_origin_type.clone()));
+                            let jdbc_type_code = get_jdbc_type_code(col_origin_type, is_pg);
+                            sql_type.insert(col_name.clone(), Value::Number(jdbc_type_code.into()));
+                        }
+                    }
+                }
+                json_obj["mysqlType"] = Value::Object(mysql_type);
+                json_obj["sqlType"] = Value::Object(sql_type);
+            }
+        }
+
+        Ok(serde_json::to_string(&json_obj)?)
+    }
+
+    pub async fn ddl_data_to_json_value(&mut self, ddl_data: DdlData) -> Result<String> {
+        let canal_type = match ddl_data.ddl_type {
+            DdlType::CreateTable => "CREATE",
+            DdlType::AlterTable => "ALTER",
+            DdlType::DropTable => "ERASE",
+            DdlType::TruncateTable => "TRUNCATE",
+            DdlType::RenameTable => "RENAME",
+            DdlType::CreateIndex => "CINDEX",
+            DdlType::DropIndex => "DINDEX",
+            _ => "QUERY",
+        };
+        let (schema, tb) = ddl_data.get_schema_tb();
+        let database_name = self.database_name.clone().unwrap_or(schema);
+
+        let ts = chrono::Utc::now().timestamp_millis();
+        let json_obj = json!({
+            "data": null,
+            "database": database_name,
+            "es": ts,
+            "id": 0,
+            "isDdl": true,
+            "mysqlType": null,
+            "old": null,
+            "pkNames": null,
+            "sql": ddl_data.query,
+            "sqlType": null,
+            "table": tb,
+            "ts": ts,
+            "type": canal_type,
+        });
+
+        Ok(serde_json::to_string(&json_obj)?)
+    }
+
+    fn changed_col_values_to_json_value(
+        before: &HashMap<String, ColValue>,
+        after: &HashMap<String, ColValue>,
+        timezone: &FixedOffset,
+    ) -> Value {
+        let mut json_map = serde_json::Map::new();
+        for (key, value) in before {
+            if after.get(key) != Some(value) {
+                json_map.insert(key.clone(), col_value_to_json_value(Some(value), timezone));
+            }
+        }
+        Value::Object(json_map)
+    }
+}
+
+fn col_values_to_json_value(
+    col_values: &HashMap<String, ColValue>,
+    timezone: &FixedOffset,
+) -> Value {
+    let mut json_map = serde_json::Map::new();
+    for (key, value) in col_values {
+        json_map.insert(key.clone(), col_value_to_json_value(Some(value), timezone));
+    }
+    Value::Object(json_map)
+}
+
+/// Canal 中的列值均为字符串，NULL 为 null
+fn col_value_to_json_value(value: Option<&ColValue>, timezone: &FixedOffset) -> Value {
+    match value {
+        Some(ColValue::Timestamp(v)) => Value::String(format_timestamp(v, timezone)),
+        _ => value
+            .and_then(|v| v.to_option_string())
+            .map_or(Value::Null, Value::String),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_row_data(row_type: RowType) -> RowData {
+        let before = HashMap::from([
+            ("id".to_string(), ColValue::Long(1)),
+            ("name".to_string(), ColValue::String("a".into())),
+            ("note".to_string(), ColValue::None),
+        ]);
+        let after = HashMap::from([
+            ("id".to_string(), ColValue::Long(1)),
+            ("name".to_string(), ColValue::String("b".into())),
+            ("note".to_string(), ColValue::None),
+        ]);
+        let (before, after) = match row_type {
+            RowType::Insert => (None, Some(after)),
+            RowType::Update => (Some(before), Some(after)),
+            RowType::Delete => (Some(before), None),
+        };
+        RowData::new("db_1".into(), "tb_1".into(), 0, row_type, before, after)
+    }
+
+    async fn to_json(row_type: RowType) -> Value {
+        let mut converter = CanalConverter::new(None, None);
+        let json_str = converter
+            .row_data_to_json_value(build_row_data(row_type))
+            .await
+            .unwrap();
+        serde_json::from_str(&json_str).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_insert() {
+        let parsed = to_json(RowType::Insert).await;
+        assert_eq!(parsed["type"], "INSERT");
+        assert_eq!(parsed["database"], "db_1");
+        assert_eq!(parsed["table"], "tb_1");
+        assert_eq!(parsed["isDdl"], false);
+        assert_eq!(
+            parsed["data"],
+            json!([{"id": "1", "name": "b", "note": null}])
+        );
+        assert_eq!(parsed["old"], Value::Null);
+        assert_eq!(parsed["es"], parsed["ts"]);
+    }
+
+    #[tokio::test]
+    async fn test_update() {
+        let parsed = to_json(RowType::Update).await;
+        assert_eq!(parsed["type"], "UPDATE");
+        assert_eq!(
+            parsed["data"],
+            json!([{"id": "1", "name": "b", "note": null}])
+        );
+        // only the changed cols
+        assert_eq!(parsed["old"], json!([{"name": "a"}]));
+    }
+
+    #[tokio::test]
+    async fn test_delete() {
+        let parsed = to_json(RowType::Delete).await;
+        assert_eq!(parsed["type"], "DELETE");
+        assert_eq!(
+            parsed["data"],
+            json!([{"id": "1", "name": "a", "note": null}])
+        );
+        assert_eq!(parsed["old"], Value::Null);
+    }
+
+    #[tokio::test]
+    async fn test_timestamp_in_timezone() {
+        let after = HashMap::from([
+            (
+                "ts".to_string(),
+                ColValue::Timestamp("2024-01-01 20:00:00".into()),
+            ),
+            (
+                "tstz".to_string(),
+                ColValue::Timestamp("2024-01-01 20:00:00+00".into()),
+            ),
+            (
+                "dt".to_string(),
+                ColValue::DateTime("2024-01-01 20:00:00".into()),
+            ),
+        ]);
+        let row_data = RowData::new(
+            "db_1".into(),
+            "tb_1".into(),
+            0,
+            RowType::Insert,
+            None,
+            Some(after),
+        );
+
+        let mut converter = CanalConverter::new(None, None);
+        converter.timezone = "+08:00".parse().unwrap();
+        let json_str = converter.row_data_to_json_value(row_data).await.unwrap();
+        let parsed: Value = serde_json::from_str(&json_str).unwrap();
+        assert_eq!(
+            parsed["data"],
+            json!([{
+                "dt": "2024-01-01 20:00:00",
+                "ts": "2024-01-02 04:00:00",
+                "tstz": "2024-01-02 04:00:00+08",
+            }])
+        );
+    }
+}
@@ -2,12 +2,16 @@ use std::collections::HashMap;
 
 use anyhow::Result;
 use base64::{engine::general_purpose, Engine as _};
+use chrono::{FixedOffset, Offset, Utc};
+use mongodb::bson::Bson;
 use serde_json::{json, Value};
 
+use super::json_converter::format_timestamp;
 use crate::{
     meta::{
         col_value::ColValue,
         ddl_meta::ddl_data::DdlData,
+        mongo::mongo_constant::MongoConstants,
         rdb_meta_manager::RdbMetaManager,
         row_data::RowData,
         row_type::RowType,
@@ -19,12 +23,31 @@ use crate::{
 #[derive(Clone)]
 pub struct CloudCanalConverter {
     pub meta_manager: Option<RdbMetaManager>,
+    /// 连接的数据库名，用于 PostgreSQL 区分 database 和 schema；MySQL 中 db 即 schema，不保存
     pub database_name: Option<String>,
+    /// Timestamp 类型的值按该时区格式化，默认 UTC
+    pub timezone: FixedOffset,
+    /// 当前批次的 bid，每个批次开始时递增，同一批次内的消息共享；从 0 开始，任务重启后重置
+    pub bid: u64,
 }
 
 impl CloudCanalConverter {
     pub fn new(meta_manager: Option<RdbMetaManager>, database_name: Option<String>) -> Self {
-        CloudCanalConverter { meta_manager, database_name }
+        let is_mysql = meta_manager
+            .as_ref()
+            .is_some_and(|m| m.mysql_meta_manager.is_some());
+        let database_name = if is_mysql { None } else { database_name };
+        CloudCanalConverter {
+            meta_manager,
+            database_name,
+            timezone: Utc.fix(),
+            bid: 0,
+        }
+    }
+
+    /// 开始新的批次，之后的消息使用新的 bid
+    pub fn next_batch(&mut self) {
+        self.bid += 1;
     }
 
     pub fn refresh_meta(&mut self, data: &[DdlData]) {
@@ -36,13 +59,18 @@ impl CloudCanalConverter {
     }
 
     pub async fn row_data_to_json_key(&mut self, row_data: &RowData) -> Result<String> {
+        // MongoDB 没有表元数据，使用文档的 _id 作为 key
+        if let Some(id) = get_mongo_id(row_data) {
+            return Ok(serde_json::to_string(&json!([id]))?);
+        }
+
         if let Some(meta_manager) = &mut self.meta_manager {
             if let Ok(tb_meta) = meta_manager.get_tb_meta(&row_data.schema, &row_data.tb).await {
                 if let Some(primary_key) = tb_meta.key_map.get("primary") {
                     let mut key_values = Vec::new();
                     for pk_col in primary_key {
                         if let Some(col_value) = row_data.after.as_ref().and_then(|after| after.get(pk_col)) {
-                            key_values.push(col_value_to_json_value(col_value));
+                            key_values.push(col_value_to_json_value(col_value, &self.timezone));
                         }
                     }
                     return Ok(serde_json::to_string(&key_values)?);
@@ -60,22 +88,15 @@ impl CloudCanalConverter {
             RowType::Delete => "DELETE",
         };
 
-        // 优先使用配置中的数据库名；若未提供则回退按现有逻辑
-        let database_name = if let Some(db) = &self.database_name {
-            db.clone()
-        } else if let Some(meta_manager) = &self.meta_manager {
-            if meta_manager.mysql_meta_manager.is_some() {
-                row_data.schema.clone()
-            } else {
-                row_data.schema.clone()
-            }
-        } else {
-            row_data.schema.clone()
-        };
+        // PostgreSQL 使用连接的数据库名，MySQL 中 db 即 schema
+        let database_name = self
+            .database_name
+            .clone()
+            .unwrap_or_else(|| row_data.schema.clone());
 
         let mut json_obj = json!({
             "action": action,
-            "bid": 0,
+            "bid": self.bid,
             "db": database_name,
             "schema": row_data.schema,
             "table": row_data.tb,
@@ -87,14 +108,19 @@ impl CloudCanalConverter {
             "pks": []
         });
 
+        // MongoDB 文档以 _id 为主键
+        if get_mongo_id(&row_data).is_some() {
+            json_obj["pks"] = json!([MongoConstants::ID]);
+        }
+
         // 添加 before 数据（用于 UPDATE 和 DELETE 操作）
         if let Some(before) = &row_data.before {
-            json_obj["before"] = json!([col_values_to_json_value(before)]);
+            json_obj["before"] = json!([col_values_to_json_value(before, &self.timezone)]);
         }
 
         // 添加 data 数据（用于 INSERT 和 UPDATE 操作）
         if let Some(after) = &row_data.after {
-            json_obj["data"] = json!([col_values_to_json_value(after)]);
+            json_obj["data"] = json!([col_values_to_json_value(after, &self.timezone)]);
         }
 
         // 获取表的元数据信息，添加字段类型信息
@@ -116,29 +142,9 @@ impl CloudCanalConverter {
                 
                 for col_name in &tb_meta.cols {
                     if let Some(col_origin_type) = tb_meta.col_origin_type_map.get(col_name) {
-                        if is_mysql {
-                            db_val_type.insert(col_name.clone(), Value::String(col_origin_type.clone()));
-                            // MySQL JDBC 类型映射（简化版本）
-                            let jdbc_type_code = match col_origin_type.to_lowercase().as_str() {
-                                s if s.contains("bigint") => -5,
-                                s if s.contains("int") => 4,
-                                s if s.contains("varchar") || s.contains("text") => 12,
-                                s if s.contains("timestamp") || s.contains("datetime") => 93,
-                                s if s.contains("json") => 1111,
-                                _ => 12, // 默认为 VARCHAR
-                            };
-                            jdbc_type.insert(col_name.clone(), Value::Number(jdbc_type_code.into()));
-                        } else if is_pg {
+                        if is_mysql || is_pg {
                             db_val_type.insert(col_name.clone(), Value::String(col_origin_type.clone()));
-                            // PostgreSQL JDBC 类型映射（简化版本）
-                            let jdbc_type_code = match col_origin_type.to_lowercase().as_str() {
-                                s if s.contains("bigint") => -5,
-                                s if s.contains("integer") => 4,
-                                s if s.contains("varchar") || s.contains("text") => 12,
-                                s if s.contains("timestamp") => 93,
-                                s if s.contains("json") => 1111,
-                                _ => 12, // 默认为 VARCHAR
-                            };
+                            let jdbc_type_code = get_jdbc_type_code(col_origin_type, is_pg);
                             jdbc_type.insert(col_name.clone(), Value::Number(jdbc_type_code.into()));
                         }
                     }
@@ -149,17 +155,26 @@ impl CloudCanalConverter {
             }
         }
 
+        // 没有表元数据时按列值推断近似的类型，pks 仅 MongoDB 可知，其余为空
+        if json_obj.get("dbValType").is_none() {
+            let (db_val_type, jdbc_type) = infer_type_maps(&row_data);
+            json_obj["dbValType"] = db_val_type;
+            json_obj["jdbcType"] = jdbc_type;
+        }
+
         Ok(serde_json::to_string(&json_obj)?)
     }
 
     pub async fn ddl_data_to_json_value(&mut self, ddl_data: DdlData) -> Result<String> {
-        let db = self.database_name.clone().unwrap_or_else(|| ddl_data.default_schema.clone());
+        // 受影响的库表取自 DDL 语句，语句未指定库时使用 default_schema；库级 DDL 的 table 为空
+        let (schema, tb) = ddl_data.get_schema_tb();
+        let db = self.database_name.clone().unwrap_or_else(|| schema.clone());
         let json_obj = json!({
             "action": "DDL",
-            "bid": 0,
+            "bid": self.bid,
             "db": db,
-            "schema": ddl_data.default_schema,
-            "table": "",
+            "schema": schema,
+            "table": tb,
             "ddl": true,
             "entryType": "DDL",
             "execTs": chrono::Utc::now().timestamp_millis(),
@@ -176,15 +191,229 @@ impl CloudCanalConverter {
     }
 }
 
-fn col_values_to_json_value(col_values: &HashMap<String, ColValue>) -> Value {
+/// MongoDB 文档的 _id（canonical extended JSON），非 MongoDB 数据返回 None
+fn get_mongo_id(row_data: &RowData) -> Option<Value> {
+    let col_values = row_data.after.as_ref().or(row_data.before.as_ref())?;
+    match col_values.get(MongoConstants::DOC) {
+        Some(ColValue::MongoDoc(doc)) => doc
+            .get(MongoConstants::ID)
+            .map(|id| id.clone().into_canonical_extjson()),
+        _ => None,
+    }
+}
+
+// java.sql.Types 编码
+const BIT: i32 = -7;
+const TINYINT: i32 = -6;
+const SMALLINT: i32 = 5;
+const INTEGER: i32 = 4;
+const BIGINT: i32 = -5;
+const REAL: i32 = 7;
+const DOUBLE: i32 = 8;
+const NUMERIC: i32 = 2;
+const DECIMAL: i32 = 3;
+const CHAR: i32 = 1;
+const VARCHAR: i32 = 12;
+const BINARY: i32 = -2;
+const VARBINARY: i32 = -3;
+const DATE: i32 = 91;
+const TIME: i32 = 92;
+const TIMESTAMP: i32 = 93;
+const OTHER: i32 = 1111;
+const BLOB: i32 = 2004;
+const CLOB: i32 = 2005;
+const BOOLEAN: i32 = 16;
+const ARRAY: i32 = 2003;
+const SQLXML: i32 = 2009;
+
+/// MySQL 类型到 JDBC 类型编码，与 Canal 一致：text 类为 CLOB，blob 类为 BLOB
+const MYSQL_JDBC_TYPES: &[(&str, i32)] = &[
+    ("bit", BIT),
+    ("bool", BOOLEAN),
+    ("boolean", BOOLEAN),
+    ("tinyint", TINYINT),
+    ("smallint", SMALLINT),
+    ("mediumint", INTEGER),
+    ("int", INTEGER),
+    ("integer", INTEGER),
+    ("bigint", BIGINT),
+    ("float", REAL),
+    ("double", DOUBLE),
+    ("decimal", DECIMAL),
+    ("numeric", DECIMAL),
+    ("char", CHAR),
+    ("varchar", VARCHAR),
+    ("tinytext", CLOB),
+    ("text", CLOB),
+    ("mediumtext", CLOB),
+    ("longtext", CLOB),
+    ("binary", BINARY),
+    ("varbinary", VARBINARY),
+    ("tinyblob", BLOB),
+    ("blob", BLOB),
+    ("mediumblob", BLOB),
+    ("longblob", BLOB),
+    ("date", DATE),
+    ("year", DATE),
+    ("time", TIME),
+    ("datetime", TIMESTAMP),
+    ("timestamp", TIMESTAMP),
+    ("enum", CHAR),
+    ("set", CHAR),
+    ("json", OTHER),
+    ("geometry", BINARY),
+    ("point", BINARY),
+    ("linestring", BINARY),
+    ("polygon", BINARY),
+    ("multipoint", BINARY),
+    ("multilinestring", BINARY),
+    ("multipolygon", BINARY),
+    ("geometrycollection", BINARY),
+    ("geomcollection", BINARY),
+];
+
+/// PostgreSQL 类型（别名及标准名称）到 JDBC 类型编码，与 pgjdbc 一致；数组类型均为 ARRAY
+const PG_JDBC_TYPES: &[(&str, i32)] = &[
+    ("bool", BOOLEAN),
+    ("boolean", BOOLEAN),
+    ("bit", BIT),
+    ("varbit", OTHER),
+    ("int2", SMALLINT),
+    ("smallint", SMALLINT),
+    ("serial2", SMALLINT),
+    ("int4", INTEGER),
+    ("int", INTEGER),
+    ("integer", INTEGER),
+    ("serial4", INTEGER),
+    ("serial", INTEGER),
+    ("int8", BIGINT),
+    ("bigint", BIGINT),
+    ("serial8", BIGINT),
+    ("bigserial", BIGINT),
+    ("oid", BIGINT),
+    ("float4", REAL),
+    ("real", REAL),
+    ("float8", DOUBLE),
+    ("double precision", DOUBLE),
+    ("money", DOUBLE),
+    ("numeric", NUMERIC),
+    ("decimal", NUMERIC),
+    ("\"char\"", CHAR),
+    ("bpchar", CHAR),
+    ("char", CHAR),
+    ("character", CHAR),
+    ("varchar", VARCHAR),
+    ("character varying", VARCHAR),
+    ("text", VARCHAR),
+    ("name", VARCHAR),
+    ("bytea", BINARY),
+    ("date", DATE),
+    ("time", TIME),
+    ("timetz", TIME),
+    ("time without time zone", TIME),
+    ("time with time zone", TIME),
+    ("timestamp", TIMESTAMP),
+    ("timestamptz", TIMESTAMP),
+    ("timestamp without time zone", TIMESTAMP),
+    ("timestamp with time zone", TIMESTAMP),
+    ("json", OTHER),
+    ("jsonb", OTHER),
+    ("uuid", OTHER),
+    ("interval", OTHER),
+    ("inet", OTHER),
+    ("cidr", OTHER),
+    ("macaddr", OTHER),
+    ("point", OTHER),
+    ("xml", SQLXML),
+];
+
+/// 字段类型到 JDBC 类型编码的映射，忽略长度/精度及 unsigned 等修饰，未知类型为 VARCHAR
+pub fn get_jdbc_type_code(col_origin_type: &str, is_pg: bool) -> i32 {
+    let col_type = col_origin_type.trim().to_lowercase();
+    // PostgreSQL 数组：别名以 _ 开头，如 _int4；或以 [] 结尾，如 integer[]
+    if is_pg && (col_type.starts_with('_') || col_type.ends_with("[]")) {
+        return ARRAY;
+    }
+
+    // decimal(10,2) -> decimal, int(11) unsigned -> int, character varying(255) -> character varying
+    let base_type = col_type
+        .split('(')
+        .next()
+        .unwrap_or_default()
+        .trim_end_matches(" zerofill")
+        .trim_end_matches(" unsigned")
+        .trim();
+    let jdbc_types = if is_pg {
+        PG_JDBC_TYPES
+    } else {
+        MYSQL_JDBC_TYPES
+    };
+    jdbc_types
+        .iter()
+        .find(|(name, _)| *name == base_type)
+        .map(|(_, code)| *code)
+        .unwrap_or(VARCHAR)
+}
+
+/// 按列值推断 dbValType 和 jdbcType，after 优先，值为 NULL 的列取 before 中的值，
+/// 均无法推断的列不输出；推断结果是近似的，如 varchar 和 text 都推断为 varchar
+fn infer_type_maps(row_data: &RowData) -> (Value, Value) {
+    let mut db_val_type = serde_json::Map::new();
+    let mut jdbc_type = serde_json::Map::new();
+    for col_values in [&row_data.after, &row_data.before].into_iter().flatten() {
+        for (col_name, col_value) in col_values {
+            if db_val_type.contains_key(col_name) {
+                continue;
+            }
+            if let Some((col_type, jdbc_type_code)) = infer_col_type(col_value) {
+                db_val_type.insert(col_name.clone(), Value::String(col_type.into()));
+                jdbc_type.insert(col_name.clone(), Value::Number(jdbc_type_code.into()));
+            }
+        }
+    }
+    (Value::Object(db_val_type), Value::Object(jdbc_type))
+}
+
+fn infer_col_type(value: &ColValue) -> Option<(&'static str, i32)> {
+    let col_type = match value {
+        ColValue::None | ColValue::UnchangedToast => return None,
+        ColValue::Bool(_) => ("boolean", BOOLEAN),
+        ColValue::Tiny(_) | ColValue::UnsignedTiny(_) => ("tinyint", TINYINT),
+        ColValue::Short(_) | ColValue::UnsignedShort(_) => ("smallint", SMALLINT),
+        ColValue::Long(_) | ColValue::UnsignedLong(_) => ("int", INTEGER),
+        ColValue::LongLong(_) | ColValue::UnsignedLongLong(_) => ("bigint", BIGINT),
+        ColValue::Float(_) => ("float", REAL),
+        ColValue::Double(_) => ("double", DOUBLE),
+        ColValue::Decimal(_) => ("decimal", DECIMAL),
+        ColValue::Date(_) => ("date", DATE),
+        ColValue::Year(_) => ("year", DATE),
+        ColValue::Time(_) => ("time", TIME),
+        ColValue::DateTime(_) => ("datetime", TIMESTAMP),
+        ColValue::Timestamp(_) => ("timestamp", TIMESTAMP),
+        ColValue::String(_) | ColValue::RawString(_) => ("varchar", VARCHAR),
+        ColValue::Blob(_) => ("blob", BLOB),
+        ColValue::Bit(_) => ("bit", BIT),
+        ColValue::Set(_) | ColValue::Set2(_) => ("set", CHAR),
+        ColValue::Enum(_) | ColValue::Enum2(_) => ("enum", CHAR),
+        ColValue::Json(_) | ColValue::Json2(_) | ColValue::Json3(_) => ("json", OTHER),
+        ColValue::MongoDoc(_) => ("document", OTHER),
+        ColValue::Geometry(_) => ("geometry", BINARY),
+    };
+    Some(col_type)
+}
+
+fn col_values_to_json_value(
+    col_values: &HashMap<String, ColValue>,
+    timezone: &FixedOffset,
+) -> Value {
     let mut json_map = serde_json::Map::new();
     for (key, value) in col_values {
-        json_map.insert(key.clone(), col_value_to_json_value(value));
+        json_map.insert(key.clone(), col_value_to_json_value(value, timezone));
     }
     Value::Object(json_map)
 }
 
-fn col_value_to_json_value(value: &ColValue) -> Value {
+fn col_value_to_json_value(value: &ColValue, timezone: &FixedOffset) -> Value {
     match value {
         ColValue::None | ColValue::UnchangedToast => Value::Null,
         ColValue::Bool(v) => Value::Bool(*v),
@@ -198,13 +427,8 @@ fn col_value_to_json_value(value: &ColValue) -> Value {
         ColValue::UnsignedLongLong(v) => Value::Number((*v).into()),
         ColValue::Float(v) => Value::Number(serde_json::Number::from_f64(*v as f64).unwrap_or_else(|| serde_json::Number::from(0))),
         ColValue::Double(v) => Value::Number(serde_json::Number::from_f64(*v).unwrap_or_else(|| serde_json::Number::from(0))),
-        ColValue::Decimal(v) => Value::String(v.clone()),
-        ColValue::String(v) => Value::String(v.clone()),
         ColValue::Blob(v) => Value::String(general_purpose::STANDARD.encode(v)),
-        ColValue::Date(v) => Value::String(v.clone()),
-        ColValue::Time(v) => Value::String(v.clone()),
-        ColValue::DateTime(v) => Value::String(v.clone()),
-        ColValue::Timestamp(v) => Value::String(v.clone()),
+        ColValue::Timestamp(v) => Value::String(format_timestamp(v, timezone)),
         ColValue::Json(v) => {
             // Convert Vec<u8> to String first
             let json_str = String::from_utf8_lossy(v);
@@ -214,13 +438,232 @@ fn col_value_to_json_value(value: &ColValue) -> Value {
             serde_json::from_str(v).unwrap_or_else(|_| Value::String(v.clone()))
         },
         ColValue::Json3(v) => v.clone(),
-        ColValue::RawString(v) => Value::String(String::from_utf8_lossy(v).to_string()),
-        ColValue::Set2(v) => Value::String(v.clone()),
-        ColValue::Enum2(v) => Value::String(v.clone()),
-        ColValue::MongoDoc(v) => Value::String(v.to_string()),
-        ColValue::Enum(v) => Value::String(v.to_string()),
-        ColValue::Set(v) => Value::String(v.to_string()),
+        // MongoDB 文档输出为 canonical extended JSON，保留 $oid、$date、$numberDecimal 等类型信息
+        ColValue::MongoDoc(v) => Bson::Document(v.clone()).into_canonical_extjson(),
+        // 空间类型输出为 WKT，如 POINT(1 2)
+        ColValue::Geometry(_) => Value::String(value.to_display_string()),
         ColValue::Year(v) => Value::Number((*v).into()),
-        ColValue::Bit(v) => Value::String(v.to_string()),
+        ColValue::Decimal(_)
+        | ColValue::String(_)
+        | ColValue::Date(_)
+        | ColValue::Time(_)
+        | ColValue::DateTime(_)
+        | ColValue::RawString(_)
+        | ColValue::Set2(_)
+        | ColValue::Enum2(_)
+        | ColValue::Enum(_)
+        | ColValue::Set(_)
+        | ColValue::Bit(_) => Value::String(value.to_display_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::config_enums::DbType,
+        meta::ddl_meta::{
+            ddl_statement::{DdlStatement, MysqlCreateTableStatement},
+            ddl_type::DdlType,
+        },
+    };
+
+    fn build_row_data() -> RowData {
+        let after = HashMap::from([("id".to_string(), ColValue::Long(1))]);
+        RowData::new(
+            "public".into(),
+            "tb_1".into(),
+            0,
+            RowType::Insert,
+            None,
+            Some(after),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_row_data_to_json_db() {
+        // pg: db is the connected database, schema is the pg schema
+        let mut converter = CloudCanalConverter::new(None, Some("app_db".into()));
+        let json_str = converter
+            .row_data_to_json_value(build_row_data())
+            .await
+            .unwrap();
+        let parsed: Value = serde_json::from_str(&json_str).unwrap();
+        assert_eq!(parsed["db"], "app_db");
+        assert_eq!(parsed["schema"], "public");
+        assert_eq!(parsed["table"], "tb_1");
+
+        // database name unknown, fall back to schema
+        let mut converter = CloudCanalConverter::new(None, None);
+        let json_str = converter
+            .row_data_to_json_value(build_row_data())
+            .await
+            .unwrap();
+        let parsed: Value = serde_json::from_str(&json_str).unwrap();
+        assert_eq!(parsed["db"], "public");
+        assert_eq!(parsed["schema"], "public");
+    }
+
+    #[tokio::test]
+    async fn test_mongo_doc_to_extended_json() {
+        use mongodb::bson::{doc, oid::ObjectId, Decimal128};
+
+        let oid = ObjectId::parse_str("65a1b2c3d4e5f60718293a4b").unwrap();
+        let price: Decimal128 = "12.50".parse().unwrap();
+        let doc = doc! { "_id": oid, "price": price };
+        let after = HashMap::from([(MongoConstants::DOC.to_string(), ColValue::MongoDoc(doc))]);
+        let row_data = RowData::new(
+            "db_1".into(),
+            "tb_1".into(),
+            0,
+            RowType::Insert,
+            None,
+            Some(after),
+        );
+
+        let mut converter = CloudCanalConverter::new(None, None);
+        let key = converter.row_data_to_json_key(&row_data).await.unwrap();
+        assert_eq!(key, r#"[{"$oid":"65a1b2c3d4e5f60718293a4b"}]"#);
+
+        let json_str = converter.row_data_to_json_value(row_data).await.unwrap();
+        let parsed: Value = serde_json::from_str(&json_str).unwrap();
+        assert_eq!(
+            parsed["data"][0]["doc"],
+            json!({
+                "_id": { "$oid": "65a1b2c3d4e5f60718293a4b" },
+                "price": { "$numberDecimal": "12.50" },
+            })
+        );
+        assert_eq!(parsed["pks"], json!(["_id"]));
+    }
+
+    #[test]
+    fn test_get_jdbc_type_code() {
+        for is_pg in [false, true] {
+            assert_eq!(get_jdbc_type_code("date", is_pg), 91);
+            assert_eq!(get_jdbc_type_code("DATE", is_pg), 91);
+            assert_eq!(get_jdbc_type_code("varchar(255)", is_pg), 12);
+            assert_eq!(get_jdbc_type_code("unknown_type", is_pg), 12);
+        }
+
+        // mysql
+        assert_eq!(get_jdbc_type_code("decimal(10,2)", false), 3);
+        assert_eq!(get_jdbc_type_code("decimal", false), 3);
+        assert_eq!(get_jdbc_type_code("int(11) unsigned", false), 4);
+        assert_eq!(get_jdbc_type_code("bigint unsigned zerofill", false), -5);
+        assert_eq!(get_jdbc_type_code("time(6)", false), 92);
+        assert_eq!(get_jdbc_type_code("datetime", false), 93);
+        assert_eq!(get_jdbc_type_code("longblob", false), 2004);
+        assert_eq!(get_jdbc_type_code("binary", false), -2);
+        assert_eq!(get_jdbc_type_code("text", false), 2005);
+
+        // pg, decimal is an alias of numeric
+        assert_eq!(get_jdbc_type_code("decimal(10,2)", true), 2);
+        assert_eq!(get_jdbc_type_code("numeric", true), 2);
+        assert_eq!(get_jdbc_type_code("bool", true), 16);
+        assert_eq!(get_jdbc_type_code("int4", true), 4);
+        assert_eq!(get_jdbc_type_code("timestamptz", true), 93);
+        assert_eq!(get_jdbc_type_code("bytea", true), -2);
+        assert_eq!(get_jdbc_type_code("jsonb", true), 1111);
+        assert_eq!(get_jdbc_type_code("_int4", true), 2003);
+        assert_eq!(get_jdbc_type_code("text[]", true), 2003);
+    }
+
+    #[tokio::test]
+    async fn test_infer_type_maps_without_meta() {
+        let before = HashMap::from([
+            ("id".to_string(), ColValue::LongLong(1)),
+            ("name".to_string(), ColValue::String("a".into())),
+            ("price".to_string(), ColValue::Decimal("1.50".into())),
+        ]);
+        let after = HashMap::from([
+            ("id".to_string(), ColValue::LongLong(1)),
+            ("name".to_string(), ColValue::None),
+            ("price".to_string(), ColValue::Decimal("2.50".into())),
+            ("created".to_string(), ColValue::Date("2024-01-01".into())),
+            ("remark".to_string(), ColValue::None),
+        ]);
+        let row_data = RowData::new(
+            "db_1".into(),
+            "tb_1".into(),
+            0,
+            RowType::Update,
+            Some(before),
+            Some(after),
+        );
+
+        let mut converter = CloudCanalConverter::new(None, None);
+        let json_str = converter.row_data_to_json_value(row_data).await.unwrap();
+        let parsed: Value = serde_json::from_str(&json_str).unwrap();
+        assert_eq!(
+            parsed["dbValType"],
+            json!({
+                "id": "bigint",
+                "name": "varchar",
+                "price": "decimal",
+                "created": "date",
+            })
+        );
+        assert_eq!(
+            parsed["jdbcType"],
+            json!({ "id": -5, "name": 12, "price": 3, "created": 91 })
+        );
+        assert_eq!(parsed["pks"], json!([]));
+    }
+
+    #[tokio::test]
+    async fn test_bid_per_batch() {
+        let mut converter = CloudCanalConverter::new(None, None);
+        let mut bids = Vec::new();
+        for _ in 0..2 {
+            converter.next_batch();
+            for _ in 0..2 {
+                let json_str = converter
+                    .row_data_to_json_value(build_row_data())
+                    .await
+                    .unwrap();
+                let parsed: Value = serde_json::from_str(&json_str).unwrap();
+                bids.push(parsed["bid"].as_u64().unwrap());
+            }
+        }
+        assert_eq!(bids, vec![1, 1, 2, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_ddl_data_to_json_table() {
+        let query = "CREATE TABLE tb_1 (id INT PRIMARY KEY)";
+        let build_ddl_data = |db: &str| DdlData {
+            default_schema: "default_db".into(),
+            query: query.into(),
+            ddl_type: DdlType::CreateTable,
+            db_type: DbType::Mysql,
+            statement: DdlStatement::MysqlCreateTable(MysqlCreateTableStatement {
+                db: db.into(),
+                tb: "tb_1".into(),
+                ..Default::default()
+            }),
+        };
+
+        let mut converter = CloudCanalConverter::new(None, None);
+        let json_str = converter
+            .ddl_data_to_json_value(build_ddl_data("db_1"))
+            .await
+            .unwrap();
+        let parsed: Value = serde_json::from_str(&json_str).unwrap();
+        assert_eq!(parsed["db"], "db_1");
+        assert_eq!(parsed["schema"], "db_1");
+        assert_eq!(parsed["table"], "tb_1");
+        assert_eq!(parsed["ddl"], true);
+        assert_eq!(parsed["sql"], query);
+
+        // the statement has no db, fall back to default_schema
+        let json_str = converter
+            .ddl_data_to_json_value(build_ddl_data(""))
+            .await
+            .unwrap();
+        let parsed: Value = serde_json::from_str(&json_str).unwrap();
+        assert_eq!(parsed["db"], "default_db");
+        assert_eq!(parsed["schema"], "default_db");
+        assert_eq!(parsed["table"], "tb_1");
     }
 }
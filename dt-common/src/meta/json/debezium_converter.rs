@@ -0,0 +1,430 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use chrono::{FixedOffset, Offset, Utc};
+use serde_json::{json, Value};
+
+use super::json_converter::col_value_to_json_value_in_tz;
+use crate::meta::{
+    col_value::ColValue, ddl_meta::ddl_data::DdlData, rdb_meta_manager::RdbMetaManager,
+    rdb_tb_meta::RdbTbMeta, row_data::RowData, row_type::RowType,
+};
+
+/// Debezium 中的 topic.prefix，用于 schema 名称和 source.name
+const SERVER_NAME: &str = "ape_dts";
+const MYSQL: &str = "mysql";
+const POSTGRESQL: &str = "postgresql";
+
+/// Debezium 格式的 JSON 转换器
+/// 生成 {schema, payload: {before, after, source, op, ts_ms}} 信封，Debezium 消费端可直接消费
+#[derive(Clone)]
+pub struct DebeziumConverter {
+    pub meta_manager: Option<RdbMetaManager>,
+    /// 连接的数据库名，用于 PostgreSQL 区分 database 和 schema；MySQL 中 db 即 schema，不保存
+    pub database_name: Option<String>,
+    /// 全量任务的 insert 对应 Debezium 的 read 事件（op=r）
+    pub snapshot: bool,
+    /// Timestamp 类型的值按该时区格式化，默认 UTC
+    pub timezone: FixedOffset,
+}
+
+impl DebeziumConverter {
+    pub fn new(
+        meta_manager: Option<RdbMetaManager>,
+        database_name: Option<String>,
+        snapshot: bool,
+    ) -> Self {
+        let is_mysql = meta_manager
+            .as_ref()
+            .is_some_and(|m| m.mysql_meta_manager.is_some());
+        let database_name = if is_mysql { None } else { database_name };
+        DebeziumConverter {
+            meta_manager,
+            database_name,
+            snapshot,
+            timezone: Utc.fix(),
+        }
+    }
+
+    pub fn refresh_meta(&mut self, data: &[DdlData]) {
+        if let Some(meta_manager) = &mut self.meta_manager {
+            for ddl_data in data {
+                meta_manager.invalidate_cache_by_ddl_data(ddl_data);
+            }
+        }
+    }
+
+    pub async fn row_data_to_json_value(&mut self, row_data: RowData) -> Result<String> {
+        // RowType 映射到 Debezium 的 op
+        let op = match row_data.row_type {
+            RowType::Insert if self.snapshot => "r",
+            RowType::Insert => "c",
+            RowType::Update => "u",
+            RowType::Delete => "d",
+        };
+
+        let connector = self.connector();
+        let database_name = self
+            .database_name
+            .clone()
+            .unwrap_or_else(|| row_data.schema.clone());
+        let ts_ms = chrono::Utc::now().timestamp_millis();
+        let (source_schema, source) = self.build_source(
+            connector,
+            &database_name,
+            &row_data.schema,
+            &row_data.tb,
+            ts_ms,
+        );
+
+        let tb_meta = match &mut self.meta_manager {
+            Some(meta_manager) => meta_manager
+                .get_tb_meta(&row_data.schema, &row_data.tb)
+                .await
+                .ok(),
+            None => None,
+        };
+        let cols = Self::get_cols(&row_data, tb_meta);
+        let col_schemas: Vec<Value> = cols
+            .iter()
+            .map(|col| Self::build_col_schema(col, &row_data, tb_meta))
+            .collect();
+
+        let name_prefix = format!("{}.{}.{}", SERVER_NAME, row_data.schema, row_data.tb);
+        let value_schema_name = format!("{}.Value", name_prefix);
+        let before_schema =
+            Self::build_struct_schema("before", &value_schema_name, col_schemas.clone(), true);
+        let after_schema =
+            Self::build_struct_schema("after", &value_schema_name, col_schemas, true);
+        let to_json = |col_values: &HashMap<String, ColValue>| {
+            Self::col_values_to_json_value(&cols, col_values, &self.timezone)
+        };
+
+        let json_obj = json!({
+            "schema": {
+                "type": "struct",
+                "name": format!("{}.Envelope", name_prefix),
+                "optional": false,
+                "fields": [
+                    before_schema,
+                    after_schema,
+                    source_schema,
+                    {"type": "string", "optional": false, "field": "op"},
+                    {"type": "int64", "optional": true, "field": "ts_ms"},
+                ],
+            },
+            "payload": {
+                "before": row_data.before.as_ref().map(to_json),
+                "after": row_data.after.as_ref().map(to_json),
+                "source": source,
+                "op": op,
+                "ts_ms": ts_ms,
+            },
+        });
+
+        Ok(serde_json::to_string(&json_obj)?)
+    }
+
+    /// Debezium 的 schema change 事件
+    pub async fn ddl_data_to_json_value(&mut self, ddl_data: DdlData) -> Result<String> {
+        let connector = self.connector();
+        let database_name = self
+            .database_name
+            .clone()
+            .unwrap_or_else(|| ddl_data.default_schema.clone());
+        let ts_ms = chrono::Utc::now().timestamp_millis();
+        let (_, source) = self.build_source(
+            connector,
+            &database_name,
+            &ddl_data.default_schema,
+            "",
+            ts_ms,
+        );
+
+        let json_obj = json!({
+            "schema": null,
+            "payload": {
+                "source": source,
+                "ts_ms": ts_ms,
+                "databaseName": database_name,
+                "schemaName": ddl_data.default_schema,
+                "ddl": ddl_data.query,
+                "tableChanges": [],
+            },
+        });
+
+        Ok(serde_json::to_string(&json_obj)?)
+    }
+
+    fn connector(&self) -> &'static str {
+        match &self.meta_manager {
+            Some(meta_manager) if meta_manager.pg_meta_manager.is_some() => POSTGRESQL,
+            _ => MYSQL,
+        }
+    }
+
+    /// 返回 (source 的 schema, source)
+    fn build_source(
+        &self,
+        connector: &str,
+        db: &str,
+        schema: &str,
+        tb: &str,
+        ts_ms: i64,
+    ) -> (Value, Value) {
+        let mut fields = vec![
+            json!({"type": "string", "optional": false, "field": "version"}),
+            json!({"type": "string", "optional": false, "field": "connector"}),
+            json!({"type": "string", "optional": false, "field": "name"}),
+            json!({"type": "int64", "optional": false, "field": "ts_ms"}),
+            json!({"type": "string", "optional": true, "field": "snapshot"}),
+            json!({"type": "string", "optional": false, "field": "db"}),
+        ];
+        let mut source = json!({
+            "version": env!("CARGO_PKG_VERSION"),
+            "connector": connector,
+            "name": SERVER_NAME,
+            "ts_ms": ts_ms,
+            "snapshot": self.snapshot.to_string(),
+            "db": db,
+        });
+
+        // MySQL 中 db 即 schema，PostgreSQL 额外带上 schema
+        if connector == POSTGRESQL {
+            fields.push(json!({"type": "string", "optional": false, "field": "schema"}));
+            source["schema"] = json!(schema);
+        }
+        fields.push(json!({"type": "string", "optional": true, "field": "table"}));
+        source["table"] = json!(tb);
+
+        let source_schema_name = format!("io.debezium.connector.{}.Source", connector);
+        (
+            Self::build_struct_schema("source", &source_schema_name, fields, false),
+            source,
+        )
+    }
+
+    fn build_struct_schema(field: &str, name: &str, fields: Vec<Value>, optional: bool) -> Value {
+        json!({
+            "type": "struct",
+            "fields": fields,
+            "optional": optional,
+            "name": name,
+            "field": field,
+        })
+    }
+
+    /// 有表元数据时按表的列顺序，否则按列名排序
+    fn get_cols(row_data: &RowData, tb_meta: Option<&RdbTbMeta>) -> Vec<String> {
+        let has_col = |col: &str| {
+            row_data
+                .before
+                .as_ref()
+                .is_some_and(|v| v.contains_key(col))
+                || row_data.after.as_ref().is_some_and(|v| v.contains_key(col))
+        };
+
+        if let Some(tb_meta) = tb_meta {
+            return tb_meta
+                .cols
+                .iter()
+                .filter(|col| has_col(col.as_str()))
+                .cloned()
+                .collect();
+        }
+
+        let mut cols: Vec<String> = row_data
+            .before
+            .iter()
+            .chain(row_data.after.iter())
+            .flat_map(|v| v.keys().cloned())
+            .collect();
+        cols.sort();
+        cols.dedup();
+        cols
+    }
+
+    fn build_col_schema(col: &str, row_data: &RowData, tb_meta: Option<&RdbTbMeta>) -> Value {
+        let not_null = |col_values: &Option<HashMap<String, ColValue>>| {
+            col_values
+                .as_ref()
+                .and_then(|v| v.get(col))
+                .filter(|v| !matches!(v, ColValue::None | ColValue::UnchangedToast))
+                .cloned()
+        };
+        let col_type = not_null(&row_data.after)
+            .or_else(|| not_null(&row_data.before))
+            .map_or("string", |v| Self::get_connect_type(&v));
+        let optional = tb_meta.is_none_or(|tb_meta| tb_meta.is_col_nullable(col));
+        json!({"type": col_type, "optional": optional, "field": col})
+    }
+
+    /// Kafka Connect 中的类型，与 col_value_to_json_value 生成的 JSON 值一致
+    fn get_connect_type(value: &ColValue) -> &'static str {
+        match value {
+            ColValue::Bool(_) => "boolean",
+            ColValue::Tiny(_) | ColValue::UnsignedTiny(_) | ColValue::Short(_) => "int16",
+            ColValue::UnsignedShort(_) | ColValue::Long(_) | ColValue::Year(_) => "int32",
+            ColValue::UnsignedLong(_) | ColValue::LongLong(_) | ColValue::UnsignedLongLong(_) => {
+                "int64"
+            }
+            ColValue::Float(_) => "float",
+            ColValue::Double(_) => "double",
+            // base64 编码，与 Kafka Connect JsonConverter 一致
+            ColValue::Blob(_) => "bytes",
+            _ => "string",
+        }
+    }
+
+    fn col_values_to_json_value(
+        cols: &[String],
+        col_values: &HashMap<String, ColValue>,
+        timezone: &FixedOffset,
+    ) -> Value {
+        let mut json_map = serde_json::Map::new();
+        for col in cols {
+            let value = col_values
+                .get(col)
+                .map_or(Value::Null, |v| col_value_to_json_value_in_tz(v, timezone));
+            json_map.insert(col.clone(), value);
+        }
+        Value::Object(json_map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_update_envelope() {
+        let before = HashMap::from([
+            ("id".to_string(), ColValue::Long(1)),
+            ("name".to_string(), ColValue::String("a".into())),
+            ("score".to_string(), ColValue::None),
+        ]);
+        let after = HashMap::from([
+            ("id".to_string(), ColValue::Long(1)),
+            ("name".to_string(), ColValue::String("b".into())),
+            ("score".to_string(), ColValue::Double(1.5)),
+        ]);
+        let row_data = RowData::new(
+            "db_1".into(),
+            "tb_1".into(),
+            0,
+            RowType::Update,
+            Some(before),
+            Some(after),
+        );
+
+        let mut converter = DebeziumConverter::new(None, None, false);
+        let json_str = converter.row_data_to_json_value(row_data).await.unwrap();
+        let parsed: Value = serde_json::from_str(&json_str).unwrap();
+
+        let payload = &parsed["payload"];
+        assert_eq!(payload["op"], "u");
+        assert_eq!(
+            payload["before"],
+            json!({"id": 1, "name": "a", "score": null})
+        );
+        assert_eq!(
+            payload["after"],
+            json!({"id": 1, "name": "b", "score": 1.5})
+        );
+        assert_eq!(payload["source"]["connector"], "mysql");
+        assert_eq!(payload["source"]["db"], "db_1");
+        assert_eq!(payload["source"]["table"], "tb_1");
+        assert_eq!(payload["source"]["snapshot"], "false");
+        assert!(payload["ts_ms"].is_i64());
+
+        let schema = &parsed["schema"];
+        assert_eq!(schema["name"], "ape_dts.db_1.tb_1.Envelope");
+        let fields: Vec<&str> = schema["fields"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|f| f["field"].as_str().unwrap())
+            .collect();
+        assert_eq!(fields, vec!["before", "after", "source", "op", "ts_ms"]);
+        assert_eq!(
+            schema["fields"][1]["fields"],
+            json!([
+                {"type": "int32", "optional": true, "field": "id"},
+                {"type": "string", "optional": true, "field": "name"},
+                {"type": "double", "optional": true, "field": "score"},
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_insert_op() {
+        let after = HashMap::from([("id".to_string(), ColValue::Long(1))]);
+        let build_row_data = || {
+            RowData::new(
+                "db_1".into(),
+                "tb_1".into(),
+                0,
+                RowType::Insert,
+                None,
+                Some(after.clone()),
+            )
+        };
+
+        let mut converter = DebeziumConverter::new(None, None, true);
+        let json_str = converter
+            .row_data_to_json_value(build_row_data())
+            .await
+            .unwrap();
+        let parsed: Value = serde_json::from_str(&json_str).unwrap();
+        assert_eq!(parsed["payload"]["op"], "r");
+        assert_eq!(parsed["payload"]["before"], Value::Null);
+        assert_eq!(parsed["payload"]["source"]["snapshot"], "true");
+
+        let mut converter = DebeziumConverter::new(None, None, false);
+        let json_str = converter
+            .row_data_to_json_value(build_row_data())
+            .await
+            .unwrap();
+        let parsed: Value = serde_json::from_str(&json_str).unwrap();
+        assert_eq!(parsed["payload"]["op"], "c");
+    }
+
+    #[tokio::test]
+    async fn test_timestamp_in_timezone() {
+        let after = HashMap::from([
+            (
+                "ts".to_string(),
+                ColValue::Timestamp("2024-01-01 20:00:00".into()),
+            ),
+            (
+                "tstz".to_string(),
+                ColValue::Timestamp("2024-01-01 20:00:00+00".into()),
+            ),
+            (
+                "dt".to_string(),
+                ColValue::DateTime("2024-01-01 20:00:00".into()),
+            ),
+        ]);
+        let row_data = RowData::new(
+            "db_1".into(),
+            "tb_1".into(),
+            0,
+            RowType::Insert,
+            None,
+            Some(after),
+        );
+
+        let mut converter = DebeziumConverter::new(None, None, false);
+        converter.timezone = "+08:00".parse().unwrap();
+        let json_str = converter.row_data_to_json_value(row_data).await.unwrap();
+        let parsed: Value = serde_json::from_str(&json_str).unwrap();
+        assert_eq!(
+            parsed["payload"]["after"],
+            json!({
+                "dt": "2024-01-01 20:00:00",
+                "ts": "2024-01-02 04:00:00",
+                "tstz": "2024-01-02 04:00:00+08",
+            })
+        );
+    }
+}
@@ -2,6 +2,8 @@ use std::collections::HashMap;
 
 use anyhow::Result;
 use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, FixedOffset, NaiveDateTime, Offset, TimeZone, Utc};
+use mongodb::bson::{Bson, Document};
 use serde_json::{json, Value};
 
 use crate::{
@@ -10,18 +12,28 @@ use crate::{
         col_value::ColValue,
         ddl_meta::ddl_data::DdlData,
         rdb_meta_manager::RdbMetaManager,
+        rdb_tb_meta::RdbTbMeta,
         row_data::RowData,
         row_type::RowType,
     },
 };
 
-use super::cloudcanal_converter::CloudCanalConverter;
+use super::{
+    canal_converter::CanalConverter, cloudcanal_converter::CloudCanalConverter,
+    debezium_converter::DebeziumConverter,
+};
 
 #[derive(Clone)]
 pub struct JsonConverter {
     pub meta_manager: Option<RdbMetaManager>,
     pub template_type: JsonTemplateType,
     pub cloudcanal_converter: Option<CloudCanalConverter>,
+    pub debezium_converter: Option<DebeziumConverter>,
+    pub canal_converter: Option<CanalConverter>,
+    /// only send the changed cols (and primary key cols) of updates, standard template only
+    pub diff_only: bool,
+    /// timezone to format Timestamp values in, UTC by default
+    pub timezone: FixedOffset,
 }
 
 impl JsonConverter {
@@ -30,6 +42,10 @@ impl JsonConverter {
             meta_manager: meta_manager.clone(),
             template_type: JsonTemplateType::Standard,
             cloudcanal_converter: None,
+            debezium_converter: None,
+            canal_converter: None,
+            diff_only: false,
+            timezone: Utc.fix(),
         }
     }
 
@@ -38,9 +54,24 @@ impl JsonConverter {
         meta_manager: Option<RdbMetaManager>,
         template_type: JsonTemplateType,
         database_name: Option<String>,
+        snapshot: bool,
     ) -> Self {
         let cloudcanal_converter = match template_type {
-            JsonTemplateType::CloudCanal => Some(CloudCanalConverter::new(meta_manager.clone(), database_name)),
+            JsonTemplateType::CloudCanal => Some(CloudCanalConverter::new(meta_manager.clone(), database_name.clone())),
+            _ => None,
+        };
+        let debezium_converter = match template_type {
+            JsonTemplateType::Debezium => Some(DebeziumConverter::new(
+                meta_manager.clone(),
+                database_name.clone(),
+                snapshot,
+            )),
+            _ => None,
+        };
+        let canal_converter = match template_type {
+            JsonTemplateType::Canal => {
+                Some(CanalConverter::new(meta_manager.clone(), database_name))
+            }
             _ => None,
         };
 
@@ -48,6 +79,30 @@ impl JsonConverter {
             meta_manager,
             template_type,
             cloudcanal_converter,
+            debezium_converter,
+            canal_converter,
+            diff_only: false,
+            timezone: Utc.fix(),
+        }
+    }
+
+    /// 每批取出的数据发送前调用一次，CloudCanal 模板的 bid 按批次递增
+    pub fn next_batch(&mut self) {
+        if let Some(cloudcanal_converter) = &mut self.cloudcanal_converter {
+            cloudcanal_converter.next_batch();
+        }
+    }
+
+    pub fn set_timezone(&mut self, timezone: FixedOffset) {
+        self.timezone = timezone;
+        if let Some(cloudcanal_converter) = &mut self.cloudcanal_converter {
+            cloudcanal_converter.timezone = timezone;
+        }
+        if let Some(debezium_converter) = &mut self.debezium_converter {
+            debezium_converter.timezone = timezone;
+        }
+        if let Some(canal_converter) = &mut self.canal_converter {
+            canal_converter.timezone = timezone;
         }
     }
 
@@ -62,11 +117,19 @@ impl JsonConverter {
         if let Some(cloudcanal_converter) = &mut self.cloudcanal_converter {
             cloudcanal_converter.refresh_meta(data);
         }
+        if let Some(debezium_converter) = &mut self.debezium_converter {
+            debezium_converter.refresh_meta(data);
+        }
+        if let Some(canal_converter) = &mut self.canal_converter {
+            canal_converter.refresh_meta(data);
+        }
     }
 
     pub async fn row_data_to_json_key(&mut self, row_data: &RowData) -> Result<String> {
         match self.template_type {
-            JsonTemplateType::Standard => self.standard_row_data_to_json_key(row_data).await,
+            JsonTemplateType::Standard | JsonTemplateType::Debezium => {
+                self.standard_row_data_to_json_key(row_data).await
+            }
             JsonTemplateType::CloudCanal => {
                 if let Some(cloudcanal_converter) = &mut self.cloudcanal_converter {
                     cloudcanal_converter.row_data_to_json_key(row_data).await
@@ -74,6 +137,13 @@ impl JsonConverter {
                     self.standard_row_data_to_json_key(row_data).await
                 }
             }
+            JsonTemplateType::Canal => {
+                if let Some(canal_converter) = &mut self.canal_converter {
+                    canal_converter.row_data_to_json_key(row_data).await
+                } else {
+                    self.standard_row_data_to_json_key(row_data).await
+                }
+            }
         }
     }
 
@@ -87,6 +157,20 @@ impl JsonConverter {
                     self.standard_row_data_to_json_value(row_data).await
                 }
             }
+            JsonTemplateType::Debezium => {
+                if let Some(debezium_converter) = &mut self.debezium_converter {
+                    debezium_converter.row_data_to_json_value(row_data).await
+                } else {
+                    self.standard_row_data_to_json_value(row_data).await
+                }
+            }
+            JsonTemplateType::Canal => {
+                if let Some(canal_converter) = &mut self.canal_converter {
+                    canal_converter.row_data_to_json_value(row_data).await
+                } else {
+                    self.standard_row_data_to_json_value(row_data).await
+                }
+            }
         }
     }
 
@@ -100,26 +184,65 @@ impl JsonConverter {
                     self.standard_ddl_data_to_json_value(ddl_data).await
                 }
             }
+            JsonTemplateType::Debezium => {
+                if let Some(debezium_converter) = &mut self.debezium_converter {
+                    debezium_converter.ddl_data_to_json_value(ddl_data).await
+                } else {
+                    self.standard_ddl_data_to_json_value(ddl_data).await
+                }
+            }
+            JsonTemplateType::Canal => {
+                if let Some(canal_converter) = &mut self.canal_converter {
+                    canal_converter.ddl_data_to_json_value(ddl_data).await
+                } else {
+                    self.standard_ddl_data_to_json_value(ddl_data).await
+                }
+            }
         }
     }
 
     async fn standard_row_data_to_json_key(&mut self, row_data: &RowData) -> Result<String> {
         if let Some(meta_manager) = &mut self.meta_manager {
             if let Ok(tb_meta) = meta_manager.get_tb_meta(&row_data.schema, &row_data.tb).await {
-                if let Some(primary_key) = tb_meta.key_map.get("primary") {
-                    let mut key_values = Vec::new();
-                    for pk_col in primary_key {
-                        if let Some(col_value) = row_data.after.as_ref().and_then(|after| after.get(pk_col)) {
-                            key_values.push(col_value_to_json_value(col_value));
-                        }
-                    }
-                    return Ok(serde_json::to_string(&key_values)?);
+                if let Some(key) = Self::get_json_key(tb_meta, row_data)? {
+                    return Ok(key);
                 }
             }
         }
         Ok(format!("{}_{}", row_data.schema, row_data.tb))
     }
 
+    /// Key built from the primary key values of the row, None if the table has no primary key.
+    pub fn get_json_key(tb_meta: &RdbTbMeta, row_data: &RowData) -> Result<Option<String>> {
+        let Some(primary_key) = tb_meta.key_map.get("primary") else {
+            return Ok(None);
+        };
+        let mut key_values = Vec::new();
+        for pk_col in primary_key {
+            if let Some(col_value) = row_data.after.as_ref().and_then(|after| after.get(pk_col)) {
+                key_values.push(col_value_to_json_value(col_value));
+            }
+        }
+        Ok(Some(serde_json::to_string(&key_values)?))
+    }
+
+    /// Key built from the given cols of the row, deletes take values from the before image.
+    pub fn get_json_key_by_cols(key_cols: &[String], row_data: &RowData) -> Result<String> {
+        let col_values = match row_data.row_type {
+            RowType::Delete => row_data.require_before()?,
+            RowType::Insert | RowType::Update => row_data.require_after()?,
+        };
+        let key_values: Vec<Value> = key_cols
+            .iter()
+            .map(|col| {
+                col_values
+                    .get(col)
+                    .map_or(Value::Null, col_value_to_json_value)
+            })
+            .collect();
+        Ok(serde_json::to_string(&key_values)?)
+    }
+
     async fn standard_row_data_to_json_value(&mut self, row_data: RowData) -> Result<String> {
         let mut json_obj = json!({
             "operation": match row_data.row_type {
@@ -133,16 +256,69 @@ impl JsonConverter {
             "after": serde_json::Value::Null,
         });
 
+        if self.diff_only && row_data.row_type == RowType::Update {
+            let mut pk_cols = Vec::new();
+            if let Some(meta_manager) = &mut self.meta_manager {
+                let tb_meta = meta_manager
+                    .get_tb_meta(&row_data.schema, &row_data.tb)
+                    .await?;
+                if let Some(primary_key) = tb_meta.key_map.get("primary") {
+                    pk_cols = primary_key.clone();
+                }
+            }
+            let (before, after) = Self::diff_col_values(
+                row_data.require_before()?,
+                row_data.require_after()?,
+                &pk_cols,
+                &self.timezone,
+            );
+            json_obj["before"] = before;
+            json_obj["after"] = after;
+            return Ok(serde_json::to_string(&json_obj)?);
+        }
+
         if let Some(before) = &row_data.before {
-            json_obj["before"] = col_values_to_json_value(before);
+            json_obj["before"] = col_values_to_json_value(before, &self.timezone);
         }
         if let Some(after) = &row_data.after {
-            json_obj["after"] = col_values_to_json_value(after);
+            json_obj["after"] = col_values_to_json_value(after, &self.timezone);
         }
 
         Ok(serde_json::to_string(&json_obj)?)
     }
 
+    /// Changed cols of an update, the primary key cols are always kept in after.
+    /// UnchangedToast values in after are treated as unchanged, other values are compared
+    /// by ColValue::semantic_eq.
+    fn diff_col_values(
+        before: &HashMap<String, ColValue>,
+        after: &HashMap<String, ColValue>,
+        pk_cols: &[String],
+        timezone: &FixedOffset,
+    ) -> (Value, Value) {
+        let mut before_map = serde_json::Map::new();
+        let mut after_map = serde_json::Map::new();
+        for (col, after_value) in after {
+            let before_value = before.get(col);
+            let changed = *after_value != ColValue::UnchangedToast
+                && !before_value.is_some_and(|v| v.semantic_eq(after_value));
+            if changed {
+                before_map.insert(
+                    col.clone(),
+                    before_value
+                        .map_or(Value::Null, |v| col_value_to_json_value_in_tz(v, timezone)),
+                );
+            }
+            if changed || pk_cols.contains(col) {
+                after_map.insert(
+                    col.clone(),
+                    col_value_to_json_value_in_tz(after_value, timezone),
+                );
+            }
+        }
+        (Value::Object(before_map), Value::Object(after_map))
+    }
+
     async fn standard_ddl_data_to_json_value(&mut self, ddl_data: DdlData) -> Result<String> {
         let json_obj = json!({
             "ddl": true,
@@ -156,15 +332,64 @@ impl JsonConverter {
     }
 }
 
-fn col_values_to_json_value(col_values: &HashMap<String, ColValue>) -> Value {
+fn col_values_to_json_value(
+    col_values: &HashMap<String, ColValue>,
+    timezone: &FixedOffset,
+) -> Value {
     let mut json_map = serde_json::Map::new();
     for (key, value) in col_values {
-        json_map.insert(key.clone(), col_value_to_json_value(value));
+        json_map.insert(key.clone(), col_value_to_json_value_in_tz(value, timezone));
     }
     Value::Object(json_map)
 }
 
-fn col_value_to_json_value(value: &ColValue) -> Value {
+/// Same as col_value_to_json_value, but Timestamp values are formatted in the given timezone.
+pub fn col_value_to_json_value_in_tz(value: &ColValue, timezone: &FixedOffset) -> Value {
+    match value {
+        ColValue::Timestamp(v) => Value::String(format_timestamp(v, timezone)),
+        _ => col_value_to_json_value(value),
+    }
+}
+
+/// Timestamp values are instants in UTC (mysql) or carry their own offset (pg timestamptz),
+/// they are formatted as the wall clock time of the given timezone,
+/// pg values keep an offset, which becomes the offset of the given timezone.
+/// DateTime values have no timezone and are never converted,
+/// values that can not be parsed (e.g. 0000-00-00 00:00:00, infinity) are kept as is.
+pub fn format_timestamp(value: &str, timezone: &FixedOffset) -> String {
+    if timezone.local_minus_utc() == 0 {
+        return value.to_string();
+    }
+    if let Ok(dt) = DateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f%#z") {
+        let local = timezone.from_utc_datetime(&dt.naive_utc());
+        return format!(
+            "{}{}",
+            local.format("%Y-%m-%d %H:%M:%S%.f"),
+            format_pg_offset(timezone)
+        );
+    }
+    if let Ok(dt) = NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f") {
+        return timezone
+            .from_utc_datetime(&dt)
+            .format("%Y-%m-%d %H:%M:%S%.f")
+            .to_string();
+    }
+    value.to_string()
+}
+
+/// Offset in the format pg prints timestamptz with: +08, -03:30
+fn format_pg_offset(timezone: &FixedOffset) -> String {
+    let secs = timezone.local_minus_utc();
+    let sign = if secs < 0 { '-' } else { '+' };
+    let (hours, minutes) = (secs.abs() / 3600, secs.abs() % 3600 / 60);
+    if minutes == 0 {
+        format!("{}{:02}", sign, hours)
+    } else {
+        format!("{}{:02}:{:02}", sign, hours, minutes)
+    }
+}
+
+pub fn col_value_to_json_value(value: &ColValue) -> Value {
     match value {
         ColValue::None | ColValue::UnchangedToast => Value::Null,
         ColValue::Bool(v) => Value::Bool(*v),
@@ -190,24 +415,50 @@ fn col_value_to_json_value(value: &ColValue) -> Value {
                 Value::String(v.to_string())
             }
         }
-        ColValue::Decimal(v) => Value::String(v.clone()),
-        ColValue::Time(v) => Value::String(v.clone()),
-        ColValue::Date(v) => Value::String(v.clone()),
-        ColValue::DateTime(v) => Value::String(v.clone()),
-        ColValue::Timestamp(v) => Value::String(v.clone()),
         ColValue::Year(v) => Value::Number((*v).into()),
-        ColValue::String(v) => Value::String(v.clone()),
         ColValue::Json(v) => Value::String(String::from_utf8(v.clone()).unwrap_or_default()),
         ColValue::Json2(v) => Value::String(v.to_string()),
         ColValue::Json3(v) => Value::String(v.to_string()),
         ColValue::Blob(v) => Value::String(general_purpose::STANDARD.encode(v)),
-        ColValue::MongoDoc(v) => Value::String(v.to_string()),
-        ColValue::RawString(v) => Value::String(String::from_utf8_lossy(v).to_string()),
-        ColValue::Bit(v) => Value::String(v.to_string()),
-        ColValue::Set(v) => Value::String(v.to_string()),
-        ColValue::Enum(v) => Value::String(v.to_string()),
-        ColValue::Set2(v) => Value::String(v.clone()),
-        ColValue::Enum2(v) => Value::String(v.clone()),
+        ColValue::MongoDoc(v) => mongo_doc_to_json_value(v),
+        // 空间类型输出为 WKT，如 POINT(1 2)
+        ColValue::Geometry(_) => Value::String(value.to_display_string()),
+        ColValue::Decimal(_)
+        | ColValue::Time(_)
+        | ColValue::Date(_)
+        | ColValue::DateTime(_)
+        | ColValue::Timestamp(_)
+        | ColValue::String(_)
+        | ColValue::RawString(_)
+        | ColValue::Bit(_)
+        | ColValue::Set(_)
+        | ColValue::Enum(_)
+        | ColValue::Set2(_)
+        | ColValue::Enum2(_) => Value::String(value.to_display_string()),
+    }
+}
+
+/// Mongo doc as a json object, ObjectIds are written as hex strings and dates as rfc3339 strings,
+/// other bson types follow the relaxed extended json format.
+pub fn mongo_doc_to_json_value(doc: &Document) -> Value {
+    let json_map = doc
+        .iter()
+        .map(|(key, value)| (key.clone(), bson_to_json_value(value)))
+        .collect();
+    Value::Object(json_map)
+}
+
+fn bson_to_json_value(bson: &Bson) -> Value {
+    match bson {
+        Bson::Document(doc) => mongo_doc_to_json_value(doc),
+        Bson::Array(items) => Value::Array(items.iter().map(bson_to_json_value).collect()),
+        Bson::ObjectId(oid) => Value::String(oid.to_hex()),
+        Bson::DateTime(date_time) => match date_time.try_to_rfc3339_string() {
+            Ok(v) => Value::String(v),
+            // out of the rfc3339 range
+            Err(_) => bson.clone().into_relaxed_extjson(),
+        },
+        _ => bson.clone().into_relaxed_extjson(),
     }
 }
 
@@ -269,4 +520,183 @@ mod tests {
         assert_eq!(parsed["schema"], "test_schema");
         assert_eq!(parsed["query"], "CREATE TABLE test (id INT)");
     }
+
+    #[test]
+    fn test_diff_col_values() {
+        let before = HashMap::from([
+            ("id".to_string(), ColValue::Long(1)),
+            ("name".to_string(), ColValue::String("a".into())),
+            ("note".to_string(), ColValue::String("x".into())),
+            ("doc".to_string(), ColValue::Json2(r#"{"a": 1}"#.into())),
+        ]);
+        let mut after = before.clone();
+        after.insert("name".to_string(), ColValue::String("b".into()));
+        // the same json in another representation is not a change
+        after.insert("doc".to_string(), ColValue::Json3(json!({"a": 1})));
+
+        let (before, after) =
+            JsonConverter::diff_col_values(&before, &after, &["id".to_string()], &Utc.fix());
+        assert_eq!(before, json!({"name": "a"}));
+        assert_eq!(after, json!({"id": 1, "name": "b"}));
+    }
+
+    #[tokio::test]
+    async fn test_timestamp_in_timezone() {
+        // Asia/Shanghai
+        let timezone: FixedOffset = "+08:00".parse().unwrap();
+        assert_eq!(
+            format_timestamp("2024-01-01 20:00:00.123456", &timezone),
+            "2024-01-02 04:00:00.123456"
+        );
+        // pg timestamptz keeps an offset
+        assert_eq!(
+            format_timestamp("2024-01-01 20:00:00+00", &timezone),
+            "2024-01-02 04:00:00+08"
+        );
+        assert_eq!(
+            format_timestamp("2024-01-01 20:00:00.5+05:30", &"-03:30".parse().unwrap()),
+            "2024-01-01 11:00:00.500-03:30"
+        );
+        assert_eq!(
+            format_timestamp("0000-00-00 00:00:00", &timezone),
+            "0000-00-00 00:00:00"
+        );
+
+        let after = HashMap::from([
+            (
+                "ts".to_string(),
+                ColValue::Timestamp("2024-01-01 20:00:00".into()),
+            ),
+            (
+                "dt".to_string(),
+                ColValue::DateTime("2024-01-01 20:00:00".into()),
+            ),
+        ]);
+        let row_data = RowData::new(
+            "db_1".into(),
+            "tb_1".into(),
+            0,
+            RowType::Insert,
+            None,
+            Some(after),
+        );
+        let mut json_converter = JsonConverter::new(None);
+        json_converter.set_timezone(timezone);
+        let json_str = json_converter
+            .row_data_to_json_value(row_data)
+            .await
+            .unwrap();
+        let parsed: Value = serde_json::from_str(&json_str).unwrap();
+        assert_eq!(parsed["after"]["ts"], "2024-01-02 04:00:00");
+        assert_eq!(parsed["after"]["dt"], "2024-01-01 20:00:00");
+    }
+
+    #[tokio::test]
+    async fn test_set_timezone_for_templates() {
+        let after = HashMap::from([(
+            "tstz".to_string(),
+            ColValue::Timestamp("2024-01-01 20:00:00+00".into()),
+        )]);
+        for template_type in [
+            JsonTemplateType::Standard,
+            JsonTemplateType::CloudCanal,
+            JsonTemplateType::Debezium,
+            JsonTemplateType::Canal,
+        ] {
+            let row_data = RowData::new(
+                "db_1".into(),
+                "tb_1".into(),
+                0,
+                RowType::Insert,
+                None,
+                Some(after.clone()),
+            );
+            let mut json_converter =
+                JsonConverter::new_with_template(None, template_type.clone(), None, false);
+            json_converter.set_timezone("+08:00".parse().unwrap());
+            let json_str = json_converter
+                .row_data_to_json_value(row_data)
+                .await
+                .unwrap();
+            assert!(
+                json_str.contains(r#""tstz":"2024-01-02 04:00:00+08""#),
+                "{:?}: {}",
+                template_type,
+                json_str
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mongo_doc_to_json() {
+        use mongodb::bson::{doc, oid::ObjectId, DateTime};
+
+        let oid = ObjectId::parse_str("65a1b2c3d4e5f60718293a4b").unwrap();
+        let doc = doc! {
+            "_id": oid,
+            "name": "a",
+            "created_at": DateTime::from_millis(0),
+            "tags": ["x", { "k": 1_i64 }, [1, 2]],
+        };
+        let after = HashMap::from([("doc".to_string(), ColValue::MongoDoc(doc))]);
+        let row_data = RowData::new(
+            "db_1".into(),
+            "tb_1".into(),
+            0,
+            RowType::Insert,
+            None,
+            Some(after),
+        );
+
+        let mut json_converter = JsonConverter::new(None);
+        let json_str = json_converter
+            .row_data_to_json_value(row_data)
+            .await
+            .unwrap();
+        let parsed: Value = serde_json::from_str(&json_str).unwrap();
+        assert_eq!(
+            parsed["after"]["doc"],
+            json!({
+                "_id": "65a1b2c3d4e5f60718293a4b",
+                "name": "a",
+                "created_at": "1970-01-01T00:00:00Z",
+                "tags": ["x", { "k": 1 }, [1, 2]],
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_geometry_to_json() {
+        // SRID 4326 + little-endian wkb of POINT(1 2)
+        let mut point = 4326u32.to_le_bytes().to_vec();
+        point.push(1);
+        point.extend_from_slice(&1u32.to_le_bytes());
+        point.extend_from_slice(&1f64.to_le_bytes());
+        point.extend_from_slice(&2f64.to_le_bytes());
+        let after = HashMap::from([
+            ("geo".to_string(), ColValue::Geometry(point)),
+            // not parsable, sent as hex
+            (
+                "bad_geo".to_string(),
+                ColValue::Geometry(vec![0, 0, 0, 0, 9]),
+            ),
+        ]);
+        let row_data = RowData::new(
+            "db_1".into(),
+            "tb_1".into(),
+            0,
+            RowType::Insert,
+            None,
+            Some(after),
+        );
+
+        let mut json_converter = JsonConverter::new(None);
+        let json_str = json_converter
+            .row_data_to_json_value(row_data)
+            .await
+            .unwrap();
+        let parsed: Value = serde_json::from_str(&json_str).unwrap();
+        assert_eq!(parsed["after"]["geo"], "POINT(1 2)");
+        assert_eq!(parsed["after"]["bad_geo"], "0000000009");
+    }
 }
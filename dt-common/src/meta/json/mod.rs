@@ -1,2 +1,4 @@
 pub mod json_converter;
-pub mod cloudcanal_converter;
\ No newline at end of file
+pub mod cloudcanal_converter;
+pub mod debezium_converter;
+pub mod canal_converter;
\ No newline at end of file
@@ -2,6 +2,7 @@
 
 pub mod adaptor;
 pub mod avro;
+pub mod col_projection;
 pub mod col_value;
 pub mod json;
 pub mod dcl_meta;
@@ -16,6 +17,7 @@ pub mod mysql;
 pub mod order_key;
 pub mod pg;
 pub mod position;
+pub mod protobuf;
 pub mod rdb_meta_manager;
 pub mod rdb_tb_meta;
 pub mod redis;
@@ -25,3 +27,4 @@ pub mod struct_meta;
 pub mod syncer;
 pub mod tagged_col_value_map;
 pub mod time;
+pub mod unsupported_col_value_handler;
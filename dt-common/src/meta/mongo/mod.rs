@@ -1,3 +1,4 @@
 pub mod mongo_cdc_source;
 pub mod mongo_constant;
+pub mod mongo_doc_projection;
 pub mod mongo_key;
@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+
+use anyhow::bail;
+use mongodb::bson::{Bson, Document};
+use serde::{Deserialize, Serialize};
+
+use crate::{error::Error, meta::col_value::ColValue};
+
+const PATH_PREFIX: &str = "$.";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArrayPolicy {
+    // fail the row if a projected path resolves to an array
+    #[default]
+    Error,
+    // join scalar array elements into a single string
+    Join,
+}
+
+/// Flattens a mongo document into relational columns,
+/// cols: HashMap<dst_col, path>, path is dotted (address.city) with an optional "$." prefix,
+/// numeric segments index into arrays (tags.0).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MongoDocProjection {
+    pub cols: HashMap<String, String>,
+    #[serde(default)]
+    pub array_policy: ArrayPolicy,
+    #[serde(default = "default_array_delimiter")]
+    pub array_delimiter: String,
+}
+
+fn default_array_delimiter() -> String {
+    ",".to_string()
+}
+
+impl MongoDocProjection {
+    pub fn project(&self, doc: &Document) -> anyhow::Result<HashMap<String, ColValue>> {
+        let mut col_values = HashMap::with_capacity(self.cols.len());
+        for (col, path) in self.cols.iter() {
+            let col_value = match Self::get_path(doc, path) {
+                Some(bson) => self.to_col_value(col, path, bson)?,
+                None => ColValue::None,
+            };
+            col_values.insert(col.clone(), col_value);
+        }
+        Ok(col_values)
+    }
+
+    fn get_path<'a>(doc: &'a Document, path: &str) -> Option<&'a Bson> {
+        let path = path.trim_start_matches(PATH_PREFIX);
+        let mut segments = path.split('.');
+        let mut current = doc.get(segments.next()?)?;
+        for segment in segments {
+            current = match current {
+                Bson::Document(d) => d.get(segment)?,
+                Bson::Array(a) => a.get(segment.parse::<usize>().ok()?)?,
+                _ => return None,
+            };
+        }
+        Some(current)
+    }
+
+    fn to_col_value(&self, col: &str, path: &str, bson: &Bson) -> anyhow::Result<ColValue> {
+        match bson {
+            Bson::Array(items) => match self.array_policy {
+                ArrayPolicy::Error => bail! {Error::MetadataError(format!(
+                    "path: {} of col: {} is an array, set array_policy to join to flatten it",
+                    path, col
+                ))},
+                ArrayPolicy::Join => {
+                    let mut values = Vec::with_capacity(items.len());
+                    for item in items {
+                        if matches!(item, Bson::Document(_) | Bson::Array(_)) {
+                            bail! {Error::MetadataError(format!(
+                                "path: {} of col: {} contains nested elements, can not be joined",
+                                path, col
+                            ))}
+                        }
+                        if let Some(v) = ColValue::from(item.clone()).to_option_string() {
+                            values.push(v);
+                        }
+                    }
+                    Ok(ColValue::String(values.join(&self.array_delimiter)))
+                }
+            },
+            Bson::Document(_) => Ok(ColValue::Json3(bson.clone().into_relaxed_extjson())),
+            _ => Ok(ColValue::from(bson.clone())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mongodb::bson::doc;
+
+    use super::*;
+
+    fn build_projection(array_policy: ArrayPolicy) -> MongoDocProjection {
+        let cols = [
+            ("id", "_id"),
+            ("name", "name"),
+            ("city", "$.address.city"),
+            ("zip", "address.zip"),
+            ("first_tag", "tags.0"),
+            ("tags", "tags"),
+            ("geo", "address.geo"),
+            ("phone", "contact.phone"),
+        ];
+        MongoDocProjection {
+            cols: cols
+                .iter()
+                .map(|(col, path)| (col.to_string(), path.to_string()))
+                .collect(),
+            array_policy,
+            array_delimiter: "|".into(),
+        }
+    }
+
+    #[test]
+    fn test_project_nested_doc() {
+        let doc = doc! {
+            "_id": 1_i64,
+            "name": "a",
+            "address": { "city": "hangzhou", "zip": 310000, "geo": { "lat": 30.5 } },
+            "tags": ["x", "y"],
+        };
+
+        let projection = build_projection(ArrayPolicy::Join);
+        let col_values = projection.project(&doc).unwrap();
+        assert_eq!(col_values.len(), 8);
+        assert_eq!(col_values["id"], ColValue::LongLong(1));
+        assert_eq!(col_values["name"], ColValue::String("a".into()));
+        assert_eq!(col_values["city"], ColValue::String("hangzhou".into()));
+        assert_eq!(col_values["zip"], ColValue::Long(310000));
+        assert_eq!(col_values["first_tag"], ColValue::String("x".into()));
+        assert_eq!(col_values["tags"], ColValue::String("x|y".into()));
+        assert_eq!(
+            col_values["geo"],
+            ColValue::Json3(serde_json::json!({ "lat": 30.5 }))
+        );
+        // missing path
+        assert_eq!(col_values["phone"], ColValue::None);
+
+        let projection = build_projection(ArrayPolicy::Error);
+        assert!(projection.project(&doc).is_err());
+    }
+
+    #[test]
+    fn test_parse_projection() {
+        let config = r#"{"cols":{"city":"address.city"},"array_policy":"join"}"#;
+        let projection: MongoDocProjection = serde_json::from_str(config).unwrap();
+        assert_eq!(projection.array_policy, ArrayPolicy::Join);
+        assert_eq!(projection.array_delimiter, ",");
+
+        let config = r#"{"cols":{"city":"address.city"}}"#;
+        let projection: MongoDocProjection = serde_json::from_str(config).unwrap();
+        assert_eq!(projection.array_policy, ArrayPolicy::Error);
+    }
+}
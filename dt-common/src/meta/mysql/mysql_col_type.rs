@@ -90,6 +90,8 @@ pub enum MysqlColType {
         items: Vec<String>,
     },
     Json,
+    // all spatial types, values are in mysql internal geometry format
+    Geometry,
 }
 
 impl MysqlColType {
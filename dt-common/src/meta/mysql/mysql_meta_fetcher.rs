@@ -304,9 +304,10 @@ impl MysqlMetaFetcher {
             "bit" => MysqlColType::Bit,
             "json" => MysqlColType::Json,
 
-            // TODO
-            // "geometry": "geometrycollection": "linestring": "multilinestring":
-            // "multipoint": "multipolygon": "polygon": "point"
+            // geomcollection is the alias of geometrycollection in mysql 8.0
+            "geometry" | "point" | "linestring" | "polygon" | "multipoint" | "multilinestring"
+            | "multipolygon" | "geometrycollection" | "geomcollection" => MysqlColType::Geometry,
+
             _ => MysqlColType::Unknown,
         };
 
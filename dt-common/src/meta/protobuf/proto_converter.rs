@@ -0,0 +1,463 @@
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::{bail, Context};
+
+use crate::meta::{col_value::ColValue, ddl_meta::ddl_data::DdlData, row_data::RowData};
+
+const DDL: &str = "ddl";
+const DB_TYPE: &str = "db_type";
+const DDL_TYPE: &str = "ddl_type";
+const QUERY: &str = "query";
+
+// wire types
+const VARINT: u64 = 0;
+const FIXED64: u64 = 1;
+const LEN: u64 = 2;
+const FIXED32: u64 = 5;
+
+/// The canonical envelope of protobuf messages, consumers compile it from:
+///
+/// ```proto
+/// syntax = "proto3";
+/// package ape_dts;
+///
+/// message Value {
+///   oneof kind {
+///     bool null_value = 1;
+///     bool bool_value = 2;
+///     sint64 int_value = 3;
+///     uint64 uint_value = 4;
+///     double double_value = 5;
+///     string string_value = 6;
+///     bytes bytes_value = 7;
+///   }
+/// }
+///
+/// message Envelope {
+///   string schema = 1;
+///   string tb = 2;
+///   string operation = 3;
+///   map<string, Value> before = 4;
+///   map<string, Value> after = 5;
+///   map<string, Value> extra = 6;
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProtoEnvelope {
+    pub schema: String,
+    pub tb: String,
+    // insert / update / delete / ddl
+    pub operation: String,
+    pub before: BTreeMap<String, ProtoValue>,
+    pub after: BTreeMap<String, ProtoValue>,
+    // db_type / ddl_type / query of ddl messages
+    pub extra: BTreeMap<String, ProtoValue>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProtoValue {
+    Null,
+    Bool(bool),
+    Int(i64),
+    UInt(u64),
+    Double(f64),
+    String(String),
+    Bytes(Vec<u8>),
+}
+
+pub struct ProtoConverter {}
+
+impl ProtoConverter {
+    pub fn row_data_to_proto_value(row_data: &RowData) -> Vec<u8> {
+        ProtoEnvelope {
+            schema: row_data.schema.clone(),
+            tb: row_data.tb.clone(),
+            operation: row_data.row_type.to_string(),
+            before: Self::col_values_to_proto(&row_data.before),
+            after: Self::col_values_to_proto(&row_data.after),
+            extra: BTreeMap::new(),
+        }
+        .encode()
+    }
+
+    pub fn ddl_data_to_proto_value(ddl_data: DdlData) -> Vec<u8> {
+        let extra = BTreeMap::from([
+            (
+                DB_TYPE.to_string(),
+                ProtoValue::String(ddl_data.db_type.to_string()),
+            ),
+            (
+                DDL_TYPE.to_string(),
+                ProtoValue::String(ddl_data.ddl_type.to_string()),
+            ),
+            (QUERY.to_string(), ProtoValue::String(ddl_data.query)),
+        ]);
+        ProtoEnvelope {
+            schema: ddl_data.default_schema,
+            operation: DDL.to_string(),
+            extra,
+            ..Default::default()
+        }
+        .encode()
+    }
+
+    fn col_values_to_proto(
+        col_values: &Option<HashMap<String, ColValue>>,
+    ) -> BTreeMap<String, ProtoValue> {
+        let Some(col_values) = col_values else {
+            return BTreeMap::new();
+        };
+        col_values
+            .iter()
+            .filter_map(|(col, value)| Self::col_value_to_proto(value).map(|v| (col.clone(), v)))
+            .collect()
+    }
+
+    /// Unchanged toast values are left out of the message since their values are unknown.
+    pub fn col_value_to_proto(value: &ColValue) -> Option<ProtoValue> {
+        let proto_value = match value {
+            ColValue::UnchangedToast => return None,
+            ColValue::None => ProtoValue::Null,
+            ColValue::Bool(v) => ProtoValue::Bool(*v),
+            ColValue::Tiny(v) => ProtoValue::Int(*v as i64),
+            ColValue::Short(v) => ProtoValue::Int(*v as i64),
+            ColValue::Long(v) => ProtoValue::Int(*v as i64),
+            ColValue::LongLong(v) => ProtoValue::Int(*v),
+            ColValue::UnsignedTiny(v) => ProtoValue::UInt(*v as u64),
+            ColValue::UnsignedShort(v) => ProtoValue::UInt(*v as u64),
+            ColValue::UnsignedLong(v) => ProtoValue::UInt(*v as u64),
+            ColValue::UnsignedLongLong(v) => ProtoValue::UInt(*v),
+            ColValue::Year(v) => ProtoValue::UInt(*v as u64),
+            ColValue::Bit(v) | ColValue::Set(v) => ProtoValue::UInt(*v),
+            ColValue::Enum(v) => ProtoValue::UInt(*v as u64),
+            ColValue::Float(v) => ProtoValue::Double(*v as f64),
+            ColValue::Double(v) => ProtoValue::Double(*v),
+            ColValue::RawString(v) | ColValue::Blob(v) | ColValue::Json(v) => {
+                ProtoValue::Bytes(v.clone())
+            }
+            // decimals, temporals, json texts, mongo docs and geometries as strings
+            _ => ProtoValue::String(value.to_option_string().unwrap_or_default()),
+        };
+        Some(proto_value)
+    }
+}
+
+impl ProtoEnvelope {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        Self::put_bytes(&mut buf, 1, self.schema.as_bytes());
+        Self::put_bytes(&mut buf, 2, self.tb.as_bytes());
+        Self::put_bytes(&mut buf, 3, self.operation.as_bytes());
+        for (field, map) in [(4, &self.before), (5, &self.after), (6, &self.extra)] {
+            for (key, value) in map {
+                let mut entry = Vec::new();
+                Self::put_bytes(&mut entry, 1, key.as_bytes());
+                Self::put_bytes(&mut entry, 2, &value.encode());
+                Self::put_tag(&mut buf, field, LEN);
+                Self::put_varint(&mut buf, entry.len() as u64);
+                buf.extend_from_slice(&entry);
+            }
+        }
+        buf
+    }
+
+    pub fn decode(payload: &[u8]) -> anyhow::Result<Self> {
+        let mut envelope = Self::default();
+        let mut reader = WireReader::new(payload);
+        while let Some((field, wire_type)) = reader.read_tag()? {
+            match (field, wire_type) {
+                (1, LEN) => envelope.schema = reader.read_string()?,
+                (2, LEN) => envelope.tb = reader.read_string()?,
+                (3, LEN) => envelope.operation = reader.read_string()?,
+                (4..=6, LEN) => {
+                    let (key, value) = Self::decode_map_entry(reader.read_bytes()?)?;
+                    let map = match field {
+                        4 => &mut envelope.before,
+                        5 => &mut envelope.after,
+                        _ => &mut envelope.extra,
+                    };
+                    map.insert(key, value);
+                }
+                _ => reader.skip(wire_type)?,
+            }
+        }
+        Ok(envelope)
+    }
+
+    fn decode_map_entry(payload: &[u8]) -> anyhow::Result<(String, ProtoValue)> {
+        let mut key = String::new();
+        // a missing value decodes as the default Value message, whose oneof is not set
+        let mut value = ProtoValue::Null;
+        let mut reader = WireReader::new(payload);
+        while let Some((field, wire_type)) = reader.read_tag()? {
+            match (field, wire_type) {
+                (1, LEN) => key = reader.read_string()?,
+                (2, LEN) => value = ProtoValue::decode(reader.read_bytes()?)?,
+                _ => reader.skip(wire_type)?,
+            }
+        }
+        Ok((key, value))
+    }
+
+    fn put_tag(buf: &mut Vec<u8>, field: u64, wire_type: u64) {
+        Self::put_varint(buf, (field << 3) | wire_type);
+    }
+
+    fn put_varint(buf: &mut Vec<u8>, mut v: u64) {
+        while v >= 0x80 {
+            buf.push((v as u8 & 0x7f) | 0x80);
+            v >>= 7;
+        }
+        buf.push(v as u8);
+    }
+
+    // proto3 leaves out empty scalars
+    fn put_bytes(buf: &mut Vec<u8>, field: u64, v: &[u8]) {
+        if v.is_empty() {
+            return;
+        }
+        Self::put_tag(buf, field, LEN);
+        Self::put_varint(buf, v.len() as u64);
+        buf.extend_from_slice(v);
+    }
+}
+
+impl ProtoValue {
+    fn encode(&self) -> Vec<u8> {
+        // oneof fields are written even if they hold default values
+        let mut buf = Vec::new();
+        match self {
+            Self::Null => Self::put_varint_field(&mut buf, 1, 1),
+            Self::Bool(v) => Self::put_varint_field(&mut buf, 2, *v as u64),
+            Self::Int(v) => Self::put_varint_field(&mut buf, 3, ((v << 1) ^ (v >> 63)) as u64),
+            Self::UInt(v) => Self::put_varint_field(&mut buf, 4, *v),
+            Self::Double(v) => {
+                ProtoEnvelope::put_tag(&mut buf, 5, FIXED64);
+                buf.extend_from_slice(&v.to_le_bytes());
+            }
+            Self::String(v) => Self::put_len_field(&mut buf, 6, v.as_bytes()),
+            Self::Bytes(v) => Self::put_len_field(&mut buf, 7, v),
+        }
+        buf
+    }
+
+    fn decode(payload: &[u8]) -> anyhow::Result<Self> {
+        let mut value = Self::Null;
+        let mut reader = WireReader::new(payload);
+        while let Some((field, wire_type)) = reader.read_tag()? {
+            value = match (field, wire_type) {
+                (1, VARINT) => {
+                    reader.read_varint()?;
+                    Self::Null
+                }
+                (2, VARINT) => Self::Bool(reader.read_varint()? != 0),
+                (3, VARINT) => {
+                    let v = reader.read_varint()?;
+                    Self::Int((v >> 1) as i64 ^ -((v & 1) as i64))
+                }
+                (4, VARINT) => Self::UInt(reader.read_varint()?),
+                (5, FIXED64) => Self::Double(f64::from_le_bytes(reader.read_fixed::<8>()?)),
+                (6, LEN) => Self::String(reader.read_string()?),
+                (7, LEN) => Self::Bytes(reader.read_bytes()?.to_vec()),
+                _ => {
+                    reader.skip(wire_type)?;
+                    continue;
+                }
+            };
+        }
+        Ok(value)
+    }
+
+    fn put_varint_field(buf: &mut Vec<u8>, field: u64, v: u64) {
+        ProtoEnvelope::put_tag(buf, field, VARINT);
+        ProtoEnvelope::put_varint(buf, v);
+    }
+
+    fn put_len_field(buf: &mut Vec<u8>, field: u64, v: &[u8]) {
+        ProtoEnvelope::put_tag(buf, field, LEN);
+        ProtoEnvelope::put_varint(buf, v.len() as u64);
+        buf.extend_from_slice(v);
+    }
+}
+
+struct WireReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> WireReader<'a> {
+    fn new(buf: &'a [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    fn read_tag(&mut self) -> anyhow::Result<Option<(u64, u64)>> {
+        if self.pos >= self.buf.len() {
+            return Ok(None);
+        }
+        let tag = self.read_varint()?;
+        Ok(Some((tag >> 3, tag & 0x7)))
+    }
+
+    fn read_varint(&mut self) -> anyhow::Result<u64> {
+        let mut v = 0u64;
+        for shift in (0..64).step_by(7) {
+            let Some(byte) = self.buf.get(self.pos) else {
+                bail!("truncated varint at: {}", self.pos)
+            };
+            self.pos += 1;
+            v |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(v);
+            }
+        }
+        bail!("varint too long at: {}", self.pos)
+    }
+
+    fn read_fixed<const N: usize>(&mut self) -> anyhow::Result<[u8; N]> {
+        let bytes = self
+            .buf
+            .get(self.pos..self.pos + N)
+            .with_context(|| format!("truncated fixed{} at: {}", N * 8, self.pos))?;
+        self.pos += N;
+        Ok(bytes.try_into()?)
+    }
+
+    fn read_bytes(&mut self) -> anyhow::Result<&'a [u8]> {
+        let len = self.read_varint()? as usize;
+        let bytes = self
+            .buf
+            .get(self.pos..self.pos + len)
+            .with_context(|| format!("truncated bytes at: {}, len: {}", self.pos, len))?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn read_string(&mut self) -> anyhow::Result<String> {
+        Ok(String::from_utf8(self.read_bytes()?.to_vec())?)
+    }
+
+    fn skip(&mut self, wire_type: u64) -> anyhow::Result<()> {
+        match wire_type {
+            VARINT => {
+                self.read_varint()?;
+            }
+            FIXED64 => {
+                self.read_fixed::<8>()?;
+            }
+            LEN => {
+                self.read_bytes()?;
+            }
+            FIXED32 => {
+                self.read_fixed::<4>()?;
+            }
+            _ => bail!("unsupported wire type: {}", wire_type),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        config::config_enums::DbType,
+        meta::{
+            ddl_meta::{ddl_statement::DdlStatement, ddl_type::DdlType},
+            row_type::RowType,
+        },
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_decode_row_data() {
+        let before = HashMap::from([
+            ("id".to_string(), ColValue::LongLong(-300)),
+            ("name".to_string(), ColValue::String("a".into())),
+            ("price".to_string(), ColValue::Decimal("1.50".into())),
+            ("toast".to_string(), ColValue::UnchangedToast),
+        ]);
+        let after = HashMap::from([
+            ("id".to_string(), ColValue::LongLong(-300)),
+            ("name".to_string(), ColValue::None),
+            ("price".to_string(), ColValue::Double(2.25)),
+            ("flag".to_string(), ColValue::Bool(false)),
+            ("cnt".to_string(), ColValue::UnsignedLongLong(u64::MAX)),
+            ("blob".to_string(), ColValue::Blob(vec![0, 255])),
+            ("empty".to_string(), ColValue::String(String::new())),
+        ]);
+        let row_data = RowData::new(
+            "db_1".into(),
+            "tb_1".into(),
+            0,
+            RowType::Update,
+            Some(before),
+            Some(after),
+        );
+
+        let payload = ProtoConverter::row_data_to_proto_value(&row_data);
+        let envelope = ProtoEnvelope::decode(&payload).unwrap();
+        assert_eq!(envelope.schema, "db_1");
+        assert_eq!(envelope.tb, "tb_1");
+        assert_eq!(envelope.operation, "update");
+        assert_eq!(
+            envelope.before,
+            BTreeMap::from([
+                ("id".to_string(), ProtoValue::Int(-300)),
+                ("name".to_string(), ProtoValue::String("a".into())),
+                ("price".to_string(), ProtoValue::String("1.50".into())),
+            ])
+        );
+        assert_eq!(
+            envelope.after,
+            BTreeMap::from([
+                ("id".to_string(), ProtoValue::Int(-300)),
+                ("name".to_string(), ProtoValue::Null),
+                ("price".to_string(), ProtoValue::Double(2.25)),
+                ("flag".to_string(), ProtoValue::Bool(false)),
+                ("cnt".to_string(), ProtoValue::UInt(u64::MAX)),
+                ("blob".to_string(), ProtoValue::Bytes(vec![0, 255])),
+                ("empty".to_string(), ProtoValue::String(String::new())),
+            ])
+        );
+        assert!(envelope.extra.is_empty());
+    }
+
+    #[test]
+    fn test_decode_ddl_data() {
+        let ddl_data = DdlData {
+            default_schema: "db_1".into(),
+            query: "DROP TABLE tb_1".into(),
+            ddl_type: DdlType::DropTable,
+            db_type: DbType::Mysql,
+            statement: DdlStatement::default(),
+        };
+        let envelope =
+            ProtoEnvelope::decode(&ProtoConverter::ddl_data_to_proto_value(ddl_data)).unwrap();
+        assert_eq!(envelope.schema, "db_1");
+        assert_eq!(envelope.tb, "");
+        assert_eq!(envelope.operation, "ddl");
+        assert!(envelope.before.is_empty() && envelope.after.is_empty());
+        assert_eq!(
+            envelope.extra.get("query"),
+            Some(&ProtoValue::String("DROP TABLE tb_1".into()))
+        );
+        assert_eq!(
+            envelope.extra.get("db_type"),
+            Some(&ProtoValue::String("mysql".into()))
+        );
+    }
+
+    #[test]
+    fn test_decode_truncated_payload() {
+        let row_data = RowData::new(
+            "db_1".into(),
+            "tb_1".into(),
+            0,
+            RowType::Insert,
+            None,
+            Some(HashMap::from([("id".to_string(), ColValue::Long(1))])),
+        );
+        let payload = ProtoConverter::row_data_to_proto_value(&row_data);
+        assert!(ProtoEnvelope::decode(&payload[..payload.len() - 1]).is_err());
+    }
+}
@@ -41,6 +41,7 @@ pub enum TaggedColValueDef {
     Json2(String),
     Json3(serde_json::Value),
     MongoDoc(Document),
+    Geometry(Vec<u8>),
 }
 
 #[derive(Serialize)]
@@ -0,0 +1,234 @@
+use std::collections::HashMap;
+
+use anyhow::bail;
+
+use crate::{
+    config::config_enums::{BinaryEncoding, UnsupportedColValuePolicy},
+    error::Error,
+    meta::{col_value::ColValue, row_data::RowData},
+    utils::sql_util::SqlUtil,
+};
+
+/// Col value types (ColValue::type_name) a sinker can not write as is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColValueCapability {
+    pub sinker: &'static str,
+    pub unsupported: &'static [&'static str],
+}
+
+impl ColValueCapability {
+    // raw strings are text of unknown charsets, every sinker converts them to strings
+    // kafka json: binaries are base64 encoded, json bytes are utf8 decoded
+    pub const KAFKA_JSON: Self = Self {
+        sinker: "kafka json",
+        unsupported: &["Blob", "Json"],
+    };
+    // kafka avro: mongo docs are stringified
+    pub const KAFKA_AVRO: Self = Self {
+        sinker: "kafka avro",
+        unsupported: &["MongoDoc"],
+    };
+    // kafka protobuf: mongo docs are stringified
+    pub const KAFKA_PROTOBUF: Self = Self {
+        sinker: "kafka protobuf",
+        unsupported: &["MongoDoc"],
+    };
+    // stream load: binaries are supported by VARBINARY/BINARY target cols, which take them
+    // encoded by binary_encoding, other target cols get them as utf8 or encoded strings
+    pub const STARROCKS: Self = Self {
+        sinker: "starrocks",
+        unsupported: &["Blob"],
+    };
+    pub const DORIS: Self = Self {
+        sinker: "doris",
+        unsupported: &["Blob"],
+    };
+    // clickhouse: binaries are sent as 0x prefixed hex strings
+    pub const CLICKHOUSE: Self = Self {
+        sinker: "clickhouse",
+        unsupported: &["Blob"],
+    };
+
+    pub fn is_supported(&self, col_value: &ColValue) -> bool {
+        !self.unsupported.contains(&col_value.type_name())
+    }
+}
+
+/// Applies [sinker] unsupported_col_value_policy to the col values a sinker can not write as is,
+/// with the default policy (convert), they are left to the sinker's own conversion.
+#[derive(Debug, Clone)]
+pub struct UnsupportedColValueHandler {
+    pub capability: ColValueCapability,
+    pub policy: UnsupportedColValuePolicy,
+}
+
+impl UnsupportedColValueHandler {
+    pub fn new(capability: ColValueCapability, policy: UnsupportedColValuePolicy) -> Self {
+        Self { capability, policy }
+    }
+
+    pub fn handle_row(&self, row_data: &mut RowData) -> anyhow::Result<()> {
+        self.handle_row_by_target(row_data, |_, _| false)
+    }
+
+    /// Same as handle_row, except that values the target col takes as is,
+    /// decided by target_accepts(col, col_value), are left unchanged.
+    pub fn handle_row_by_target<F>(
+        &self,
+        row_data: &mut RowData,
+        target_accepts: F,
+    ) -> anyhow::Result<()>
+    where
+        F: Fn(&str, &ColValue) -> bool,
+    {
+        if self.policy == UnsupportedColValuePolicy::Convert {
+            return Ok(());
+        }
+        let (schema, tb) = (&row_data.schema, &row_data.tb);
+        if let Some(before) = &mut row_data.before {
+            self.handle_col_values(schema, tb, before, &target_accepts)?;
+        }
+        if let Some(after) = &mut row_data.after {
+            self.handle_col_values(schema, tb, after, &target_accepts)?;
+        }
+        Ok(())
+    }
+
+    fn handle_col_values<F>(
+        &self,
+        schema: &str,
+        tb: &str,
+        col_values: &mut HashMap<String, ColValue>,
+        target_accepts: &F,
+    ) -> anyhow::Result<()>
+    where
+        F: Fn(&str, &ColValue) -> bool,
+    {
+        let unsupported_cols: Vec<String> = col_values
+            .iter()
+            .filter(|(col, v)| !self.capability.is_supported(v) && !target_accepts(col, v))
+            .map(|(col, _)| col.clone())
+            .collect();
+
+        for col in unsupported_cols {
+            match self.policy {
+                UnsupportedColValuePolicy::Convert => {}
+                UnsupportedColValuePolicy::Error => {
+                    bail! {Error::SinkerError(format!(
+                        "{} sinker does not support {} value, schema: {}, tb: {}, col: {}",
+                        self.capability.sinker,
+                        col_values[&col].type_name(),
+                        schema,
+                        tb,
+                        col
+                    ))}
+                }
+                UnsupportedColValuePolicy::Null => {
+                    col_values.insert(col, ColValue::None);
+                }
+                UnsupportedColValuePolicy::Base64String => {
+                    let col_value = Self::to_base64_string(&col_values[&col]);
+                    col_values.insert(col, col_value);
+                }
+                UnsupportedColValuePolicy::SkipColumn => {
+                    col_values.remove(&col);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn to_base64_string(col_value: &ColValue) -> ColValue {
+        // binaries are encoded as is, others by their string representation
+        let bytes = match col_value {
+            ColValue::RawString(v) | ColValue::Blob(v) | ColValue::Json(v) => v.clone(),
+            _ => match col_value.to_option_string() {
+                Some(v) => v.into_bytes(),
+                None => return ColValue::None,
+            },
+        };
+        ColValue::String(SqlUtil::binary_to_encoded_str(
+            &bytes,
+            &BinaryEncoding::Base64,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::{engine::general_purpose, Engine as _};
+
+    use super::*;
+    use crate::meta::row_type::RowType;
+
+    fn build_row() -> RowData {
+        let after = HashMap::from([
+            ("id".to_string(), ColValue::Long(1)),
+            ("geo".to_string(), ColValue::Blob(vec![0x01, 0x02, 0xff])),
+            ("doc".to_string(), ColValue::Json(br#"{"a":1}"#.to_vec())),
+        ]);
+        RowData::new(
+            "db_1".into(),
+            "tb_1".into(),
+            0,
+            RowType::Insert,
+            None,
+            Some(after),
+        )
+    }
+
+    #[test]
+    fn test_handle_row() {
+        let handle = |policy| {
+            let handler = UnsupportedColValueHandler::new(ColValueCapability::KAFKA_JSON, policy);
+            let mut row_data = build_row();
+            handler
+                .handle_row(&mut row_data)
+                .map(|_| row_data.after.unwrap())
+        };
+
+        let after = handle(UnsupportedColValuePolicy::Convert).unwrap();
+        assert_eq!(after, build_row().after.unwrap());
+
+        let err = handle(UnsupportedColValuePolicy::Error).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("kafka json sinker does not support"));
+
+        let after = handle(UnsupportedColValuePolicy::Null).unwrap();
+        assert_eq!(after["id"], ColValue::Long(1));
+        assert_eq!(after["geo"], ColValue::None);
+        assert_eq!(after["doc"], ColValue::None);
+
+        let after = handle(UnsupportedColValuePolicy::Base64String).unwrap();
+        assert_eq!(after["geo"], ColValue::String("AQL/".into()));
+        assert_eq!(
+            after["doc"],
+            ColValue::String(general_purpose::STANDARD.encode(r#"{"a":1}"#))
+        );
+
+        let after = handle(UnsupportedColValuePolicy::SkipColumn).unwrap();
+        assert_eq!(after.len(), 1);
+        assert_eq!(after["id"], ColValue::Long(1));
+    }
+
+    #[test]
+    fn test_handle_row_by_target() {
+        let handler = UnsupportedColValueHandler::new(
+            ColValueCapability::STARROCKS,
+            UnsupportedColValuePolicy::Null,
+        );
+        let mut row_data = build_row();
+        // the target col of geo takes binaries
+        handler
+            .handle_row_by_target(&mut row_data, |col, _| col == "geo")
+            .unwrap();
+        assert_eq!(row_data.after, build_row().after);
+
+        let mut row_data = build_row();
+        handler
+            .handle_row_by_target(&mut row_data, |_, _| false)
+            .unwrap();
+        assert_eq!(row_data.after.unwrap()["geo"], ColValue::None);
+    }
+}
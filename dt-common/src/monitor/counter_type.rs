@@ -25,6 +25,23 @@ pub enum CounterType {
     CheckerMissCount,
     #[strum(serialize = "checker_diff_count")]
     CheckerDiffCount,
+    // rows of each sub batch split by rdb_partition parallelizer
+    #[strum(serialize = "partition_records")]
+    PartitionRecords,
+    // max sub batch rows * 100 / avg sub batch rows of each batch, 100 means even
+    #[strum(serialize = "partition_skew")]
+    PartitionSkew,
+    // milliseconds a write waited for the sinker rate limiter
+    #[strum(serialize = "throttle_wait")]
+    ThrottleWait,
+    // rows loaded and filtered by the target, as reported by stream load responses
+    #[strum(serialize = "loaded_records")]
+    LoadedRecords,
+    #[strum(serialize = "filtered_records")]
+    FilteredRecords,
+    // milliseconds the target took to load a stream load request, LoadTimeMs
+    #[strum(serialize = "load_time")]
+    LoadTime,
     // time window counter, aggregate by: avg by count
     #[strum(serialize = "record_size")]
     RecordSize,
@@ -68,6 +85,12 @@ pub enum AggregateType {
     Sum,
     #[strum(serialize = "count")]
     Count,
+    #[strum(serialize = "p50")]
+    P50,
+    #[strum(serialize = "p95")]
+    P95,
+    #[strum(serialize = "p99")]
+    P99,
 }
 
 pub enum WindowType {
@@ -86,6 +109,12 @@ impl CounterType {
             | Self::RecordsPerQuery
             | Self::RtPerQuery
             | Self::BufferSize
+            | Self::PartitionRecords
+            | Self::PartitionSkew
+            | Self::ThrottleWait
+            | Self::LoadedRecords
+            | Self::FilteredRecords
+            | Self::LoadTime
             | Self::DataBytes
             | Self::RecordSize
             | Self::ExtractedRecords
@@ -107,7 +136,23 @@ impl CounterType {
             WindowType::NoWindow => vec![AggregateType::Latest],
 
             WindowType::TimeWindow => match self {
-                Self::RecordsPerQuery | Self::RtPerQuery | Self::BufferSize => {
+                Self::RtPerQuery => {
+                    vec![
+                        AggregateType::Sum,
+                        AggregateType::AvgByCount,
+                        AggregateType::MaxByCount,
+                        AggregateType::MinByCount,
+                        AggregateType::P50,
+                        AggregateType::P95,
+                        AggregateType::P99,
+                    ]
+                }
+
+                Self::RecordsPerQuery
+                | Self::BufferSize
+                | Self::PartitionRecords
+                | Self::ThrottleWait
+                | Self::LoadTime => {
                     vec![
                         AggregateType::Sum,
                         AggregateType::AvgByCount,
@@ -120,12 +165,18 @@ impl CounterType {
                     vec![AggregateType::AvgByCount]
                 }
 
+                Self::PartitionSkew => {
+                    vec![AggregateType::AvgByCount, AggregateType::MaxByCount]
+                }
+
                 Self::BatchWriteFailures
                 | Self::SerialWrites
                 | Self::RecordCount
                 | Self::CheckerMissCount
                 | Self::CheckerDiffCount
                 | Self::DataBytes
+                | Self::LoadedRecords
+                | Self::FilteredRecords
                 | Self::ExtractedRecords
                 | Self::ExtractedBytes => {
                     vec![
@@ -140,4 +191,13 @@ impl CounterType {
             },
         }
     }
+
+    pub fn has_percentiles(&self) -> bool {
+        self.get_aggregate_types().iter().any(|aggregate_type| {
+            matches!(
+                aggregate_type,
+                AggregateType::P50 | AggregateType::P95 | AggregateType::P99
+            )
+        })
+    }
 }
@@ -78,7 +78,11 @@ impl GroupMonitor {
                     if !counter.has_live_data().await {
                         continue;
                     }
-                    let statistics = counter.statistics().await;
+                    let statistics = if counter_type.has_percentiles() {
+                        counter.statistics_with_percentiles().await
+                    } else {
+                        counter.statistics().await
+                    };
                     window_counter_statistics_map
                         .entry(counter_type)
                         .or_default()
@@ -107,6 +111,9 @@ impl GroupMonitor {
                         AggregateType::MaxBySec => statistics.max_by_sec,
                         AggregateType::MaxByCount => statistics.max,
                         AggregateType::Count => statistics.count,
+                        AggregateType::P50 => statistics.p50,
+                        AggregateType::P95 => statistics.p95,
+                        AggregateType::P99 => statistics.p99,
                         _ => continue,
                     };
                 }
@@ -3,6 +3,8 @@ use async_trait::async_trait;
 pub mod counter;
 pub mod counter_type;
 pub mod group_monitor;
+pub mod status_server;
+pub mod table_metrics;
 pub mod task_metrics;
 pub mod task_monitor;
 pub mod task_monitor_handle;
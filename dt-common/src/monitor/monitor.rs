@@ -107,7 +107,11 @@ impl Monitor {
                 .get(&counter_type)
                 .map(|r| r.value().clone());
             if let Some(counter) = counter {
-                let statistics = counter.statistics().await;
+                let statistics = if counter_type.has_percentiles() {
+                    counter.statistics_with_percentiles().await
+                } else {
+                    counter.statistics().await
+                };
                 let mut log = format!("{} | {} | {}", self.name, self.description, counter_type);
                 for aggregate_type in counter_type.get_aggregate_types() {
                     let aggregate_value = match aggregate_type {
@@ -117,6 +121,9 @@ impl Monitor {
                         AggregateType::MaxBySec => statistics.max_by_sec,
                         AggregateType::MaxByCount => statistics.max,
                         AggregateType::Count => statistics.count,
+                        AggregateType::P50 => statistics.p50,
+                        AggregateType::P95 => statistics.p95,
+                        AggregateType::P99 => statistics.p99,
                         _ => continue,
                     };
                     log = format!("{} | {}={}", log, aggregate_type, aggregate_value);
@@ -3,15 +3,19 @@ use std::{collections::BTreeMap, sync::Arc};
 
 use actix_web::{middleware::Logger, web, App, HttpResponse, HttpServer, Responder, Result};
 use dashmap::DashMap;
-use prometheus::{Gauge, Opts, Registry, TextEncoder};
+use prometheus::{Gauge, GaugeVec, Opts, Registry, TextEncoder};
 
 use crate::config::config_enums::{TaskKind, TaskType};
 use crate::config::metrics_config::MetricsConfig;
-use crate::monitor::task_metrics::TaskMetricsType;
+use crate::monitor::{table_metrics::TableSinkCounts, task_metrics::TaskMetricsType};
+
+const OTHER_TABLES_LABEL: &str = "_other";
 
 pub struct PrometheusMetrics {
     registry: Arc<Registry>,
     metrics: DashMap<TaskMetricsType, Gauge>,
+    // labeled by table: schema.tb
+    table_metrics: DashMap<&'static str, GaugeVec>,
     task_type: Option<TaskType>,
     config: MetricsConfig,
 }
@@ -21,6 +25,7 @@ impl PrometheusMetrics {
         Self {
             registry: Arc::new(Registry::new()),
             metrics: DashMap::new(),
+            table_metrics: DashMap::new(),
             task_type,
             config,
         }
@@ -127,6 +132,21 @@ impl PrometheusMetrics {
             "the average response time of sinker, the unit is millisecond",
             TaskMetricsType::SinkerRtAvg,
         );
+        register_handler(
+            "sinker_rt_p50",
+            "the 50th percentile response time of sinker, the unit is millisecond",
+            TaskMetricsType::SinkerRtP50,
+        );
+        register_handler(
+            "sinker_rt_p95",
+            "the 95th percentile response time of sinker, the unit is millisecond",
+            TaskMetricsType::SinkerRtP95,
+        );
+        register_handler(
+            "sinker_rt_p99",
+            "the 99th percentile response time of sinker, the unit is millisecond",
+            TaskMetricsType::SinkerRtP99,
+        );
 
         register_handler(
             "sinker_rps_max",
@@ -230,6 +250,31 @@ impl PrometheusMetrics {
             TaskMetricsType::CheckerDiffRpsAvg,
         );
 
+        let register_table_handler = |metrics_name: &'static str, metrics_desc: &str| {
+            let metrics = GaugeVec::new(
+                Opts::new(metrics_name, metrics_desc)
+                    .const_labels(self.config.metrics_labels.to_owned()),
+                &["schema", "table"],
+            )
+            .unwrap();
+
+            self.registry.register(Box::new(metrics.clone())).unwrap();
+            self.table_metrics.insert(metrics_name, metrics);
+        };
+
+        register_table_handler(
+            "sinker_table_records",
+            "the records written to each target table",
+        );
+        register_table_handler(
+            "sinker_table_failed_records",
+            "the records failed to be written to each target table",
+        );
+        register_table_handler(
+            "sinker_table_bytes",
+            "the bytes written to each target table",
+        );
+
         if let Some(task_type) = &self.task_type {
             match task_type.kind {
                 TaskKind::Snapshot => {
@@ -250,11 +295,21 @@ impl PrometheusMetrics {
                         "the timestamp of task",
                         TaskMetricsType::Timestamp,
                     );
+                    register_handler(
+                        "lag_secs",
+                        "the seconds between now and the timestamp of task",
+                        TaskMetricsType::Delay,
+                    );
                     register_handler(
                         "sinker_ddl_count",
                         "the count of DDL operations",
                         TaskMetricsType::SinkerDdlCount,
                     );
+                    register_handler(
+                        "task_restart_count",
+                        "the count of task restarts after recoverable errors",
+                        TaskMetricsType::TaskRestartCount,
+                    );
                 }
                 TaskKind::Struct => {}
             }
@@ -270,6 +325,46 @@ impl PrometheusMetrics {
         }
     }
 
+    pub fn set_table_metrics(&self, table_counts: &BTreeMap<String, TableSinkCounts>) {
+        // to bound the label cardinality, tables not in allowlist are summed up
+        let mut label_counts: BTreeMap<(&str, &str), TableSinkCounts> = BTreeMap::new();
+        for (table, counts) in table_counts.iter() {
+            let (schema, tb) = table.split_once('.').unwrap_or(("", table.as_str()));
+            let labels = if self.is_table_allowed(schema, tb) {
+                (schema, tb)
+            } else {
+                (OTHER_TABLES_LABEL, OTHER_TABLES_LABEL)
+            };
+            let label_counts = label_counts.entry(labels).or_default();
+            label_counts.sinked_records += counts.sinked_records;
+            label_counts.failed_records += counts.failed_records;
+            label_counts.sinked_bytes += counts.sinked_bytes;
+        }
+
+        for (&(schema, tb), counts) in label_counts.iter() {
+            for (metrics_name, value) in [
+                ("sinker_table_records", counts.sinked_records),
+                ("sinker_table_failed_records", counts.failed_records),
+                ("sinker_table_bytes", counts.sinked_bytes),
+            ] {
+                if let Some(metrics) = self.table_metrics.get(metrics_name) {
+                    metrics.with_label_values(&[schema, tb]).set(value as f64);
+                }
+            }
+        }
+    }
+
+    fn is_table_allowed(&self, schema: &str, tb: &str) -> bool {
+        let allowlist = &self.config.table_allowlist;
+        allowlist.is_empty()
+            || allowlist.iter().any(|table| match table.split_once('.') {
+                Some((allowed_schema, allowed_tb)) => {
+                    allowed_schema == schema && (allowed_tb == "*" || allowed_tb == tb)
+                }
+                None => false,
+            })
+    }
+
     pub async fn start_metrics(&self) -> tokio::task::JoinHandle<Result<(), std::io::Error>> {
         let registry = self.registry.clone();
         let addr = format!("{}:{}", self.config.http_host, self.config.http_port);
@@ -298,11 +393,14 @@ impl PrometheusMetrics {
     }
 }
 
+fn encode_metrics(registry: &Registry, buffer: &mut String) -> prometheus::Result<()> {
+    TextEncoder::new().encode_utf8(&registry.gather(), buffer)
+}
+
 async fn metrics_handler(registry: web::Data<Arc<Registry>>) -> impl Responder {
     let mut buffer = String::new();
-    let encoder = TextEncoder::new();
 
-    match encoder.encode_utf8(&registry.gather(), &mut buffer) {
+    match encode_metrics(&registry, &mut buffer) {
         Ok(_) => HttpResponse::Ok()
             .content_type("text/plain; charset=utf-8; version=0.0.4")
             .body(buffer),
@@ -324,3 +422,115 @@ async fn not_found_handler() -> Result<impl Responder> {
         .content_type("application/json")
         .body(r#"{"error":"Not Found","message":"The requested endpoint does not exist"}"#))
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use regex::Regex;
+
+    use super::*;
+
+    fn build_metrics(table_allowlist: Vec<String>) -> PrometheusMetrics {
+        let config = MetricsConfig {
+            http_host: "127.0.0.1".into(),
+            http_port: 0,
+            workers: 1,
+            metrics_labels: HashMap::from([("task_id".to_string(), "task_1".to_string())]),
+            table_allowlist,
+        };
+        let metrics = PrometheusMetrics::new(Some(TaskType::new(TaskKind::Cdc, None)), config);
+        metrics.initialization();
+        metrics
+    }
+
+    fn table_counts(sinked_records: u64) -> TableSinkCounts {
+        TableSinkCounts {
+            sinked_records,
+            failed_records: 1,
+            sinked_bytes: sinked_records * 10,
+        }
+    }
+
+    #[test]
+    fn test_exposition_text() {
+        let metrics = build_metrics(vec!["db_1.*".into(), "db_2.tb_1".into()]);
+        metrics.set_metrics(&BTreeMap::from([
+            (TaskMetricsType::SinkerSinkedRecords, 13),
+            (TaskMetricsType::SinkerSinkedBytes, 586),
+            (TaskMetricsType::SinkerRtP50, 3),
+            (TaskMetricsType::SinkerRtP95, 8),
+            (TaskMetricsType::SinkerRtP99, 12),
+            (TaskMetricsType::Delay, 2),
+        ]));
+        metrics.set_table_metrics(&BTreeMap::from([
+            ("db_1.tb_1".to_string(), table_counts(5)),
+            ("db_2.tb_1".to_string(), table_counts(4)),
+            ("db_2.tb_2".to_string(), table_counts(3)),
+            ("db_3.tb_1".to_string(), table_counts(1)),
+        ]));
+
+        let mut text = String::new();
+        encode_metrics(&metrics.registry, &mut text).unwrap();
+
+        // # HELP name text | # TYPE name type | name{label="value",...} value
+        let comment_re = Regex::new(r"^# (HELP|TYPE) [a-zA-Z_:][a-zA-Z0-9_:]* .+$").unwrap();
+        let sample_re = Regex::new(
+            r#"^[a-zA-Z_:][a-zA-Z0-9_:]*(\{[a-zA-Z_][a-zA-Z0-9_]*="[^"]*"(,[a-zA-Z_][a-zA-Z0-9_]*="[^"]*")*\})? [-+]?[0-9.eE+-]+$"#,
+        )
+        .unwrap();
+        let mut samples = HashMap::new();
+        for line in text.lines().filter(|line| !line.is_empty()) {
+            if comment_re.is_match(line) {
+                continue;
+            }
+            assert!(
+                sample_re.is_match(line),
+                "invalid prometheus line: {}",
+                line
+            );
+            assert!(line.contains(r#"task_id="task_1""#), "{}", line);
+            let (key, value) = line.rsplit_once(' ').unwrap();
+            samples.insert(key.to_string(), value.to_string());
+        }
+
+        for (key, value) in [
+            (r#"sinker_sinked_records{task_id="task_1"}"#, "13"),
+            (r#"sinker_sinked_bytes{task_id="task_1"}"#, "586"),
+            (r#"sinker_rt_p50{task_id="task_1"}"#, "3"),
+            (r#"sinker_rt_p95{task_id="task_1"}"#, "8"),
+            (r#"sinker_rt_p99{task_id="task_1"}"#, "12"),
+            (r#"lag_secs{task_id="task_1"}"#, "2"),
+            (
+                r#"sinker_table_records{schema="db_1",table="tb_1",task_id="task_1"}"#,
+                "5",
+            ),
+            (
+                r#"sinker_table_records{schema="db_2",table="tb_1",task_id="task_1"}"#,
+                "4",
+            ),
+            // db_2.tb_2 and db_3.tb_1 are not in allowlist
+            (
+                r#"sinker_table_records{schema="_other",table="_other",task_id="task_1"}"#,
+                "4",
+            ),
+            (
+                r#"sinker_table_failed_records{schema="_other",table="_other",task_id="task_1"}"#,
+                "2",
+            ),
+            (
+                r#"sinker_table_bytes{schema="_other",table="_other",task_id="task_1"}"#,
+                "40",
+            ),
+        ] {
+            assert_eq!(samples.get(key).map(String::as_str), Some(value), "{}", key);
+        }
+        assert_eq!(
+            samples
+                .keys()
+                .filter(|key| key.starts_with("sinker_table_records{"))
+                .count(),
+            3
+        );
+    }
+}
@@ -0,0 +1,189 @@
+use std::{collections::BTreeMap, net::SocketAddr, sync::Arc};
+
+use actix_web::{dev::ServerHandle, web, App, HttpResponse, HttpServer, Responder};
+use anyhow::Context;
+use chrono::Utc;
+use serde::Serialize;
+
+use crate::{
+    config::status_server_config::StatusServerConfig,
+    log_info,
+    meta::position::Position,
+    monitor::{table_metrics::TableSinkCounts, task_monitor::TaskMonitor},
+};
+
+#[derive(Debug, Serialize)]
+pub struct TaskStatus {
+    // position of the latest data received by the pipeline
+    pub current_position: Position,
+    // position from which the task resumes
+    pub checkpoint_position: Position,
+    // seconds behind the source, by the timestamp of current_position, null if unknown
+    pub lag_secs: Option<u64>,
+    // records failed to sink, counted only by sinkers with per table counters
+    pub error_count: u64,
+    pub task_restart_count: u64,
+    pub sinked_records: u64,
+    // keyed by schema.tb
+    pub tables: BTreeMap<String, TableSinkCounts>,
+}
+
+/// Serves GET /status with the TaskStatus as JSON and GET /healthz,
+/// stopped once the task finishes, so /healthz answers as long as the task is running.
+pub struct StatusServer {
+    pub addr: SocketAddr,
+    handle: Option<ServerHandle>,
+}
+
+impl StatusServer {
+    pub fn start(
+        config: &StatusServerConfig,
+        task_monitor: Arc<TaskMonitor>,
+    ) -> anyhow::Result<Self> {
+        let addr = format!("{}:{}", config.http_host, config.http_port);
+        let server = HttpServer::new(move || {
+            App::new()
+                .app_data(web::Data::new(task_monitor.clone()))
+                .service(web::resource("/status").route(web::get().to(status_handler)))
+                .service(web::resource("/healthz").route(web::get().to(healthz_handler)))
+        })
+        .workers(1)
+        // ctrl-c is handled by the task itself
+        .disable_signals()
+        .bind(&addr)
+        .with_context(|| format!("failed to bind status server on {}", addr))?;
+
+        let addr = server.addrs()[0];
+        let server = server.run();
+        let handle = server.handle();
+        tokio::spawn(server);
+        log_info!("status server listening on {}", addr);
+        Ok(Self {
+            addr,
+            handle: Some(handle),
+        })
+    }
+
+    /// Returns once the server is stopped and its port is released
+    pub async fn stop(mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle.stop(false).await;
+        }
+    }
+}
+
+impl Drop for StatusServer {
+    // not stopped by stop(), the port is released later
+    fn drop(&mut self) {
+        let Some(handle) = self.handle.take() else {
+            return;
+        };
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            runtime.spawn(async move { handle.stop(false).await });
+        }
+    }
+}
+
+async fn status_handler(task_monitor: web::Data<Arc<TaskMonitor>>) -> impl Responder {
+    let now_millis = Utc::now().timestamp_millis() as u64;
+    HttpResponse::Ok().json(task_monitor.status(now_millis))
+}
+
+async fn healthz_handler() -> impl Responder {
+    HttpResponse::Ok()
+        .content_type("application/json")
+        .body(r#"{"status":"ok","service":"ape-dts"}"#)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpStream,
+    };
+
+    use super::*;
+    use crate::{
+        config::config_enums::{TaskKind, TaskType},
+        meta::{col_value::ColValue, row_data::RowData, row_type::RowType},
+    };
+
+    async fn http_get(addr: SocketAddr, path: &str) -> (String, String) {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        let request =
+            format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n");
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        (head.lines().next().unwrap().to_string(), body.to_string())
+    }
+
+    #[tokio::test]
+    async fn test_serve_status() {
+        let task_monitor = Arc::new(TaskMonitor::new(Some(TaskType::new(TaskKind::Cdc, None))));
+        let row_data = RowData::new(
+            "db_1".into(),
+            "tb_1".into(),
+            0,
+            RowType::Insert,
+            None,
+            Some(HashMap::from([("id".to_string(), ColValue::Long(1))])),
+        );
+        task_monitor.add_table_rows(&[row_data.clone(), row_data.clone()], true);
+        task_monitor.add_table_rows(&[row_data], false);
+        let position = Position::PgCdc {
+            lsn: "0/16B3748".into(),
+            timestamp: "2024-02-29 01:25:09.554".into(),
+        };
+        task_monitor.set_positions(&position, &Position::None);
+
+        let config = StatusServerConfig {
+            http_host: "127.0.0.1".into(),
+            http_port: 0,
+        };
+        let server = StatusServer::start(&config, task_monitor).unwrap();
+
+        let (status_line, body) = http_get(server.addr, "/healthz").await;
+        assert_eq!(status_line, "HTTP/1.1 200 OK");
+        assert_eq!(body, r#"{"status":"ok","service":"ape-dts"}"#);
+
+        let (status_line, body) = http_get(server.addr, "/status").await;
+        assert_eq!(status_line, "HTTP/1.1 200 OK");
+        let status: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(status["current_position"]["type"], "PgCdc");
+        assert_eq!(status["current_position"]["lsn"], "0/16B3748");
+        assert_eq!(status["checkpoint_position"]["type"], "None");
+        assert!(status["lag_secs"].as_u64().unwrap() > 0);
+        assert_eq!(status["error_count"], 1);
+        assert_eq!(status["task_restart_count"], 0);
+        assert_eq!(status["tables"]["db_1.tb_1"]["sinked_records"], 2);
+        assert_eq!(status["tables"]["db_1.tb_1"]["failed_records"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_rebind_after_stop() {
+        let task_monitor = Arc::new(TaskMonitor::new(None));
+        let config = StatusServerConfig {
+            http_host: "127.0.0.1".into(),
+            http_port: 0,
+        };
+        let server = StatusServer::start(&config, task_monitor.clone()).unwrap();
+        let config = StatusServerConfig {
+            http_host: "127.0.0.1".into(),
+            http_port: server.addr.port() as u64,
+        };
+
+        // the port is in use
+        assert!(StatusServer::start(&config, task_monitor.clone()).is_err());
+
+        // a restarted task binds the same port once the previous server is stopped
+        server.stop().await;
+        let server = StatusServer::start(&config, task_monitor).unwrap();
+        let (status_line, _) = http_get(server.addr, "/healthz").await;
+        assert_eq!(status_line, "HTTP/1.1 200 OK");
+        server.stop().await;
+    }
+}
@@ -0,0 +1,184 @@
+use std::{
+    cmp,
+    collections::{BTreeMap, HashMap},
+};
+
+use dashmap::DashMap;
+use serde::Serialize;
+
+use crate::meta::row_data::RowData;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct TableSinkCounts {
+    pub sinked_records: u64,
+    pub failed_records: u64,
+    pub sinked_bytes: u64,
+}
+
+/// Accumulated sink counts of each table since the task started, keyed by schema.table,
+/// so the table of a degraded job can be told from the global counters.
+#[derive(Default)]
+pub struct TableMetrics {
+    counts: DashMap<String, TableSinkCounts>,
+}
+
+impl TableMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_rows(&self, rows: &[RowData], succeeded: bool) {
+        // HashMap<(schema, tb), (records, bytes)>
+        let mut batch_counts: HashMap<(&str, &str), (u64, u64)> = HashMap::new();
+        for row_data in rows {
+            let entry = batch_counts
+                .entry((row_data.schema.as_str(), row_data.tb.as_str()))
+                .or_default();
+            entry.0 += 1;
+            entry.1 += row_data.get_data_size();
+        }
+
+        for ((schema, tb), (records, bytes)) in batch_counts {
+            let mut counts = self.counts.entry(format!("{}.{}", schema, tb)).or_default();
+            if succeeded {
+                counts.sinked_records += records;
+                counts.sinked_bytes += bytes;
+            } else {
+                counts.failed_records += records;
+            }
+        }
+    }
+
+    pub fn get(&self, schema: &str, tb: &str) -> Option<TableSinkCounts> {
+        self.counts
+            .get(&format!("{}.{}", schema, tb))
+            .map(|entry| entry.value().clone())
+    }
+
+    pub fn snapshot(&self) -> BTreeMap<String, TableSinkCounts> {
+        self.counts
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.value().clone()))
+            .collect()
+    }
+
+    /// Splits the counts into the `limit` tables with the most records and the rest summed up,
+    /// returns: (top tables, (count of the other tables, their summed counts))
+    pub fn top_tables(
+        table_counts: &BTreeMap<String, TableSinkCounts>,
+        limit: usize,
+    ) -> (
+        Vec<(&String, &TableSinkCounts)>,
+        Option<(usize, TableSinkCounts)>,
+    ) {
+        let mut tables: Vec<(&String, &TableSinkCounts)> = table_counts.iter().collect();
+        // stable, so tables with the same records stay in name order
+        tables
+            .sort_by_key(|(_, counts)| cmp::Reverse(counts.sinked_records + counts.failed_records));
+        if tables.len() <= limit {
+            return (tables, None);
+        }
+
+        let others = tables.split_off(limit);
+        let mut other_counts = TableSinkCounts::default();
+        for (_, counts) in others.iter() {
+            other_counts.sinked_records += counts.sinked_records;
+            other_counts.failed_records += counts.failed_records;
+            other_counts.sinked_bytes += counts.sinked_bytes;
+        }
+        (tables, Some((others.len(), other_counts)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::meta::{col_value::ColValue, row_type::RowType};
+
+    fn build_row(schema: &str, tb: &str, id: i32) -> RowData {
+        let after = HashMap::from([("id".to_string(), ColValue::Long(id))]);
+        RowData::new(
+            schema.into(),
+            tb.into(),
+            0,
+            RowType::Insert,
+            None,
+            Some(after),
+        )
+    }
+
+    #[test]
+    fn test_tables_accumulate_independent_counts() {
+        let table_metrics = TableMetrics::new();
+        let batch_1 = vec![
+            build_row("db_1", "tb_1", 1),
+            build_row("db_1", "tb_2", 2),
+            build_row("db_1", "tb_1", 3),
+        ];
+        let batch_2 = vec![build_row("db_1", "tb_2", 4), build_row("db_1", "tb_2", 5)];
+        let row_size = batch_1[0].get_data_size();
+
+        table_metrics.add_rows(&batch_1, true);
+        table_metrics.add_rows(&batch_2, false);
+        table_metrics.add_rows(&batch_2[..1], true);
+
+        assert_eq!(
+            table_metrics.get("db_1", "tb_1"),
+            Some(TableSinkCounts {
+                sinked_records: 2,
+                failed_records: 0,
+                sinked_bytes: 2 * row_size,
+            })
+        );
+        assert_eq!(
+            table_metrics.get("db_1", "tb_2"),
+            Some(TableSinkCounts {
+                sinked_records: 2,
+                failed_records: 2,
+                sinked_bytes: 2 * row_size,
+            })
+        );
+        assert_eq!(table_metrics.get("db_1", "tb_3"), None);
+        assert_eq!(
+            table_metrics.snapshot().keys().collect::<Vec<_>>(),
+            vec!["db_1.tb_1", "db_1.tb_2"]
+        );
+    }
+
+    #[test]
+    fn test_top_tables() {
+        let table_counts: BTreeMap<String, TableSinkCounts> =
+            [("tb_1", 1), ("tb_2", 3), ("tb_3", 2)]
+                .into_iter()
+                .map(|(tb, records)| {
+                    (
+                        format!("db_1.{}", tb),
+                        TableSinkCounts {
+                            sinked_records: records,
+                            failed_records: 0,
+                            sinked_bytes: records * 10,
+                        },
+                    )
+                })
+                .collect();
+
+        let (tables, others) = TableMetrics::top_tables(&table_counts, 2);
+        let tables: Vec<&str> = tables.iter().map(|(table, _)| table.as_str()).collect();
+        assert_eq!(tables, vec!["db_1.tb_2", "db_1.tb_3"]);
+        assert_eq!(
+            others,
+            Some((
+                1,
+                TableSinkCounts {
+                    sinked_records: 1,
+                    failed_records: 0,
+                    sinked_bytes: 10,
+                }
+            ))
+        );
+
+        let (tables, others) = TableMetrics::top_tables(&table_counts, 3);
+        assert_eq!(tables.len(), 3);
+        assert_eq!(others, None);
+    }
+}
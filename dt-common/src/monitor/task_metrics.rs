@@ -63,6 +63,9 @@ pub enum TaskMetricsType {
     SinkerRtMax,
     SinkerRtMin,
     SinkerRtAvg,
+    SinkerRtP50,
+    SinkerRtP95,
+    SinkerRtP99,
 
     SinkerRpsMax,
     SinkerRpsMin,
@@ -75,4 +78,6 @@ pub enum TaskMetricsType {
     SinkerSinkedBytes,
 
     SinkerDdlCount,
+
+    TaskRestartCount,
 }
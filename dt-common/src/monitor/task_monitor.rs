@@ -1,17 +1,29 @@
-use std::{cmp, collections::BTreeMap, sync::Arc};
+use std::{
+    cmp,
+    collections::BTreeMap,
+    sync::{Arc, RwLock},
+};
 
-use super::{group_monitor::GroupMonitor, monitor::Monitor};
+use super::{
+    group_monitor::GroupMonitor, monitor::Monitor, status_server::TaskStatus,
+    table_metrics::TableMetrics,
+};
 #[cfg(feature = "metrics")]
 use crate::monitor::prometheus_metrics::PrometheusMetrics;
 use crate::{
     config::config_enums::{TaskKind, TaskType},
-    log_task,
+    log_monitor, log_task,
+    meta::{position::Position, row_data::RowData},
     monitor::{counter_type::CounterType, task_metrics::TaskMetricsType, FlushableMonitor},
     utils::limit_queue::LimitedQueue,
 };
 use async_trait::async_trait;
+use chrono::Utc;
 use dashmap::DashMap;
 
+// the tables with the most records logged by each flush
+const MAX_LOGGED_TABLES: usize = 100;
+
 #[derive(Clone)]
 pub struct TaskMonitor {
     task_type: Option<TaskType>,
@@ -26,6 +38,11 @@ pub struct TaskMonitor {
     checkers: DashMap<String, Arc<Monitor>>,
 
     no_window_metrics_map: DashMap<TaskMetricsType, u64>,
+    // sink counts of each target table, kept along with the global sinker counters
+    table_metrics: Arc<TableMetrics>,
+    // the latest positions reported by the pipeline, served by the status server
+    current_position: Arc<RwLock<Position>>,
+    checkpoint_position: Arc<RwLock<Position>>,
     #[cfg(feature = "metrics")]
     pub prometheus_metrics: Arc<PrometheusMetrics>,
 }
@@ -68,6 +85,7 @@ impl FlushableMonitor for TaskMonitor {
             #[cfg(feature = "metrics")]
             self.prometheus_metrics.set_metrics(&metrics);
         }
+        self.flush_table_metrics();
     }
 }
 
@@ -85,6 +103,9 @@ impl TaskMonitor {
             sinkers: DashMap::new(),
             checkers: DashMap::new(),
             no_window_metrics_map: DashMap::new(),
+            table_metrics: Arc::new(TableMetrics::new()),
+            current_position: Arc::new(RwLock::new(Position::None)),
+            checkpoint_position: Arc::new(RwLock::new(Position::None)),
         }
     }
 
@@ -101,6 +122,9 @@ impl TaskMonitor {
             sinkers: DashMap::new(),
             checkers: DashMap::new(),
             no_window_metrics_map: DashMap::new(),
+            table_metrics: Arc::new(TableMetrics::new()),
+            current_position: Arc::new(RwLock::new(Position::None)),
+            checkpoint_position: Arc::new(RwLock::new(Position::None)),
             prometheus_metrics,
         }
     }
@@ -302,6 +326,43 @@ impl TaskMonitor {
             .or_insert(value);
     }
 
+    pub fn add_table_rows(&self, rows: &[RowData], succeeded: bool) {
+        if self.task_type.is_none() {
+            return;
+        }
+        self.table_metrics.add_rows(rows, succeeded);
+    }
+
+    pub fn table_metrics(&self) -> &TableMetrics {
+        &self.table_metrics
+    }
+
+    fn flush_table_metrics(&self) {
+        let table_counts = self.table_metrics.snapshot();
+        // tasks of many tables would flood the log, the rest of the tables are summed up
+        let (tables, others) = TableMetrics::top_tables(&table_counts, MAX_LOGGED_TABLES);
+        for (table, counts) in tables {
+            log_monitor!(
+                "sinker | {} | table_sink | sinked_records={} | failed_records={} | sinked_bytes={}",
+                table,
+                counts.sinked_records,
+                counts.failed_records,
+                counts.sinked_bytes
+            );
+        }
+        if let Some((table_count, counts)) = others {
+            log_monitor!(
+                "sinker | {} other tables | table_sink | sinked_records={} | failed_records={} | sinked_bytes={}",
+                table_count,
+                counts.sinked_records,
+                counts.failed_records,
+                counts.sinked_bytes
+            );
+        }
+        #[cfg(feature = "metrics")]
+        self.prometheus_metrics.set_table_metrics(&table_counts);
+    }
+
     pub fn get_no_window_metric(&self, metrics_type: TaskMetricsType) -> u64 {
         self.no_window_metrics_map
             .get(&metrics_type)
@@ -309,6 +370,39 @@ impl TaskMonitor {
             .unwrap_or_default()
     }
 
+    // None positions are ignored, chunks of snapshot tables may be sunk without positions
+    pub fn set_positions(&self, current: &Position, checkpoint: &Position) {
+        for (position, target) in [
+            (current, &self.current_position),
+            (checkpoint, &self.checkpoint_position),
+        ] {
+            if !matches!(position, Position::None) {
+                *target.write().unwrap() = position.clone();
+            }
+        }
+    }
+
+    // None if the position has no timestamp
+    pub fn lag_secs(timestamp_millis: u64, now_millis: u64) -> Option<u64> {
+        (timestamp_millis > 0).then(|| now_millis.saturating_sub(timestamp_millis) / 1000)
+    }
+
+    pub fn status(&self, now_millis: u64) -> TaskStatus {
+        let current_position = self.current_position.read().unwrap().clone();
+        let checkpoint_position = self.checkpoint_position.read().unwrap().clone();
+        let timestamp = current_position.to_timestamp();
+        let tables = self.table_metrics.snapshot();
+        TaskStatus {
+            current_position,
+            checkpoint_position,
+            lag_secs: Self::lag_secs(timestamp, now_millis),
+            error_count: tables.values().map(|counts| counts.failed_records).sum(),
+            task_restart_count: self.get_no_window_metric(TaskMetricsType::TaskRestartCount),
+            sinked_records: self.get_no_window_metric(TaskMetricsType::SinkerSinkedRecords),
+            tables,
+        }
+    }
+
     async fn calc(&self) -> Option<BTreeMap<TaskMetricsType, u64>> {
         self.task_type.as_ref()?;
 
@@ -489,6 +583,10 @@ impl TaskMonitor {
                     TaskMetricsType::SinkerRtAvg,
                     statics.avg_by_sec,
                 );
+                // the slowest sinker decides the quantiles of the task
+                calc_handler(CalcType::Max, TaskMetricsType::SinkerRtP50, statics.p50);
+                calc_handler(CalcType::Max, TaskMetricsType::SinkerRtP95, statics.p95);
+                calc_handler(CalcType::Max, TaskMetricsType::SinkerRtP99, statics.p99);
             }
             // sinker rps
             let counter = monitor
@@ -637,6 +735,12 @@ impl TaskMonitor {
             #[cfg(feature = "metrics")]
             self.prometheus_metrics.set_metrics(&metrics);
         }
+        if let Some(lag_secs) = metrics
+            .get(&TaskMetricsType::Timestamp)
+            .and_then(|timestamp| Self::lag_secs(*timestamp, Utc::now().timestamp_millis() as u64))
+        {
+            metrics.insert(TaskMetricsType::Delay, lag_secs);
+        }
         if total_progress_count > 0 {
             metrics.insert(
                 TaskMetricsType::Progress,
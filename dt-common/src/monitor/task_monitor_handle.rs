@@ -2,7 +2,10 @@ use std::sync::Arc;
 
 use crate::{
     config::config_enums::{TaskKind, TaskType},
-    meta::{ddl_meta::ddl_data::DdlData, row_data::RowData, struct_meta::struct_data::StructData},
+    meta::{
+        ddl_meta::ddl_data::DdlData, position::Position, row_data::RowData,
+        struct_meta::struct_data::StructData,
+    },
     monitor::{
         counter_type::CounterType,
         monitor::Monitor,
@@ -181,6 +184,12 @@ impl TaskMonitorHandle {
         self
     }
 
+    pub fn add_table_rows(&self, rows: &[RowData], succeeded: bool) {
+        if let Some(task_monitor) = &self.task_monitor {
+            task_monitor.add_table_rows(rows, succeeded);
+        }
+    }
+
     pub fn add_no_window_metrics(&self, metrics_type: TaskMetricsType, value: u64) {
         if let Some(task_monitor) = &self.task_monitor {
             task_monitor.add_no_window_metrics(metrics_type, value);
@@ -221,6 +230,12 @@ impl TaskMonitorHandle {
         }
     }
 
+    pub fn set_positions(&self, current: &Position, checkpoint: &Position) {
+        if let Some(task_monitor) = &self.task_monitor {
+            task_monitor.set_positions(current, checkpoint);
+        }
+    }
+
     pub fn time_window_secs(&self) -> u64 {
         self.time_window_secs
     }
@@ -15,6 +15,9 @@ pub struct WindowCounterStatistics {
     pub min_by_sec: u64,
     pub avg_by_sec: u64,
     pub count: u64,
+    pub p50: u64,
+    pub p95: u64,
+    pub p99: u64,
 }
 
 pub struct TimeWindowCounter {
@@ -81,11 +84,22 @@ impl TimeWindowCounter {
 
     #[inline(always)]
     pub async fn statistics(&self) -> WindowCounterStatistics {
-        self.statistics_in_window(self.time_window_secs).await
+        self.statistics_in_window(self.time_window_secs, false)
+            .await
     }
 
+    // p50/p95/p99 sort the values in the window, only for counters which publish them
     #[inline(always)]
-    pub async fn statistics_in_window(&self, time_window_secs: u64) -> WindowCounterStatistics {
+    pub async fn statistics_with_percentiles(&self) -> WindowCounterStatistics {
+        self.statistics_in_window(self.time_window_secs, true).await
+    }
+
+    #[inline(always)]
+    pub async fn statistics_in_window(
+        &self,
+        time_window_secs: u64,
+        with_percentiles: bool,
+    ) -> WindowCounterStatistics {
         let counters = self.counters.read().await;
         if counters.is_empty() {
             return WindowCounterStatistics::default();
@@ -100,6 +114,7 @@ impl TimeWindowCounter {
         let mut sum_in_current_sec = 0;
         let mut current_elapsed_secs = None;
         let mut sec_sums = LimitedQueue::new(1000);
+        let mut values = LimitedQueue::new(if with_percentiles { counters.len() } else { 0 });
 
         for counter in counters.iter() {
             if counter.timestamp.elapsed().as_secs() >= time_window_secs {
@@ -110,6 +125,9 @@ impl TimeWindowCounter {
             statistics.count += counter.count;
             statistics.max = cmp::max(statistics.max, counter.value);
             statistics.min = cmp::min(statistics.min, counter.value);
+            if with_percentiles {
+                values.push(counter.value);
+            }
 
             let counter_elapsed_secs = counter.timestamp.elapsed().as_secs();
 
@@ -149,6 +167,12 @@ impl TimeWindowCounter {
             }
         }
 
+        if let Some(percentiles) = values.percentiles(&[50.0, 95.0, 99.0]) {
+            statistics.p50 = percentiles[0];
+            statistics.p95 = percentiles[1];
+            statistics.p99 = percentiles[2];
+        }
+
         if statistics.min == u64::MAX {
             statistics.min = 0;
         }
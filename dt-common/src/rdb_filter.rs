@@ -9,7 +9,7 @@ use crate::meta::dcl_meta::dcl_type::DclType;
 use crate::{
     config::{
         config_enums::DbType,
-        config_token_parser::{ConfigTokenParser, TokenEscapePair},
+        config_token_parser::{ConfigTokenParser, TokenEscapePair, REGEX_ESCAPE_PAIR},
         filter_config::FilterConfig,
     },
     meta::{
@@ -24,8 +24,6 @@ type WhereConditions = HashMap<(String, String), String>;
 
 const JSON_PREFIX: &str = "json:";
 
-const REGEX_ESCAPE_PAIR: (&str, &str) = ("r#", "#");
-
 #[derive(Debug, Clone)]
 pub struct RdbFilter {
     pub db_type: DbType,
@@ -191,7 +189,7 @@ impl RdbFilter {
         false
     }
 
-    fn match_token(pattern: &str, item: &str, escape_pairs: &[(char, char)]) -> bool {
+    pub(crate) fn match_token(pattern: &str, item: &str, escape_pairs: &[(char, char)]) -> bool {
         // if pattern is enclosed by escapes, it is considered as exactly match
         // example: mysql table name : `aaa*`, it can only match the table `aaa*`, it won't match `aaa_bbb`
         for escape_pair in escape_pairs.iter() {
@@ -278,7 +276,7 @@ impl RdbFilter {
         Ok(results)
     }
 
-    fn parse_where_conditions(config_str: &str) -> anyhow::Result<WhereConditions> {
+    pub(crate) fn parse_where_conditions(config_str: &str) -> anyhow::Result<WhereConditions> {
         let mut results = WhereConditions::new();
         if config_str.trim().is_empty() {
             return Ok(results);
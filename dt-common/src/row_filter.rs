@@ -0,0 +1,692 @@
+use std::{cmp::Ordering, collections::HashMap};
+
+use anyhow::bail;
+
+use crate::{
+    config::filter_config::FilterConfig,
+    error::Error,
+    meta::{col_value::ColValue, row_data::RowData, row_type::RowType},
+    rdb_filter::RdbFilter,
+};
+
+// evaluates [filter] where_conditions against cdc rows if where_conditions_in_cdc is set,
+// snapshot extractors append the same conditions to their SELECT sqls instead.
+// only a subset of sql is supported in cdc:
+//   col = / != / <> / < / <= / > / >= value
+//   col IS [NOT] NULL, col [NOT] IN (value, ...)
+//   AND / OR / NOT and parentheses
+#[derive(Debug, Clone, Default)]
+pub struct RowFilter {
+    conditions: HashMap<(String, String), String>,
+    predicates: HashMap<(String, String), RowPredicate>,
+}
+
+impl RowFilter {
+    /// The syntax of the conditions is checked here so an invalid one fails the task at startup,
+    /// the cols are checked against the table meta once the first row of the table arrives.
+    pub fn from_config(config: &FilterConfig) -> anyhow::Result<Self> {
+        let conditions = if config.where_conditions_in_cdc {
+            RdbFilter::parse_where_conditions(&config.where_conditions)?
+        } else {
+            HashMap::new()
+        };
+        for ((schema, tb), condition) in conditions.iter() {
+            if let Err(e) = RowPredicate::check_syntax(condition) {
+                bail! {Error::ConfigError(format!(
+                    "invalid where_conditions of {}.{}: [{}], {}",
+                    schema, tb, condition, e
+                ))}
+            }
+        }
+        Ok(Self {
+            conditions,
+            predicates: HashMap::new(),
+        })
+    }
+
+    pub fn has_condition(&self, schema: &str, tb: &str) -> bool {
+        self.conditions
+            .contains_key(&(schema.to_string(), tb.to_string()))
+    }
+
+    // whether the condition of the table was parsed by a previous row
+    pub fn is_prepared(&self, schema: &str, tb: &str) -> bool {
+        self.predicates
+            .contains_key(&(schema.to_string(), tb.to_string()))
+    }
+
+    /// Returns true if the row does not match the where condition of its table.
+    /// Insert / delete are checked by the after / before image. An update of a row leaving
+    /// the condition becomes a delete of the before image, an update of a row entering it
+    /// becomes an insert of the after image, so the target keeps only the matching rows.
+    pub fn filter_row(
+        &mut self,
+        row_data: &mut RowData,
+        tb_cols: &[String],
+    ) -> anyhow::Result<bool> {
+        let key = (row_data.schema.clone(), row_data.tb.clone());
+        if !self.predicates.contains_key(&key) {
+            let Some(condition) = self.conditions.get(&key) else {
+                return Ok(false);
+            };
+            let predicate = RowPredicate::parse(condition, tb_cols).map_err(|e| {
+                Error::ConfigError(format!(
+                    "invalid where_conditions of {}.{}: [{}], {}",
+                    row_data.schema, row_data.tb, condition, e
+                ))
+            })?;
+            self.predicates.insert(key.clone(), predicate);
+        }
+
+        let predicate = &self.predicates[&key];
+        let matches = |col_values: &Option<HashMap<String, ColValue>>| {
+            col_values
+                .as_ref()
+                .is_some_and(|col_values| predicate.eval(col_values))
+        };
+        let keep = match row_data.row_type {
+            RowType::Insert => matches(&row_data.after),
+            RowType::Delete => matches(&row_data.before),
+            RowType::Update => match (matches(&row_data.before), matches(&row_data.after)) {
+                (true, true) => true,
+                (true, false) => {
+                    row_data.row_type = RowType::Delete;
+                    row_data.after = None;
+                    row_data.refresh_data_size();
+                    true
+                }
+                (false, true) => {
+                    row_data.row_type = RowType::Insert;
+                    row_data.before = None;
+                    row_data.refresh_data_size();
+                    true
+                }
+                (false, false) => false,
+            },
+        };
+        Ok(!keep)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CmpOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl CmpOp {
+    fn flip(self) -> Self {
+        match self {
+            Self::Lt => Self::Gt,
+            Self::Le => Self::Ge,
+            Self::Gt => Self::Lt,
+            Self::Ge => Self::Le,
+            other => other,
+        }
+    }
+
+    fn matches(self, ordering: Ordering) -> bool {
+        match self {
+            Self::Eq => ordering == Ordering::Equal,
+            Self::Ne => ordering != Ordering::Equal,
+            Self::Lt => ordering == Ordering::Less,
+            Self::Le => ordering != Ordering::Greater,
+            Self::Gt => ordering == Ordering::Greater,
+            Self::Ge => ordering != Ordering::Less,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident { name: String, quoted: bool },
+    Value(String),
+    Null,
+    Op(CmpOp),
+    LParen,
+    RParen,
+    Comma,
+    And,
+    Or,
+    Not,
+    Is,
+    In,
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Cmp {
+        col: String,
+        op: CmpOp,
+        value: String,
+    },
+    IsNull {
+        col: String,
+        negated: bool,
+    },
+    In {
+        col: String,
+        values: Vec<String>,
+        negated: bool,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct RowPredicate {
+    expr: Expr,
+}
+
+impl RowPredicate {
+    /// Parses the condition, cols referenced must exist in tb_cols. Unquoted cols are
+    /// matched case-insensitively if there is no exact match.
+    pub fn parse(condition: &str, tb_cols: &[String]) -> anyhow::Result<Self> {
+        Self::parse_with_cols(condition, Some(tb_cols))
+    }
+
+    /// Parses the condition without the table meta, cols referenced are not checked.
+    pub fn check_syntax(condition: &str) -> anyhow::Result<()> {
+        Self::parse_with_cols(condition, None).map(|_| ())
+    }
+
+    fn parse_with_cols(condition: &str, tb_cols: Option<&[String]>) -> anyhow::Result<Self> {
+        let tokens = Self::tokenize(condition)?;
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            tb_cols,
+        };
+        let expr = parser.parse_or()?;
+        if let Some(token) = parser.peek() {
+            bail!("unexpected token: {:?}", token)
+        }
+        Ok(Self { expr })
+    }
+
+    /// Rows whose condition evaluates to NULL (unknown) do not match, same as sql.
+    pub fn eval(&self, col_values: &HashMap<String, ColValue>) -> bool {
+        Self::eval_expr(&self.expr, col_values).unwrap_or(false)
+    }
+
+    fn eval_expr(expr: &Expr, col_values: &HashMap<String, ColValue>) -> Option<bool> {
+        match expr {
+            Expr::And(left, right) => {
+                match (
+                    Self::eval_expr(left, col_values),
+                    Self::eval_expr(right, col_values),
+                ) {
+                    (Some(false), _) | (_, Some(false)) => Some(false),
+                    (Some(true), Some(true)) => Some(true),
+                    _ => None,
+                }
+            }
+            Expr::Or(left, right) => {
+                match (
+                    Self::eval_expr(left, col_values),
+                    Self::eval_expr(right, col_values),
+                ) {
+                    (Some(true), _) | (_, Some(true)) => Some(true),
+                    (Some(false), Some(false)) => Some(false),
+                    _ => None,
+                }
+            }
+            Expr::Not(inner) => Self::eval_expr(inner, col_values).map(|v| !v),
+            Expr::Cmp { col, op, value } => {
+                Self::compare(col_values.get(col)?, value).map(|ordering| op.matches(ordering))
+            }
+            Expr::IsNull { col, negated } => match col_values.get(col) {
+                None | Some(ColValue::None) => Some(!negated),
+                Some(ColValue::UnchangedToast) => None,
+                Some(_) => Some(*negated),
+            },
+            Expr::In {
+                col,
+                values,
+                negated,
+            } => {
+                let col_value = col_values.get(col)?;
+                let mut found = Some(false);
+                for value in values {
+                    match Self::compare(col_value, value) {
+                        Some(Ordering::Equal) => {
+                            found = Some(true);
+                            break;
+                        }
+                        Some(_) => {}
+                        None => found = None,
+                    }
+                }
+                found.map(|v| v != *negated)
+            }
+        }
+    }
+
+    // values are compared as integers, then as floats if both sides are numeric,
+    // otherwise as strings
+    fn compare(col_value: &ColValue, value: &str) -> Option<Ordering> {
+        let text = match col_value {
+            ColValue::None | ColValue::UnchangedToast => return None,
+            ColValue::Bool(v) => (*v as u8).to_string(),
+            _ => col_value.to_utf8_or_hex_string()?,
+        };
+        if let (Ok(left), Ok(right)) = (text.parse::<i128>(), value.parse::<i128>()) {
+            return Some(left.cmp(&right));
+        }
+        if let (Ok(left), Ok(right)) = (text.parse::<f64>(), value.parse::<f64>()) {
+            return left.partial_cmp(&right);
+        }
+        Some(text.as_str().cmp(value))
+    }
+
+    fn tokenize(condition: &str) -> anyhow::Result<Vec<Token>> {
+        let chars: Vec<char> = condition.chars().collect();
+        let mut tokens = Vec::new();
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            match c {
+                _ if c.is_whitespace() => i += 1,
+                '(' | ')' | ',' => {
+                    tokens.push(match c {
+                        '(' => Token::LParen,
+                        ')' => Token::RParen,
+                        _ => Token::Comma,
+                    });
+                    i += 1;
+                }
+                '=' | '!' | '<' | '>' => {
+                    let next = chars.get(i + 1).copied();
+                    let (op, len) = match (c, next) {
+                        ('=', _) => (CmpOp::Eq, 1),
+                        ('!', Some('=')) => (CmpOp::Ne, 2),
+                        ('<', Some('>')) => (CmpOp::Ne, 2),
+                        ('<', Some('=')) => (CmpOp::Le, 2),
+                        ('<', _) => (CmpOp::Lt, 1),
+                        ('>', Some('=')) => (CmpOp::Ge, 2),
+                        ('>', _) => (CmpOp::Gt, 1),
+                        _ => bail!("unsupported operator at: {}", i),
+                    };
+                    tokens.push(Token::Op(op));
+                    i += len;
+                }
+                '\'' | '`' | '"' => {
+                    // a doubled quote inside the quoted text escapes itself
+                    let mut text = String::new();
+                    let mut closed = false;
+                    i += 1;
+                    while i < chars.len() {
+                        if chars[i] == c {
+                            if chars.get(i + 1) == Some(&c) {
+                                text.push(c);
+                                i += 2;
+                                continue;
+                            }
+                            closed = true;
+                            i += 1;
+                            break;
+                        }
+                        text.push(chars[i]);
+                        i += 1;
+                    }
+                    if !closed {
+                        bail!("unclosed quote: {}", c)
+                    }
+                    tokens.push(if c == '\'' {
+                        Token::Value(text)
+                    } else {
+                        Token::Ident {
+                            name: text,
+                            quoted: true,
+                        }
+                    });
+                }
+                _ if c.is_ascii_digit() || c == '-' || c == '.' => {
+                    let start = i;
+                    i += 1;
+                    while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '.') {
+                        i += 1;
+                    }
+                    let text: String = chars[start..i].iter().collect();
+                    if text.parse::<f64>().is_err() {
+                        bail!("invalid number: {}", text)
+                    }
+                    tokens.push(Token::Value(text));
+                }
+                _ if c.is_alphanumeric() || c == '_' => {
+                    let start = i;
+                    while i < chars.len()
+                        && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '$')
+                    {
+                        i += 1;
+                    }
+                    let word: String = chars[start..i].iter().collect();
+                    tokens.push(match word.to_uppercase().as_str() {
+                        "AND" => Token::And,
+                        "OR" => Token::Or,
+                        "NOT" => Token::Not,
+                        "IS" => Token::Is,
+                        "IN" => Token::In,
+                        "NULL" => Token::Null,
+                        "TRUE" => Token::Value("1".into()),
+                        "FALSE" => Token::Value("0".into()),
+                        _ => Token::Ident {
+                            name: word,
+                            quoted: false,
+                        },
+                    });
+                }
+                _ => bail!("unsupported character: {}", c),
+            }
+        }
+        Ok(tokens)
+    }
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    pos: usize,
+    // None if the cols are not checked
+    tb_cols: Option<&'a [String]>,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next_token(&mut self) -> anyhow::Result<Token> {
+        let Some(token) = self.tokens.get(self.pos).cloned() else {
+            bail!("unexpected end of condition")
+        };
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, expected: Token) -> anyhow::Result<()> {
+        let token = self.next_token()?;
+        if token != expected {
+            bail!("expect {:?}, got: {:?}", expected, token)
+        }
+        Ok(())
+    }
+
+    fn parse_or(&mut self) -> anyhow::Result<Expr> {
+        let mut expr = self.parse_and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            expr = Expr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> anyhow::Result<Expr> {
+        let mut expr = self.parse_not()?;
+        while self.peek() == Some(&Token::And) {
+            self.pos += 1;
+            expr = Expr::And(Box::new(expr), Box::new(self.parse_not()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_not(&mut self) -> anyhow::Result<Expr> {
+        if self.peek() == Some(&Token::Not) {
+            self.pos += 1;
+            return Ok(Expr::Not(Box::new(self.parse_not()?)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> anyhow::Result<Expr> {
+        match self.next_token()? {
+            Token::LParen => {
+                let expr = self.parse_or()?;
+                self.expect(Token::RParen)?;
+                Ok(expr)
+            }
+
+            Token::Ident { name, quoted } => {
+                let col = self.resolve_col(&name, quoted)?;
+                match self.next_token()? {
+                    Token::Op(op) => Ok(Expr::Cmp {
+                        col,
+                        op,
+                        value: self.parse_value()?,
+                    }),
+                    Token::Is => {
+                        let negated = self.peek() == Some(&Token::Not);
+                        if negated {
+                            self.pos += 1;
+                        }
+                        self.expect(Token::Null)?;
+                        Ok(Expr::IsNull { col, negated })
+                    }
+                    Token::In => self.parse_in(col, false),
+                    Token::Not => {
+                        self.expect(Token::In)?;
+                        self.parse_in(col, true)
+                    }
+                    token => bail!("unexpected token after col {}: {:?}", col, token),
+                }
+            }
+
+            Token::Value(value) => {
+                let Token::Op(op) = self.next_token()? else {
+                    bail!("expect comparison operator after value: {}", value)
+                };
+                let Token::Ident { name, quoted } = self.next_token()? else {
+                    bail!("expect col after value: {}", value)
+                };
+                Ok(Expr::Cmp {
+                    col: self.resolve_col(&name, quoted)?,
+                    op: op.flip(),
+                    value,
+                })
+            }
+
+            token => bail!("unexpected token: {:?}", token),
+        }
+    }
+
+    fn parse_in(&mut self, col: String, negated: bool) -> anyhow::Result<Expr> {
+        self.expect(Token::LParen)?;
+        let mut values = vec![self.parse_value()?];
+        while self.peek() == Some(&Token::Comma) {
+            self.pos += 1;
+            values.push(self.parse_value()?);
+        }
+        self.expect(Token::RParen)?;
+        Ok(Expr::In {
+            col,
+            values,
+            negated,
+        })
+    }
+
+    fn parse_value(&mut self) -> anyhow::Result<String> {
+        match self.next_token()? {
+            Token::Value(value) => Ok(value),
+            Token::Null => bail!("comparing with NULL is always unknown, use IS [NOT] NULL"),
+            token => bail!("expect value, got: {:?}", token),
+        }
+    }
+
+    fn resolve_col(&self, name: &str, quoted: bool) -> anyhow::Result<String> {
+        let Some(tb_cols) = self.tb_cols else {
+            return Ok(name.to_string());
+        };
+        if let Some(col) = tb_cols.iter().find(|col| *col == name) {
+            return Ok(col.clone());
+        }
+        if !quoted {
+            if let Some(col) = tb_cols.iter().find(|col| col.eq_ignore_ascii_case(name)) {
+                return Ok(col.clone());
+            }
+        }
+        bail!("col {} not found in table", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tb_cols() -> Vec<String> {
+        vec!["id".into(), "status".into(), "score".into(), "Name".into()]
+    }
+
+    fn row(id: i32, status: &str, score: Option<f64>) -> HashMap<String, ColValue> {
+        HashMap::from([
+            ("id".to_string(), ColValue::Long(id)),
+            ("status".to_string(), ColValue::String(status.into())),
+            (
+                "score".to_string(),
+                score.map_or(ColValue::None, ColValue::Double),
+            ),
+            ("Name".to_string(), ColValue::String(format!("n_{}", id))),
+        ])
+    }
+
+    fn eval(condition: &str, col_values: &HashMap<String, ColValue>) -> bool {
+        RowPredicate::parse(condition, &tb_cols())
+            .unwrap()
+            .eval(col_values)
+    }
+
+    #[test]
+    fn test_eval_predicate() {
+        let active = row(5, "active", Some(9.5));
+        let deleted = row(20, "it's deleted", None);
+
+        assert!(eval("status = 'active'", &active));
+        assert!(!eval("status = 'active'", &deleted));
+        assert!(eval("status = 'it''s deleted'", &deleted));
+        assert!(eval("`id` > 1 and id <= 5", &active));
+        assert!(eval("10 < id", &deleted));
+        assert!(eval("id <> 5 OR score >= 9.5", &active));
+        assert!(eval("NOT (id in (1, 2, 3)) AND status != 'x'", &active));
+        assert!(eval("id not in ('1', '2')", &deleted));
+        assert!(eval("score IS NULL", &deleted));
+        assert!(eval("score is not null", &active));
+        assert!(eval("name = 'n_5'", &active));
+
+        // unknown does not match, in both directions
+        assert!(!eval("score > 1", &deleted));
+        assert!(!eval("NOT (score > 1)", &deleted));
+        assert!(!eval("score in (1, 2)", &deleted));
+    }
+
+    #[test]
+    fn test_parse_invalid_predicate() {
+        let cols = tb_cols();
+        for condition in [
+            "unknown_col = 1",
+            "\"name\" = 'n_1'",
+            "id = NULL",
+            "id > ",
+            "(id > 1",
+            "id > 1 id < 2",
+            "status = 'active",
+            "LENGTH(status) > 1",
+        ] {
+            assert!(
+                RowPredicate::parse(condition, &cols).is_err(),
+                "{}",
+                condition
+            );
+        }
+    }
+
+    #[test]
+    fn test_filter_cdc_rows() {
+        let mut config = FilterConfig {
+            where_conditions: r#"json:[{"db":"db_1","tb":"tb_1","condition":"status = 'active'"}]"#
+                .into(),
+            ..Default::default()
+        };
+        // cdc rows are filtered only if opted in
+        assert!(!RowFilter::from_config(&config)
+            .unwrap()
+            .has_condition("db_1", "tb_1"));
+
+        // invalid conditions fail at startup, unknown cols are found with the table meta
+        for (condition, valid) in [
+            ("status = 'active", false),
+            ("id > 1 id < 2", false),
+            ("unknown_col = 1", true),
+        ] {
+            let config = FilterConfig {
+                where_conditions: format!(
+                    r#"json:[{{"db":"db_1","tb":"tb_1","condition":"{}"}}]"#,
+                    condition
+                ),
+                where_conditions_in_cdc: true,
+                ..Default::default()
+            };
+            assert_eq!(
+                RowFilter::from_config(&config).is_ok(),
+                valid,
+                "{}",
+                condition
+            );
+        }
+
+        config.where_conditions_in_cdc = true;
+        let mut filter = RowFilter::from_config(&config).unwrap();
+        let cols = tb_cols();
+        let build_row = |tb: &str, row_type: RowType, before: Option<&str>, after: Option<&str>| {
+            RowData::new(
+                "db_1".into(),
+                tb.into(),
+                0,
+                row_type,
+                before.map(|status| row(1, status, None)),
+                after.map(|status| row(1, status, None)),
+            )
+        };
+
+        let cases = [
+            ("tb_1", RowType::Insert, None, Some("active"), false),
+            ("tb_1", RowType::Insert, None, Some("inactive"), true),
+            ("tb_1", RowType::Delete, Some("inactive"), None, true),
+            ("tb_1", RowType::Update, Some("inactive"), Some("x"), true),
+            // tables without condition are never filtered
+            ("tb_2", RowType::Insert, None, Some("inactive"), false),
+        ];
+        for (tb, row_type, before, after, filtered) in cases {
+            let mut row_data = build_row(tb, row_type, before, after);
+            assert_eq!(filter.filter_row(&mut row_data, &cols).unwrap(), filtered);
+        }
+        assert!(filter.is_prepared("db_1", "tb_1"));
+        assert!(!filter.is_prepared("db_1", "tb_2"));
+
+        // updates crossing the condition
+        let cases = [
+            (
+                Some("active"),
+                Some("active"),
+                build_row("tb_1", RowType::Update, Some("active"), Some("active")),
+            ),
+            (
+                Some("active"),
+                Some("inactive"),
+                build_row("tb_1", RowType::Delete, Some("active"), None),
+            ),
+            (
+                Some("inactive"),
+                Some("active"),
+                build_row("tb_1", RowType::Insert, None, Some("active")),
+            ),
+        ];
+        for (before, after, expected) in cases {
+            let mut row_data = build_row("tb_1", RowType::Update, before, after);
+            assert!(!filter.filter_row(&mut row_data, &cols).unwrap());
+            assert_eq!(row_data, expected);
+        }
+    }
+}
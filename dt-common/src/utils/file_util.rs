@@ -1,3 +1,5 @@
+use std::{fs, path::Path};
+
 use tokio::{
     fs::File,
     io::{AsyncReadExt, AsyncSeekExt, SeekFrom},
@@ -38,4 +40,27 @@ impl FileUtil {
         let lines: Vec<String> = buf.split('\n').map(|i| i.to_string()).collect();
         Ok(lines)
     }
+
+    /// The seq after the largest one of the files {dir}/{prefix}{seq}.{extension}, 0 if none,
+    /// so files written before a restart are not overwritten.
+    pub fn next_file_seq(dir: &Path, prefix: &str, extension: &str) -> anyhow::Result<usize> {
+        if !dir.exists() {
+            return Ok(0);
+        }
+
+        let suffix = format!(".{}", extension);
+        let mut next_seq = 0;
+        for entry in fs::read_dir(dir)? {
+            let file_name = entry?.file_name();
+            let seq = file_name
+                .to_str()
+                .and_then(|name| name.strip_prefix(prefix))
+                .and_then(|name| name.strip_suffix(suffix.as_str()))
+                .and_then(|seq| seq.parse::<usize>().ok());
+            if let Some(seq) = seq {
+                next_seq = next_seq.max(seq + 1);
+            }
+        }
+        Ok(next_seq)
+    }
 }
@@ -0,0 +1,251 @@
+use std::io::Cursor;
+
+use anyhow::bail;
+use byteorder::{BigEndian, LittleEndian, ReadBytesExt};
+
+// geometry types in WKB
+const POINT: u32 = 1;
+const LINESTRING: u32 = 2;
+const POLYGON: u32 = 3;
+const MULTIPOINT: u32 = 4;
+const MULTILINESTRING: u32 = 5;
+const MULTIPOLYGON: u32 = 6;
+const GEOMETRYCOLLECTION: u32 = 7;
+
+// mysql stores geometry values as a 4 bytes little-endian SRID followed by the WKB,
+// refer: https://dev.mysql.com/doc/refman/8.0/en/gis-data-formats.html#gis-internal-format
+const SRID_LEN: usize = 4;
+
+pub struct GeometryUtil {}
+
+impl GeometryUtil {
+    /// The WKB of a mysql geometry value, without the SRID.
+    pub fn to_wkb(value: &[u8]) -> anyhow::Result<&[u8]> {
+        if value.len() <= SRID_LEN {
+            bail!("invalid geometry value, length: {}", value.len())
+        }
+        Ok(&value[SRID_LEN..])
+    }
+
+    /// The WKT of a mysql geometry value, in the format of mysql ST_AsText, e.g. POINT(1 2),
+    /// the SRID is not included.
+    pub fn to_wkt(value: &[u8]) -> anyhow::Result<String> {
+        Self::wkb_to_wkt(Self::to_wkb(value)?)
+    }
+
+    pub fn wkb_to_wkt(wkb: &[u8]) -> anyhow::Result<String> {
+        let mut cursor = Cursor::new(wkb);
+        let wkt = Self::read_geometry(&mut cursor)?;
+        if (cursor.position() as usize) < wkb.len() {
+            bail!(
+                "invalid wkb, {} trailing bytes",
+                wkb.len() - cursor.position() as usize
+            )
+        }
+        Ok(wkt)
+    }
+
+    fn read_geometry(cursor: &mut Cursor<&[u8]>) -> anyhow::Result<String> {
+        let (little_endian, geometry_type) = Self::read_header(cursor)?;
+        let name = match geometry_type {
+            POINT => "POINT",
+            LINESTRING => "LINESTRING",
+            POLYGON => "POLYGON",
+            MULTIPOINT => "MULTIPOINT",
+            MULTILINESTRING => "MULTILINESTRING",
+            MULTIPOLYGON => "MULTIPOLYGON",
+            GEOMETRYCOLLECTION => "GEOMETRYCOLLECTION",
+            _ => bail!("unsupported wkb geometry type: {}", geometry_type),
+        };
+        let body = Self::read_body(cursor, little_endian, geometry_type)?;
+        Ok(format!("{}{}", name, body))
+    }
+
+    // the wkt of a geometry without its type name, e.g. (1 2) for POINT(1 2)
+    fn read_body(
+        cursor: &mut Cursor<&[u8]>,
+        little_endian: bool,
+        geometry_type: u32,
+    ) -> anyhow::Result<String> {
+        let items = match geometry_type {
+            POINT => return Ok(format!("({})", Self::read_point(cursor, little_endian)?)),
+
+            LINESTRING => {
+                let count = Self::read_u32(cursor, little_endian)?;
+                let mut points = Vec::new();
+                for _ in 0..count {
+                    points.push(Self::read_point(cursor, little_endian)?);
+                }
+                points
+            }
+
+            POLYGON => {
+                let count = Self::read_u32(cursor, little_endian)?;
+                let mut rings = Vec::new();
+                for _ in 0..count {
+                    // a ring has the same layout as a linestring
+                    rings.push(Self::read_body(cursor, little_endian, LINESTRING)?);
+                }
+                rings
+            }
+
+            MULTIPOINT | MULTILINESTRING | MULTIPOLYGON => {
+                let item_type = geometry_type - 3;
+                let count = Self::read_u32(cursor, little_endian)?;
+                let mut items = Vec::new();
+                for _ in 0..count {
+                    let (item_little_endian, actual_type) = Self::read_header(cursor)?;
+                    if actual_type != item_type {
+                        bail!(
+                            "invalid wkb, expect geometry type {} in {}, got {}",
+                            item_type,
+                            geometry_type,
+                            actual_type
+                        )
+                    }
+                    items.push(Self::read_body(cursor, item_little_endian, item_type)?);
+                }
+                items
+            }
+
+            GEOMETRYCOLLECTION => {
+                let count = Self::read_u32(cursor, little_endian)?;
+                let mut items = Vec::new();
+                for _ in 0..count {
+                    items.push(Self::read_geometry(cursor)?);
+                }
+                items
+            }
+
+            _ => bail!("unsupported wkb geometry type: {}", geometry_type),
+        };
+
+        if items.is_empty() {
+            return Ok(" EMPTY".to_string());
+        }
+        Ok(format!("({})", items.join(",")))
+    }
+
+    fn read_header(cursor: &mut Cursor<&[u8]>) -> anyhow::Result<(bool, u32)> {
+        let little_endian = match cursor.read_u8()? {
+            0 => false,
+            1 => true,
+            v => bail!("invalid wkb byte order: {}", v),
+        };
+        let geometry_type = Self::read_u32(cursor, little_endian)?;
+        Ok((little_endian, geometry_type))
+    }
+
+    fn read_point(cursor: &mut Cursor<&[u8]>, little_endian: bool) -> anyhow::Result<String> {
+        let x = Self::read_f64(cursor, little_endian)?;
+        let y = Self::read_f64(cursor, little_endian)?;
+        Ok(format!("{} {}", x, y))
+    }
+
+    fn read_u32(cursor: &mut Cursor<&[u8]>, little_endian: bool) -> anyhow::Result<u32> {
+        if little_endian {
+            Ok(cursor.read_u32::<LittleEndian>()?)
+        } else {
+            Ok(cursor.read_u32::<BigEndian>()?)
+        }
+    }
+
+    fn read_f64(cursor: &mut Cursor<&[u8]>, little_endian: bool) -> anyhow::Result<f64> {
+        if little_endian {
+            Ok(cursor.read_f64::<LittleEndian>()?)
+        } else {
+            Ok(cursor.read_f64::<BigEndian>()?)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // SRID 0 + little-endian wkb of POINT(1 2)
+    const POINT_1_2: [u8; 25] = [
+        0, 0, 0, 0, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 240, 63, 0, 0, 0, 0, 0, 0, 0, 64,
+    ];
+
+    fn le_wkb(geometry_type: u32, body: &[u8]) -> Vec<u8> {
+        let mut wkb = vec![1];
+        wkb.extend_from_slice(&geometry_type.to_le_bytes());
+        wkb.extend_from_slice(body);
+        wkb
+    }
+
+    fn le_points(points: &[(f64, f64)]) -> Vec<u8> {
+        let mut buf = (points.len() as u32).to_le_bytes().to_vec();
+        for (x, y) in points {
+            buf.extend_from_slice(&x.to_le_bytes());
+            buf.extend_from_slice(&y.to_le_bytes());
+        }
+        buf
+    }
+
+    #[test]
+    fn test_point_to_wkt() {
+        assert_eq!(GeometryUtil::to_wkt(&POINT_1_2).unwrap(), "POINT(1 2)");
+        assert_eq!(GeometryUtil::to_wkb(&POINT_1_2).unwrap(), &POINT_1_2[4..]);
+
+        // big-endian wkb
+        let mut wkb = vec![0];
+        wkb.extend_from_slice(&POINT.to_be_bytes());
+        wkb.extend_from_slice(&1.5f64.to_be_bytes());
+        wkb.extend_from_slice(&(-2.25f64).to_be_bytes());
+        assert_eq!(GeometryUtil::wkb_to_wkt(&wkb).unwrap(), "POINT(1.5 -2.25)");
+    }
+
+    #[test]
+    fn test_to_wkt() {
+        let ring = [(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 0.0)];
+        // a single ring
+        let mut polygon = 1u32.to_le_bytes().to_vec();
+        polygon.extend(le_points(&ring));
+        let polygon = le_wkb(POLYGON, &polygon);
+        assert_eq!(
+            GeometryUtil::wkb_to_wkt(&polygon).unwrap(),
+            "POLYGON((0 0,10 0,10 10,0 0))"
+        );
+
+        let linestring = le_wkb(LINESTRING, &le_points(&[(0.0, 0.0), (1.0, 1.0)]));
+        assert_eq!(
+            GeometryUtil::wkb_to_wkt(&linestring).unwrap(),
+            "LINESTRING(0 0,1 1)"
+        );
+
+        let mut multipoint = 2u32.to_le_bytes().to_vec();
+        multipoint.extend(GeometryUtil::to_wkb(&POINT_1_2).unwrap());
+        multipoint.extend(le_wkb(POINT, &le_points(&[(3.0, 4.0)])[4..]));
+        assert_eq!(
+            GeometryUtil::wkb_to_wkt(&le_wkb(MULTIPOINT, &multipoint)).unwrap(),
+            "MULTIPOINT((1 2),(3 4))"
+        );
+
+        let mut collection = 2u32.to_le_bytes().to_vec();
+        collection.extend(GeometryUtil::to_wkb(&POINT_1_2).unwrap());
+        collection.extend(&linestring);
+        assert_eq!(
+            GeometryUtil::wkb_to_wkt(&le_wkb(GEOMETRYCOLLECTION, &collection)).unwrap(),
+            "GEOMETRYCOLLECTION(POINT(1 2),LINESTRING(0 0,1 1))"
+        );
+        assert_eq!(
+            GeometryUtil::wkb_to_wkt(&le_wkb(GEOMETRYCOLLECTION, &0u32.to_le_bytes())).unwrap(),
+            "GEOMETRYCOLLECTION EMPTY"
+        );
+    }
+
+    #[test]
+    fn test_invalid_wkb() {
+        assert!(GeometryUtil::to_wkt(&[0, 0, 0, 0]).is_err());
+        // truncated
+        assert!(GeometryUtil::to_wkt(&POINT_1_2[..20]).is_err());
+        // trailing bytes
+        let mut value = POINT_1_2.to_vec();
+        value.push(0);
+        assert!(GeometryUtil::to_wkt(&value).is_err());
+        // unknown type
+        assert!(GeometryUtil::wkb_to_wkt(&le_wkb(8, &[])).is_err());
+    }
+}
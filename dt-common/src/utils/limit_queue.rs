@@ -50,3 +50,100 @@ impl<T> LimitedQueue<T> {
         self.data.clear();
     }
 }
+
+/// Values a [LimitedQueue] computes statistics over.
+pub trait QueueValue {
+    fn value(&self) -> u64;
+}
+
+impl QueueValue for u64 {
+    fn value(&self) -> u64 {
+        *self
+    }
+}
+
+// (value, count) entries as pushed by monitors, e.g. (rt, records) of a write
+impl QueueValue for (u64, u64) {
+    fn value(&self) -> u64 {
+        self.0
+    }
+}
+
+impl<T: QueueValue> LimitedQueue<T> {
+    /// Nearest-rank percentile of the values, p ranges from 0 to 100.
+    pub fn percentile(&self, p: f64) -> Option<u64> {
+        self.percentiles(&[p]).map(|values| values[0])
+    }
+
+    /// Nearest-rank percentiles of the values for each of ps, the values are sorted once.
+    pub fn percentiles(&self, ps: &[f64]) -> Option<Vec<u64>> {
+        if self.data.is_empty() {
+            return None;
+        }
+        let mut values: Vec<u64> = self.data.iter().map(|i| i.value()).collect();
+        values.sort_unstable();
+        let percentiles = ps
+            .iter()
+            .map(|p| {
+                let rank = (p.clamp(0.0, 100.0) * values.len() as f64 / 100.0).ceil() as usize;
+                values[rank.clamp(1, values.len()) - 1]
+            })
+            .collect();
+        Some(percentiles)
+    }
+
+    pub fn avg(&self) -> Option<f64> {
+        if self.data.is_empty() {
+            return None;
+        }
+        let sum: u64 = self.data.iter().map(|i| i.value()).sum();
+        Some(sum as f64 / self.data.len() as f64)
+    }
+
+    pub fn max(&self) -> Option<u64> {
+        self.data.iter().map(|i| i.value()).max()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentile() {
+        let mut rts = LimitedQueue::new(20);
+        // pushed out of order, the oldest 10 values are evicted
+        for rt in (1..=30u64).rev() {
+            rts.push(rt);
+        }
+        assert_eq!(rts.len(), 20);
+        assert_eq!(rts.percentile(50.0), Some(10));
+        assert_eq!(rts.percentile(95.0), Some(19));
+        assert_eq!(rts.percentile(99.0), Some(20));
+        assert_eq!(rts.percentile(0.0), Some(1));
+        assert_eq!(rts.percentile(100.0), Some(20));
+        assert_eq!(rts.percentiles(&[50.0, 95.0, 99.0]), Some(vec![10, 19, 20]));
+        assert_eq!(rts.avg(), Some(10.5));
+        assert_eq!(rts.max(), Some(20));
+
+        // counts of (value, count) entries are ignored
+        let mut rts = LimitedQueue::new(4);
+        for entry in [(30, 1), (10, 100), (20, 5), (40, 2)] {
+            rts.push(entry);
+        }
+        assert_eq!(rts.percentile(50.0), Some(20));
+        assert_eq!(rts.percentile(99.0), Some(40));
+        assert_eq!(rts.avg(), Some(25.0));
+        assert_eq!(rts.max(), Some(40));
+    }
+
+    #[test]
+    fn test_percentile_of_empty_queue() {
+        let rts: LimitedQueue<u64> = LimitedQueue::new(10);
+        assert_eq!(rts.percentile(50.0), None);
+        assert_eq!(rts.percentile(99.0), None);
+        assert_eq!(rts.percentiles(&[50.0, 99.0]), None);
+        assert_eq!(rts.avg(), None);
+        assert_eq!(rts.max(), None);
+    }
+}
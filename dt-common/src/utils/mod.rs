@@ -1,4 +1,5 @@
 pub mod file_util;
+pub mod geometry_util;
 pub mod limit_queue;
 pub mod redis_util;
 pub mod serialize_util;
@@ -1,7 +1,8 @@
+use base64::{engine::general_purpose, Engine as _};
 use regex::Regex;
 use sqlx::{mysql::MySqlRow, ColumnIndex, Row};
 
-use crate::config::config_enums::DbType;
+use crate::config::config_enums::{BinaryEncoding, DbType};
 
 pub struct SqlUtil {}
 
@@ -97,6 +98,16 @@ impl SqlUtil {
         }
     }
 
+    /// Unlike binary_to_str, Hex and Base64 encode every value,
+    /// so non utf8 bytes survive the round trip and consumers always know how to decode.
+    pub fn binary_to_encoded_str(v: &[u8], encoding: &BinaryEncoding) -> String {
+        match encoding {
+            BinaryEncoding::Utf8 => Self::binary_to_str(v).0,
+            BinaryEncoding::Hex => hex::encode(v),
+            BinaryEncoding::Base64 => general_purpose::STANDARD.encode(v),
+        }
+    }
+
     pub fn try_get_mysql_string<I>(row: &MySqlRow, index: I) -> anyhow::Result<String>
     where
         I: ColumnIndex<MySqlRow> + Copy,
@@ -161,6 +172,35 @@ impl SqlUtil {
 mod tests {
 
     use super::*;
+
+    #[test]
+    fn test_binary_to_encoded_str_round_trip() {
+        // invalid utf8
+        let v = vec![0xff, 0xfe, 0x00, b'a', 0xc3];
+        assert!(String::from_utf8(v.clone()).is_err());
+
+        let hex_str = SqlUtil::binary_to_encoded_str(&v, &BinaryEncoding::Hex);
+        assert_eq!(hex_str, "fffe0061c3");
+        assert_eq!(hex::decode(&hex_str).unwrap(), v);
+
+        let base64_str = SqlUtil::binary_to_encoded_str(&v, &BinaryEncoding::Base64);
+        assert_eq!(base64_str, "//4AYcM=");
+        assert_eq!(general_purpose::STANDARD.decode(&base64_str).unwrap(), v);
+
+        // valid utf8 is encoded too
+        assert_eq!(
+            SqlUtil::binary_to_encoded_str(b"abc", &BinaryEncoding::Hex),
+            "616263"
+        );
+        assert_eq!(
+            SqlUtil::binary_to_encoded_str(b"abc", &BinaryEncoding::Utf8),
+            "abc"
+        );
+        assert_eq!(
+            SqlUtil::binary_to_encoded_str(&v, &BinaryEncoding::Utf8),
+            "fffe0061c3"
+        );
+    }
     #[test]
     #[ignore]
     fn test_check_valid_token_without_escapes() {
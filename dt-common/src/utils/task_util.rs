@@ -32,7 +32,7 @@ mod tests {
 
     use crate::{
         config::{
-            config_enums::{DbType, ExtractType, SinkType},
+            config_enums::{DbType, ExtractType, SinkType, UnsupportedColValuePolicy},
             connection_auth_config::ConnectionAuthConfig,
             extractor_config::BasicExtractorConfig,
             filter_config::FilterConfig,
@@ -61,6 +61,9 @@ mod tests {
             batch_size: 0,
             max_connections: 10,
             rate_limiter: RateLimiterConfig::default(),
+            unsupported_col_value_policy: UnsupportedColValuePolicy::default(),
+            include_cols: String::new(),
+            exclude_cols: String::new(),
         };
         let mut filter_config = FilterConfig {
             do_schemas: "db1,db2".to_string(),
@@ -80,6 +83,7 @@ mod tests {
             tb_map: "".to_string(),
             col_map: "".to_string(),
             topic_map: "".to_string(),
+            doc_projection: "".to_string(),
         };
         let mut generate_task_id = "".to_string();
         for _i in 0..10 {
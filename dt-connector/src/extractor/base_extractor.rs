@@ -182,7 +182,7 @@ impl BaseExtractor {
         position: Position,
     ) -> anyhow::Result<()> {
         let row_data = if let Some(router) = &self.router {
-            router.route_row(row_data)
+            router.route_row(router.project_row(row_data)?)
         } else {
             row_data
         };
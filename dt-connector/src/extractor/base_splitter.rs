@@ -356,4 +356,28 @@ mod tests {
         // 1, 2(empty), 3 collapse — final value carried is 30, not None.
         assert_eq!(extract_value(&p).as_deref(), Some("30"));
     }
+
+    #[test]
+    fn evenly_sized_chunks_cover_each_row_once() {
+        let mut s = BaseSplitter::new();
+        let chunks = s
+            .gen_next_evenly_sized_chunks((ColValue::Long(1), ColValue::Long(30)), 10, 30, &None)
+            .unwrap();
+        assert_eq!(chunks.len(), 3);
+        assert!(s.has_no_next_chunks());
+
+        // chunk range is left-open and right-closed, None means unbounded
+        let in_chunk = |id: i128, chunk: &SnapshotChunk| {
+            let (start, end) = &chunk.chunk_range;
+            let after_start = match start {
+                ColValue::None => true,
+                v => id > v.convert_into_integer_128().unwrap(),
+            };
+            after_start && id <= end.convert_into_integer_128().unwrap()
+        };
+        for id in 1..=30 {
+            let hits = chunks.iter().filter(|chunk| in_chunk(id, chunk)).count();
+            assert_eq!(hits, 1, "id {} hit {} chunks", id, hits);
+        }
+    }
 }
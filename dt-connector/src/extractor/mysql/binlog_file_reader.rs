@@ -0,0 +1,476 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{BufReader, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context};
+use dt_common::{error::Error, log_info};
+use mysql_binlog_connector_rust::{
+    binlog_parser::BinlogParser,
+    event::{event_data::EventData, event_header::EventHeader},
+};
+
+const BINLOG_MAGIC: [u8; 4] = [0xfe, b'b', b'i', b'n'];
+const EVENT_HEADER_LENGTH: usize = 19;
+const FORMAT_DESCRIPTION_EVENT: u8 = 15;
+const CHECKSUM_ALG_CRC32: u8 = 1;
+const CRC32_CHECKSUM_LENGTH: u8 = 4;
+
+/// Reads binlog events from local files instead of a live server,
+/// local_path can be a single binlog file or a directory of binlog files (mysql-bin.000001, mysql-bin.000002 ...),
+/// files in a directory are read in the order of their sequence numbers.
+pub struct BinlogFileReader {
+    files: Vec<PathBuf>,
+    file_index: usize,
+    current: Option<OpenedBinlogFile>,
+}
+
+struct OpenedBinlogFile {
+    filename: String,
+    reader: BufReader<File>,
+    len: u64,
+    parser: BinlogParser,
+}
+
+impl BinlogFileReader {
+    pub fn new(local_path: &str, start_filename: &str) -> anyhow::Result<Self> {
+        let files = Self::list_binlog_files(Path::new(local_path))?;
+        if files.is_empty() {
+            bail! {Error::ExtractorError(format!(
+                "no binlog files found in: {}",
+                local_path
+            ))}
+        }
+
+        let file_index = if start_filename.is_empty() {
+            0
+        } else {
+            match files
+                .iter()
+                .position(|f| Self::get_filename(f) == start_filename)
+            {
+                Some(i) => i,
+                None => bail! {Error::ExtractorError(format!(
+                    "binlog file: {} not found in: {}",
+                    start_filename, local_path
+                ))},
+            }
+        };
+
+        Ok(Self {
+            files,
+            file_index,
+            current: None,
+        })
+    }
+
+    /// The binlog filename reading starts from.
+    pub fn start_filename(&self) -> String {
+        Self::get_filename(&self.files[self.file_index])
+    }
+
+    /// Returns the next event and the binlog filename it belongs to, None if all files are read.
+    pub fn read(&mut self) -> anyhow::Result<Option<(String, EventHeader, EventData)>> {
+        loop {
+            if self.current.is_none() {
+                if self.file_index >= self.files.len() {
+                    return Ok(None);
+                }
+                self.current = Some(Self::open(&self.files[self.file_index])?);
+                self.file_index += 1;
+            }
+
+            let current = self.current.as_mut().unwrap();
+            if current.reader.stream_position()? >= current.len {
+                // rotate to the next file
+                self.current = None;
+                continue;
+            }
+
+            let (header, data) = current
+                .parser
+                .next(&mut current.reader)
+                .with_context(|| format!("failed to parse binlog file: {}", current.filename))?;
+            return Ok(Some((current.filename.clone(), header, data)));
+        }
+    }
+
+    fn open(path: &Path) -> anyhow::Result<OpenedBinlogFile> {
+        let file = File::open(path)
+            .with_context(|| format!("failed to open binlog file: {}", path.display()))?;
+        let len = file.metadata()?.len();
+        let mut reader = BufReader::new(file);
+
+        let mut magic = [0u8; 4];
+        if reader.read_exact(&mut magic).is_err() || magic != BINLOG_MAGIC {
+            bail! {Error::ExtractorError(format!(
+                "invalid binlog file: {}, magic number mismatch",
+                path.display()
+            ))}
+        }
+
+        let checksum_length = Self::read_checksum_length(&mut reader)
+            .with_context(|| format!("failed to parse binlog file: {}", path.display()))?;
+        log_info!(
+            "reading local binlog file: {}, checksum length: {}",
+            path.display(),
+            checksum_length
+        );
+        Ok(OpenedBinlogFile {
+            filename: Self::get_filename(path),
+            reader,
+            len,
+            parser: BinlogParser {
+                checksum_length,
+                table_map_event_by_table_id: HashMap::new(),
+            },
+        })
+    }
+
+    /// Gets the checksum length from the FormatDescriptionEvent which follows the magic number,
+    /// its checksum_alg is the byte before the 4 bytes checksum at the end of the event,
+    /// the reader is rewound to the first event afterwards.
+    fn read_checksum_length(reader: &mut BufReader<File>) -> anyhow::Result<u8> {
+        let start = reader.stream_position()?;
+        let mut header = [0u8; EVENT_HEADER_LENGTH];
+        let mut checksum_length = CRC32_CHECKSUM_LENGTH;
+        // files without a FormatDescriptionEvent keep the default: CRC32
+        if reader.read_exact(&mut header).is_ok() && header[4] == FORMAT_DESCRIPTION_EVENT {
+            let event_length = u32::from_le_bytes(header[9..13].try_into()?) as usize;
+            if event_length < EVENT_HEADER_LENGTH + 5 {
+                bail! {Error::ExtractorError(format!(
+                    "invalid FormatDescriptionEvent length: {}",
+                    event_length
+                ))}
+            }
+
+            let mut data = vec![0u8; event_length - EVENT_HEADER_LENGTH];
+            reader.read_exact(&mut data)?;
+            if data[data.len() - 5] != CHECKSUM_ALG_CRC32 {
+                checksum_length = 0;
+            }
+        }
+        reader.seek(SeekFrom::Start(start))?;
+        Ok(checksum_length)
+    }
+
+    fn list_binlog_files(local_path: &Path) -> anyhow::Result<Vec<PathBuf>> {
+        if local_path.is_file() {
+            return Ok(vec![local_path.to_path_buf()]);
+        }
+
+        let mut files = Vec::new();
+        for entry in fs::read_dir(local_path)
+            .with_context(|| format!("failed to read binlog dir: {}", local_path.display()))?
+        {
+            let path = entry?.path();
+            // skip mysql-bin.index and other non binlog files
+            if let Some(seq) = Self::get_sequence(&path) {
+                if path.is_file() {
+                    files.push((seq, path));
+                }
+            }
+        }
+        files.sort();
+        Ok(files.into_iter().map(|(_, path)| path).collect())
+    }
+
+    fn get_sequence(path: &Path) -> Option<u64> {
+        let extension = path.extension()?.to_str()?;
+        if extension.is_empty() || !extension.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        extension.parse::<u64>().ok()
+    }
+
+    fn get_filename(path: &Path) -> String {
+        path.file_name()
+            .map(|v| v.to_string_lossy().to_string())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::SystemTime;
+
+    use super::*;
+
+    const QUERY_EVENT: u8 = 2;
+    const ROTATE_EVENT: u8 = 4;
+    const XID_EVENT: u8 = 16;
+
+    fn create_test_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "ape-dts-binlog-file-reader-{}",
+            SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_list_binlog_files_in_order() {
+        let dir = create_test_dir();
+        for name in [
+            "mysql-bin.000010",
+            "mysql-bin.000002",
+            "mysql-bin.000009",
+            "mysql-bin.index",
+        ] {
+            fs::write(dir.join(name), BINLOG_MAGIC).unwrap();
+        }
+
+        let files: Vec<String> = BinlogFileReader::list_binlog_files(&dir)
+            .unwrap()
+            .iter()
+            .map(|f| BinlogFileReader::get_filename(f))
+            .collect();
+        assert_eq!(
+            files,
+            vec!["mysql-bin.000002", "mysql-bin.000009", "mysql-bin.000010"]
+        );
+
+        let reader = BinlogFileReader::new(dir.to_str().unwrap(), "").unwrap();
+        assert_eq!(reader.start_filename(), "mysql-bin.000002");
+        let reader = BinlogFileReader::new(dir.to_str().unwrap(), "mysql-bin.000009").unwrap();
+        assert_eq!(reader.start_filename(), "mysql-bin.000009");
+        assert!(BinlogFileReader::new(dir.to_str().unwrap(), "mysql-bin.000003").is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_read_rotates_across_files() {
+        let dir = create_test_dir();
+        // binlog files containing only the magic number have no events
+        fs::write(dir.join("mysql-bin.000001"), BINLOG_MAGIC).unwrap();
+        fs::write(dir.join("mysql-bin.000002"), BINLOG_MAGIC).unwrap();
+
+        let mut reader = BinlogFileReader::new(dir.to_str().unwrap(), "").unwrap();
+        assert!(reader.read().unwrap().is_none());
+        assert_eq!(reader.file_index, 2);
+
+        // single file
+        let file = dir.join("mysql-bin.000001");
+        let mut reader = BinlogFileReader::new(file.to_str().unwrap(), "").unwrap();
+        assert_eq!(reader.start_filename(), "mysql-bin.000001");
+        assert!(reader.read().unwrap().is_none());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    // crc32 of zlib, which mysql uses for binlog_checksum=CRC32
+    fn crc32(bytes: &[u8]) -> u32 {
+        let mut crc = !0u32;
+        for b in bytes {
+            crc ^= *b as u32;
+            for _ in 0..8 {
+                crc = if crc & 1 == 1 {
+                    (crc >> 1) ^ 0xedb8_8320
+                } else {
+                    crc >> 1
+                };
+            }
+        }
+        !crc
+    }
+
+    // appends an event as mysql writes it: 19 bytes header, data, 4 bytes checksum
+    fn append_event(file: &mut Vec<u8>, event_type: u8, data: &[u8]) -> u32 {
+        append_event_with_checksum(file, event_type, data, true)
+    }
+
+    fn append_event_with_checksum(
+        file: &mut Vec<u8>,
+        event_type: u8,
+        data: &[u8],
+        with_checksum: bool,
+    ) -> u32 {
+        let checksum_length = if with_checksum { 4 } else { 0 };
+        let event_length = (19 + data.len() + checksum_length) as u32;
+        let next_event_position = file.len() as u32 + event_length;
+        let mut event = Vec::new();
+        event.extend_from_slice(&1_700_000_000u32.to_le_bytes());
+        event.push(event_type);
+        event.extend_from_slice(&1u32.to_le_bytes());
+        event.extend_from_slice(&event_length.to_le_bytes());
+        event.extend_from_slice(&next_event_position.to_le_bytes());
+        event.extend_from_slice(&0u16.to_le_bytes());
+        event.extend_from_slice(data);
+        if with_checksum {
+            event.extend_from_slice(&crc32(&event).to_le_bytes());
+        }
+        file.extend_from_slice(&event);
+        next_event_position
+    }
+
+    fn query_event_data(schema: &str, query: &str) -> Vec<u8> {
+        let mut data = Vec::new();
+        // thread_id, exec_time
+        data.extend_from_slice(&1u32.to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.push(schema.len() as u8);
+        // error_code, status_vars_length
+        data.extend_from_slice(&0u16.to_le_bytes());
+        data.extend_from_slice(&0u16.to_le_bytes());
+        data.extend_from_slice(schema.as_bytes());
+        data.push(0);
+        data.extend_from_slice(query.as_bytes());
+        data
+    }
+
+    // the FormatDescriptionEvent always ends with checksum_alg and 4 bytes checksum
+    fn format_description_event_data(checksum_alg: u8) -> Vec<u8> {
+        let mut data = 4u16.to_le_bytes().to_vec();
+        let mut server_version = b"8.0.32".to_vec();
+        server_version.resize(50, 0);
+        data.extend_from_slice(&server_version);
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.push(19);
+        // post header lengths of each event type
+        data.extend_from_slice(&[0u8; 41]);
+        data.push(checksum_alg);
+        data.extend_from_slice(&[0u8; 4]);
+        data
+    }
+
+    fn rotate_event_data(position: u64, binlog_filename: &str) -> Vec<u8> {
+        let mut data = position.to_le_bytes().to_vec();
+        data.extend_from_slice(binlog_filename.as_bytes());
+        data
+    }
+
+    #[test]
+    fn test_read_events() {
+        let dir = create_test_dir();
+        // a transaction followed by a rotation, then a ddl in the next file
+        let mut file_1 = BINLOG_MAGIC.to_vec();
+        let begin_position = append_event(
+            &mut file_1,
+            QUERY_EVENT,
+            &query_event_data("test_db", "BEGIN"),
+        );
+        let xid_position = append_event(&mut file_1, XID_EVENT, &10u64.to_le_bytes());
+        append_event(
+            &mut file_1,
+            ROTATE_EVENT,
+            &rotate_event_data(4, "mysql-bin.000002"),
+        );
+        let mut file_2 = BINLOG_MAGIC.to_vec();
+        let ddl = "CREATE TABLE test_db.tb_1 (id INT PRIMARY KEY)";
+        let ddl_position =
+            append_event(&mut file_2, QUERY_EVENT, &query_event_data("test_db", ddl));
+        fs::write(dir.join("mysql-bin.000001"), &file_1).unwrap();
+        fs::write(dir.join("mysql-bin.000002"), &file_2).unwrap();
+
+        let mut reader = BinlogFileReader::new(dir.to_str().unwrap(), "").unwrap();
+        let (filename, header, data) = reader.read().unwrap().unwrap();
+        assert_eq!(filename, "mysql-bin.000001");
+        assert_eq!(header.next_event_position, begin_position);
+        let EventData::Query(query) = &data else {
+            panic!("unexpected event: {:?}", data);
+        };
+        assert_eq!(
+            (query.schema.as_str(), query.query.as_str()),
+            ("test_db", "BEGIN")
+        );
+
+        let (_, header, data) = reader.read().unwrap().unwrap();
+        assert_eq!(header.next_event_position, xid_position);
+        let EventData::Xid(xid) = &data else {
+            panic!("unexpected event: {:?}", data);
+        };
+        assert_eq!(xid.xid, 10);
+
+        let (_, _, data) = reader.read().unwrap().unwrap();
+        let EventData::Rotate(rotate) = &data else {
+            panic!("unexpected event: {:?}", data);
+        };
+        assert_eq!(rotate.binlog_filename, "mysql-bin.000002");
+
+        // the next file is read after the rotation
+        let (filename, header, data) = reader.read().unwrap().unwrap();
+        assert_eq!(filename, "mysql-bin.000002");
+        assert_eq!(header.next_event_position, ddl_position);
+        let EventData::Query(query) = &data else {
+            panic!("unexpected event: {:?}", data);
+        };
+        assert_eq!(query.query, ddl);
+        assert!(reader.read().unwrap().is_none());
+
+        // starts from the given file
+        let mut reader = BinlogFileReader::new(dir.to_str().unwrap(), "mysql-bin.000002").unwrap();
+        let (filename, _, _) = reader.read().unwrap().unwrap();
+        assert_eq!(filename, "mysql-bin.000002");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_read_checksum_length() {
+        let dir = create_test_dir();
+        for (checksum_alg, expected) in [(CHECKSUM_ALG_CRC32, 4), (0, 0)] {
+            let mut file = BINLOG_MAGIC.to_vec();
+            append_event_with_checksum(
+                &mut file,
+                FORMAT_DESCRIPTION_EVENT,
+                &format_description_event_data(checksum_alg),
+                false,
+            );
+            let path = dir.join("mysql-bin.000001");
+            fs::write(&path, &file).unwrap();
+
+            let opened = BinlogFileReader::open(&path).unwrap();
+            assert_eq!(opened.parser.checksum_length, expected);
+            // rewound to the FormatDescriptionEvent
+            let mut reader = opened.reader;
+            assert_eq!(reader.stream_position().unwrap(), 4);
+        }
+
+        // events without checksum are read when binlog_checksum=NONE
+        let mut file = BINLOG_MAGIC.to_vec();
+        append_event_with_checksum(
+            &mut file,
+            FORMAT_DESCRIPTION_EVENT,
+            &format_description_event_data(0),
+            false,
+        );
+        let ddl = "CREATE TABLE test_db.tb_1 (id INT PRIMARY KEY)";
+        append_event_with_checksum(
+            &mut file,
+            QUERY_EVENT,
+            &query_event_data("test_db", ddl),
+            false,
+        );
+        fs::write(dir.join("mysql-bin.000001"), &file).unwrap();
+
+        let mut reader = BinlogFileReader::new(dir.to_str().unwrap(), "").unwrap();
+        let (_, _, data) = reader.read().unwrap().unwrap();
+        assert!(matches!(data, EventData::FormatDescription(..)));
+        let (_, _, data) = reader.read().unwrap().unwrap();
+        let EventData::Query(query) = &data else {
+            panic!("unexpected event: {:?}", data);
+        };
+        assert_eq!(query.query, ddl);
+        assert!(reader.read().unwrap().is_none());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_read_invalid_magic() {
+        let dir = create_test_dir();
+        fs::write(dir.join("mysql-bin.000001"), b"not a binlog").unwrap();
+
+        let mut reader = BinlogFileReader::new(dir.to_str().unwrap(), "").unwrap();
+        assert!(reader.read().is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,3 +1,4 @@
+pub mod binlog_file_reader;
 pub mod binlog_util;
 pub mod mysql_cdc_extractor;
 pub mod mysql_check_extractor;
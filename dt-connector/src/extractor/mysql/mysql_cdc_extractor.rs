@@ -26,7 +26,7 @@ use mysql_binlog_connector_rust::{
 use crate::{
     extractor::{
         base_extractor::{BaseExtractor, ExtractState},
-        mysql::binlog_util::BinlogUtil,
+        mysql::{binlog_file_reader::BinlogFileReader, binlog_util::BinlogUtil},
         resumer::recovery::Recovery,
     },
     Extractor,
@@ -41,6 +41,7 @@ use dt_common::{
         row_data::RowData, row_type::RowType, syncer::Syncer,
     },
     rdb_filter::RdbFilter,
+    row_filter::RowFilter,
     utils::time_util::TimeUtil,
 };
 
@@ -50,6 +51,7 @@ pub struct MysqlCdcExtractor {
     pub meta_manager: MysqlMetaManager,
     pub conn_pool: Pool<MySql>,
     pub filter: RdbFilter,
+    pub row_filter: RowFilter,
     pub url: String,
     pub connection_auth: ConnectionAuthConfig,
     pub binlog_filename: String,
@@ -63,6 +65,10 @@ pub struct MysqlCdcExtractor {
     pub heartbeat_tb: String,
     pub keepalive_idle_secs: u64,
     pub keepalive_interval_secs: u64,
+    // read binlog from local files instead of the server if set
+    pub binlog_local_path: String,
+    // split ALTER TABLE with multiple ADD INDEX into one ddl per index
+    pub split_add_indexes: bool,
     pub syncer: Arc<Mutex<Syncer>>,
     pub recovery: Option<Arc<dyn Recovery + Send + Sync>>,
 }
@@ -78,7 +84,7 @@ const QUERY_BEGIN: &str = "BEGIN";
 #[async_trait]
 impl Extractor for MysqlCdcExtractor {
     async fn extract(&mut self) -> anyhow::Result<()> {
-        if self.extract_state.time_filter.start_timestamp > 0 {
+        if self.extract_state.time_filter.start_timestamp > 0 && self.binlog_local_path.is_empty() {
             self.binlog_filename = BinlogUtil::find_last_binlog_before_timestamp(
                 self.extract_state.time_filter.start_timestamp,
                 &self.url,
@@ -126,7 +132,11 @@ impl Extractor for MysqlCdcExtractor {
             self.heartbeat_interval_secs,
             self.heartbeat_tb
         );
-        self.extract_internal().await?;
+        if self.binlog_local_path.is_empty() {
+            self.extract_internal().await?;
+        } else {
+            self.extract_local_binlog().await?;
+        }
         self.base_extractor
             .wait_task_finish(&mut self.extract_state)
             .await
@@ -191,6 +201,53 @@ impl MysqlCdcExtractor {
         }
     }
 
+    async fn extract_local_binlog(&mut self) -> anyhow::Result<()> {
+        let mut reader = BinlogFileReader::new(&self.binlog_local_path, &self.binlog_filename)?;
+        let start_filename = reader.start_filename();
+        log_info!(
+            "reading local binlog from: {}, start binlog_filename: {}, binlog_position: {}",
+            self.binlog_local_path,
+            start_filename,
+            self.binlog_position
+        );
+
+        let mut ctx = Context {
+            binlog_filename: start_filename.clone(),
+            table_map_event_map: HashMap::new(),
+            gtid_set: None,
+        };
+        if self.gtid_enabled {
+            ctx.gtid_set = Some(GtidSet::new(self.gtid_set.as_str())?);
+        }
+
+        while let Some((binlog_filename, header, data)) = reader.read()? {
+            if self.extract_state.time_filter.ended {
+                break;
+            }
+
+            // events before the start position are only used to rebuild table maps
+            if binlog_filename == start_filename
+                && header.next_event_position <= self.binlog_position
+                && !matches!(data, EventData::TableMap(_))
+            {
+                continue;
+            }
+
+            ctx.binlog_filename = binlog_filename;
+            match data {
+                // rotation is driven by the file order
+                EventData::Rotate(_) => {}
+                _ => self.parse_events(header, data, &mut ctx).await?,
+            }
+        }
+
+        log_info!(
+            "all local binlog files read from: {}",
+            self.binlog_local_path
+        );
+        Ok(())
+    }
+
     #[async_recursion]
     async fn parse_events(
         &mut self,
@@ -343,9 +400,23 @@ impl MysqlCdcExtractor {
 
     async fn push_row_to_buf(
         &mut self,
-        row_data: RowData,
+        mut row_data: RowData,
         position: Position,
     ) -> anyhow::Result<()> {
+        if self.extract_state.time_filter.started
+            && self
+                .row_filter
+                .has_condition(&row_data.schema, &row_data.tb)
+        {
+            let tb_meta = self.meta_manager.get_tb_meta_by_row_data(&row_data).await?;
+            if self
+                .row_filter
+                .filter_row(&mut row_data, &tb_meta.basic.cols)?
+            {
+                self.extract_state.record_extracted_metrics_row(&row_data);
+                return Ok(());
+            }
+        }
         self.base_extractor
             .push_row(&mut self.extract_state, row_data, position)
             .await
@@ -429,7 +500,14 @@ impl MysqlCdcExtractor {
                 .parse_ddl(&DbType::Mysql, &query.schema, &query.query)
                 .await
             {
-                for sub_ddl_data in ddl_data.clone().split_to_multi() {
+                let mut sub_ddl_datas = ddl_data.clone().split_to_multi();
+                if self.split_add_indexes {
+                    sub_ddl_datas = sub_ddl_datas
+                        .into_iter()
+                        .flat_map(|i| i.split_add_indexes())
+                        .collect();
+                }
+                for sub_ddl_data in sub_ddl_datas {
                     let (db, tb) = sub_ddl_data.get_schema_tb();
                     // invalidate metadata cache
                     self.meta_manager.invalidate_cache(&db, &tb);
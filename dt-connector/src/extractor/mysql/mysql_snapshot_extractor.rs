@@ -60,6 +60,7 @@ pub struct MysqlSnapshotShared {
     pub filter: Arc<RdbFilter>,
     pub partition_cols: Arc<HashMap<(String, String), String>>,
     pub batch_size: usize,
+    pub chunk_size: usize,
     pub parallel_type: RdbParallelType,
     pub sample_rate: Option<u8>,
     pub recovery: Option<Arc<dyn Recovery + Send + Sync>>,
@@ -791,7 +792,7 @@ impl MysqlTableCtx {
         Ok(MySqlSnapshotSplitter::new(
             Arc::new(tb_meta.clone()),
             self.shared.conn_pool.clone(),
-            self.shared.batch_size,
+            self.shared.chunk_size,
             if !self.user_defined_partition_col.is_empty() {
                 self.user_defined_partition_col.clone()
             } else {
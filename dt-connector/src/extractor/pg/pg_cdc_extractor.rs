@@ -9,6 +9,7 @@ use std::{
     time::UNIX_EPOCH,
 };
 
+use anyhow::bail;
 use async_trait::async_trait;
 use futures::StreamExt;
 use postgres_protocol::message::backend::{
@@ -21,7 +22,7 @@ use postgres_protocol::message::backend::{
     TupleData, UpdateBody,
 };
 use postgres_types::PgLsn;
-use sqlx::{postgres::PgArguments, query::Query, Pool, Postgres};
+use sqlx::{postgres::PgArguments, query::Query, Pool, Postgres, Row};
 use tokio::{sync::Mutex, time::Duration, time::Instant};
 use tokio_postgres::replication::LogicalReplicationStream;
 
@@ -38,6 +39,7 @@ use dt_common::{
         config_enums::DbType, config_token_parser::ConfigTokenParser,
         connection_auth_config::ConnectionAuthConfig,
     },
+    error::Error,
     log_error, log_info, log_warn,
     meta::{
         adaptor::pg_col_value_convertor::PgColValueConvertor,
@@ -51,6 +53,7 @@ use dt_common::{
         syncer::Syncer,
     },
     rdb_filter::RdbFilter,
+    row_filter::RowFilter,
     utils::time_util::TimeUtil,
 };
 
@@ -60,6 +63,7 @@ pub struct PgCdcExtractor {
     pub meta_manager: PgMetaManager,
     pub conn_pool: Pool<Postgres>,
     pub filter: RdbFilter,
+    pub row_filter: RowFilter,
     pub url: String,
     pub connection_auth: ConnectionAuthConfig,
     pub slot_name: String,
@@ -524,14 +528,52 @@ impl PgCdcExtractor {
 
     async fn push_row_to_buf(
         &mut self,
-        row_data: RowData,
+        mut row_data: RowData,
         position: Position,
     ) -> anyhow::Result<()> {
+        if self.extract_state.time_filter.started
+            && self
+                .row_filter
+                .has_condition(&row_data.schema, &row_data.tb)
+        {
+            let tb_meta = self.meta_manager.get_tb_meta_by_row_data(&row_data).await?;
+            if !self.row_filter.is_prepared(&row_data.schema, &row_data.tb) {
+                Self::check_replica_identity_full(&self.conn_pool, tb_meta).await?;
+            }
+            if self
+                .row_filter
+                .filter_row(&mut row_data, &tb_meta.basic.cols)?
+            {
+                self.extract_state.record_extracted_metrics_row(&row_data);
+                return Ok(());
+            }
+        }
         self.base_extractor
             .push_row(&mut self.extract_state, row_data, position)
             .await
     }
 
+    // without REPLICA IDENTITY FULL, the before images of updates and deletes carry
+    // only the key cols, which can not be checked by where conditions
+    async fn check_replica_identity_full(
+        conn_pool: &Pool<Postgres>,
+        tb_meta: &PgTbMeta,
+    ) -> anyhow::Result<()> {
+        let sql = format!(
+            "SELECT relreplident::text AS relreplident FROM pg_class WHERE oid = {}",
+            tb_meta.oid
+        );
+        let row = sqlx::query(&sql).fetch_one(conn_pool).await?;
+        let replica_identity: String = row.try_get("relreplident")?;
+        if replica_identity != "f" {
+            bail! {Error::ConfigError(format!(
+                "config [filter].where_conditions_in_cdc requires REPLICA IDENTITY FULL, schema: {}, tb: {}",
+                tb_meta.basic.schema, tb_meta.basic.tb
+            ))}
+        }
+        Ok(())
+    }
+
     fn filter_event(&mut self, tb_meta: &PgTbMeta, row_type: RowType) -> bool {
         let schema = &tb_meta.basic.schema;
         let tb = &tb_meta.basic.tb;
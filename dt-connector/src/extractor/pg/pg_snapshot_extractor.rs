@@ -57,6 +57,7 @@ pub struct PgSnapshotShared {
     pub filter: Arc<RdbFilter>,
     pub partition_cols: Arc<HashMap<(String, String), String>>,
     pub batch_size: usize,
+    pub chunk_size: usize,
     pub parallel_type: RdbParallelType,
     pub sample_rate: Option<u8>,
     pub recovery: Option<Arc<dyn Recovery + Send + Sync>>,
@@ -781,7 +782,7 @@ impl PgTableCtx {
         Ok(PgSnapshotSplitter::new(
             Arc::new(tb_meta.clone()),
             self.shared.conn_pool.clone(),
-            self.shared.batch_size,
+            self.shared.chunk_size,
             if !user_defined_partition_col.is_empty() {
                 user_defined_partition_col.clone()
             } else {
@@ -153,6 +153,14 @@ impl<'r> RdbSnapshotExtractStatement<'r> {
             .into_iter()
             .filter(|p| !p.is_empty())
             .collect::<Vec<_>>();
+        // user defined condition may contain OR, keep it from binding with order key predicates
+        if predicates.len() > 1
+            && self
+                .where_condition
+                .is_some_and(|where_condition| !where_condition.is_empty())
+        {
+            predicates[0] = format!("({})", predicates[0]);
+        }
         if !predicates.is_empty() {
             sql.push_str(" WHERE ");
             sql.push_str(&predicates.join(" AND "));
@@ -614,6 +622,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_mysql_order_col_gt_with_where_condition() {
+        let mysql_meta = create_mysql_tb_meta();
+        let stmt = RdbSnapshotExtractStatement::from(&mysql_meta);
+        let order_cols = vec!["id".to_string()];
+        let where_condition = "username = 'active' OR price > 1".to_string();
+        let stmt = stmt
+            .with_order_cols(&order_cols)
+            .with_where_condition(&where_condition)
+            .with_predicate_type(OrderKeyPredicateType::GreaterThan)
+            .with_limit(100);
+
+        let sql = stmt.build().unwrap();
+        assert_eq!(
+            sql,
+            r#"SELECT `id`,`price`,`username`,`bio`,`large_blob` FROM `test_schema`.`test_table` WHERE (username = 'active' OR price > 1) AND `id` > ? ORDER BY `test_schema`.`test_table`.`id` ASC LIMIT 100"#
+        );
+
+        // no parentheses if there is no other predicate
+        let stmt =
+            RdbSnapshotExtractStatement::from(&mysql_meta).with_where_condition(&where_condition);
+        assert_eq!(
+            stmt.build().unwrap(),
+            r#"SELECT `id`,`price`,`username`,`bio`,`large_blob` FROM `test_schema`.`test_table` WHERE username = 'active' OR price > 1"#
+        );
+    }
+
     #[test]
     fn test_mysql_is_null_predicate_with_where_condition() {
         let mysql_meta = create_mysql_tb_meta();
@@ -635,7 +670,7 @@ mod tests {
         let sql = stmt.build().unwrap();
         assert_eq!(
             sql,
-            r#"SELECT `id`,`price`,`username`,`bio`,`large_blob` FROM `test_schema`.`test_table` WHERE id > 100 AND (`price` IS NULL OR `bio` IS NULL OR `large_blob` IS NULL) ORDER BY `test_schema`.`test_table`.`id` ASC, `test_schema`.`test_table`.`price` ASC, `test_schema`.`test_table`.`username` ASC, `test_schema`.`test_table`.`bio` ASC, `test_schema`.`test_table`.`large_blob` ASC LIMIT 100"#
+            r#"SELECT `id`,`price`,`username`,`bio`,`large_blob` FROM `test_schema`.`test_table` WHERE (id > 100) AND (`price` IS NULL OR `bio` IS NULL OR `large_blob` IS NULL) ORDER BY `test_schema`.`test_table`.`id` ASC, `test_schema`.`test_table`.`price` ASC, `test_schema`.`test_table`.`username` ASC, `test_schema`.`test_table`.`bio` ASC, `test_schema`.`test_table`.`large_blob` ASC LIMIT 100"#
         );
     }
 
@@ -782,7 +817,7 @@ mod tests {
         let sql = stmt.build().unwrap();
         assert_eq!(
             sql,
-            r#"SELECT "id"::int8,"price"::float8,"username"::text,"bio"::text,"large_blob"::bytea FROM "test_schema"."test_table" WHERE id > 100 AND ("price" IS NULL OR "bio" IS NULL OR "large_blob" IS NULL) ORDER BY "test_schema"."test_table"."id" ASC, "test_schema"."test_table"."price" ASC, "test_schema"."test_table"."username" ASC, "test_schema"."test_table"."bio" ASC, "test_schema"."test_table"."large_blob" ASC LIMIT 100"#
+            r#"SELECT "id"::int8,"price"::float8,"username"::text,"bio"::text,"large_blob"::bytea FROM "test_schema"."test_table" WHERE (id > 100) AND ("price" IS NULL OR "bio" IS NULL OR "large_blob" IS NULL) ORDER BY "test_schema"."test_table"."id" ASC, "test_schema"."test_table"."price" ASC, "test_schema"."test_table"."username" ASC, "test_schema"."test_table"."bio" ASC, "test_schema"."test_table"."large_blob" ASC LIMIT 100"#
         );
     }
 
@@ -1,6 +1,13 @@
+use anyhow::bail;
 use async_trait::async_trait;
-use redis::{Connection, ConnectionLike};
-use std::{cmp, collections::HashMap};
+use redis::{Connection, ConnectionLike, RedisResult, Value};
+use std::{
+    cmp,
+    collections::{HashMap, HashSet},
+    fs::{self, OpenOptions},
+    io::Write,
+    path::Path,
+};
 use url::Url;
 
 use crate::{
@@ -9,20 +16,61 @@ use crate::{
 };
 use dt_common::{
     config::connection_auth_config::ConnectionAuthConfig,
-    log_debug, log_info,
+    error::Error,
+    log_debug, log_error, log_info, log_warn,
     meta::redis::{
         cluster_node::ClusterNode, command::cmd_encoder::CmdEncoder, redis_object::RedisCmd,
     },
+    monitor::task_metrics::TaskMetricsType,
     utils::redis_util::RedisUtil,
 };
 
 const SLOTS_COUNT: usize = 16384;
 
+#[derive(Debug, Default, PartialEq)]
+struct MigrateCounts {
+    migrated: usize,
+    // keys already gone from the source node
+    skipped: usize,
+    failed: usize,
+}
+
+#[derive(Debug, PartialEq)]
+enum MigrateReply {
+    Migrated,
+    // the key is already gone from the source node
+    NoKey,
+    // the key already exists in the target node
+    BusyKey,
+    Failed(String),
+}
+
+// credentials passed to the target node by MIGRATE
+#[derive(Debug, Clone, Default)]
+struct MigrateAuth {
+    username: String,
+    password: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ReshardProgress {
+    pub total_slots: usize,
+    pub moved_slots: usize,
+    pub current_slot: u16,
+    pub src_node: String,
+    pub dst_node: String,
+}
+
 pub struct RedisReshardExtractor {
     pub base_extractor: BaseExtractor,
     pub extract_state: ExtractState,
     pub url: String,
     pub connection_auth: ConnectionAuthConfig,
+    // completed slots, one per line, skipped after restart, empty to disable
+    pub resume_file: String,
+    pub migrate_timeout_ms: u64,
+    // keys migrated by a single MIGRATE
+    pub migrate_batch_size: usize,
 }
 
 #[async_trait]
@@ -41,37 +89,62 @@ impl RedisReshardExtractor {
         let mut conn = RedisUtil::create_redis_conn(&self.url, &self.connection_auth).await?;
         let nodes = RedisUtil::get_cluster_master_nodes(&mut conn)?;
         let slot_address_map = RedisUtil::get_slot_address_map(&nodes);
+        let node_move_in_slots = Self::plan_slot_moves(&nodes);
+        let completed_slots = Self::load_completed_slots(&self.resume_file)?;
+        let auth = self.get_migrate_auth()?;
+        self.move_slots(
+            &nodes,
+            &node_move_in_slots,
+            &slot_address_map,
+            &completed_slots,
+            &auth,
+        )
+        .await?;
+        self.verify_reshard(&nodes, &node_move_in_slots, &slot_address_map)
+            .await
+    }
+
+    // the first SLOTS_COUNT % nodes.len() nodes take one more slot than the others,
+    // so the targets add up to SLOTS_COUNT and every moved out slot has a destination
+    fn plan_slot_moves(nodes: &[ClusterNode]) -> HashMap<String, Vec<u16>> {
         let avg_slot_count = SLOTS_COUNT / nodes.len();
+        let remainder = SLOTS_COUNT % nodes.len();
+        let target_slot_count = |i: usize| avg_slot_count + usize::from(i < remainder);
 
         // find nodes with slots to be moved out
         let mut move_out_slots = Vec::new();
-        for node in nodes.iter() {
+        for (i, node) in nodes.iter().enumerate() {
             log_info!("node: [{}] has [{}] slots", node.id, node.slots.len());
-            for i in avg_slot_count..node.slots.len() {
-                move_out_slots.push(node.slots[i]);
+            for j in target_slot_count(i)..node.slots.len() {
+                move_out_slots.push(node.slots[j]);
             }
         }
 
         // find nodes with slots to be moved in
         let mut node_move_in_slots = HashMap::new();
-        let mut i = 0;
-        for node in nodes.iter() {
-            if avg_slot_count <= node.slots.len() || move_out_slots.len() <= i {
+        let mut start = 0;
+        for (i, node) in nodes.iter().enumerate() {
+            let target = target_slot_count(i);
+            if target <= node.slots.len() || move_out_slots.len() <= start {
                 continue;
             }
 
-            let count = cmp::min(move_out_slots.len() - i, avg_slot_count - node.slots.len());
-            let slots = move_out_slots[i..i + count].to_vec();
-            i += count;
+            let count = cmp::min(move_out_slots.len() - start, target - node.slots.len());
+            let slots = move_out_slots[start..start + count].to_vec();
+            start += count;
 
             log_info!("will move slots to: [{}], slots: {:?}", node.id, slots);
             node_move_in_slots.insert(node.id.clone(), slots);
         }
 
-        self.move_slots(&nodes, &node_move_in_slots, &slot_address_map)
-            .await?;
-
-        Ok(())
+        if start < move_out_slots.len() {
+            log_warn!(
+                "{} slots have no destination: {:?}",
+                move_out_slots.len() - start,
+                &move_out_slots[start..]
+            );
+        }
+        node_move_in_slots
     }
 
     async fn move_slots(
@@ -79,11 +152,29 @@ impl RedisReshardExtractor {
         nodes: &[ClusterNode],
         node_move_in_slots: &HashMap<String, Vec<u16>>,
         slot_address_map: &HashMap<u16, &str>,
+        completed_slots: &HashSet<u16>,
+        auth: &MigrateAuth,
     ) -> anyhow::Result<()> {
+        let mut progress = ReshardProgress {
+            total_slots: node_move_in_slots.values().map(|v| v.len()).sum(),
+            ..Default::default()
+        };
+        self.extract_state.monitor.monitor.add_no_window_metrics(
+            TaskMetricsType::TotalProgressCount,
+            progress.total_slots as u64,
+        );
+
         for (dst_node_id, move_in_slots) in node_move_in_slots.iter() {
             // get dst_node by id
             let dst_node = nodes.iter().find(|i| i.id == *dst_node_id).unwrap();
             let mut dst_conn = self.get_node_conn(dst_node).await?;
+            // slots completed before restart are checked against the view of the dst node,
+            // the topology fetched from another node may not be updated yet
+            let dst_owned_slots = if move_in_slots.iter().any(|i| completed_slots.contains(i)) {
+                Self::get_owned_slots(&mut dst_conn, dst_node_id)?
+            } else {
+                HashSet::new()
+            };
 
             let mut cur_src_node: Option<ClusterNode> = None;
             let mut cur_src_conn: Option<Connection> = None;
@@ -100,17 +191,192 @@ impl RedisReshardExtractor {
                     cur_src_conn = Some(self.get_node_conn(src_node).await?);
                 }
 
-                // move slot
-                self.setslot_and_migrate(
-                    src_node,
-                    dst_node,
-                    cur_src_conn.as_mut().unwrap(),
-                    &mut dst_conn,
-                    *slot,
-                )
-                .await?;
+                progress.current_slot = *slot;
+                progress.src_node = src_node.id.clone();
+                progress.dst_node = dst_node.id.clone();
+
+                let src_conn = cur_src_conn.as_mut().unwrap();
+                if Self::is_slot_moved(*slot, completed_slots, &dst_owned_slots) {
+                    log_info!("slot {} already moved, skip", slot);
+                } else {
+                    if completed_slots.contains(slot) {
+                        log_warn!(
+                            "slot {} is completed in resume file but not owned by {}, move again",
+                            slot,
+                            dst_node.id
+                        );
+                    }
+                    // move slot
+                    self.setslot_and_migrate(
+                        src_node,
+                        dst_node,
+                        src_conn,
+                        &mut dst_conn,
+                        *slot,
+                        auth,
+                    )
+                    .await?;
+                    Self::append_completed_slot(&self.resume_file, *slot)?;
+                }
+
+                progress.moved_slots += 1;
+                self.publish_progress(&progress);
+            }
+        }
+        Ok(())
+    }
+
+    // every moved slot should be owned by its dst node in the view of the dst node,
+    // and no keys should be left in its src node
+    async fn verify_reshard(
+        &self,
+        nodes: &[ClusterNode],
+        node_move_in_slots: &HashMap<String, Vec<u16>>,
+        slot_address_map: &HashMap<u16, &str>,
+    ) -> anyhow::Result<()> {
+        let mut src_conns: HashMap<String, Connection> = HashMap::new();
+        let mut inconsistent_slots = Vec::new();
+        for (dst_node_id, move_in_slots) in node_move_in_slots.iter() {
+            let dst_node = nodes.iter().find(|i| i.id == *dst_node_id).unwrap();
+            let mut dst_conn = self.get_node_conn(dst_node).await?;
+            let mut slot_owner_map = HashMap::new();
+            for node in RedisUtil::get_cluster_master_nodes(&mut dst_conn)? {
+                for slot in node.slots.iter() {
+                    slot_owner_map.insert(*slot, node.id.clone());
+                }
+            }
+
+            for slot in move_in_slots.iter() {
+                let src_address = slot_address_map.get(slot).unwrap().to_string();
+                if !src_conns.contains_key(&src_address) {
+                    let src_node = nodes.iter().find(|i| i.address == src_address).unwrap();
+                    let src_conn = self.get_node_conn(src_node).await?;
+                    src_conns.insert(src_address.clone(), src_conn);
+                }
+                let src_conn = src_conns.get_mut(&src_address).unwrap();
+                let src_key_count = Self::count_keys_in_slot(src_conn, *slot)?;
+
+                let slot_owner = slot_owner_map.get(slot).map(|i| i.as_str());
+                if let Some(err) =
+                    Self::check_moved_slot(*slot, dst_node_id, slot_owner, src_key_count)
+                {
+                    log_error!("reshard verification failed, {}, src: {}", err, src_address);
+                    inconsistent_slots.push(*slot);
+                }
+            }
+        }
+
+        if !inconsistent_slots.is_empty() {
+            inconsistent_slots.sort_unstable();
+            bail! {Error::RedisCmdError(format!(
+                "{} slots are inconsistent after reshard: {:?}",
+                inconsistent_slots.len(),
+                inconsistent_slots
+            ))}
+        }
+        log_info!(
+            "reshard verified, {} slots moved",
+            node_move_in_slots.values().map(|v| v.len()).sum::<usize>()
+        );
+        Ok(())
+    }
+
+    // returns the discrepancies of a moved slot, None if consistent
+    fn check_moved_slot(
+        slot: u16,
+        dst_node_id: &str,
+        slot_owner: Option<&str>,
+        src_key_count: i64,
+    ) -> Option<String> {
+        let mut discrepancies = Vec::new();
+        match slot_owner {
+            Some(owner) if owner == dst_node_id => {}
+            Some(owner) => {
+                discrepancies.push(format!("owned by {} instead of {}", owner, dst_node_id))
+            }
+            None => discrepancies.push(format!("owned by no node instead of {}", dst_node_id)),
+        }
+        if src_key_count != 0 {
+            discrepancies.push(format!("{} keys left in src node", src_key_count));
+        }
+
+        if discrepancies.is_empty() {
+            None
+        } else {
+            Some(format!("slot {}: {}", slot, discrepancies.join(", ")))
+        }
+    }
+
+    fn publish_progress(&self, progress: &ReshardProgress) {
+        log_info!(
+            "reshard progress: {}/{} slots, current slot: {}, from {} to {}",
+            progress.moved_slots,
+            progress.total_slots,
+            progress.current_slot,
+            progress.src_node,
+            progress.dst_node
+        );
+        self.extract_state
+            .monitor
+            .monitor
+            .add_no_window_metrics(TaskMetricsType::FinishedProgressCount, 1);
+    }
+
+    fn load_completed_slots(resume_file: &str) -> anyhow::Result<HashSet<u16>> {
+        let mut slots = HashSet::new();
+        if resume_file.is_empty() || !Path::new(resume_file).exists() {
+            return Ok(slots);
+        }
+
+        for line in fs::read_to_string(resume_file)?.lines() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            // the last line may be partially written if the task was killed
+            match line.parse::<u16>() {
+                Ok(slot) => {
+                    slots.insert(slot);
+                }
+                Err(_) => log_warn!("invalid slot: [{}] in resume file: {}", line, resume_file),
             }
         }
+        log_info!(
+            "loaded {} completed slots from resume file: {}",
+            slots.len(),
+            resume_file
+        );
+        Ok(slots)
+    }
+
+    // a slot completed before restart is skipped only if the dst node already owns it,
+    // otherwise the move may be interrupted before cluster setslot node
+    fn is_slot_moved(
+        slot: u16,
+        completed_slots: &HashSet<u16>,
+        dst_owned_slots: &HashSet<u16>,
+    ) -> bool {
+        completed_slots.contains(&slot) && dst_owned_slots.contains(&slot)
+    }
+
+    fn get_owned_slots(conn: &mut Connection, node_id: &str) -> anyhow::Result<HashSet<u16>> {
+        let slots = RedisUtil::get_cluster_master_nodes(conn)?
+            .into_iter()
+            .filter(|node| node.id == node_id)
+            .flat_map(|node| node.slots)
+            .collect();
+        Ok(slots)
+    }
+
+    fn append_completed_slot(resume_file: &str, slot: u16) -> anyhow::Result<()> {
+        if resume_file.is_empty() {
+            return Ok(());
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(resume_file)?;
+        writeln!(file, "{}", slot)?;
         Ok(())
     }
 
@@ -121,6 +387,7 @@ impl RedisReshardExtractor {
         src_conn: &mut Connection,
         dst_conn: &mut Connection,
         slot: u16,
+        auth: &MigrateAuth,
     ) -> anyhow::Result<()> {
         log_info!(
             "moving slot {} from {} to {}",
@@ -151,26 +418,39 @@ impl RedisReshardExtractor {
         dst_conn.req_packed_command(&CmdEncoder::encode(&dst_cmd))?;
         src_conn.req_packed_command(&CmdEncoder::encode(&src_cmd))?;
 
-        // migrate
-        for key in keys.iter() {
-            log_debug!(
-                "migrating key: [{}] in slot {} from {} to {}",
-                key,
+        let counts =
+            Self::migrate_in_batches(&keys, self.migrate_batch_size, slot, |keys, replace| {
+                // keys missing in the source node are skipped by MIGRATE, count the moved ones
+                let before = Self::count_keys_in_slot(src_conn, slot)?;
+                let reply = self.migrate(src_conn, dst_node, keys, replace, auth);
+                let after = Self::count_keys_in_slot(src_conn, slot)?;
+                Ok((reply, (before - after).clamp(0, keys.len() as i64) as usize))
+            })?;
+
+        log_info!(
+            "slot {} migrated: {}, skipped: {}, failed: {}",
+            slot,
+            counts.migrated,
+            counts.skipped,
+            counts.failed
+        );
+
+        // keep the slot in migrating state, otherwise the failed keys would be lost
+        if counts.failed > 0 {
+            bail! {Error::RedisCmdError(format!(
+                "failed to migrate {} keys in slot {} from {} to {}",
+                counts.failed, slot, src_node.id, dst_node.id
+            ))}
+        }
+
+        let left_keys = Self::get_keys_in_slot(src_conn, slot)?;
+        if !left_keys.is_empty() {
+            bail! {Error::RedisCmdError(format!(
+                "slot {} still has {} keys in {} after migration",
                 slot,
-                src_node.id,
-                dst_node.id
-            );
-            let cmd = RedisCmd::from_str_args(&[
-                "migrate",
-                &dst_node.host,
-                &dst_node.port,
-                "",
-                "0",
-                "5000",
-                "keys",
-                key,
-            ]);
-            src_conn.req_packed_command(&CmdEncoder::encode(&cmd))?;
+                left_keys.len(),
+                src_node.id
+            ))}
         }
 
         // cluster setslot node
@@ -193,6 +473,155 @@ impl RedisReshardExtractor {
         Ok(())
     }
 
+    // migrate in batches, keys of a failed batch are retried one by one,
+    // migrate_fn(keys, replace) returns the MIGRATE reply and the number of keys moved
+    fn migrate_in_batches(
+        keys: &[String],
+        batch_size: usize,
+        slot: u16,
+        mut migrate_fn: impl FnMut(&[String], bool) -> anyhow::Result<(MigrateReply, usize)>,
+    ) -> anyhow::Result<MigrateCounts> {
+        let mut counts = MigrateCounts::default();
+        for batch_keys in keys.chunks(batch_size) {
+            log_debug!("migrating {} keys in slot {}", batch_keys.len(), slot);
+            // OK is replied if any key of the batch is migrated, NOKEY if none exists
+            match migrate_fn(batch_keys, false)? {
+                (MigrateReply::Migrated, migrated) => {
+                    counts.migrated += migrated;
+                    counts.skipped += batch_keys.len() - migrated;
+                    continue;
+                }
+                (MigrateReply::NoKey, _) => {
+                    counts.skipped += batch_keys.len();
+                    continue;
+                }
+                (reply, _) => log_warn!(
+                    "failed to migrate {} keys in slot {} in batch, reply: {:?}, retry one by one",
+                    batch_keys.len(),
+                    slot,
+                    reply
+                ),
+            }
+
+            for key in batch_keys.iter() {
+                let key = std::slice::from_ref(key);
+                let (mut reply, _) = migrate_fn(key, false)?;
+                if reply == MigrateReply::BusyKey {
+                    log_warn!(
+                        "key: [{}] in slot {} already exists in the target node, migrate with replace",
+                        key[0],
+                        slot
+                    );
+                    (reply, _) = migrate_fn(key, true)?;
+                }
+
+                match reply {
+                    MigrateReply::Migrated => counts.migrated += 1,
+                    MigrateReply::NoKey => counts.skipped += 1,
+                    MigrateReply::BusyKey => {
+                        counts.failed += 1;
+                        log_error!(
+                            "failed to migrate key: [{}] in slot {}, BUSYKEY",
+                            key[0],
+                            slot
+                        );
+                    }
+                    MigrateReply::Failed(err) => {
+                        counts.failed += 1;
+                        log_error!(
+                            "failed to migrate key: [{}] in slot {}, error: {}",
+                            key[0],
+                            slot,
+                            err
+                        );
+                    }
+                }
+            }
+        }
+        Ok(counts)
+    }
+
+    fn migrate(
+        &self,
+        src_conn: &mut Connection,
+        dst_node: &ClusterNode,
+        keys: &[String],
+        replace: bool,
+        auth: &MigrateAuth,
+    ) -> MigrateReply {
+        let cmd = Self::build_migrate_cmd(dst_node, keys, replace, self.migrate_timeout_ms, auth);
+        Self::classify_migrate_reply(src_conn.req_packed_command(&CmdEncoder::encode(&cmd)))
+    }
+
+    // MIGRATE host port "" 0 timeout [REPLACE] [AUTH password | AUTH2 username password] KEYS key [key ...]
+    fn build_migrate_cmd(
+        dst_node: &ClusterNode,
+        keys: &[String],
+        replace: bool,
+        timeout_ms: u64,
+        auth: &MigrateAuth,
+    ) -> RedisCmd {
+        let timeout_ms = timeout_ms.to_string();
+        let mut args = vec![
+            "migrate",
+            dst_node.host.as_str(),
+            dst_node.port.as_str(),
+            "",
+            "0",
+            timeout_ms.as_str(),
+        ];
+        if replace {
+            args.push("replace");
+        }
+        if !auth.password.is_empty() {
+            // AUTH2 requires redis 6.0+, only used for acl users
+            if auth.username.is_empty() || auth.username == "default" {
+                args.extend_from_slice(&["auth", auth.password.as_str()]);
+            } else {
+                args.extend_from_slice(&["auth2", auth.username.as_str(), auth.password.as_str()]);
+            }
+        }
+        args.push("keys");
+        args.extend(keys.iter().map(|key| key.as_str()));
+        RedisCmd::from_str_args(&args)
+    }
+
+    // the target node is authed with the same credentials as the task connection
+    fn get_migrate_auth(&self) -> anyhow::Result<MigrateAuth> {
+        let url = ConnectionAuthConfig::merge_url_with_auth(&self.url, &self.connection_auth)?;
+        let url_info = Url::parse(&url)?;
+        let decode = |component: &str| -> anyhow::Result<String> {
+            Ok(percent_encoding::percent_decode_str(component)
+                .decode_utf8()?
+                .to_string())
+        };
+        Ok(MigrateAuth {
+            username: decode(url_info.username())?,
+            password: decode(url_info.password().unwrap_or(""))?,
+        })
+    }
+
+    // MIGRATE replies OK if migrated, NOKEY if no key exists in the source node,
+    // errors of the target node (e.g. BUSYKEY) are forwarded as errors
+    fn classify_migrate_reply(reply: RedisResult<Value>) -> MigrateReply {
+        match reply {
+            Ok(Value::Okay) => MigrateReply::Migrated,
+            Ok(Value::SimpleString(s)) if s.eq_ignore_ascii_case("OK") => MigrateReply::Migrated,
+            Ok(Value::SimpleString(s)) if s.eq_ignore_ascii_case("NOKEY") => MigrateReply::NoKey,
+            Ok(value) => MigrateReply::Failed(format!("unexpected reply: {:?}", value)),
+            Err(err) => {
+                let message = err.to_string();
+                if message.contains("BUSYKEY") {
+                    MigrateReply::BusyKey
+                } else if message.contains("NOKEY") {
+                    MigrateReply::NoKey
+                } else {
+                    MigrateReply::Failed(message)
+                }
+            }
+        }
+    }
+
     fn get_keys_in_slot(conn: &mut Connection, slot: u16) -> anyhow::Result<Vec<String>> {
         // get all keys in slot
         let cmd =
@@ -202,6 +631,17 @@ impl RedisReshardExtractor {
         RedisUtil::parse_result_as_string(result)
     }
 
+    fn count_keys_in_slot(conn: &mut Connection, slot: u16) -> anyhow::Result<i64> {
+        let slot = slot.to_string();
+        match RedisUtil::send_cmd(conn, &["cluster", "countkeysinslot", &slot])? {
+            Value::Int(count) => Ok(count),
+            value => bail! {Error::RedisResultError(format!(
+                "unexpected reply of countkeysinslot {}: {:?}",
+                slot, value
+            ))},
+        }
+    }
+
     async fn get_node_conn(&self, node: &ClusterNode) -> anyhow::Result<Connection> {
         let url_info = Url::parse(&self.url)?;
         let username = url_info.username();
@@ -210,3 +650,264 @@ impl RedisReshardExtractor {
         RedisUtil::create_redis_conn(&url, &self.connection_auth).await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use redis::{ErrorKind, RedisError};
+
+    use super::*;
+
+    fn build_node(id: &str, slots: std::ops::Range<u16>) -> ClusterNode {
+        ClusterNode {
+            is_master: true,
+            id: id.into(),
+            master_id: String::new(),
+            host: "127.0.0.1".into(),
+            port: "6379".into(),
+            address: format!("127.0.0.1:{}", id),
+            slots: slots.collect(),
+            slot_hash_tag_map: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_plan_slot_moves_uneven() {
+        // 16384 = 5462 + 5461 + 5461
+        let nodes = vec![
+            build_node("node_1", 0..10000),
+            build_node("node_2", 10000..16384),
+            build_node("node_3", 0..0),
+        ];
+        let node_move_in_slots = RedisReshardExtractor::plan_slot_moves(&nodes);
+
+        let move_out_count = (10000 - 5462) + (16384 - 10000 - 5461);
+        let moved_in: Vec<u16> = node_move_in_slots.values().flatten().cloned().collect();
+        assert_eq!(moved_in.len(), move_out_count);
+        assert_eq!(node_move_in_slots["node_3"].len(), 5461);
+        assert!(moved_in
+            .iter()
+            .all(|slot| (5462..10000).contains(slot) || (10000 + 5461..16384).contains(slot)));
+
+        // the first node takes the remainder slot
+        let nodes = vec![
+            build_node("node_1", 0..0),
+            build_node("node_2", 0..8192),
+            build_node("node_3", 8192..16384),
+        ];
+        let node_move_in_slots = RedisReshardExtractor::plan_slot_moves(&nodes);
+        assert_eq!(node_move_in_slots.len(), 1);
+        assert_eq!(node_move_in_slots["node_1"].len(), 5462);
+    }
+
+    #[test]
+    fn test_resume_file() {
+        let resume_file = std::env::temp_dir().join(format!(
+            "ape-dts-reshard-resume-{}",
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        let resume_file = resume_file.to_str().unwrap();
+
+        // not exists
+        assert!(RedisReshardExtractor::load_completed_slots(resume_file)
+            .unwrap()
+            .is_empty());
+        // disabled
+        RedisReshardExtractor::append_completed_slot("", 1).unwrap();
+        assert!(RedisReshardExtractor::load_completed_slots("")
+            .unwrap()
+            .is_empty());
+
+        RedisReshardExtractor::append_completed_slot(resume_file, 100).unwrap();
+        RedisReshardExtractor::append_completed_slot(resume_file, 16383).unwrap();
+        // partially written line of a killed task
+        let mut file = OpenOptions::new().append(true).open(resume_file).unwrap();
+        write!(file, "16").unwrap();
+
+        let slots = RedisReshardExtractor::load_completed_slots(resume_file).unwrap();
+        assert_eq!(slots, HashSet::from([100, 16383, 16]));
+        fs::remove_file(resume_file).unwrap();
+    }
+
+    #[test]
+    fn test_resume_partially_moved_plan() {
+        // the topology is fetched from a node which has not seen the moves before restart,
+        // so the plan is the same as the one of the first run
+        let nodes = vec![build_node("node_1", 0..16384), build_node("node_2", 0..0)];
+        let move_in_slots = &RedisReshardExtractor::plan_slot_moves(&nodes)["node_2"];
+        assert_eq!(move_in_slots.len(), 8192);
+
+        // 8192..8202 are completed, but the move of 8201 was interrupted and
+        // node_2 does not own it, 8202 is owned by node_2 but not completed
+        let completed_slots: HashSet<u16> = (8192..8202).collect();
+        let dst_owned_slots: HashSet<u16> = (8192..8201).chain([8202]).collect();
+        let to_move: Vec<u16> = move_in_slots
+            .iter()
+            .cloned()
+            .filter(|slot| {
+                !RedisReshardExtractor::is_slot_moved(*slot, &completed_slots, &dst_owned_slots)
+            })
+            .collect();
+        assert_eq!(to_move.len(), 8192 - 9);
+        assert_eq!(to_move[0], 8201);
+        assert!(to_move.contains(&8202));
+        assert!(!to_move.iter().any(|slot| (8192..8201).contains(slot)));
+
+        // a completed slot with no knowledge of the dst node is moved again
+        assert!(!RedisReshardExtractor::is_slot_moved(
+            8192,
+            &completed_slots,
+            &HashSet::new()
+        ));
+    }
+
+    #[test]
+    fn test_check_moved_slot() {
+        let check = RedisReshardExtractor::check_moved_slot;
+        assert_eq!(check(100, "node_2", Some("node_2"), 0), None);
+        assert_eq!(
+            check(100, "node_2", Some("node_1"), 0).unwrap(),
+            "slot 100: owned by node_1 instead of node_2"
+        );
+        assert_eq!(
+            check(100, "node_2", None, 0).unwrap(),
+            "slot 100: owned by no node instead of node_2"
+        );
+        assert_eq!(
+            check(100, "node_2", Some("node_2"), 3).unwrap(),
+            "slot 100: 3 keys left in src node"
+        );
+        assert_eq!(
+            check(100, "node_2", Some("node_1"), 3).unwrap(),
+            "slot 100: owned by node_1 instead of node_2, 3 keys left in src node"
+        );
+    }
+
+    #[test]
+    fn test_build_migrate_cmd() {
+        let node = build_node("node_2", 0..0);
+        let keys = vec!["key_1".to_string()];
+        let build = |replace: bool, username: &str, password: &str| {
+            let auth = MigrateAuth {
+                username: username.into(),
+                password: password.into(),
+            };
+            RedisReshardExtractor::build_migrate_cmd(&node, &keys, replace, 60000, &auth)
+                .to_string()
+        };
+
+        assert_eq!(
+            build(false, "", ""),
+            "migrate 127.0.0.1 6379  0 60000 keys key_1"
+        );
+        assert_eq!(
+            build(true, "", "pass word"),
+            "migrate 127.0.0.1 6379  0 60000 replace auth pass word keys key_1"
+        );
+        assert_eq!(
+            build(false, "default", "123456"),
+            "migrate 127.0.0.1 6379  0 60000 auth 123456 keys key_1"
+        );
+        assert_eq!(
+            build(false, "user_1", "123456"),
+            "migrate 127.0.0.1 6379  0 60000 auth2 user_1 123456 keys key_1"
+        );
+
+        // the password is a single arg even if it contains spaces
+        let auth = MigrateAuth {
+            username: String::new(),
+            password: "pass word".into(),
+        };
+        let cmd = RedisReshardExtractor::build_migrate_cmd(&node, &keys, false, 5000, &auth);
+        assert_eq!(cmd.args[6], b"auth");
+        assert_eq!(cmd.args[7], b"pass word");
+    }
+
+    #[test]
+    fn test_migrate_in_batches() {
+        let node = build_node("node_2", 0..0);
+        let keys: Vec<String> = (0..250).map(|i| format!("key_{}", i)).collect();
+        // key_5 expired and key_120 was deleted before migration,
+        // key_210 exists in the target node so its batch fails with BUSYKEY
+        let mut source: HashSet<String> = keys.iter().cloned().collect();
+        source.remove("key_5");
+        source.remove("key_120");
+
+        let mut cmds = Vec::new();
+        let counts = RedisReshardExtractor::migrate_in_batches(&keys, 100, 1, |keys, replace| {
+            cmds.push(RedisReshardExtractor::build_migrate_cmd(
+                &node,
+                keys,
+                replace,
+                60000,
+                &MigrateAuth::default(),
+            ));
+            if keys.iter().any(|key| key == "key_210") && !replace {
+                return Ok((MigrateReply::BusyKey, 0));
+            }
+            let moved = keys.iter().filter(|key| source.remove(*key)).count();
+            if moved == 0 {
+                return Ok((MigrateReply::NoKey, 0));
+            }
+            Ok((MigrateReply::Migrated, moved))
+        })
+        .unwrap();
+
+        assert_eq!(
+            counts,
+            MigrateCounts {
+                migrated: 248,
+                skipped: 2,
+                failed: 0,
+            }
+        );
+        assert!(source.is_empty());
+
+        // 3 batches, the failed one is retried key by key and key_210 with replace
+        assert_eq!(cmds.len(), 3 + 50 + 1);
+        for (cmd, range) in cmds.iter().zip([0..100, 100..200, 200..250]) {
+            let args = cmd.args_to_string();
+            let keys_index = args.iter().position(|arg| arg == "keys").unwrap();
+            let expected: Vec<String> = range.map(|i| format!("key_{}", i)).collect();
+            assert_eq!(args[keys_index + 1..], expected[..]);
+        }
+        let retried: Vec<String> = cmds[3..]
+            .iter()
+            .map(|cmd| cmd.args_to_string().last().unwrap().clone())
+            .collect();
+        let mut expected: Vec<String> = (200..250).map(|i| format!("key_{}", i)).collect();
+        expected.insert(11, "key_210".into());
+        assert_eq!(retried, expected);
+        assert!(cmds[14].args_to_string().contains(&"replace".to_string()));
+    }
+
+    #[test]
+    fn test_classify_migrate_reply() {
+        let classify = RedisReshardExtractor::classify_migrate_reply;
+        assert_eq!(classify(Ok(Value::Okay)), MigrateReply::Migrated);
+        assert_eq!(
+            classify(Ok(Value::SimpleString("NOKEY".into()))),
+            MigrateReply::NoKey
+        );
+
+        let err = RedisError::from((
+            ErrorKind::ResponseError,
+            "An error was signalled by the server",
+            "Target instance replied with error: BUSYKEY Target key name already exists.".into(),
+        ));
+        assert_eq!(classify(Err(err)), MigrateReply::BusyKey);
+
+        let err = RedisError::from((
+            ErrorKind::IoError,
+            "IOERR",
+            "error or timeout writing to target instance".into(),
+        ));
+        assert!(matches!(classify(Err(err)), MigrateReply::Failed(_)));
+        assert!(matches!(
+            classify(Ok(Value::Int(1))),
+            MigrateReply::Failed(_)
+        ));
+    }
+}
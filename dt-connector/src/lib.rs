@@ -14,8 +14,8 @@ pub mod sinker;
 use async_trait::async_trait;
 use checker::check_log::CheckLog;
 use dt_common::meta::{
-    dcl_meta::dcl_data::DclData, ddl_meta::ddl_data::DdlData, dt_data::DtItem, row_data::RowData,
-    struct_meta::struct_data::StructData,
+    dcl_meta::dcl_data::DclData, ddl_meta::ddl_data::DdlData, dt_data::DtItem, position::Position,
+    row_data::RowData, struct_meta::struct_data::StructData,
 };
 #[async_trait]
 pub trait Sinker {
@@ -51,6 +51,18 @@ pub trait Sinker {
         Ok(())
     }
 
+    // called by the pipeline before the rows of a batch are sunk, with the source positions
+    // of the first and last items of the batch
+    async fn begin_dml_batch(&mut self, _first: &Position, _last: &Position) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    // called by the pipeline periodically, even if no data flows,
+    // sinkers sending heartbeats to the target decide their own intervals
+    async fn sink_heartbeat(&mut self, _position: &Position) -> anyhow::Result<()> {
+        Ok(())
+    }
+
     fn get_id(&self) -> String {
         String::new()
     }
@@ -232,6 +232,80 @@ impl RdbQueryBuilder<'_> {
         Ok((RdbQueryInfo { sql, cols, binds }, malloc_size))
     }
 
+    /// Batch insert which updates the existing rows on conflicts,
+    /// mysql: INSERT ... ON DUPLICATE KEY UPDATE, pg: INSERT ... ON CONFLICT (key cols) DO UPDATE.
+    pub fn get_batch_upsert_query<'a>(
+        &self,
+        data: &'a [RowData],
+        start_index: usize,
+        batch_size: usize,
+    ) -> anyhow::Result<(RdbQueryInfo<'a>, usize)> {
+        let (mut query_info, data_size) =
+            self.get_batch_insert_query(data, start_index, batch_size, false)?;
+        query_info.sql = format!("{} {}", query_info.sql, self.get_upsert_clause());
+        Ok((query_info, data_size))
+    }
+
+    pub fn get_upsert_query_info<'a>(
+        &self,
+        row_data: &'a RowData,
+    ) -> anyhow::Result<RdbQueryInfo<'a>> {
+        let (query_info, _) = self.get_batch_upsert_query(std::slice::from_ref(row_data), 0, 1)?;
+        Ok(query_info)
+    }
+
+    fn get_upsert_clause(&self) -> String {
+        // the primary key if exists, otherwise the unique key chosen as id_cols
+        let conflict_cols: &[String] = if self.rdb_tb_meta.key_map.is_empty() {
+            &[]
+        } else {
+            self.rdb_tb_meta
+                .key_map
+                .get("primary")
+                .unwrap_or(&self.rdb_tb_meta.id_cols)
+        };
+        let update_cols: Vec<String> = self
+            .rdb_tb_meta
+            .cols
+            .iter()
+            .filter(|col| !conflict_cols.contains(col))
+            .cloned()
+            .collect();
+
+        if self.db_type == DbType::Pg {
+            if conflict_cols.is_empty() {
+                return "ON CONFLICT DO NOTHING".to_string();
+            }
+            let conflict_target = SqlUtil::escape_cols(&conflict_cols.to_vec(), &self.db_type);
+            if update_cols.is_empty() {
+                return format!("ON CONFLICT ({}) DO NOTHING", conflict_target.join(","));
+            }
+            let set_pairs: Vec<String> = self
+                .escape_cols(&update_cols)
+                .iter()
+                .map(|col| format!("{}=EXCLUDED.{}", col, col))
+                .collect();
+            format!(
+                "ON CONFLICT ({}) DO UPDATE SET {}",
+                conflict_target.join(","),
+                set_pairs.join(",")
+            )
+        } else {
+            // any unique key may conflict in mysql, if all cols are keys, update them as is
+            let update_cols = if update_cols.is_empty() {
+                &self.rdb_tb_meta.cols
+            } else {
+                &update_cols
+            };
+            let set_pairs: Vec<String> = self
+                .escape_cols(update_cols)
+                .iter()
+                .map(|col| format!("{}=VALUES({})", col, col))
+                .collect();
+            format!("ON DUPLICATE KEY UPDATE {}", set_pairs.join(","))
+        }
+    }
+
     fn get_replace_query<'a>(
         &self,
         row_data: &'a RowData,
@@ -797,8 +871,8 @@ impl RdbQueryBuilder<'_> {
             // varchar, char, tinytext, mediumtext, longtext, text
             ColValue::RawString(v) => SqlUtil::binary_to_str(v),
 
-            // tinyblob, mediumblob, longblob, blob, varbinary, binary
-            ColValue::Blob(v) => (hex::encode(v), true),
+            // tinyblob, mediumblob, longblob, blob, varbinary, binary, geometry
+            ColValue::Blob(v) | ColValue::Geometry(v) => (hex::encode(v), true),
 
             _ => {
                 if let Some(v) = col_value.to_option_string() {
@@ -898,6 +972,7 @@ mod tests {
 
     use dt_common::meta::{
         col_value::ColValue,
+        mysql::mysql_tb_meta::MysqlTbMeta,
         pg::{pg_col_type::PgColType, pg_tb_meta::PgTbMeta, pg_value_type::PgValueType},
         rdb_tb_meta::RdbTbMeta,
         row_data::RowData,
@@ -1290,4 +1365,92 @@ mod tests {
             .contains(r#"WHERE ctid IN (SELECT ctid FROM "public"."t1" WHERE"#));
         assert!(query_info.sql.contains("LIMIT 1"));
     }
+
+    fn build_two_col_pk_tb_meta() -> RdbTbMeta {
+        RdbTbMeta {
+            schema: "test_db".to_string(),
+            tb: "t2".to_string(),
+            cols: vec![
+                "id".to_string(),
+                "region".to_string(),
+                "name".to_string(),
+                "age".to_string(),
+            ],
+            key_map: HashMap::from([(
+                "primary".to_string(),
+                vec!["id".to_string(), "region".to_string()],
+            )]),
+            id_cols: vec!["id".to_string(), "region".to_string()],
+            ..Default::default()
+        }
+    }
+
+    fn build_two_col_pk_insert_rows() -> Vec<RowData> {
+        (1..=2)
+            .map(|id| {
+                let after = HashMap::from([
+                    ("id".to_string(), ColValue::Long(id)),
+                    ("region".to_string(), ColValue::String("eu".to_string())),
+                    ("name".to_string(), ColValue::String(format!("n{}", id))),
+                    ("age".to_string(), ColValue::Long(20)),
+                ]);
+                RowData::new(
+                    "test_db".to_string(),
+                    "t2".to_string(),
+                    0,
+                    RowType::Insert,
+                    None,
+                    Some(after),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_mysql_batch_upsert_query_with_two_col_pk() {
+        let tb_meta = MysqlTbMeta {
+            basic: build_two_col_pk_tb_meta(),
+            ..Default::default()
+        };
+        let data = build_two_col_pk_insert_rows();
+        let builder = RdbQueryBuilder::new_for_mysql(&tb_meta, None);
+
+        let (query_info, _) = builder.get_batch_upsert_query(&data, 0, 2).unwrap();
+        assert_eq!(
+            query_info.sql,
+            "INSERT INTO `test_db`.`t2`(`id`,`region`,`name`,`age`) VALUES(?,?,?,?),(?,?,?,?) \
+            ON DUPLICATE KEY UPDATE `name`=VALUES(`name`),`age`=VALUES(`age`)"
+        );
+        assert_eq!(query_info.binds.len(), 8);
+
+        let query_info = builder.get_upsert_query_info(&data[1]).unwrap();
+        assert!(query_info.sql.ends_with(
+            "VALUES(?,?,?,?) ON DUPLICATE KEY UPDATE `name`=VALUES(`name`),`age`=VALUES(`age`)"
+        ));
+        assert_eq!(query_info.binds[0], Some(&ColValue::Long(2)));
+    }
+
+    #[test]
+    fn test_pg_batch_upsert_query_with_two_col_pk() {
+        let col_type_map = HashMap::from([
+            ("id".to_string(), build_pg_col_type("int4")),
+            ("region".to_string(), build_pg_col_type("text")),
+            ("name".to_string(), build_pg_col_type("text")),
+            ("age".to_string(), build_pg_col_type("int4")),
+        ]);
+        let tb_meta = PgTbMeta {
+            basic: build_two_col_pk_tb_meta(),
+            oid: 1,
+            col_type_map,
+        };
+        let data = build_two_col_pk_insert_rows();
+        let builder = RdbQueryBuilder::new_for_pg(&tb_meta, None);
+
+        let (query_info, _) = builder.get_batch_upsert_query(&data, 0, 2).unwrap();
+        assert_eq!(
+            query_info.sql,
+            r#"INSERT INTO "test_db"."t2"("id","region","name","age") VALUES($1::int4,$2::text,$3::text,$4::int4),($5::int4,$6::text,$7::text,$8::int4) ON CONFLICT ("id","region") DO UPDATE SET "name"=EXCLUDED."name","age"=EXCLUDED."age""#
+        );
+        assert_eq!(query_info.binds.len(), 8);
+    }
 }
@@ -3,13 +3,15 @@ use dt_common::{
     config::{
         config_enums::DbType, config_token_parser::ConfigTokenParser, router_config::RouterConfig,
     },
+    error::Error,
     meta::{
         ddl_meta::{ddl_data::DdlData, ddl_statement::DdlStatement},
+        mongo::{mongo_constant::MongoConstants, mongo_doc_projection::MongoDocProjection},
         struct_meta::{statement::struct_statement::StructStatement, struct_data::StructData},
     },
     utils::sql_util::SqlUtil,
 };
-use std::collections::HashMap;
+use std::{borrow::Cow, collections::HashMap};
 
 use dt_common::meta::{col_value::ColValue, row_data::RowData};
 use serde::{Deserialize, Serialize};
@@ -17,6 +19,7 @@ use serde::{Deserialize, Serialize};
 type SchemaMap = HashMap<String, String>;
 type TbMap = HashMap<(String, String), (String, String)>;
 type TbColMap = HashMap<(String, String), HashMap<String, String>>;
+type DocProjectionMap = HashMap<(String, String), MongoDocProjection>;
 
 const JSON_PREFIX: &str = "json:";
 
@@ -25,6 +28,8 @@ pub struct RdbRouter {
     forward: RdbRouterInner,
     reverse: RdbRouterInner,
     topic: RdbTopicRouterInner,
+    // HashMap<(src_schema, src_tb), MongoDocProjection>, flattens mongo docs into columns
+    doc_projection: DocProjectionMap,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -57,16 +62,20 @@ impl RdbRouter {
         let inner = RdbRouterInner::from_config(config, db_type)?;
         let topic = RdbTopicRouterInner::from_config(config, db_type)?;
         let reverse = inner.reverse();
+        let doc_projection = match config {
+            RouterConfig::Rdb { doc_projection, .. } => Self::parse_doc_projection(doc_projection)?,
+        };
 
         Ok(Self {
             forward: inner,
             reverse,
             topic,
+            doc_projection,
         })
     }
 
     pub fn has_route_rules(&self) -> bool {
-        self.forward.has_route_rules()
+        self.forward.has_route_rules() || !self.doc_projection.is_empty()
     }
 
     pub fn get_schema_map<'a>(&'a self, schema: &'a str) -> &'a str {
@@ -101,10 +110,58 @@ impl RdbRouter {
         self.forward.route_row(row_data)
     }
 
+    /// Replace the mongo doc of a row with the projected columns, keyed by the source db and tb,
+    /// so this should be called before route_row.
+    pub fn project_row(&self, mut row_data: RowData) -> anyhow::Result<RowData> {
+        let Some(projection) = self
+            .doc_projection
+            .get(&(row_data.schema.clone(), row_data.tb.clone()))
+        else {
+            return Ok(row_data);
+        };
+
+        let project_col_values =
+            |col_values: HashMap<String, ColValue>| -> anyhow::Result<HashMap<String, ColValue>> {
+                match col_values.get(MongoConstants::DOC) {
+                    Some(ColValue::MongoDoc(doc)) => projection.project(doc).with_context(|| {
+                        format!(
+                            "failed to project mongo doc, schema: {}, tb: {}",
+                            row_data.schema, row_data.tb
+                        )
+                    }),
+                    _ => Ok(col_values),
+                }
+            };
+
+        if let Some(before) = row_data.before.take() {
+            row_data.before = Some(project_col_values(before)?);
+        }
+        if let Some(after) = row_data.after.take() {
+            row_data.after = Some(project_col_values(after)?);
+        }
+        row_data.refresh_data_size();
+        Ok(row_data)
+    }
+
     pub fn reverse_route_row(&self, row_data: RowData) -> RowData {
         self.reverse.route_row(row_data)
     }
 
+    /// Rows reaching sinkers are already routed, sinkers building keys with the source meta
+    /// (key_map, order_cols) should look them up in the row mapped back to the source table and cols.
+    pub fn reverse_route_row_for_key<'a>(&self, row_data: &'a RowData) -> Cow<'a, RowData> {
+        let (src_schema, src_tb) = self.reverse_get_tb_map(&row_data.schema, &row_data.tb);
+        if src_schema == row_data.schema
+            && src_tb == row_data.tb
+            && self
+                .reverse_get_col_map(&row_data.schema, &row_data.tb)
+                .is_none()
+        {
+            return Cow::Borrowed(row_data);
+        }
+        Cow::Owned(self.reverse_route_row(row_data.clone()))
+    }
+
     pub fn route_ddl(&self, ddl_data: DdlData) -> DdlData {
         self.forward.route_ddl(ddl_data)
     }
@@ -150,6 +207,28 @@ impl RdbRouter {
         RdbRouterInner::parse_col_map(config_str)
     }
 
+    fn parse_doc_projection(config_str: &str) -> anyhow::Result<DocProjectionMap> {
+        let mut results = DocProjectionMap::new();
+        if config_str.trim().is_empty() {
+            return Ok(results);
+        }
+
+        #[derive(Serialize, Deserialize)]
+        struct TbDocProjectionType {
+            db: String,
+            tb: String,
+            #[serde(flatten)]
+            projection: MongoDocProjection,
+        }
+        // doc_projection=json:[{"db":"test_db","tb":"tb_1","cols":{"city":"address.city"},"array_policy":"join"}]
+        let config: Vec<TbDocProjectionType> =
+            serde_json::from_str(config_str.trim_start_matches(JSON_PREFIX))?;
+        for i in config {
+            results.insert((i.db, i.tb), i.projection);
+        }
+        Ok(results)
+    }
+
     #[cfg(test)]
     pub(crate) fn from_maps_for_test(
         schema_map: SchemaMap,
@@ -167,6 +246,7 @@ impl RdbRouter {
             forward: inner,
             reverse,
             topic: RdbTopicRouterInner { topic_map },
+            doc_projection: DocProjectionMap::new(),
         }
     }
 }
@@ -227,8 +307,9 @@ impl RdbRouterInner {
             for (src_col, dst_col) in col_map.iter() {
                 reverse_col_map.insert(dst_col.into(), src_col.into());
             }
-            let dst_tb = self.tb_map.get(src_schema_tb).unwrap();
-            reverse_tb_col_map.insert(dst_tb.clone(), reverse_col_map);
+            // col_map may be configured without a tb_map entry for the table
+            let (dst_schema, dst_tb) = self.get_tb_map(&src_schema_tb.0, &src_schema_tb.1);
+            reverse_tb_col_map.insert((dst_schema.into(), dst_tb.into()), reverse_col_map);
         }
 
         for (src_tb, dst_tb) in self.tb_map.iter() {
@@ -287,8 +368,10 @@ impl RdbRouterInner {
 
     fn route_ddl(&self, mut ddl_data: DdlData) -> DdlData {
         match &mut ddl_data.statement {
-            DdlStatement::MysqlAlterTableRename(_)
+            DdlStatement::AlterDatabaseRename(_)
+            | DdlStatement::MysqlAlterTableRename(_)
             | DdlStatement::PgAlterTableRename(_)
+            | DdlStatement::PgAlterViewRename(_)
             | DdlStatement::RenameTable(_) => {
                 let (src_schema, src_tb) = ddl_data.get_schema_tb();
                 let (src_new_schema, src_new_tb) = ddl_data.get_rename_to_schema_tb();
@@ -305,7 +388,18 @@ impl RdbRouterInner {
             _ => {
                 let (src_schema, src_tb) = ddl_data.get_schema_tb();
                 let (dst_schema, dst_tb) = self.get_tb_map(&src_schema, &src_tb);
+                ddl_data.statement.route_referenced_tables(
+                    &src_schema,
+                    dst_schema,
+                    |schema, tb| {
+                        let (dst_schema, dst_tb) = self.get_tb_map(schema, tb);
+                        (dst_schema.into(), dst_tb.into())
+                    },
+                );
                 ddl_data.statement.route(dst_schema.into(), dst_tb.into());
+                if let Some(col_map) = self.get_col_map(&src_schema, &src_tb) {
+                    ddl_data.statement.route_columns(col_map);
+                }
             }
         }
 
@@ -398,14 +492,21 @@ impl RdbRouterInner {
     fn parse_tb_map(config_str: &str, db_type: &DbType) -> anyhow::Result<TbMap> {
         // tb_map=src_db_1.src_tb_1:dst_db_1.dst_tb_1,src_db_2.src_tb_2:dst_db_2.dst_tb_2
         let mut tb_map = HashMap::new();
-        let tokens = Self::parse_config(config_str, db_type)?;
-        let mut i = 0;
-        while i < tokens.len() {
+        let escape_pairs = SqlUtil::get_escape_pairs(db_type);
+        let unescape = |token: &str| {
+            let mut token = token.to_string();
+            for escape_pair in escape_pairs.iter() {
+                token = SqlUtil::unescape(&token, escape_pair);
+            }
+            token
+        };
+        for ((src_schema, src_tb), (dst_schema, dst_tb)) in
+            ConfigTokenParser::parse_router_pairs(config_str, db_type)?
+        {
             tb_map.insert(
-                (tokens[i].to_string(), tokens[i + 1].to_string()),
-                (tokens[i + 2].to_string(), tokens[i + 3].to_string()),
+                (unescape(&src_schema), unescape(&src_tb)),
+                (unescape(&dst_schema), unescape(&dst_tb)),
             );
-            i += 4;
         }
         Ok(tb_map)
     }
@@ -426,11 +527,29 @@ impl RdbRouterInner {
         let config: Vec<TbColMapType> =
             serde_json::from_str(config_str.trim_start_matches(JSON_PREFIX))?;
         for i in config {
+            Self::validate_col_map(&i.db, &i.tb, &i.col_map)?;
             results.insert((i.db, i.tb), i.col_map);
         }
         Ok(results)
     }
 
+    fn validate_col_map(
+        db: &str,
+        tb: &str,
+        col_map: &HashMap<String, String>,
+    ) -> anyhow::Result<()> {
+        let mut dst_cols = HashMap::new();
+        for (src_col, dst_col) in col_map.iter() {
+            if let Some(other_src_col) = dst_cols.insert(dst_col, src_col) {
+                bail! {Error::ConfigError(format!(
+                    "col_map of {}.{} maps both {} and {} to {}",
+                    db, tb, other_src_col, src_col, dst_col
+                ))}
+            }
+        }
+        Ok(())
+    }
+
     fn parse_config(config_str: &str, db_type: &DbType) -> anyhow::Result<Vec<String>> {
         let delimiters = vec![',', '.', ':'];
         let tokens = ConfigTokenParser::parse_config(config_str, db_type, &delimiters, None)?;
@@ -489,9 +608,16 @@ impl RdbTopicRouterInner {
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashMap;
+    use std::collections::{HashMap, HashSet};
 
-    use dt_common::config::{config_enums::DbType, router_config::RouterConfig};
+    use dt_common::{
+        config::{config_enums::DbType, router_config::RouterConfig},
+        meta::{
+            col_value::ColValue, ddl_meta::ddl_parser::DdlParser,
+            mongo::mongo_constant::MongoConstants, row_data::RowData, row_type::RowType,
+        },
+    };
+    use mongodb::bson::doc;
 
     use super::{RdbRouter, TbColMap, TbMap};
 
@@ -508,6 +634,59 @@ mod tests {
         assert_eq!(*tb_1.get("f_1").unwrap(), "dst_f_1".to_string());
     }
 
+    #[test]
+    fn test_parse_col_map_collision() {
+        let config_str =
+            r#"json:[{"db":"db_1","tb":"tb_1","col_map":{"f_0":"dst_f","f_1":"dst_f"}}]"#;
+        assert!(RdbRouter::parse_col_map(config_str).is_err());
+
+        // swapping col names is allowed
+        let config_str = r#"json:[{"db":"db_1","tb":"tb_1","col_map":{"f_0":"f_1","f_1":"f_0"}}]"#;
+        assert!(RdbRouter::parse_col_map(config_str).is_ok());
+    }
+
+    #[test]
+    fn test_route_col_map_without_tb_map() {
+        let config = RouterConfig::Rdb {
+            schema_map: "db_1:dst_db_1".into(),
+            tb_map: String::new(),
+            col_map: r#"json:[{"db":"db_1","tb":"tb_1","col_map":{"id":"user_id"}}]"#.into(),
+            topic_map: String::new(),
+            doc_projection: String::new(),
+        };
+        let router = RdbRouter::from_config(&config, &DbType::Mysql)
+            .unwrap()
+            .unwrap();
+
+        let after = HashMap::from([
+            ("id".to_string(), ColValue::Long(1)),
+            ("name".to_string(), ColValue::String("a".into())),
+        ]);
+        let row_data = RowData::new(
+            "db_1".into(),
+            "tb_1".into(),
+            0,
+            RowType::Insert,
+            None,
+            Some(after.clone()),
+        );
+        let row_data = router.route_row(row_data);
+        assert_eq!(row_data.schema, "dst_db_1");
+        assert_eq!(
+            row_data
+                .after
+                .as_ref()
+                .unwrap()
+                .keys()
+                .collect::<HashSet<_>>(),
+            HashSet::from([&"user_id".to_string(), &"name".to_string()])
+        );
+
+        let key_row = router.reverse_route_row_for_key(&row_data);
+        assert_eq!(key_row.schema, "db_1");
+        assert_eq!(key_row.after.as_ref().unwrap(), &after);
+    }
+
     #[test]
     fn test_parse_schema_map() {
         // mysql
@@ -612,6 +791,7 @@ mod tests {
             tb_map: tb_map_str.into(),
             col_map: col_map_str.into(),
             topic_map: topic_map.into(),
+            doc_projection: String::new(),
         };
         let router = RdbRouter::from_config(&config, &DbType::Mysql)
             .unwrap()
@@ -650,6 +830,194 @@ mod tests {
         assert_eq!(router.get_topic("db:2", "tb:1"), "test");
     }
 
+    #[test]
+    fn test_route_alter_database_rename() {
+        let config = RouterConfig::Rdb {
+            schema_map: "app_v1:app_v2,app_v1_bak:app_v2_bak".into(),
+            tb_map: String::new(),
+            col_map: String::new(),
+            topic_map: String::new(),
+            doc_projection: String::new(),
+        };
+        let router = RdbRouter::from_config(&config, &DbType::Pg)
+            .unwrap()
+            .unwrap();
+
+        let parser = DdlParser::new(DbType::Pg);
+        let ddl_data = parser
+            .parse("ALTER DATABASE app_v1 RENAME TO app_v1_bak")
+            .unwrap()
+            .unwrap();
+        let ddl_data = router.route_ddl(ddl_data);
+        assert_eq!(
+            ddl_data.to_sql(),
+            r#"ALTER DATABASE "app_v2" RENAME TO "app_v2_bak""#
+        );
+    }
+
+    #[test]
+    fn test_route_alter_view_rename() {
+        let config = RouterConfig::Rdb {
+            schema_map: "schema_1:dst_schema_1,schema_2:dst_schema_2".into(),
+            tb_map: String::new(),
+            col_map: String::new(),
+            topic_map: String::new(),
+            doc_projection: String::new(),
+        };
+        let router = RdbRouter::from_config(&config, &DbType::Pg)
+            .unwrap()
+            .unwrap();
+
+        let parser = DdlParser::new(DbType::Pg);
+        let cases = [
+            (
+                "ALTER VIEW schema_1.v_1 RENAME TO schema_2.v_2",
+                r#"ALTER VIEW "dst_schema_1"."v_1" RENAME TO "dst_schema_2"."v_2""#,
+            ),
+            (
+                "ALTER MATERIALIZED VIEW IF EXISTS schema_1.mv_1 RENAME TO mv_2",
+                r#"ALTER MATERIALIZED VIEW IF EXISTS "dst_schema_1"."mv_1" RENAME TO "mv_2""#,
+            ),
+        ];
+        for (sql, expect_sql) in cases {
+            let ddl_data = parser.parse(sql).unwrap().unwrap();
+            let ddl_data = router.route_ddl(ddl_data);
+            assert_eq!(ddl_data.to_sql(), expect_sql);
+        }
+    }
+
+    #[test]
+    fn test_route_alter_table_referenced_tables() {
+        let build_router = |db_type: &DbType| {
+            let config = RouterConfig::Rdb {
+                schema_map: "db_1:dst_db_1,db_2:dst_db_2".into(),
+                tb_map: "db_2.tb_2:dst_db_3.dst_tb_2,db_1.tb_4:dst_db_4.tb_4".into(),
+                col_map: String::new(),
+                topic_map: String::new(),
+                doc_projection: String::new(),
+            };
+            RdbRouter::from_config(&config, db_type).unwrap().unwrap()
+        };
+
+        let cases = [
+            // quoted, cross schema
+            (
+                DbType::Mysql,
+                "ALTER TABLE db_1.tb_1 ADD CONSTRAINT fk_1 FOREIGN KEY (pid) REFERENCES `db_2`.`tb_2` (id)",
+                "ALTER TABLE `dst_db_1`.`tb_1` ADD CONSTRAINT fk_1 FOREIGN KEY (pid) REFERENCES `dst_db_3`.`dst_tb_2` (id)",
+            ),
+            // unqualified, same schema after routing
+            (
+                DbType::Mysql,
+                "ALTER TABLE db_1.tb_1 ADD FOREIGN KEY (pid) references tb_3(id) ON DELETE CASCADE",
+                "ALTER TABLE `dst_db_1`.`tb_1` ADD FOREIGN KEY (pid) references `tb_3`(id) ON DELETE CASCADE",
+            ),
+            // string literals are kept as is
+            (
+                DbType::Mysql,
+                "ALTER TABLE db_1.tb_1 COMMENT 'references db_2.tb_2'",
+                "ALTER TABLE `dst_db_1`.`tb_1` COMMENT 'references db_2.tb_2'",
+            ),
+            (
+                DbType::Pg,
+                r#"ALTER TABLE db_1.tb_1 ADD CONSTRAINT fk_1 FOREIGN KEY (pid) REFERENCES "db_2" . "tb_2"(id)"#,
+                r#"ALTER TABLE "dst_db_1"."tb_1" ADD CONSTRAINT fk_1 FOREIGN KEY (pid) REFERENCES "dst_db_3"."dst_tb_2"(id)"#,
+            ),
+            // unqualified, routed to another schema
+            (
+                DbType::Pg,
+                r#"ALTER TABLE db_1.tb_1 ADD CONSTRAINT "fk_1" FOREIGN KEY (pid) REFERENCES tb_4 (id)"#,
+                r#"ALTER TABLE "dst_db_1"."tb_1" ADD CONSTRAINT "fk_1" FOREIGN KEY (pid) REFERENCES "dst_db_4"."tb_4" (id)"#,
+            ),
+            // unquoted pg identifiers are folded to lower case, quoted ones are kept
+            (
+                DbType::Pg,
+                r#"ALTER TABLE db_1.tb_1 ADD FOREIGN KEY (pid) REFERENCES DB_2.Tb_2 (id), ADD FOREIGN KEY (pid) REFERENCES "DB_2"."Tb_2" (id)"#,
+                r#"ALTER TABLE "dst_db_1"."tb_1" ADD FOREIGN KEY (pid) REFERENCES "dst_db_3"."dst_tb_2" (id), ADD FOREIGN KEY (pid) REFERENCES "DB_2"."Tb_2" (id)"#,
+            ),
+        ];
+
+        for (db_type, sql, expect_sql) in cases {
+            let router = build_router(&db_type);
+            let ddl_data = DdlParser::new(db_type).parse(sql).unwrap().unwrap();
+            let ddl_data = router.route_ddl(ddl_data);
+            assert_eq!(ddl_data.to_sql(), expect_sql);
+        }
+    }
+
+    #[test]
+    fn test_route_alter_table_cols() {
+        let col_map = r#"json:[{"db":"db_1","tb":"tb_1","col_map":{"name":"user_name","f 1":"dst f 1","int":"int_2"}}]"#;
+        let build_router = |db_type: &DbType| {
+            let config = RouterConfig::Rdb {
+                schema_map: String::new(),
+                tb_map: String::new(),
+                col_map: col_map.into(),
+                topic_map: String::new(),
+                doc_projection: String::new(),
+            };
+            RdbRouter::from_config(&config, db_type).unwrap().unwrap()
+        };
+
+        let cases = [
+            (
+                DbType::Mysql,
+                "ALTER TABLE db_1.tb_1 CHANGE name name_2 varchar(100) COMMENT 'name'",
+                "ALTER TABLE `db_1`.`tb_1` CHANGE `user_name` name_2 varchar(100) COMMENT 'name'",
+            ),
+            (
+                DbType::Mysql,
+                "ALTER TABLE db_1.tb_1 RENAME COLUMN `name` TO `nick_name`, MODIFY `f 1` int DEFAULT 0",
+                "ALTER TABLE `db_1`.`tb_1` RENAME COLUMN `user_name` TO `nick_name`, MODIFY `dst f 1` int DEFAULT 0",
+            ),
+            // type names, keywords, literals and index names which equal a col are kept,
+            // mysql cols are matched case-insensitively
+            (
+                DbType::Mysql,
+                "ALTER TABLE db_1.tb_1 ADD COLUMN `INT` int DEFAULT 'name' AFTER NAME, ADD INDEX name (Name, `f 1`(10) DESC)",
+                "ALTER TABLE `db_1`.`tb_1` ADD COLUMN `int_2` int DEFAULT 'name' AFTER `user_name`, ADD INDEX name (`user_name`, `dst f 1`(10) DESC)",
+            ),
+            (
+                DbType::Mysql,
+                "ALTER TABLE db_1.tb_1 ADD CONSTRAINT name FOREIGN KEY (name) REFERENCES tb_2 (name), DROP INDEX name, DROP Int",
+                "ALTER TABLE `db_1`.`tb_1` ADD CONSTRAINT name FOREIGN KEY (`user_name`) REFERENCES `tb_2` (name), DROP INDEX name, DROP `int_2`",
+            ),
+            (
+                DbType::Pg,
+                r#"ALTER TABLE db_1.tb_1 ADD COLUMN Name int, ADD COLUMN "Name" int, ADD PRIMARY KEY (name)"#,
+                r#"ALTER TABLE "db_1"."tb_1" ADD COLUMN "user_name" int, ADD COLUMN "Name" int, ADD PRIMARY KEY ("user_name")"#,
+            ),
+            (
+                DbType::Pg,
+                r#"ALTER TABLE db_1.tb_1 RENAME COLUMN name TO nick_name"#,
+                r#"ALTER TABLE "db_1"."tb_1" RENAME COLUMN "user_name" TO nick_name"#,
+            ),
+            (
+                DbType::Pg,
+                r#"ALTER TABLE db_1.tb_1 ALTER COLUMN "f 1" SET DEFAULT 'name'"#,
+                r#"ALTER TABLE "db_1"."tb_1" ALTER COLUMN "dst f 1" SET DEFAULT 'name'"#,
+            ),
+        ];
+
+        for (db_type, sql, expect_sql) in cases {
+            let router = build_router(&db_type);
+            let ddl_data = DdlParser::new(db_type).parse(sql).unwrap().unwrap();
+            let ddl_data = router.route_ddl(ddl_data);
+            assert_eq!(ddl_data.to_sql(), expect_sql);
+        }
+
+        // ddls of other tables are not changed
+        let router = build_router(&DbType::Mysql);
+        let ddl_data = DdlParser::new(DbType::Mysql)
+            .parse("ALTER TABLE db_1.tb_2 DROP COLUMN name")
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            router.route_ddl(ddl_data).to_sql(),
+            "ALTER TABLE `db_1`.`tb_2` DROP COLUMN name"
+        );
+    }
+
     #[test]
     fn test_topic_only_router_does_not_enable_table_route() {
         let config = RouterConfig::Rdb {
@@ -657,6 +1025,7 @@ mod tests {
             tb_map: String::new(),
             col_map: String::new(),
             topic_map: "*.*:test".into(),
+            doc_projection: String::new(),
         };
         let router = RdbRouter::from_config(&config, &DbType::Mysql).unwrap();
 
@@ -704,4 +1073,44 @@ mod tests {
             RdbRouter::from_maps_for_test(db_map, HashMap::new(), HashMap::new(), HashMap::new());
         router.validate_redis_db_map(true).unwrap();
     }
+
+    #[test]
+    fn test_project_mongo_doc_row() {
+        let config = RouterConfig::Rdb {
+            schema_map: String::new(),
+            tb_map: "mongo_db.users:rdb_db.users".into(),
+            col_map: String::new(),
+            topic_map: String::new(),
+            doc_projection: r#"json:[{"db":"mongo_db","tb":"users","cols":{"id":"_id","city":"address.city","tags":"tags"},"array_policy":"join"}]"#.into(),
+        };
+        let router = RdbRouter::from_config(&config, &DbType::Mongo)
+            .unwrap()
+            .unwrap();
+
+        let mut after = HashMap::new();
+        after.insert(MongoConstants::ID.to_string(), ColValue::String("1".into()));
+        after.insert(
+            MongoConstants::DOC.to_string(),
+            ColValue::MongoDoc(
+                doc! {"_id": 1, "address": {"city": "hangzhou"}, "tags": ["a", "b"]},
+            ),
+        );
+        let row_data = RowData::new(
+            "mongo_db".into(),
+            "users".into(),
+            0,
+            RowType::Insert,
+            None,
+            Some(after),
+        );
+
+        let row_data = router.route_row(router.project_row(row_data).unwrap());
+        assert_eq!(row_data.schema, "rdb_db");
+        assert_eq!(row_data.tb, "users");
+        let after = row_data.after.unwrap();
+        assert_eq!(after.len(), 3);
+        assert_eq!(after["id"], ColValue::Long(1));
+        assert_eq!(after["city"], ColValue::String("hangzhou".into()));
+        assert_eq!(after["tags"], ColValue::String("a,b".into()));
+    }
 }
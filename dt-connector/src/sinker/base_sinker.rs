@@ -81,6 +81,11 @@ impl BaseSinker {
         Ok(())
     }
 
+    // per table counts of the written or failed rows, rows are routed so keyed by target tables
+    pub fn update_table_monitor(&self, rows: &[RowData], succeeded: bool) {
+        self.monitor.add_table_rows(rows, succeeded);
+    }
+
     pub async fn update_serial_monitor(
         &self,
         record_count: u64,
@@ -128,6 +133,31 @@ impl BaseSinker {
             .await;
         Ok(())
     }
+
+    pub async fn update_monitor_throttle_for(&self, task_id: &str, wait_ms: u64) {
+        self.monitor
+            .add_batch_counter(task_id, CounterType::ThrottleWait, wait_ms, 1)
+            .await;
+    }
+
+    pub async fn update_monitor_load_for(
+        &self,
+        task_id: &str,
+        loaded_records: u64,
+        filtered_records: u64,
+        load_time_ms: Option<u64>,
+    ) {
+        self.monitor
+            .add_counter(task_id, CounterType::LoadedRecords, loaded_records)
+            .await
+            .add_counter(task_id, CounterType::FilteredRecords, filtered_records)
+            .await;
+        if let Some(load_time_ms) = load_time_ms {
+            self.monitor
+                .add_batch_counter(task_id, CounterType::LoadTime, load_time_ms, 1)
+                .await;
+        }
+    }
 }
 
 #[macro_export(local_inner_macros)]
@@ -1,8 +1,8 @@
 use async_trait::async_trait;
 use dt_common::log_warn;
 use dt_common::meta::{
-    dcl_meta::dcl_data::DclData, ddl_meta::ddl_data::DdlData, dt_data::DtItem, row_data::RowData,
-    struct_meta::struct_data::StructData,
+    dcl_meta::dcl_data::DclData, ddl_meta::ddl_data::DdlData, dt_data::DtItem, position::Position,
+    row_data::RowData, struct_meta::struct_data::StructData,
 };
 
 use crate::{checker::DataCheckerHandle, Sinker};
@@ -78,6 +78,14 @@ impl<S: CheckableSink + Send> Sinker for SinkerWithChecker<S> {
         delegate_inner!(self, handle_control_item(item))
     }
 
+    async fn begin_dml_batch(&mut self, first: &Position, last: &Position) -> anyhow::Result<()> {
+        delegate_inner!(self, begin_dml_batch(first, last))
+    }
+
+    async fn sink_heartbeat(&mut self, position: &Position) -> anyhow::Result<()> {
+        delegate_inner!(self, sink_heartbeat(position))
+    }
+
     fn get_id(&self) -> String {
         self.inner.get_id()
     }
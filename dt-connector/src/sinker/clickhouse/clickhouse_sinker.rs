@@ -9,7 +9,10 @@ use tokio::time::Instant;
 use dt_common::{
     config::config_enums::DbType,
     error::Error,
-    meta::{col_value::ColValue, row_data::RowData, row_type::RowType},
+    meta::{
+        col_value::ColValue, row_data::RowData, row_type::RowType,
+        unsupported_col_value_handler::UnsupportedColValueHandler,
+    },
     utils::{limit_queue::LimitedQueue, sql_util::SqlUtil},
 };
 
@@ -28,6 +31,7 @@ pub struct ClickhouseSinker {
     pub password: String,
     pub base_sinker: BaseSinker,
     pub sync_timestamp: i64,
+    pub col_value_handler: UnsupportedColValueHandler,
 }
 
 #[async_trait]
@@ -53,7 +57,10 @@ impl ClickhouseSinker {
             .base_sinker
             .task_id_for_rows(&data[start_index..start_index + batch_size]);
         self.base_sinker.ensure_monitor_for(&task_id);
-        let data_size = self.send_data(data, start_index, batch_size).await?;
+        let result = self.send_data(data, start_index, batch_size).await;
+        self.base_sinker
+            .update_table_monitor(&data[start_index..start_index + batch_size], result.is_ok());
+        let data_size = result?;
         self.base_sinker
             .update_batch_monitor_for(&task_id, batch_size as u64, data_size as u64)
             .await
@@ -75,6 +82,7 @@ impl ClickhouseSinker {
         for row_data in data.iter_mut().skip(start_index).take(batch_size) {
             data_size += row_data.get_data_size() as usize;
             let is_delete = row_data.row_type == RowType::Delete;
+            self.col_value_handler.handle_row(row_data)?;
             Self::convert_row_data(row_data)?;
             let col_values = Self::active_col_values_mut(row_data)?;
 
@@ -225,6 +225,8 @@ impl ClickhouseStructSinker {
             MysqlColType::Set { items: _ } => "String",
             MysqlColType::Enum { items: _ } => "String",
             MysqlColType::Json => "String",
+            // geometry values are sent as WKT
+            MysqlColType::Geometry => "String",
             MysqlColType::Unknown => "String",
         };
         Ok(dst_col.to_string())
@@ -0,0 +1,447 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::PathBuf,
+};
+
+use anyhow::{bail, Context};
+use async_trait::async_trait;
+use base64::{engine::general_purpose, Engine as _};
+use tokio::time::Instant;
+
+use dt_common::{
+    error::Error,
+    log_info,
+    meta::{
+        col_value::ColValue, rdb_meta_manager::RdbMetaManager, row_data::RowData, row_type::RowType,
+    },
+    utils::{file_util::FileUtil, limit_queue::LimitedQueue},
+};
+
+use crate::{sinker::base_sinker::BaseSinker, Sinker};
+
+const SIGN_COL_NAME: &str = "_ape_dts_is_deleted";
+
+#[derive(Debug, Clone)]
+pub struct CsvOptions {
+    pub delimiter: char,
+    pub quote: char,
+    // quote every cell, otherwise only cells containing delimiter/quote/line breaks are quoted
+    pub quote_all: bool,
+    pub header: bool,
+    pub file_max_rows: usize,
+    // 0 means no limit
+    pub file_max_bytes: usize,
+    pub hard_delete: bool,
+    // written for NULL values without quotes, cells equal to it are always quoted,
+    // so with the default empty null_value, empty strings are written as ""
+    pub null_value: String,
+}
+
+/// Writes rows to csv files: {root_dir}/{schema}/{tb}/{sinker_id}_{file_seq}.csv,
+/// cells follow the col order of the source table, a new file is started once the current one
+/// reaches file_max_rows or file_max_bytes. After a restart, file_seq continues from the
+/// existing files so they are not overwritten.
+/// If hard_delete is off, a sign col _ape_dts_is_deleted is appended and deletes are written
+/// with their before values and sign 1, otherwise deletes are skipped.
+pub struct CsvSinker {
+    pub id: usize,
+    pub root_dir: String,
+    pub options: CsvOptions,
+    pub meta_manager: RdbMetaManager,
+    pub base_sinker: BaseSinker,
+    pub writers: HashMap<(String, String), CsvTableWriter>,
+}
+
+pub struct CsvTableWriter {
+    cols: Vec<String>,
+    dir: PathBuf,
+    sinker_id: usize,
+    writer: Option<BufWriter<File>>,
+    file_rows: usize,
+    file_bytes: usize,
+    file_seq: usize,
+}
+
+#[async_trait]
+impl Sinker for CsvSinker {
+    async fn sink_dml(&mut self, mut data: Vec<RowData>, _batch: bool) -> anyhow::Result<()> {
+        if data.is_empty() {
+            return Ok(());
+        }
+
+        let task_id = self.base_sinker.task_id_for_rows(&data);
+        self.base_sinker.ensure_monitor_for(&task_id);
+        let data_size: u64 = data.iter().map(|r| r.get_data_size()).sum();
+        let start_time = Instant::now();
+        let mut rts = LimitedQueue::new(1);
+
+        let result = self.write_rows(&mut data).await;
+        self.base_sinker.update_table_monitor(&data, result.is_ok());
+        result?;
+        rts.push((start_time.elapsed().as_millis() as u64, 1));
+
+        self.base_sinker
+            .update_batch_monitor_for(&task_id, data.len() as u64, data_size)
+            .await?;
+        self.base_sinker.update_monitor_rt_for(&task_id, &rts).await
+    }
+
+    async fn close(&mut self) -> anyhow::Result<()> {
+        for (_, writer) in self.writers.iter_mut() {
+            writer.close_file()?;
+        }
+        self.meta_manager.close().await
+    }
+}
+
+impl CsvSinker {
+    async fn write_rows(&mut self, data: &mut [RowData]) -> anyhow::Result<()> {
+        for row_data in data.iter_mut() {
+            row_data.convert_raw_string();
+            self.write_row(row_data).await?;
+        }
+        for writer in self.writers.values_mut() {
+            writer.flush()?;
+        }
+        Ok(())
+    }
+
+    async fn write_row(&mut self, row_data: &RowData) -> anyhow::Result<()> {
+        let (col_values, sign) = match row_data.row_type {
+            RowType::Delete if self.options.hard_delete => return Ok(()),
+            RowType::Delete => (row_data.require_before()?, 1),
+            _ => (row_data.require_after()?, 0),
+        };
+
+        let key = (row_data.schema.clone(), row_data.tb.clone());
+        if !self.writers.contains_key(&key) {
+            let tb_meta = self
+                .meta_manager
+                .get_tb_meta(&row_data.schema, &row_data.tb)
+                .await?;
+            let dir = PathBuf::from(&self.root_dir).join(&key.0).join(&key.1);
+            let writer = CsvTableWriter::new(tb_meta.cols.clone(), dir, self.id)?;
+            self.writers.insert(key.clone(), writer);
+        }
+        let writer = self.writers.get_mut(&key).unwrap();
+
+        if let Some(col) = col_values.keys().find(|col| !writer.cols.contains(col)) {
+            bail! {Error::SinkerError(format!(
+                "col: {} of {}.{} not found in table meta",
+                col, row_data.schema, row_data.tb
+            ))}
+        }
+
+        let mut cells: Vec<Option<String>> = writer
+            .cols
+            .iter()
+            .map(|col| col_values.get(col).and_then(Self::to_cell))
+            .collect();
+        if !self.options.hard_delete {
+            cells.push(Some(sign.to_string()));
+        }
+        let line = Self::build_line(&cells, &self.options);
+        writer.write(&line, &self.options)
+    }
+
+    fn to_cell(col_value: &ColValue) -> Option<String> {
+        match col_value {
+            ColValue::Blob(v) => Some(general_purpose::STANDARD.encode(v)),
+            ColValue::Json(v) => Some(String::from_utf8_lossy(v).to_string()),
+            _ => col_value.to_option_string(),
+        }
+    }
+
+    /// NULL values are written as null_value without quotes, empty cells by default.
+    pub fn build_line(cells: &[Option<String>], options: &CsvOptions) -> String {
+        let quote = options.quote.to_string();
+        let escaped_quote = quote.repeat(2);
+        let mut line = String::new();
+        for (i, cell) in cells.iter().enumerate() {
+            if i > 0 {
+                line.push(options.delimiter);
+            }
+            let Some(cell) = cell else {
+                line.push_str(&options.null_value);
+                continue;
+            };
+            let need_quote = options.quote_all
+                || *cell == options.null_value
+                || cell.contains(options.delimiter)
+                || cell.contains(options.quote)
+                || cell.contains(['\r', '\n']);
+            if need_quote {
+                line.push_str(&quote);
+                line.push_str(&cell.replace(&quote, &escaped_quote));
+                line.push_str(&quote);
+            } else {
+                line.push_str(cell);
+            }
+        }
+        line.push('\n');
+        line
+    }
+}
+
+impl CsvTableWriter {
+    fn new(cols: Vec<String>, dir: PathBuf, sinker_id: usize) -> anyhow::Result<Self> {
+        let file_seq = FileUtil::next_file_seq(&dir, &format!("{}_", sinker_id), "csv")?;
+        Ok(Self {
+            cols,
+            dir,
+            sinker_id,
+            writer: None,
+            file_rows: 0,
+            file_bytes: 0,
+            file_seq,
+        })
+    }
+
+    fn write(&mut self, line: &str, options: &CsvOptions) -> anyhow::Result<()> {
+        let reach_max_bytes =
+            options.file_max_bytes > 0 && self.file_bytes >= options.file_max_bytes;
+        if self.writer.is_some() && (self.file_rows >= options.file_max_rows || reach_max_bytes) {
+            self.close_file()?;
+        }
+        if self.writer.is_none() {
+            self.open_file(options)?;
+        }
+
+        self.writer.as_mut().unwrap().write_all(line.as_bytes())?;
+        self.file_rows += 1;
+        self.file_bytes += line.len();
+        Ok(())
+    }
+
+    fn open_file(&mut self, options: &CsvOptions) -> anyhow::Result<()> {
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("failed to create dir: {}", self.dir.display()))?;
+        let path = self
+            .dir
+            .join(format!("{}_{}.csv", self.sinker_id, self.file_seq));
+        let file = File::create(&path)
+            .with_context(|| format!("failed to create csv file: {}", path.display()))?;
+
+        log_info!("writing csv file: {}", path.display());
+        let mut writer = BufWriter::new(file);
+        self.file_rows = 0;
+        self.file_bytes = 0;
+        self.file_seq += 1;
+        if options.header {
+            let mut header: Vec<Option<String>> =
+                self.cols.iter().map(|col| Some(col.clone())).collect();
+            if !options.hard_delete {
+                header.push(Some(SIGN_COL_NAME.to_string()));
+            }
+            let line = CsvSinker::build_line(&header, options);
+            writer.write_all(line.as_bytes())?;
+            self.file_bytes += line.len();
+        }
+        self.writer = Some(writer);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> anyhow::Result<()> {
+        if let Some(writer) = self.writer.as_mut() {
+            writer.flush()?;
+        }
+        Ok(())
+    }
+
+    fn close_file(&mut self) -> anyhow::Result<()> {
+        if let Some(mut writer) = self.writer.take() {
+            writer.flush()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::Path, time::SystemTime};
+
+    use dt_common::{
+        config::config_enums::DbType,
+        meta::{
+            mysql::{
+                mysql_meta_fetcher::MysqlMetaFetcher, mysql_meta_manager::MysqlMetaManager,
+                mysql_tb_meta::MysqlTbMeta,
+            },
+            rdb_tb_meta::RdbTbMeta,
+        },
+    };
+    use sqlx::mysql::MySqlPoolOptions;
+
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "ape-dts-csv-sinker-{}",
+            SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ))
+    }
+
+    // the meta of db_1.tb_1 is cached, so the pool is never connected
+    fn build_sinker(root_dir: &Path) -> CsvSinker {
+        let tb_meta = MysqlTbMeta {
+            basic: RdbTbMeta {
+                schema: "db_1".into(),
+                tb: "tb_1".into(),
+                cols: vec!["id".into(), "name".into()],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let meta_fetcher = MysqlMetaFetcher {
+            conn_pool: MySqlPoolOptions::new()
+                .connect_lazy("mysql://127.0.0.1:3306")
+                .unwrap(),
+            cache: HashMap::from([("db_1.tb_1".to_string(), tb_meta)]),
+            version: String::new(),
+            db_type: DbType::Mysql,
+        };
+        let meta_manager = MysqlMetaManager {
+            meta_center: None,
+            meta_fetcher,
+        };
+        CsvSinker {
+            id: 0,
+            root_dir: root_dir.to_str().unwrap().into(),
+            options: build_options(),
+            meta_manager: RdbMetaManager::from_mysql(meta_manager),
+            base_sinker: BaseSinker::default(),
+            writers: HashMap::new(),
+        }
+    }
+
+    fn build_row(row_type: RowType, id: i64, name: Option<&str>) -> RowData {
+        let col_values = HashMap::from([
+            ("id".to_string(), ColValue::LongLong(id)),
+            (
+                "name".to_string(),
+                name.map_or(ColValue::None, |v| ColValue::String(v.into())),
+            ),
+        ]);
+        let (before, after) = match row_type {
+            RowType::Insert => (None, Some(col_values)),
+            RowType::Delete => (Some(col_values), None),
+            _ => (Some(col_values.clone()), Some(col_values)),
+        };
+        RowData::new("db_1".into(), "tb_1".into(), 0, row_type, before, after)
+    }
+
+    fn build_options() -> CsvOptions {
+        CsvOptions {
+            delimiter: ',',
+            quote: '"',
+            quote_all: false,
+            header: true,
+            file_max_rows: 2,
+            file_max_bytes: 0,
+            hard_delete: false,
+            null_value: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_build_line() {
+        let cells = vec![
+            CsvSinker::to_cell(&ColValue::Long(1)),
+            CsvSinker::to_cell(&ColValue::None),
+            CsvSinker::to_cell(&ColValue::String("a,\"b\"\nc".into())),
+            CsvSinker::to_cell(&ColValue::Decimal("12.30".into())),
+            CsvSinker::to_cell(&ColValue::DateTime("2024-01-01 00:00:00".into())),
+            CsvSinker::to_cell(&ColValue::Blob(vec![1, 2, 3])),
+            CsvSinker::to_cell(&ColValue::String(String::new())),
+        ];
+        let mut options = build_options();
+        assert_eq!(
+            CsvSinker::build_line(&cells, &options),
+            "1,,\"a,\"\"b\"\"\nc\",12.30,2024-01-01 00:00:00,AQID,\"\"\n"
+        );
+
+        // explicit NULL marker
+        options.null_value = "\\N".into();
+        assert_eq!(
+            CsvSinker::build_line(&[cells[1].clone(), cells[6].clone()], &options),
+            "\\N,\n"
+        );
+        assert_eq!(
+            CsvSinker::build_line(&[Some("\\N".into())], &options),
+            "\"\\N\"\n"
+        );
+        options.null_value = String::new();
+
+        options.delimiter = '\t';
+        options.quote = '\'';
+        options.quote_all = true;
+        assert_eq!(
+            CsvSinker::build_line(&cells[..4], &options),
+            "'1'\t\t'a,\"b\"\nc'\t'12.30'\n"
+        );
+    }
+
+    #[test]
+    fn test_write_rotating_files() {
+        let dir = temp_dir();
+        let mut options = build_options();
+        let mut writer =
+            CsvTableWriter::new(vec!["id".into(), "name".into()], dir.clone(), 0).unwrap();
+        for line in ["1,a,0\n", "2,b,0\n", "1,a,1\n"] {
+            writer.write(line, &options).unwrap();
+        }
+        // rotate by bytes, the header takes 28 bytes
+        options.file_max_rows = 100;
+        options.file_max_bytes = 40;
+        for line in ["3,c,0\n", "4,d,0\n"] {
+            writer.write(line, &options).unwrap();
+        }
+        writer.close_file().unwrap();
+
+        let read = |name: &str| fs::read_to_string(dir.join(name)).unwrap();
+        let header = "id,name,_ape_dts_is_deleted\n";
+        assert_eq!(read("0_0.csv"), format!("{header}1,a,0\n2,b,0\n"));
+        assert_eq!(read("0_1.csv"), format!("{header}1,a,1\n3,c,0\n"));
+        assert_eq!(read("0_2.csv"), format!("{header}4,d,0\n"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_sink_dml() {
+        let dir = temp_dir();
+        let mut sinker = build_sinker(&dir);
+        let rows = vec![
+            build_row(RowType::Insert, 1, Some("")),
+            build_row(RowType::Insert, 2, None),
+            build_row(RowType::Update, 1, Some("a,b")),
+            build_row(RowType::Delete, 2, None),
+        ];
+        sinker.sink_dml(rows, true).await.unwrap();
+        sinker.close().await.unwrap();
+
+        let tb_dir = dir.join("db_1").join("tb_1");
+        let read = |name: &str| fs::read_to_string(tb_dir.join(name)).unwrap();
+        let header = "id,name,_ape_dts_is_deleted\n";
+        // empty string is quoted while NULL is not
+        assert_eq!(read("0_0.csv"), format!("{header}1,\"\",0\n2,,0\n"));
+        assert_eq!(read("0_1.csv"), format!("{header}1,\"a,b\",0\n2,,1\n"));
+
+        // files written before restart are kept
+        let mut sinker = build_sinker(&dir);
+        sinker
+            .sink_dml(vec![build_row(RowType::Insert, 3, Some("c"))], true)
+            .await
+            .unwrap();
+        sinker.close().await.unwrap();
+        assert_eq!(read("0_0.csv"), format!("{header}1,\"\",0\n2,,0\n"));
+        assert_eq!(read("0_2.csv"), format!("{header}3,c,0\n"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
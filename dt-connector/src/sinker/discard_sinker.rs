@@ -0,0 +1,125 @@
+use async_trait::async_trait;
+use dt_common::{
+    meta::{ddl_meta::ddl_data::DdlData, dt_data::DtItem, row_data::RowData},
+    utils::time_util::TimeUtil,
+};
+
+use crate::{sinker::base_sinker::BaseSinker, Sinker};
+
+// drops everything it receives, only the monitor is updated,
+// used to benchmark extractors and pipelines without a real target
+pub struct DiscardSinker {
+    pub batch_delay_ms: u64,
+    pub base_sinker: BaseSinker,
+}
+
+#[async_trait]
+impl Sinker for DiscardSinker {
+    async fn sink_dml(&mut self, data: Vec<RowData>, _batch: bool) -> anyhow::Result<()> {
+        if data.is_empty() {
+            return Ok(());
+        }
+
+        let data_size = data.iter().map(|row_data| row_data.get_data_size()).sum();
+        self.discard(data.len() as u64, data_size).await
+    }
+
+    async fn sink_ddl(&mut self, _data: Vec<DdlData>, _batch: bool) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    async fn sink_raw(&mut self, data: Vec<DtItem>, _batch: bool) -> anyhow::Result<()> {
+        if data.is_empty() {
+            return Ok(());
+        }
+
+        let data_size = data.iter().map(|item| item.get_data_size()).sum();
+        self.discard(data.len() as u64, data_size).await
+    }
+}
+
+impl DiscardSinker {
+    async fn discard(&self, batch_size: u64, data_size: u64) -> anyhow::Result<()> {
+        if self.batch_delay_ms > 0 {
+            TimeUtil::sleep_millis(self.batch_delay_ms).await;
+        }
+        self.base_sinker
+            .update_batch_monitor(batch_size, data_size)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, sync::Arc};
+
+    use dt_common::{
+        config::config_enums::{TaskKind, TaskType},
+        meta::{col_value::ColValue, row_type::RowType},
+        monitor::{
+            counter_type::CounterType,
+            monitor::Monitor,
+            task_monitor::{MonitorType, TaskMonitor},
+            task_monitor_handle::TaskMonitorHandle,
+        },
+    };
+
+    use super::*;
+
+    fn build_row(id: i32) -> RowData {
+        let after = HashMap::from([("id".to_string(), ColValue::Long(id))]);
+        RowData::new(
+            "db_1".into(),
+            "tb_1".into(),
+            0,
+            RowType::Insert,
+            None,
+            Some(after),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_rows_are_counted_but_discarded() {
+        let task_id = "discard_task";
+        let task_monitor = Arc::new(TaskMonitor::new(Some(TaskType::new(
+            TaskKind::Snapshot,
+            None,
+        ))));
+        let monitor = Arc::new(Monitor::new("sinker", "discard", 60, 100, 100));
+        task_monitor.register(task_id, vec![(MonitorType::Sinker, monitor.clone())]);
+        let handle = TaskMonitorHandle::new(
+            task_monitor,
+            MonitorType::Sinker,
+            task_id.into(),
+            60,
+            100,
+            100,
+        );
+
+        let mut sinker = DiscardSinker {
+            batch_delay_ms: 0,
+            base_sinker: BaseSinker::new(handle, 10),
+        };
+        let rows: Vec<RowData> = (0..3).map(build_row).collect();
+        let data_size: u64 = rows.iter().map(|row_data| row_data.get_data_size()).sum();
+        sinker.sink_dml(rows, true).await.unwrap();
+        sinker.sink_dml(vec![build_row(3)], false).await.unwrap();
+
+        let record_count = monitor
+            .time_window_counters
+            .get(&CounterType::RecordCount)
+            .map(|entry| entry.value().clone())
+            .unwrap();
+        assert_eq!(record_count.statistics().await.sum, 4);
+
+        let data_bytes = monitor
+            .time_window_counters
+            .get(&CounterType::DataBytes)
+            .map(|entry| entry.value().clone())
+            .unwrap();
+        assert_eq!(
+            data_bytes.statistics().await.sum,
+            data_size + build_row(3).get_data_size()
+        );
+    }
+}
@@ -343,7 +343,8 @@ impl FoxlakePusher {
                         match Self::get_col_value(row_data, col)? {
                             Some(ColValue::Json(v))
                             | Some(ColValue::Blob(v))
-                            | Some(ColValue::RawString(v)) => field_data.write(v),
+                            | Some(ColValue::RawString(v))
+                            | Some(ColValue::Geometry(v)) => field_data.write(v),
 
                             Some(ColValue::Bit(v)) => {
                                 let bit = Self::u64_to_bytes(*v);
@@ -444,6 +445,7 @@ impl FoxlakePusher {
             | MysqlColType::MediumBlob
             | MysqlColType::LongBlob
             | MysqlColType::Blob
+            | MysqlColType::Geometry
             | MysqlColType::Unknown => Schema::Binary,
 
             MysqlColType::Char { .. }
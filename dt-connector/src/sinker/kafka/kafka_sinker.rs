@@ -1,18 +1,28 @@
+use std::{collections::HashMap, time::Duration};
+
 use async_trait::async_trait;
+use chrono::Utc;
 use kafka::producer::{Producer, Record};
+use serde_json::json;
 use tokio::time::Instant;
 
 use dt_common::{
-    config::message_format::MessageFormat,
+    config::{message_format::MessageFormat, topic_strategy::TopicStrategy},
+    error::Error,
     meta::{
-        avro::avro_converter::AvroConverter, ddl_meta::ddl_data::DdlData,
-        json::json_converter::JsonConverter, row_data::RowData,
+        avro::avro_converter::AvroConverter, col_projection::ColProjection,
+        ddl_meta::ddl_data::DdlData, json::json_converter::JsonConverter, position::Position,
+        protobuf::proto_converter::ProtoConverter, row_data::RowData,
+        unsupported_col_value_handler::UnsupportedColValueHandler,
     },
     utils::limit_queue::LimitedQueue,
 };
 
 use crate::{call_batch_fn, rdb_router::RdbRouter, sinker::base_sinker::BaseSinker, Sinker};
 
+// HashMap<(src_schema, src_tb), src_key_cols>
+pub type KeyCols = HashMap<(String, String), Vec<String>>;
+
 pub struct KafkaSinker {
     pub batch_size: usize,
     pub router: RdbRouter,
@@ -20,9 +30,58 @@ pub struct KafkaSinker {
     pub avro_converter: AvroConverter,
     pub json_converter: JsonConverter,
     pub message_format: MessageFormat,
+    // decides the topic of each message, the message key is still built per row
+    pub topic_strategy: TopicStrategy,
+    pub col_value_handler: UnsupportedColValueHandler,
+    // removes cols from payloads only, message keys are built before projection
+    pub col_projection: ColProjection,
+    // message keys of these tables are built from the configured cols instead of the primary key,
+    // rows with the same key col values go to the same partition and keep their order
+    pub key_cols: KeyCols,
+    // pack the rows of a batch into one newline-delimited json message per topic
+    pub batch_payload: bool,
+    pub heartbeat: Option<KafkaHeartbeat>,
     pub base_sinker: BaseSinker,
 }
 
+/// Heartbeats carry the latest source position to a separate topic,
+/// so consumers can tell the task is alive and advance watermarks while no data flows.
+pub struct KafkaHeartbeat {
+    pub topic: String,
+    pub interval: Duration,
+    pub last_send_time: Instant,
+}
+
+impl KafkaHeartbeat {
+    pub fn new(topic: &str, interval: Duration) -> Self {
+        Self {
+            topic: topic.to_string(),
+            interval,
+            last_send_time: Instant::now(),
+        }
+    }
+
+    /// Returns the heartbeat payload if the interval has elapsed since the last one.
+    pub fn poll(&mut self, position: &Position) -> Option<String> {
+        if self.last_send_time.elapsed() < self.interval {
+            return None;
+        }
+        self.last_send_time = Instant::now();
+        Some(Self::build_payload(position))
+    }
+
+    fn build_payload(position: &Position) -> String {
+        json!({
+            "type": "heartbeat",
+            "position": position,
+            // millis, 0 if the position has no timestamp
+            "position_timestamp": position.to_timestamp(),
+            "heartbeat_timestamp": Utc::now().timestamp_millis(),
+        })
+        .to_string()
+    }
+}
+
 #[async_trait]
 impl Sinker for KafkaSinker {
     async fn sink_dml(&mut self, mut data: Vec<RowData>, _batch: bool) -> anyhow::Result<()> {
@@ -34,20 +93,34 @@ impl Sinker for KafkaSinker {
             MessageFormat::Avro => {
                 call_batch_fn!(self, data, Self::send_avro);
             }
-            MessageFormat::Json | MessageFormat::JsonTemplate(_) => {
-                call_batch_fn!(self, data, Self::send_json);
+            MessageFormat::Protobuf => {
+                call_batch_fn!(self, data, Self::send_proto);
+            }
+            MessageFormat::Json | MessageFormat::JsonTemplate { .. } => {
+                // the drained data shares one bid, though call_batch_fn sends it in chunks
+                self.json_converter.next_batch();
+                if self.batch_payload {
+                    call_batch_fn!(self, data, Self::send_json_batched);
+                } else {
+                    call_batch_fn!(self, data, Self::send_json);
+                }
             }
         };
         Ok(())
     }
 
     async fn sink_ddl(&mut self, data: Vec<DdlData>, _batch: bool) -> anyhow::Result<()> {
+        let topics: Vec<String> = data
+            .iter()
+            .map(|ddl_data| self.get_topic(&ddl_data.default_schema, ""))
+            .collect();
+        self.json_converter.next_batch();
         let mut messages = Vec::new();
-        for ddl_data in data {
-            let topic = self.router.get_topic(&ddl_data.default_schema, "");
+        for (ddl_data, topic) in data.into_iter().zip(topics.iter()) {
             let payload = match &self.message_format {
                 MessageFormat::Avro => self.avro_converter.ddl_data_to_avro_value(ddl_data).await?,
-                MessageFormat::Json | MessageFormat::JsonTemplate(_) => self
+                MessageFormat::Protobuf => ProtoConverter::ddl_data_to_proto_value(ddl_data),
+                MessageFormat::Json | MessageFormat::JsonTemplate { .. } => self
                     .json_converter
                     .ddl_data_to_json_value(ddl_data)
                     .await?
@@ -69,6 +142,29 @@ impl Sinker for KafkaSinker {
         self.json_converter.refresh_meta(&data);
         Ok(())
     }
+
+    async fn sink_heartbeat(&mut self, position: &Position) -> anyhow::Result<()> {
+        let Some(heartbeat) = &mut self.heartbeat else {
+            return Ok(());
+        };
+        let Some(payload) = heartbeat.poll(position) else {
+            return Ok(());
+        };
+        // heartbeats have no key, they never share topics with data
+        let record = Record {
+            key: String::new(),
+            value: payload.into_bytes(),
+            topic: heartbeat.topic.as_str(),
+            partition: -1,
+        };
+        self.producer.send(&record).map_err(|err| {
+            Error::HeartbeatError(format!(
+                "failed to send heartbeat to topic: {}, error: {}",
+                heartbeat.topic, err
+            ))
+        })?;
+        Ok(())
+    }
 }
 
 impl KafkaSinker {
@@ -85,11 +181,22 @@ impl KafkaSinker {
         let mut data_size = 0;
 
         let mut messages = Vec::new();
-        for row_data in data.iter_mut().skip(sinked_count).take(batch_size) {
+        let topics = self.get_topics(&data[sinked_count..sinked_count + batch_size]);
+        for (row_data, topic) in data
+            .iter_mut()
+            .skip(sinked_count)
+            .take(batch_size)
+            .zip(topics.iter())
+        {
             data_size += row_data.get_data_size();
             row_data.convert_raw_string();
-            let topic = self.router.get_topic(&row_data.schema, &row_data.tb);
-            let key = self.avro_converter.row_data_to_avro_key(row_data).await?;
+            self.col_value_handler.handle_row(row_data)?;
+            let key_row = self.router.reverse_route_row_for_key(row_data);
+            let key = match Self::get_key_cols(&self.key_cols, &key_row) {
+                Some(key_cols) => AvroConverter::get_avro_key_by_cols(key_cols, &key_row)?,
+                None => self.avro_converter.row_data_to_avro_key(&key_row).await?,
+            };
+            self.col_projection.project_row(row_data, &[]);
             let payload = self.avro_converter.row_data_to_avro_value(row_data).await?;
             messages.push(Record {
                 key,
@@ -104,7 +211,70 @@ impl KafkaSinker {
         //       making it impossible to see individual broker RT. This can be optimized in the future.
         let start_time = Instant::now();
         let mut rts = LimitedQueue::new(1);
-        self.producer.send_all(&messages)?;
+        let result = self.producer.send_all(&messages);
+        self.base_sinker.update_table_monitor(
+            &data[sinked_count..sinked_count + batch_size],
+            result.is_ok(),
+        );
+        result?;
+        rts.push((
+            start_time.elapsed().as_millis() as u64,
+            messages.len() as u64,
+        ));
+
+        self.base_sinker
+            .update_batch_monitor_for(&task_id, batch_size as u64, data_size)
+            .await?;
+        self.base_sinker.update_monitor_rt_for(&task_id, &rts).await
+    }
+
+    // message keys are the same as avro messages
+    async fn send_proto(
+        &mut self,
+        data: &mut [RowData],
+        sinked_count: usize,
+        batch_size: usize,
+    ) -> anyhow::Result<()> {
+        let task_id = self
+            .base_sinker
+            .task_id_for_rows(&data[sinked_count..sinked_count + batch_size]);
+        self.base_sinker.ensure_monitor_for(&task_id);
+        let mut data_size = 0;
+
+        let mut messages = Vec::new();
+        let topics = self.get_topics(&data[sinked_count..sinked_count + batch_size]);
+        for (row_data, topic) in data
+            .iter_mut()
+            .skip(sinked_count)
+            .take(batch_size)
+            .zip(topics.iter())
+        {
+            data_size += row_data.get_data_size();
+            row_data.convert_raw_string();
+            self.col_value_handler.handle_row(row_data)?;
+            let key_row = self.router.reverse_route_row_for_key(row_data);
+            let key = match Self::get_key_cols(&self.key_cols, &key_row) {
+                Some(key_cols) => AvroConverter::get_avro_key_by_cols(key_cols, &key_row)?,
+                None => self.avro_converter.row_data_to_avro_key(&key_row).await?,
+            };
+            self.col_projection.project_row(row_data, &[]);
+            let payload = ProtoConverter::row_data_to_proto_value(row_data);
+            messages.push(Record {
+                key,
+                value: payload,
+                topic,
+                partition: -1,
+            });
+        }
+
+        let start_time = Instant::now();
+        let mut rts = LimitedQueue::new(1);
+        let result = self.producer.send_all(&messages);
+        self.base_sinker.update_table_monitor(
+            &data[sinked_count..sinked_count + batch_size],
+            result.is_ok(),
+        );
+        result?;
         rts.push((
             start_time.elapsed().as_millis() as u64,
             messages.len() as u64,
@@ -129,11 +299,22 @@ impl KafkaSinker {
         let mut data_size = 0;
 
         let mut messages = Vec::new();
-        for row_data in data.iter_mut().skip(sinked_count).take(batch_size) {
+        let topics = self.get_topics(&data[sinked_count..sinked_count + batch_size]);
+        for (row_data, topic) in data
+            .iter_mut()
+            .skip(sinked_count)
+            .take(batch_size)
+            .zip(topics.iter())
+        {
             data_size += row_data.get_data_size();
             row_data.convert_raw_string();
-            let topic = self.router.get_topic(&row_data.schema, &row_data.tb);
-            let key = self.json_converter.row_data_to_json_key(row_data).await?;
+            self.col_value_handler.handle_row(row_data)?;
+            let key_row = self.router.reverse_route_row_for_key(row_data);
+            let key = match Self::get_key_cols(&self.key_cols, &key_row) {
+                Some(key_cols) => JsonConverter::get_json_key_by_cols(key_cols, &key_row)?,
+                None => self.json_converter.row_data_to_json_key(&key_row).await?,
+            };
+            self.col_projection.project_row(row_data, &[]);
             let payload = self
                 .json_converter
                 .row_data_to_json_value(row_data.clone())
@@ -149,7 +330,12 @@ impl KafkaSinker {
 
         let start_time = Instant::now();
         let mut rts = LimitedQueue::new(1);
-        self.producer.send_all(&messages)?;
+        let result = self.producer.send_all(&messages);
+        self.base_sinker.update_table_monitor(
+            &data[sinked_count..sinked_count + batch_size],
+            result.is_ok(),
+        );
+        result?;
         rts.push((
             start_time.elapsed().as_millis() as u64,
             messages.len() as u64,
@@ -160,4 +346,340 @@ impl KafkaSinker {
             .await?;
         self.base_sinker.update_monitor_rt_for(&task_id, &rts).await
     }
+
+    async fn send_json_batched(
+        &mut self,
+        data: &mut [RowData],
+        sinked_count: usize,
+        batch_size: usize,
+    ) -> anyhow::Result<()> {
+        let task_id = self
+            .base_sinker
+            .task_id_for_rows(&data[sinked_count..sinked_count + batch_size]);
+        self.base_sinker.ensure_monitor_for(&task_id);
+        let mut data_size = 0;
+
+        // Vec<(topic, key of the first row, payloads)>, in the order topics first appear
+        let mut batches: Vec<(&str, String, Vec<String>)> = Vec::new();
+        let topics = self.get_topics(&data[sinked_count..sinked_count + batch_size]);
+        for (row_data, topic) in data
+            .iter_mut()
+            .skip(sinked_count)
+            .take(batch_size)
+            .zip(topics.iter())
+        {
+            data_size += row_data.get_data_size();
+            row_data.convert_raw_string();
+            self.col_value_handler.handle_row(row_data)?;
+            let mut payload_row = row_data.clone();
+            self.col_projection.project_row(&mut payload_row, &[]);
+            let payload = self
+                .json_converter
+                .row_data_to_json_value(payload_row)
+                .await?;
+
+            if let Some((_, _, payloads)) =
+                batches.iter_mut().find(|(t, _, _)| *t == topic.as_str())
+            {
+                payloads.push(payload);
+                continue;
+            }
+            let key_row = self.router.reverse_route_row_for_key(row_data);
+            let key = match Self::get_key_cols(&self.key_cols, &key_row) {
+                Some(key_cols) => JsonConverter::get_json_key_by_cols(key_cols, &key_row)?,
+                None => self.json_converter.row_data_to_json_key(&key_row).await?,
+            };
+            batches.push((topic.as_str(), key, vec![payload]));
+        }
+
+        let messages = Self::build_batched_records(batches);
+        let start_time = Instant::now();
+        let mut rts = LimitedQueue::new(1);
+        if !messages.is_empty() {
+            let result = self.producer.send_all(&messages);
+            self.base_sinker.update_table_monitor(
+                &data[sinked_count..sinked_count + batch_size],
+                result.is_ok(),
+            );
+            result?;
+            rts.push((
+                start_time.elapsed().as_millis() as u64,
+                messages.len() as u64,
+            ));
+        }
+
+        self.base_sinker
+            .update_batch_monitor_for(&task_id, batch_size as u64, data_size)
+            .await?;
+        self.base_sinker.update_monitor_rt_for(&task_id, &rts).await
+    }
+
+    fn build_batched_records<'a>(
+        batches: Vec<(&'a str, String, Vec<String>)>,
+    ) -> Vec<Record<'a, String, Vec<u8>>> {
+        batches
+            .into_iter()
+            .filter(|(_, _, payloads)| !payloads.is_empty())
+            .map(|(topic, key, payloads)| Record {
+                key,
+                value: payloads.join("\n").into_bytes(),
+                topic,
+                partition: -1,
+            })
+            .collect()
+    }
+
+    fn get_topics(&self, data: &[RowData]) -> Vec<String> {
+        data.iter()
+            .map(|row_data| self.get_topic(&row_data.schema, &row_data.tb))
+            .collect()
+    }
+
+    fn get_topic(&self, schema: &str, tb: &str) -> String {
+        Self::get_topic_by_strategy(&self.topic_strategy, &self.router, schema, tb)
+    }
+
+    fn get_topic_by_strategy(
+        topic_strategy: &TopicStrategy,
+        router: &RdbRouter,
+        schema: &str,
+        tb: &str,
+    ) -> String {
+        match topic_strategy {
+            TopicStrategy::TopicMap => router.get_topic(schema, tb).to_string(),
+            // ddl without a table goes to the topic named by its schema
+            TopicStrategy::PerTable if tb.is_empty() => schema.to_string(),
+            TopicStrategy::PerTable => format!("{}.{}", schema, tb),
+            TopicStrategy::PerSchema => schema.to_string(),
+            TopicStrategy::Single(name) => name.clone(),
+        }
+    }
+
+    fn get_key_cols<'a>(key_cols: &'a KeyCols, row_data: &RowData) -> Option<&'a Vec<String>> {
+        if key_cols.is_empty() {
+            return None;
+        }
+        key_cols.get(&(row_data.schema.clone(), row_data.tb.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use dt_common::{
+        config::{config_enums::DbType, router_config::RouterConfig},
+        meta::{col_value::ColValue, rdb_tb_meta::RdbTbMeta, row_type::RowType},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_key_of_renamed_cols() {
+        let config = RouterConfig::Rdb {
+            schema_map: String::new(),
+            tb_map: "db_1.tb_1:db_2.tb_2".into(),
+            col_map:
+                r#"json:[{"db":"db_1","tb":"tb_1","col_map":{"id":"user_id","name":"user_name"}}]"#
+                    .into(),
+            topic_map: "*.*:test".into(),
+            doc_projection: String::new(),
+        };
+        let router = RdbRouter::from_config_for_topic(&config, &DbType::Mysql).unwrap();
+
+        // source meta, the pk col id is renamed to user_id, the non-pk col name to user_name
+        let tb_meta = RdbTbMeta {
+            schema: "db_1".into(),
+            tb: "tb_1".into(),
+            cols: vec!["id".into(), "name".into(), "age".into()],
+            key_map: HashMap::from([("primary".into(), vec!["id".into()])]),
+            order_cols: vec!["id".into()],
+            id_cols: vec!["id".into()],
+            ..Default::default()
+        };
+
+        let after = HashMap::from([
+            ("id".to_string(), ColValue::Long(1)),
+            ("name".to_string(), ColValue::String("a".into())),
+            ("age".to_string(), ColValue::Long(10)),
+        ]);
+        let row_data = RowData::new(
+            "db_1".into(),
+            "tb_1".into(),
+            0,
+            RowType::Insert,
+            None,
+            Some(after),
+        );
+        let row_data = router.route_row(row_data);
+        let after = row_data.after.as_ref().unwrap();
+        assert_eq!(after["user_id"], ColValue::Long(1));
+        assert_eq!(after["user_name"], ColValue::String("a".into()));
+        assert!(!after.contains_key("id") && !after.contains_key("name"));
+
+        let key_row = router.reverse_route_row_for_key(&row_data);
+        assert_eq!(
+            (key_row.schema.as_str(), key_row.tb.as_str()),
+            ("db_1", "tb_1")
+        );
+        assert_eq!(
+            JsonConverter::get_json_key(&tb_meta, &key_row).unwrap(),
+            Some("[1]".to_string())
+        );
+        assert_eq!(
+            AvroConverter::get_avro_key(&tb_meta, &key_row).unwrap(),
+            Some("1".to_string())
+        );
+        // the renamed pk col can not be found by the source meta directly
+        assert_eq!(
+            JsonConverter::get_json_key(&tb_meta, &row_data).unwrap(),
+            Some("[]".to_string())
+        );
+    }
+
+    #[test]
+    fn test_key_of_key_cols() {
+        let key_cols: KeyCols = HashMap::from([(
+            ("db_1".to_string(), "no_pk_tb".to_string()),
+            vec!["user_id".to_string()],
+        )]);
+
+        let build_update = |user_id: i32, value: &str| {
+            let before = HashMap::from([
+                ("user_id".to_string(), ColValue::Long(user_id)),
+                ("value".to_string(), ColValue::String("old".into())),
+            ]);
+            let mut after = before.clone();
+            after.insert("value".to_string(), ColValue::String(value.into()));
+            RowData::new(
+                "db_1".into(),
+                "no_pk_tb".into(),
+                0,
+                RowType::Update,
+                Some(before),
+                Some(after),
+            )
+        };
+        let get_keys = |row_data: &RowData| {
+            let cols = KafkaSinker::get_key_cols(&key_cols, row_data).unwrap();
+            (
+                JsonConverter::get_json_key_by_cols(cols, row_data).unwrap(),
+                AvroConverter::get_avro_key_by_cols(cols, row_data).unwrap(),
+            )
+        };
+
+        // updates to the same entity have identical keys
+        let (json_key_1, avro_key_1) = get_keys(&build_update(1, "a"));
+        let (json_key_2, avro_key_2) = get_keys(&build_update(1, "b"));
+        assert_eq!(json_key_1, "[1]");
+        assert_eq!(avro_key_1, "1");
+        assert_eq!(json_key_1, json_key_2);
+        assert_eq!(avro_key_1, avro_key_2);
+
+        let (json_key_3, avro_key_3) = get_keys(&build_update(2, "a"));
+        assert_ne!(json_key_1, json_key_3);
+        assert_ne!(avro_key_1, avro_key_3);
+
+        // tables not configured fall back to the primary key
+        let row_data = RowData::new(
+            "db_1".into(),
+            "tb_1".into(),
+            0,
+            RowType::Insert,
+            None,
+            Some(HashMap::new()),
+        );
+        assert!(KafkaSinker::get_key_cols(&key_cols, &row_data).is_none());
+    }
+
+    #[test]
+    fn test_build_batched_records() {
+        let batches = vec![
+            (
+                "topic_1",
+                "[1]".to_string(),
+                vec![r#"{"id":1}"#.to_string(), r#"{"id":2}"#.to_string()],
+            ),
+            (
+                "topic_2",
+                "[3]".to_string(),
+                vec![r#"{"id":3}"#.to_string()],
+            ),
+            ("topic_3", "[4]".to_string(), vec![]),
+        ];
+
+        let records = KafkaSinker::build_batched_records(batches);
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].topic, "topic_1");
+        assert_eq!(records[0].key, "[1]");
+        assert_eq!(records[0].value, b"{\"id\":1}\n{\"id\":2}".to_vec());
+        assert_eq!(records[1].topic, "topic_2");
+        assert_eq!(records[1].value, br#"{"id":3}"#.to_vec());
+
+        // an empty batch produces no message
+        assert!(KafkaSinker::build_batched_records(Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn test_get_topic_by_strategy() {
+        let config = RouterConfig::Rdb {
+            schema_map: String::new(),
+            tb_map: String::new(),
+            col_map: String::new(),
+            topic_map: "*.*:default_topic,db_1.*:db_1_topic".into(),
+            doc_projection: String::new(),
+        };
+        let router = RdbRouter::from_config_for_topic(&config, &DbType::Mysql).unwrap();
+        let get_topics = |topic_strategy: &TopicStrategy| {
+            [
+                ("db_1", "tb_1"),
+                ("db_1", "tb_2"),
+                ("db_2", "tb_1"),
+                ("db_2", ""),
+            ]
+            .iter()
+            .map(|(schema, tb)| {
+                KafkaSinker::get_topic_by_strategy(topic_strategy, &router, schema, tb)
+            })
+            .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            get_topics(&TopicStrategy::TopicMap),
+            vec!["db_1_topic", "db_1_topic", "default_topic", "default_topic"]
+        );
+        assert_eq!(
+            get_topics(&TopicStrategy::PerTable),
+            vec!["db_1.tb_1", "db_1.tb_2", "db_2.tb_1", "db_2"]
+        );
+        assert_eq!(
+            get_topics(&TopicStrategy::PerSchema),
+            vec!["db_1", "db_1", "db_2", "db_2"]
+        );
+        assert_eq!(
+            get_topics(&TopicStrategy::Single("all".into())),
+            vec!["all", "all", "all", "all"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat_after_interval() {
+        let position = Position::PgCdc {
+            lsn: "0/1A2B3C".into(),
+            timestamp: "2024-01-01 00:00:00.000".into(),
+        };
+        let mut heartbeat = KafkaHeartbeat::new("ape_dts_heartbeat", Duration::from_millis(100));
+
+        // no data flows, nothing is sent before the interval
+        assert!(heartbeat.poll(&position).is_none());
+        tokio::time::sleep(Duration::from_millis(150)).await;
+
+        let payload: serde_json::Value =
+            serde_json::from_str(&heartbeat.poll(&position).unwrap()).unwrap();
+        assert_eq!(payload["type"], "heartbeat");
+        assert_eq!(payload["position"], json!(position));
+        assert_eq!(payload["position_timestamp"], 1704067200000u64);
+        assert!(payload["heartbeat_timestamp"].as_i64().unwrap() > 0);
+
+        // the interval restarts after each heartbeat
+        assert!(heartbeat.poll(&position).is_none());
+    }
 }
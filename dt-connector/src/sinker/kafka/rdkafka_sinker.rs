@@ -1,8 +1,15 @@
-use std::cmp;
+use std::{cmp, fmt::Debug, future::Future};
 
-use anyhow::bail;
+use anyhow::{bail, Context};
 use async_trait::async_trait;
-use rdkafka::producer::{FutureProducer, FutureRecord};
+use chrono::Utc;
+use futures::{stream::FuturesUnordered, StreamExt};
+use rdkafka::{
+    error::{KafkaError, KafkaResult},
+    message::{Header, OwnedHeaders},
+    producer::{FutureProducer, FutureRecord, Producer},
+    types::RDKafkaErrorCode,
+};
 use tokio::{time::Duration, time::Instant};
 
 use dt_common::{
@@ -12,7 +19,9 @@ use dt_common::{
 
 use crate::{rdb_router::RdbRouter, sinker::base_sinker::BaseSinker, Sinker};
 
-// Deprecated: use KafkaSinker instead
+const QUEUE_FULL_RETRY_INTERVAL: Duration = Duration::from_millis(100);
+
+// Used with [sinker].producer_type=rdkafka, sends avro messages only
 pub struct RdkafkaSinker {
     pub batch_size: usize,
     pub router: RdbRouter,
@@ -20,6 +29,17 @@ pub struct RdkafkaSinker {
     pub avro_converter: AvroConverter,
     pub base_sinker: BaseSinker,
     pub queue_timeout_secs: u64,
+    // max unresolved delivery futures, 0 for no limit
+    pub max_in_flight: usize,
+    // attach schema / table / op / ts_ms headers to each message
+    pub with_headers: bool,
+    // send each batch in a kafka transaction, committed after all messages are delivered
+    // and aborted on any failure, the producer must be created with transactional.id.
+    // exactly-once costs throughput: a batch waits for the commit before the next one starts,
+    // and consumers with isolation.level=read_committed only see messages after the commit
+    pub transactional: bool,
+    // init_transactions is called once before the first transaction
+    transactions_initialized: bool,
 }
 
 #[async_trait]
@@ -29,11 +49,55 @@ impl Sinker for RdkafkaSinker {
             return Ok(());
         }
 
-        self.send_avro(data.as_mut_slice()).await
+        let result = if self.transactional {
+            self.send_avro_in_transaction(data.as_mut_slice()).await
+        } else {
+            self.send_avro(data.as_mut_slice()).await
+        };
+        self.base_sinker.update_table_monitor(&data, result.is_ok());
+        result
     }
 }
 
 impl RdkafkaSinker {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        batch_size: usize,
+        router: RdbRouter,
+        producer: FutureProducer,
+        avro_converter: AvroConverter,
+        base_sinker: BaseSinker,
+        queue_timeout_secs: u64,
+        max_in_flight: usize,
+        with_headers: bool,
+        transactional: bool,
+    ) -> Self {
+        Self {
+            batch_size,
+            router,
+            producer,
+            avro_converter,
+            base_sinker,
+            queue_timeout_secs,
+            max_in_flight,
+            with_headers,
+            transactional,
+            transactions_initialized: false,
+        }
+    }
+
+    async fn send_avro_in_transaction(&mut self, data: &mut [RowData]) -> anyhow::Result<()> {
+        let producer = self.producer.clone();
+        let timeout = Duration::from_secs(self.queue_timeout_secs);
+        if !self.transactions_initialized {
+            run_blocking(&producer, move |p| p.init_transactions(timeout))
+                .await
+                .context("failed to init kafka transactions")?;
+            self.transactions_initialized = true;
+        }
+        run_in_transaction(&producer, timeout, self.send_avro(data)).await
+    }
+
     async fn send_avro(&mut self, data: &mut [RowData]) -> anyhow::Result<()> {
         let task_id = self.base_sinker.task_id_for_rows(data);
         self.base_sinker.ensure_monitor_for(&task_id);
@@ -42,38 +106,44 @@ impl RdkafkaSinker {
 
         let producer = &self.producer.clone();
         let queue_timeout = Duration::from_secs(self.queue_timeout_secs);
-        let mut futures = Vec::new();
+        let mut in_flight = FuturesUnordered::new();
+        let mut rts = LimitedQueue::new(cmp::min(100, data.len()));
 
-        // This loop is non blocking: all messages will be sent one after the other, without waiting
-        // for the results.
+        // Messages are enqueued strictly in order, this loop only blocks when the producer queue
+        // is full or max_in_flight messages are waiting for their delivery statuses.
         for row_data in data.iter_mut() {
             data_size += row_data.get_data_size();
             row_data.convert_raw_string();
+            let headers = if self.with_headers {
+                Some(build_headers(row_data, Utc::now().timestamp_millis()))
+            } else {
+                None
+            };
             let topic = self.router.get_topic(&row_data.schema, &row_data.tb);
-            let key = self.avro_converter.row_data_to_avro_key(row_data).await?;
+            let key_row = self.router.reverse_route_row_for_key(row_data);
+            let key = self.avro_converter.row_data_to_avro_key(&key_row).await?;
             let payload = self.avro_converter.row_data_to_avro_value(row_data).await?;
 
-            // The send operation on the topic returns a future, which will be
-            // completed once the result or failure from Kafka is received.
-            let delivery_status = async move {
-                producer
-                    .send(
-                        FutureRecord::to(topic).payload(&payload).key(&key),
-                        queue_timeout,
-                    )
-                    .await
-            };
-            futures.push(delivery_status);
-        }
-
-        // This loop will wait until all delivery statuses have been received.
-        let mut rts = LimitedQueue::new(cmp::min(100, futures.len()));
-        for future in futures {
+            let mut record = FutureRecord::to(topic).payload(&payload).key(&key);
+            if let Some(headers) = headers {
+                record = record.headers(headers);
+            }
+            // The enqueued message returns a future, which will be completed once
+            // the result or failure from Kafka is received.
             let start_time = Instant::now();
-            if let Err(err) = future.await {
-                bail!(format!("failed in kafka producer, error: {:?}", err));
+            let delivery = enqueue(|r| producer.send_result(r), record, queue_timeout).await?;
+            let delivery_status = async move { (delivery.await, start_time.elapsed()) };
+            let done = push_bounded(&mut in_flight, delivery_status, self.max_in_flight).await;
+            if let Some((res, rt)) = done {
+                check_delivery(res)?;
+                rts.push((rt.as_millis() as u64, 1));
             }
-            rts.push((start_time.elapsed().as_millis() as u64, 1));
+        }
+
+        // This loop will wait until the remaining delivery statuses have been received.
+        while let Some((res, rt)) = in_flight.next().await {
+            check_delivery(res)?;
+            rts.push((rt.as_millis() as u64, 1));
         }
 
         self.base_sinker
@@ -82,3 +152,330 @@ impl RdkafkaSinker {
         self.base_sinker.update_monitor_rt_for(&task_id, &rts).await
     }
 }
+
+// the transaction api of the producer, abstracted for tests
+trait TransactionalProducer: Clone + Send + 'static {
+    fn init_transactions(&self, timeout: Duration) -> KafkaResult<()>;
+
+    fn begin_transaction(&self) -> KafkaResult<()>;
+
+    fn commit_transaction(&self, timeout: Duration) -> KafkaResult<()>;
+
+    fn abort_transaction(&self, timeout: Duration) -> KafkaResult<()>;
+}
+
+impl TransactionalProducer for FutureProducer {
+    fn init_transactions(&self, timeout: Duration) -> KafkaResult<()> {
+        Producer::init_transactions(self, timeout)
+    }
+
+    fn begin_transaction(&self) -> KafkaResult<()> {
+        Producer::begin_transaction(self)
+    }
+
+    fn commit_transaction(&self, timeout: Duration) -> KafkaResult<()> {
+        Producer::commit_transaction(self, timeout)
+    }
+
+    fn abort_transaction(&self, timeout: Duration) -> KafkaResult<()> {
+        Producer::abort_transaction(self, timeout)
+    }
+}
+
+// Runs send in a transaction, commits if it succeeds, otherwise aborts and returns the error.
+async fn run_in_transaction<P, F>(producer: &P, timeout: Duration, send: F) -> anyhow::Result<()>
+where
+    P: TransactionalProducer,
+    F: Future<Output = anyhow::Result<()>>,
+{
+    producer.begin_transaction()?;
+
+    let err = match send.await {
+        Ok(()) => match run_blocking(producer, move |p| p.commit_transaction(timeout)).await {
+            Ok(()) => return Ok(()),
+            Err(err) => err.context("failed to commit kafka transaction"),
+        },
+        Err(err) => err,
+    };
+
+    if let Err(abort_err) = run_blocking(producer, move |p| p.abort_transaction(timeout)).await {
+        bail!(
+            "kafka transaction failed, error: {:?}, abort error: {:?}",
+            err,
+            abort_err
+        );
+    }
+    Err(err.context("kafka transaction aborted"))
+}
+
+// init / commit / abort block until the broker responds, so they run off the async workers
+async fn run_blocking<P, F>(producer: &P, f: F) -> anyhow::Result<()>
+where
+    P: TransactionalProducer,
+    F: FnOnce(&P) -> KafkaResult<()> + Send + 'static,
+{
+    let producer = producer.clone();
+    tokio::task::spawn_blocking(move || f(&producer)).await??;
+    Ok(())
+}
+
+fn build_headers(row_data: &RowData, ts_ms: i64) -> OwnedHeaders {
+    let row_type = row_data.row_type.to_string();
+    let ts_ms = ts_ms.to_string();
+    OwnedHeaders::new_with_capacity(4)
+        .insert(Header {
+            key: "schema",
+            value: Some(&row_data.schema),
+        })
+        .insert(Header {
+            key: "table",
+            value: Some(&row_data.tb),
+        })
+        .insert(Header {
+            key: "op",
+            value: Some(&row_type),
+        })
+        .insert(Header {
+            key: "ts_ms",
+            value: Some(&ts_ms),
+        })
+}
+
+// Enqueues the record to the producer before the next one is built, retrying inline while the
+// producer queue is full so later records never overtake it. Fails if the queue is still full
+// after queue_timeout.
+async fn enqueue<R, D>(
+    mut send: impl FnMut(R) -> Result<D, (KafkaError, R)>,
+    mut record: R,
+    queue_timeout: Duration,
+) -> anyhow::Result<D> {
+    let deadline = Instant::now() + queue_timeout;
+    loop {
+        match send(record) {
+            Ok(delivery) => return Ok(delivery),
+            Err((KafkaError::MessageProduction(RDKafkaErrorCode::QueueFull), r))
+                if Instant::now() < deadline =>
+            {
+                record = r;
+                tokio::time::sleep(QUEUE_FULL_RETRY_INTERVAL).await;
+            }
+            Err((err, _)) => bail!(format!("failed in kafka producer, error: {:?}", err)),
+        }
+    }
+}
+
+// the delivery status of an enqueued message, or canceled if the producer was dropped
+fn check_delivery<D, M, C: Debug>(
+    res: Result<Result<D, (KafkaError, M)>, C>,
+) -> anyhow::Result<()> {
+    match res {
+        Ok(Ok(_)) => Ok(()),
+        Ok(Err((err, _))) => bail!(format!("failed in kafka producer, error: {:?}", err)),
+        Err(err) => bail!(format!("failed in kafka producer, error: {:?}", err)),
+    }
+}
+
+// Pushes the future, and if max_in_flight futures are unresolved, waits for one of them
+// and returns its output. max_in_flight = 0 means no limit.
+async fn push_bounded<F: Future>(
+    in_flight: &mut FuturesUnordered<F>,
+    future: F,
+    max_in_flight: usize,
+) -> Option<F::Output> {
+    in_flight.push(future);
+    if max_in_flight > 0 && in_flight.len() >= max_in_flight {
+        return in_flight.next().await;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    };
+
+    use dt_common::meta::{row_data::RowData, row_type::RowType};
+    use futures::{stream::FuturesUnordered, StreamExt};
+    use rdkafka::message::Headers;
+
+    use anyhow::bail;
+    use rdkafka::{
+        error::{KafkaError, KafkaResult},
+        types::RDKafkaErrorCode,
+    };
+    use tokio::time::Duration;
+
+    use super::{
+        build_headers, enqueue, push_bounded, run_blocking, run_in_transaction,
+        TransactionalProducer,
+    };
+
+    // clones share the calls, as clones of a FutureProducer share the client
+    #[derive(Clone, Default)]
+    struct MockProducer {
+        fail_commit: bool,
+        calls: Arc<Mutex<Vec<&'static str>>>,
+    }
+
+    impl MockProducer {
+        fn calls(&self) -> Vec<&'static str> {
+            self.calls.lock().unwrap().clone()
+        }
+    }
+
+    impl TransactionalProducer for MockProducer {
+        fn init_transactions(&self, _timeout: Duration) -> KafkaResult<()> {
+            self.calls.lock().unwrap().push("init");
+            Ok(())
+        }
+
+        fn begin_transaction(&self) -> KafkaResult<()> {
+            self.calls.lock().unwrap().push("begin");
+            Ok(())
+        }
+
+        fn commit_transaction(&self, _timeout: Duration) -> KafkaResult<()> {
+            self.calls.lock().unwrap().push("commit");
+            if self.fail_commit {
+                return Err(KafkaError::Canceled);
+            }
+            Ok(())
+        }
+
+        fn abort_transaction(&self, _timeout: Duration) -> KafkaResult<()> {
+            self.calls.lock().unwrap().push("abort");
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_in_transaction() {
+        let timeout = Duration::from_secs(1);
+
+        // committed
+        let producer = MockProducer::default();
+        run_blocking(&producer, move |p| p.init_transactions(timeout))
+            .await
+            .unwrap();
+        run_in_transaction(&producer, timeout, async { Ok(()) })
+            .await
+            .unwrap();
+        assert_eq!(producer.calls(), vec!["init", "begin", "commit"]);
+
+        // delivery failed, aborted
+        let producer = MockProducer::default();
+        let err = run_in_transaction(&producer, timeout, async {
+            bail!("failed in kafka producer")
+        })
+        .await
+        .unwrap_err();
+        assert_eq!(producer.calls(), vec!["begin", "abort"]);
+        assert_eq!(err.root_cause().to_string(), "failed in kafka producer");
+
+        // commit failed, aborted
+        let producer = MockProducer {
+            fail_commit: true,
+            ..Default::default()
+        };
+        run_in_transaction(&producer, timeout, async { Ok(()) })
+            .await
+            .unwrap_err();
+        assert_eq!(producer.calls(), vec!["begin", "commit", "abort"]);
+    }
+
+    #[test]
+    fn test_build_headers() {
+        let row_data = RowData::new("db_1".into(), "tb_1".into(), 0, RowType::Update, None, None);
+        let headers = build_headers(&row_data, 1700000000123);
+
+        let headers: Vec<(&str, &[u8])> = headers
+            .iter()
+            .map(|header| (header.key, header.value.unwrap()))
+            .collect();
+        assert_eq!(
+            headers,
+            vec![
+                ("schema", "db_1".as_bytes()),
+                ("table", "tb_1".as_bytes()),
+                ("op", "update".as_bytes()),
+                ("ts_ms", "1700000000123".as_bytes()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_enqueue_in_order() {
+        // the queue is full for the first 2 attempts of record 0
+        let mut attempts = 0;
+        let mut enqueued = Vec::new();
+        let mut send = |record: usize| {
+            attempts += 1;
+            if record == 0 && attempts <= 2 {
+                return Err((
+                    KafkaError::MessageProduction(RDKafkaErrorCode::QueueFull),
+                    record,
+                ));
+            }
+            enqueued.push(record);
+            Ok(record)
+        };
+        for record in 0..3 {
+            let delivery = enqueue(&mut send, record, Duration::from_secs(5))
+                .await
+                .unwrap();
+            assert_eq!(delivery, record);
+        }
+        assert_eq!(enqueued, vec![0, 1, 2]);
+
+        // still full after queue_timeout
+        let full = |record: usize| -> Result<usize, _> {
+            Err((
+                KafkaError::MessageProduction(RDKafkaErrorCode::QueueFull),
+                record,
+            ))
+        };
+        assert!(enqueue(full, 0, Duration::ZERO).await.is_err());
+
+        // other errors are not retried
+        let mut attempts = 0;
+        let failed = |record: usize| -> Result<usize, _> {
+            attempts += 1;
+            Err((KafkaError::Canceled, record))
+        };
+        assert!(enqueue(failed, 0, Duration::from_secs(5)).await.is_err());
+        assert_eq!(attempts, 1);
+    }
+
+    #[tokio::test]
+    async fn test_push_bounded() {
+        let max_in_flight = 2;
+        let unresolved = Arc::new(AtomicUsize::new(0));
+        let max_unresolved = Arc::new(AtomicUsize::new(0));
+
+        let mut in_flight = FuturesUnordered::new();
+        let mut outputs = Vec::new();
+        for i in 0..5 {
+            let count = unresolved.fetch_add(1, Ordering::SeqCst) + 1;
+            max_unresolved.fetch_max(count, Ordering::SeqCst);
+
+            let unresolved = unresolved.clone();
+            let future = async move {
+                tokio::task::yield_now().await;
+                unresolved.fetch_sub(1, Ordering::SeqCst);
+                i
+            };
+            outputs.extend(push_bounded(&mut in_flight, future, max_in_flight).await);
+            assert!(in_flight.len() < max_in_flight);
+        }
+        while let Some(output) = in_flight.next().await {
+            outputs.push(output);
+        }
+
+        outputs.sort();
+        assert_eq!(outputs, vec![0, 1, 2, 3, 4]);
+        assert_eq!(max_unresolved.load(Ordering::SeqCst), max_in_flight);
+        assert_eq!(unresolved.load(Ordering::SeqCst), 0);
+    }
+}
@@ -2,11 +2,14 @@ pub mod base_sinker;
 pub mod base_struct_sinker;
 pub mod checkable_sinker;
 pub mod clickhouse;
+pub mod csv;
+pub mod discard_sinker;
 pub mod dummy_sinker;
 pub mod foxlake;
 pub mod kafka;
 pub mod mongo;
 pub mod mysql;
+pub mod parquet;
 pub mod pg;
 pub mod redis;
 pub mod sql_sinker;
@@ -1,4 +1,4 @@
-use std::cmp;
+use std::{cmp, slice};
 
 use anyhow::Context;
 use async_trait::async_trait;
@@ -94,71 +94,12 @@ impl MongoSinker {
             data_size += row_data.get_data_size() as usize;
             data_len += 1;
 
-            let collection = self
-                .mongo_client
-                .database(&row_data.schema)
-                .collection::<Document>(&row_data.tb);
-
             let start_time = Instant::now();
-            match row_data.row_type {
-                RowType::Insert => {
-                    let after = row_data.require_after()?;
-                    if let Some(ColValue::MongoDoc(doc)) = after.get(MongoConstants::DOC) {
-                        let id = doc
-                            .get(MongoConstants::ID)
-                            .context("mongo doc missing `_id`")?;
-                        let query_doc = doc! {MongoConstants::ID: id};
-                        let update_doc = doc! {MongoConstants::SET: doc.clone()};
-                        self.upsert(&collection, query_doc, update_doc).await?;
-                        rts.push((start_time.elapsed().as_millis() as u64, 1));
-                    }
-                }
-
-                RowType::Delete => {
-                    let before = row_data.require_before()?;
-                    if let Some(ColValue::MongoDoc(doc)) = before.get(MongoConstants::DOC) {
-                        let id = doc
-                            .get(MongoConstants::ID)
-                            .context("mongo doc missing `_id`")?;
-                        let query_doc = doc! {MongoConstants::ID: id};
-                        collection.delete_one(query_doc, None).await?;
-                        rts.push((start_time.elapsed().as_millis() as u64, 1));
-                    }
-                }
-
-                RowType::Update => {
-                    let query_doc = {
-                        let before = row_data.require_before()?;
-                        if let Some(ColValue::MongoDoc(doc)) = before.get(MongoConstants::DOC) {
-                            let id = doc
-                                .get(MongoConstants::ID)
-                                .context("mongo doc missing `_id`")?;
-                            Some(doc! {MongoConstants::ID: id})
-                        } else {
-                            None
-                        }
-                    };
-
-                    let update_doc = {
-                        let after = row_data.require_after()?;
-                        if let Some(ColValue::MongoDoc(doc)) = after.get(MongoConstants::DOC) {
-                            Some(doc.clone())
-                        } else if let Some(ColValue::MongoDoc(doc)) =
-                            after.get(MongoConstants::DIFF_DOC)
-                        {
-                            // for Update row_data from oplog (NOT change stream), after contains diff_doc instead of doc
-                            Some(doc.clone())
-                        } else {
-                            None
-                        }
-                    };
-
-                    if query_doc.is_some() && update_doc.is_some() {
-                        self.upsert(&collection, query_doc.unwrap(), update_doc.unwrap())
-                            .await?;
-                        rts.push((start_time.elapsed().as_millis() as u64, 1));
-                    }
-                }
+            let result = self.sink_row(row_data).await;
+            self.base_sinker
+                .update_table_monitor(slice::from_ref(row_data), result.is_ok());
+            if result? {
+                rts.push((start_time.elapsed().as_millis() as u64, 1));
             }
 
             if last_monitor_time.elapsed().as_secs() >= monitor_interval {
@@ -186,6 +127,76 @@ impl MongoSinker {
         Ok(())
     }
 
+    // writes the row, returns false if the row has no doc to write
+    async fn sink_row(&mut self, row_data: &RowData) -> anyhow::Result<bool> {
+        let collection = self
+            .mongo_client
+            .database(&row_data.schema)
+            .collection::<Document>(&row_data.tb);
+
+        match row_data.row_type {
+            RowType::Insert => {
+                let after = row_data.require_after()?;
+                if let Some(ColValue::MongoDoc(doc)) = after.get(MongoConstants::DOC) {
+                    let id = doc
+                        .get(MongoConstants::ID)
+                        .context("mongo doc missing `_id`")?;
+                    let query_doc = doc! {MongoConstants::ID: id};
+                    let update_doc = doc! {MongoConstants::SET: doc.clone()};
+                    self.upsert(&collection, query_doc, update_doc).await?;
+                    return Ok(true);
+                }
+            }
+
+            RowType::Delete => {
+                let before = row_data.require_before()?;
+                if let Some(ColValue::MongoDoc(doc)) = before.get(MongoConstants::DOC) {
+                    let id = doc
+                        .get(MongoConstants::ID)
+                        .context("mongo doc missing `_id`")?;
+                    let query_doc = doc! {MongoConstants::ID: id};
+                    collection.delete_one(query_doc, None).await?;
+                    return Ok(true);
+                }
+            }
+
+            RowType::Update => {
+                let query_doc = {
+                    let before = row_data.require_before()?;
+                    if let Some(ColValue::MongoDoc(doc)) = before.get(MongoConstants::DOC) {
+                        let id = doc
+                            .get(MongoConstants::ID)
+                            .context("mongo doc missing `_id`")?;
+                        Some(doc! {MongoConstants::ID: id})
+                    } else {
+                        None
+                    }
+                };
+
+                let update_doc = {
+                    let after = row_data.require_after()?;
+                    if let Some(ColValue::MongoDoc(doc)) = after.get(MongoConstants::DOC) {
+                        Some(doc.clone())
+                    } else if let Some(ColValue::MongoDoc(doc)) =
+                        after.get(MongoConstants::DIFF_DOC)
+                    {
+                        // for Update row_data from oplog (NOT change stream), after contains diff_doc instead of doc
+                        Some(doc.clone())
+                    } else {
+                        None
+                    }
+                };
+
+                if query_doc.is_some() && update_doc.is_some() {
+                    self.upsert(&collection, query_doc.unwrap(), update_doc.unwrap())
+                        .await?;
+                    return Ok(true);
+                }
+            }
+        }
+        Ok(false)
+    }
+
     async fn batch_delete(
         &mut self,
         data: &mut [RowData],
@@ -223,7 +234,10 @@ impl MongoSinker {
         };
         let start_time = Instant::now();
         let mut rts = LimitedQueue::new(1);
-        collection.delete_many(query, None).await?;
+        let result = collection.delete_many(query, None).await;
+        self.base_sinker
+            .update_table_monitor(&data[start_index..start_index + batch_size], result.is_ok());
+        result?;
         rts.push((start_time.elapsed().as_millis() as u64, 1));
 
         self.base_sinker
@@ -267,6 +281,9 @@ impl MongoSinker {
             );
             let sub_data = &data[start_index..start_index + batch_size];
             self.serial_sink(sub_data).await?;
+        } else {
+            self.base_sinker
+                .update_table_monitor(&data[start_index..start_index + batch_size], true);
         }
 
         self.base_sinker
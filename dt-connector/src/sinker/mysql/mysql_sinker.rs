@@ -3,7 +3,8 @@ use std::{cmp, str::FromStr, sync::Arc, time::Duration};
 use anyhow::Context;
 use async_trait::async_trait;
 use sqlx::{
-    mysql::{MySqlConnectOptions, MySqlPoolOptions},
+    mysql::{MySqlArguments, MySqlConnectOptions, MySqlPoolOptions},
+    query::Query,
     MySql, Pool,
 };
 use tokio::{sync::RwLock, time::Instant};
@@ -15,7 +16,7 @@ use crate::{
 };
 use dt_common::{
     config::connection_auth_config::ConnectionAuthConfig,
-    log_error, log_info,
+    log_error, log_info, log_warn,
     meta::{
         dcl_meta::dcl_data::DclData,
         ddl_meta::{ddl_data::DdlData, ddl_type::DdlType},
@@ -39,6 +40,8 @@ pub struct MysqlSinker {
     pub base_sinker: BaseSinker,
     pub data_marker: Option<Arc<RwLock<DataMarker>>>,
     pub replace: bool,
+    // inserts update the existing rows on conflicts of the primary key, or the unique key if no primary key
+    pub upsert: bool,
 }
 
 #[async_trait]
@@ -76,6 +79,10 @@ impl Sinker for MysqlSinker {
             let sql = ddl_data.to_sql();
             data_size += ddl_data.get_data_size();
             data_len += 1;
+            if sql.is_empty() {
+                log_warn!("skip ddl not supported by mysql: {}", ddl_data.query);
+                continue;
+            }
             let query = sqlx::query(&sql);
             let (db, _tb) = ddl_data.get_schema_tb();
             log_info!("sink ddl, db: {}, sql: {}", db, sql);
@@ -226,16 +233,25 @@ impl MysqlSinker {
             data_len += 1;
             let tb_meta = self.meta_manager.get_tb_meta_by_row_data(row_data).await?;
             let query_builder = RdbQueryBuilder::new_for_mysql(tb_meta, None);
-            let query_info = query_builder.get_query_info(row_data, self.replace)?;
+            let query_info = if self.upsert && matches!(row_data.row_type, RowType::Insert) {
+                query_builder.get_upsert_query_info(row_data)?
+            } else {
+                query_builder.get_query_info(row_data, self.replace)?
+            };
             let query = query_builder.create_mysql_query(&query_info)?;
 
             let start_time = Instant::now();
-            query.execute(&mut *tx).await.with_context(|| {
+            let result = query.execute(&mut *tx).await.with_context(|| {
                 format!(
                     "serial sink failed, sql: [{}], row_data: [{}]",
                     query_info.sql, row_data
                 )
-            })?;
+            });
+            if result.is_err() {
+                // the transaction is rolled back
+                self.base_sinker.update_table_monitor(data, false);
+            }
+            result?;
 
             rts.push((start_time.elapsed().as_millis() as u64, 1));
             if last_monitor_time.elapsed().as_secs() >= monitor_interval {
@@ -251,7 +267,9 @@ impl MysqlSinker {
                 last_monitor_time = Instant::now();
             }
         }
-        tx.commit().await?;
+        let result = tx.commit().await;
+        self.base_sinker.update_table_monitor(data, result.is_ok());
+        result?;
 
         if data_len > 0 || data_size > 0 {
             self.base_sinker
@@ -286,14 +304,10 @@ impl MysqlSinker {
 
         let start_time = Instant::now();
         let mut rts = LimitedQueue::new(1);
-        if let Some(sql) = self.get_data_marker_sql().await {
-            let mut tx = self.conn_pool.begin().await?;
-            sqlx::query(&sql).execute(&mut *tx).await?;
-            query.execute(&mut *tx).await?;
-            tx.commit().await?;
-        } else {
-            query.execute(&self.conn_pool).await?;
-        }
+        let result = self.execute_with_data_marker(query).await;
+        self.base_sinker
+            .update_table_monitor(&data[start_index..start_index + batch_size], result.is_ok());
+        result?;
         rts.push((start_time.elapsed().as_millis() as u64, 1));
 
         self.base_sinker
@@ -319,29 +333,19 @@ impl MysqlSinker {
             .to_owned();
         let query_builder = RdbQueryBuilder::new_for_mysql(&tb_meta, None);
 
-        let (query_info, data_size) =
-            query_builder.get_batch_insert_query(data, start_index, batch_size, self.replace)?;
+        let (query_info, data_size) = if self.upsert {
+            query_builder.get_batch_upsert_query(data, start_index, batch_size)?
+        } else {
+            query_builder.get_batch_insert_query(data, start_index, batch_size, self.replace)?
+        };
         let query = query_builder.create_mysql_query(&query_info)?;
 
         let start_time = Instant::now();
         let mut rts = LimitedQueue::new(1);
-        let exec_error = if let Some(sql) = self.get_data_marker_sql().await {
-            let mut tx = self.conn_pool.begin().await?;
-            sqlx::query(&sql).execute(&mut *tx).await?;
-            query.execute(&mut *tx).await?;
-            match tx.commit().await {
-                Err(e) => Some(e),
-                _ => None,
-            }
-        } else {
-            match query.execute(&self.conn_pool).await {
-                Err(e) => Some(e),
-                _ => None,
-            }
-        };
+        let exec_result = self.execute_with_data_marker(query).await;
         rts.push((start_time.elapsed().as_millis() as u64, 1));
 
-        if let Some(error) = exec_error {
+        if let Err(error) = exec_result {
             log_error!(
                 "batch insert failed, will insert one by one, schema: {}, tb: {}, error: {}",
                 tb_meta.basic.schema,
@@ -352,6 +356,8 @@ impl MysqlSinker {
             let sub_data = &data[start_index..start_index + batch_size];
             self.serial_sink(sub_data).await?;
         } else {
+            self.base_sinker
+                .update_table_monitor(&data[start_index..start_index + batch_size], true);
             self.base_sinker
                 .update_monitor_rt_for(&task_id, &rts)
                 .await?;
@@ -362,6 +368,22 @@ impl MysqlSinker {
             .await
     }
 
+    // executes the query, in a transaction with the data marker sql if any
+    async fn execute_with_data_marker(
+        &self,
+        query: Query<'_, MySql, MySqlArguments>,
+    ) -> anyhow::Result<()> {
+        if let Some(sql) = self.get_data_marker_sql().await {
+            let mut tx = self.conn_pool.begin().await?;
+            sqlx::query(&sql).execute(&mut *tx).await?;
+            query.execute(&mut *tx).await?;
+            tx.commit().await?;
+        } else {
+            query.execute(&self.conn_pool).await?;
+        }
+        Ok(())
+    }
+
     async fn get_data_marker_sql(&self) -> Option<String> {
         if let Some(data_marker) = &self.data_marker {
             let data_marker = data_marker.read().await;
@@ -0,0 +1,527 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    path::PathBuf,
+    sync::Arc,
+};
+
+use anyhow::{bail, Context};
+use arrow::{
+    array::{
+        ArrayRef, BinaryArray, BooleanArray, Float32Array, Float64Array, Int16Array, Int32Array,
+        Int64Array, Int8Array, StringArray, UInt16Array, UInt32Array, UInt64Array, UInt8Array,
+    },
+    datatypes::{DataType, Field, Schema, SchemaRef},
+    record_batch::RecordBatch,
+};
+use async_trait::async_trait;
+use parquet::{arrow::ArrowWriter, basic::Compression, file::properties::WriterProperties};
+use tokio::time::Instant;
+
+use dt_common::{
+    error::Error,
+    log_info,
+    meta::{
+        col_value::ColValue, mysql::mysql_col_type::MysqlColType, pg::pg_value_type::PgValueType,
+        rdb_meta_manager::RdbMetaManager, row_data::RowData, row_type::RowType,
+    },
+    utils::{file_util::FileUtil, limit_queue::LimitedQueue},
+};
+
+use crate::{sinker::base_sinker::BaseSinker, Sinker};
+
+/// Writes snapshot rows to parquet files: {root_dir}/{schema}/{tb}/{sinker_id}_{file_seq}.parquet,
+/// rows of a table are buffered and written as row groups of batch_size,
+/// a new file is started once the current one has file_max_rows rows,
+/// file_seq continues from the files left by previous runs.
+pub struct ParquetSinker {
+    pub id: usize,
+    pub batch_size: usize,
+    pub root_dir: String,
+    pub file_max_rows: usize,
+    // cols and types of parquet files follow the source tables
+    pub meta_manager: RdbMetaManager,
+    pub base_sinker: BaseSinker,
+    pub buffers: HashMap<(String, String), Vec<RowData>>,
+    pub writers: HashMap<(String, String), ParquetTableWriter>,
+}
+
+pub struct ParquetTableWriter {
+    schema: SchemaRef,
+    dir: PathBuf,
+    sinker_id: usize,
+    writer: Option<ArrowWriter<File>>,
+    file_rows: usize,
+    file_seq: usize,
+}
+
+#[async_trait]
+impl Sinker for ParquetSinker {
+    async fn sink_dml(&mut self, data: Vec<RowData>, _batch: bool) -> anyhow::Result<()> {
+        for mut row_data in data {
+            if row_data.row_type != RowType::Insert {
+                bail! {Error::SinkerError(format!(
+                    "parquet sinker only supports snapshot rows, got {} row of {}.{}",
+                    row_data.row_type, row_data.schema, row_data.tb
+                ))}
+            }
+            row_data.convert_raw_string();
+
+            let key = (row_data.schema.clone(), row_data.tb.clone());
+            let buffer = self.buffers.entry(key.clone()).or_default();
+            buffer.push(row_data);
+            if buffer.len() >= self.batch_size {
+                let rows = std::mem::take(buffer);
+                self.flush_rows(key, rows).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn close(&mut self) -> anyhow::Result<()> {
+        let buffers: Vec<_> = self.buffers.drain().collect();
+        for (key, rows) in buffers {
+            if !rows.is_empty() {
+                self.flush_rows(key, rows).await?;
+            }
+        }
+        for (_, writer) in self.writers.iter_mut() {
+            writer.close_file()?;
+        }
+        self.meta_manager.close().await
+    }
+}
+
+impl ParquetSinker {
+    async fn flush_rows(
+        &mut self,
+        key: (String, String),
+        rows: Vec<RowData>,
+    ) -> anyhow::Result<()> {
+        let task_id = self.base_sinker.task_id_for_rows(&rows);
+        self.base_sinker.ensure_monitor_for(&task_id);
+        let data_size: u64 = rows.iter().map(|r| r.get_data_size()).sum();
+
+        let start_time = Instant::now();
+        let mut rts = LimitedQueue::new(1);
+        let result = self.write_rows(key, &rows).await;
+        self.base_sinker.update_table_monitor(&rows, result.is_ok());
+        result?;
+        rts.push((start_time.elapsed().as_millis() as u64, 1));
+
+        self.base_sinker
+            .update_batch_monitor_for(&task_id, rows.len() as u64, data_size)
+            .await?;
+        self.base_sinker.update_monitor_rt_for(&task_id, &rts).await
+    }
+
+    async fn write_rows(&mut self, key: (String, String), rows: &[RowData]) -> anyhow::Result<()> {
+        if !self.writers.contains_key(&key) {
+            let schema = self.get_schema(&key.0, &key.1).await?;
+            let dir = PathBuf::from(&self.root_dir).join(&key.0).join(&key.1);
+            let writer = ParquetTableWriter::new(schema, dir, self.id)?;
+            self.writers.insert(key.clone(), writer);
+        }
+        let writer = self.writers.get_mut(&key).unwrap();
+        let batch = Self::build_record_batch(&writer.schema, rows)?;
+        writer.write(&batch, self.file_max_rows)
+    }
+
+    async fn get_schema(&mut self, schema: &str, tb: &str) -> anyhow::Result<SchemaRef> {
+        if let Some(meta_manager) = self.meta_manager.mysql_meta_manager.as_mut() {
+            let tb_meta = meta_manager.get_tb_meta(schema, tb).await?;
+            let data_types = tb_meta
+                .col_type_map
+                .iter()
+                .map(|(col, col_type)| (col.clone(), Self::get_mysql_data_type(col_type)))
+                .collect();
+            return Ok(Self::build_schema(&tb_meta.basic.cols, &data_types));
+        }
+
+        if let Some(meta_manager) = self.meta_manager.pg_meta_manager.as_mut() {
+            let tb_meta = meta_manager.get_tb_meta(schema, tb).await?;
+            let data_types = tb_meta
+                .col_type_map
+                .iter()
+                .map(|(col, col_type)| (col.clone(), Self::get_pg_data_type(&col_type.value_type)))
+                .collect();
+            return Ok(Self::build_schema(&tb_meta.basic.cols, &data_types));
+        }
+
+        bail! {Error::Unexpected(
+            "no available meta_manager in parquet sinker".into(),
+        )}
+    }
+
+    /// Cols are in the order of the table, all nullable,
+    /// cols of unknown types are written as strings.
+    fn build_schema(cols: &[String], data_types: &HashMap<String, DataType>) -> SchemaRef {
+        let fields: Vec<Field> = cols
+            .iter()
+            .map(|col| {
+                let data_type = data_types.get(col).cloned().unwrap_or(DataType::Utf8);
+                Field::new(col, data_type, true)
+            })
+            .collect();
+        Arc::new(Schema::new(fields))
+    }
+
+    // the types of the col values extracted by MysqlColValueConvertor
+    fn get_mysql_data_type(col_type: &MysqlColType) -> DataType {
+        match col_type {
+            MysqlColType::TinyInt { unsigned: false } => DataType::Int8,
+            MysqlColType::TinyInt { unsigned: true } => DataType::UInt8,
+            MysqlColType::SmallInt { unsigned: false } => DataType::Int16,
+            MysqlColType::SmallInt { unsigned: true } | MysqlColType::Year => DataType::UInt16,
+            MysqlColType::MediumInt { unsigned: false } | MysqlColType::Int { unsigned: false } => {
+                DataType::Int32
+            }
+            MysqlColType::MediumInt { unsigned: true } | MysqlColType::Int { unsigned: true } => {
+                DataType::UInt32
+            }
+            MysqlColType::BigInt { unsigned: false } => DataType::Int64,
+            MysqlColType::BigInt { unsigned: true } | MysqlColType::Bit => DataType::UInt64,
+            MysqlColType::Float => DataType::Float32,
+            MysqlColType::Double => DataType::Float64,
+            MysqlColType::Binary { .. }
+            | MysqlColType::VarBinary { .. }
+            | MysqlColType::TinyBlob
+            | MysqlColType::MediumBlob
+            | MysqlColType::Blob
+            | MysqlColType::LongBlob => DataType::Binary,
+            // decimals are written as strings to keep their precision
+            _ => DataType::Utf8,
+        }
+    }
+
+    // the types of the col values extracted by PgColValueConvertor
+    fn get_pg_data_type(value_type: &PgValueType) -> DataType {
+        match value_type {
+            PgValueType::Boolean => DataType::Boolean,
+            PgValueType::Int16 => DataType::Int16,
+            PgValueType::Int32 => DataType::Int32,
+            PgValueType::Int64 => DataType::Int64,
+            PgValueType::Float32 => DataType::Float32,
+            PgValueType::Float64 => DataType::Float64,
+            PgValueType::Bytes => DataType::Binary,
+            _ => DataType::Utf8,
+        }
+    }
+
+    fn build_record_batch(schema: &SchemaRef, rows: &[RowData]) -> anyhow::Result<RecordBatch> {
+        let none = ColValue::None;
+        for row_data in rows {
+            if let Some(after) = &row_data.after {
+                if let Some(col) = after.keys().find(|col| schema.index_of(col).is_err()) {
+                    bail! {Error::SinkerError(format!(
+                        "col: {} of {}.{} not found in parquet schema",
+                        col, row_data.schema, row_data.tb
+                    ))}
+                }
+            }
+        }
+
+        let mut columns = Vec::with_capacity(schema.fields().len());
+        for field in schema.fields() {
+            let col = field.name();
+            let values: Vec<&ColValue> = rows
+                .iter()
+                .map(|row_data| {
+                    row_data
+                        .after
+                        .as_ref()
+                        .and_then(|after| after.get(col))
+                        .unwrap_or(&none)
+                })
+                .collect();
+            columns.push(Self::build_array(col, field.data_type(), &values)?);
+        }
+        Ok(RecordBatch::try_new(schema.clone(), columns)?)
+    }
+
+    fn build_array(
+        col: &str,
+        data_type: &DataType,
+        values: &[&ColValue],
+    ) -> anyhow::Result<ArrayRef> {
+        macro_rules! primitive_array {
+            ($array:ty, $($variant:ident)|+) => {{
+                let mut items = Vec::with_capacity(values.len());
+                for value in values {
+                    match value {
+                        ColValue::None | ColValue::UnchangedToast => items.push(None),
+                        $(ColValue::$variant(v) => items.push(Some(*v)),)+
+                        _ => bail! {Error::SinkerError(format!(
+                            "col: {} has value: {:?} which does not match parquet type: {}",
+                            col, value, data_type
+                        ))},
+                    }
+                }
+                Arc::new(<$array>::from(items)) as ArrayRef
+            }};
+        }
+
+        let array = match data_type {
+            DataType::Boolean => primitive_array!(BooleanArray, Bool),
+            DataType::Int8 => primitive_array!(Int8Array, Tiny),
+            DataType::UInt8 => primitive_array!(UInt8Array, UnsignedTiny),
+            DataType::Int16 => primitive_array!(Int16Array, Short),
+            DataType::UInt16 => primitive_array!(UInt16Array, UnsignedShort | Year),
+            DataType::Int32 => primitive_array!(Int32Array, Long),
+            DataType::UInt32 => primitive_array!(UInt32Array, UnsignedLong | Enum),
+            DataType::Int64 => primitive_array!(Int64Array, LongLong),
+            DataType::UInt64 => primitive_array!(UInt64Array, UnsignedLongLong | Bit | Set),
+            DataType::Float32 => primitive_array!(Float32Array, Float),
+            DataType::Float64 => primitive_array!(Float64Array, Double),
+            DataType::Binary => {
+                let mut items = Vec::with_capacity(values.len());
+                for value in values {
+                    match value {
+                        ColValue::None | ColValue::UnchangedToast => items.push(None),
+                        ColValue::Blob(v) | ColValue::RawString(v) => {
+                            items.push(Some(v.as_slice()))
+                        }
+                        _ => bail! {Error::SinkerError(format!(
+                            "col: {} has value: {:?} which does not match parquet type: {}",
+                            col, value, data_type
+                        ))},
+                    }
+                }
+                Arc::new(BinaryArray::from(items)) as ArrayRef
+            }
+            _ => {
+                let items: Vec<Option<String>> = values
+                    .iter()
+                    .map(|value| match value {
+                        ColValue::Json(v) => Some(String::from_utf8_lossy(v).to_string()),
+                        _ => value.to_option_string(),
+                    })
+                    .collect();
+                Arc::new(StringArray::from(items)) as ArrayRef
+            }
+        };
+        Ok(array)
+    }
+}
+
+impl ParquetTableWriter {
+    fn new(schema: SchemaRef, dir: PathBuf, sinker_id: usize) -> anyhow::Result<Self> {
+        let file_seq = FileUtil::next_file_seq(&dir, &format!("{}_", sinker_id), "parquet")?;
+        Ok(Self {
+            schema,
+            dir,
+            sinker_id,
+            writer: None,
+            file_rows: 0,
+            file_seq,
+        })
+    }
+
+    fn write(&mut self, batch: &RecordBatch, file_max_rows: usize) -> anyhow::Result<()> {
+        if self.writer.is_some() && self.file_rows >= file_max_rows {
+            self.close_file()?;
+        }
+        if self.writer.is_none() {
+            self.open_file()?;
+        }
+
+        let writer = self.writer.as_mut().unwrap();
+        writer.write(batch)?;
+        // each batch is written as a row group
+        writer.flush()?;
+        self.file_rows += batch.num_rows();
+        Ok(())
+    }
+
+    fn open_file(&mut self) -> anyhow::Result<()> {
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("failed to create dir: {}", self.dir.display()))?;
+        let path = self
+            .dir
+            .join(format!("{}_{}.parquet", self.sinker_id, self.file_seq));
+        let file = File::create(&path)
+            .with_context(|| format!("failed to create parquet file: {}", path.display()))?;
+        let props = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build();
+
+        log_info!("writing parquet file: {}", path.display());
+        self.writer = Some(ArrowWriter::try_new(
+            file,
+            self.schema.clone(),
+            Some(props),
+        )?);
+        self.file_rows = 0;
+        self.file_seq += 1;
+        Ok(())
+    }
+
+    fn close_file(&mut self) -> anyhow::Result<()> {
+        if let Some(writer) = self.writer.take() {
+            writer.close()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::SystemTime;
+
+    use arrow::{
+        array::{Array, AsArray},
+        datatypes::Int32Type,
+    };
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    use super::*;
+
+    fn build_row(id: i32, name: Option<&str>, blob: &[u8]) -> RowData {
+        let after = HashMap::from([
+            ("id".to_string(), ColValue::Long(id)),
+            (
+                "name".to_string(),
+                name.map_or(ColValue::None, |v| ColValue::String(v.into())),
+            ),
+            ("price".to_string(), ColValue::Decimal("12.30".into())),
+            ("data".to_string(), ColValue::Blob(blob.to_vec())),
+            ("note".to_string(), ColValue::None),
+        ]);
+        RowData::new(
+            "db_1".into(),
+            "tb_1".into(),
+            0,
+            RowType::Insert,
+            None,
+            Some(after),
+        )
+    }
+
+    fn build_test_schema() -> SchemaRef {
+        let cols: Vec<String> = ["data", "id", "name", "note", "price"]
+            .iter()
+            .map(|col| col.to_string())
+            .collect();
+        let data_types = HashMap::from([
+            (
+                "data".to_string(),
+                ParquetSinker::get_mysql_data_type(&MysqlColType::Blob),
+            ),
+            (
+                "id".to_string(),
+                ParquetSinker::get_mysql_data_type(&MysqlColType::Int { unsigned: false }),
+            ),
+            (
+                "name".to_string(),
+                ParquetSinker::get_mysql_data_type(&MysqlColType::Varchar {
+                    length: 10,
+                    charset: "utf8mb4".into(),
+                }),
+            ),
+            (
+                "price".to_string(),
+                ParquetSinker::get_mysql_data_type(&MysqlColType::Decimal {
+                    precision: 10,
+                    scale: 2,
+                }),
+            ),
+        ]);
+        ParquetSinker::build_schema(&cols, &data_types)
+    }
+
+    #[test]
+    fn test_build_record_batch() {
+        // types come from the table meta, not from the values of the first rows
+        let rows = vec![
+            build_row(1, None, b"\x01"),
+            build_row(2, Some("b"), b"\x02"),
+        ];
+        let schema = build_test_schema();
+        let types: Vec<(&str, &DataType)> = schema
+            .fields()
+            .iter()
+            .map(|f| (f.name().as_str(), f.data_type()))
+            .collect();
+        assert_eq!(
+            types,
+            vec![
+                ("data", &DataType::Binary),
+                ("id", &DataType::Int32),
+                ("name", &DataType::Utf8),
+                ("note", &DataType::Utf8),
+                ("price", &DataType::Utf8),
+            ]
+        );
+
+        let batch = ParquetSinker::build_record_batch(&schema, &rows).unwrap();
+        assert_eq!(batch.num_rows(), 2);
+        let ids = batch.column(1).as_primitive::<Int32Type>();
+        assert_eq!((ids.value(0), ids.value(1)), (1, 2));
+        let names = batch.column(2).as_string::<i32>();
+        assert!(names.is_null(0));
+        assert_eq!(names.value(1), "b");
+        assert_eq!(batch.column(4).as_string::<i32>().value(0), "12.30");
+        assert_eq!(batch.column(0).as_binary::<i32>().value(1), b"\x02");
+
+        // type mismatch
+        let mut row_data = build_row(3, None, b"");
+        row_data
+            .after
+            .as_mut()
+            .unwrap()
+            .insert("id".into(), ColValue::String("3".into()));
+        assert!(ParquetSinker::build_record_batch(&schema, &[row_data]).is_err());
+
+        let mut row_data = build_row(4, None, b"");
+        row_data
+            .after
+            .as_mut()
+            .unwrap()
+            .insert("data".into(), ColValue::Long(4));
+        let err = ParquetSinker::build_record_batch(&schema, &[row_data]).unwrap_err();
+        assert!(err.to_string().contains("col: data has value: Long(4)"));
+    }
+
+    #[test]
+    fn test_write_rotating_files() {
+        let dir = std::env::temp_dir().join(format!(
+            "ape-dts-parquet-sinker-{}",
+            SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        let rows = vec![build_row(1, Some("a"), b""), build_row(2, Some("b"), b"")];
+        let schema = build_test_schema();
+        let batch = ParquetSinker::build_record_batch(&schema, &rows).unwrap();
+
+        let mut writer = ParquetTableWriter::new(schema.clone(), dir.clone(), 0).unwrap();
+        // 2 row groups in the first file, then rotate
+        writer.write(&batch, 4).unwrap();
+        writer.write(&batch, 4).unwrap();
+        writer.write(&batch, 4).unwrap();
+        writer.close_file().unwrap();
+
+        // a restarted sinker continues with a new file instead of overwriting
+        let mut writer = ParquetTableWriter::new(schema, dir.clone(), 0).unwrap();
+        writer.write(&batch, 4).unwrap();
+        writer.close_file().unwrap();
+
+        let read_rows = |name: &str| {
+            let file = File::open(dir.join(name)).unwrap();
+            let builder = ParquetRecordBatchReaderBuilder::try_new(file).unwrap();
+            let row_groups = builder.metadata().num_row_groups();
+            let rows: usize = builder
+                .build()
+                .unwrap()
+                .map(|b| b.unwrap().num_rows())
+                .sum();
+            (row_groups, rows)
+        };
+        assert_eq!(read_rows("0_0.parquet"), (2, 4));
+        assert_eq!(read_rows("0_1.parquet"), (1, 2));
+        assert_eq!(read_rows("0_2.parquet"), (1, 2));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
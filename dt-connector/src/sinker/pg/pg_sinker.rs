@@ -3,30 +3,38 @@ use std::{cmp, str::FromStr, sync::Arc};
 use anyhow::Context;
 use async_trait::async_trait;
 use sqlx::{
-    postgres::{PgConnectOptions, PgPoolOptions},
+    postgres::{PgArguments, PgConnectOptions, PgPoolCopyExt, PgPoolOptions},
+    query::Query,
     Executor, Pool, Postgres,
 };
 use tokio::{sync::RwLock, time::Instant};
 
-use crate::sinker::checkable_sinker::CheckableSink;
+use crate::sinker::{
+    checkable_sinker::CheckableSink,
+    csv::csv_sinker::{CsvOptions, CsvSinker},
+};
 use crate::{
     call_batch_fn, data_marker::DataMarker, rdb_query_builder::RdbQueryBuilder,
     rdb_router::RdbRouter, sinker::base_sinker::BaseSinker, Sinker,
 };
 use dt_common::{
-    config::connection_auth_config::ConnectionAuthConfig,
+    config::{config_enums::DbType, connection_auth_config::ConnectionAuthConfig},
     log_error, log_info,
     meta::{
+        col_value::ColValue,
         ddl_meta::{ddl_data::DdlData, ddl_type::DdlType},
         dt_data::{DtData, DtItem},
         pg::pg_meta_manager::PgMetaManager,
         position::Position,
+        rdb_tb_meta::RdbTbMeta,
         row_data::RowData,
         row_type::RowType,
     },
-    utils::limit_queue::LimitedQueue,
+    utils::{limit_queue::LimitedQueue, sql_util::SqlUtil},
 };
 
+const COPY_NULL: &str = "\\N";
+
 #[derive(Clone)]
 pub struct PgSinker {
     pub url: String,
@@ -38,6 +46,11 @@ pub struct PgSinker {
     pub base_sinker: BaseSinker,
     pub data_marker: Option<Arc<RwLock<DataMarker>>>,
     pub replace: bool,
+    // inserts update the existing rows on conflicts of the primary key, or the unique key if no primary key
+    pub upsert: bool,
+    // write batched inserts with COPY ... FROM STDIN (FORMAT csv),
+    // falls back to batch_insert if COPY fails, e.g. on conflicts
+    pub use_copy: bool,
 }
 
 #[async_trait]
@@ -51,6 +64,9 @@ impl Sinker for PgSinker {
             self.serial_sink(&data).await?;
         } else {
             match data[0].row_type {
+                RowType::Insert if self.use_copy => {
+                    call_batch_fn!(self, data, Self::batch_copy);
+                }
                 RowType::Insert => {
                     call_batch_fn!(self, data, Self::batch_insert);
                 }
@@ -179,6 +195,9 @@ impl CheckableSink for PgSinker {
             self.serial_sink(data).await?;
         } else {
             match data[0].row_type {
+                RowType::Insert if self.use_copy => {
+                    call_batch_fn!(self, data, Self::batch_copy);
+                }
                 RowType::Insert => {
                     call_batch_fn!(self, data, Self::batch_insert);
                 }
@@ -216,16 +235,25 @@ impl PgSinker {
             let tb_meta = self.meta_manager.get_tb_meta_by_row_data(row_data).await?;
             let query_builder = RdbQueryBuilder::new_for_pg(tb_meta, None);
 
-            let query_info = query_builder.get_query_info(row_data, self.replace)?;
+            let query_info = if self.upsert && matches!(row_data.row_type, RowType::Insert) {
+                query_builder.get_upsert_query_info(row_data)?
+            } else {
+                query_builder.get_query_info(row_data, self.replace)?
+            };
             let query = query_builder.create_pg_query(&query_info)?;
 
             let start_time = Instant::now();
-            query.execute(&mut *tx).await.with_context(|| {
+            let result = query.execute(&mut *tx).await.with_context(|| {
                 format!(
                     "serial sink failed, sql: [{}], row_data: [{}]",
                     query_info.sql, row_data
                 )
-            })?;
+            });
+            if result.is_err() {
+                // the transaction is rolled back
+                self.base_sinker.update_table_monitor(data, false);
+            }
+            result?;
 
             rts.push((start_time.elapsed().as_millis() as u64, 1));
             if last_monitor_time.elapsed().as_secs() >= monitor_interval {
@@ -241,7 +269,9 @@ impl PgSinker {
                 last_monitor_time = Instant::now();
             }
         }
-        tx.commit().await?;
+        let result = tx.commit().await;
+        self.base_sinker.update_table_monitor(data, result.is_ok());
+        result?;
 
         if data_len > 0 || data_size > 0 {
             self.base_sinker
@@ -273,14 +303,10 @@ impl PgSinker {
 
         let start_time = Instant::now();
         let mut rts = LimitedQueue::new(1);
-        if let Some(sql) = self.get_data_marker_sql().await {
-            let mut tx = self.conn_pool.begin().await?;
-            sqlx::query(&sql).execute(&mut *tx).await?;
-            query.execute(&mut *tx).await?;
-            tx.commit().await?;
-        } else {
-            query.execute(&self.conn_pool).await?;
-        }
+        let result = self.execute_with_data_marker(query).await;
+        self.base_sinker
+            .update_table_monitor(&data[start_index..start_index + batch_size], result.is_ok());
+        result?;
         rts.push((start_time.elapsed().as_millis() as u64, 1));
 
         self.base_sinker
@@ -306,25 +332,18 @@ impl PgSinker {
             .to_owned();
         let query_builder = RdbQueryBuilder::new_for_pg(&tb_meta, None);
 
-        let (query_info, data_size) =
-            query_builder.get_batch_insert_query(data, start_index, batch_size, self.replace)?;
+        let (query_info, data_size) = if self.upsert {
+            query_builder.get_batch_upsert_query(data, start_index, batch_size)?
+        } else {
+            query_builder.get_batch_insert_query(data, start_index, batch_size, self.replace)?
+        };
         let query = query_builder.create_pg_query(&query_info)?;
 
         let start_time = Instant::now();
         let mut rts = LimitedQueue::new(1);
-        let exec_error = if let Some(sql) = self.get_data_marker_sql().await {
-            let mut tx = self.conn_pool.begin().await?;
-            sqlx::query(&sql).execute(&mut *tx).await?;
-            query.execute(&mut *tx).await?;
-            tx.commit().await
-        } else {
-            match query.execute(&self.conn_pool).await {
-                Err(e) => Err(e),
-                _ => Ok(()),
-            }
-        };
+        let exec_result = self.execute_with_data_marker(query).await;
 
-        if let Err(error) = exec_error {
+        if let Err(error) = exec_result {
             log_error!(
                 "batch insert failed, will insert one by one, schema: {}, tb: {}, error: {}",
                 tb_meta.basic.schema,
@@ -334,6 +353,8 @@ impl PgSinker {
             let sub_data = &data[start_index..start_index + batch_size];
             self.serial_sink(sub_data).await?;
         } else {
+            self.base_sinker
+                .update_table_monitor(&data[start_index..start_index + batch_size], true);
             rts.push((start_time.elapsed().as_millis() as u64, 1));
         }
 
@@ -343,6 +364,126 @@ impl PgSinker {
         self.base_sinker.update_monitor_rt_for(&task_id, &rts).await
     }
 
+    async fn batch_copy(
+        &mut self,
+        data: &mut [RowData],
+        start_index: usize,
+        batch_size: usize,
+    ) -> anyhow::Result<()> {
+        let task_id = self
+            .base_sinker
+            .source_task_id_for_rows(&data[start_index..start_index + batch_size], &self.router);
+        self.base_sinker.ensure_monitor_for(&task_id);
+        let tb_meta = self
+            .meta_manager
+            .get_tb_meta_by_row_data(&data[0])
+            .await?
+            .to_owned();
+
+        let sub_data = &data[start_index..start_index + batch_size];
+        let sql = Self::build_copy_sql(&tb_meta.basic);
+        let (body, data_size) = Self::build_copy_body(&tb_meta.basic, sub_data)?;
+
+        let start_time = Instant::now();
+        let mut rts = LimitedQueue::new(1);
+        if let Err(error) = self.execute_copy(&sql, body).await {
+            log_error!(
+                "batch copy failed, will insert by batch, schema: {}, tb: {}, error: {}",
+                tb_meta.basic.schema,
+                tb_meta.basic.tb,
+                error.to_string()
+            );
+            return self.batch_insert(data, start_index, batch_size).await;
+        }
+        rts.push((start_time.elapsed().as_millis() as u64, 1));
+        self.base_sinker
+            .update_table_monitor(&data[start_index..start_index + batch_size], true);
+
+        self.base_sinker
+            .update_batch_monitor_for(&task_id, batch_size as u64, data_size as u64)
+            .await?;
+        self.base_sinker.update_monitor_rt_for(&task_id, &rts).await
+    }
+
+    async fn execute_copy(&self, sql: &str, body: String) -> anyhow::Result<u64> {
+        if let Some(marker_sql) = self.get_data_marker_sql().await {
+            let mut tx = self.conn_pool.begin().await?;
+            sqlx::query(&marker_sql).execute(&mut *tx).await?;
+            let mut copy_in = tx.copy_in_raw(sql).await?;
+            copy_in.send(body.as_bytes()).await?;
+            let rows = copy_in.finish().await?;
+            tx.commit().await?;
+            Ok(rows)
+        } else {
+            let mut copy_in = self.conn_pool.copy_in_raw(sql).await?;
+            copy_in.send(body.as_bytes()).await?;
+            Ok(copy_in.finish().await?)
+        }
+    }
+
+    fn build_copy_sql(tb_meta: &RdbTbMeta) -> String {
+        format!(
+            "COPY {}.{}({}) FROM STDIN WITH (FORMAT csv, NULL '{}')",
+            SqlUtil::escape_by_db_type(&tb_meta.schema, &DbType::Pg),
+            SqlUtil::escape_by_db_type(&tb_meta.tb, &DbType::Pg),
+            SqlUtil::escape_cols(&tb_meta.cols, &DbType::Pg).join(","),
+            COPY_NULL
+        )
+    }
+
+    /// One csv line per row, cells follow tb_meta.cols, NULL values are written as \N.
+    fn build_copy_body(tb_meta: &RdbTbMeta, data: &[RowData]) -> anyhow::Result<(String, usize)> {
+        let options = CsvOptions {
+            delimiter: ',',
+            quote: '"',
+            quote_all: false,
+            header: false,
+            file_max_rows: 0,
+            file_max_bytes: 0,
+            hard_delete: true,
+            null_value: COPY_NULL.into(),
+        };
+
+        let mut body = String::new();
+        let mut data_size = 0;
+        for row_data in data.iter() {
+            data_size += row_data.data_size;
+            let after = row_data.require_after()?;
+            let cells: Vec<Option<String>> = tb_meta
+                .cols
+                .iter()
+                .map(|col| after.get(col).and_then(Self::to_copy_cell))
+                .collect();
+            body.push_str(&CsvSinker::build_line(&cells, &options));
+        }
+        Ok((body, data_size))
+    }
+
+    fn to_copy_cell(col_value: &ColValue) -> Option<String> {
+        match col_value {
+            // bytea hex format
+            ColValue::Blob(v) => Some(format!("\\x{}", hex::encode(v))),
+            ColValue::Json(v) => Some(String::from_utf8_lossy(v).to_string()),
+            _ => col_value.to_utf8_or_hex_string(),
+        }
+    }
+
+    // executes the query, in a transaction with the data marker sql if any
+    async fn execute_with_data_marker(
+        &self,
+        query: Query<'_, Postgres, PgArguments>,
+    ) -> anyhow::Result<()> {
+        if let Some(sql) = self.get_data_marker_sql().await {
+            let mut tx = self.conn_pool.begin().await?;
+            sqlx::query(&sql).execute(&mut *tx).await?;
+            query.execute(&mut *tx).await?;
+            tx.commit().await?;
+        } else {
+            query.execute(&self.conn_pool).await?;
+        }
+        Ok(())
+    }
+
     async fn get_data_marker_sql(&self) -> Option<String> {
         if let Some(data_marker) = &self.data_marker {
             let data_marker = data_marker.read().await;
@@ -372,3 +513,61 @@ impl PgSinker {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn test_build_copy_body() {
+        let tb_meta = RdbTbMeta {
+            schema: "test_db".into(),
+            tb: "tb_1".into(),
+            cols: vec!["id".into(), "name".into(), "data".into(), "note".into()],
+            ..Default::default()
+        };
+        let build_row = |id: i32, name: ColValue, data: ColValue| {
+            let after = HashMap::from([
+                ("id".to_string(), ColValue::Long(id)),
+                ("name".to_string(), name),
+                ("data".to_string(), data),
+            ]);
+            RowData::new(
+                "test_db".into(),
+                "tb_1".into(),
+                0,
+                RowType::Insert,
+                None,
+                Some(after),
+            )
+        };
+        let data = vec![
+            build_row(
+                1,
+                ColValue::String("a,\"b\"\nc".into()),
+                ColValue::Blob(vec![1, 2]),
+            ),
+            build_row(2, ColValue::None, ColValue::String(String::new())),
+            build_row(3, ColValue::String("\\N".into()), ColValue::None),
+        ];
+
+        assert_eq!(
+            PgSinker::build_copy_sql(&tb_meta),
+            r#"COPY "test_db"."tb_1"("id","name","data","note") FROM STDIN WITH (FORMAT csv, NULL '\N')"#
+        );
+        let (body, data_size) = PgSinker::build_copy_body(&tb_meta, &data).unwrap();
+        // the missing col note is NULL, the string \N is quoted to be kept as is
+        assert_eq!(
+            body,
+            "1,\"a,\"\"b\"\"\nc\",\\x0102,\\N\n2,\\N,,\\N\n3,\"\\N\",\\N,\\N\n"
+        );
+        assert_eq!(
+            data_size,
+            data.iter()
+                .map(|row_data| row_data.data_size)
+                .sum::<usize>()
+        );
+    }
+}
@@ -3,10 +3,14 @@ use dt_common::error::Error;
 use dt_common::meta::redis::{
     redis_entry::RedisEntry,
     redis_object::{
-        HashObject, ListObject, ModuleObject, RedisCmd, SetObject, StringObject, ZsetObject,
+        HashObject, ListObject, ModuleObject, RedisCmd, RedisObject, SetObject, StringObject,
+        ZsetObject,
     },
+    redis_write_method::RedisWriteMethod,
 };
 
+use crate::rdb_router::RdbRouter;
+
 const CRC64_TABLE: [u64; 256] = [
     0x0000000000000000,
     0x7ad870c830358979,
@@ -269,6 +273,64 @@ const CRC64_TABLE: [u64; 256] = [
 pub struct EntryRewriter {}
 
 impl EntryRewriter {
+    /// Rewrites the entry into the cmds to run on the target, a SELECT is prepended
+    /// if the routed db of the entry differs from now_db_id, which is then updated.
+    pub fn rewrite_entry(
+        entry: &mut RedisEntry,
+        method: &RedisWriteMethod,
+        version: f32,
+        router: Option<&RdbRouter>,
+        now_db_id: &mut i64,
+    ) -> anyhow::Result<Vec<RedisCmd>> {
+        let mut cmds = Vec::new();
+        let dst_db_id = if let Some(router) = router {
+            router.route_redis_db_id(entry.db_id)?
+        } else {
+            entry.db_id
+        };
+
+        if dst_db_id != *now_db_id {
+            let db_id = &dst_db_id.to_string();
+            let args = vec!["SELECT", db_id];
+            let cmd = RedisCmd::from_str_args(&args);
+            cmds.push(cmd);
+            *now_db_id = dst_db_id;
+        }
+
+        match method {
+            RedisWriteMethod::Restore => {
+                if entry.is_raw() {
+                    let cmd = Self::rewrite_as_restore(entry, version)?;
+                    cmds.push(cmd);
+                } else {
+                    cmds.push(entry.cmd.clone());
+                }
+            }
+
+            RedisWriteMethod::Rewrite => {
+                let mut rewrite_cmds = match entry.value {
+                    RedisObject::String(ref mut obj) => Self::rewrite_string(obj),
+                    RedisObject::List(ref mut obj) => Self::rewrite_list(obj),
+                    RedisObject::Set(ref mut obj) => Self::rewrite_set(obj),
+                    RedisObject::Hash(ref mut obj) => Self::rewrite_hash(obj),
+                    RedisObject::Zset(ref mut obj) => Self::rewrite_zset(obj),
+                    RedisObject::Stream(ref mut obj) => Ok(obj.cmds.drain(..).collect()),
+                    RedisObject::Module(_) => {
+                        // TODO: support rewrite some 8.0+ major module2 types, such as: JSON, Bloom, CountMinSketch, TDigest, TopK, TimeSeries
+                        let cmd = Self::rewrite_as_restore(entry, version)?;
+                        Ok(vec![cmd])
+                    }
+                    _ => bail! {Error::SinkerError("rewrite not implemented".into())},
+                }?;
+                if let Some(expire_cmd) = Self::rewrite_expire(entry)? {
+                    rewrite_cmds.push(expire_cmd)
+                }
+                cmds.extend(rewrite_cmds);
+            }
+        }
+        Ok(cmds)
+    }
+
     pub fn rewrite_hash(obj: &mut HashObject) -> anyhow::Result<Vec<RedisCmd>> {
         let mut cmds = vec![];
         for (k, v) in &obj.value {
@@ -0,0 +1,279 @@
+use std::{
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::PathBuf,
+};
+
+use anyhow::Context;
+use async_trait::async_trait;
+use tokio::time::Instant;
+
+use dt_common::{
+    log_info,
+    meta::{
+        dt_data::{DtData, DtItem},
+        redis::{command::cmd_encoder::CmdEncoder, redis_write_method::RedisWriteMethod},
+    },
+    utils::{file_util::FileUtil, limit_queue::LimitedQueue},
+};
+
+use super::entry_rewriter::EntryRewriter;
+use crate::{rdb_router::RdbRouter, sinker::base_sinker::BaseSinker, Sinker};
+
+/// Writes the RESP encoded cmds of redis entries to files: {dump_dir}/{sinker_id}_{file_seq}.resp,
+/// in the order they are received, which can be replayed by `redis-cli --pipe`.
+/// Entries are rewritten the same way as RedisSinker does, cluster and non-cluster sources alike,
+/// every file starts with a SELECT so it can be replayed alone.
+/// A new file is started once the current one reaches file_max_bytes,
+/// files of previous runs are kept, a restarted sinker continues from the next file_seq.
+pub struct RedisDumpSinker {
+    pub id: usize,
+    pub dump_dir: String,
+    // 0 means no limit
+    pub file_max_bytes: usize,
+    pub method: RedisWriteMethod,
+    pub version: f32,
+    pub router: Option<RdbRouter>,
+    now_db_id: i64,
+    pub base_sinker: BaseSinker,
+    writer: Option<BufWriter<File>>,
+    file_bytes: usize,
+}
+
+#[async_trait]
+impl Sinker for RedisDumpSinker {
+    async fn sink_raw(&mut self, mut data: Vec<DtItem>, _batch: bool) -> anyhow::Result<()> {
+        if data.is_empty() {
+            return Ok(());
+        }
+
+        let start_time = Instant::now();
+        let mut rts = LimitedQueue::new(1);
+        let mut data_size = 0;
+        let mut cmd_count = 0;
+
+        for dt_item in data.iter_mut() {
+            data_size += dt_item.get_data_size();
+            if let DtData::Redis { entry } = &mut dt_item.dt_data {
+                self.rotate_file()?;
+                let cmds = EntryRewriter::rewrite_entry(
+                    entry,
+                    &self.method,
+                    self.version,
+                    self.router.as_ref(),
+                    &mut self.now_db_id,
+                )?;
+                for cmd in cmds.iter() {
+                    self.write(&CmdEncoder::encode(cmd))?;
+                }
+                cmd_count += cmds.len();
+            }
+        }
+        if let Some(writer) = self.writer.as_mut() {
+            writer.flush()?;
+        }
+        rts.push((start_time.elapsed().as_millis() as u64, 1));
+
+        self.base_sinker
+            .update_batch_monitor(cmd_count as u64, data_size)
+            .await?;
+        self.base_sinker.update_monitor_rt(&rts).await
+    }
+
+    async fn close(&mut self) -> anyhow::Result<()> {
+        self.close_file()
+    }
+}
+
+impl RedisDumpSinker {
+    pub fn new(
+        id: usize,
+        dump_dir: String,
+        file_max_bytes: usize,
+        method: RedisWriteMethod,
+        version: f32,
+        router: Option<RdbRouter>,
+        base_sinker: BaseSinker,
+    ) -> Self {
+        Self {
+            id,
+            dump_dir,
+            file_max_bytes,
+            method,
+            version,
+            router,
+            now_db_id: -1,
+            base_sinker,
+            writer: None,
+            file_bytes: 0,
+        }
+    }
+
+    // cmds of an entry are never split across files
+    fn rotate_file(&mut self) -> anyhow::Result<()> {
+        let reach_max_bytes = self.file_max_bytes > 0 && self.file_bytes >= self.file_max_bytes;
+        if self.writer.is_some() && reach_max_bytes {
+            self.close_file()?;
+        }
+        if self.writer.is_none() {
+            self.open_file()?;
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, buf: &[u8]) -> anyhow::Result<()> {
+        self.writer.as_mut().unwrap().write_all(buf)?;
+        self.file_bytes += buf.len();
+        Ok(())
+    }
+
+    fn open_file(&mut self) -> anyhow::Result<()> {
+        let dir = PathBuf::from(&self.dump_dir);
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("failed to create dir: {}", dir.display()))?;
+        let prefix = format!("{}_", self.id);
+        let file_seq = FileUtil::next_file_seq(&dir, &prefix, "resp")?;
+        let path = dir.join(format!("{}{}.resp", prefix, file_seq));
+        let file = File::options()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .with_context(|| format!("failed to create dump file: {}", path.display()))?;
+
+        log_info!("writing redis dump file: {}", path.display());
+        self.writer = Some(BufWriter::new(file));
+        self.file_bytes = 0;
+        // force a SELECT at the head of the new file
+        self.now_db_id = -1;
+        Ok(())
+    }
+
+    fn close_file(&mut self) -> anyhow::Result<()> {
+        if let Some(mut writer) = self.writer.take() {
+            writer.flush()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::Path, sync::Arc, time::SystemTime};
+
+    use dt_common::{
+        config::config_enums::{TaskKind, TaskType},
+        meta::{
+            position::Position,
+            redis::{redis_entry::RedisEntry, redis_object::RedisCmd},
+        },
+        monitor::{
+            monitor::Monitor,
+            task_monitor::{MonitorType, TaskMonitor},
+            task_monitor_handle::TaskMonitorHandle,
+        },
+    };
+
+    use super::*;
+
+    fn build_item(db_id: i64, args: &[&str]) -> DtItem {
+        let mut entry = RedisEntry::new();
+        entry.db_id = db_id;
+        entry.cmd = RedisCmd::from_str_args(args);
+        DtItem {
+            dt_data: DtData::Redis { entry },
+            position: Position::None,
+            data_origin_node: String::new(),
+        }
+    }
+
+    fn build_sinker(dir: &Path, file_max_bytes: usize) -> RedisDumpSinker {
+        let task_id = "redis_dump_task";
+        let task_monitor = Arc::new(TaskMonitor::new(Some(TaskType::new(TaskKind::Cdc, None))));
+        let monitor = Arc::new(Monitor::new("sinker", "redis_dump", 60, 100, 100));
+        task_monitor.register(task_id, vec![(MonitorType::Sinker, monitor)]);
+        let handle = TaskMonitorHandle::new(
+            task_monitor,
+            MonitorType::Sinker,
+            task_id.into(),
+            60,
+            100,
+            100,
+        );
+        RedisDumpSinker::new(
+            0,
+            dir.to_string_lossy().to_string(),
+            file_max_bytes,
+            RedisWriteMethod::Restore,
+            7.0,
+            None,
+            BaseSinker::new(handle, 10),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_dump_set_cmds() {
+        let dir = std::env::temp_dir().join(format!(
+            "ape-dts-redis-dump-sinker-{}",
+            SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+
+        let mut sinker = build_sinker(&dir, 0);
+        let data = vec![
+            build_item(0, &["SET", "k1", "v1"]),
+            build_item(0, &["SET", "k2", "v2"]),
+            build_item(1, &["SET", "k3", "value3"]),
+        ];
+        sinker.sink_raw(data, false).await.unwrap();
+        sinker.close().await.unwrap();
+
+        let expected = concat!(
+            "*2\r\n$6\r\nSELECT\r\n$1\r\n0\r\n",
+            "*3\r\n$3\r\nSET\r\n$2\r\nk1\r\n$2\r\nv1\r\n",
+            "*3\r\n$3\r\nSET\r\n$2\r\nk2\r\n$2\r\nv2\r\n",
+            "*2\r\n$6\r\nSELECT\r\n$1\r\n1\r\n",
+            "*3\r\n$3\r\nSET\r\n$2\r\nk3\r\n$6\r\nvalue3\r\n",
+        );
+        assert_eq!(fs::read(dir.join("0_0.resp")).unwrap(), expected.as_bytes());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_dump_rotating_files() {
+        let dir = std::env::temp_dir().join(format!(
+            "ape-dts-redis-dump-sinker-rotate-{}",
+            SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+
+        // SELECT 0 takes 23 bytes and each SET 29 bytes, so every file holds 2 SETs
+        let mut sinker = build_sinker(&dir, 60);
+        let data = (1..=3)
+            .map(|i| build_item(0, &["SET", &format!("k{}", i), &format!("v{}", i)]))
+            .collect();
+        sinker.sink_raw(data, false).await.unwrap();
+        sinker.close().await.unwrap();
+
+        let select = "*2\r\n$6\r\nSELECT\r\n$1\r\n0\r\n";
+        let set = |i: i32| format!("*3\r\n$3\r\nSET\r\n$2\r\nk{i}\r\n$2\r\nv{i}\r\n");
+        let read = |name: &str| fs::read_to_string(dir.join(name)).unwrap();
+        assert_eq!(read("0_0.resp"), format!("{select}{}{}", set(1), set(2)));
+        assert_eq!(read("0_1.resp"), format!("{select}{}", set(3)));
+
+        // a restarted sinker keeps the files of the previous run
+        let mut sinker = build_sinker(&dir, 60);
+        let data = vec![build_item(0, &["SET", "k4", "v4"])];
+        sinker.sink_raw(data, false).await.unwrap();
+        sinker.close().await.unwrap();
+        assert_eq!(read("0_0.resp"), format!("{select}{}{}", set(1), set(2)));
+        assert_eq!(read("0_1.resp"), format!("{select}{}", set(3)));
+        assert_eq!(read("0_2.resp"), format!("{select}{}", set(4)));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{slice, sync::Arc};
 
 use anyhow::bail;
 use async_trait::async_trait;
@@ -18,7 +18,6 @@ use dt_common::meta::redis::cluster_node::ClusterNode;
 use dt_common::meta::redis::command::cmd_encoder::CmdEncoder;
 use dt_common::meta::redis::command::key_parser::KeyParser;
 use dt_common::meta::redis::redis_object::RedisCmd;
-use dt_common::meta::redis::redis_object::RedisObject;
 use dt_common::meta::redis::redis_write_method::RedisWriteMethod;
 use dt_common::meta::row_data::RowData;
 use dt_common::meta::row_type::RowType;
@@ -121,55 +120,16 @@ impl RedisSinker {
     }
 
     fn rewrite_entry(&mut self, dt_data: &mut DtData) -> anyhow::Result<Vec<RedisCmd>> {
-        let mut cmds = Vec::new();
         if let DtData::Redis { entry } = dt_data {
-            let dst_db_id = if let Some(router) = &self.router {
-                router.route_redis_db_id(entry.db_id)?
-            } else {
-                entry.db_id
-            };
-
-            if dst_db_id != self.now_db_id {
-                let db_id = &dst_db_id.to_string();
-                let args = vec!["SELECT", db_id];
-                let cmd = RedisCmd::from_str_args(&args);
-                cmds.push(cmd);
-                self.now_db_id = dst_db_id;
-            }
-
-            match self.method {
-                RedisWriteMethod::Restore => {
-                    if entry.is_raw() {
-                        let cmd = EntryRewriter::rewrite_as_restore(entry, self.version)?;
-                        cmds.push(cmd);
-                    } else {
-                        cmds.push(entry.cmd.clone());
-                    }
-                }
-
-                RedisWriteMethod::Rewrite => {
-                    let mut rewrite_cmds = match entry.value {
-                        RedisObject::String(ref mut obj) => EntryRewriter::rewrite_string(obj),
-                        RedisObject::List(ref mut obj) => EntryRewriter::rewrite_list(obj),
-                        RedisObject::Set(ref mut obj) => EntryRewriter::rewrite_set(obj),
-                        RedisObject::Hash(ref mut obj) => EntryRewriter::rewrite_hash(obj),
-                        RedisObject::Zset(ref mut obj) => EntryRewriter::rewrite_zset(obj),
-                        RedisObject::Stream(ref mut obj) => Ok(obj.cmds.drain(..).collect()),
-                        RedisObject::Module(_) => {
-                            // TODO: support rewrite some 8.0+ major module2 types, such as: JSON, Bloom, CountMinSketch, TDigest, TopK, TimeSeries
-                            let cmd = EntryRewriter::rewrite_as_restore(entry, self.version)?;
-                            Ok(vec![cmd])
-                        }
-                        _ => bail! {Error::SinkerError("rewrite not implemented".into())},
-                    }?;
-                    if let Some(expire_cmd) = EntryRewriter::rewrite_expire(entry)? {
-                        rewrite_cmds.push(expire_cmd)
-                    }
-                    cmds.extend(rewrite_cmds);
-                }
-            }
+            return EntryRewriter::rewrite_entry(
+                entry,
+                &self.method,
+                self.version,
+                self.router.as_ref(),
+                &mut self.now_db_id,
+            );
         }
-        Ok(cmds)
+        Ok(Vec::new())
     }
 }
 
@@ -194,7 +154,10 @@ impl RedisSinker {
                 cmds.push(cmd);
             }
         }
-        self.batch_sink(&cmds).await?;
+        let result = self.batch_sink(&cmds).await;
+        self.base_sinker
+            .update_table_monitor(&data[start_index..start_index + batch_size], result.is_ok());
+        result?;
 
         self.base_sinker
             .update_batch_monitor_for(&task_id, cmds.len() as u64, data_size)
@@ -209,7 +172,10 @@ impl RedisSinker {
         for row_data in data.iter() {
             data_size += row_data.get_data_size();
             if let Some(cmd) = self.dml_to_redis_cmd(row_data).await? {
-                self.batch_sink(&[cmd]).await?
+                let result = self.batch_sink(&[cmd]).await;
+                self.base_sinker
+                    .update_table_monitor(slice::from_ref(row_data), result.is_ok());
+                result?;
             }
         }
 
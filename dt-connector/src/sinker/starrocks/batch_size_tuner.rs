@@ -0,0 +1,31 @@
+/// Adapts the stream load batch_size within [min_batch_size, max_batch_size] by the LoadTimeMs
+/// reported by the target: doubled after a full batch loaded within half of target_load_time_ms,
+/// halved after a load slower than target_load_time_ms or a failed load.
+#[derive(Clone, Debug)]
+pub struct BatchSizeTuner {
+    pub min_batch_size: usize,
+    pub max_batch_size: usize,
+    pub target_load_time_ms: u64,
+}
+
+impl BatchSizeTuner {
+    pub fn clamp(&self, batch_size: usize) -> usize {
+        batch_size.clamp(self.min_batch_size, self.max_batch_size)
+    }
+
+    // sent_batch_size is smaller than batch_size for the tail of the data,
+    // a fast load of a partial batch says nothing about a larger one
+    pub fn on_loaded(&self, batch_size: usize, sent_batch_size: usize, load_time_ms: u64) -> usize {
+        if load_time_ms > self.target_load_time_ms {
+            self.clamp(batch_size / 2)
+        } else if load_time_ms < self.target_load_time_ms / 2 && sent_batch_size >= batch_size {
+            self.clamp(batch_size.saturating_mul(2))
+        } else {
+            self.clamp(batch_size)
+        }
+    }
+
+    pub fn on_failed(&self, batch_size: usize) -> usize {
+        self.clamp(batch_size / 2)
+    }
+}
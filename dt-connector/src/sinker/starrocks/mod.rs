@@ -1,2 +1,3 @@
+pub mod batch_size_tuner;
 pub mod starrocks_sinker;
 pub mod starrocks_struct_sinker;
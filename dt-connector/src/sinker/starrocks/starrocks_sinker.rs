@@ -1,38 +1,77 @@
-use std::{cmp, collections::HashMap, str::FromStr};
+use std::{
+    cmp,
+    collections::{HashMap, HashSet},
+    fs,
+    io::Write,
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
 
-use anyhow::bail;
+use anyhow::{bail, Context};
 use async_trait::async_trait;
 use chrono::Utc;
-use reqwest::{header, Client, Method, Response, StatusCode};
+use flate2::{write::GzEncoder, Compression};
+use futures::TryStreamExt;
+use lz4_flex::frame::FrameEncoder;
+use reqwest::{
+    header, redirect::Policy, Certificate, Client, Method, RequestBuilder, Response, StatusCode,
+};
 use serde_json::Value;
+use sha2::{Digest, Sha256};
 use tokio::time::Instant;
 
 use dt_common::{
-    config::config_enums::DbType,
+    config::config_enums::{BinaryEncoding, DbType},
     error::Error,
-    log_error,
+    limiter::sink_rate_limiter::SinkRateLimiter,
+    log_error, log_info, log_warn,
     meta::{
+        col_projection::ColProjection,
         col_value::ColValue,
         mysql::{
             mysql_col_type::MysqlColType, mysql_meta_manager::MysqlMetaManager,
             mysql_tb_meta::MysqlTbMeta,
         },
+        position::Position,
         row_data::RowData,
         row_type::RowType,
+        unsupported_col_value_handler::UnsupportedColValueHandler,
     },
     utils::{limit_queue::LimitedQueue, sql_util::SqlUtil},
 };
 
+use super::batch_size_tuner::BatchSizeTuner;
 use crate::{call_batch_fn, sinker::base_sinker::BaseSinker, Sinker};
 
 const SIGN_COL_NAME: &str = "_ape_dts_is_deleted";
 const TIMESTAMP_COL_NAME: &str = "_ape_dts_timestamp";
+// exposed by doris tables with batch delete enabled, visible if show_hidden_columns=true
+const DORIS_DELETE_SIGN_COL_NAME: &str = "__DORIS_DELETE_SIGN__";
+// https://docs.starrocks.io/docs/sql-reference/sql-statements/loading_unloading/STREAM_LOAD/
+const MAX_LABEL_LEN: usize = 128;
+// the fe redirects a stream load to a be once, more hops are allowed for proxies in front of the fe
+const MAX_STREAM_LOAD_REDIRECTS: usize = 3;
+
+// parsed from the stream load response
+#[derive(Debug, Default, PartialEq)]
+struct StreamLoadStats {
+    loaded_rows: u64,
+    // rows rejected by the target for bad data, which are silently lost unless max_filter_ratio=0
+    filtered_rows: u64,
+    load_time_ms: Option<u64>,
+    // details of the filtered rows
+    error_url: Option<String>,
+}
 
 #[derive(Clone)]
 pub struct StarRocksSinker {
+    pub id: usize,
     pub db_type: DbType,
     pub batch_size: usize,
     pub http_client: Client,
+    // http or https, taken from stream_load_url
+    pub scheme: String,
     pub host: String,
     pub port: String,
     pub username: String,
@@ -41,6 +80,33 @@ pub struct StarRocksSinker {
     pub base_sinker: BaseSinker,
     pub sync_timestamp: i64,
     pub hard_delete: bool,
+    // log stream load requests instead of sending them
+    pub dry_run: bool,
+    // set explicit stream load labels so retried batches are deduplicated by the target
+    pub label_prefix: Option<String>,
+    // gzip or lz4_frame, only supported by starrocks
+    pub compression: Option<String>,
+    pub col_value_handler: UnsupportedColValueHandler,
+    // removes the cols not wanted by the target, key cols are kept
+    pub col_projection: ColProjection,
+    // limits the stream loads of all sub sinkers, throttled loads wait instead of failing
+    pub rate_limiter: Option<Arc<SinkRateLimiter>>,
+    // timeout of each stream load request, overrides the timeout of http_client if set
+    pub request_timeout: Option<Duration>,
+    // how binary col values are written to VARBINARY/STRING cols
+    pub binary_encoding: BinaryEncoding,
+    // adapts batch_size to the load time of each stream load, None keeps it fixed
+    pub batch_size_tuner: Option<BatchSizeTuner>,
+    // sync_mode or async_mode, only supported by doris, ignored for starrocks
+    pub group_commit: Option<String>,
+    // source positions of the pipeline batch being sunk, None if the rows carry no position,
+    // and the rows of it sunk by previous sink_dml calls, they label a stream load with the
+    // digest of the rows it carries
+    pub batch_positions: Option<String>,
+    pub batch_row_offset: usize,
+    // key cols of primary/unique key tables taken from SHOW CREATE TABLE,
+    // None for duplicate/aggregate key tables which keep every loaded row
+    pub table_keys: HashMap<(String, String), Option<Vec<String>>>,
 }
 
 #[async_trait]
@@ -50,11 +116,23 @@ impl Sinker for StarRocksSinker {
             return Ok(());
         }
 
+        let row_count = data.len();
         if !batch {
             self.serial_sink(data.as_mut_slice()).await?;
         } else {
             call_batch_fn!(self, data, Self::batch_sink);
         }
+        self.batch_row_offset += row_count;
+        Ok(())
+    }
+
+    async fn begin_dml_batch(&mut self, first: &Position, last: &Position) -> anyhow::Result<()> {
+        // rows without positions (e.g. snapshot rows) can not be told apart on replay
+        self.batch_positions = match (first, last) {
+            (Position::None, _) | (_, Position::None) => None,
+            _ => Some(format!("{}|{}", first, last)),
+        };
+        self.batch_row_offset = 0;
         Ok(())
     }
 
@@ -70,7 +148,10 @@ impl StarRocksSinker {
         let mut data_size = 0;
         for i in 0..data.len() {
             data_size += data[i].get_data_size();
-            self.send_data(data, i, 1).await?;
+            let result = self.send_data(data, i, 1).await;
+            self.base_sinker
+                .update_table_monitor(&data[i..i + 1], result.is_ok());
+            result?;
         }
 
         self.base_sinker
@@ -78,17 +159,63 @@ impl StarRocksSinker {
             .await
     }
 
+    // rows of a stream load share one op, so a batch mixing deletes which need their own op
+    // with other rows is loaded by contiguous runs in order, later rows of a key still win
     async fn batch_sink(
         &mut self,
         data: &mut [RowData],
         start_index: usize,
         batch_size: usize,
+    ) -> anyhow::Result<()> {
+        let first = &data[start_index];
+        let tb_meta = self
+            .meta_manager
+            .get_tb_meta(&first.schema, &first.tb)
+            .await?;
+        let delete_op = Self::get_op(&self.db_type, self.hard_delete, &RowType::Delete, tb_meta);
+        if delete_op.is_empty() {
+            // deletes are loaded as upserts of the sign col
+            return self.load_batch(data, start_index, batch_size).await;
+        }
+
+        let runs = Self::split_delete_runs(&data[start_index..start_index + batch_size]);
+        for (offset, run_size) in runs {
+            self.load_batch(data, start_index + offset, run_size)
+                .await?;
+        }
+        Ok(())
+    }
+
+    // (offset, size) of contiguous runs of deletes and other rows
+    fn split_delete_runs(data: &[RowData]) -> Vec<(usize, usize)> {
+        let is_delete = |row_data: &RowData| row_data.row_type == RowType::Delete;
+        let mut runs: Vec<(usize, usize)> = Vec::new();
+        for (i, row_data) in data.iter().enumerate() {
+            match runs.last_mut() {
+                Some((offset, size)) if is_delete(&data[*offset]) == is_delete(row_data) => {
+                    *size += 1
+                }
+                _ => runs.push((i, 1)),
+            }
+        }
+        runs
+    }
+
+    async fn load_batch(
+        &mut self,
+        data: &mut [RowData],
+        start_index: usize,
+        batch_size: usize,
     ) -> anyhow::Result<()> {
         let task_id = self
             .base_sinker
             .task_id_for_rows(&data[start_index..start_index + batch_size]);
         self.base_sinker.ensure_monitor_for(&task_id);
-        let data_size = self.send_data(data, start_index, batch_size).await?;
+        let result = self.send_data(data, start_index, batch_size).await;
+        self.base_sinker
+            .update_table_monitor(&data[start_index..start_index + batch_size], result.is_ok());
+        self.tune_batch_size(batch_size, &result);
+        let (data_size, _) = result?;
 
         self.base_sinker
             .update_batch_monitor_for(&task_id, batch_size as u64, data_size as u64)
@@ -100,77 +227,398 @@ impl StarRocksSinker {
         data: &mut [RowData],
         start_index: usize,
         batch_size: usize,
-    ) -> anyhow::Result<usize> {
+    ) -> anyhow::Result<(usize, Option<u64>)> {
         let db = data[start_index].schema.clone();
         let tb = data[start_index].tb.clone();
         let first_row_type = data[start_index].row_type.clone();
+        let key_cols = self.get_table_keys(&db, &tb).await?;
         let tb_meta = self.meta_manager.get_tb_meta(&db, &tb).await?;
         self.sync_timestamp = cmp::max(Utc::now().timestamp_millis(), self.sync_timestamp + 1);
 
-        let mut data_size = 0;
         let mut rts = LimitedQueue::new(1);
-        // build stream load data
-        let mut load_data = Vec::with_capacity(batch_size);
-        for row_data in data.iter_mut().skip(start_index).take(batch_size) {
+        let (load_data, data_size) = Self::build_load_data(
+            &self.db_type,
+            self.sync_timestamp,
+            &self.col_value_handler,
+            &mut self.col_projection,
+            &self.binary_encoding,
+            &mut data[start_index..start_index + batch_size],
+            tb_meta,
+            key_cols.as_deref(),
+        )?;
+
+        let op = Self::get_op(&self.db_type, self.hard_delete, &first_row_type, tb_meta);
+        let columns = Self::build_columns(&self.db_type, &load_data, tb_meta, op);
+
+        // group committed loads are labeled by the target, an explicit label is rejected,
+        // a load replayed from the same source positions carries the same rows and label
+        let label = match (
+            self.group_commit_mode(),
+            &self.label_prefix,
+            &self.batch_positions,
+        ) {
+            (None, Some(prefix), Some(positions)) => {
+                let digest = Self::build_load_digest(
+                    positions,
+                    self.batch_row_offset + start_index,
+                    op,
+                    columns.as_deref(),
+                    &load_data,
+                )?;
+                Some(Self::build_label(prefix, self.id, &db, &tb, &digest))
+            }
+            _ => None,
+        };
+        // do stream load
+        let url = Self::build_stream_load_url(&self.scheme, &self.host, &self.port, &db, &tb);
+
+        if self.dry_run {
+            let body = Self::build_body(None, &load_data, data_size)?;
+            log_info!(
+                "dry run stream load, url: {}, op: {}, columns: {}, label: {}, body: {}",
+                url,
+                op,
+                columns.as_deref().unwrap_or_default(),
+                label.as_deref().unwrap_or_default(),
+                String::from_utf8_lossy(&body)
+            );
+            self.build_request(
+                &url,
+                op,
+                columns.as_deref(),
+                Self::build_body(self.compression.as_deref(), &load_data, data_size)?,
+                label.as_deref(),
+            )?;
+            return Ok((data_size, None));
+        }
+
+        // data_size counts the uncompressed rows, the request carries the compressed bytes
+        let body = Self::build_body(self.compression.as_deref(), &load_data, data_size)?;
+        let body_size = body.len();
+        let request = self.build_request(&url, op, columns.as_deref(), body, label.as_deref())?;
+        let task_id = self.base_sinker.task_id_for_schema_tb(&db, &tb);
+        self.base_sinker.ensure_monitor_for(&task_id);
+
+        if let Some(rate_limiter) = &self.rate_limiter {
+            let wait_time = rate_limiter.acquire(body_size).await?;
+            self.base_sinker
+                .update_monitor_throttle_for(&task_id, wait_time.as_millis() as u64)
+                .await;
+        }
+
+        let start_time = Instant::now();
+        let response = Self::execute_request(&self.http_client, request).await?;
+        rts.push((start_time.elapsed().as_millis() as u64, 1));
+        self.base_sinker
+            .update_monitor_rt_for(&task_id, &rts)
+            .await?;
+
+        let stats = Self::check_response(response).await?;
+        Self::record_load_stats(&self.base_sinker, &task_id, &url, &stats).await;
+
+        Ok((data_size, stats.load_time_ms))
+    }
+
+    async fn record_load_stats(
+        base_sinker: &BaseSinker,
+        task_id: &str,
+        url: &str,
+        stats: &StreamLoadStats,
+    ) {
+        if let Some(warning) = Self::filtered_rows_warning(url, stats) {
+            log_warn!("{}", warning);
+        }
+        base_sinker
+            .update_monitor_load_for(
+                task_id,
+                stats.loaded_rows,
+                stats.filtered_rows,
+                stats.load_time_ms,
+            )
+            .await;
+    }
+
+    fn filtered_rows_warning(url: &str, stats: &StreamLoadStats) -> Option<String> {
+        if stats.filtered_rows == 0 {
+            return None;
+        }
+        Some(format!(
+            "stream load filtered {} rows, url: {}, error_url: {}",
+            stats.filtered_rows,
+            url,
+            stats.error_url.as_deref().unwrap_or_default()
+        ))
+    }
+
+    // the new batch_size takes effect from the next batch of call_batch_fn
+    fn tune_batch_size(
+        &mut self,
+        sent_batch_size: usize,
+        result: &anyhow::Result<(usize, Option<u64>)>,
+    ) {
+        let Some(tuner) = &self.batch_size_tuner else {
+            return;
+        };
+        let batch_size = match result {
+            Ok((_, Some(load_time_ms))) => {
+                tuner.on_loaded(self.batch_size, sent_batch_size, *load_time_ms)
+            }
+            Ok((_, None)) => return,
+            Err(_) => tuner.on_failed(self.batch_size),
+        };
+        if batch_size != self.batch_size {
+            log_info!(
+                "stream load batch_size of sinker {} tuned from {} to {}",
+                self.id,
+                self.batch_size,
+                batch_size
+            );
+            self.batch_size = batch_size;
+        }
+    }
+
+    // build stream load data, rows are routed so they carry the target col names
+    fn build_load_data<'a>(
+        db_type: &DbType,
+        sync_timestamp: i64,
+        col_value_handler: &UnsupportedColValueHandler,
+        col_projection: &mut ColProjection,
+        binary_encoding: &BinaryEncoding,
+        data: &'a mut [RowData],
+        tb_meta: &MysqlTbMeta,
+        key_cols: Option<&[String]>,
+    ) -> anyhow::Result<(Vec<&'a mut HashMap<String, ColValue>>, usize)> {
+        let mut data_size = 0;
+        let superseded_rows = Self::get_superseded_rows(data, key_cols);
+        let mut load_data = Vec::with_capacity(data.len() - superseded_rows.len());
+        for (i, row_data) in data.iter_mut().enumerate() {
             data_size += row_data.get_data_size() as usize;
+            if superseded_rows.contains(&i) {
+                continue;
+            }
             let is_delete = row_data.row_type == RowType::Delete;
-            Self::convert_row_data(row_data, tb_meta)?;
+            col_value_handler.handle_row_by_target(row_data, |col, col_value| {
+                Self::is_binary_target(tb_meta, col, col_value)
+            })?;
+            col_projection.project_row(row_data, &tb_meta.basic.id_cols);
+            Self::convert_row_data(row_data, tb_meta, binary_encoding)?;
             let col_values = Self::active_col_values_mut(row_data)?;
 
-            if is_delete && self.db_type == DbType::StarRocks {
-                // SIGN_COL value
-                col_values.insert(SIGN_COL_NAME.into(), ColValue::Long(1));
+            if is_delete {
+                match db_type {
+                    // SIGN_COL value
+                    DbType::StarRocks => {
+                        col_values.insert(SIGN_COL_NAME.into(), ColValue::Long(1));
+                    }
+                    // the delete is loaded as an upsert of the delete sign col
+                    DbType::Doris if Self::has_doris_delete_sign(tb_meta) => {
+                        col_values.insert(DORIS_DELETE_SIGN_COL_NAME.into(), ColValue::Long(1));
+                    }
+                    _ => {}
+                }
             }
 
-            if self.db_type == DbType::StarRocks {
+            if *db_type == DbType::StarRocks {
                 col_values.insert(
                     TIMESTAMP_COL_NAME.into(),
-                    ColValue::LongLong(self.sync_timestamp),
+                    ColValue::LongLong(sync_timestamp),
                 );
             }
 
             load_data.push(col_values);
         }
+        Ok((load_data, data_size))
+    }
 
-        let mut op = "";
-        if self.db_type == DbType::StarRocks {
-            let hard_delete = self.hard_delete
-                || !tb_meta
-                    .basic
-                    .col_origin_type_map
-                    .contains_key(SIGN_COL_NAME);
-            if first_row_type == RowType::Delete && hard_delete {
-                op = "delete";
+    // the loaded cols in the order of the target table, followed by __op='{op}' for deletes,
+    // target cols not listed take their defaults, so rows missing them still load,
+    // hidden cols like SIGN_COL_NAME and TIMESTAMP_COL_NAME are listed only if the table has them,
+    // col names are quoted so reserved words like `order` are accepted
+    // https://docs.starrocks.io/docs/loading/Load_to_Primary_Key_tables/
+    // https://docs.starrocks.io/docs/loading/Stream_Load_transaction_interface/
+    fn build_columns(
+        db_type: &DbType,
+        load_data: &[&mut HashMap<String, ColValue>],
+        tb_meta: &MysqlTbMeta,
+        op: &str,
+    ) -> Option<String> {
+        if *db_type != DbType::StarRocks {
+            return None;
+        }
+        let mut columns: Vec<String> = tb_meta
+            .basic
+            .cols
+            .iter()
+            .filter(|col| {
+                load_data
+                    .iter()
+                    .any(|col_values| col_values.contains_key(*col))
+            })
+            .map(|col| format!("`{}`", col.replace('`', "``")))
+            .collect();
+        if !op.is_empty() {
+            columns.push(format!("__op='{}'", op));
+        }
+        if columns.is_empty() {
+            None
+        } else {
+            Some(columns.join(","))
+        }
+    }
+
+    // primary/unique key tables only keep the last row of each key, so the earlier rows of a key
+    // in the batch are not loaded, a trailing delete is kept as the last row,
+    // the keys are those of the target table, not of the source
+    fn get_superseded_rows(data: &[RowData], key_cols: Option<&[String]>) -> HashSet<usize> {
+        let mut superseded_rows = HashSet::new();
+        let Some(key_cols) = key_cols else {
+            return superseded_rows;
+        };
+
+        // key col values -> index of the last row
+        let mut last_rows: HashMap<Vec<Option<String>>, usize> = HashMap::new();
+        for (i, row_data) in data.iter().enumerate() {
+            let col_values = match row_data.row_type {
+                RowType::Delete => &row_data.before,
+                _ => &row_data.after,
+            };
+            let Some(col_values) = col_values else {
+                continue;
+            };
+            let key = key_cols
+                .iter()
+                .map(|col| col_values.get(col).and_then(|v| v.to_option_string()))
+                .collect();
+            if let Some(last_row) = last_rows.insert(key, i) {
+                superseded_rows.insert(last_row);
             }
-        } else if first_row_type == RowType::Delete {
-            op = "delete";
         }
+        superseded_rows
+    }
 
-        let body = serde_json::to_string(&load_data)?;
-        // do stream load
-        let url = format!(
-            "http://{}:{}/api/{}/{}/_stream_load",
-            self.host, self.port, db, tb
+    async fn get_table_keys(&mut self, db: &str, tb: &str) -> anyhow::Result<Option<Vec<String>>> {
+        let full_name = (db.to_string(), tb.to_string());
+        if let Some(key_cols) = self.table_keys.get(&full_name) {
+            return Ok(key_cols.clone());
+        }
+
+        let sql = format!(
+            "SHOW CREATE TABLE `{}`.`{}`",
+            db.replace('`', "``"),
+            tb.replace('`', "``")
+        );
+        let mut rows = sqlx::raw_sql(&sql).fetch(&self.meta_manager.meta_fetcher.conn_pool);
+        let create_sql = match rows.try_next().await? {
+            Some(row) => SqlUtil::try_get_mysql_optional_string(&row, 1)?.unwrap_or_default(),
+            None => String::new(),
+        };
+        let key_cols = Self::parse_table_keys(&create_sql);
+        log_info!(
+            "stream load dedupe keys of `{}`.`{}`: {:?}",
+            db,
+            tb,
+            key_cols
         );
-        let request = self.build_request(&url, op, body)?;
+        self.table_keys.insert(full_name, key_cols.clone());
+        Ok(key_cols)
+    }
 
-        let start_time = Instant::now();
-        let response = self.http_client.execute(request).await?;
-        rts.push((start_time.elapsed().as_millis() as u64, 1));
-        let task_id = self.base_sinker.task_id_for_schema_tb(&db, &tb);
-        self.base_sinker.ensure_monitor_for(&task_id);
-        self.base_sinker
-            .update_monitor_rt_for(&task_id, &rts)
-            .await?;
+    // the key cols of PRIMARY KEY(..) / UNIQUE KEY(..) in the output of SHOW CREATE TABLE,
+    // DUPLICATE KEY and AGGREGATE KEY tables keep all rows or merge them, so no keys are returned
+    fn parse_table_keys(create_sql: &str) -> Option<Vec<String>> {
+        for line in create_sql.lines() {
+            let line = line.trim();
+            let upper = line.to_ascii_uppercase();
+            let rest = if let Some(rest) = upper.strip_prefix("PRIMARY KEY") {
+                rest
+            } else if let Some(rest) = upper.strip_prefix("UNIQUE KEY") {
+                rest
+            } else {
+                continue;
+            };
+            // keep the original case of the col names
+            let rest = line[line.len() - rest.len()..].trim_start();
+            let Some(rest) = rest.strip_prefix('(') else {
+                continue;
+            };
+
+            let mut key_cols = Vec::new();
+            let mut col = String::new();
+            let mut chars = rest.chars().peekable();
+            let mut quoted = false;
+            while let Some(c) = chars.next() {
+                match c {
+                    '`' if quoted && chars.peek() == Some(&'`') => {
+                        chars.next();
+                        col.push('`');
+                    }
+                    '`' => quoted = !quoted,
+                    _ if quoted => col.push(c),
+                    ',' | ')' => {
+                        key_cols.push(col.trim().to_string());
+                        col.clear();
+                        if c == ')' {
+                            break;
+                        }
+                    }
+                    _ => col.push(c),
+                }
+            }
+            if !key_cols.is_empty() {
+                return Some(key_cols);
+            }
+        }
+        None
+    }
 
-        Self::check_response(response).await?;
+    // empty op means upsert, deletes are loaded as upserts of the sign col if possible
+    fn get_op(
+        db_type: &DbType,
+        hard_delete: bool,
+        row_type: &RowType,
+        tb_meta: &MysqlTbMeta,
+    ) -> &'static str {
+        if *row_type != RowType::Delete {
+            return "";
+        }
+        let hard_delete = match db_type {
+            DbType::StarRocks => {
+                hard_delete
+                    || !tb_meta
+                        .basic
+                        .col_origin_type_map
+                        .contains_key(SIGN_COL_NAME)
+            }
+            // tables without batch delete enabled can only take merge_type=DELETE
+            _ => !Self::has_doris_delete_sign(tb_meta),
+        };
+        if hard_delete {
+            "delete"
+        } else {
+            ""
+        }
+    }
+
+    // VARBINARY/BINARY cols take binaries encoded by binary_encoding without loss
+    fn is_binary_target(tb_meta: &MysqlTbMeta, col: &str, col_value: &ColValue) -> bool {
+        matches!(col_value, ColValue::Blob(_))
+            && matches!(
+                tb_meta.col_type_map.get(col),
+                Some(MysqlColType::Binary { .. } | MysqlColType::VarBinary { .. })
+            )
+    }
 
-        Ok(data_size)
+    fn has_doris_delete_sign(tb_meta: &MysqlTbMeta) -> bool {
+        tb_meta
+            .basic
+            .col_origin_type_map
+            .contains_key(DORIS_DELETE_SIGN_COL_NAME)
     }
 
     fn convert_col_values(
         col_values: &mut HashMap<String, ColValue>,
         tb_meta: &MysqlTbMeta,
+        binary_encoding: &BinaryEncoding,
     ) -> anyhow::Result<()> {
         let mut new_col_values: HashMap<String, ColValue> = HashMap::new();
         for (col, col_value) in col_values.iter() {
@@ -190,7 +638,21 @@ impl StarRocksSinker {
             }
 
             match col_value {
-                ColValue::Blob(v) | ColValue::RawString(v) => {
+                // NULL must be sent as json null and never conflated with "",
+                // stream load treats them differently for NOT NULL cols with defaults
+                ColValue::None => {
+                    new_col_values.insert(col.to_owned(), ColValue::Json3(Value::Null));
+                }
+
+                ColValue::Blob(v) => {
+                    new_col_values.insert(
+                        col.to_owned(),
+                        ColValue::String(SqlUtil::binary_to_encoded_str(v, binary_encoding)),
+                    );
+                }
+
+                // strings of non utf8 charsets, e.g. gbk
+                ColValue::RawString(v) => {
                     new_col_values.insert(
                         col.to_owned(),
                         ColValue::String(SqlUtil::binary_to_str(v).0),
@@ -201,6 +663,13 @@ impl StarRocksSinker {
                     new_col_values.insert(col.to_owned(), ColValue::LongLong(*v as i64));
                 }
 
+                // WKT, which can be read by ST_GeomFromText
+                ColValue::Geometry(_) => {
+                    if let Some(wkt) = col_value.to_option_string() {
+                        new_col_values.insert(col.to_owned(), ColValue::String(wkt));
+                    }
+                }
+
                 _ => {}
             }
         }
@@ -211,12 +680,16 @@ impl StarRocksSinker {
         Ok(())
     }
 
-    fn convert_row_data(row_data: &mut RowData, tb_meta: &MysqlTbMeta) -> anyhow::Result<()> {
+    fn convert_row_data(
+        row_data: &mut RowData,
+        tb_meta: &MysqlTbMeta,
+        binary_encoding: &BinaryEncoding,
+    ) -> anyhow::Result<()> {
         if let Some(before) = &mut row_data.before {
-            Self::convert_col_values(before, tb_meta)?;
+            Self::convert_col_values(before, tb_meta, binary_encoding)?;
         }
         if let Some(after) = &mut row_data.after {
-            Self::convert_col_values(after, tb_meta)?;
+            Self::convert_col_values(after, tb_meta, binary_encoding)?;
         }
         Ok(())
     }
@@ -230,7 +703,162 @@ impl StarRocksSinker {
         }
     }
 
-    fn build_request(&self, url: &str, op: &str, body: String) -> anyhow::Result<reqwest::Request> {
+    // label: {prefix}_{db}_{tb}_{sinker_id}_{digest}, where digest is from build_load_digest,
+    // chars other than [-_:a-zA-Z0-9] are replaced by '_',
+    // and {prefix}_{db}_{tb} is truncated to fit the length limit
+    fn build_label(prefix: &str, id: usize, db: &str, tb: &str, digest: &str) -> String {
+        let suffix = format!("_{}_{}", id, digest);
+        let mut label: String = format!("{}_{}_{}", prefix, db, tb)
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':') {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        label.truncate(MAX_LABEL_LEN.saturating_sub(suffix.len()));
+        label.push_str(&suffix);
+        label
+    }
+
+    // sha256 of the source positions of the pipeline batch, the first row of the load in the rows
+    // of the batch given to the sinker, the op and the rows, a label taken by a finished load
+    // therefore proves the same rows were loaded, the cols of each row are hashed in name order
+    // without the sync timestamp, so a replayed load of the same rows has the same digest
+    fn build_load_digest(
+        positions: &str,
+        row_offset: usize,
+        op: &str,
+        columns: Option<&str>,
+        load_data: &[&mut HashMap<String, ColValue>],
+    ) -> anyhow::Result<String> {
+        let mut hasher = Sha256::new();
+        hasher.update(positions.as_bytes());
+        hasher.update((row_offset as u64).to_le_bytes());
+        hasher.update(op.as_bytes());
+        hasher.update([0]);
+        hasher.update(columns.unwrap_or_default().as_bytes());
+        for col_values in load_data.iter() {
+            let mut cols: Vec<&String> = col_values
+                .keys()
+                .filter(|col| *col != TIMESTAMP_COL_NAME)
+                .collect();
+            cols.sort();
+            hasher.update([0]);
+            for col in cols {
+                hasher.update(col.as_bytes());
+                hasher.update([0]);
+                hasher.update(serde_json::to_vec(&col_values[col])?);
+                hasher.update([0]);
+            }
+        }
+        // 128 bits keep the label short
+        Ok(hex::encode(&hasher.finalize()[..16]))
+    }
+
+    // rows are serialized into the (compressed) body one by one instead of building the whole
+    // json array as a string first, the uncompressed bytes are the same as
+    // serde_json::to_string(&load_data), size_hint presizes the uncompressed body
+    fn build_body(
+        compression: Option<&str>,
+        load_data: &[&mut HashMap<String, ColValue>],
+        size_hint: usize,
+    ) -> anyhow::Result<Vec<u8>> {
+        let Some(compression) = compression else {
+            return Self::write_rows(Vec::with_capacity(size_hint), load_data);
+        };
+        match compression {
+            "gzip" => {
+                let encoder = GzEncoder::new(Vec::new(), Compression::default());
+                Ok(Self::write_rows(encoder, load_data)?.finish()?)
+            }
+            "lz4_frame" => {
+                let encoder = FrameEncoder::new(Vec::new());
+                Ok(Self::write_rows(encoder, load_data)?.finish()?)
+            }
+            _ => bail! {Error::SinkerError(format!(
+                "unsupported stream load compression: {}",
+                compression
+            ))},
+        }
+    }
+
+    // a json array of rows, as expected with strip_outer_array=true
+    fn write_rows<W: Write>(
+        mut writer: W,
+        load_data: &[&mut HashMap<String, ColValue>],
+    ) -> anyhow::Result<W> {
+        writer.write_all(b"[")?;
+        for (i, col_values) in load_data.iter().enumerate() {
+            if i > 0 {
+                writer.write_all(b",")?;
+            }
+            serde_json::to_writer(&mut writer, col_values)?;
+        }
+        writer.write_all(b"]")?;
+        Ok(writer)
+    }
+
+    pub fn build_http_client(
+        connect_timeout_secs: u64,
+        ssl_ca_path: &str,
+        accept_invalid_certs: bool,
+    ) -> anyhow::Result<Client> {
+        // redirects are followed by execute_request, which re-sends the body and all headers
+        let mut builder = Client::builder()
+            .http1_title_case_headers()
+            .redirect(Policy::none());
+        if connect_timeout_secs > 0 {
+            builder = builder.connect_timeout(Duration::from_secs(connect_timeout_secs));
+        }
+        if !ssl_ca_path.is_empty() {
+            let pem = fs::read(ssl_ca_path)
+                .with_context(|| format!("failed to read stream load ssl ca: {}", ssl_ca_path))?;
+            let cert = Certificate::from_pem(&pem)
+                .with_context(|| format!("failed to parse stream load ssl ca: {}", ssl_ca_path))?;
+            builder = builder.add_root_certificate(cert);
+        }
+        if accept_invalid_certs {
+            builder = builder.danger_accept_invalid_certs(true);
+        }
+        Ok(builder.build()?)
+    }
+
+    fn group_commit_mode(&self) -> Option<&str> {
+        match self.db_type {
+            DbType::Doris => self.group_commit.as_deref(),
+            _ => None,
+        }
+    }
+
+    fn add_group_commit_header(
+        put: RequestBuilder,
+        db_type: &DbType,
+        group_commit: Option<&str>,
+    ) -> RequestBuilder {
+        match (db_type, group_commit) {
+            (DbType::Doris, Some(mode)) => put.header("group_commit", mode),
+            _ => put,
+        }
+    }
+
+    fn build_stream_load_url(scheme: &str, host: &str, port: &str, db: &str, tb: &str) -> String {
+        format!(
+            "{}://{}:{}/api/{}/{}/_stream_load",
+            scheme, host, port, db, tb
+        )
+    }
+
+    fn build_request(
+        &self,
+        url: &str,
+        op: &str,
+        columns: Option<&str>,
+        body: Vec<u8>,
+        label: Option<&str>,
+    ) -> anyhow::Result<reqwest::Request> {
         let password = if self.password.is_empty() {
             None
         } else {
@@ -246,40 +874,93 @@ impl StarRocksSinker {
             .header("strip_outer_array", "true")
             .header("timezone", "UTC")
             .body(body);
+        if let Some(timeout) = self.request_timeout {
+            put = put.timeout(timeout);
+        }
+        // both starrocks and doris reject a label which was used by a successful load
+        if let Some(label) = label {
+            put = put.header("label", label);
+        }
+        // https://doris.apache.org/docs/data-operate/import/group-commit-manual
+        put = Self::add_group_commit_header(put, &self.db_type, self.group_commit.as_deref());
+        // https://docs.starrocks.io/docs/loading/StreamLoad/#compression
+        if let Some(compression) = &self.compression {
+            put = put.header("compression", compression);
+        }
+        // starrocks only, carries the __op of deletes
+        if let Some(columns) = columns {
+            put = put.header("columns", columns);
+        }
         // by default, the __op will be upsert
-        if !op.is_empty() {
-            match self.db_type {
-                DbType::StarRocks => {
-                    // https://docs.starrocks.io/docs/loading/Load_to_Primary_Key_tables/
-                    // https://docs.starrocks.io/docs/loading/Stream_Load_transaction_interface/
-                    let op = format!("__op='{}'", op);
-                    put = put.header("columns", op);
-                }
-                DbType::Doris => {
-                    // https://doris.apache.org/docs/1.2/data-operate/update-delete/batch-delete-manual
-                    // https://doris.apache.org/docs/1.2/data-operate/import/import-way/stream-load-manual
-                    // if bulk delete support is enabled (enable_batch_delete_by_default=true or ALTER TABLE tablename ENABLE FEATURE "BATCH_DELETE"),
-                    // there will be 2 hidden columns for each table:
-                    // Doris > DESC `test_db`.`tb_1`;
-                    // +-----------------------+---------+------+-------+---------+-------+
-                    // | Field                 | Type    | Null | Key   | Default | Extra |
-                    // +-----------------------+---------+------+-------+---------+-------+
-                    // | id                    | INT     | No   | true  | NULL    |       |
-                    // | value                 | INT     | Yes  | false | NULL    | NONE  |
-                    // | __DORIS_DELETE_SIGN__ | TINYINT | No   | false | 0       | NONE  |
-                    // | __DORIS_VERSION_COL__ | BIGINT  | No   | false | 0       | NONE  |
-                    // +-----------------------+---------+------+-------+---------+-------+
-                    put = put.header("merge_type", op);
-                }
-                _ => {}
-            }
+        if !op.is_empty() && self.db_type == DbType::Doris {
+            // https://doris.apache.org/docs/1.2/data-operate/update-delete/batch-delete-manual
+            // https://doris.apache.org/docs/1.2/data-operate/import/import-way/stream-load-manual
+            // if bulk delete support is enabled (enable_batch_delete_by_default=true or ALTER TABLE tablename ENABLE FEATURE "BATCH_DELETE"),
+            // there will be 2 hidden columns for each table:
+            // Doris > DESC `test_db`.`tb_1`;
+            // +-----------------------+---------+------+-------+---------+-------+
+            // | Field                 | Type    | Null | Key   | Default | Extra |
+            // +-----------------------+---------+------+-------+---------+-------+
+            // | id                    | INT     | No   | true  | NULL    |       |
+            // | value                 | INT     | Yes  | false | NULL    | NONE  |
+            // | __DORIS_DELETE_SIGN__ | TINYINT | No   | false | 0       | NONE  |
+            // | __DORIS_VERSION_COL__ | BIGINT  | No   | false | 0       | NONE  |
+            // +-----------------------+---------+------+-------+---------+-------+
+            put = put.header("merge_type", op);
         }
         Ok(put.build()?)
     }
 
-    async fn check_response(response: Response) -> anyhow::Result<()> {
+    // the fe answers a stream load with 307 and the location of a be, the request is re-sent to
+    // the location with the same auth, headers and body,
+    // timed out loads fail with HttpError so the task can be recovered from the last checkpoint
+    async fn execute_request(
+        http_client: &Client,
+        mut request: reqwest::Request,
+    ) -> anyhow::Result<Response> {
+        for _ in 0..=MAX_STREAM_LOAD_REDIRECTS {
+            let url = request.url().clone();
+            let next_request = request.try_clone();
+            let response = match http_client.execute(request).await {
+                Ok(response) => response,
+                Err(err) if err.is_timeout() => bail! {Error::HttpError(format!(
+                    "stream load request timed out, url: {}, error: {}",
+                    url, err
+                ))},
+                Err(err) => return Err(err.into()),
+            };
+
+            if !response.status().is_redirection() {
+                return Ok(response);
+            }
+            let Some(location) = response.headers().get(header::LOCATION).cloned() else {
+                return Ok(response);
+            };
+            let Some(next_request) = next_request else {
+                bail! {Error::HttpError(format!(
+                    "stream load request can not be redirected, url: {}",
+                    url
+                ))}
+            };
+            request = next_request;
+            *request.url_mut() = url.join(location.to_str()?)?;
+        }
+        bail! {Error::HttpError(format!(
+            "stream load request redirected more than {} times",
+            MAX_STREAM_LOAD_REDIRECTS
+        ))}
+    }
+
+    async fn check_response(response: Response) -> anyhow::Result<StreamLoadStats> {
         let status_code = response.status();
-        let response_text = &response.text().await?;
+        let response_text = response.text().await?;
+        Self::check_load_result(status_code, &response_text)
+    }
+
+    fn check_load_result(
+        status_code: StatusCode,
+        response_text: &str,
+    ) -> anyhow::Result<StreamLoadStats> {
         if status_code != StatusCode::OK {
             bail! {Error::HttpError(format!(
                 "data load request failed, status_code: {}, response_text: {:?}",
@@ -305,7 +986,24 @@ impl StarRocksSinker {
         //     "WriteDataTimeMs": 107,
         //     "CommitAndPublishTimeMs": 36
         // }
+        // group committed loads of doris answer with "GroupCommit": true and a label generated
+        // by the target like "group_commit_c84d2099208436ab_96e33fda01eddba8", the rows of
+        // async_mode are written to the wal and become visible later, but the load is done
         let json_value: Value = serde_json::from_str(response_text)?;
+        // a replayed load whose label was taken by a finished load, the label carries the digest
+        // of the rows, so the same rows are already there
+        if json_value["Status"] == "Label Already Exists"
+            && matches!(
+                json_value["ExistingJobStatus"].as_str(),
+                Some("FINISHED") | Some("VISIBLE")
+            )
+        {
+            log_info!(
+                "stream load skipped, label: {} is already loaded",
+                json_value["Label"].as_str().unwrap_or_default()
+            );
+            return Ok(StreamLoadStats::default());
+        }
         if json_value["Status"] != "Success" {
             let err = format!(
                 "stream load request failed, status_code: {}, load_result: {}",
@@ -314,6 +1012,1450 @@ impl StarRocksSinker {
             log_error!("{}", err);
             bail! {Error::HttpError(err)}
         }
-        Ok(())
+        Ok(StreamLoadStats {
+            loaded_rows: json_value["NumberLoadedRows"].as_u64().unwrap_or_default(),
+            filtered_rows: json_value["NumberFilteredRows"]
+                .as_u64()
+                .unwrap_or_default(),
+            load_time_ms: json_value["LoadTimeMs"].as_u64(),
+            error_url: json_value["ErrorURL"].as_str().map(String::from),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use base64::{engine::general_purpose, Engine as _};
+
+    use dt_common::{
+        config::{
+            config_enums::{TaskKind, TaskType, UnsupportedColValuePolicy},
+            router_config::RouterConfig,
+        },
+        meta::{
+            mysql::mysql_meta_fetcher::MysqlMetaFetcher, rdb_tb_meta::RdbTbMeta,
+            unsupported_col_value_handler::ColValueCapability,
+        },
+        monitor::{
+            counter_type::CounterType,
+            monitor::Monitor,
+            task_monitor::{MonitorType, TaskMonitor},
+            task_monitor_handle::TaskMonitorHandle,
+        },
+    };
+    use sqlx::mysql::MySqlPoolOptions;
+
+    use super::*;
+    use crate::rdb_router::RdbRouter;
+
+    #[test]
+    fn test_load_data_of_renamed_cols() {
+        let config = RouterConfig::Rdb {
+            schema_map: String::new(),
+            tb_map: String::new(),
+            col_map:
+                r#"json:[{"db":"db_1","tb":"tb_1","col_map":{"id":"user_id","name":"user_name"}}]"#
+                    .into(),
+            topic_map: String::new(),
+            doc_projection: String::new(),
+        };
+        let router = RdbRouter::from_config(&config, &DbType::Mysql)
+            .unwrap()
+            .unwrap();
+
+        // target meta, the pk col id is renamed to user_id, the non-pk col name to user_name
+        let tb_meta = MysqlTbMeta {
+            basic: RdbTbMeta {
+                schema: "db_1".into(),
+                tb: "tb_1".into(),
+                cols: vec!["user_id".into(), "user_name".into(), "age".into()],
+                key_map: HashMap::from([("primary".into(), vec!["user_id".into()])]),
+                order_cols: vec!["user_id".into()],
+                id_cols: vec!["user_id".into()],
+                ..Default::default()
+            },
+            col_type_map: HashMap::from([
+                ("user_id".into(), MysqlColType::BigInt { unsigned: false }),
+                (
+                    "user_name".into(),
+                    MysqlColType::Varchar {
+                        length: 100,
+                        charset: "utf8mb4".into(),
+                    },
+                ),
+                ("age".into(), MysqlColType::Int { unsigned: false }),
+            ]),
+        };
+
+        let col_values = HashMap::from([
+            ("id".to_string(), ColValue::Long(1)),
+            ("name".to_string(), ColValue::String("a".into())),
+            ("age".to_string(), ColValue::Long(10)),
+        ]);
+        let mut data: Vec<RowData> = [
+            (RowType::Insert, None, Some(col_values.clone())),
+            (RowType::Delete, Some(col_values), None),
+        ]
+        .into_iter()
+        .map(|(row_type, before, after)| {
+            router.route_row(RowData::new(
+                "db_1".into(),
+                "tb_1".into(),
+                0,
+                row_type,
+                before,
+                after,
+            ))
+        })
+        .collect();
+
+        let (load_data, _) = StarRocksSinker::build_load_data(
+            &DbType::StarRocks,
+            100,
+            &UnsupportedColValueHandler::new(ColValueCapability::STARROCKS, Default::default()),
+            &mut ColProjection::default(),
+            &BinaryEncoding::default(),
+            &mut data,
+            &tb_meta,
+            None,
+        )
+        .unwrap();
+        let body: Value = serde_json::to_value(&load_data).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!([
+                {"user_id": 1, "user_name": "a", "age": 10, TIMESTAMP_COL_NAME: 100},
+                {"user_id": 1, "user_name": "a", "age": 10, SIGN_COL_NAME: 1, TIMESTAMP_COL_NAME: 100},
+            ])
+        );
+    }
+
+    #[test]
+    fn test_load_data_of_same_key() {
+        // the source table has no keys, the keys of the target table decide the dedupe
+        let tb_meta = MysqlTbMeta {
+            basic: RdbTbMeta {
+                schema: "db_1".into(),
+                tb: "tb_1".into(),
+                cols: vec!["id".into(), "value".into()],
+                ..Default::default()
+            },
+            col_type_map: HashMap::from([
+                ("id".into(), MysqlColType::Int { unsigned: false }),
+                ("value".into(), MysqlColType::Int { unsigned: false }),
+            ]),
+        };
+        let build_row = |row_type: RowType, id: i32, value: i32| {
+            let col_values = HashMap::from([
+                ("id".to_string(), ColValue::Long(id)),
+                ("value".to_string(), ColValue::Long(value)),
+            ]);
+            let (before, after) = match row_type {
+                RowType::Insert => (None, Some(col_values)),
+                RowType::Update => (Some(col_values.clone()), Some(col_values)),
+                RowType::Delete => (Some(col_values), None),
+            };
+            RowData::new("db_1".into(), "tb_1".into(), 0, row_type, before, after)
+        };
+        let load = |mut data: Vec<RowData>, key_cols: Option<&[String]>| {
+            // superseded rows are still counted
+            let expected_size: u64 = data.iter().map(|row_data| row_data.get_data_size()).sum();
+            let (load_data, data_size) = StarRocksSinker::build_load_data(
+                &DbType::StarRocks,
+                100,
+                &UnsupportedColValueHandler::new(ColValueCapability::STARROCKS, Default::default()),
+                &mut ColProjection::default(),
+                &BinaryEncoding::default(),
+                &mut data,
+                &tb_meta,
+                key_cols,
+            )
+            .unwrap();
+            assert_eq!(data_size as u64, expected_size);
+            serde_json::to_value(&load_data).unwrap()
+        };
+
+        let starrocks_primary_key = StarRocksSinker::parse_table_keys(
+            r#"CREATE TABLE `tb_1` (
+  `id` int(11) NOT NULL COMMENT "",
+  `value` int(11) NULL COMMENT ""
+) ENGINE=OLAP
+PRIMARY KEY(`id`)
+DISTRIBUTED BY HASH(`id`)
+PROPERTIES (
+"replication_num" = "1"
+);"#,
+        );
+        assert_eq!(starrocks_primary_key, Some(vec!["id".to_string()]));
+        let doris_unique_key = StarRocksSinker::parse_table_keys(
+            r#"CREATE TABLE `tb_1` (
+  `id` INT NULL,
+  `value` INT NULL
+) ENGINE=OLAP
+UNIQUE KEY(`id`)
+DISTRIBUTED BY HASH(`id`) BUCKETS 10
+PROPERTIES (
+"enable_unique_key_merge_on_write" = "true"
+);"#,
+        );
+        assert_eq!(doris_unique_key, Some(vec!["id".to_string()]));
+        let duplicate_key = StarRocksSinker::parse_table_keys(
+            r#"CREATE TABLE `tb_1` (
+  `id` int(11) NULL COMMENT "",
+  `value` int(11) NULL COMMENT ""
+) ENGINE=OLAP
+DUPLICATE KEY(`id`)
+DISTRIBUTED BY HASH(`id`)
+PROPERTIES (
+"replication_num" = "1"
+);"#,
+        );
+        assert_eq!(duplicate_key, None);
+
+        // 3 updates of id 1 collapse to the last one
+        let data = vec![
+            build_row(RowType::Update, 1, 10),
+            build_row(RowType::Insert, 2, 20),
+            build_row(RowType::Update, 1, 11),
+            build_row(RowType::Update, 1, 12),
+        ];
+        assert_eq!(
+            load(data, starrocks_primary_key.as_deref()),
+            serde_json::json!([
+                {"id": 2, "value": 20, TIMESTAMP_COL_NAME: 100},
+                {"id": 1, "value": 12, TIMESTAMP_COL_NAME: 100},
+            ])
+        );
+
+        // a trailing delete wins
+        let data = vec![
+            build_row(RowType::Update, 1, 10),
+            build_row(RowType::Update, 1, 11),
+            build_row(RowType::Insert, 2, 20),
+            build_row(RowType::Update, 1, 12),
+            build_row(RowType::Delete, 1, 12),
+        ];
+        assert_eq!(
+            load(data, doris_unique_key.as_deref()),
+            serde_json::json!([
+                {"id": 2, "value": 20, TIMESTAMP_COL_NAME: 100},
+                {"id": 1, "value": 12, SIGN_COL_NAME: 1, TIMESTAMP_COL_NAME: 100},
+            ])
+        );
+
+        // duplicate key tables keep every row
+        let data = vec![
+            build_row(RowType::Insert, 1, 10),
+            build_row(RowType::Insert, 1, 11),
+        ];
+        assert_eq!(
+            load(data, duplicate_key.as_deref()),
+            serde_json::json!([
+                {"id": 1, "value": 10, TIMESTAMP_COL_NAME: 100},
+                {"id": 1, "value": 11, TIMESTAMP_COL_NAME: 100},
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_table_keys() {
+        let create_sql = "CREATE TABLE `tb_1` (
+  `a``b` int NOT NULL,
+  `Order` int NOT NULL
+) ENGINE=OLAP
+primary key (`a``b`, `Order`)
+DISTRIBUTED BY HASH(`a``b`)";
+        assert_eq!(
+            StarRocksSinker::parse_table_keys(create_sql),
+            Some(vec!["a`b".to_string(), "Order".to_string()])
+        );
+
+        let create_sql = "CREATE TABLE `tb_1` (
+  `k` int NULL,
+  `v` bigint SUM NULL
+) ENGINE=OLAP
+AGGREGATE KEY(`k`)";
+        assert_eq!(StarRocksSinker::parse_table_keys(create_sql), None);
+        assert_eq!(StarRocksSinker::parse_table_keys(""), None);
+    }
+
+    #[test]
+    fn test_load_data_of_unsupported_col_value() {
+        let tb_meta = MysqlTbMeta {
+            basic: RdbTbMeta {
+                schema: "db_1".into(),
+                tb: "tb_1".into(),
+                cols: vec!["id".into(), "geo".into()],
+                ..Default::default()
+            },
+            col_type_map: HashMap::from([
+                ("id".into(), MysqlColType::Int { unsigned: false }),
+                ("geo".into(), MysqlColType::Blob),
+            ]),
+        };
+
+        let build_body = |policy: UnsupportedColValuePolicy| -> anyhow::Result<Value> {
+            let after = HashMap::from([
+                ("id".to_string(), ColValue::Long(1)),
+                // binary values, sent as hex strings by default
+                ("geo".to_string(), ColValue::Blob(vec![0x01, 0x02, 0xff])),
+            ]);
+            let mut data = vec![RowData::new(
+                "db_1".into(),
+                "tb_1".into(),
+                0,
+                RowType::Insert,
+                None,
+                Some(after),
+            )];
+            let handler = UnsupportedColValueHandler::new(ColValueCapability::DORIS, policy);
+            let (load_data, _) = StarRocksSinker::build_load_data(
+                &DbType::Doris,
+                100,
+                &handler,
+                &mut ColProjection::default(),
+                &BinaryEncoding::default(),
+                &mut data,
+                &tb_meta,
+                None,
+            )?;
+            Ok(serde_json::to_value(&load_data)?)
+        };
+
+        assert_eq!(
+            build_body(UnsupportedColValuePolicy::Convert).unwrap(),
+            serde_json::json!([{"id": 1, "geo": "0102ff"}])
+        );
+        assert!(build_body(UnsupportedColValuePolicy::Error)
+            .unwrap_err()
+            .to_string()
+            .contains("doris sinker does not support Blob value"));
+        assert_eq!(
+            build_body(UnsupportedColValuePolicy::Null).unwrap(),
+            serde_json::json!([{"id": 1, "geo": null}])
+        );
+        assert_eq!(
+            build_body(UnsupportedColValuePolicy::Base64String).unwrap(),
+            serde_json::json!([{"id": 1, "geo": "AQL/"}])
+        );
+        assert_eq!(
+            build_body(UnsupportedColValuePolicy::SkipColumn).unwrap(),
+            serde_json::json!([{"id": 1}])
+        );
+    }
+
+    #[test]
+    fn test_load_data_of_null_and_empty_string() {
+        let varchar = MysqlColType::Varchar {
+            length: 100,
+            charset: "utf8mb4".into(),
+        };
+        let tb_meta = MysqlTbMeta {
+            basic: RdbTbMeta {
+                schema: "db_1".into(),
+                tb: "tb_1".into(),
+                cols: vec!["id".into(), "name".into(), "note".into()],
+                ..Default::default()
+            },
+            col_type_map: HashMap::from([
+                ("id".into(), MysqlColType::Int { unsigned: false }),
+                ("name".into(), varchar.clone()),
+                ("note".into(), varchar),
+            ]),
+        };
+
+        let after = HashMap::from([
+            ("id".to_string(), ColValue::Long(1)),
+            ("name".to_string(), ColValue::None),
+            ("note".to_string(), ColValue::String(String::new())),
+        ]);
+        let mut data = vec![RowData::new(
+            "db_1".into(),
+            "tb_1".into(),
+            0,
+            RowType::Insert,
+            None,
+            Some(after),
+        )];
+        let (load_data, _) = StarRocksSinker::build_load_data(
+            &DbType::StarRocks,
+            100,
+            &UnsupportedColValueHandler::new(ColValueCapability::STARROCKS, Default::default()),
+            &mut ColProjection::default(),
+            &BinaryEncoding::default(),
+            &mut data,
+            &tb_meta,
+            None,
+        )
+        .unwrap();
+
+        let body = serde_json::to_string(&load_data).unwrap();
+        assert!(body.contains(r#""name":null"#));
+        assert!(body.contains(r#""note":"""#));
+        assert_eq!(
+            serde_json::from_str::<Value>(&body).unwrap(),
+            serde_json::json!([{"id": 1, "name": null, "note": "", TIMESTAMP_COL_NAME: 100}])
+        );
+    }
+
+    #[test]
+    fn test_load_data_of_binary() {
+        let tb_meta = MysqlTbMeta {
+            basic: RdbTbMeta {
+                schema: "db_1".into(),
+                tb: "tb_1".into(),
+                cols: vec!["id".into(), "data".into()],
+                ..Default::default()
+            },
+            col_type_map: HashMap::from([
+                ("id".into(), MysqlColType::Int { unsigned: false }),
+                ("data".into(), MysqlColType::VarBinary { length: 100 }),
+            ]),
+        };
+        // invalid utf8
+        let bytes = vec![0xff, 0xfe, 0x00, b'a', 0xc3];
+        let build_data = || {
+            let after = HashMap::from([
+                ("id".to_string(), ColValue::Long(1)),
+                ("data".to_string(), ColValue::Blob(bytes.clone())),
+            ]);
+            vec![RowData::new(
+                "db_1".into(),
+                "tb_1".into(),
+                0,
+                RowType::Insert,
+                None,
+                Some(after),
+            )]
+        };
+        // VARBINARY cols take binaries, so the policy does not apply to them
+        let handler = UnsupportedColValueHandler::new(
+            ColValueCapability::STARROCKS,
+            UnsupportedColValuePolicy::Error,
+        );
+
+        let load = |binary_encoding: BinaryEncoding| {
+            let mut data = build_data();
+            let (load_data, _) = StarRocksSinker::build_load_data(
+                &DbType::StarRocks,
+                100,
+                &handler,
+                &mut ColProjection::default(),
+                &binary_encoding,
+                &mut data,
+                &tb_meta,
+                None,
+            )
+            .unwrap();
+            let body = serde_json::to_value(&load_data).unwrap();
+            body[0]["data"].as_str().unwrap().to_string()
+        };
+
+        let hex_str = load(BinaryEncoding::Hex);
+        assert_eq!(hex::decode(hex_str).unwrap(), bytes);
+        let base64_str = load(BinaryEncoding::Base64);
+        assert_eq!(general_purpose::STANDARD.decode(base64_str).unwrap(), bytes);
+    }
+
+    #[test]
+    fn test_load_data_of_excluded_cols() {
+        let tb_meta = MysqlTbMeta {
+            basic: RdbTbMeta {
+                schema: "db_1".into(),
+                tb: "tb_1".into(),
+                cols: vec!["id".into(), "name".into()],
+                key_map: HashMap::from([("primary".into(), vec!["id".into()])]),
+                order_cols: vec!["id".into()],
+                id_cols: vec!["id".into()],
+                ..Default::default()
+            },
+            col_type_map: HashMap::from([
+                ("id".into(), MysqlColType::Int { unsigned: false }),
+                (
+                    "name".into(),
+                    MysqlColType::Varchar {
+                        length: 100,
+                        charset: "utf8mb4".into(),
+                    },
+                ),
+            ]),
+        };
+        let after = HashMap::from([
+            ("id".to_string(), ColValue::Long(1)),
+            ("name".to_string(), ColValue::String("a".into())),
+            ("password".to_string(), ColValue::String("secret".into())),
+        ]);
+        let mut data = vec![RowData::new(
+            "db_1".into(),
+            "tb_1".into(),
+            0,
+            RowType::Insert,
+            None,
+            Some(after),
+        )];
+        // the pk is excluded too, but kept for keying
+        let mut col_projection =
+            ColProjection::from_config("", "db_1.tb_1.password,db_1.*.id", &DbType::Mysql).unwrap();
+
+        let (load_data, _) = StarRocksSinker::build_load_data(
+            &DbType::StarRocks,
+            100,
+            &UnsupportedColValueHandler::new(ColValueCapability::STARROCKS, Default::default()),
+            &mut col_projection,
+            &BinaryEncoding::default(),
+            &mut data,
+            &tb_meta,
+            None,
+        )
+        .unwrap();
+        let body = serde_json::to_string(&load_data).unwrap();
+        assert!(!body.contains("password") && !body.contains("secret"));
+        assert_eq!(
+            serde_json::from_str::<Value>(&body).unwrap(),
+            serde_json::json!([{"id": 1, "name": "a", TIMESTAMP_COL_NAME: 100}])
+        );
+    }
+
+    #[test]
+    fn test_columns_of_rows_missing_defaulted_cols() {
+        // created_at has a default and is not sent, order is a reserved word
+        let int = MysqlColType::Int { unsigned: false };
+        let tb_meta = MysqlTbMeta {
+            basic: RdbTbMeta {
+                schema: "db_1".into(),
+                tb: "tb_1".into(),
+                cols: vec![
+                    "id".into(),
+                    "order".into(),
+                    "created_at".into(),
+                    SIGN_COL_NAME.into(),
+                    TIMESTAMP_COL_NAME.into(),
+                ],
+                col_origin_type_map: HashMap::from([
+                    ("id".into(), "int".into()),
+                    ("order".into(), "varchar(100)".into()),
+                    ("created_at".into(), "datetime".into()),
+                    (SIGN_COL_NAME.into(), "int".into()),
+                    (TIMESTAMP_COL_NAME.into(), "bigint".into()),
+                ]),
+                key_map: HashMap::from([("primary".into(), vec!["id".into()])]),
+                order_cols: vec!["id".into()],
+                id_cols: vec!["id".into()],
+                ..Default::default()
+            },
+            col_type_map: HashMap::from([
+                ("id".into(), int.clone()),
+                (
+                    "order".into(),
+                    MysqlColType::Varchar {
+                        length: 100,
+                        charset: "utf8mb4".into(),
+                    },
+                ),
+                (
+                    "created_at".into(),
+                    MysqlColType::DateTime {
+                        precision: 0,
+                        is_nullable: false,
+                    },
+                ),
+                (SIGN_COL_NAME.into(), int),
+                (
+                    TIMESTAMP_COL_NAME.into(),
+                    MysqlColType::BigInt { unsigned: false },
+                ),
+            ]),
+        };
+        let build_row = |row_type: RowType| {
+            let col_values = Some(HashMap::from([
+                ("id".to_string(), ColValue::Long(1)),
+                ("order".to_string(), ColValue::String("a".into())),
+            ]));
+            match row_type {
+                RowType::Delete => {
+                    RowData::new("db_1".into(), "tb_1".into(), 0, row_type, col_values, None)
+                }
+                _ => RowData::new("db_1".into(), "tb_1".into(), 0, row_type, None, col_values),
+            }
+        };
+        let columns = |db_type: DbType, row_type: RowType, hard_delete: bool| {
+            let mut data = vec![build_row(row_type.clone())];
+            let (load_data, _) = StarRocksSinker::build_load_data(
+                &db_type,
+                100,
+                &UnsupportedColValueHandler::new(ColValueCapability::STARROCKS, Default::default()),
+                &mut ColProjection::default(),
+                &BinaryEncoding::default(),
+                &mut data,
+                &tb_meta,
+                None,
+            )
+            .unwrap();
+            let op = StarRocksSinker::get_op(&db_type, hard_delete, &row_type, &tb_meta);
+            StarRocksSinker::build_columns(&db_type, &load_data, &tb_meta, op)
+        };
+
+        assert_eq!(
+            columns(DbType::StarRocks, RowType::Insert, false).as_deref(),
+            Some("`id`,`order`,`_ape_dts_timestamp`")
+        );
+        // soft deletes set the sign col
+        assert_eq!(
+            columns(DbType::StarRocks, RowType::Delete, false).as_deref(),
+            Some("`id`,`order`,`_ape_dts_is_deleted`,`_ape_dts_timestamp`")
+        );
+        // hard deletes compose with __op
+        assert_eq!(
+            columns(DbType::StarRocks, RowType::Delete, true).as_deref(),
+            Some("`id`,`order`,`_ape_dts_is_deleted`,`_ape_dts_timestamp`,__op='delete'")
+        );
+        assert_eq!(columns(DbType::Doris, RowType::Insert, false), None);
+
+        // backticks in col names are doubled
+        let mut tb_meta = tb_meta.clone();
+        tb_meta.basic.cols = vec!["a`b".into()];
+        let mut col_values = HashMap::from([("a`b".to_string(), ColValue::Long(1))]);
+        assert_eq!(
+            StarRocksSinker::build_columns(&DbType::StarRocks, &[&mut col_values], &tb_meta, "")
+                .as_deref(),
+            Some("`a``b`")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_mixed_insert_delete_batch() {
+        // without the sign col, deletes are loaded with __op='delete'
+        let tb_meta = MysqlTbMeta {
+            basic: RdbTbMeta {
+                schema: "db_1".into(),
+                tb: "tb_1".into(),
+                cols: vec!["id".into(), "name".into()],
+                key_map: HashMap::from([("primary".into(), vec!["id".into()])]),
+                order_cols: vec!["id".into()],
+                id_cols: vec!["id".into()],
+                ..Default::default()
+            },
+            col_type_map: HashMap::from([
+                ("id".into(), MysqlColType::Int { unsigned: false }),
+                (
+                    "name".into(),
+                    MysqlColType::Varchar {
+                        length: 100,
+                        charset: "utf8mb4".into(),
+                    },
+                ),
+            ]),
+        };
+        let build_row = |row_type: RowType, id: i32| {
+            let col_values = Some(HashMap::from([
+                ("id".to_string(), ColValue::Long(id)),
+                ("name".to_string(), ColValue::String(format!("name_{}", id))),
+            ]));
+            match row_type {
+                RowType::Delete => {
+                    RowData::new("db_1".into(), "tb_1".into(), 0, row_type, col_values, None)
+                }
+                _ => RowData::new("db_1".into(), "tb_1".into(), 0, row_type, None, col_values),
+            }
+        };
+        // insert 1, 2, delete 1, insert 3, 1 again, delete 2
+        let mut data = vec![
+            build_row(RowType::Insert, 1),
+            build_row(RowType::Insert, 2),
+            build_row(RowType::Delete, 1),
+            build_row(RowType::Insert, 3),
+            build_row(RowType::Update, 1),
+            build_row(RowType::Delete, 2),
+        ];
+
+        let runs = StarRocksSinker::split_delete_runs(&data);
+        assert_eq!(runs, vec![(0, 2), (2, 1), (3, 2), (5, 1)]);
+
+        // all rows are in one run if the batch has no deletes
+        assert_eq!(
+            StarRocksSinker::split_delete_runs(&data[3..5]),
+            vec![(0, 2)]
+        );
+
+        // each run is sent as a stream load with its own op, in order
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            use tokio::io::AsyncWriteExt;
+
+            let mut requests = Vec::new();
+            for _ in 0..runs.len() {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                requests.push(read_request(&mut socket).await);
+                let body = r#"{"Status":"Success","Message":"OK"}"#;
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+            requests
+        });
+
+        let mut sinker = build_sinker(DbType::StarRocks, tb_meta);
+        sinker.port = addr.port().to_string();
+        sinker.sink_dml(data, true).await.unwrap();
+
+        let loads: Vec<(String, String)> = server
+            .await
+            .unwrap()
+            .iter()
+            .map(|request| {
+                assert!(request.starts_with("put /api/db_1/tb_1/_stream_load"));
+                let (headers, body) = request.split_once("\r\n\r\n").unwrap();
+                let columns = headers
+                    .lines()
+                    .find_map(|line| line.strip_prefix("columns: "))
+                    .unwrap();
+                let ids: Vec<String> = serde_json::from_str::<Value>(body)
+                    .unwrap()
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|row| row["id"].to_string())
+                    .collect();
+                (columns.to_string(), ids.join(","))
+            })
+            .collect();
+        assert_eq!(
+            loads,
+            vec![
+                ("`id`,`name`".to_string(), "1,2".to_string()),
+                ("`id`,`name`,__op='delete'".to_string(), "1".to_string()),
+                ("`id`,`name`".to_string(), "3,1".to_string()),
+                ("`id`,`name`,__op='delete'".to_string(), "2".to_string()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_sends_nothing() {
+        let tb_meta = MysqlTbMeta {
+            basic: RdbTbMeta {
+                schema: "db_1".into(),
+                tb: "tb_1".into(),
+                cols: vec!["id".into()],
+                key_map: HashMap::from([("primary".into(), vec!["id".into()])]),
+                order_cols: vec!["id".into()],
+                id_cols: vec!["id".into()],
+                ..Default::default()
+            },
+            col_type_map: HashMap::from([("id".into(), MysqlColType::Int { unsigned: false })]),
+        };
+        let build_data = || {
+            (1..=2)
+                .map(|id| {
+                    let after = HashMap::from([("id".to_string(), ColValue::Long(id))]);
+                    RowData::new(
+                        "db_1".into(),
+                        "tb_1".into(),
+                        0,
+                        RowType::Insert,
+                        None,
+                        Some(after),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut sinker = build_sinker(DbType::StarRocks, tb_meta);
+        sinker.port = listener.local_addr().unwrap().port().to_string();
+        sinker.dry_run = true;
+
+        // the load is built with the data size of the rows, but no response is checked
+        let mut data = build_data();
+        let (data_size, load_time_ms) = sinker.send_data(&mut data, 0, 2).await.unwrap();
+        assert!(data_size > 0);
+        assert_eq!(load_time_ms, None);
+        sinker.sink_dml(build_data(), true).await.unwrap();
+
+        // the backend is never connected
+        let accepted = tokio::time::timeout(Duration::from_millis(300), listener.accept()).await;
+        assert!(accepted.is_err());
+    }
+
+    #[test]
+    fn test_doris_delete() {
+        let int = MysqlColType::Int { unsigned: false };
+        let build_tb_meta = |with_delete_sign: bool| {
+            let mut cols = vec!["id".to_string(), "value".to_string()];
+            if with_delete_sign {
+                cols.push(DORIS_DELETE_SIGN_COL_NAME.into());
+            }
+            MysqlTbMeta {
+                basic: RdbTbMeta {
+                    schema: "db_1".into(),
+                    tb: "tb_1".into(),
+                    col_origin_type_map: cols
+                        .iter()
+                        .map(|col| (col.clone(), "int".to_string()))
+                        .collect(),
+                    cols: cols.clone(),
+                    ..Default::default()
+                },
+                col_type_map: cols.into_iter().map(|col| (col, int.clone())).collect(),
+            }
+        };
+        let build_data = || {
+            let before = HashMap::from([
+                ("id".to_string(), ColValue::Long(1)),
+                ("value".to_string(), ColValue::Long(2)),
+            ]);
+            vec![RowData::new(
+                "db_1".into(),
+                "tb_1".into(),
+                0,
+                RowType::Delete,
+                Some(before),
+                None,
+            )]
+        };
+        let handler =
+            UnsupportedColValueHandler::new(ColValueCapability::DORIS, Default::default());
+
+        // batch delete enabled, the delete sign col is set explicitly
+        let tb_meta = build_tb_meta(true);
+        let mut data = build_data();
+        let (load_data, _) = StarRocksSinker::build_load_data(
+            &DbType::Doris,
+            100,
+            &handler,
+            &mut ColProjection::default(),
+            &BinaryEncoding::default(),
+            &mut data,
+            &tb_meta,
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            serde_json::to_value(&load_data).unwrap(),
+            serde_json::json!([{"id": 1, "value": 2, DORIS_DELETE_SIGN_COL_NAME: 1}])
+        );
+        let op = StarRocksSinker::get_op(&DbType::Doris, false, &RowType::Delete, &tb_meta);
+        assert_eq!(op, "");
+
+        // no delete sign col, fall back to merge_type=DELETE
+        let tb_meta = build_tb_meta(false);
+        let mut data = build_data();
+        let (load_data, _) = StarRocksSinker::build_load_data(
+            &DbType::Doris,
+            100,
+            &handler,
+            &mut ColProjection::default(),
+            &BinaryEncoding::default(),
+            &mut data,
+            &tb_meta,
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            serde_json::to_value(&load_data).unwrap(),
+            serde_json::json!([{"id": 1, "value": 2}])
+        );
+        let op = StarRocksSinker::get_op(&DbType::Doris, false, &RowType::Delete, &tb_meta);
+        assert_eq!(op, "delete");
+        let op = StarRocksSinker::get_op(&DbType::Doris, false, &RowType::Insert, &tb_meta);
+        assert_eq!(op, "");
+    }
+
+    #[test]
+    fn test_build_label() {
+        let digest = "0123456789abcdef0123456789abcdef";
+        let label = StarRocksSinker::build_label("ape_dts", 1, "db-1", "tb 1.中", digest);
+        assert_eq!(label, format!("ape_dts_db-1_tb_1___1_{}", digest));
+
+        // the prefix part is truncated, the suffix is kept
+        let tb = "t".repeat(200);
+        let label = StarRocksSinker::build_label("ape_dts", 12, "db_1", &tb, digest);
+        assert_eq!(label.len(), MAX_LABEL_LEN);
+        assert!(label.starts_with("ape_dts_db_1_ttt"));
+        assert!(label.ends_with(&format!("t_12_{}", digest)));
+    }
+
+    #[test]
+    fn test_build_load_digest() {
+        let build_rows = |ids: &[i64], timestamp: i64| -> Vec<HashMap<String, ColValue>> {
+            ids.iter()
+                .map(|id| {
+                    HashMap::from([
+                        ("id".to_string(), ColValue::LongLong(*id)),
+                        ("name".to_string(), ColValue::String(format!("name_{}", id))),
+                        (
+                            TIMESTAMP_COL_NAME.to_string(),
+                            ColValue::LongLong(timestamp),
+                        ),
+                    ])
+                })
+                .collect()
+        };
+        let digest = |positions: &str,
+                      row_offset: usize,
+                      op: &str,
+                      rows: &mut [HashMap<String, ColValue>]| {
+            let load_data: Vec<&mut HashMap<String, ColValue>> = rows.iter_mut().collect();
+            StarRocksSinker::build_load_digest(positions, row_offset, op, None, &load_data).unwrap()
+        };
+
+        let positions = "first|last";
+        let expected = digest(positions, 0, "", &mut build_rows(&[1, 2], 100));
+        assert_eq!(expected.len(), 32);
+        // a replay of the same rows at another sync timestamp
+        assert_eq!(
+            digest(positions, 0, "", &mut build_rows(&[1, 2], 200)),
+            expected
+        );
+        // other rows, positions, offsets or ops of the same batch
+        assert_ne!(
+            digest(positions, 0, "", &mut build_rows(&[1, 3], 100)),
+            expected
+        );
+        assert_ne!(
+            digest(positions, 0, "", &mut build_rows(&[1], 100)),
+            expected
+        );
+        assert_ne!(
+            digest("first|other", 0, "", &mut build_rows(&[1, 2], 100)),
+            expected
+        );
+        assert_ne!(
+            digest(positions, 2, "", &mut build_rows(&[1, 2], 100)),
+            expected
+        );
+        assert_ne!(
+            digest(positions, 0, "delete", &mut build_rows(&[1, 2], 100)),
+            expected
+        );
+    }
+
+    #[tokio::test]
+    async fn test_no_label_without_positions() {
+        let mut sinker = build_sinker(DbType::StarRocks, MysqlTbMeta::default());
+        let position = Position::RdbSnapshotFinished {
+            db_type: "mysql".into(),
+            schema: "db_1".into(),
+            tb: "tb_1".into(),
+        };
+        sinker.begin_dml_batch(&position, &position).await.unwrap();
+        assert!(sinker.batch_positions.is_some());
+        // snapshot rows carry no position, every batch would get the same label
+        sinker
+            .begin_dml_batch(&Position::None, &Position::None)
+            .await
+            .unwrap();
+        assert!(sinker.batch_positions.is_none());
+    }
+
+    #[test]
+    fn test_load_of_existing_label() {
+        // starrocks
+        let response_text = r#"{"TxnId": 2040, "Label": "ape_dts_db_1_tb_1_0_0000000000000abc_0_1",
+            "Status": "Label Already Exists", "ExistingJobStatus": "FINISHED",
+            "Message": "Label [ape_dts_db_1_tb_1_0_0000000000000abc_0_1] has already been used."}"#;
+        let stats = StarRocksSinker::check_load_result(StatusCode::OK, response_text).unwrap();
+        assert_eq!(stats, StreamLoadStats::default());
+
+        // doris
+        let response_text = r#"{"TxnId": 2040, "Label": "ape_dts_db_1_tb_1_0_0000000000000abc_0_1",
+            "Status": "Label Already Exists", "ExistingJobStatus": "VISIBLE"}"#;
+        assert!(StarRocksSinker::check_load_result(StatusCode::OK, response_text).is_ok());
+
+        // the load of the label may still fail
+        let response_text = r#"{"TxnId": 2040, "Label": "ape_dts_db_1_tb_1_0_0000000000000abc_0_1",
+            "Status": "Label Already Exists", "ExistingJobStatus": "RUNNING"}"#;
+        assert!(StarRocksSinker::check_load_result(StatusCode::OK, response_text).is_err());
+    }
+
+    #[test]
+    fn test_compress_round_trip() {
+        let mut col_values =
+            HashMap::from([("name".to_string(), ColValue::String("a".repeat(100)))]);
+        let load_data = vec![&mut col_values];
+        let body = serde_json::to_string(&load_data).unwrap();
+
+        let compressed = StarRocksSinker::build_body(Some("gzip"), &load_data, 0).unwrap();
+        assert!(compressed.len() < body.len());
+        let mut decompressed = String::new();
+        flate2::read::GzDecoder::new(compressed.as_slice())
+            .read_to_string(&mut decompressed)
+            .unwrap();
+        assert_eq!(decompressed, body);
+
+        let compressed = StarRocksSinker::build_body(Some("lz4_frame"), &load_data, 0).unwrap();
+        let mut decompressed = String::new();
+        lz4_flex::frame::FrameDecoder::new(compressed.as_slice())
+            .read_to_string(&mut decompressed)
+            .unwrap();
+        assert_eq!(decompressed, body);
+
+        let uncompressed = StarRocksSinker::build_body(None, &load_data, 0).unwrap();
+        assert_eq!(uncompressed, body.as_bytes());
+        assert!(StarRocksSinker::build_body(Some("zstd"), &load_data, 0).is_err());
+    }
+
+    #[test]
+    fn test_build_body_of_rows() {
+        let tb_meta = MysqlTbMeta {
+            basic: RdbTbMeta {
+                schema: "db_1".into(),
+                tb: "tb_1".into(),
+                cols: vec!["id".into(), "name".into()],
+                ..Default::default()
+            },
+            col_type_map: HashMap::from([
+                ("id".into(), MysqlColType::Int { unsigned: false }),
+                (
+                    "name".into(),
+                    MysqlColType::Varchar {
+                        length: 100,
+                        charset: "utf8mb4".into(),
+                    },
+                ),
+            ]),
+        };
+        let mut data: Vec<RowData> = (0..3)
+            .map(|i| {
+                let after = HashMap::from([
+                    ("id".to_string(), ColValue::Long(i)),
+                    (
+                        "name".to_string(),
+                        ColValue::String(format!("\"a\"\n{}", i)),
+                    ),
+                ]);
+                RowData::new(
+                    "db_1".into(),
+                    "tb_1".into(),
+                    0,
+                    RowType::Insert,
+                    None,
+                    Some(after),
+                )
+            })
+            .collect();
+        let (load_data, data_size) = StarRocksSinker::build_load_data(
+            &DbType::StarRocks,
+            100,
+            &UnsupportedColValueHandler::new(ColValueCapability::STARROCKS, Default::default()),
+            &mut ColProjection::default(),
+            &BinaryEncoding::default(),
+            &mut data,
+            &tb_meta,
+            None,
+        )
+        .unwrap();
+
+        let body = StarRocksSinker::build_body(None, &load_data, data_size).unwrap();
+        assert_eq!(
+            body,
+            serde_json::to_string(&load_data).unwrap().into_bytes()
+        );
+        assert_eq!(
+            serde_json::from_slice::<Value>(&body).unwrap(),
+            serde_json::json!(load_data)
+        );
+
+        let load_data: Vec<&mut HashMap<String, ColValue>> = Vec::new();
+        let body = StarRocksSinker::build_body(None, &load_data, 0).unwrap();
+        assert_eq!(body, b"[]");
+    }
+
+    #[tokio::test]
+    async fn test_execute_request_timeout() {
+        // a backend which accepts the connection but never responds
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (_socket, _) = listener.accept().await.unwrap();
+            tokio::time::sleep(Duration::from_secs(10)).await;
+        });
+
+        let http_client = Client::new();
+        let request = http_client
+            .request(
+                Method::PUT,
+                format!("http://{}/api/db_1/tb_1/_stream_load", addr),
+            )
+            .timeout(Duration::from_millis(200))
+            .body("[]")
+            .build()
+            .unwrap();
+
+        let start_time = Instant::now();
+        let err = StarRocksSinker::execute_request(&http_client, request)
+            .await
+            .unwrap_err();
+        assert!(start_time.elapsed() < Duration::from_secs(5));
+        match err.downcast_ref::<Error>() {
+            Some(Error::HttpError(msg)) => {
+                assert!(msg.starts_with("stream load request timed out"));
+            }
+            _ => panic!("unexpected error: {}", err),
+        }
+        assert!(Error::is_recoverable_task_error(&err));
+    }
+
+    #[test]
+    fn test_build_stream_load_url() {
+        assert_eq!(
+            StarRocksSinker::build_stream_load_url("http", "127.0.0.1", "8030", "db_1", "tb_1"),
+            "http://127.0.0.1:8030/api/db_1/tb_1/_stream_load"
+        );
+        assert_eq!(
+            StarRocksSinker::build_stream_load_url("https", "fe.local", "8443", "db_1", "tb_1"),
+            "https://fe.local:8443/api/db_1/tb_1/_stream_load"
+        );
+    }
+
+    #[test]
+    fn test_build_http_client_with_ssl_ca() {
+        const CA_PEM: &str = "\
+-----BEGIN CERTIFICATE-----\n\
+MIIBjDCCATGgAwIBAgIUYnrrxCx6gd8onzUxet0+nYaxBx8wCgYIKoZIzj0EAwIw\n\
+GjEYMBYGA1UEAwwPYXBlLWR0cy10ZXN0LWNhMCAXDTI2MTAxNjEwMTIxOFoYDzIx\n\
+MjYwOTIyMTAxMjE4WjAaMRgwFgYDVQQDDA9hcGUtZHRzLXRlc3QtY2EwWTATBgcq\n\
+hkjOPQIBBggqhkjOPQMBBwNCAATwif8jGyh1kjy7BpgSbMqKG3FRPuDGVESbpH0G\n\
+jOS5/KoVRCAGsWpN+hNwQyl4CB204Eic15/2KFwwk2vbKQuHo1MwUTAdBgNVHQ4E\n\
+FgQU6CI8MNerjQ6aLZAp98SZBa37s/QwHwYDVR0jBBgwFoAU6CI8MNerjQ6aLZAp\n\
+98SZBa37s/QwDwYDVR0TAQH/BAUwAwEB/zAKBggqhkjOPQQDAgNJADBGAiEA3Q90\n\
+ZJPkQfHfog6xc5vzFrGD3PkBSKB148FU8BX4LdoCIQDJStNftAJO7bMDDbR4Vfcz\n\
+ZV26taKKrjrEtDJKkNZXrw==\n\
+-----END CERTIFICATE-----\n";
+
+        let dir = std::env::temp_dir().join(format!(
+            "ape-dts-starrocks-sinker-{}",
+            std::time::SystemTime::now()
+                .duration_since(std::time::SystemTime::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        let ca_path = dir.join("ca.pem");
+        fs::write(&ca_path, CA_PEM).unwrap();
+        let invalid_ca_path = dir.join("invalid_ca.pem");
+        fs::write(&invalid_ca_path, "not a certificate").unwrap();
+
+        assert!(StarRocksSinker::build_http_client(10, ca_path.to_str().unwrap(), false).is_ok());
+        assert!(StarRocksSinker::build_http_client(0, "", true).is_ok());
+        let err = StarRocksSinker::build_http_client(0, invalid_ca_path.to_str().unwrap(), false)
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("failed to parse stream load ssl ca"));
+        let err = StarRocksSinker::build_http_client(0, "/not/exist/ca.pem", false).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("failed to read stream load ssl ca"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    // reads a request of the mock stream load server, lowercased
+    async fn read_request(socket: &mut tokio::net::TcpStream) -> String {
+        use tokio::io::AsyncReadExt;
+
+        let mut buf = Vec::new();
+        let mut chunk = [0u8; 1024];
+        loop {
+            let n = socket.read(&mut chunk).await.unwrap();
+            if n == 0 {
+                break;
+            }
+            buf.extend_from_slice(&chunk[..n]);
+            let text = String::from_utf8_lossy(&buf).to_lowercase();
+            if let Some(pos) = text.find("\r\n\r\n") {
+                let content_length = text[..pos]
+                    .lines()
+                    .find_map(|line| line.strip_prefix("content-length:"))
+                    .map(|v| v.trim().parse::<usize>().unwrap())
+                    .unwrap_or(0);
+                if buf.len() >= pos + 4 + content_length {
+                    break;
+                }
+            }
+        }
+        String::from_utf8_lossy(&buf).to_lowercase()
+    }
+
+    #[tokio::test]
+    async fn test_execute_request_redirect() {
+        use tokio::io::AsyncWriteExt;
+
+        // the fe redirects to the be, which loads the data
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let mut requests = Vec::new();
+            let (mut socket, _) = listener.accept().await.unwrap();
+            requests.push(read_request(&mut socket).await);
+            let response = format!(
+                "HTTP/1.1 307 Temporary Redirect\r\nLocation: http://{}/be/db_1/tb_1/_stream_load\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                addr
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+            drop(socket);
+
+            let (mut socket, _) = listener.accept().await.unwrap();
+            requests.push(read_request(&mut socket).await);
+            let body = r#"{"Status":"Success","Message":"OK"}"#;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+            requests
+        });
+
+        let http_client = StarRocksSinker::build_http_client(0, "", false).unwrap();
+        let request = http_client
+            .request(
+                Method::PUT,
+                format!("http://{}/api/db_1/tb_1/_stream_load", addr),
+            )
+            .basic_auth("root", Some("123456"))
+            .header("label", "ape_dts_db_1_tb_1_0_1")
+            .header("columns", "__op='delete'")
+            .body(r#"[{"id":1}]"#)
+            .build()
+            .unwrap();
+
+        let response = StarRocksSinker::execute_request(&http_client, request)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        StarRocksSinker::check_response(response).await.unwrap();
+
+        let requests = server.await.unwrap();
+        assert!(requests[0].starts_with("put /api/db_1/tb_1/_stream_load"));
+        assert!(requests[1].starts_with("put /be/db_1/tb_1/_stream_load"));
+        for request in requests.iter() {
+            assert!(request.contains("authorization: basic cm9vddoxmjm0nty="));
+            assert!(request.contains("label: ape_dts_db_1_tb_1_0_1"));
+            assert!(request.contains("columns: __op='delete'"));
+            assert!(request.ends_with(r#"[{"id":1}]"#));
+        }
+    }
+
+    // the meta and keys of tb_meta are cached, so the pool is never connected
+    fn build_sinker(db_type: DbType, tb_meta: MysqlTbMeta) -> StarRocksSinker {
+        let full_name = (tb_meta.basic.schema.clone(), tb_meta.basic.tb.clone());
+        let key_cols = tb_meta.basic.id_cols.clone();
+        let meta_fetcher = MysqlMetaFetcher {
+            conn_pool: MySqlPoolOptions::new()
+                .connect_lazy("mysql://127.0.0.1:3306")
+                .unwrap(),
+            cache: HashMap::from([(format!("{}.{}", full_name.0, full_name.1), tb_meta)]),
+            version: String::new(),
+            db_type: DbType::Mysql,
+        };
+        let capability = match db_type {
+            DbType::Doris => ColValueCapability::DORIS,
+            _ => ColValueCapability::STARROCKS,
+        };
+        StarRocksSinker {
+            id: 0,
+            db_type,
+            batch_size: 200,
+            http_client: Client::new(),
+            scheme: "http".into(),
+            host: "127.0.0.1".into(),
+            port: "8030".into(),
+            username: "root".into(),
+            password: String::new(),
+            meta_manager: MysqlMetaManager {
+                meta_center: None,
+                meta_fetcher,
+            },
+            base_sinker: BaseSinker::default(),
+            sync_timestamp: 0,
+            hard_delete: false,
+            dry_run: false,
+            label_prefix: None,
+            compression: None,
+            col_value_handler: UnsupportedColValueHandler::new(capability, Default::default()),
+            col_projection: ColProjection::default(),
+            rate_limiter: None,
+            request_timeout: None,
+            binary_encoding: BinaryEncoding::default(),
+            batch_size_tuner: None,
+            group_commit: None,
+            batch_positions: None,
+            batch_row_offset: 0,
+            table_keys: HashMap::from([(full_name, Some(key_cols))]),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tune_batch_size_by_load_time() {
+        let mut sinker = build_sinker(DbType::StarRocks, MysqlTbMeta::default());
+        sinker.batch_size = 200;
+        sinker.batch_size_tuner = Some(BatchSizeTuner {
+            min_batch_size: 100,
+            max_batch_size: 1000,
+            target_load_time_ms: 1000,
+        });
+        let response = |load_time_ms: u64| {
+            format!(
+                r#"{{"Status": "Success", "Message": "OK", "LoadTimeMs": {}}}"#,
+                load_time_ms
+            )
+        };
+        // the result of send_data for a stream load response
+        let load = |sinker: &mut StarRocksSinker, sent_batch_size: usize, response_text: &str| {
+            let result = StarRocksSinker::check_load_result(StatusCode::OK, response_text)
+                .map(|stats| (0, stats.load_time_ms));
+            sinker.tune_batch_size(sent_batch_size, &result);
+            sinker.batch_size
+        };
+
+        // fast loads of full batches grow up to max_batch_size
+        assert_eq!(load(&mut sinker, 200, &response(100)), 400);
+        assert_eq!(load(&mut sinker, 400, &response(300)), 800);
+        assert_eq!(load(&mut sinker, 800, &response(400)), 1000);
+        assert_eq!(load(&mut sinker, 1000, &response(400)), 1000);
+        // a fast partial batch or a load within the target keeps the size
+        assert_eq!(load(&mut sinker, 10, &response(50)), 1000);
+        assert_eq!(load(&mut sinker, 1000, &response(800)), 1000);
+        // slow and failed loads back off down to min_batch_size
+        assert_eq!(load(&mut sinker, 1000, &response(2000)), 500);
+        let failed = r#"{"Status": "Fail", "Message": "too many versions", "LoadTimeMs": 10}"#;
+        assert_eq!(load(&mut sinker, 500, failed), 250);
+        assert_eq!(load(&mut sinker, 250, &response(1500)), 125);
+        assert_eq!(load(&mut sinker, 125, &response(3000)), 100);
+        // loads without LoadTimeMs keep the size
+        let no_load_time = r#"{"Status": "Success", "Message": "OK"}"#;
+        assert_eq!(load(&mut sinker, 100, no_load_time), 100);
+
+        // the size is fixed without a tuner
+        sinker.batch_size_tuner = None;
+        assert_eq!(load(&mut sinker, 100, &response(10)), 100);
+    }
+
+    #[tokio::test]
+    async fn test_record_load_stats() {
+        let response_text = r#"{
+            "TxnId": 2040,
+            "Label": "ape_dts_db_1_tb_1_0_1709169909554",
+            "Status": "Success",
+            "Message": "OK",
+            "NumberTotalRows": 5,
+            "NumberLoadedRows": 3,
+            "NumberFilteredRows": 2,
+            "NumberUnselectedRows": 0,
+            "LoadBytes": 342,
+            "LoadTimeMs": 180,
+            "ErrorURL": "http://192.168.1.10:8040/api/_load_error_log?file=error_log_1"
+        }"#;
+        let stats = StarRocksSinker::check_load_result(StatusCode::OK, response_text).unwrap();
+        assert_eq!(
+            stats,
+            StreamLoadStats {
+                loaded_rows: 3,
+                filtered_rows: 2,
+                load_time_ms: Some(180),
+                error_url: Some(
+                    "http://192.168.1.10:8040/api/_load_error_log?file=error_log_1".into()
+                ),
+            }
+        );
+
+        let url = "http://127.0.0.1:8030/api/db_1/tb_1/_stream_load";
+        assert_eq!(
+            StarRocksSinker::filtered_rows_warning(url, &stats).unwrap(),
+            "stream load filtered 2 rows, url: http://127.0.0.1:8030/api/db_1/tb_1/_stream_load, \
+             error_url: http://192.168.1.10:8040/api/_load_error_log?file=error_log_1"
+        );
+        let clean_stats = StreamLoadStats {
+            loaded_rows: 5,
+            ..Default::default()
+        };
+        assert_eq!(
+            StarRocksSinker::filtered_rows_warning(url, &clean_stats),
+            None
+        );
+
+        let task_id = "starrocks_task";
+        let task_monitor = Arc::new(TaskMonitor::new(Some(TaskType::new(TaskKind::Cdc, None))));
+        let monitor = Arc::new(Monitor::new("sinker", "starrocks", 60, 100, 100));
+        task_monitor.register(task_id, vec![(MonitorType::Sinker, monitor.clone())]);
+        let handle = TaskMonitorHandle::new(
+            task_monitor,
+            MonitorType::Sinker,
+            task_id.into(),
+            60,
+            100,
+            100,
+        );
+        let base_sinker = BaseSinker::new(handle, 10);
+        StarRocksSinker::record_load_stats(&base_sinker, task_id, url, &stats).await;
+        StarRocksSinker::record_load_stats(&base_sinker, task_id, url, &clean_stats).await;
+
+        let sum = |counter_type: CounterType| {
+            let counter = monitor
+                .time_window_counters
+                .get(&counter_type)
+                .map(|entry| entry.value().clone())
+                .unwrap();
+            async move { counter.statistics().await.sum }
+        };
+        assert_eq!(sum(CounterType::LoadedRecords).await, 8);
+        assert_eq!(sum(CounterType::FilteredRecords).await, 2);
+        assert_eq!(sum(CounterType::LoadTime).await, 180);
+    }
+
+    #[test]
+    fn test_group_commit() {
+        let url = "http://127.0.0.1:8030/api/db_1/tb_1/_stream_load";
+        let group_commit_header = |db_type: DbType, group_commit: Option<&str>| {
+            let put = Client::new().request(Method::PUT, url);
+            StarRocksSinker::add_group_commit_header(put, &db_type, group_commit)
+                .build()
+                .unwrap()
+                .headers()
+                .get("group_commit")
+                .map(|v| v.to_str().unwrap().to_string())
+        };
+        assert_eq!(
+            group_commit_header(DbType::Doris, Some("async_mode")).as_deref(),
+            Some("async_mode")
+        );
+        assert_eq!(
+            group_commit_header(DbType::Doris, Some("sync_mode")).as_deref(),
+            Some("sync_mode")
+        );
+        assert_eq!(group_commit_header(DbType::Doris, None), None);
+        assert_eq!(
+            group_commit_header(DbType::StarRocks, Some("async_mode")),
+            None
+        );
+
+        let response_text = r#"{
+            "TxnId": 7009,
+            "Label": "group_commit_c84d2099208436ab_96e33fda01eddba8",
+            "Comment": "",
+            "GroupCommit": true,
+            "Status": "Success",
+            "Message": "OK",
+            "NumberTotalRows": 2,
+            "NumberLoadedRows": 2,
+            "NumberFilteredRows": 0,
+            "NumberUnselectedRows": 0,
+            "LoadBytes": 19,
+            "LoadTimeMs": 35,
+            "StreamLoadPutTimeMs": 5,
+            "ReadDataTimeMs": 0,
+            "WriteDataTimeMs": 26
+        }"#;
+        let stats = StarRocksSinker::check_load_result(StatusCode::OK, response_text).unwrap();
+        assert_eq!(stats.loaded_rows, 2);
+        assert_eq!(stats.load_time_ms, Some(35));
     }
 }
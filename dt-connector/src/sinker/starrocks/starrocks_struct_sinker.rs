@@ -313,6 +313,8 @@ impl StarrocksStructSinker {
             MysqlColType::Set { items: _ } => "VARCHAR(255)",
             MysqlColType::Enum { items: _ } => "VARCHAR(255)",
             MysqlColType::Json => "JSON",
+            // geometry values are sent as WKT, which can be read by ST_GeomFromText
+            MysqlColType::Geometry => "STRING",
             MysqlColType::Unknown => "STRING",
         };
         Ok(dst_col.to_string())
@@ -1,25 +1,41 @@
-use std::env;
+use std::{env, fs, sync::Arc, time::Duration};
 
+use anyhow::Context;
 use clap::Parser;
 
 use config_source::{ConfigSourceKind, NacosConfig};
 use dt_precheck::{
-    config::task_config::PrecheckTaskConfig, do_precheck, do_precheck_with_config_str,
+    config::task_config::PrecheckTaskConfig, do_precheck_with_config_str_checked,
+    meta::precheck_report::PrecheckReport, report_precheck_result,
 };
-use dt_task::task_runner::TaskRunner;
+use dt_task::{shutdown_token::ShutdownToken, task_runner::TaskRunner};
 
 mod config_source;
 
 const ENV_SHUTDOWN_TIMEOUT_SECS: &str = "SHUTDOWN_TIMEOUT_SECS";
 
+/// Runs an ape-dts task, or a precheck if the config contains a [precheck] section
 #[derive(Debug, Parser)]
+#[command(
+    name = "dt-main",
+    after_help = concat!(
+        "Examples:\n",
+        "  dt-main ./task_config.ini\n",
+        "  dt-main --config-path ./task_config.ini\n",
+        "  cat ./task_config.ini | dt-main --config-path -\n",
+        "  dt-main --config-source nacos --nacos-address http://nacos:8848 --nacos-dataid task.ini",
+    )
+)]
 struct Args {
+    /// Print the version
     #[arg(short = 'v', long = "version", alias = "versions")]
     version: bool,
 
+    /// Local config file, same as --config-path
     #[arg(short, long, value_name = "CONFIG", conflicts_with = "legacy_config")]
     config: Option<String>,
 
+    /// Local config file, - reads the config from stdin
     #[arg(
         long = "config-path",
         value_name = "CONFIG",
@@ -27,23 +43,58 @@ struct Args {
     )]
     config_path: Option<String>,
 
+    /// Where the config is loaded from
     #[arg(long = "config-source", value_enum, default_value = "local")]
     config_source: ConfigSourceKind,
 
+    /// Nacos server address, required when --config-source=nacos, e.g. http://nacos:8848
     #[arg(long = "nacos-address")]
     nacos_address: Option<String>,
 
+    /// Nacos dataId of the config, required when --config-source=nacos
     #[arg(long = "nacos-dataid", alias = "nacos-data-id")]
     nacos_dataid: Option<String>,
 
+    /// Nacos group of the config
     #[arg(long = "nacos-group", default_value = config_source::DEFAULT_NACOS_GROUP)]
     nacos_group: String,
 
+    /// Nacos namespace id, the public namespace if not set
+    #[arg(long = "nacos-namespace")]
+    nacos_namespace: Option<String>,
+
+    /// Nacos username, if nacos auth is enabled
+    #[arg(long = "nacos-username")]
+    nacos_username: Option<String>,
+
+    /// Nacos password, if nacos auth is enabled
+    #[arg(long = "nacos-password")]
+    nacos_password: Option<String>,
+
+    /// Watch the nacos config and restart the task when it changes
+    #[arg(long = "nacos-watch")]
+    nacos_watch: bool,
+
+    /// Keep the full-line comments preceding allowed sections in the config loaded from nacos
+    #[arg(long = "nacos-keep-comments")]
+    nacos_keep_comments: bool,
+
+    /// Local config file, shorthand of --config-path
     #[arg(value_name = "CONFIG")]
     legacy_config: Option<String>,
 
+    /// Clear the positions of the task recorded by [resumer] with resume_type=from_db
     #[arg(long)]
     init: bool,
+
+    /// Print the precheck report as JSON to stdout, exit with code 1 if the precheck is not passed,
+    /// the progress of the checks is printed to stderr
+    #[arg(long = "precheck-report")]
+    precheck_report: bool,
+
+    /// Write the precheck report as JSON to this file, whether the precheck is passed or not
+    #[arg(long = "precheck-report-path", value_name = "PATH")]
+    precheck_report_path: Option<String>,
 }
 
 impl Args {
@@ -67,6 +118,9 @@ impl Args {
                         "--config-path, --config, or positional CONFIG is required when --config-source=local"
                     );
                 }
+                if self.nacos_watch {
+                    anyhow::bail!("--nacos-watch can only be used when --config-source=nacos");
+                }
             }
             ConfigSourceKind::Nacos => {
                 if self.config_path().is_some() {
@@ -74,11 +128,7 @@ impl Args {
                         "local CONFIG arguments can not be used when --config-source=nacos"
                     );
                 }
-                NacosConfig::new(
-                    self.nacos_address.as_deref().unwrap_or_default(),
-                    self.nacos_dataid.as_deref().unwrap_or_default(),
-                    &self.nacos_group,
-                )?;
+                self.nacos_config()?;
             }
         }
         Ok(())
@@ -89,7 +139,46 @@ impl Args {
             self.nacos_address.as_deref().unwrap_or_default(),
             self.nacos_dataid.as_deref().unwrap_or_default(),
             &self.nacos_group,
+            self.nacos_namespace.as_deref(),
+        )?
+        .with_auth(
+            self.nacos_username.as_deref(),
+            self.nacos_password.as_deref(),
         )
+        .map(|config| config.with_keep_comments(self.nacos_keep_comments))
+    }
+}
+
+fn handle_precheck_result(args: &Args, result: anyhow::Result<PrecheckReport>) {
+    if let (Some(path), Ok(report)) = (&args.precheck_report_path, &result) {
+        write_precheck_report(path, report).unwrap();
+    }
+    if args.precheck_report {
+        emit_precheck_report(result);
+    } else if let Err(err) = report_precheck_result(result) {
+        eprintln!("precheck meet error: {err}");
+        std::process::exit(1);
+    }
+}
+
+fn write_precheck_report(path: &str, report: &PrecheckReport) -> anyhow::Result<()> {
+    let content = serde_json::to_string_pretty(report)?;
+    fs::write(path, content)
+        .with_context(|| format!("failed to write precheck report to: {}", path))
+}
+
+fn emit_precheck_report(result: anyhow::Result<PrecheckReport>) {
+    match result {
+        Ok(report) => {
+            println!("{}", serde_json::to_string(&report).unwrap());
+            if !report.passed {
+                std::process::exit(1);
+            }
+        }
+        Err(err) => {
+            eprintln!("precheck meet error: {err}");
+            std::process::exit(1);
+        }
     }
 }
 
@@ -100,6 +189,51 @@ fn validate_config_str(config: &str) -> anyhow::Result<()> {
     TaskRunner::new_from_str(config).map(|_| ())
 }
 
+async fn run_with_nacos_watch(
+    nacos_config: NacosConfig,
+    mut config: String,
+    mut is_init: bool,
+    shutdown_token: Arc<ShutdownToken>,
+) -> anyhow::Result<()> {
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    tokio::spawn(config_source::watch_nacos_config(
+        nacos_config,
+        config.clone(),
+        |config| TaskRunner::new_from_str(config).map(|_| ()),
+        move |changed| {
+            let _ = tx.send(changed);
+        },
+    ));
+
+    loop {
+        // each run has its own token, so a config change stops only the current run
+        let task_token = Arc::new(ShutdownToken::default());
+        let runner = TaskRunner::new_from_str(&config)?.with_shutdown_token(task_token.clone());
+        let task = runner.start_task(is_init);
+        tokio::pin!(task);
+        tokio::select! {
+            result = &mut task => return result,
+            _ = shutdown_token.cancelled() => {
+                task_token.cancel();
+                return task.await;
+            }
+            Some(changed) = rx.recv() => {
+                // the running task sinks the queued data and closes sinkers before it returns,
+                // the restarted task continues from the positions recorded by resumer
+                eprintln!("nacos config changed, restarting task");
+                task_token.cancel();
+                // start_task stops the status server before it returns,
+                // so the replacement runner binds the same port again
+                if let Err(err) = task.await {
+                    eprintln!("warn: task failed while stopping for restart: {err:#}");
+                }
+                config = changed;
+                is_init = false;
+            }
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() {
     unsafe {
@@ -116,25 +250,33 @@ async fn main() {
         return;
     }
 
-    tokio::spawn(async {
+    // the running task stops extracting, sinks the queued data and closes sinkers on ctrl-c,
+    // the process is only killed if that takes longer than SHUTDOWN_TIMEOUT_SECS
+    let shutdown_token = Arc::new(ShutdownToken::default());
+    let signal_shutdown_token = shutdown_token.clone();
+    tokio::spawn(async move {
         tokio::signal::ctrl_c().await.unwrap();
-        tokio::time::sleep(std::time::Duration::from_secs(
-            std::env::var(ENV_SHUTDOWN_TIMEOUT_SECS)
-                .ok()
-                .and_then(|s| s.parse().ok())
-                .unwrap_or(3),
-        ))
-        .await;
-        std::process::exit(0);
+        signal_shutdown_token.cancel();
+        let timeout_secs = env::var(ENV_SHUTDOWN_TIMEOUT_SECS)
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(3);
+        tokio::time::sleep(Duration::from_secs(timeout_secs)).await;
+        eprintln!("task did not stop within {timeout_secs}s after ctrl-c, exiting");
+        std::process::exit(1);
     });
 
     match args.config_source {
         ConfigSourceKind::Local => {
-            let config = args.config_path().unwrap();
-            if PrecheckTaskConfig::new(config).is_ok() {
-                do_precheck(config).await;
+            // read once, so a config piped via stdin is parsed the same as a file
+            let config =
+                config_source::load_local_config_string(args.config_path().unwrap()).unwrap();
+            if PrecheckTaskConfig::new_from_str(&config).is_ok() {
+                handle_precheck_result(&args, do_precheck_with_config_str_checked(&config).await);
             } else {
-                let runner = TaskRunner::new(config).unwrap();
+                let runner = TaskRunner::new_from_str(&config)
+                    .unwrap()
+                    .with_shutdown_token(shutdown_token);
                 runner.start_task(args.init).await.unwrap()
             }
         }
@@ -145,9 +287,15 @@ async fn main() {
                     .await
                     .unwrap();
             if PrecheckTaskConfig::new_from_str(&config).is_ok() {
-                do_precheck_with_config_str(&config).await;
+                handle_precheck_result(&args, do_precheck_with_config_str_checked(&config).await);
+            } else if args.nacos_watch {
+                run_with_nacos_watch(nacos_config, config, args.init, shutdown_token)
+                    .await
+                    .unwrap()
             } else {
-                let runner = TaskRunner::new_from_str(&config).unwrap();
+                let runner = TaskRunner::new_from_str(&config)
+                    .unwrap()
+                    .with_shutdown_token(shutdown_token);
                 runner.start_task(args.init).await.unwrap()
             }
         }
@@ -184,6 +332,56 @@ mod tests {
         assert!(args.validate().is_ok());
     }
 
+    #[test]
+    fn prints_help_without_other_args() {
+        let err = Args::try_parse_from(["dt-main", "--help"]).unwrap_err();
+        assert_eq!(err.kind(), clap::error::ErrorKind::DisplayHelp);
+        // clap exits with 0 for help
+        assert_eq!(err.exit_code(), 0);
+
+        let help = err.to_string();
+        for flag in [
+            "--version",
+            "--config-path",
+            "--config-source",
+            "--nacos-address",
+            "--nacos-dataid",
+            "--nacos-group",
+            "--nacos-namespace",
+            "--nacos-username",
+            "--nacos-password",
+            "--nacos-watch",
+            "--nacos-keep-comments",
+            "--init",
+            "--precheck-report",
+            "--precheck-report-path",
+            "[CONFIG]",
+            "[default: local]",
+            "[default: DEFAULT_GROUP]",
+        ] {
+            assert!(help.contains(flag), "{flag} is missing in help:\n{help}");
+        }
+    }
+
+    #[test]
+    fn suggests_closest_flag_for_unknown_flag() {
+        let err =
+            Args::try_parse_from(["dt-main", "--nacos-adress", "http://nacos:8848"]).unwrap_err();
+        assert_eq!(err.kind(), clap::error::ErrorKind::UnknownArgument);
+        assert!(err.to_string().contains("--nacos-address"), "{err}");
+    }
+
+    #[test]
+    fn accepts_stdin_config_path() {
+        let args = Args::try_parse_from(["dt-main", "--config-path", "-"]).unwrap();
+        assert_eq!(args.config_path(), Some(config_source::STDIN_CONFIG_PATH));
+        assert!(args.validate().is_ok());
+
+        let args = Args::try_parse_from(["dt-main", "-"]).unwrap();
+        assert_eq!(args.config_path(), Some(config_source::STDIN_CONFIG_PATH));
+        assert!(args.validate().is_ok());
+    }
+
     #[test]
     fn accepts_nacos_config_source() {
         let args = Args::try_parse_from([
@@ -198,7 +396,38 @@ mod tests {
         .unwrap();
         assert_eq!(args.config_source, ConfigSourceKind::Nacos);
         assert!(args.config_path().is_none());
+        assert!(args.nacos_namespace.is_none());
+        assert!(args.validate().is_ok());
+
+        let args = Args::try_parse_from([
+            "dt-main",
+            "--config-source",
+            "nacos",
+            "--nacos-address",
+            "http://nacos:8848",
+            "--nacos-dataid",
+            "task_pg.ini",
+            "--nacos-namespace",
+            "dev",
+        ])
+        .unwrap();
+        assert_eq!(args.nacos_namespace.as_deref(), Some("dev"));
+        assert!(!args.nacos_watch);
         assert!(args.validate().is_ok());
+
+        let args = Args::try_parse_from([
+            "dt-main",
+            "--config-source",
+            "nacos",
+            "--nacos-address",
+            "http://nacos:8848",
+            "--nacos-dataid",
+            "task_pg.ini",
+            "--nacos-username",
+            "nacos",
+        ])
+        .unwrap();
+        assert!(args.validate().is_err());
     }
 
     #[test]
@@ -218,6 +447,71 @@ mod tests {
         assert!(args.validate().is_err());
     }
 
+    #[test]
+    fn accepts_nacos_watch_flag() {
+        let args = Args::try_parse_from([
+            "dt-main",
+            "--config-source",
+            "nacos",
+            "--nacos-address",
+            "http://nacos:8848",
+            "--nacos-dataid",
+            "task_pg.ini",
+            "--nacos-watch",
+        ])
+        .unwrap();
+        assert!(args.nacos_watch);
+        assert!(args.validate().is_ok());
+
+        let args =
+            Args::try_parse_from(["dt-main", "--config", "task.ini", "--nacos-watch"]).unwrap();
+        assert!(args.validate().is_err());
+    }
+
+    #[test]
+    fn accepts_precheck_report_flag() {
+        let args =
+            Args::try_parse_from(["dt-main", "--config", "precheck.ini", "--precheck-report"])
+                .unwrap();
+        assert!(args.precheck_report);
+        assert!(args.validate().is_ok());
+
+        let args = Args::try_parse_from(["dt-main", "precheck.ini"]).unwrap();
+        assert!(!args.precheck_report);
+        assert!(args.precheck_report_path.is_none());
+
+        let args = Args::try_parse_from([
+            "dt-main",
+            "precheck.ini",
+            "--precheck-report-path",
+            "precheck_report.json",
+        ])
+        .unwrap();
+        assert_eq!(
+            args.precheck_report_path.as_deref(),
+            Some("precheck_report.json")
+        );
+    }
+
+    #[test]
+    fn writes_precheck_report() {
+        let path = env::temp_dir().join(format!(
+            "ape-dts-precheck-report-{}.json",
+            std::process::id()
+        ));
+        let report = PrecheckReport {
+            passed: true,
+            checks: Vec::new(),
+        };
+        write_precheck_report(path.to_str().unwrap(), &report).unwrap();
+
+        let content: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(content["passed"], serde_json::Value::Bool(true));
+        assert!(content["checks"].as_array().unwrap().is_empty());
+        fs::remove_file(&path).unwrap();
+    }
+
     #[test]
     fn version_does_not_require_config() {
         let args = Args::try_parse_from(["dt-main", "--version"]).unwrap();
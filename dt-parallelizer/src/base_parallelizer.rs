@@ -21,6 +21,8 @@ type SharedSinker = Arc<async_mutex::Mutex<Box<dyn Sinker + Send>>>;
 pub struct BaseParallelizer {
     pub popped_data: VecDeque<DtItem>,
     pub monitor: TaskMonitorHandle,
+    // draining stops once the popped bytes reach it, 0 means no limit
+    pub max_drain_bytes: u64,
 }
 
 impl BaseParallelizer {
@@ -39,6 +41,9 @@ impl BaseParallelizer {
             {
                 // merge when sql type is the same
                 data.push(item);
+                if self.reach_max_drain_bytes(&record_size_counter) {
+                    break;
+                }
             } else {
                 self.popped_data.push_back(item);
                 break;
@@ -66,6 +71,11 @@ impl BaseParallelizer {
         Ok(data)
     }
 
+    // the item reaching the cap is kept in the batch, so a batch may exceed the cap by one item
+    pub fn reach_max_drain_bytes(&self, record_size_counter: &Counter) -> bool {
+        self.max_drain_bytes > 0 && record_size_counter.value >= self.max_drain_bytes
+    }
+
     pub async fn pop(
         &self,
         buffer: &DtQueue,
@@ -0,0 +1,188 @@
+use dt_common::meta::{rdb_meta_manager::RdbMetaManager, row_data::RowData, row_type::RowType};
+
+// Reorders rows of a batch so deletes are sinked before inserts/updates.
+// Rows of the same key keep their final state:
+//   [insert k, delete k, insert k] -> [delete k, insert k]
+//   [insert k, delete k] -> [delete k]
+// rows without a usable key (no pk/uk, NULL key values, key changed by update) are barriers,
+// rows before a barrier are never moved after it and vice versa.
+pub struct DmlOrderer {
+    pub meta_manager: RdbMetaManager,
+}
+
+type RowKey = (String, String, u128);
+
+impl DmlOrderer {
+    pub async fn order(&mut self, data: Vec<RowData>) -> anyhow::Result<Vec<RowData>> {
+        let mut keys = Vec::with_capacity(data.len());
+        for row_data in data.iter() {
+            keys.push(self.get_key(row_data).await?);
+        }
+        Ok(Self::order_by_keys(data, keys))
+    }
+
+    pub async fn close(&self) -> anyhow::Result<()> {
+        self.meta_manager.close().await
+    }
+
+    async fn get_key(&mut self, row_data: &RowData) -> anyhow::Result<Option<u128>> {
+        if row_data.contains_unchanged_toast() {
+            return Ok(None);
+        }
+
+        let tb_meta = self
+            .meta_manager
+            .get_tb_meta(&row_data.schema, &row_data.tb)
+            .await?;
+        if tb_meta.key_map.is_empty() {
+            return Ok(None);
+        }
+
+        if row_data.row_type == RowType::Update {
+            let before = row_data.require_before()?;
+            let after = row_data.require_after()?;
+            for key_cols in tb_meta.key_map.values() {
+                if key_cols.iter().any(|col| before.get(col) != after.get(col)) {
+                    return Ok(None);
+                }
+            }
+        }
+
+        let hash_code = row_data.get_hash_code(tb_meta)?;
+        if hash_code == 0 {
+            return Ok(None);
+        }
+        Ok(Some(hash_code))
+    }
+
+    fn order_by_keys(data: Vec<RowData>, keys: Vec<Option<u128>>) -> Vec<RowData> {
+        let mut results = Vec::with_capacity(data.len());
+        let mut deletes = Vec::new();
+        let mut upserts: Vec<(RowKey, RowData)> = Vec::new();
+
+        for (row_data, key) in data.into_iter().zip(keys) {
+            let Some(hash_code) = key else {
+                Self::flush(&mut results, &mut deletes, &mut upserts);
+                results.push(row_data);
+                continue;
+            };
+
+            let row_key = (row_data.schema.clone(), row_data.tb.clone(), hash_code);
+            if row_data.row_type == RowType::Delete {
+                // the delete wipes out the effect of earlier upserts of the same key
+                upserts.retain(|(k, _)| *k != row_key);
+                deletes.push(row_data);
+            } else {
+                upserts.push((row_key, row_data));
+            }
+        }
+
+        Self::flush(&mut results, &mut deletes, &mut upserts);
+        results
+    }
+
+    fn flush(
+        results: &mut Vec<RowData>,
+        deletes: &mut Vec<RowData>,
+        upserts: &mut Vec<(RowKey, RowData)>,
+    ) {
+        results.append(deletes);
+        results.extend(upserts.drain(..).map(|(_, row_data)| row_data));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use dt_common::meta::col_value::ColValue;
+
+    use super::*;
+
+    fn build_row(row_type: RowType, id: i32, value: &str) -> RowData {
+        let mut col_values = HashMap::new();
+        col_values.insert("id".to_string(), ColValue::Long(id));
+        col_values.insert("value".to_string(), ColValue::String(value.into()));
+        let (before, after) = match row_type {
+            RowType::Insert => (None, Some(col_values)),
+            RowType::Delete => (Some(col_values), None),
+            RowType::Update => (Some(col_values.clone()), Some(col_values)),
+        };
+        RowData::new("db_1".into(), "tb_1".into(), 0, row_type, before, after)
+    }
+
+    // applies rows to an upsert target keyed by id
+    fn apply(rows: &[RowData]) -> HashMap<i32, String> {
+        let mut target = HashMap::new();
+        for row_data in rows {
+            let col_values = match row_data.row_type {
+                RowType::Delete => row_data.before.as_ref().unwrap(),
+                _ => row_data.after.as_ref().unwrap(),
+            };
+            let id = match col_values["id"] {
+                ColValue::Long(v) => v,
+                _ => unreachable!(),
+            };
+            if row_data.row_type == RowType::Delete {
+                target.remove(&id);
+            } else {
+                target.insert(id, col_values["value"].to_option_string().unwrap());
+            }
+        }
+        target
+    }
+
+    #[test]
+    fn test_order_delete_insert_same_key() {
+        // replace-style update of id=1, then a plain insert of id=2
+        let rows = vec![
+            build_row(RowType::Insert, 1, "a"),
+            build_row(RowType::Insert, 2, "b"),
+            build_row(RowType::Delete, 1, "a"),
+            build_row(RowType::Insert, 1, "c"),
+        ];
+        let keys = vec![Some(1), Some(2), Some(1), Some(1)];
+        let expected = apply(&rows);
+
+        let ordered = DmlOrderer::order_by_keys(rows, keys);
+        let row_types: Vec<RowType> = ordered.iter().map(|r| r.row_type.clone()).collect();
+        assert_eq!(
+            row_types,
+            vec![RowType::Delete, RowType::Insert, RowType::Insert]
+        );
+        assert_eq!(apply(&ordered), expected);
+        assert_eq!(expected[&1], "c");
+    }
+
+    #[test]
+    fn test_order_trailing_delete() {
+        let rows = vec![
+            build_row(RowType::Insert, 1, "a"),
+            build_row(RowType::Update, 1, "b"),
+            build_row(RowType::Delete, 1, "b"),
+        ];
+        let keys = vec![Some(1), Some(1), Some(1)];
+
+        let ordered = DmlOrderer::order_by_keys(rows, keys);
+        assert_eq!(ordered.len(), 1);
+        assert_eq!(ordered[0].row_type, RowType::Delete);
+        assert!(apply(&ordered).is_empty());
+    }
+
+    #[test]
+    fn test_order_keeps_barrier() {
+        let rows = vec![
+            build_row(RowType::Insert, 1, "a"),
+            build_row(RowType::Update, 2, "b"),
+            build_row(RowType::Delete, 3, "c"),
+        ];
+        let keys = vec![Some(1), None, Some(3)];
+
+        let ordered = DmlOrderer::order_by_keys(rows, keys);
+        let row_types: Vec<RowType> = ordered.iter().map(|r| r.row_type.clone()).collect();
+        assert_eq!(
+            row_types,
+            vec![RowType::Insert, RowType::Update, RowType::Delete]
+        );
+    }
+}
@@ -1,5 +1,6 @@
 pub mod base_parallelizer;
 pub mod chunk_partitioner;
+pub mod dml_orderer;
 pub mod foxlake_parallelizer;
 pub mod merge_parallelizer;
 pub mod mongo_merger;
@@ -6,16 +6,19 @@ use dt_common::meta::{
     dt_queue::DtQueue,
     row_data::RowData,
 };
-use dt_common::monitor::counter::Counter;
+use dt_common::monitor::{counter::Counter, counter_type::CounterType};
 use dt_connector::Sinker;
 
-use super::{base_parallelizer::BaseParallelizer, rdb_partitioner::RdbPartitioner};
+use super::{
+    base_parallelizer::BaseParallelizer, dml_orderer::DmlOrderer, rdb_partitioner::RdbPartitioner,
+};
 use crate::{DataSize, Parallelizer};
 
 pub struct PartitionParallelizer {
     pub base_parallelizer: BaseParallelizer,
     pub partitioner: RdbPartitioner,
     pub parallel_size: usize,
+    pub dml_orderer: Option<DmlOrderer>,
 }
 
 #[async_trait]
@@ -25,6 +28,9 @@ impl Parallelizer for PartitionParallelizer {
     }
 
     async fn close(&mut self) -> anyhow::Result<()> {
+        if let Some(dml_orderer) = &self.dml_orderer {
+            dml_orderer.close().await?;
+        }
         self.partitioner.close().await
     }
 
@@ -54,6 +60,13 @@ impl Parallelizer for PartitionParallelizer {
 
                 _ => {}
             }
+
+            if self
+                .base_parallelizer
+                .reach_max_drain_bytes(&record_size_counter)
+            {
+                break;
+            }
         }
 
         self.base_parallelizer
@@ -72,7 +85,13 @@ impl Parallelizer for PartitionParallelizer {
             bytes: data.iter().map(|v| v.get_data_size()).sum(),
         };
 
+        let data = if let Some(dml_orderer) = &mut self.dml_orderer {
+            dml_orderer.order(data).await?
+        } else {
+            data
+        };
         let sub_data = self.partitioner.partition(data, self.parallel_size).await?;
+        Self::record_partition_sizes(&self.base_parallelizer, &sub_data).await;
         let _ = self
             .base_parallelizer
             .sink_dml(sub_data, sinkers, self.parallel_size, false)
@@ -81,3 +100,145 @@ impl Parallelizer for PartitionParallelizer {
         Ok(data_size)
     }
 }
+
+impl PartitionParallelizer {
+    // max/min/avg of partition_records and partition_skew show how evenly rows are partitioned
+    async fn record_partition_sizes(
+        base_parallelizer: &BaseParallelizer,
+        sub_data: &[Vec<RowData>],
+    ) {
+        let partition_sizes = Self::get_partition_sizes(sub_data);
+        let Some(skew) = Self::get_partition_skew(&partition_sizes) else {
+            return;
+        };
+
+        let monitor = &base_parallelizer.monitor;
+        for partition_size in partition_sizes {
+            monitor
+                .add_counter(
+                    monitor.default_task_id(),
+                    CounterType::PartitionRecords,
+                    partition_size,
+                )
+                .await;
+        }
+        monitor
+            .add_counter(monitor.default_task_id(), CounterType::PartitionSkew, skew)
+            .await;
+    }
+
+    fn get_partition_sizes(sub_data: &[Vec<RowData>]) -> Vec<u64> {
+        sub_data.iter().map(|v| v.len() as u64).collect()
+    }
+
+    // none if nothing was partitioned
+    fn get_partition_skew(partition_sizes: &[u64]) -> Option<u64> {
+        let total: u64 = partition_sizes.iter().sum();
+        if partition_sizes.len() <= 1 || total == 0 {
+            return None;
+        }
+        let max = partition_sizes.iter().max().copied().unwrap_or(0);
+        Some(max * 100 * partition_sizes.len() as u64 / total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use dt_common::{
+        config::config_enums::{TaskKind, TaskType},
+        meta::{col_value::ColValue, row_type::RowType},
+        monitor::{
+            monitor::Monitor,
+            task_monitor::{MonitorType, TaskMonitor},
+            task_monitor_handle::TaskMonitorHandle,
+        },
+    };
+
+    use super::*;
+
+    fn build_rows(count: usize) -> Vec<RowData> {
+        (0..count)
+            .map(|i| {
+                let after = HashMap::from([("id".to_string(), ColValue::Long(i as i32))]);
+                RowData::new(
+                    "db_1".into(),
+                    "tb_1".into(),
+                    0,
+                    RowType::Insert,
+                    None,
+                    Some(after),
+                )
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_record_partition_sizes() {
+        let task_id = "partition_task";
+        let task_monitor = Arc::new(TaskMonitor::new(Some(TaskType::new(TaskKind::Cdc, None))));
+        let monitor = Arc::new(Monitor::new("pipeline", "partition", 60, 100, 100));
+        task_monitor.register(task_id, vec![(MonitorType::Pipeline, monitor.clone())]);
+        let base_parallelizer = BaseParallelizer {
+            monitor: TaskMonitorHandle::new(
+                task_monitor,
+                MonitorType::Pipeline,
+                task_id.into(),
+                60,
+                100,
+                100,
+            ),
+            ..Default::default()
+        };
+
+        let statistics = |counter_type: CounterType| {
+            let counter = monitor
+                .time_window_counters
+                .get(&counter_type)
+                .map(|entry| entry.value().clone());
+            async move {
+                match counter {
+                    Some(counter) => Some(counter.statistics().await),
+                    None => None,
+                }
+            }
+        };
+
+        // a single partition or an empty batch records nothing
+        PartitionParallelizer::record_partition_sizes(&base_parallelizer, &[build_rows(5)]).await;
+        PartitionParallelizer::record_partition_sizes(
+            &base_parallelizer,
+            &[build_rows(0), build_rows(0)],
+        )
+        .await;
+        assert!(statistics(CounterType::PartitionRecords).await.is_none());
+        assert!(statistics(CounterType::PartitionSkew).await.is_none());
+
+        // max 6 vs avg 3
+        let sub_data = vec![build_rows(6), build_rows(0), build_rows(3), build_rows(3)];
+        PartitionParallelizer::record_partition_sizes(&base_parallelizer, &sub_data).await;
+        let records = statistics(CounterType::PartitionRecords).await.unwrap();
+        assert_eq!(records.sum, 12);
+        assert_eq!(records.count, 4);
+        assert_eq!(records.max, 6);
+        assert_eq!(records.min, 0);
+        let skew = statistics(CounterType::PartitionSkew).await.unwrap();
+        assert_eq!(skew.sum, 200);
+        assert_eq!(skew.count, 1);
+
+        // even partitions
+        PartitionParallelizer::record_partition_sizes(
+            &base_parallelizer,
+            &[build_rows(4), build_rows(4)],
+        )
+        .await;
+        let records = statistics(CounterType::PartitionRecords).await.unwrap();
+        assert_eq!(records.sum, 20);
+        assert_eq!(records.count, 6);
+        let skew = statistics(CounterType::PartitionSkew).await.unwrap();
+        assert_eq!(skew.sum, 300);
+        assert_eq!(skew.max, 200);
+        assert_eq!(skew.min, 100);
+    }
+}
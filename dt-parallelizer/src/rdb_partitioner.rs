@@ -1,8 +1,19 @@
+use std::collections::HashMap;
+
+use dt_common::config::config_enums::NoKeyPartition;
 use dt_common::log_debug;
-use dt_common::meta::{rdb_meta_manager::RdbMetaManager, row_data::RowData, row_type::RowType};
+use dt_common::meta::{
+    col_value::ColValue, rdb_meta_manager::RdbMetaManager, rdb_tb_meta::RdbTbMeta,
+    row_data::RowData, row_type::RowType,
+};
+
+// partition cols of tables without primary/unique keys, by (db, tb)
+pub type NoKeyPartitionCols = HashMap<(String, String), Vec<String>>;
 
 pub struct RdbPartitioner {
     pub meta_manager: RdbMetaManager,
+    pub no_key_partition: NoKeyPartition,
+    pub no_key_partition_cols: NoKeyPartitionCols,
 }
 
 impl RdbPartitioner {
@@ -70,12 +81,21 @@ impl RdbPartitioner {
                 }
             }
         }
-        // no need to check partition_col if key_map is not empty,
+        // no need to check partition cols if key_map is not empty,
         // in which case partition_col is one of the key cols and has been checked
-        if tb_meta.key_map.is_empty()
-            && before.get(&tb_meta.partition_col) != after.get(&tb_meta.partition_col)
-        {
-            return Ok(false);
+        if tb_meta.key_map.is_empty() {
+            let partition_cols = Self::get_partition_cols(
+                tb_meta,
+                row_data,
+                &self.no_key_partition,
+                &self.no_key_partition_cols,
+            );
+            if partition_cols
+                .iter()
+                .any(|col| before.get(col) != after.get(col))
+            {
+                return Ok(false);
+            }
         }
 
         Ok(true)
@@ -99,13 +119,52 @@ impl RdbPartitioner {
             .meta_manager
             .get_tb_meta(&row_data.schema, &row_data.tb)
             .await?;
-        if let Some(partition_col_value) = col_values.get(&tb_meta.partition_col) {
-            Ok(partition_col_value.hash_code()? as usize % partition_count)
-        } else {
-            Ok(0)
+        let partition_cols = Self::get_partition_cols(
+            tb_meta,
+            row_data,
+            &self.no_key_partition,
+            &self.no_key_partition_cols,
+        );
+        Ok(Self::hash_cols(partition_cols, col_values)? as usize % partition_count)
+    }
+
+    fn get_partition_cols<'a>(
+        tb_meta: &'a RdbTbMeta,
+        row_data: &RowData,
+        no_key_partition: &NoKeyPartition,
+        no_key_partition_cols: &'a NoKeyPartitionCols,
+    ) -> &'a [String] {
+        if !tb_meta.key_map.is_empty() {
+            return std::slice::from_ref(&tb_meta.partition_col);
+        }
+
+        if !no_key_partition_cols.is_empty() {
+            if let Some(cols) =
+                no_key_partition_cols.get(&(row_data.schema.clone(), row_data.tb.clone()))
+            {
+                return cols;
+            }
+        }
+
+        match no_key_partition {
+            NoKeyPartition::FirstCol => std::slice::from_ref(&tb_meta.partition_col),
+            NoKeyPartition::AllCols => &tb_meta.cols,
         }
     }
 
+    // missing cols count as NULL, a single col hashes the same as its value
+    fn hash_cols(cols: &[String], col_values: &HashMap<String, ColValue>) -> anyhow::Result<u64> {
+        let mut hash_code: u64 = 0;
+        for col in cols {
+            let col_hash_code = match col_values.get(col) {
+                Some(col_value) => col_value.hash_code()?,
+                None => 0,
+            };
+            hash_code = hash_code.wrapping_mul(31).wrapping_add(col_hash_code);
+        }
+        Ok(hash_code)
+    }
+
     pub fn partition_for_row_data(
         &mut self,
         _data: Vec<RowData>,
@@ -117,3 +176,93 @@ impl RdbPartitioner {
         self.meta_manager.close().await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_tb_meta() -> RdbTbMeta {
+        RdbTbMeta {
+            schema: "db_1".into(),
+            tb: "tb_1".into(),
+            cols: vec!["c1".into(), "c2".into(), "c3".into()],
+            partition_col: "c1".into(),
+            ..Default::default()
+        }
+    }
+
+    fn build_row(c1: i32, c2: &str, c3: i32) -> RowData {
+        let after = HashMap::from([
+            ("c1".to_string(), ColValue::Long(c1)),
+            ("c2".to_string(), ColValue::String(c2.into())),
+            ("c3".to_string(), ColValue::Long(c3)),
+        ]);
+        RowData::new(
+            "db_1".into(),
+            "tb_1".into(),
+            0,
+            RowType::Insert,
+            None,
+            Some(after),
+        )
+    }
+
+    fn get_partition_index(
+        row_data: &RowData,
+        no_key_partition: &NoKeyPartition,
+        no_key_partition_cols: &NoKeyPartitionCols,
+    ) -> usize {
+        let tb_meta = build_tb_meta();
+        let partition_cols = RdbPartitioner::get_partition_cols(
+            &tb_meta,
+            row_data,
+            no_key_partition,
+            no_key_partition_cols,
+        );
+        RdbPartitioner::hash_cols(partition_cols, row_data.require_after().unwrap()).unwrap()
+            as usize
+            % 16
+    }
+
+    #[test]
+    fn test_no_key_partition_cols() {
+        let no_key_partition_cols = NoKeyPartitionCols::from([(
+            ("db_1".to_string(), "tb_1".to_string()),
+            vec!["c2".to_string(), "c3".to_string()],
+        )]);
+        let indexes: Vec<usize> = (0..32)
+            .map(|c1| {
+                get_partition_index(
+                    &build_row(c1, "a", 1),
+                    &NoKeyPartition::FirstCol,
+                    &no_key_partition_cols,
+                )
+            })
+            .collect();
+        // rows with the same c2, c3 land in the same sub-batch whatever c1 is
+        assert!(indexes.iter().all(|i| *i == indexes[0]));
+    }
+
+    #[test]
+    fn test_no_key_partition_all_cols() {
+        let no_key_partition_cols = NoKeyPartitionCols::new();
+        let index = |row_data: &RowData| {
+            get_partition_index(row_data, &NoKeyPartition::AllCols, &no_key_partition_cols)
+        };
+        assert_eq!(index(&build_row(1, "a", 1)), index(&build_row(1, "a", 1)));
+        // rows sharing only the first col are spread across sub-batches
+        let indexes: Vec<usize> = (0..32).map(|c3| index(&build_row(1, "a", c3))).collect();
+        assert!(indexes.iter().any(|i| *i != indexes[0]));
+
+        // a single partition col hashes the same as its value
+        let first_col_index = get_partition_index(
+            &build_row(7, "a", 1),
+            &NoKeyPartition::FirstCol,
+            &no_key_partition_cols,
+        );
+        assert_eq!(
+            first_col_index,
+            ColValue::Long(7).hash_code().unwrap() as usize % 16
+        );
+    }
+}
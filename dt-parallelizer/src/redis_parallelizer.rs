@@ -1,4 +1,4 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
 use anyhow::bail;
 use async_trait::async_trait;
@@ -8,11 +8,17 @@ use crate::{DataSize, Parallelizer};
 use dt_common::meta::{
     dt_data::{DtData, DtItem},
     dt_queue::DtQueue,
-    redis::command::key_parser::KeyParser,
+    redis::{command::key_parser::KeyParser, redis_entry::RedisEntry},
 };
-use dt_common::{error::Error, log_warn};
+use dt_common::{error::Error, log_error, log_warn};
 use dt_connector::Sinker;
 
+// max commands named in the error of a failed node
+const MAX_REPORTED_CMDS: usize = 3;
+// times to sink the batch of a failed node again before the task fails
+const MAX_NODE_RETRIES: usize = 2;
+const NODE_RETRY_INTERVAL: Duration = Duration::from_millis(500);
+
 pub struct RedisParallelizer {
     pub base_parallelizer: BaseParallelizer,
     pub parallel_size: usize,
@@ -65,6 +71,14 @@ impl Parallelizer for RedisParallelizer {
         // for redis cluster
         for mut dt_item in data {
             let slots = if let DtData::Redis { entry } = &mut dt_item.dt_data {
+                // redis cluster only has db 0
+                if Self::is_db_cmd(entry) {
+                    bail! {Error::RedisCmdError(format!(
+                        "cmds across dbs are not supported by redis cluster, cmd: {}",
+                        entry.cmd
+                    ))};
+                }
+
                 let slots = entry.cal_slots(&self.key_parser)?;
                 for i in 1..slots.len() {
                     if slots[i] != slots[0] {
@@ -84,7 +98,7 @@ impl Parallelizer for RedisParallelizer {
                 vec![]
             };
 
-            // example: SWAPDB 0 1
+            // example: FLUSHDB
             // sink to all nodes
             if slots.is_empty() {
                 for node_data in node_data_items.iter_mut() {
@@ -96,28 +110,504 @@ impl Parallelizer for RedisParallelizer {
             // find the dst node for entry by slot
             let node = *self.slot_node_map.get(&slots[0]).unwrap();
             let sinker_index = *self.node_sinker_index_map.get(node).unwrap();
-            node_data_items[sinker_index].push(dt_item);
+            let node_data = &mut node_data_items[sinker_index];
+            if !Self::try_merge_del(node_data, &dt_item, slots[0]) {
+                node_data.push(dt_item);
+            }
+        }
+
+        let index_node_map: HashMap<usize, String> = self
+            .node_sinker_index_map
+            .iter()
+            .map(|(node, i)| (*i, node.clone()))
+            .collect();
+
+        let mut node_batches: Vec<(usize, Vec<DtItem>)> =
+            node_data_items.into_iter().enumerate().collect();
+        let mut retries = 0;
+        loop {
+            let failures = Self::sink_to_nodes(node_batches, sinkers).await;
+            if failures.is_empty() {
+                return Ok(data_size);
+            }
+
+            let mut failed_nodes = Vec::new();
+            let mut messages = Vec::new();
+            for (i, node_data, err) in failures.iter() {
+                let node = index_node_map.get(i).cloned().unwrap_or_default();
+                let cmds = Self::summarize_cmds(node_data);
+                log_error!(
+                    "failed to sink to redis node: {}, {}, retries: {}, error: {}",
+                    node,
+                    cmds,
+                    retries,
+                    err
+                );
+                messages.push(format!("node: {}, {}, error: {}", node, cmds, err));
+                failed_nodes.push(node);
+            }
+
+            if retries >= MAX_NODE_RETRIES {
+                bail! {Error::RedisClusterSinkError {
+                    nodes: failed_nodes,
+                    message: messages.join("; "),
+                }}
+            }
+
+            // only the batches of the failed nodes are sinked again,
+            // the batches already sinked to the healthy nodes are not repeated
+            retries += 1;
+            tokio::time::sleep(NODE_RETRY_INTERVAL).await;
+            node_batches = failures
+                .into_iter()
+                .map(|(i, node_data, _)| (i, node_data))
+                .collect();
+        }
+    }
+}
+
+impl RedisParallelizer {
+    // cmds moving keys across dbs, SELECT never gets here since the extractor turns it into
+    // the db_id of the following entries, which the sinker maps by [router] db_map
+    fn is_db_cmd(entry: &RedisEntry) -> bool {
+        if entry.is_base {
+            return false;
+        }
+        match entry.cmd.get_name().to_ascii_uppercase().as_str() {
+            "SWAPDB" | "MOVE" => true,
+            // COPY source destination [DB destination-db] [REPLACE]
+            "COPY" => entry
+                .cmd
+                .args
+                .iter()
+                .skip(3)
+                .any(|arg| arg.eq_ignore_ascii_case(b"DB")),
+            _ => false,
+        }
+    }
+
+    // merges a single key DEL/UNLINK into the previous DEL/UNLINK of the node to save round trips,
+    // only if both are of the same db and slot since redis cluster rejects multi keys across slots,
+    // returns false if not merged
+    fn try_merge_del(node_data: &mut [DtItem], dt_item: &DtItem, slot: u16) -> bool {
+        let DtData::Redis { entry } = &dt_item.dt_data else {
+            return false;
+        };
+        let Some(last_item) = node_data.last_mut() else {
+            return false;
+        };
+        let DtData::Redis { entry: last_entry } = &mut last_item.dt_data else {
+            return false;
+        };
+
+        if !Self::is_del(entry)
+            || !Self::is_del(last_entry)
+            || entry.cmd.keys.len() != 1
+            || entry.cmd.get_name() != last_entry.cmd.get_name()
+            || entry.db_id != last_entry.db_id
+            || KeyParser::calc_slot(last_entry.cmd.keys[0].as_bytes()) != slot
+        {
+            return false;
         }
 
+        // the merged cmd carries the data of both, taken before the cmd grows
+        last_entry.data_size = last_entry.get_data_malloc_size() + entry.get_data_malloc_size();
+        last_entry.cmd.key_indexes.push(last_entry.cmd.args.len());
+        last_entry
+            .cmd
+            .add_arg(entry.cmd.args[entry.cmd.key_indexes[0]].clone());
+        last_entry.cmd.keys.push(entry.cmd.keys[0].clone());
+        last_item.position = dt_item.position.clone();
+        true
+    }
+
+    // sinks the batch of each node in parallel and waits for all nodes even if some of them failed,
+    // returns the batches of the failed nodes with their errors
+    async fn sink_to_nodes(
+        node_batches: Vec<(usize, Vec<DtItem>)>,
+        sinkers: &[Arc<async_mutex::Mutex<Box<dyn Sinker + Send>>>],
+    ) -> Vec<(usize, Vec<DtItem>, String)> {
         let mut futures = Vec::new();
-        for sinker in sinkers.iter().take(node_data_items.len()) {
-            let node_data = node_data_items.remove(0);
-            let sinker = sinker.clone();
-            let future = tokio::spawn(async move {
-                sinker
-                    .lock()
-                    .await
-                    .sink_raw(node_data, false)
-                    .await
-                    .unwrap()
+        for (i, node_data) in node_batches {
+            let sinker = sinkers[i].clone();
+            let retry_data = node_data.clone();
+            let future =
+                tokio::spawn(async move { sinker.lock().await.sink_raw(node_data, false).await });
+            futures.push((i, retry_data, future));
+        }
+
+        let mut failures = Vec::new();
+        for (i, node_data, future) in futures {
+            let err = match future.await {
+                Ok(Ok(())) => continue,
+                Ok(Err(err)) => format!("{:#}", err),
+                Err(err) => format!("sink task aborted: {}", err),
+            };
+            failures.push((i, node_data, err));
+        }
+        failures
+    }
+
+    fn is_del(entry: &RedisEntry) -> bool {
+        !entry.is_base
+            && !entry.cmd.keys.is_empty()
+            && entry.cmd.key_indexes.len() == entry.cmd.keys.len()
+            && matches!(entry.cmd.get_name().as_str(), "DEL" | "UNLINK")
+    }
+
+    // e.g. cmds: 120, [SET key_1, HSET key_2, rdb key_3, ...]
+    fn summarize_cmds(node_data: &[DtItem]) -> String {
+        let mut samples = Vec::new();
+        for dt_item in node_data.iter().take(MAX_REPORTED_CMDS) {
+            if let DtData::Redis { entry } = &dt_item.dt_data {
+                if entry.is_base {
+                    samples.push(format!("rdb {}", entry.key));
+                } else {
+                    samples.push(format!(
+                        "{} {}",
+                        entry.cmd.get_name(),
+                        entry.cmd.keys.join(" ")
+                    ));
+                }
+            }
+        }
+        if node_data.len() > MAX_REPORTED_CMDS {
+            samples.push("...".into());
+        }
+        format!("cmds: {}, [{}]", node_data.len(), samples.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use dt_common::meta::{
+        position::Position,
+        redis::{redis_entry::RedisEntry, redis_object::RedisCmd},
+    };
+
+    use super::*;
+
+    struct MockSinker {
+        id: String,
+        // sink_raw fails for this many times before it succeeds
+        fail_times: usize,
+        sinked: Arc<std::sync::Mutex<Vec<String>>>,
+        sinked_db_ids: Arc<std::sync::Mutex<Vec<i64>>>,
+    }
+
+    #[async_trait]
+    impl Sinker for MockSinker {
+        async fn sink_raw(&mut self, data: Vec<DtItem>, _batch: bool) -> anyhow::Result<()> {
+            if self.fail_times > 0 {
+                self.fail_times -= 1;
+                bail!("connection refused")
+            }
+            for dt_item in data {
+                if let DtData::Redis { entry } = dt_item.dt_data {
+                    self.sinked.lock().unwrap().push(entry.cmd.to_string());
+                    self.sinked_db_ids.lock().unwrap().push(entry.db_id);
+                }
+            }
+            Ok(())
+        }
+
+        fn get_id(&self) -> String {
+            self.id.clone()
+        }
+    }
+
+    fn build_item(key: &str) -> DtItem {
+        build_cmd_item(&["SET", key, "v"])
+    }
+
+    fn build_cmd_item(args: &[&str]) -> DtItem {
+        let mut entry = RedisEntry::new();
+        entry.cmd = RedisCmd::from_str_args(args);
+        DtItem {
+            dt_data: DtData::Redis { entry },
+            position: Position::None,
+            data_origin_node: String::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sink_raw_merges_dels() {
+        let sinked = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sinker: Box<dyn Sinker + Send> = Box::new(MockSinker {
+            id: "node_1".into(),
+            fail_times: 0,
+            sinked: sinked.clone(),
+            sinked_db_ids: Default::default(),
+        });
+        let sinkers = vec![Arc::new(async_mutex::Mutex::new(sinker))];
+
+        let mut parallelizer = RedisParallelizer {
+            base_parallelizer: BaseParallelizer::default(),
+            parallel_size: 1,
+            slot_node_map: (0..16384).map(|slot| (slot, "node_1")).collect(),
+            key_parser: KeyParser::new(),
+            node_sinker_index_map: HashMap::new(),
+        };
+
+        // keys with the same hash tag are in the same slot
+        let data = vec![
+            build_cmd_item(&["DEL", "{user}:1"]),
+            build_cmd_item(&["DEL", "{user}:2"]),
+            build_cmd_item(&["DEL", "{user}:3"]),
+            build_cmd_item(&["SET", "{user}:1", "v"]),
+            build_cmd_item(&["UNLINK", "{user}:2"]),
+            build_cmd_item(&["DEL", "{user}:3"]),
+        ];
+        parallelizer.sink_raw(data, &sinkers).await.unwrap();
+
+        assert_eq!(
+            *sinked.lock().unwrap(),
+            vec![
+                "DEL {user}:1 {user}:2 {user}:3",
+                "SET {user}:1 v",
+                "UNLINK {user}:2",
+                "DEL {user}:3",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merge_del_adds_data_size() {
+        let build_del_item = |key: &str, data_size: usize| {
+            let mut dt_item = build_cmd_item(&["DEL", key]);
+            if let DtData::Redis { entry } = &mut dt_item.dt_data {
+                entry.data_size = data_size;
+            }
+            dt_item
+        };
+
+        let slot = KeyParser::calc_slot("{user}:1".as_bytes());
+        let mut node_data = vec![build_del_item("{user}:1", 10)];
+        assert!(RedisParallelizer::try_merge_del(
+            &mut node_data,
+            &build_del_item("{user}:2", 20),
+            slot
+        ));
+        assert!(RedisParallelizer::try_merge_del(
+            &mut node_data,
+            &build_del_item("{user}:3", 30),
+            slot
+        ));
+        assert_eq!(node_data.len(), 1);
+        assert_eq!(node_data[0].get_data_size(), 60);
+
+        // without a data size set by the extractor, the size of each merged cmd is added
+        let mut node_data = vec![build_cmd_item(&["DEL", "{user}:1"])];
+        let dt_item = build_cmd_item(&["DEL", "{user}:2"]);
+        let expected_size = node_data[0].get_data_size() + dt_item.get_data_size();
+        assert!(RedisParallelizer::try_merge_del(
+            &mut node_data,
+            &dt_item,
+            slot
+        ));
+        assert_eq!(node_data[0].get_data_size(), expected_size);
+    }
+
+    #[tokio::test]
+    async fn test_sink_raw_with_failed_node() {
+        let sinked = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut sinkers = Vec::new();
+        for (id, fail_times) in [("node_1", 0), ("node_2", usize::MAX)] {
+            let sinker: Box<dyn Sinker + Send> = Box::new(MockSinker {
+                id: id.into(),
+                fail_times,
+                sinked: sinked.clone(),
+                sinked_db_ids: Default::default(),
             });
-            futures.push(future);
+            sinkers.push(Arc::new(async_mutex::Mutex::new(sinker)));
+        }
+
+        // slots [0, 8192) belong to node_1, the others to node_2
+        let slot_node_map = (0..16384)
+            .map(|slot| (slot, if slot < 8192 { "node_1" } else { "node_2" }))
+            .collect();
+        let mut parallelizer = RedisParallelizer {
+            base_parallelizer: BaseParallelizer::default(),
+            parallel_size: 2,
+            slot_node_map,
+            key_parser: KeyParser::new(),
+            node_sinker_index_map: HashMap::new(),
+        };
+
+        let (mut node_1_keys, mut node_2_keys) = (Vec::new(), Vec::new());
+        for i in 0..10 {
+            let key = format!("key_{}", i);
+            if KeyParser::calc_slot(key.as_bytes()) < 8192 {
+                node_1_keys.push(key);
+            } else {
+                node_2_keys.push(key);
+            }
         }
+        assert!(!node_1_keys.is_empty() && !node_2_keys.is_empty());
+        let data = node_1_keys
+            .iter()
+            .chain(node_2_keys.iter())
+            .map(|key| build_item(key))
+            .collect();
 
-        for future in futures {
-            future.await.unwrap();
+        let Err(err) = parallelizer.sink_raw(data, &sinkers).await else {
+            panic!("sink_raw should fail");
+        };
+        match err.downcast_ref::<Error>() {
+            Some(Error::RedisClusterSinkError { nodes, message }) => {
+                assert_eq!(nodes, &vec!["node_2".to_string()]);
+                assert!(message.contains(&format!("cmds: {}", node_2_keys.len())));
+                assert!(message.contains(&format!("SET {}", node_2_keys[0])));
+                assert!(message.contains("connection refused"));
+            }
+            _ => panic!("unexpected error: {}", err),
         }
 
-        Ok(data_size)
+        // the batch of the healthy node is sinked once, it is not retried with the failed node
+        let expected: Vec<String> = node_1_keys.iter().map(|k| format!("SET {} v", k)).collect();
+        assert_eq!(*sinked.lock().unwrap(), expected);
+    }
+
+    #[tokio::test]
+    async fn test_sink_raw_retries_failed_node() {
+        let sinked = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut sinkers = Vec::new();
+        for (id, fail_times) in [("node_1", 0), ("node_2", MAX_NODE_RETRIES)] {
+            let sinker: Box<dyn Sinker + Send> = Box::new(MockSinker {
+                id: id.into(),
+                fail_times,
+                sinked: sinked.clone(),
+                sinked_db_ids: Default::default(),
+            });
+            sinkers.push(Arc::new(async_mutex::Mutex::new(sinker)));
+        }
+
+        let mut parallelizer = build_parallelizer(
+            (0..16384)
+                .map(|slot| (slot, if slot < 8192 { "node_1" } else { "node_2" }))
+                .collect(),
+        );
+        let keys: Vec<String> = (0..10).map(|i| format!("key_{}", i)).collect();
+        let data = keys.iter().map(|key| build_item(key)).collect();
+        parallelizer.sink_raw(data, &sinkers).await.unwrap();
+
+        // node_2 succeeds in the last retry, every cmd is sinked exactly once
+        let mut sinked = sinked.lock().unwrap().clone();
+        sinked.sort();
+        let mut expected: Vec<String> = keys.iter().map(|k| format!("SET {} v", k)).collect();
+        expected.sort();
+        assert_eq!(sinked, expected);
+    }
+
+    fn build_parallelizer(slot_node_map: HashMap<u16, &'static str>) -> RedisParallelizer {
+        RedisParallelizer {
+            base_parallelizer: BaseParallelizer::default(),
+            parallel_size: 1,
+            slot_node_map,
+            key_parser: KeyParser::new(),
+            node_sinker_index_map: HashMap::new(),
+        }
+    }
+
+    fn build_db_item(db_id: i64, args: &[&str]) -> DtItem {
+        let mut dt_item = build_cmd_item(args);
+        if let DtData::Redis { entry } = &mut dt_item.dt_data {
+            entry.db_id = db_id;
+        }
+        dt_item
+    }
+
+    #[tokio::test]
+    async fn test_sink_raw_keeps_db_ids() {
+        // SELECT 1 then SET k1, the extractor gives the SET db_id 1
+        let data = || {
+            vec![
+                build_db_item(0, &["SET", "k0", "v"]),
+                build_db_item(1, &["SET", "k1", "v"]),
+                build_db_item(1, &["DEL", "k2"]),
+            ]
+        };
+        for slot_node_map in [
+            HashMap::new(),
+            (0..16384).map(|slot| (slot, "node_1")).collect(),
+        ] {
+            let sinked = Arc::new(std::sync::Mutex::new(Vec::new()));
+            let sinked_db_ids = Arc::new(std::sync::Mutex::new(Vec::new()));
+            let sinker: Box<dyn Sinker + Send> = Box::new(MockSinker {
+                id: "node_1".into(),
+                fail_times: 0,
+                sinked: sinked.clone(),
+                sinked_db_ids: sinked_db_ids.clone(),
+            });
+            let sinkers = vec![Arc::new(async_mutex::Mutex::new(sinker))];
+
+            // in cluster, the db of an entry is mapped to 0 by [router] db_map in the sinker
+            let mut parallelizer = build_parallelizer(slot_node_map);
+            parallelizer.sink_raw(data(), &sinkers).await.unwrap();
+            assert_eq!(
+                *sinked.lock().unwrap(),
+                vec!["SET k0 v", "SET k1 v", "DEL k2"]
+            );
+            assert_eq!(*sinked_db_ids.lock().unwrap(), vec![0, 1, 1]);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sink_raw_rejects_db_cmds_in_cluster() {
+        let sinked = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sinker: Box<dyn Sinker + Send> = Box::new(MockSinker {
+            id: "node_1".into(),
+            fail_times: 0,
+            sinked: sinked.clone(),
+            sinked_db_ids: Default::default(),
+        });
+        let sinkers = vec![Arc::new(async_mutex::Mutex::new(sinker))];
+
+        let mut parallelizer =
+            build_parallelizer((0..16384).map(|slot| (slot, "node_1")).collect());
+        for args in [
+            vec!["SWAPDB", "0", "1"],
+            vec!["MOVE", "k1", "1"],
+            vec!["COPY", "k1", "k2", "DB", "1"],
+        ] {
+            let data = vec![build_item("k0"), build_cmd_item(&args)];
+            let err = parallelizer.sink_raw(data, &sinkers).await.unwrap_err();
+            match err.downcast_ref::<Error>() {
+                Some(Error::RedisCmdError(message)) => {
+                    assert!(message.contains(&args.join(" ")), "{}", message)
+                }
+                _ => panic!("unexpected error: {}", err),
+            }
+        }
+        // nothing is sinked once a batch is rejected
+        assert!(sinked.lock().unwrap().is_empty());
+
+        // COPY in the same db is routed as usual
+        let data = vec![build_cmd_item(&["COPY", "{k}1", "{k}2", "REPLACE"])];
+        parallelizer.sink_raw(data, &sinkers).await.unwrap();
+        assert_eq!(*sinked.lock().unwrap(), vec!["COPY {k}1 {k}2 REPLACE"]);
+    }
+    #[tokio::test]
+    async fn test_drain_with_max_drain_bytes() {
+        let buffer = DtQueue::new(100, 0, None, None);
+        for (i, data_size) in [40, 40, 40, 500, 40].into_iter().enumerate() {
+            let mut item = build_item(&format!("key_{}", i));
+            if let DtData::Redis { entry } = &mut item.dt_data {
+                entry.data_size = data_size;
+            }
+            buffer.push(item).await.unwrap();
+        }
+
+        let mut parallelizer = build_parallelizer(HashMap::new());
+        parallelizer.base_parallelizer.max_drain_bytes = 100;
+        let mut batch_sizes = Vec::new();
+        while !buffer.is_empty() {
+            let data = parallelizer.drain(&buffer).await.unwrap();
+            let sizes: Vec<u64> = data.iter().map(|item| item.get_data_size()).collect();
+            // only the last item may push the batch over the cap
+            assert!(sizes[..sizes.len() - 1].iter().sum::<u64>() < 100);
+            batch_sizes.push(sizes);
+        }
+        assert_eq!(batch_sizes, vec![vec![40, 40, 40], vec![500], vec![40]]);
     }
 }
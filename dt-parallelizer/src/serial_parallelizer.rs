@@ -7,11 +7,12 @@ use dt_common::meta::{
 };
 use dt_connector::Sinker;
 
-use super::base_parallelizer::BaseParallelizer;
+use super::{base_parallelizer::BaseParallelizer, dml_orderer::DmlOrderer};
 use crate::{DataSize, Parallelizer};
 
 pub struct SerialParallelizer {
     pub base_parallelizer: BaseParallelizer,
+    pub dml_orderer: Option<DmlOrderer>,
 }
 
 #[async_trait]
@@ -20,6 +21,13 @@ impl Parallelizer for SerialParallelizer {
         "SerialParallelizer".to_string()
     }
 
+    async fn close(&mut self) -> anyhow::Result<()> {
+        if let Some(dml_orderer) = &self.dml_orderer {
+            dml_orderer.close().await?;
+        }
+        Ok(())
+    }
+
     async fn drain(&mut self, buffer: &DtQueue) -> anyhow::Result<Vec<DtItem>> {
         self.base_parallelizer.drain(buffer).await
     }
@@ -34,6 +42,11 @@ impl Parallelizer for SerialParallelizer {
             bytes: data.iter().map(|v| v.get_data_size()).sum(),
         };
 
+        let data = if let Some(dml_orderer) = &mut self.dml_orderer {
+            dml_orderer.order(data).await?
+        } else {
+            data
+        };
         let _ = self
             .base_parallelizer
             .sink_dml(vec![data], sinkers, 1, false)
@@ -10,11 +10,12 @@ use dt_common::meta::{
 };
 use dt_connector::Sinker;
 
-use super::base_parallelizer::BaseParallelizer;
+use super::{base_parallelizer::BaseParallelizer, dml_orderer::DmlOrderer};
 
 pub struct TableParallelizer {
     pub base_parallelizer: BaseParallelizer,
     pub parallel_size: usize,
+    pub dml_orderer: Option<DmlOrderer>,
 }
 
 #[async_trait]
@@ -23,6 +24,13 @@ impl Parallelizer for TableParallelizer {
         "TableParallelizer".to_string()
     }
 
+    async fn close(&mut self) -> anyhow::Result<()> {
+        if let Some(dml_orderer) = &self.dml_orderer {
+            dml_orderer.close().await?;
+        }
+        Ok(())
+    }
+
     async fn drain(&mut self, buffer: &DtQueue) -> anyhow::Result<Vec<DtItem>> {
         self.base_parallelizer.drain(buffer).await
     }
@@ -37,6 +45,11 @@ impl Parallelizer for TableParallelizer {
             bytes: data.iter().map(|v| v.get_data_size()).sum(),
         };
 
+        let data = if let Some(dml_orderer) = &mut self.dml_orderer {
+            dml_orderer.order(data).await?
+        } else {
+            data
+        };
         let sub_data = Self::partition_dml(data)?;
         self.base_parallelizer
             .sink_dml(sub_data, sinkers, self.parallel_size, false)
@@ -1,5 +1,5 @@
 use async_trait::async_trait;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::{
     atomic::{AtomicBool, Ordering},
     Arc,
@@ -10,7 +10,7 @@ use tokio::{
     time::Instant,
 };
 
-use crate::{lua_processor::LuaProcessor, Pipeline};
+use crate::{col_transformer::ColTransformer, lua_processor::LuaProcessor, Pipeline};
 use dt_common::{
     config::sinker_config::SinkerConfig,
     log_error, log_finished, log_info, log_position, log_warn,
@@ -47,7 +47,10 @@ pub struct BasePipeline {
     pub syncer: Arc<Mutex<Syncer>>,
     pub monitor: TaskMonitorHandle,
     pub pending_snapshot_finished: HashMap<String, Position>,
+    // the last sunk snapshot position of each table being extracted, keyed by task id
+    pub snapshot_positions: HashMap<String, Position>,
     pub data_marker: Option<Arc<RwLock<DataMarker>>>,
+    pub col_transformer: Option<ColTransformer>,
     pub lua_processor: Option<LuaProcessor>,
     pub recorder: Option<Arc<dyn Recorder + Send + Sync>>,
     pub checker: Option<CheckerHandle>,
@@ -89,6 +92,7 @@ impl Pipeline for BasePipeline {
 
         let mut last_sink_time = Instant::now();
         let mut last_checkpoint_time = Instant::now();
+        let mut last_heartbeat_time = Instant::now();
         let mut last_received_position = Position::None;
         let mut last_commit_positions = HashMap::new();
         let mut record_time = Instant::now();
@@ -165,6 +169,12 @@ impl Pipeline for BasePipeline {
                 )
                 .await?;
 
+            // sinkers decide their own heartbeat intervals, they are only polled every second
+            if last_heartbeat_time.elapsed().as_secs() >= 1 {
+                self.sink_heartbeat(&last_received_position).await;
+                last_heartbeat_time = Instant::now();
+            }
+
             self.monitor
                 .add_counter(
                     self.monitor.default_task_id(),
@@ -192,6 +202,15 @@ impl Pipeline for BasePipeline {
 }
 
 impl BasePipeline {
+    // only the first sinker sends heartbeats, failures do not stop the task
+    async fn sink_heartbeat(&self, position: &Position) {
+        if let Some(sinker) = self.sinkers.first() {
+            if let Err(err) = sinker.lock().await.sink_heartbeat(position).await {
+                log_warn!("sink heartbeat failed: {}", err);
+            }
+        }
+    }
+
     fn checker_close_position(syncer: &Syncer) -> Option<Position> {
         (!matches!(syncer.committed_position, Position::None))
             .then_some(syncer.committed_position.clone())
@@ -245,6 +264,12 @@ impl BasePipeline {
         &mut self,
         all_data: Vec<DtItem>,
     ) -> anyhow::Result<(DataSize, Option<Position>, Vec<Position>)> {
+        let mut snapshot_positions = HashMap::new();
+        Self::collect_snapshot_positions(&all_data, &mut snapshot_positions);
+        let batch_positions = all_data
+            .first()
+            .zip(all_data.last())
+            .map(|(first, last)| (first.position.clone(), last.position.clone()));
         let (mut data, last_received_position, last_commit_position) =
             Self::fetch_dml(all_data, &mut self.pending_snapshot_finished);
         let commit_positions = last_commit_position.into_iter().collect();
@@ -256,12 +281,24 @@ impl BasePipeline {
             ));
         }
 
+        // col transforms are keyed by routed names, run them before lua may change the rows
+        if let Some(col_transformer) = &self.col_transformer {
+            col_transformer.process(&mut data)?;
+        }
+
         // execute lua processor
         if let Some(lua_processor) = &self.lua_processor {
             data = lua_processor.process(data)?;
         }
 
+        if let Some((first, last)) = &batch_positions {
+            for sinker in self.sinkers.iter() {
+                sinker.lock().await.begin_dml_batch(first, last).await?;
+            }
+        }
+
         let data_size = self.parallelizer.sink_dml(data, &self.sinkers).await?;
+        self.snapshot_positions.extend(snapshot_positions);
         Ok((data_size, last_received_position, commit_positions))
     }
 
@@ -518,6 +555,7 @@ impl BasePipeline {
             let Some(finish_position) = self.pending_snapshot_finished.remove(&task_id) else {
                 continue;
             };
+            self.snapshot_positions.remove(&task_id);
 
             self.handle_snapshot_finished_control_item(&finish_position)
                 .await?;
@@ -567,6 +605,34 @@ impl BasePipeline {
         Ok(())
     }
 
+    // rows of a table are extracted in order key order, so the last position of each table
+    // in the batch carries the max order key value sunk
+    fn collect_snapshot_positions(
+        data: &[DtItem],
+        snapshot_positions: &mut HashMap<String, Position>,
+    ) {
+        let mut collected_tbs = HashSet::new();
+        for item in data.iter().rev() {
+            if let (
+                DtData::Dml { .. },
+                Position::RdbSnapshot {
+                    schema,
+                    tb,
+                    order_key: Some(_),
+                    ..
+                },
+            ) = (&item.dt_data, &item.position)
+            {
+                if collected_tbs.insert((schema, tb)) {
+                    snapshot_positions.insert(
+                        TaskMonitorHandle::task_id_from_schema_tb(schema, tb),
+                        item.position.clone(),
+                    );
+                }
+            }
+        }
+    }
+
     fn collect_snapshot_finished(
         position: &Position,
         pending_snapshot_finished: &mut HashMap<String, Position>,
@@ -594,9 +660,23 @@ impl BasePipeline {
             }
         }
 
-        if !matches!(last_received_position, Position::None) {
-            // extracting chunks will sink None position.
-            log_position!("current_position | {}", last_received_position.to_string());
+        // tables may be extracted in parallel, each of them resumes from its own position
+        let mut current_positions: Vec<(&String, &Position)> =
+            self.snapshot_positions.iter().collect();
+        current_positions.sort_by(|left, right| left.0.cmp(right.0));
+        let current_positions: Vec<&Position> = if current_positions.is_empty() {
+            vec![last_received_position]
+        } else {
+            current_positions
+                .into_iter()
+                .map(|(_, position)| position)
+                .collect()
+        };
+        for position in current_positions.iter() {
+            if !matches!(position, Position::None) {
+                // extracting chunks will sink None position.
+                log_position!("current_position | {}", position.to_string());
+            }
         }
         let mut commit_positions: Vec<(&String, &Position)> =
             last_commit_positions.iter().collect();
@@ -619,12 +699,10 @@ impl BasePipeline {
         }
         if let Some(handler) = &self.recorder {
             if commit_positions.is_empty() {
-                if let Err(e) = handler.record_position(last_received_position).await {
-                    log_error!(
-                        "failed to record position: {}, err: {:#}",
-                        last_received_position,
-                        e
-                    );
+                for position in current_positions.iter() {
+                    if let Err(e) = handler.record_position(position).await {
+                        log_error!("failed to record position: {}, err: {:#}", position, e);
+                    }
                 }
             } else {
                 for (_, position) in commit_positions.iter() {
@@ -648,6 +726,10 @@ impl BasePipeline {
             CounterType::Timestamp,
             last_received_position.to_timestamp(),
         );
+        if let Some(current_position) = current_positions.last() {
+            self.monitor
+                .set_positions(current_position, checker_position);
+        }
 
         Ok(Instant::now())
     }
@@ -655,14 +737,47 @@ impl BasePipeline {
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashMap;
+    use std::{
+        collections::HashMap,
+        fs,
+        sync::{atomic::AtomicBool, Arc, Mutex as StdMutex},
+        time::SystemTime,
+    };
 
-    use dt_common::meta::{
-        dt_data::{DtData, DtItem},
-        position::Position,
-        redis::redis_entry::RedisEntry,
+    use async_trait::async_trait;
+    use tokio::sync::Mutex;
+
+    use dt_common::{
+        config::{
+            config_enums::{TaskKind, TaskType},
+            resumer_config::ResumerConfig,
+            sinker_config::SinkerConfig,
+        },
+        meta::{
+            col_value::ColValue,
+            dt_data::{DtData, DtItem},
+            dt_queue::DtQueue,
+            order_key::OrderKey,
+            position::Position,
+            redis::redis_entry::RedisEntry,
+            row_data::RowData,
+            row_type::RowType,
+            syncer::Syncer,
+        },
+        monitor::task_monitor_handle::TaskMonitorHandle,
+    };
+    use dt_connector::{
+        extractor::resumer::{
+            recorder::Recorder,
+            recovery::{from_log::LogRecovery, Recovery},
+            utils::ResumerUtil,
+        },
+        sinker::dummy_sinker::DummySinker,
+        Sinker,
+    };
+    use dt_parallelizer::{
+        base_parallelizer::BaseParallelizer, serial_parallelizer::SerialParallelizer,
     };
-    use dt_connector::extractor::resumer::utils::ResumerUtil;
 
     use super::BasePipeline;
 
@@ -711,4 +826,134 @@ mod tests {
         assert_eq!(by_key.get("redis-node-node-1"), Some(&node_1_new));
         assert_eq!(by_key.get("redis-node-node-2"), Some(&node_2));
     }
+
+    fn snapshot_item(tb: &str, id: i32) -> DtItem {
+        let after = HashMap::from([("id".to_string(), ColValue::Long(id))]);
+        DtItem {
+            dt_data: DtData::Dml {
+                row_data: RowData::new(
+                    "db_1".into(),
+                    tb.into(),
+                    0,
+                    RowType::Insert,
+                    None,
+                    Some(after),
+                ),
+            },
+            position: Position::RdbSnapshot {
+                db_type: "mysql".into(),
+                schema: "db_1".into(),
+                tb: tb.into(),
+                order_key: Some(OrderKey::Single(("id".into(), Some(id.to_string())))),
+            },
+            data_origin_node: String::new(),
+        }
+    }
+
+    struct PositionRecorder {
+        positions: Arc<StdMutex<Vec<Position>>>,
+    }
+
+    #[async_trait]
+    impl Recorder for PositionRecorder {
+        async fn record_position(&self, position: &Position) -> anyhow::Result<()> {
+            self.positions.lock().unwrap().push(position.clone());
+            Ok(())
+        }
+    }
+
+    fn build_pipeline(recorder: PositionRecorder) -> BasePipeline {
+        BasePipeline {
+            buffer: Arc::new(DtQueue::new(100, 0, None, None)),
+            parallelizer: Box::new(SerialParallelizer {
+                base_parallelizer: BaseParallelizer::default(),
+                dml_orderer: None,
+            }),
+            sinker_config: SinkerConfig::Dummy,
+            sinkers: vec![Arc::new(async_mutex::Mutex::new(
+                Box::new(DummySinker {}) as Box<dyn Sinker + Send>
+            ))],
+            shut_down: Arc::new(AtomicBool::new(false)),
+            checkpoint_interval_secs: 1,
+            batch_sink_interval_secs: 0,
+            syncer: Arc::new(Mutex::new(Syncer::default())),
+            monitor: TaskMonitorHandle::default(),
+            pending_snapshot_finished: HashMap::new(),
+            snapshot_positions: HashMap::new(),
+            data_marker: None,
+            col_transformer: None,
+            lua_processor: None,
+            recorder: Some(Arc::new(recorder)),
+            checker: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn snapshot_positions_are_recovered_per_table() {
+        let positions = Arc::new(StdMutex::new(Vec::new()));
+        let mut pipeline = build_pipeline(PositionRecorder {
+            positions: positions.clone(),
+        });
+
+        // tb_1 and tb_2 are extracted in parallel, their rows interleave in the batches
+        let batch_1 = vec![
+            snapshot_item("tb_1", 1),
+            snapshot_item("tb_2", 1),
+            snapshot_item("tb_1", 2),
+        ];
+        let batch_2 = vec![snapshot_item("tb_2", 2), snapshot_item("tb_2", 3)];
+        for batch in [batch_1, batch_2] {
+            let (_, last_received, _) = pipeline.sink_dml(batch).await.unwrap();
+            pipeline
+                .record_checkpoint(None, &last_received.unwrap(), &HashMap::new())
+                .await
+                .unwrap();
+        }
+
+        // simulate a restart, the recorded positions are read back by the resumer
+        let log_dir = std::env::temp_dir().join(format!(
+            "ape-dts-base-pipeline-{}",
+            SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        fs::create_dir_all(&log_dir).unwrap();
+        let position_log: String = positions
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|position| {
+                format!(
+                    "2024-03-29 07:02:24.463776 | current_position | {}\n",
+                    position
+                )
+            })
+            .collect();
+        fs::write(log_dir.join("position.log"), position_log).unwrap();
+        let recovery = LogRecovery::new(
+            TaskType::new(TaskKind::Snapshot, None),
+            &ResumerConfig::FromLog {
+                log_dir: log_dir.to_string_lossy().to_string(),
+                config_file: String::new(),
+            },
+        )
+        .await
+        .unwrap();
+
+        // each table resumes after its own last sunk id
+        assert_eq!(
+            recovery
+                .get_snapshot_resume_position("db_1", "tb_1", false)
+                .await,
+            Some(snapshot_item("tb_1", 2).position)
+        );
+        assert_eq!(
+            recovery
+                .get_snapshot_resume_position("db_1", "tb_2", false)
+                .await,
+            Some(snapshot_item("tb_2", 3).position)
+        );
+        fs::remove_dir_all(log_dir).unwrap();
+    }
 }
@@ -0,0 +1,358 @@
+use std::collections::HashMap;
+
+use anyhow::bail;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use dt_common::{
+    error::Error,
+    meta::{col_value::ColValue, rdb_meta_manager::RdbMetaManager, row_data::RowData},
+};
+use dt_connector::rdb_router::RdbRouter;
+
+const JSON_PREFIX: &str = "json:";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "transform", rename_all = "snake_case")]
+pub enum ColTransform {
+    // hex of the sha256 digest, only for string cols
+    Sha256,
+    // keeps the first and the last quarter of the chars, others are replaced by *
+    MaskMiddle,
+    // the value is parsed to the type of the col
+    Constant { value: String },
+}
+
+impl ColTransform {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Sha256 => "sha256",
+            Self::MaskMiddle => "mask_middle",
+            Self::Constant { .. } => "constant",
+        }
+    }
+}
+
+/// Transforms col values of the configured (schema, tb, col) before rows are sunk,
+/// e.g. to mask PII. A transform fails if the col value type is not supported by it,
+/// NULLs and unchanged toasts are kept as they are.
+#[derive(Debug, Clone, Default)]
+pub struct ColTransformer {
+    // HashMap<(schema, tb), HashMap<col, transform>>, names of the source from config,
+    // names of the target once routed
+    pub transforms: HashMap<(String, String), HashMap<String, ColTransform>>,
+}
+
+impl ColTransformer {
+    pub fn from_config(config_str: &str) -> anyhow::Result<Self> {
+        let mut transforms: HashMap<(String, String), HashMap<String, ColTransform>> =
+            HashMap::new();
+        if config_str.trim().is_empty() {
+            return Ok(Self { transforms });
+        }
+        // col_transforms=json:[{"db":"test_db","tb":"tb_1","col":"email","transform":"sha256"}]
+        #[derive(Serialize, Deserialize)]
+        struct ColTransformType {
+            db: String,
+            tb: String,
+            col: String,
+            #[serde(flatten)]
+            transform: ColTransform,
+        }
+        let config: Vec<ColTransformType> =
+            serde_json::from_str(config_str.trim_start_matches(JSON_PREFIX)).map_err(|err| {
+                Error::ConfigError(format!("config [processor].col_transforms error: {}", err))
+            })?;
+        for i in config {
+            transforms
+                .entry((i.db, i.tb))
+                .or_default()
+                .insert(i.col, i.transform);
+        }
+        Ok(Self { transforms })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.transforms.is_empty()
+    }
+
+    /// Fails if a transform matches no table or col of the source.
+    pub async fn validate(&self, meta_manager: &mut RdbMetaManager) -> anyhow::Result<()> {
+        for ((schema, tb), tb_transforms) in self.transforms.iter() {
+            let tb_meta = match meta_manager.get_tb_meta(schema, tb).await {
+                Ok(tb_meta) => tb_meta,
+                Err(err) => bail! {Error::ConfigError(format!(
+                    "config [processor].col_transforms error, table not found, schema: {}, tb: {}, error: {}",
+                    schema, tb, err
+                ))},
+            };
+            if let Some(col) = tb_transforms.keys().find(|col| !tb_meta.cols.contains(col)) {
+                bail! {Error::ConfigError(format!(
+                    "config [processor].col_transforms error, col not found, schema: {}, tb: {}, col: {}",
+                    schema, tb, col
+                ))}
+            }
+        }
+        Ok(())
+    }
+
+    /// Rows are routed by the extractor before reaching the pipeline,
+    /// so the transforms are keyed by the routed schema, tb and cols.
+    pub fn route(&mut self, router: &RdbRouter) {
+        let transforms = std::mem::take(&mut self.transforms);
+        for ((schema, tb), tb_transforms) in transforms {
+            let (dst_schema, dst_tb) = router.get_tb_map(&schema, &tb);
+            let key = (dst_schema.to_string(), dst_tb.to_string());
+            let col_map = router.get_col_map(&schema, &tb);
+            let dst_transforms = self.transforms.entry(key).or_default();
+            for (col, transform) in tb_transforms {
+                let dst_col = col_map
+                    .and_then(|col_map| col_map.get(&col))
+                    .cloned()
+                    .unwrap_or(col);
+                dst_transforms.insert(dst_col, transform);
+            }
+        }
+    }
+
+    pub fn process(&self, data: &mut [RowData]) -> anyhow::Result<()> {
+        for row_data in data.iter_mut() {
+            self.process_row(row_data)?;
+        }
+        Ok(())
+    }
+
+    fn process_row(&self, row_data: &mut RowData) -> anyhow::Result<()> {
+        let Some(tb_transforms) = self
+            .transforms
+            .get(&(row_data.schema.clone(), row_data.tb.clone()))
+        else {
+            return Ok(());
+        };
+
+        for col_values in [&mut row_data.before, &mut row_data.after]
+            .into_iter()
+            .flatten()
+        {
+            for (col, transform) in tb_transforms.iter() {
+                let Some(col_value) = col_values.get_mut(col) else {
+                    continue;
+                };
+                match Self::transform(transform, col_value) {
+                    Some(transformed) => *col_value = transformed,
+                    None => {
+                        bail! {Error::PipelineError(format!(
+                            "col transform {} can not be applied to {} value, schema: {}, tb: {}, col: {}",
+                            transform.name(),
+                            col_value.type_name(),
+                            row_data.schema,
+                            row_data.tb,
+                            col
+                        ))}
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // None if the transform does not support the col value type
+    fn transform(transform: &ColTransform, col_value: &ColValue) -> Option<ColValue> {
+        if matches!(col_value, ColValue::None | ColValue::UnchangedToast) {
+            return Some(col_value.clone());
+        }
+
+        let transformed = match (transform, col_value) {
+            (ColTransform::Sha256, ColValue::String(v)) => Self::sha256(v.as_bytes()),
+            (ColTransform::Sha256, ColValue::RawString(v)) => Self::sha256(v),
+
+            (ColTransform::MaskMiddle, ColValue::String(v)) => Self::mask_middle(v),
+            (ColTransform::MaskMiddle, ColValue::RawString(v)) => {
+                Self::mask_middle(&String::from_utf8_lossy(v))
+            }
+
+            (ColTransform::Constant { value }, _) => {
+                return Self::parse_constant(value, col_value);
+            }
+
+            _ => return None,
+        };
+        Some(ColValue::String(transformed))
+    }
+
+    fn sha256(v: &[u8]) -> String {
+        hex::encode(Sha256::digest(v))
+    }
+
+    fn mask_middle(v: &str) -> String {
+        let chars: Vec<char> = v.chars().collect();
+        let keep = chars.len() / 4;
+        chars
+            .iter()
+            .enumerate()
+            .map(|(i, c)| {
+                if i < keep || i >= chars.len() - keep {
+                    *c
+                } else {
+                    '*'
+                }
+            })
+            .collect()
+    }
+
+    // the constant keeps the type of the col, None if it can not be parsed to the type
+    fn parse_constant(value: &str, col_value: &ColValue) -> Option<ColValue> {
+        let col_value = match col_value {
+            ColValue::String(_) | ColValue::RawString(_) => ColValue::String(value.to_string()),
+            ColValue::Tiny(_) => ColValue::Tiny(value.parse().ok()?),
+            ColValue::UnsignedTiny(_) => ColValue::UnsignedTiny(value.parse().ok()?),
+            ColValue::Short(_) => ColValue::Short(value.parse().ok()?),
+            ColValue::UnsignedShort(_) => ColValue::UnsignedShort(value.parse().ok()?),
+            ColValue::Long(_) => ColValue::Long(value.parse().ok()?),
+            ColValue::UnsignedLong(_) => ColValue::UnsignedLong(value.parse().ok()?),
+            ColValue::LongLong(_) => ColValue::LongLong(value.parse().ok()?),
+            ColValue::UnsignedLongLong(_) => ColValue::UnsignedLongLong(value.parse().ok()?),
+            ColValue::Float(_) => ColValue::Float(value.parse().ok()?),
+            ColValue::Double(_) => ColValue::Double(value.parse().ok()?),
+            ColValue::Decimal(_) => {
+                value.parse::<f64>().ok()?;
+                ColValue::Decimal(value.to_string())
+            }
+            _ => return None,
+        };
+        Some(col_value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use dt_common::{
+        config::{config_enums::DbType, router_config::RouterConfig},
+        meta::row_type::RowType,
+    };
+
+    use super::*;
+
+    fn build_row() -> RowData {
+        let after = HashMap::from([
+            ("id".to_string(), ColValue::Long(1)),
+            (
+                "email".to_string(),
+                ColValue::String("alice@example.com".into()),
+            ),
+            ("phone".to_string(), ColValue::String("13812345678".into())),
+            ("salary".to_string(), ColValue::Double(1000.5)),
+            ("note".to_string(), ColValue::None),
+        ]);
+        RowData::new(
+            "db_1".into(),
+            "tb_1".into(),
+            0,
+            RowType::Insert,
+            None,
+            Some(after),
+        )
+    }
+
+    fn transform_row(config: &str) -> anyhow::Result<HashMap<String, ColValue>> {
+        let transformer = ColTransformer::from_config(config).unwrap();
+        let mut data = vec![build_row()];
+        transformer.process(&mut data)?;
+        Ok(data.remove(0).after.unwrap())
+    }
+
+    #[test]
+    fn test_sha256() {
+        let after = transform_row(
+            r#"json:[{"db":"db_1","tb":"tb_1","col":"email","transform":"sha256"},
+            {"db":"db_1","tb":"tb_1","col":"note","transform":"sha256"}]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            after["email"],
+            ColValue::String(
+                "ff8d9819fc0e12bf0d24892e45987e249a28dce836a85cad60e28eaaa8c6d976".into()
+            )
+        );
+        // NULL is kept
+        assert_eq!(after["note"], ColValue::None);
+        assert_eq!(after["id"], ColValue::Long(1));
+    }
+
+    #[test]
+    fn test_mask_middle_and_constant() {
+        let after = transform_row(
+            r#"json:[{"db":"db_1","tb":"tb_1","col":"phone","transform":"mask_middle"},
+            {"db":"db_1","tb":"tb_1","col":"salary","transform":"constant","value":"0"}]"#,
+        )
+        .unwrap();
+        assert_eq!(after["phone"], ColValue::String("13*******78".into()));
+        assert_eq!(after["salary"], ColValue::Double(0.0));
+
+        // tables not configured are not transformed
+        let after = transform_row(
+            r#"json:[{"db":"db_1","tb":"tb_2","col":"phone","transform":"mask_middle"}]"#,
+        )
+        .unwrap();
+        assert_eq!(after, build_row().after.unwrap());
+    }
+
+    #[test]
+    fn test_routed_rows() {
+        let config = RouterConfig::Rdb {
+            schema_map: String::new(),
+            tb_map: "db_1.tb_1:dst_db.dst_tb".into(),
+            col_map: r#"json:[{"db":"db_1","tb":"tb_1","col_map":{"id":"id","phone":"mobile"}}]"#
+                .into(),
+            topic_map: String::new(),
+            doc_projection: String::new(),
+        };
+        let router = RdbRouter::from_config(&config, &DbType::Mysql)
+            .unwrap()
+            .unwrap();
+
+        let mut transformer = ColTransformer::from_config(
+            r#"json:[{"db":"db_1","tb":"tb_1","col":"phone","transform":"mask_middle"},
+            {"db":"db_1","tb":"tb_1","col":"email","transform":"sha256"}]"#,
+        )
+        .unwrap();
+        transformer.route(&router);
+        assert_eq!(
+            transformer.transforms[&("dst_db".to_string(), "dst_tb".to_string())]
+                .keys()
+                .collect::<HashSet<_>>(),
+            HashSet::from([&"mobile".to_string(), &"email".to_string()])
+        );
+
+        // rows reach the pipeline routed
+        let mut data = vec![router.route_row(build_row())];
+        transformer.process(&mut data).unwrap();
+        let after = data.remove(0).after.unwrap();
+        assert_eq!(after["mobile"], ColValue::String("13*******78".into()));
+    }
+
+    #[test]
+    fn test_type_mismatch() {
+        let err =
+            transform_row(r#"json:[{"db":"db_1","tb":"tb_1","col":"id","transform":"sha256"}]"#)
+                .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("sha256 can not be applied to Long value, schema: db_1, tb: tb_1, col: id"));
+
+        let err = transform_row(
+            r#"json:[{"db":"db_1","tb":"tb_1","col":"id","transform":"constant","value":"x"}]"#,
+        )
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("constant can not be applied to Long value"));
+
+        assert!(ColTransformer::from_config(
+            r#"json:[{"db":"db_1","tb":"tb_1","col":"id","transform":"md5"}]"#
+        )
+        .is_err());
+    }
+}
@@ -1,4 +1,5 @@
 pub mod base_pipeline;
+pub mod col_transformer;
 pub mod http_server_pipeline;
 pub mod lua_processor;
 
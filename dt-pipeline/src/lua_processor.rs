@@ -1,10 +1,11 @@
 use std::collections::HashMap;
 use std::str::FromStr;
 
+use dt_common::error::Error;
 use dt_common::meta::col_value::ColValue;
 use dt_common::meta::row_data::RowData;
 use dt_common::meta::row_type::RowType;
-use mlua::{IntoLua, Lua};
+use mlua::{IntoLua, Lua, RegistryKey};
 
 type PreservedColValues = HashMap<String, ColValue>;
 
@@ -14,46 +15,83 @@ pub struct LuaProcessor {
 
 impl LuaProcessor {
     pub fn process(&self, data: Vec<RowData>) -> anyhow::Result<Vec<RowData>> {
+        // the script is compiled once for the whole batch
+        let row_processor = LuaRowProcessor::new(&self.lua_code)?;
         let mut new_data = Vec::new();
+        for row_data in data {
+            if let Some(row_data) = row_processor.process(row_data)? {
+                new_data.push(row_data);
+            }
+        }
+        Ok(new_data)
+    }
+}
+
+/// Runs the user lua script on single rows, the script is loaded once when created.
+///
+/// The script gets the globals: schema, tb, row_type, before, after,
+/// it drops the row by setting row_type to "", other changes on the globals are applied to the row.
+pub struct LuaRowProcessor {
+    lua: Lua,
+    chunk: RegistryKey,
+}
+
+impl LuaRowProcessor {
+    pub fn new(lua_code: &str) -> anyhow::Result<Self> {
         let lua = Lua::new();
+        let chunk = lua
+            .load(lua_code)
+            .into_function()
+            .and_then(|chunk| lua.create_registry_value(chunk))
+            .map_err(Error::MluaError)?;
+        Ok(Self { lua, chunk })
+    }
 
-        for row_data in data {
-            // to lua
-            let (lua_before, blob_before) =
-                self.col_values_to_lua_table(row_data.before.clone(), &lua)?;
-            let (lua_after, blob_after) =
-                self.col_values_to_lua_table(row_data.after.clone(), &lua)?;
-
-            lua.globals().set("before", lua_before)?;
-            lua.globals().set("after", lua_after)?;
-            lua.globals().set("schema", row_data.schema)?;
-            lua.globals().set("tb", row_data.tb)?;
-            lua.globals()
-                .set("row_type", row_data.row_type.to_string())?;
-
-            // execute lua
-            lua.load(&self.lua_code).exec()?;
-
-            // row filtered
-            let row_type: String = lua.globals().get("row_type")?;
-            if row_type.is_empty() {
-                continue;
+    /// Returns None if the row is dropped by the script.
+    pub fn process(&self, row_data: RowData) -> anyhow::Result<Option<RowData>> {
+        self.process_row(row_data).map_err(|err| {
+            // mlua errors raised by the script or the conversions
+            match err.downcast::<mlua::Error>() {
+                Ok(err) => Error::MluaError(err).into(),
+                Err(err) => err,
             }
+        })
+    }
+
+    fn process_row(&self, row_data: RowData) -> anyhow::Result<Option<RowData>> {
+        let lua = &self.lua;
 
-            // from lua
-            let lua_before: mlua::Table = lua.globals().get("before")?;
-            let lua_after: mlua::Table = lua.globals().get("after")?;
-            let before = self.lua_table_to_col_values(lua_before, blob_before)?;
-            let after = self.lua_table_to_col_values(lua_after, blob_after)?;
-
-            let schema = lua.globals().get("schema")?;
-            let tb = lua.globals().get("tb")?;
-            let row_type = RowType::from_str(&row_type)?;
-            let new_row_data = RowData::new(schema, tb, 0, row_type, before, after);
-            new_data.push(new_row_data);
+        // to lua
+        let (lua_before, blob_before) = self.col_values_to_lua_table(row_data.before, lua)?;
+        let (lua_after, blob_after) = self.col_values_to_lua_table(row_data.after, lua)?;
+
+        lua.globals().set("before", lua_before)?;
+        lua.globals().set("after", lua_after)?;
+        lua.globals().set("schema", row_data.schema)?;
+        lua.globals().set("tb", row_data.tb)?;
+        lua.globals()
+            .set("row_type", row_data.row_type.to_string())?;
+
+        // execute lua
+        let chunk: mlua::Function = lua.registry_value(&self.chunk)?;
+        chunk.call::<_, ()>(())?;
+
+        // row filtered
+        let row_type: String = lua.globals().get("row_type")?;
+        if row_type.is_empty() {
+            return Ok(None);
         }
 
-        Ok(new_data)
+        // from lua
+        let lua_before: mlua::Table = lua.globals().get("before")?;
+        let lua_after: mlua::Table = lua.globals().get("after")?;
+        let before = self.lua_table_to_col_values(lua_before, blob_before)?;
+        let after = self.lua_table_to_col_values(lua_after, blob_after)?;
+
+        let schema = lua.globals().get("schema")?;
+        let tb = lua.globals().get("tb")?;
+        let row_type = RowType::from_str(&row_type)?;
+        Ok(Some(RowData::new(schema, tb, 0, row_type, before, after)))
     }
 
     fn col_values_to_lua_table<'lua>(
@@ -120,9 +158,10 @@ impl LuaProcessor {
         lua: &'lua mlua::Lua,
     ) -> anyhow::Result<(mlua::Value<'lua>, Option<ColValue>)> {
         match col_value {
-            // Blob columns are intentionally read-only in Lua. Preserve the original value and
-            // expose NULL so a no-op script keeps the source bytes untouched.
+            // Blob and geometry columns are intentionally read-only in Lua. Preserve the original
+            // value and expose NULL so a no-op script keeps the source bytes untouched.
             ColValue::Blob(v) => Ok((mlua::Value::NULL, Some(ColValue::Blob(v)))),
+            ColValue::Geometry(v) => Ok((mlua::Value::NULL, Some(ColValue::Geometry(v)))),
 
             // MySQL CDC text columns may arrive as RawString. If bytes are valid UTF-8, expose
             // them as normal Lua strings. Otherwise preserve the original bytes and expose NULL.
@@ -171,6 +210,7 @@ impl LuaProcessor {
             ColValue::Json3(_)
             | ColValue::RawString(_)
             | ColValue::Blob(_)
+            | ColValue::Geometry(_)
             | ColValue::Json(_)
             | ColValue::MongoDoc(_)
             | ColValue::None
@@ -190,3 +230,78 @@ impl LuaProcessor {
         Ok(col_value)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use dt_common::{
+        error::Error,
+        meta::{col_value::ColValue, row_data::RowData, row_type::RowType},
+    };
+
+    use super::{LuaProcessor, LuaRowProcessor};
+
+    fn insert_row(id: i64, name: &str) -> RowData {
+        let after = HashMap::from([
+            ("id".to_string(), ColValue::LongLong(id)),
+            ("name".to_string(), ColValue::String(name.to_string())),
+        ]);
+        RowData::new(
+            "db_1".into(),
+            "tb_1".into(),
+            0,
+            RowType::Insert,
+            None,
+            Some(after),
+        )
+    }
+
+    #[test]
+    fn drops_rows_with_small_id() {
+        let lua_code = r#"
+            if schema == "db_1" and tb == "tb_1" and after.id < 100 then
+                row_type = ""
+            else
+                after["name"] = after.name .. "_kept"
+            end
+        "#;
+        let processor = LuaProcessor {
+            lua_code: lua_code.into(),
+        };
+
+        let data = vec![
+            insert_row(1, "a"),
+            insert_row(99, "b"),
+            insert_row(100, "c"),
+            insert_row(200, "d"),
+        ];
+        let data = processor.process(data).unwrap();
+
+        assert_eq!(data.len(), 2);
+        for (row_data, (id, name)) in data.iter().zip([(100, "c_kept"), (200, "d_kept")]) {
+            assert_eq!(row_data.row_type, RowType::Insert);
+            let after = row_data.after.as_ref().unwrap();
+            assert_eq!(after["id"], ColValue::LongLong(id));
+            assert_eq!(after["name"], ColValue::String(name.into()));
+        }
+    }
+
+    #[test]
+    fn maps_script_errors_to_mlua_error() {
+        // syntax error when loading
+        let err = LuaRowProcessor::new("if then").err().unwrap();
+        assert!(matches!(
+            err.downcast_ref::<Error>(),
+            Some(Error::MluaError(_))
+        ));
+
+        // runtime error when processing
+        let processor = LuaRowProcessor::new("error('boom')").unwrap();
+        let err = processor.process(insert_row(1, "a")).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<Error>(),
+            Some(Error::MluaError(_))
+        ));
+    }
+}
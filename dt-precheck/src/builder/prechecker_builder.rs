@@ -3,6 +3,7 @@ use std::vec;
 use anyhow::bail;
 use dt_common::{
     config::{config_enums::DbType, task_config::TaskConfig},
+    error::Error,
     rdb_filter::RdbFilter,
 };
 
@@ -12,7 +13,7 @@ use crate::{
         mongo::mongo_fetcher::MongoFetcher, mysql::mysql_fetcher::MysqlFetcher,
         postgresql::pg_fetcher::PgFetcher, redis::redis_fetcher::RedisFetcher,
     },
-    meta::check_result::CheckResult,
+    meta::{check_item::CheckItem, check_result::CheckResult, precheck_report::PrecheckReport},
     prechecker::{
         mongo_prechecker::MongoPrechecker, mysql_prechecker::MySqlPrechecker,
         pg_prechecker::PostgresqlPrechecker, redis_prechecker::RedisPrechecker, traits::Prechecker,
@@ -109,79 +110,95 @@ impl PrecheckerBuilder {
 
     pub async fn check(&self) -> anyhow::Result<Vec<anyhow::Result<CheckResult>>> {
         if !self.valid_config() {
-            bail! {"config is invalid."};
+            bail! {Error::ConfigError("config is invalid.".into())};
         }
         let (source_checker_option, sink_checker_option) =
             (self.build_checker(true), self.build_checker(false));
         if source_checker_option.is_none() || sink_checker_option.is_none() {
-            bail! {
-                "config is invalid when build checker.maybe db_type is wrong."
-            };
+            bail! {Error::ConfigError(
+                "config is invalid when build checker.maybe db_type is wrong.".into()
+            )};
         }
         let (mut source_checker, mut sink_checker) =
             (source_checker_option.unwrap(), sink_checker_option.unwrap());
 
-        println!("[*]begin to check the connection");
-        let check_source_connection = source_checker.build_connection().await?;
-        let check_sink_connection = sink_checker.build_connection().await?;
+        eprintln!("[*]begin to check the connection");
+        let check_source_connection = Self::connection_check_result(
+            source_checker.build_connection().await,
+            true,
+            &self.task_config.extractor_basic.db_type,
+        );
+        let check_sink_connection = Self::connection_check_result(
+            sink_checker.build_connection().await,
+            false,
+            &self.task_config.sinker_basic.db_type,
+        );
 
         // if connection failed, no need to do other check
-        if !check_source_connection.is_validate || !check_sink_connection.is_validate {
-            check_source_connection.log();
-            check_sink_connection.log();
-            bail! {
-                "connection failed, precheck not passed."
-            };
-        }
-
+        let connection_failed =
+            !check_source_connection.is_validate || !check_sink_connection.is_validate;
         let mut check_results: Vec<anyhow::Result<CheckResult>> = vec![];
         check_results.push(Ok(check_source_connection));
         check_results.push(Ok(check_sink_connection));
+        if connection_failed || self.precheck_config.connection_only {
+            return Ok(check_results);
+        }
 
-        println!("[*]begin to check the database version");
+        eprintln!("[*]begin to check the database version");
         check_results.push(source_checker.check_database_version().await);
         check_results.push(sink_checker.check_database_version().await);
 
         if self.precheck_config.do_cdc {
-            println!("[*]begin to check the cdc setting");
+            eprintln!("[*]begin to check the cdc setting");
             check_results.push(source_checker.check_cdc_supported().await);
         }
 
-        println!("[*]begin to check the if the structs is existed or not");
+        eprintln!("[*]begin to check the if the structs is existed or not");
         check_results.push(source_checker.check_struct_existed_or_not().await);
         check_results.push(sink_checker.check_struct_existed_or_not().await);
 
-        println!("[*]begin to check the database structs");
+        eprintln!("[*]begin to check the database structs");
         check_results.push(source_checker.check_table_structs().await);
         check_results.push(sink_checker.check_table_structs().await);
 
         Ok(check_results)
     }
 
-    pub async fn verify_check_result(&self) -> anyhow::Result<()> {
-        let check_results = self.check().await;
-        match check_results {
-            Ok(results) => {
-                println!("check result:");
-                let mut error_count = 0;
-                for check_result in results {
-                    match check_result {
-                        Ok(result) => {
-                            result.log();
-                            if !result.is_validate {
-                                error_count += 1;
-                            }
-                        }
-                        Err(e) => bail! {e},
-                    }
-                }
-                if error_count > 0 {
-                    bail! {"precheck not passed."}
-                } else {
-                    Ok(())
-                }
-            }
-            Err(e) => Err(e),
+    // a connection error is reported as a failed check instead of aborting the precheck,
+    // so it ends up in the report and in Error::PrecheckError
+    fn connection_check_result(
+        result: anyhow::Result<CheckResult>,
+        is_source: bool,
+        db_type: &DbType,
+    ) -> CheckResult {
+        result.unwrap_or_else(|err| {
+            CheckResult::build_with_err(
+                CheckItem::CheckDatabaseConnection,
+                is_source,
+                db_type.clone(),
+                Some(err),
+                None,
+            )
+        })
+    }
+
+    pub async fn build_report(&self) -> anyhow::Result<PrecheckReport> {
+        let results = self.check().await?;
+        eprintln!("check result:");
+        let mut check_results = Vec::with_capacity(results.len());
+        for check_result in results {
+            let result = check_result?;
+            result.log();
+            check_results.push(result);
         }
+        Ok(PrecheckReport::from_check_results(
+            &check_results,
+            &self.task_config.extractor_basic.db_type,
+            &self.task_config.sinker_basic.db_type,
+        ))
+    }
+
+    pub async fn verify_check_result(&self) -> anyhow::Result<()> {
+        self.build_report().await?.verify()
     }
 }
@@ -2,4 +2,6 @@
 pub struct PrecheckConfig {
     pub do_struct_init: bool,
     pub do_cdc: bool,
+    // only check whether the source and sink can be connected
+    pub connection_only: bool,
 }
@@ -31,10 +31,21 @@ impl PrecheckTaskConfig {
             ini.get(PRECHECK, "do_struct_init"),
             ini.get(PRECHECK, "do_cdc"),
         );
+        let connection_only = match ini.get(PRECHECK, "connection_only") {
+            Some(v) => match v.parse() {
+                Ok(connection_only) => connection_only,
+                Err(_) => bail! {Error::ConfigError(format!(
+                    "config [precheck].connection_only={} is not a bool.",
+                    v
+                ))},
+            },
+            None => false,
+        };
         if let (Some(do_struct), Some(do_cdc)) = (do_struct_opt, do_cdc_opt) {
             Ok(PrecheckConfig {
                 do_struct_init: do_struct.parse().unwrap(),
                 do_cdc: do_cdc.parse().unwrap(),
+                connection_only,
             })
         } else {
             bail! {Error::ConfigError(
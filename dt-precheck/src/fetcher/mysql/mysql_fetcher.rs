@@ -229,7 +229,7 @@ impl MysqlFetcher {
         };
 
         sql_msg = if sql_msg.is_empty() { "sql" } else { sql_msg };
-        println!("{}: {}", sql_msg, sql);
+        eprintln!("{}: {}", sql_msg, sql);
 
         let rows_result = query(&sql).fetch_all(mysql_pool).await;
         match rows_result {
@@ -246,7 +246,7 @@ impl MysqlFetcher {
         match &self.pool {
             Some(pool) => {
                 sql_msg = if sql_msg.is_empty() { "sql" } else { sql_msg };
-                println!("{}: {}", sql_msg, sql);
+                eprintln!("{}: {}", sql_msg, sql);
                 Ok(query(sql).fetch(pool))
             }
             None => bail! {Error::from(sqlx::Error::PoolClosed)},
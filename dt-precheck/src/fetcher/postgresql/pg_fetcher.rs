@@ -211,7 +211,7 @@ impl PgFetcher {
         };
 
         sql_msg = if sql_msg.is_empty() { "sql" } else { sql_msg };
-        println!("{}: {}", sql_msg, sql);
+        eprintln!("{}: {}", sql_msg, sql);
 
         let rows_result = query(&sql).fetch_all(pg_pool).await;
         match rows_result {
@@ -228,7 +228,7 @@ impl PgFetcher {
         match &self.pool {
             Some(pool) => {
                 sql_msg = if sql_msg.is_empty() { "sql" } else { sql_msg };
-                println!("{}: {}", sql_msg, sql);
+                eprintln!("{}: {}", sql_msg, sql);
                 Ok(query(sql).fetch(pool))
             }
             None => bail! {Error::from(sqlx::Error::PoolClosed)},
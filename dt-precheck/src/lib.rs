@@ -2,6 +2,7 @@ use dt_common::config::task_config::TaskConfig;
 
 use crate::{
     builder::prechecker_builder::PrecheckerBuilder, config::task_config::PrecheckTaskConfig,
+    meta::precheck_report::PrecheckReport,
 };
 
 pub mod builder;
@@ -10,27 +11,53 @@ pub mod fetcher;
 pub mod meta;
 pub mod prechecker;
 
-pub async fn do_precheck(config: &str) {
-    let task_config = TaskConfig::new(config).unwrap();
-    let precheck_config = PrecheckTaskConfig::new(config).unwrap();
+/// Runs the precheck, Err(Error::PrecheckError) is returned if any check is not passed.
+pub async fn do_precheck(config: &str) -> anyhow::Result<()> {
+    do_precheck_checked(config).await?.verify()
+}
+
+pub async fn do_precheck_with_config_str(config_str: &str) -> anyhow::Result<()> {
+    do_precheck_with_config_str_checked(config_str)
+        .await?
+        .verify()
+}
+
+/// Runs the precheck and returns the result of each check instead of panicking,
+/// Err is returned only if the precheck could not be run (invalid config, check error, etc.)
+pub async fn do_precheck_checked(config: &str) -> anyhow::Result<PrecheckReport> {
+    let task_config = TaskConfig::new(config)?;
+    let precheck_config = PrecheckTaskConfig::new(config)?;
 
-    do_precheck_internal(task_config, precheck_config).await;
+    do_precheck_internal(task_config, precheck_config).await
 }
 
-pub async fn do_precheck_with_config_str(config_str: &str) {
-    let task_config = TaskConfig::new_from_str(config_str).unwrap();
-    let precheck_config = PrecheckTaskConfig::new_from_str(config_str).unwrap();
+pub async fn do_precheck_with_config_str_checked(
+    config_str: &str,
+) -> anyhow::Result<PrecheckReport> {
+    let task_config = TaskConfig::new_from_str(config_str)?;
+    let precheck_config = PrecheckTaskConfig::new_from_str(config_str)?;
 
-    do_precheck_internal(task_config, precheck_config).await;
+    do_precheck_internal(task_config, precheck_config).await
 }
 
-async fn do_precheck_internal(task_config: TaskConfig, precheck_config: PrecheckTaskConfig) {
+async fn do_precheck_internal(
+    task_config: TaskConfig,
+    precheck_config: PrecheckTaskConfig,
+) -> anyhow::Result<PrecheckReport> {
     let checker_connector = PrecheckerBuilder::build(precheck_config.precheck, task_config);
-    let result = checker_connector.verify_check_result().await;
-    if let Err(e) = result {
-        println!("precheck not passed.");
-        panic!("precheck meet error: {}", e);
-    }
+    checker_connector.build_report().await
+}
 
-    println!("precheck passed.");
+/// Prints the precheck outcome to stderr, Err is returned if it is not passed.
+pub fn report_precheck_result(result: anyhow::Result<PrecheckReport>) -> anyhow::Result<()> {
+    match result.and_then(|report| report.verify()) {
+        Ok(()) => {
+            eprintln!("precheck passed.");
+            Ok(())
+        }
+        Err(e) => {
+            eprintln!("precheck not passed.");
+            Err(e)
+        }
+    }
 }
@@ -9,3 +9,15 @@ pub enum CheckItem {
     CheckIfStructExisted,
     CheckIfTableStructSupported,
 }
+
+impl CheckItem {
+    pub fn category(&self) -> &'static str {
+        match self {
+            CheckItem::CheckDatabaseConnection => "connection",
+            CheckItem::CheckDatabaseVersionSupported => "version",
+            CheckItem::CheckAccountPermission => "permission",
+            CheckItem::CheckIfDatabaseSupportCdc => "cdc",
+            CheckItem::CheckIfStructExisted | CheckItem::CheckIfTableStructSupported => "struct",
+        }
+    }
+}
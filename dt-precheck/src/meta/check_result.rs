@@ -5,6 +5,7 @@ use super::check_item::CheckItem;
 #[derive(Debug, Clone)]
 pub struct CheckResult {
     pub check_type_name: String,
+    pub check_category: String,
     pub check_desc: String,
     pub is_validate: bool,
     pub error_msg: String,
@@ -17,6 +18,7 @@ impl CheckResult {
     pub fn build(check_item: CheckItem, is_source: bool) -> Self {
         Self {
             check_type_name: check_item.to_string(),
+            check_category: check_item.category().to_string(),
             check_desc: String::from(""),
             is_validate: true,
             error_msg: String::from(""),
@@ -99,6 +101,7 @@ impl CheckResult {
         match err_option {
             Some(err) => Self {
                 check_type_name: check_item.to_string(),
+                check_category: check_item.category().to_string(),
                 check_desc,
                 is_validate: false,
                 error_msg: err.to_string(),
@@ -108,6 +111,7 @@ impl CheckResult {
             },
             None => Self {
                 check_type_name: check_item.to_string(),
+                check_category: check_item.category().to_string(),
                 check_desc,
                 is_validate: true,
                 error_msg: String::from(""),
@@ -119,7 +123,7 @@ impl CheckResult {
     }
 
     pub fn log(&self) {
-        println!("======================================");
-        println!("[check_type_name]:{} \n[is_validate]:{} \n[check_desc]:{} \n[error_messaeg]:{} \n[warn_message]:{} \n[advise_message]:{}\n", self.check_type_name, self.is_validate, self.check_desc, self.error_msg, self.warn_msg, self.advise_msg);
+        eprintln!("======================================");
+        eprintln!("[check_type_name]:{} \n[is_validate]:{} \n[check_desc]:{} \n[error_messaeg]:{} \n[warn_message]:{} \n[advise_message]:{}\n", self.check_type_name, self.is_validate, self.check_desc, self.error_msg, self.warn_msg, self.advise_msg);
     }
 }
@@ -3,3 +3,4 @@ pub mod check_result;
 pub mod database_mode;
 pub mod db_table_model;
 pub mod pg_enums;
+pub mod precheck_report;
@@ -0,0 +1,88 @@
+use anyhow::bail;
+use dt_common::{config::config_enums::DbType, error::Error};
+use serde::Serialize;
+
+use super::check_result::CheckResult;
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PrecheckReport {
+    pub passed: bool,
+    pub checks: Vec<CheckReport>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckReport {
+    pub name: String,
+    // connection, version, permission, cdc, struct
+    pub category: String,
+    // source or sink
+    pub target: String,
+    pub db_type: String,
+    pub passed: bool,
+    // error of failed checks, warning (if any) of passed ones
+    pub message: String,
+    // how to fix failed checks, empty for passed ones
+    pub advise: String,
+}
+
+impl PrecheckReport {
+    pub fn from_check_results(
+        check_results: &[CheckResult],
+        source_db_type: &DbType,
+        sink_db_type: &DbType,
+    ) -> Self {
+        let checks: Vec<CheckReport> = check_results
+            .iter()
+            .map(|check_result| {
+                let db_type = if check_result.is_source {
+                    source_db_type
+                } else {
+                    sink_db_type
+                };
+                CheckReport::new(check_result, db_type)
+            })
+            .collect();
+        Self {
+            passed: checks.iter().all(|c| c.passed),
+            checks,
+        }
+    }
+
+    /// Err(Error::PrecheckError) of the first failed check if the precheck is not passed.
+    pub fn verify(&self) -> anyhow::Result<()> {
+        if let Some(check) = self.checks.iter().find(|c| !c.passed) {
+            bail!(Error::PrecheckError {
+                check: check.name.clone(),
+                target: check.target.clone(),
+                message: check.message.clone(),
+            })
+        }
+        Ok(())
+    }
+}
+
+impl CheckReport {
+    fn new(check_result: &CheckResult, db_type: &DbType) -> Self {
+        let (message, advise) = if check_result.is_validate {
+            (check_result.warn_msg.clone(), String::new())
+        } else {
+            (
+                check_result.error_msg.clone(),
+                check_result.advise_msg.clone(),
+            )
+        };
+        Self {
+            name: check_result.check_type_name.clone(),
+            category: check_result.check_category.clone(),
+            target: if check_result.is_source {
+                "source".to_string()
+            } else {
+                "sink".to_string()
+            },
+            db_type: db_type.to_string(),
+            passed: check_result.is_validate,
+            message,
+            advise,
+        }
+    }
+}
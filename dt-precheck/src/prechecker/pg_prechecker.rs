@@ -313,7 +313,7 @@ impl Prechecker for PostgresqlPrechecker {
         all_schemas.extend(&schemas);
         all_schemas.extend(&tb_schemas);
         if all_schemas.is_empty() {
-            println!("found no schema need to do migrate, very strange");
+            eprintln!("found no schema need to do migrate, very strange");
             bail! {
             "found no schema need to do migrate"};
         }
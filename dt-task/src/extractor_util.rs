@@ -22,6 +22,7 @@ use dt_common::{
     },
     monitor::task_monitor_handle::TaskMonitorHandle,
     rdb_filter::RdbFilter,
+    row_filter::RowFilter,
     time_filter::TimeFilter,
     utils::redis_util::RedisUtil,
 };
@@ -129,6 +130,7 @@ impl ExtractorUtil {
                 parallel_size,
                 parallel_type,
                 batch_size,
+                chunk_size,
                 ..
             } => {
                 let conn_pool = match extractor_client {
@@ -154,6 +156,7 @@ impl ExtractorUtil {
                         filter: Arc::new(filter),
                         partition_cols: Arc::new(Self::parse_partition_cols(&partition_cols)?),
                         batch_size,
+                        chunk_size,
                         parallel_type,
                         sample_rate: Self::sample_rate(config, extractor_config),
                         recovery,
@@ -215,6 +218,8 @@ impl ExtractorUtil {
                 keepalive_interval_secs,
                 start_time_utc,
                 end_time_utc,
+                binlog_local_path,
+                split_add_indexes,
             } => {
                 let conn_pool = match extractor_client {
                     ConnClient::MySQL(conn_pool) => conn_pool,
@@ -233,6 +238,7 @@ impl ExtractorUtil {
                 let extractor = MysqlCdcExtractor {
                     meta_manager,
                     filter,
+                    row_filter: RowFilter::from_config(&config.filter)?,
                     conn_pool,
                     url,
                     connection_auth,
@@ -245,6 +251,8 @@ impl ExtractorUtil {
                     heartbeat_tb,
                     keepalive_idle_secs,
                     keepalive_interval_secs,
+                    binlog_local_path,
+                    split_add_indexes,
                     syncer,
                     base_extractor,
                     extract_state,
@@ -261,6 +269,7 @@ impl ExtractorUtil {
                 parallel_size,
                 parallel_type,
                 batch_size,
+                chunk_size,
                 ..
             } => {
                 let conn_pool = match extractor_client {
@@ -278,6 +287,7 @@ impl ExtractorUtil {
                         filter: Arc::new(filter),
                         partition_cols: Arc::new(Self::parse_partition_cols(&partition_cols)?),
                         batch_size,
+                        chunk_size,
                         parallel_type,
                         sample_rate: Self::sample_rate(config, extractor_config),
                         recovery,
@@ -337,6 +347,7 @@ impl ExtractorUtil {
                 let extractor = PgCdcExtractor {
                     meta_manager,
                     filter,
+                    row_filter: RowFilter::from_config(&config.filter)?,
                     url,
                     connection_auth,
                     conn_pool,
@@ -655,12 +666,18 @@ impl ExtractorUtil {
             ExtractorConfig::RedisReshard {
                 url,
                 connection_auth,
+                resume_file,
+                migrate_timeout_ms,
+                migrate_batch_size,
             } => {
                 let extractor = RedisReshardExtractor {
                     base_extractor,
                     extract_state,
                     url,
                     connection_auth,
+                    resume_file,
+                    migrate_timeout_ms,
+                    migrate_batch_size,
                 };
                 Box::new(extractor)
             }
@@ -674,7 +691,7 @@ impl ExtractorUtil {
                 ack_interval_secs,
             } => {
                 let meta_manager = TaskUtil::create_rdb_meta_manager(config).await?;
-                let avro_converter = AvroConverter::new(meta_manager, false);
+                let avro_converter = AvroConverter::new(meta_manager, false, false);
                 let extractor = KafkaExtractor {
                     url,
                     group,
@@ -2,6 +2,7 @@
 
 pub mod extractor_util;
 pub mod parallelizer_util;
+pub mod shutdown_token;
 pub mod sinker_util;
 pub mod task_runner;
 pub mod task_util;
@@ -1,21 +1,36 @@
 use std::collections::{HashMap, VecDeque};
 
 use super::task_util::TaskUtil;
-use anyhow::anyhow;
+use anyhow::{anyhow, bail};
 use dt_common::{
-    config::{config_enums::ParallelType, sinker_config::SinkerConfig, task_config::TaskConfig},
+    config::{
+        config_enums::{DmlOrder, ParallelType},
+        sinker_config::SinkerConfig,
+        task_config::TaskConfig,
+    },
+    error::Error,
     meta::redis::command::key_parser::KeyParser,
     monitor::task_monitor_handle::TaskMonitorHandle,
     utils::redis_util::RedisUtil,
 };
 use dt_parallelizer::{
-    base_parallelizer::BaseParallelizer, foxlake_parallelizer::FoxlakeParallelizer,
-    merge_parallelizer::MergeParallelizer, mongo_merger::MongoMerger,
-    partition_parallelizer::PartitionParallelizer, rdb_merger::RdbMerger,
-    rdb_partitioner::RdbPartitioner, redis_parallelizer::RedisParallelizer,
-    serial_parallelizer::SerialParallelizer, snapshot_parallelizer::SnapshotParallelizer,
-    table_parallelizer::TableParallelizer, Merger, Parallelizer,
+    base_parallelizer::BaseParallelizer,
+    dml_orderer::DmlOrderer,
+    foxlake_parallelizer::FoxlakeParallelizer,
+    merge_parallelizer::MergeParallelizer,
+    mongo_merger::MongoMerger,
+    partition_parallelizer::PartitionParallelizer,
+    rdb_merger::RdbMerger,
+    rdb_partitioner::{NoKeyPartitionCols, RdbPartitioner},
+    redis_parallelizer::RedisParallelizer,
+    serial_parallelizer::SerialParallelizer,
+    snapshot_parallelizer::SnapshotParallelizer,
+    table_parallelizer::TableParallelizer,
+    Merger, Parallelizer,
 };
+use serde::Deserialize;
+
+const JSON_PREFIX: &str = "json:";
 
 pub struct ParallelizerUtil {}
 
@@ -29,6 +44,7 @@ impl ParallelizerUtil {
         let base_parallelizer = BaseParallelizer {
             popped_data: VecDeque::new(),
             monitor,
+            max_drain_bytes: config.parallelizer.max_drain_bytes(),
         };
 
         let parallelizer: Box<dyn Parallelizer + Send + Sync> = match parallel_type {
@@ -48,6 +64,7 @@ impl ParallelizerUtil {
                     base_parallelizer,
                     partitioner,
                     parallel_size,
+                    dml_orderer: Self::create_dml_orderer(config).await?,
                 })
             }
 
@@ -56,11 +73,15 @@ impl ParallelizerUtil {
                     .await?
             }
 
-            ParallelType::Serial => Box::new(SerialParallelizer { base_parallelizer }),
+            ParallelType::Serial => Box::new(SerialParallelizer {
+                base_parallelizer,
+                dml_orderer: Self::create_dml_orderer(config).await?,
+            }),
 
             ParallelType::Table => Box::new(TableParallelizer {
                 base_parallelizer,
                 parallel_size,
+                dml_orderer: Self::create_dml_orderer(config).await?,
             }),
 
             ParallelType::Mongo => {
@@ -115,11 +136,57 @@ impl ParallelizerUtil {
         Ok(Box::new(rdb_merger))
     }
 
+    async fn create_dml_orderer(config: &TaskConfig) -> anyhow::Result<Option<DmlOrderer>> {
+        if config.parallelizer.dml_order() == DmlOrder::Origin {
+            return Ok(None);
+        }
+        // keys of rows are required to keep the final state of each row
+        let Some(meta_manager) = TaskUtil::create_rdb_meta_manager(config).await? else {
+            bail!(Error::ConfigError(
+                "config [parallelizer].dml_order=delete_first requires a mysql/pg compatible target"
+                    .into()
+            ));
+        };
+        Ok(Some(DmlOrderer { meta_manager }))
+    }
+
     async fn create_rdb_partitioner(config: &TaskConfig) -> anyhow::Result<RdbPartitioner> {
         let meta_manager = TaskUtil::create_rdb_meta_manager(config)
             .await?
             .ok_or_else(|| anyhow!("failed to create RDB meta manager for partitioner target"))?;
-        Ok(RdbPartitioner { meta_manager })
+        let no_key_partition_cols =
+            Self::parse_partition_cols(config.parallelizer.no_key_partition_cols())?;
+        Ok(RdbPartitioner {
+            meta_manager,
+            no_key_partition: config.parallelizer.no_key_partition(),
+            no_key_partition_cols,
+        })
+    }
+
+    fn parse_partition_cols(config_str: &str) -> anyhow::Result<NoKeyPartitionCols> {
+        let mut results = NoKeyPartitionCols::new();
+        if config_str.trim().is_empty() {
+            return Ok(results);
+        }
+        // no_key_partition_cols=json:[{"db":"test_db","tb":"tb_1","partition_cols":["c1","c2"]}]
+        #[derive(Deserialize)]
+        struct PartitionColsType {
+            db: String,
+            tb: String,
+            partition_cols: Vec<String>,
+        }
+        let config: Vec<PartitionColsType> =
+            serde_json::from_str(config_str.trim_start_matches(JSON_PREFIX))?;
+        for i in config {
+            if i.partition_cols.is_empty() {
+                bail! {Error::ConfigError(format!(
+                    "config [parallelizer].no_key_partition_cols of {}.{} is empty",
+                    i.db, i.tb
+                ))}
+            }
+            results.insert((i.db, i.tb), i.partition_cols);
+        }
+        Ok(results)
     }
 
     async fn create_rdb_merge_parallelizer(
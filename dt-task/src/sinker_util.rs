@@ -1,19 +1,29 @@
-use std::{str::FromStr, sync::Arc};
+use std::{collections::HashMap, str::FromStr, sync::Arc, time::Duration};
 
 use anyhow::{bail, Context};
 use kafka::producer::{Producer, RequiredAcks};
+use rdkafka::{producer::FutureProducer, ClientConfig};
 use reqwest::{redirect::Policy, Url};
-use sqlx::types::chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::types::chrono::{FixedOffset, Offset, Utc};
 use tokio::sync::{Mutex, RwLock};
 
 use dt_common::{
     config::{
-        config_enums::DbType, connection_auth_config::ConnectionAuthConfig,
-        extractor_config::ExtractorConfig, message_format::MessageFormat,
-        sinker_config::SinkerConfig, task_config::TaskConfig,
+        config_enums::{DbType, ExtractType, KafkaProducerType, UnsupportedColValuePolicy},
+        connection_auth_config::ConnectionAuthConfig,
+        extractor_config::ExtractorConfig,
+        json_template_type::JsonTemplateType,
+        message_format::MessageFormat,
+        sinker_config::SinkerConfig,
+        task_config::TaskConfig,
+        topic_strategy::TopicStrategy,
     },
+    error::Error,
+    limiter::sink_rate_limiter::SinkRateLimiter,
     meta::{
         avro::avro_converter::AvroConverter,
+        col_projection::ColProjection,
         json::json_converter::JsonConverter,
         mysql::mysql_meta_manager::MysqlMetaManager,
         pg::pg_meta_manager::PgMetaManager,
@@ -21,6 +31,7 @@ use dt_common::{
             command::key_parser::KeyParser, redis_statistic_type::RedisStatisticType,
             redis_write_method::RedisWriteMethod,
         },
+        unsupported_col_value_handler::{ColValueCapability, UnsupportedColValueHandler},
     },
     monitor::task_monitor_handle::TaskMonitorHandle,
     rdb_filter::RdbFilter,
@@ -39,20 +50,30 @@ use dt_connector::{
         clickhouse::{
             clickhouse_sinker::ClickhouseSinker, clickhouse_struct_sinker::ClickhouseStructSinker,
         },
+        csv::csv_sinker::{CsvOptions, CsvSinker},
+        discard_sinker::DiscardSinker,
         dummy_sinker::DummySinker,
         foxlake::{
             foxlake_merger::FoxlakeMerger, foxlake_pusher::FoxlakePusher,
             foxlake_sinker::FoxlakeSinker, foxlake_struct_sinker::FoxlakeStructSinker,
             orc_sequencer::OrcSequencer,
         },
-        kafka::kafka_sinker::KafkaSinker,
+        kafka::{
+            kafka_sinker::{KafkaHeartbeat, KafkaSinker, KeyCols},
+            rdkafka_sinker::RdkafkaSinker,
+        },
         mongo::mongo_sinker::MongoSinker,
         mysql::{mysql_sinker::MysqlSinker, mysql_struct_sinker::MysqlStructSinker},
+        parquet::parquet_sinker::ParquetSinker,
         pg::{pg_sinker::PgSinker, pg_struct_sinker::PgStructSinker},
-        redis::{redis_sinker::RedisSinker, redis_statistic_sinker::RedisStatisticSinker},
+        redis::{
+            redis_dump_sinker::RedisDumpSinker, redis_sinker::RedisSinker,
+            redis_statistic_sinker::RedisStatisticSinker,
+        },
         sql_sinker::SqlSinker,
         starrocks::{
-            starrocks_sinker::StarRocksSinker, starrocks_struct_sinker::StarrocksStructSinker,
+            batch_size_tuner::BatchSizeTuner, starrocks_sinker::StarRocksSinker,
+            starrocks_struct_sinker::StarrocksStructSinker,
         },
     },
     Sinker,
@@ -60,6 +81,8 @@ use dt_connector::{
 
 type Sinkers = Vec<Arc<async_mutex::Mutex<Box<dyn Sinker + Send>>>>;
 
+const JSON_PREFIX: &str = "json:";
+
 pub struct SinkerUtil {}
 
 #[macro_export]
@@ -108,11 +131,22 @@ impl SinkerUtil {
                 }
             }
 
+            SinkerConfig::Discard { batch_delay_ms } => {
+                for _ in 0..parallel_size {
+                    let sinker = DiscardSinker {
+                        batch_delay_ms,
+                        base_sinker: BaseSinker::new(monitor.clone(), monitor_interval),
+                    };
+                    Self::push_sinker(&mut sub_sinkers, sinker);
+                }
+            }
+
             SinkerConfig::Mysql {
                 url,
                 connection_auth,
                 batch_size,
                 replace,
+                upsert,
                 ..
             } => {
                 let router = RdbRouter::from_config(&config.router, &DbType::Mysql)?;
@@ -136,6 +170,7 @@ impl SinkerUtil {
                         base_sinker: BaseSinker::new(monitor.clone(), monitor_interval),
                         data_marker: data_marker.clone(),
                         replace,
+                        upsert,
                     };
                     Self::push_checkable_sinker(&mut sub_sinkers, sinker, &checker);
                 }
@@ -146,6 +181,8 @@ impl SinkerUtil {
                 connection_auth,
                 batch_size,
                 replace,
+                upsert,
+                use_copy,
                 ..
             } => {
                 let router = RdbRouter::from_config(&config.router, &DbType::Pg)?;
@@ -168,6 +205,8 @@ impl SinkerUtil {
                         base_sinker: BaseSinker::new(monitor.clone(), monitor_interval),
                         data_marker: data_marker.clone(),
                         replace,
+                        upsert,
+                        use_copy,
                     };
                     Self::push_checkable_sinker(&mut sub_sinkers, sinker, &checker);
                 }
@@ -198,9 +237,53 @@ impl SinkerUtil {
                 ack_timeout_secs,
                 required_acks,
                 with_field_defs,
+                avro_logical_types,
                 message_format,
                 json_template,
+                topic_strategy,
+                key_cols,
+                batch_payload,
+                diff_only,
+                timezone,
+                heartbeat_interval_secs,
+                heartbeat_topic,
+                producer_type,
+                max_in_flight,
+                with_headers,
+                transactional,
+                transactional_id,
             } => {
+                let binary_format = matches!(
+                    message_format,
+                    MessageFormat::Avro | MessageFormat::Protobuf
+                );
+                if batch_payload && binary_format {
+                    bail!(Error::ConfigError(
+                        "config [sinker].batch_payload requires a json message_format".into()
+                    ));
+                }
+                // the rdkafka producer only sends avro messages of whole rows keyed by the primary key,
+                // to topics resolved by [router].topic_map
+                let is_rdkafka = producer_type == KafkaProducerType::Rdkafka;
+                if is_rdkafka
+                    && (message_format != MessageFormat::Avro
+                        || !key_cols.is_empty()
+                        || topic_strategy != TopicStrategy::TopicMap
+                        || heartbeat_interval_secs > 0
+                        || !config.sinker_basic.include_cols.is_empty()
+                        || !config.sinker_basic.exclude_cols.is_empty()
+                        || config.sinker_basic.unsupported_col_value_policy
+                            != UnsupportedColValuePolicy::Convert)
+                {
+                    bail!(Error::ConfigError(
+                        "config [sinker].producer_type=rdkafka requires message_format=avro, and does not support key_cols, topic_strategy, heartbeat_interval_secs, include_cols, exclude_cols or unsupported_col_value_policy".into()
+                    ));
+                }
+                if transactional && (!is_rdkafka || transactional_id.is_empty()) {
+                    bail!(Error::ConfigError(
+                        "config [sinker].transactional requires producer_type=rdkafka and transactional_id".into()
+                    ));
+                }
                 let router = RdbRouter::from_config_for_topic(
                     &config.router,
                     // use the db_type of extractor
@@ -208,7 +291,8 @@ impl SinkerUtil {
                 )?;
                 // kafka sinker may need meta data from RDB extractor
                 let meta_manager = ExtractorUtil::get_extractor_meta_manager(config).await?;
-                let avro_converter = AvroConverter::new(meta_manager.clone(), with_field_defs);
+                let avro_converter =
+                    AvroConverter::new(meta_manager.clone(), with_field_defs, avro_logical_types);
                 let database_name = match extractor_config.to_owned() {
                     ExtractorConfig::MysqlStruct { db, .. }
                     | ExtractorConfig::MysqlSnapshot { db, .. }
@@ -226,39 +310,111 @@ impl SinkerUtil {
                     _ => None,
                 };
                 let template_type = match &message_format {
-                    MessageFormat::JsonTemplate(template_type) => template_type.clone(),
+                    MessageFormat::JsonTemplate { template_type, .. } => template_type.clone(),
                     _ => json_template,
                 };
-                let json_converter =
-                    JsonConverter::new_with_template(meta_manager, template_type, database_name);
-
-                let brokers = vec![url.to_string()];
-                let acks = match required_acks.as_str() {
-                    "all" => RequiredAcks::All,
-                    "none" => RequiredAcks::None,
-                    _ => RequiredAcks::One,
+                if diff_only && (binary_format || template_type != JsonTemplateType::Standard) {
+                    bail!(Error::ConfigError(
+                        "config [sinker].diff_only requires the standard json template".into()
+                    ));
+                }
+                let snapshot = config.extractor_basic.extract_type == ExtractType::Snapshot;
+                let mut json_converter = JsonConverter::new_with_template(
+                    meta_manager,
+                    template_type,
+                    database_name,
+                    snapshot,
+                );
+                json_converter.diff_only = diff_only;
+                json_converter.set_timezone(Self::parse_timezone(&timezone)?);
+
+                let capability = match message_format {
+                    MessageFormat::Avro => ColValueCapability::KAFKA_AVRO,
+                    MessageFormat::Protobuf => ColValueCapability::KAFKA_PROTOBUF,
+                    _ => ColValueCapability::KAFKA_JSON,
                 };
+                let col_value_handler = UnsupportedColValueHandler::new(
+                    capability,
+                    config.sinker_basic.unsupported_col_value_policy.clone(),
+                );
 
-                for _ in 0..parallel_size {
-                    // TODO, authentication, https://github.com/kafka-rust/kafka-rust/blob/master/examples/example-ssl.rs
-                    let producer = Producer::from_hosts(brokers.clone())
-                        .with_ack_timeout(std::time::Duration::from_secs(ack_timeout_secs))
-                        .with_required_acks(acks)
-                        .create()
-                        .with_context(|| {
-                            format!("failed to create kafka producer, url: [{}]", url)
-                        })?;
-                    // the sending performance of RdkafkaSinker is much worse than KafkaSinker
-                    let sinker = KafkaSinker {
-                        batch_size,
-                        router: router.clone(),
-                        producer,
-                        avro_converter: avro_converter.clone(),
-                        json_converter: json_converter.clone(),
-                        message_format: message_format.clone(),
-                        base_sinker: BaseSinker::new(monitor.clone(), monitor_interval),
+                let key_cols = Self::parse_key_cols(&key_cols)?;
+                let col_projection = Self::build_col_projection(config)?;
+
+                if is_rdkafka {
+                    let acks = match required_acks.as_str() {
+                        "all" => "all",
+                        "none" => "0",
+                        _ => "1",
                     };
-                    Self::push_sinker(&mut sub_sinkers, sinker);
+                    for i in 0..parallel_size {
+                        let mut client_config = ClientConfig::new();
+                        client_config.set("bootstrap.servers", &url);
+                        if transactional {
+                            // transactions imply idempotence and acks=all,
+                            // the id of each sinker keeps the same after restart to fence zombies
+                            client_config
+                                .set("transactional.id", format!("{}_{}", transactional_id, i));
+                        } else {
+                            client_config.set("acks", acks);
+                        }
+                        let producer: FutureProducer =
+                            client_config.create().with_context(|| {
+                                format!("failed to create rdkafka producer, url: [{}]", url)
+                            })?;
+                        let sinker = RdkafkaSinker::new(
+                            batch_size,
+                            router.clone(),
+                            producer,
+                            avro_converter.clone(),
+                            BaseSinker::new(monitor.clone(), monitor_interval),
+                            ack_timeout_secs,
+                            max_in_flight,
+                            with_headers,
+                            transactional,
+                        );
+                        Self::push_sinker(&mut sub_sinkers, sinker);
+                    }
+                } else {
+                    let brokers = vec![url.to_string()];
+                    let acks = match required_acks.as_str() {
+                        "all" => RequiredAcks::All,
+                        "none" => RequiredAcks::None,
+                        _ => RequiredAcks::One,
+                    };
+
+                    for _ in 0..parallel_size {
+                        // TODO, authentication, https://github.com/kafka-rust/kafka-rust/blob/master/examples/example-ssl.rs
+                        let producer = Producer::from_hosts(brokers.clone())
+                            .with_ack_timeout(std::time::Duration::from_secs(ack_timeout_secs))
+                            .with_required_acks(acks)
+                            .create()
+                            .with_context(|| {
+                                format!("failed to create kafka producer, url: [{}]", url)
+                            })?;
+                        // the sending performance of RdkafkaSinker is much worse than KafkaSinker
+                        let sinker = KafkaSinker {
+                            batch_size,
+                            router: router.clone(),
+                            producer,
+                            avro_converter: avro_converter.clone(),
+                            json_converter: json_converter.clone(),
+                            message_format: message_format.clone(),
+                            topic_strategy: topic_strategy.clone(),
+                            col_value_handler: col_value_handler.clone(),
+                            col_projection: col_projection.clone(),
+                            key_cols: key_cols.clone(),
+                            batch_payload,
+                            heartbeat: (heartbeat_interval_secs > 0).then(|| {
+                                KafkaHeartbeat::new(
+                                    &heartbeat_topic,
+                                    Duration::from_secs(heartbeat_interval_secs),
+                                )
+                            }),
+                            base_sinker: BaseSinker::new(monitor.clone(), monitor_interval),
+                        };
+                        Self::push_sinker(&mut sub_sinkers, sinker);
+                    }
                 }
             }
 
@@ -390,11 +546,43 @@ impl SinkerUtil {
                 }
             }
 
+            SinkerConfig::RedisDump {
+                dump_dir,
+                file_max_bytes,
+                method,
+                version,
+            } => {
+                let method = RedisWriteMethod::from_str(&method)?;
+                let router = RdbRouter::from_config(&config.router, &DbType::Redis)?;
+                if let Some(router) = &router {
+                    router.validate_redis_db_map(false)?;
+                }
+                for id in 0..parallel_size as usize {
+                    let sinker = RedisDumpSinker::new(
+                        id,
+                        dump_dir.clone(),
+                        file_max_bytes,
+                        method.clone(),
+                        version,
+                        router.clone(),
+                        BaseSinker::new(monitor.clone(), monitor_interval),
+                    );
+                    Self::push_sinker(&mut sub_sinkers, sinker);
+                }
+            }
+
             SinkerConfig::StarRocks {
                 url,
                 connection_auth,
                 batch_size,
                 stream_load_url,
+                dry_run,
+                label_prefix,
+                stream_load_connect_timeout_secs,
+                stream_load_timeout_secs,
+                stream_load_ssl_ca_path,
+                stream_load_accept_invalid_certs,
+                binary_encoding,
                 ..
             }
             | SinkerConfig::Doris {
@@ -402,25 +590,85 @@ impl SinkerUtil {
                 connection_auth,
                 batch_size,
                 stream_load_url,
+                dry_run,
+                label_prefix,
+                stream_load_connect_timeout_secs,
+                stream_load_timeout_secs,
+                stream_load_ssl_ca_path,
+                stream_load_accept_invalid_certs,
+                binary_encoding,
+                ..
             } => {
-                for _ in 0..parallel_size {
+                let capability = match config.sinker_basic.db_type {
+                    DbType::Doris => ColValueCapability::DORIS,
+                    _ => ColValueCapability::STARROCKS,
+                };
+                let label_prefix = if label_prefix.is_empty() {
+                    None
+                } else {
+                    Some(label_prefix)
+                };
+                let request_timeout = if stream_load_timeout_secs > 0 {
+                    Some(Duration::from_secs(stream_load_timeout_secs))
+                } else {
+                    None
+                };
+                let (compression, rate_limiter, batch_size_tuner) = match &config.sinker {
+                    SinkerConfig::StarRocks {
+                        compression,
+                        stream_load_max_bps,
+                        stream_load_max_rps,
+                        min_batch_size,
+                        max_batch_size,
+                        target_load_time_ms,
+                        ..
+                    } => (
+                        Self::parse_stream_load_compression(compression)?,
+                        SinkRateLimiter::new(*stream_load_max_bps, *stream_load_max_rps)
+                            .map(Arc::new),
+                        (*max_batch_size > 0).then(|| BatchSizeTuner {
+                            min_batch_size: *min_batch_size,
+                            max_batch_size: *max_batch_size,
+                            target_load_time_ms: *target_load_time_ms,
+                        }),
+                    ),
+                    _ => (None, None, None),
+                };
+                let group_commit = match &config.sinker {
+                    SinkerConfig::Doris { group_commit, .. } => {
+                        Self::parse_group_commit(group_commit)?
+                    }
+                    _ => None,
+                };
+                // batch_size is the initial size when tuned
+                let batch_size = batch_size_tuner
+                    .as_ref()
+                    .map_or(batch_size, |tuner| tuner.clamp(batch_size));
+                let col_projection = Self::build_col_projection(config)?;
+                for id in 0..parallel_size as usize {
                     let url_info = Url::parse(&stream_load_url)?;
                     let host = url_info.host_str().unwrap().to_string();
-                    let port = format!("{}", url_info.port().unwrap());
+                    let scheme = Self::parse_stream_load_scheme(&url_info);
+                    let port = Self::parse_stream_load_port(&url_info, scheme);
                     let username = url_info.username().to_string();
                     let password = url_info.password().unwrap_or("").to_string();
-                    let custom = Policy::custom(|attempt| attempt.follow());
-                    let http_client = reqwest::Client::builder()
-                        .http1_title_case_headers()
-                        .redirect(custom)
-                        .build()?;
+                    let http_client = StarRocksSinker::build_http_client(
+                        stream_load_connect_timeout_secs,
+                        &stream_load_ssl_ca_path,
+                        stream_load_accept_invalid_certs,
+                    )?;
+                    // so the meta of doris tables contains __DORIS_DELETE_SIGN__
+                    let after_connect_settings = match config.sinker_basic.db_type {
+                        DbType::Doris => Some(vec!["SET show_hidden_columns = true"]),
+                        _ => None,
+                    };
                     let conn_pool = TaskUtil::create_mysql_conn_pool(
                         &url,
                         &DbType::StarRocks,
                         &connection_auth,
                         parallel_size * 2,
                         enable_sqlx_log,
-                        None,
+                        after_connect_settings,
                     )
                     .await?;
                     let meta_manager = MysqlMetaManager::new_mysql_compatible(
@@ -430,8 +678,10 @@ impl SinkerUtil {
                     .await?;
 
                     let mut sinker = StarRocksSinker {
+                        id,
                         db_type: config.sinker_basic.db_type.clone(),
                         http_client,
+                        scheme: scheme.to_string(),
                         host,
                         port,
                         username,
@@ -441,6 +691,22 @@ impl SinkerUtil {
                         base_sinker: BaseSinker::new(monitor.clone(), monitor_interval),
                         sync_timestamp: Utc::now().timestamp_millis(),
                         hard_delete: false,
+                        dry_run,
+                        label_prefix: label_prefix.clone(),
+                        compression: compression.clone(),
+                        col_value_handler: UnsupportedColValueHandler::new(
+                            capability.clone(),
+                            config.sinker_basic.unsupported_col_value_policy.clone(),
+                        ),
+                        col_projection: col_projection.clone(),
+                        rate_limiter: rate_limiter.clone(),
+                        request_timeout,
+                        binary_encoding: binary_encoding.clone(),
+                        batch_size_tuner: batch_size_tuner.clone(),
+                        group_commit: group_commit.clone(),
+                        batch_positions: None,
+                        batch_row_offset: 0,
+                        table_keys: HashMap::new(),
                     };
                     if let SinkerConfig::StarRocks { hard_delete, .. } = config.sinker {
                         sinker.hard_delete = hard_delete;
@@ -507,6 +773,78 @@ impl SinkerUtil {
                         batch_size,
                         base_sinker: BaseSinker::new(monitor.clone(), monitor_interval),
                         sync_timestamp: Utc::now().timestamp_millis(),
+                        col_value_handler: UnsupportedColValueHandler::new(
+                            ColValueCapability::CLICKHOUSE,
+                            config.sinker_basic.unsupported_col_value_policy.clone(),
+                        ),
+                    };
+                    Self::push_sinker(&mut sub_sinkers, sinker);
+                }
+            }
+
+            SinkerConfig::Parquet {
+                batch_size,
+                root_dir,
+                file_max_rows,
+            } => {
+                // cols of parquet files follow the source tables
+                let Some(meta_manager) = ExtractorUtil::get_extractor_meta_manager(config).await?
+                else {
+                    bail!(Error::ConfigError(
+                        "config [sinker].db_type=parquet requires a mysql/pg source".into()
+                    ));
+                };
+                for id in 0..parallel_size as usize {
+                    let sinker = ParquetSinker {
+                        id,
+                        batch_size,
+                        root_dir: root_dir.clone(),
+                        file_max_rows,
+                        meta_manager: meta_manager.clone(),
+                        base_sinker: BaseSinker::new(monitor.clone(), monitor_interval),
+                        buffers: HashMap::new(),
+                        writers: HashMap::new(),
+                    };
+                    Self::push_sinker(&mut sub_sinkers, sinker);
+                }
+            }
+
+            SinkerConfig::Csv {
+                root_dir,
+                delimiter,
+                quote,
+                quote_all,
+                header,
+                file_max_rows,
+                file_max_bytes,
+                hard_delete,
+                null_value,
+            } => {
+                let options = CsvOptions {
+                    delimiter: Self::parse_csv_char("delimiter", &delimiter)?,
+                    quote: Self::parse_csv_char("quote", &quote)?,
+                    quote_all,
+                    header,
+                    file_max_rows,
+                    file_max_bytes,
+                    hard_delete,
+                    null_value,
+                };
+                // cols of csv files follow the source tables
+                let Some(meta_manager) = ExtractorUtil::get_extractor_meta_manager(config).await?
+                else {
+                    bail!(Error::ConfigError(
+                        "config [sinker].db_type=csv requires a mysql/pg source".into()
+                    ));
+                };
+                for id in 0..parallel_size as usize {
+                    let sinker = CsvSinker {
+                        id,
+                        root_dir: root_dir.clone(),
+                        options: options.clone(),
+                        meta_manager: meta_manager.clone(),
+                        base_sinker: BaseSinker::new(monitor.clone(), monitor_interval),
+                        writers: HashMap::new(),
                     };
                     Self::push_sinker(&mut sub_sinkers, sinker);
                 }
@@ -732,4 +1070,107 @@ impl SinkerUtil {
         };
         Ok(sub_sinkers)
     }
+
+    // patterns are parsed with the escapes of the source db type, the same as [filter]
+    fn build_col_projection(config: &TaskConfig) -> anyhow::Result<ColProjection> {
+        ColProjection::from_config(
+            &config.sinker_basic.include_cols,
+            &config.sinker_basic.exclude_cols,
+            &config.extractor_basic.db_type,
+        )
+    }
+
+    pub fn parse_key_cols(config_str: &str) -> anyhow::Result<KeyCols> {
+        let mut results = KeyCols::new();
+        if config_str.trim().is_empty() {
+            return Ok(results);
+        }
+        // key_cols=json:[{"db":"test_db","tb":"tb_1","key_cols":["user_id"]}]
+        #[derive(Serialize, Deserialize)]
+        struct KeyColsType {
+            db: String,
+            tb: String,
+            key_cols: Vec<String>,
+        }
+        let config: Vec<KeyColsType> =
+            serde_json::from_str(config_str.trim_start_matches(JSON_PREFIX))?;
+        for i in config {
+            if i.key_cols.is_empty() {
+                bail! {Error::ConfigError(format!(
+                    "config [sinker].key_cols of {}.{} is empty",
+                    i.db, i.tb
+                ))}
+            }
+            results.insert((i.db, i.tb), i.key_cols);
+        }
+        Ok(results)
+    }
+
+    // empty for no compression
+    fn parse_stream_load_compression(value: &str) -> anyhow::Result<Option<String>> {
+        match value {
+            "" => Ok(None),
+            "gzip" | "lz4_frame" => Ok(Some(value.to_string())),
+            _ => bail! {Error::ConfigError(format!(
+                "config [sinker].compression should be gzip or lz4_frame, got: {}",
+                value
+            ))},
+        }
+    }
+
+    // stream loads are sent by https only if stream_load_url is https://,
+    // others like the documented mysql://root:@127.0.0.1:8040 are sent by http
+    fn parse_stream_load_scheme(url_info: &Url) -> &'static str {
+        if url_info.scheme().eq_ignore_ascii_case("https") {
+            "https"
+        } else {
+            "http"
+        }
+    }
+
+    // the default port of the stream load scheme if not set, 80 for http and 443 for https
+    fn parse_stream_load_port(url_info: &Url, scheme: &str) -> String {
+        let default_port = if scheme == "https" { 443 } else { 80 };
+        url_info.port().unwrap_or(default_port).to_string()
+    }
+
+    fn parse_group_commit(value: &str) -> anyhow::Result<Option<String>> {
+        match value {
+            "" => Ok(None),
+            "sync_mode" | "async_mode" => Ok(Some(value.to_string())),
+            _ => bail! {Error::ConfigError(format!(
+                "config [sinker].group_commit should be sync_mode or async_mode, got: {}",
+                value
+            ))},
+        }
+    }
+
+    // a fixed offset like +08:00, empty for UTC
+    fn parse_timezone(value: &str) -> anyhow::Result<FixedOffset> {
+        if value.is_empty() {
+            return Ok(Utc.fix());
+        }
+        match value.parse::<FixedOffset>() {
+            Ok(timezone) => Ok(timezone),
+            Err(_) => bail! {Error::ConfigError(format!(
+                "config [sinker].timezone should be an offset like +08:00, got: {}",
+                value
+            ))},
+        }
+    }
+
+    // a single char, \t stands for tab since tabs are trimmed from ini values
+    fn parse_csv_char(key: &str, value: &str) -> anyhow::Result<char> {
+        if value == "\\t" {
+            return Ok('\t');
+        }
+        let mut chars = value.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(c),
+            _ => bail! {Error::ConfigError(format!(
+                "config [sinker].{} should be a single char, got: {}",
+                key, value
+            ))},
+        }
+    }
 }
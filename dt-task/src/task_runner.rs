@@ -1,11 +1,12 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, VecDeque},
     panic,
     path::{Component, Path},
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
     },
+    time::{Duration, Instant},
 };
 
 use anyhow::{bail, Context};
@@ -21,7 +22,8 @@ use tokio::{
 };
 
 use super::{
-    extractor_util::ExtractorUtil, parallelizer_util::ParallelizerUtil, sinker_util::SinkerUtil,
+    extractor_util::ExtractorUtil, parallelizer_util::ParallelizerUtil,
+    shutdown_token::ShutdownToken, sinker_util::SinkerUtil,
 };
 use crate::task_util::{ConnClient, TaskUtil};
 use async_mutex::Mutex as AsyncMutex;
@@ -46,6 +48,7 @@ use dt_common::{
         row_type::RowType, syncer::Syncer,
     },
     monitor::{
+        status_server::StatusServer,
         task_metrics::TaskMetricsType,
         task_monitor::{MonitorType, TaskMonitor},
         task_monitor_handle::TaskMonitorHandle,
@@ -68,8 +71,8 @@ use dt_connector::{
     Extractor, Sinker,
 };
 use dt_pipeline::{
-    base_pipeline::BasePipeline, http_server_pipeline::HttpServerPipeline,
-    lua_processor::LuaProcessor, Pipeline,
+    base_pipeline::BasePipeline, col_transformer::ColTransformer,
+    http_server_pipeline::HttpServerPipeline, lua_processor::LuaProcessor, Pipeline,
 };
 
 #[cfg(feature = "metrics")]
@@ -87,6 +90,7 @@ pub struct TaskRunner {
     config: TaskConfig,
     filter: RdbFilter,
     task_monitor: Arc<TaskMonitor>,
+    shutdown_token: Arc<ShutdownToken>,
     #[cfg(feature = "metrics")]
     prometheus_metrics: Arc<PrometheusMetrics>,
 }
@@ -100,6 +104,8 @@ const RUNTIME_STDOUT_APPENDER_PLACEHOLDER: &str = "RUNTIME_STDOUT_APPENDER_PLACE
 const CHECK_RESULT_STDOUT_APPENDER_PLACEHOLDER: &str = "CHECK_RESULT_STDOUT_APPENDER_PLACEHOLDER";
 const DEFAULT_CHECK_LOG_DIR_PLACEHOLDER: &str = "LOG_DIR_PLACEHOLDER/check";
 const DEFAULT_STATISTIC_LOG_DIR_PLACEHOLDER: &str = "LOG_DIR_PLACEHOLDER/statistic";
+const STATUS_SERVER_BIND_RETRIES: usize = 10;
+const STATUS_SERVER_BIND_RETRY_INTERVAL: Duration = Duration::from_millis(500);
 
 fn init_task_check_summary() -> CheckSummaryLog {
     CheckSummaryLog {
@@ -153,12 +159,20 @@ impl TaskRunner {
             filter: RdbFilter::from_config(&config.filter, &config.extractor_basic.db_type)?,
             config,
             task_monitor,
+            shutdown_token: Arc::new(ShutdownToken::default()),
             #[cfg(feature = "metrics")]
             prometheus_metrics,
             task_type,
         })
     }
 
+    /// Once the token is cancelled, the extractor stops, the data already queued is sunk,
+    /// sinkers are closed and start_task returns
+    pub fn with_shutdown_token(mut self, shutdown_token: Arc<ShutdownToken>) -> Self {
+        self.shutdown_token = shutdown_token;
+        self
+    }
+
     pub async fn start_task(&self, is_init: bool) -> anyhow::Result<()> {
         self.clear_check_logs().await?;
         self.init_log4rs().await?;
@@ -179,7 +193,16 @@ impl TaskRunner {
             &self.config.global.task_id,
             &self.task_type
         );
+        let status_server = self.start_status_server().await?;
+        let result = self.run_task(is_init).await;
+        // the port is released before returning, so a restarted task binds it again
+        if let Some(status_server) = status_server {
+            status_server.stop().await;
+        }
+        result
+    }
 
+    async fn run_task(&self, is_init: bool) -> anyhow::Result<()> {
         let db_type = &self.config.extractor_basic.db_type;
         let router = Arc::new(RdbRouter::from_config(&self.config.router, db_type)?);
         let (recorder, recovery, checker_state_store) = match &self.task_type {
@@ -227,18 +250,17 @@ impl TaskRunner {
             .is_some_and(|task_type| matches!(task_type.kind, TaskKind::Snapshot))
             && task_info.no_snapshot_data;
         if !should_skip_task {
-            self.clone()
-                .create_task(
-                    task_info.extractor_config,
-                    extractor_client.clone(),
-                    sinker_client.clone(),
-                    router,
-                    recorder,
-                    recovery,
-                    check_summary.clone(),
-                    checker_state_store.clone(),
-                )
-                .await?;
+            self.run_supervised_task(
+                task_info.extractor_config,
+                extractor_client.clone(),
+                sinker_client.clone(),
+                router,
+                recorder,
+                recovery,
+                check_summary.clone(),
+                checker_state_store.clone(),
+            )
+            .await?;
         }
 
         // close connections
@@ -266,11 +288,37 @@ impl TaskRunner {
         log::logger().flush();
         self.remove_empty_check_logs().await?;
         self.upload_check_logs_to_s3().await?;
-        log_finished!("task finished");
+        // a stopped task is not finished, it resumes from its positions when started again
+        if self.shutdown_token.is_cancelled() {
+            log_finished!("task stopped");
+        } else {
+            log_finished!("task finished");
+        }
         log::logger().flush();
         Ok(())
     }
 
+    // the port is configured explicitly, so failing to bind it fails the task,
+    // the bind is retried since the server of a previous run may not have released the port yet,
+    // e.g. if that run panicked and its server was stopped by drop
+    async fn start_status_server(&self) -> anyhow::Result<Option<StatusServer>> {
+        if self.config.status_server.http_port == 0 {
+            return Ok(None);
+        }
+        let mut retries = 0;
+        loop {
+            match StatusServer::start(&self.config.status_server, self.task_monitor.clone()) {
+                Ok(status_server) => return Ok(Some(status_server)),
+                Err(err) if retries < STATUS_SERVER_BIND_RETRIES => {
+                    log_warn!("failed to start status server, will retry: {:#}", err);
+                    retries += 1;
+                    tokio::time::sleep(STATUS_SERVER_BIND_RETRY_INTERVAL).await;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
     async fn clear_check_logs(&self) -> anyhow::Result<()> {
         let Some(cfg) = self.config.checker.as_ref() else {
             return Ok(());
@@ -468,6 +516,111 @@ impl TaskRunner {
         format!("{base}/{scope}")
     }
 
+    async fn run_supervised_task(
+        &self,
+        extractor_config: ExtractorConfig,
+        extractor_client: ConnClient,
+        sinker_client: ConnClient,
+        router: Arc<Option<RdbRouter>>,
+        mut recorder: Option<Arc<dyn Recorder + Send + Sync>>,
+        mut recovery: Option<Arc<dyn Recovery + Send + Sync>>,
+        check_summary: Option<Arc<AsyncMutex<CheckSummaryLog>>>,
+        mut checker_state_store: Option<Arc<CheckerStateStore>>,
+    ) -> anyhow::Result<()> {
+        let runtime = &self.config.runtime;
+        let restart_window = Duration::from_secs(runtime.task_restart_window_secs);
+        let mut restart_history = VecDeque::new();
+
+        loop {
+            let err = match self
+                .clone()
+                .create_task(
+                    extractor_config.clone(),
+                    extractor_client.clone(),
+                    sinker_client.clone(),
+                    router.clone(),
+                    recorder.clone(),
+                    recovery.clone(),
+                    check_summary.clone(),
+                    checker_state_store.clone(),
+                )
+                .await
+            {
+                Ok(()) => return Ok(()),
+                Err(err) => err,
+            };
+
+            if self.shutdown_token.is_cancelled() {
+                return Err(err);
+            }
+
+            let Some(task_type) = self.task_type.filter(|task_type| {
+                task_type.kind == TaskKind::Cdc && runtime.max_task_restarts > 0
+            }) else {
+                return Err(err);
+            };
+
+            if !Error::is_recoverable_task_error(&err) {
+                log_error!("task failed with unrecoverable error, will not restart");
+                return Err(err);
+            }
+
+            if !Self::acquire_restart(
+                &mut restart_history,
+                Instant::now(),
+                runtime.max_task_restarts,
+                restart_window,
+            ) {
+                log_error!(
+                    "task restarted {} time(s) within {}s, giving up",
+                    restart_history.len(),
+                    runtime.task_restart_window_secs
+                );
+                return Err(err);
+            }
+
+            self.task_monitor
+                .add_no_window_metrics(TaskMetricsType::TaskRestartCount, 1);
+            log_warn!(
+                "task failed with recoverable error, restart {}/{} from last checkpoint in {}s, error: {:#}",
+                restart_history.len(),
+                runtime.max_task_restarts,
+                runtime.task_restart_interval_secs,
+                err
+            );
+            tokio::time::sleep(Duration::from_secs(runtime.task_restart_interval_secs)).await;
+
+            // reload resumer so that the rebuilt extractor starts from the last checkpoint
+            (recorder, recovery, checker_state_store) = TaskUtil::build_resumer(
+                task_type,
+                &self.config.global,
+                &self.config.resumer,
+                false,
+            )
+            .await?;
+        }
+    }
+
+    fn acquire_restart(
+        restart_history: &mut VecDeque<Instant>,
+        now: Instant,
+        max_restarts: u32,
+        window: Duration,
+    ) -> bool {
+        while restart_history
+            .front()
+            .is_some_and(|restart_time| now.duration_since(*restart_time) > window)
+        {
+            restart_history.pop_front();
+        }
+
+        if restart_history.len() >= max_restarts as usize {
+            return false;
+        }
+        restart_history.push_back(now);
+        true
+    }
+
     async fn create_task(
         self,
         extractor_config: ExtractorConfig,
@@ -616,6 +769,7 @@ impl TaskRunner {
                 rw_sinker_data_marker.clone(),
                 recorder.clone(),
                 checker,
+                router.as_ref(),
             )
             .await?;
         let pipeline = Arc::new(Mutex::new(pipeline));
@@ -650,8 +804,13 @@ impl TaskRunner {
             Ok(())
         });
 
-        let worker_result =
-            Self::run_task_workers(extractor.clone(), pipeline.clone(), shut_down.clone()).await;
+        let worker_result = Self::run_task_workers(
+            extractor.clone(),
+            pipeline.clone(),
+            shut_down.clone(),
+            self.shutdown_token.clone(),
+        )
+        .await;
 
         monitor_shut_down.store(true, Ordering::Release);
         let monitor_result = monitor_task
@@ -674,14 +833,17 @@ impl TaskRunner {
         extractor: Arc<Mutex<Box<dyn Extractor + Send>>>,
         pipeline: Arc<Mutex<Box<dyn Pipeline + Send>>>,
         shut_down: Arc<AtomicBool>,
+        shutdown_token: Arc<ShutdownToken>,
     ) -> anyhow::Result<()> {
         let mut join_set = JoinSet::new();
 
         let extractor_worker = extractor.clone();
+        let extractor_shut_down = shut_down.clone();
         join_set.spawn(async move {
             (
                 SingleTaskWorker::Extractor,
-                Self::run_extractor_worker(extractor_worker).await,
+                Self::run_extractor_worker(extractor_worker, extractor_shut_down, shutdown_token)
+                    .await,
             )
         });
 
@@ -763,15 +925,28 @@ impl TaskRunner {
 
     async fn run_extractor_worker(
         extractor: Arc<Mutex<Box<dyn Extractor + Send>>>,
+        shut_down: Arc<AtomicBool>,
+        shutdown_token: Arc<ShutdownToken>,
     ) -> anyhow::Result<()> {
-        let extract_result = {
+        let (extract_result, cancelled) = {
             let mut extractor = extractor.lock().await;
-            extractor.extract().await
+            tokio::select! {
+                result = extractor.extract() => (result, false),
+                _ = shutdown_token.cancelled() => {
+                    log_info!("shutdown requested, stop extracting and flush the queued data");
+                    (Ok(()), true)
+                }
+            }
         };
         let close_result = {
             let mut extractor = extractor.lock().await;
             extractor.close().await
         };
+        // extractors only set it when they finish, the pipeline then drains the buffer,
+        // closes sinkers and exits
+        if cancelled {
+            shut_down.store(true, Ordering::Release);
+        }
 
         extract_result.context("extractor.extract failed")?;
         close_result.context("extractor.close failed")?;
@@ -815,6 +990,34 @@ impl TaskRunner {
             .context("pipeline.stop after abort failed")
     }
 
+    // rules are checked against the source tables, then keyed by the routed names of the rows
+    async fn create_col_transformer(
+        &self,
+        config_str: &str,
+        router: &Option<RdbRouter>,
+    ) -> anyhow::Result<Option<ColTransformer>> {
+        let mut col_transformer = ColTransformer::from_config(config_str)?;
+        if col_transformer.is_empty() {
+            return Ok(None);
+        }
+
+        let Some(mut meta_manager) =
+            ExtractorUtil::get_extractor_meta_manager(&self.config).await?
+        else {
+            bail!(Error::ConfigError(
+                "config [processor].col_transforms requires a mysql/pg source".into()
+            ));
+        };
+        let result = col_transformer.validate(&mut meta_manager).await;
+        meta_manager.close().await?;
+        result?;
+
+        if let Some(router) = router {
+            col_transformer.route(router);
+        }
+        Ok(Some(col_transformer))
+    }
+
     async fn create_pipeline(
         &self,
         buffer: Arc<DtQueue>,
@@ -825,16 +1028,26 @@ impl TaskRunner {
         data_marker: Option<Arc<RwLock<DataMarker>>>,
         recorder: Option<Arc<dyn Recorder + Send + Sync>>,
         checker: Option<CheckerHandle>,
+        router: &Option<RdbRouter>,
     ) -> anyhow::Result<Box<dyn Pipeline + Send>> {
         match self.config.pipeline.pipeline_type {
             PipelineType::Basic => {
-                let lua_processor =
-                    self.config
-                        .processor
-                        .as_ref()
-                        .map(|processor_config| LuaProcessor {
-                            lua_code: processor_config.lua_code.clone(),
-                        });
+                let lua_processor = self
+                    .config
+                    .processor
+                    .as_ref()
+                    // only col_transforms may be configured
+                    .filter(|processor_config| !processor_config.lua_code.is_empty())
+                    .map(|processor_config| LuaProcessor {
+                        lua_code: processor_config.lua_code.clone(),
+                    });
+                let col_transformer = match &self.config.processor {
+                    Some(processor_config) => {
+                        self.create_col_transformer(&processor_config.col_transforms, router)
+                            .await?
+                    }
+                    None => None,
+                };
 
                 let parallelizer =
                     ParallelizerUtil::create_parallelizer(&self.config, monitor.clone()).await?;
@@ -850,7 +1063,9 @@ impl TaskRunner {
                     syncer,
                     monitor,
                     pending_snapshot_finished: HashMap::new(),
+                    snapshot_positions: HashMap::new(),
                     data_marker,
+                    col_transformer,
                     lua_processor,
                     recorder,
                     checker,
@@ -861,7 +1076,7 @@ impl TaskRunner {
             PipelineType::HttpServer => {
                 let meta_manager = ExtractorUtil::get_extractor_meta_manager(&self.config).await?;
                 let avro_converter =
-                    AvroConverter::new(meta_manager, self.config.pipeline.with_field_defs);
+                    AvroConverter::new(meta_manager, self.config.pipeline.with_field_defs, false);
                 let pipeline = HttpServerPipeline::new(
                     buffer,
                     syncer,
@@ -1571,6 +1786,7 @@ impl TaskRunner {
                 parallel_size,
                 parallel_type,
                 batch_size,
+                chunk_size,
                 ..
             } => ExtractorConfig::MysqlSnapshot {
                 url: url.clone(),
@@ -1582,6 +1798,7 @@ impl TaskRunner {
                 parallel_size: *parallel_size,
                 parallel_type: parallel_type.clone(),
                 batch_size: *batch_size,
+                chunk_size: *chunk_size,
                 partition_cols: String::new(),
             },
 
@@ -1592,6 +1809,7 @@ impl TaskRunner {
                 parallel_size,
                 parallel_type,
                 batch_size,
+                chunk_size,
                 ..
             } => ExtractorConfig::PgSnapshot {
                 url: url.clone(),
@@ -1603,6 +1821,7 @@ impl TaskRunner {
                 parallel_size: *parallel_size,
                 parallel_type: parallel_type.clone(),
                 batch_size: *batch_size,
+                chunk_size: *chunk_size,
                 partition_cols: String::new(),
             },
 
@@ -1655,14 +1874,167 @@ impl TaskRunner {
 
 #[cfg(test)]
 mod tests {
-    use super::TaskRunner;
-    use dt_common::config::{
-        config_enums::{CheckMode, TaskKind, TaskType},
-        connection_auth_config::ConnectionAuthConfig,
-        extractor_config::ExtractorConfig,
+    use super::{ShutdownToken, TaskRunner};
+    use async_mutex::Mutex as AsyncMutex;
+    use async_trait::async_trait;
+    use dt_common::{
+        config::{
+            config_enums::{CheckMode, TaskKind, TaskType},
+            connection_auth_config::ConnectionAuthConfig,
+            extractor_config::ExtractorConfig,
+            sinker_config::SinkerConfig,
+        },
+        error::Error,
+        meta::{
+            col_value::ColValue,
+            dt_data::{DtData, DtItem},
+            dt_queue::DtQueue,
+            position::Position,
+            row_data::RowData,
+            row_type::RowType,
+            syncer::Syncer,
+        },
+        monitor::task_monitor_handle::TaskMonitorHandle,
     };
+    use dt_connector::{Extractor, Sinker};
+    use dt_parallelizer::{
+        base_parallelizer::BaseParallelizer, serial_parallelizer::SerialParallelizer,
+    };
+    use dt_pipeline::{base_pipeline::BasePipeline, Pipeline};
     use opendal::{services::Memory, Operator};
-    use std::{fs, time::SystemTime};
+    use std::{
+        collections::{HashMap, VecDeque},
+        fs,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, Mutex as StdMutex,
+        },
+        time::{Duration, Instant, SystemTime},
+    };
+    use tokio::sync::Mutex;
+
+    // pushes some rows and then waits forever like a cdc extractor
+    struct MockExtractor {
+        buffer: Arc<DtQueue>,
+        row_count: i32,
+        closed: Arc<AtomicBool>,
+    }
+
+    #[async_trait]
+    impl Extractor for MockExtractor {
+        async fn extract(&mut self) -> anyhow::Result<()> {
+            for id in 0..self.row_count {
+                let after = HashMap::from([("id".to_string(), ColValue::Long(id))]);
+                let row_data = RowData::new(
+                    "db_1".into(),
+                    "tb_1".into(),
+                    0,
+                    RowType::Insert,
+                    None,
+                    Some(after),
+                );
+                self.buffer
+                    .push(DtItem {
+                        dt_data: DtData::Dml { row_data },
+                        position: Position::None,
+                        data_origin_node: String::new(),
+                    })
+                    .await?;
+            }
+            std::future::pending().await
+        }
+
+        async fn close(&mut self) -> anyhow::Result<()> {
+            self.closed.store(true, Ordering::Release);
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct MockSinkerState {
+        buffered_rows: usize,
+        flushed_rows: usize,
+        closed: bool,
+    }
+
+    // keeps rows in memory until close, like sinkers writing batches to files
+    struct MockSinker {
+        state: Arc<StdMutex<MockSinkerState>>,
+    }
+
+    #[async_trait]
+    impl Sinker for MockSinker {
+        async fn sink_dml(&mut self, data: Vec<RowData>, _batch: bool) -> anyhow::Result<()> {
+            self.state.lock().unwrap().buffered_rows += data.len();
+            Ok(())
+        }
+
+        async fn close(&mut self) -> anyhow::Result<()> {
+            let mut state = self.state.lock().unwrap();
+            state.flushed_rows += std::mem::take(&mut state.buffered_rows);
+            state.closed = true;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn acquire_restart_is_bounded_within_window() {
+        let window = Duration::from_secs(60);
+        let start = Instant::now();
+        let mut history = VecDeque::new();
+
+        assert!(TaskRunner::acquire_restart(&mut history, start, 2, window));
+        assert!(TaskRunner::acquire_restart(
+            &mut history,
+            start + Duration::from_secs(10),
+            2,
+            window
+        ));
+        assert!(!TaskRunner::acquire_restart(
+            &mut history,
+            start + Duration::from_secs(20),
+            2,
+            window
+        ));
+        // the first restart falls out of the window
+        assert!(TaskRunner::acquire_restart(
+            &mut history,
+            start + Duration::from_secs(61),
+            2,
+            window
+        ));
+        assert!(!TaskRunner::acquire_restart(&mut history, start, 0, window));
+    }
+
+    #[test]
+    fn recoverable_task_error_is_classified_by_chain() {
+        let err = anyhow::Error::from(Error::ExtractorError("binlog connection lost".into()))
+            .context("extractor.extract failed");
+        assert!(Error::is_recoverable_task_error(&err));
+
+        let err = anyhow::Error::from(std::io::Error::from(std::io::ErrorKind::ConnectionReset))
+            .context("extractor.extract failed");
+        assert!(Error::is_recoverable_task_error(&err));
+
+        // extractor errors caused by data or config are not transient
+        for msg in [
+            "included_columns not match column_values in binlog",
+            "invalid rdb format, expected '$', got byte: 0",
+            "binlog file: mysql-bin.000003 not found in: /tmp/binlogs",
+        ] {
+            let err = anyhow::Error::from(Error::ExtractorError(msg.into()))
+                .context("extractor.extract failed");
+            assert!(!Error::is_recoverable_task_error(&err));
+        }
+
+        let err = anyhow::Error::from(Error::ConfigError("bad url".into()))
+            .context("extractor.extract failed");
+        assert!(!Error::is_recoverable_task_error(&err));
+
+        assert!(!Error::is_recoverable_task_error(&anyhow::anyhow!(
+            "unknown error"
+        )));
+    }
 
     #[test]
     fn should_clear_task_type_none_by_default() {
@@ -1742,4 +2114,71 @@ mod tests {
         );
         fs::remove_dir_all(dir).unwrap();
     }
+
+    #[tokio::test]
+    async fn run_task_workers_flushes_sinkers_on_shutdown() {
+        let buffer = Arc::new(DtQueue::new(100, 0, None, None));
+        let shut_down = Arc::new(AtomicBool::new(false));
+        let shutdown_token = Arc::new(ShutdownToken::default());
+        let extractor_closed = Arc::new(AtomicBool::new(false));
+        let sinker_state = Arc::new(StdMutex::new(MockSinkerState::default()));
+
+        let extractor = MockExtractor {
+            buffer: buffer.clone(),
+            row_count: 10,
+            closed: extractor_closed.clone(),
+        };
+        let sinker = MockSinker {
+            state: sinker_state.clone(),
+        };
+        let pipeline = BasePipeline {
+            buffer,
+            parallelizer: Box::new(SerialParallelizer {
+                base_parallelizer: BaseParallelizer::default(),
+                dml_orderer: None,
+            }),
+            sinker_config: SinkerConfig::Dummy,
+            sinkers: vec![Arc::new(AsyncMutex::new(
+                Box::new(sinker) as Box<dyn Sinker + Send>
+            ))],
+            shut_down: shut_down.clone(),
+            checkpoint_interval_secs: 1,
+            batch_sink_interval_secs: 0,
+            syncer: Arc::new(Mutex::new(Syncer {
+                received_position: Position::None,
+                committed_position: Position::None,
+                committed_positions: HashMap::new(),
+            })),
+            monitor: TaskMonitorHandle::default(),
+            pending_snapshot_finished: HashMap::new(),
+            snapshot_positions: HashMap::new(),
+            data_marker: None,
+            col_transformer: None,
+            lua_processor: None,
+            recorder: None,
+            checker: None,
+        };
+
+        let workers = tokio::spawn(TaskRunner::run_task_workers(
+            Arc::new(Mutex::new(Box::new(extractor) as Box<dyn Extractor + Send>)),
+            Arc::new(Mutex::new(Box::new(pipeline) as Box<dyn Pipeline + Send>)),
+            shut_down,
+            shutdown_token.clone(),
+        ));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(!workers.is_finished());
+
+        shutdown_token.cancel();
+        tokio::time::timeout(Duration::from_secs(5), workers)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+
+        assert!(extractor_closed.load(Ordering::Acquire));
+        let state = sinker_state.lock().unwrap();
+        assert!(state.closed);
+        assert_eq!(state.buffered_rows, 0);
+        assert_eq!(state.flushed_rows, 10);
+    }
 }
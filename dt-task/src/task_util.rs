@@ -834,7 +834,10 @@ impl ConnClient {
                 "`extractor.max_connections` must be greater than 0".into()
             ));
         }
-        let sinker_exists = !matches!(task_config.sinker, SinkerConfig::Dummy);
+        let sinker_exists = !matches!(
+            task_config.sinker,
+            SinkerConfig::Dummy | SinkerConfig::Discard { .. } | SinkerConfig::RedisDump { .. }
+        );
         if sinker_exists && sinker_max_connections < 1 {
             bail!(Error::ConfigError(
                 "`sinker.max_connections` must be greater than 0".into()